@@ -153,6 +153,16 @@ impl FeedbackReceiver for IndicatifFeedbackReceiver {
                 }
                 *certificate_validation_pb = None;
             }
+            MithrilEvent::AggregatorRateLimited { route, retry_after } => {
+                println!(
+                    "Aggregator rate limited request to '{route}', retrying in {retry_after:?}"
+                );
+            }
+            MithrilEvent::NewCertificateVerified { .. }
+            | MithrilEvent::CertificateChainRegression { .. }
+            | MithrilEvent::SnapshotDigestComputationStarted { .. }
+            | MithrilEvent::SnapshotDigestComputationProgress { .. }
+            | MithrilEvent::SnapshotDigestComputationCompleted { .. } => {}
         }
     }
 }
@@ -95,6 +95,12 @@ impl FeedbackReceiver for IndicatifFeedbackReceiver {
                 }
                 *certificate_validation_pb = None;
             }
+            MithrilEvent::AggregatorRateLimited { .. }
+            | MithrilEvent::NewCertificateVerified { .. }
+            | MithrilEvent::CertificateChainRegression { .. }
+            | MithrilEvent::SnapshotDigestComputationStarted { .. }
+            | MithrilEvent::SnapshotDigestComputationProgress { .. }
+            | MithrilEvent::SnapshotDigestComputationCompleted { .. } => {}
         }
     }
 }
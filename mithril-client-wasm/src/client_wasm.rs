@@ -33,6 +33,38 @@ impl FeedbackReceiver for JSBroadcastChannelFeedbackReceiver {
     }
 }
 
+/// A feedback receiver that calls a JS function with each event.
+struct JSCallbackFeedbackReceiver {
+    callback: js_sys::Function,
+}
+
+impl JSCallbackFeedbackReceiver {
+    pub fn new(callback: js_sys::Function) -> Self {
+        Self { callback }
+    }
+}
+
+// SAFETY: a `js_sys::Function` is a handle to an object of the JS heap of the thread that
+// created it, so it must never be used from another thread. Without the `atomics` target feature
+// a wasm module runs on a single thread: the receiver can't be sent nor shared between threads.
+// The implementations are restricted to this configuration so a multi-threaded build fails to
+// compile instead of being unsound.
+#[cfg(all(target_family = "wasm", not(target_feature = "atomics")))]
+unsafe impl Send for JSCallbackFeedbackReceiver {}
+#[cfg(all(target_family = "wasm", not(target_feature = "atomics")))]
+unsafe impl Sync for JSCallbackFeedbackReceiver {}
+
+#[cfg_attr(target_family = "wasm", async_trait(?Send))]
+#[cfg_attr(not(target_family = "wasm"), async_trait)]
+impl FeedbackReceiver for JSCallbackFeedbackReceiver {
+    async fn handle_event(&self, event: MithrilEvent) {
+        let event = MithrilEventWasm::from(event);
+        if let Ok(event) = serde_wasm_bindgen::to_value(&event) {
+            let _ = self.callback.call1(&JsValue::NULL, &event);
+        }
+    }
+}
+
 #[derive(Serialize)]
 struct MithrilEventWasm {
     #[serde(rename = "type")]
@@ -58,15 +90,26 @@ pub struct MithrilClient {
 #[wasm_bindgen(js_class = MithrilClient)]
 impl MithrilClient {
     /// Constructor for wasm client
+    ///
+    /// The events of the client are posted on the `mithril-client` broadcast channel and, if a
+    /// `feedback_callback` function is given, passed to it as `{ type, payload }` objects.
     #[wasm_bindgen(constructor)]
-    pub async fn new(aggregator_endpoint: &str, genesis_verification_key: &str) -> MithrilClient {
+    pub async fn new(
+        aggregator_endpoint: &str,
+        genesis_verification_key: &str,
+        feedback_callback: Option<js_sys::Function>,
+    ) -> Result<MithrilClient, JsValue> {
         let feedback_receiver = Arc::new(JSBroadcastChannelFeedbackReceiver::new("mithril-client"));
-        let client = ClientBuilder::aggregator(aggregator_endpoint, genesis_verification_key)
-            .add_feedback_receiver(feedback_receiver)
-            .build()
-            .map_err(|err| format!("{err:?}"))
-            .unwrap();
-        MithrilClient { client }
+        let mut client_builder =
+            ClientBuilder::aggregator(aggregator_endpoint, genesis_verification_key)
+                .add_feedback_receiver(feedback_receiver);
+        if let Some(callback) = feedback_callback {
+            client_builder = client_builder
+                .add_feedback_receiver(Arc::new(JSCallbackFeedbackReceiver::new(callback)));
+        }
+        let client = client_builder.build().map_err(|err| format!("{err:?}"))?;
+
+        Ok(MithrilClient { client })
     }
 
     /// Call the client to get a snapshot from a digest
@@ -201,7 +244,8 @@ mod tests {
         let wasm_client = MithrilClient::new(
         "https://aggregator.testing-preview.api.mithril.network/aggregator",
         "5b33322c3235332c3138362c3230312c3137372c31312c3131372c3133352c3138372c3136372c3138312c3138382c32322c35392c3230362c3130352c3233312c3135302c3231352c33302c37382c3231322c37362c31362c3235322c3138302c37322c3133342c3133372c3234372c3136312c36385d",
-    ).await;
+        None,
+    ).await.expect("client creation should not fail");
 
         let list = wasm_client
             .list_mithril_stake_distributions()
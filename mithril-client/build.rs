@@ -29,10 +29,42 @@ fn list_all_open_api_spec_files() -> Vec<PathBuf> {
     open_api_spec_files
 }
 
+/// Convert the paths and the schemas of the given spec to JSON, the rest of the spec is discarded.
+fn read_schemas_from_open_api_spec_file(spec_file_path: &Path) -> String {
+    let yaml_spec = std::fs::read_to_string(spec_file_path).unwrap();
+    let open_api: serde_yaml::Value = serde_yaml::from_str(&yaml_spec).unwrap();
+    let schemas = serde_json::json!({
+        "paths": serde_json::to_value(&open_api["paths"]).unwrap(),
+        "schemas": serde_json::to_value(&open_api["components"]["schemas"]).unwrap(),
+    });
+
+    serde_json::to_string(&schemas).unwrap()
+}
+
 fn main() {
     let out_dir = env::var_os("OUT_DIR").unwrap();
     let dest_path = Path::new(&out_dir).join("open_api.rs");
+    let schemas_dest_path = Path::new(&out_dir).join("open_api_schemas.json");
     let open_api_spec_files = list_all_open_api_spec_files();
+    println!("cargo:rerun-if-changed=build.rs");
+    for spec_file in &open_api_spec_files {
+        println!("cargo:rerun-if-changed={}", spec_file.display());
+    }
+    let latest_open_api_spec_file = open_api_spec_files.iter().max_by_key(|path| {
+        Version::parse(&read_version_from_open_api_spec_file(path.to_path_buf())).unwrap()
+    });
+    // The spec is not packaged with the crate: without it no schema is embedded and the schema
+    // validation is disabled.
+    let schemas = match latest_open_api_spec_file {
+        Some(spec_file) => read_schemas_from_open_api_spec_file(spec_file),
+        None => {
+            println!(
+                "cargo:warning=No Open API specification found, the schema validation is disabled"
+            );
+            serde_json::json!({ "paths": {}, "schemas": {} }).to_string()
+        }
+    };
+    fs::write(schemas_dest_path, schemas).unwrap();
     let open_api_versions = open_api_spec_files
         .into_iter()
         .map(|path| (path.clone(), read_version_from_open_api_spec_file(path)))
@@ -61,6 +93,9 @@ pub type OpenAPIVersionRaw = String;
 pub fn get_open_api_versions_mapping() -> HashMap<OpenAPIFileName, OpenAPIVersionRaw> {{
     serde_json::from_str({}).unwrap()
 }}
+
+/// Paths and schemas of the latest Open API specification, as JSON
+pub const OPEN_API_SCHEMAS_JSON: &str = include_str!(concat!(env!("OUT_DIR"), "/open_api_schemas.json"));
         "#,
             open_api_versions_json
         ),
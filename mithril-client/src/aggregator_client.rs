@@ -6,23 +6,69 @@
 //! [AggregatorRequest] enum.
 //!
 //! An implementation using HTTP is available: [AggregatorHTTPClient].
+//!
+//! Aggregators can be [probed][AggregatorHTTPClient::probe] to measure their availability and
+//! latency, see also [Client::probe_aggregators][crate::Client::probe_aggregators] to rank a list
+//! of aggregators.
+//!
+//! The responses of the list requests are cached by the [AggregatorHTTPClient] with their `ETag`
+//! or `Last-Modified` validators: when a list is requested again the aggregator is asked to send
+//! it only if it changed, so polling an unchanged list doesn't download its body again.
 
 use anyhow::{anyhow, Context};
 use async_recursion::async_recursion;
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use flate2::read::{DeflateDecoder, GzDecoder, ZlibDecoder};
+#[cfg(all(feature = "fs", not(target_family = "wasm")))]
+use futures::StreamExt;
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use reqwest::header::{
+    HeaderMap, HeaderValue, CONTENT_ENCODING, CONTENT_TYPE, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH,
+    LAST_MODIFIED,
+};
 use reqwest::{Response, StatusCode, Url};
-use semver::Version;
+use semver::{Version, VersionReq};
+use serde::de::DeserializeOwned;
 use slog::{debug, Logger};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::io::{BufRead, BufReader, Cursor, Read};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 use thiserror::Error;
 use tokio::sync::RwLock;
 
 #[cfg(test)]
 use mockall::automock;
 
+use crate::common::entities::Beacon;
 use crate::common::MITHRIL_API_VERSION_HEADER;
+#[cfg(not(target_family = "wasm"))]
+use crate::connection_pool::ConnectionPoolConfig;
+#[cfg(not(target_family = "wasm"))]
+use crate::dns::DnsConfig;
+use crate::metrics::MetricsRecorder;
+#[cfg(not(target_family = "wasm"))]
+use crate::proxy::ProxyConfig;
+#[cfg(not(target_family = "wasm"))]
+use crate::tls::{SpkiPins, TlsConfig};
+use crate::utils::time::Instant;
+#[cfg(not(target_family = "wasm"))]
+use crate::utils::HttpClientSettings;
+
+use crate::{MithrilCertificateListItem, MithrilError, MithrilResult};
+
+/// Maximum number of chunks of a streamed response body buffered until they are read, see
+/// [AggregatorClient::get_content_reader].
+#[cfg(all(feature = "fs", not(target_family = "wasm")))]
+const STREAMED_BODY_BUFFER_CHUNKS: usize = 16;
 
-use crate::{MithrilError, MithrilResult};
+/// Default maximum size of a decompressed response body, see
+/// [AggregatorHTTPClient::with_max_decoded_body_size].
+pub const DEFAULT_MAX_DECODED_BODY_SIZE: u64 = 128 * 1024 * 1024;
 
 /// Error tied with the Aggregator client
 #[derive(Error, Debug)]
@@ -42,18 +88,93 @@ pub enum AggregatorClientError {
     /// HTTP subsystem error
     #[error("HTTP subsystem error")]
     SubsystemError(#[source] MithrilError),
+
+    /// Error raised when a response diverged from its Open API schema, see
+    /// [schema validation][crate::schema_validation].
+    #[error("response does not match its API schema")]
+    ResponseSchemaMismatch(#[source] MithrilError),
+
+    /// Error raised when the aggregator refused the request because too many requests were sent
+    /// (429 status), see [retry][crate::retry].
+    #[error("rate limited by the aggregator")]
+    RateLimited {
+        /// Delay to wait before sending the request again, from the `Retry-After` header
+        retry_after: Option<Duration>,
+    },
+
+    /// Error raised when the TLS public key of the aggregator matches none of its pins, the
+    /// source can be downcast to a [PinMismatch][crate::tls::PinMismatch], see
+    /// [SpkiPins][crate::tls::SpkiPins].
+    #[error("aggregator TLS public key does not match its pins")]
+    TlsPinMismatch(#[source] MithrilError),
+
+    /// Error raised when the aggregator answered with a content that is not JSON, ie: the HTML
+    /// page of a captive portal or of a misconfigured proxy.
+    #[error(
+        "aggregator answered with a non JSON response of content type '{content_type}': {snippet}"
+    )]
+    NonJsonResponse {
+        /// Content type of the response
+        content_type: String,
+        /// Beginning of the response body
+        snippet: String,
+    },
+}
+
+/// Maximum number of characters of a response body kept in a
+/// [NonJsonResponse][AggregatorClientError::NonJsonResponse] error
+const NON_JSON_RESPONSE_SNIPPET_LENGTH: usize = 200;
+
+/// Check if the given content type is a JSON one, ie: `application/json; charset=utf-8` or
+/// `application/problem+json`.
+fn is_json_content_type(content_type: &str) -> bool {
+    let mime_type = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_lowercase();
+
+    mime_type == "application/json" || mime_type.ends_with("+json")
+}
+
+/// Keep the beginning of a response body, with its whitespaces collapsed, to be displayed in a
+/// [NonJsonResponse][AggregatorClientError::NonJsonResponse] error.
+fn response_snippet(body: &[u8]) -> String {
+    String::from_utf8_lossy(body)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .chars()
+        .take(NON_JSON_RESPONSE_SNIPPET_LENGTH)
+        .collect()
 }
 
 /// What can be read from an [AggregatorClient].
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum AggregatorRequest {
+    /// Get the features advertised by the aggregator
+    GetAggregatorFeatures,
     /// Get a specific [certificate][crate::MithrilCertificate] from the aggregator
     GetCertificate {
         /// Hash of the certificate to retrieve
         hash: String,
     },
+    /// Get a [certificate][crate::MithrilCertificate] and all its previous certificates, down to
+    /// the genesis certificate, from the aggregator in a single response
+    GetCertificateChain {
+        /// Hash of the latest certificate of the chain to retrieve
+        from_hash: String,
+    },
     /// Lists the aggregator [certificates][crate::MithrilCertificate]
     ListCertificates,
+    /// Lists a page of the aggregator [certificates][crate::MithrilCertificate]
+    ListCertificatesPage {
+        /// Maximum number of certificates of the page
+        limit: u64,
+        /// Hash of the certificate preceding the page, `None` for the first page
+        before: Option<String>,
+    },
     /// Get a specific [Mithril stake distribution][crate::MithrilStakeDistribution] from the aggregator
     GetMithrilStakeDistribution {
         /// Hash of the Mithril stake distribution to retrieve
@@ -68,27 +189,176 @@ pub enum AggregatorRequest {
     },
     /// Lists the aggregator [snapshots][crate::Snapshot]
     ListSnapshots,
+    /// Get the settings of the current epoch of the aggregator
+    GetEpochSettings,
+    /// Lists the artifacts of a [signed entity][crate::signed_entity::SignedEntity] at the given
+    /// route
+    ListSignedEntityArtifacts {
+        /// Route of the list of artifacts
+        route: AggregatorRoute,
+    },
+    /// Get an artifact of a [signed entity][crate::signed_entity::SignedEntity] at the given route
+    GetSignedEntityArtifact {
+        /// Route of the artifact
+        route: AggregatorRoute,
+    },
 }
 
 impl AggregatorRequest {
-    /// Get the request route relative to the aggregator root endpoint.
-    pub fn route(&self) -> String {
+    /// Get the typed [AggregatorRoute] of the request.
+    pub fn aggregator_route(&self) -> AggregatorRoute {
         match self {
+            AggregatorRequest::GetAggregatorFeatures => AggregatorRoute::new(""),
             AggregatorRequest::GetCertificate { hash } => {
-                format!("certificate/{hash}")
+                AggregatorRoute::new("certificate").with_parameter(hash)
+            }
+            AggregatorRequest::GetCertificateChain { from_hash } => {
+                AggregatorRoute::new("certificate-chain").with_parameter(from_hash)
+            }
+            AggregatorRequest::ListCertificates => AggregatorRoute::new("certificates"),
+            AggregatorRequest::ListCertificatesPage { limit, before } => {
+                let route =
+                    AggregatorRoute::new("certificates").with_query_parameter("limit", limit);
+                match before {
+                    Some(before) => route.with_query_parameter("before", before),
+                    None => route,
+                }
             }
-            AggregatorRequest::ListCertificates => "certificates".to_string(),
             AggregatorRequest::GetMithrilStakeDistribution { hash } => {
-                format!("artifact/mithril-stake-distribution/{hash}")
+                AggregatorRoute::new("artifact/mithril-stake-distribution").with_parameter(hash)
             }
             AggregatorRequest::ListMithrilStakeDistributions => {
-                "artifact/mithril-stake-distributions".to_string()
+                AggregatorRoute::new("artifact/mithril-stake-distributions")
             }
             AggregatorRequest::GetSnapshot { digest } => {
-                format!("artifact/snapshot/{}", digest)
+                AggregatorRoute::new("artifact/snapshot").with_parameter(digest)
+            }
+            AggregatorRequest::ListSnapshots => AggregatorRoute::new("artifact/snapshots"),
+            AggregatorRequest::GetEpochSettings => AggregatorRoute::new("epoch-settings"),
+            AggregatorRequest::ListSignedEntityArtifacts { route }
+            | AggregatorRequest::GetSignedEntityArtifact { route } => route.clone(),
+        }
+    }
+
+    /// Get the request route relative to the aggregator root endpoint.
+    pub fn route(&self) -> String {
+        self.aggregator_route().to_string()
+    }
+
+    /// Tell if the request lists artifacts, the lists being the requests that are polled.
+    pub fn is_list(&self) -> bool {
+        matches!(
+            self,
+            AggregatorRequest::ListCertificates
+                | AggregatorRequest::ListCertificatesPage { .. }
+                | AggregatorRequest::ListMithrilStakeDistributions
+                | AggregatorRequest::ListSnapshots
+                | AggregatorRequest::ListSignedEntityArtifacts { .. }
+        )
+    }
+}
+
+/// Characters kept as is in the parameters of a route: the RFC 3986 unreserved characters.
+const ROUTE_PARAMETER_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+/// Route of a request relative to the aggregator root endpoint.
+///
+/// A route is made of static path segments, parameter path segments and query parameters. The
+/// parameters are percent-encoded so any value can be given safely, and they are validated when
+/// the route is [resolved][AggregatorRoute::to_url] against the aggregator endpoint so they
+/// can't point out of the route (ie: an empty or `..` parameter).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AggregatorRoute {
+    segments: Vec<RouteSegment>,
+    query: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum RouteSegment {
+    Static(&'static str),
+    Parameter(String),
+}
+
+impl AggregatorRoute {
+    /// Create a route from its static path, ie: `artifact/snapshots`.
+    pub fn new(path: &'static str) -> Self {
+        Self {
+            segments: path
+                .split('/')
+                .filter(|segment| !segment.is_empty())
+                .map(RouteSegment::Static)
+                .collect(),
+            query: vec![],
+        }
+    }
+
+    /// Append a parameter path segment, percent-encoded.
+    pub fn with_parameter<T: ToString>(mut self, parameter: T) -> Self {
+        self.segments
+            .push(RouteSegment::Parameter(parameter.to_string()));
+        self
+    }
+
+    /// Append a query parameter, percent-encoded.
+    pub fn with_query_parameter<K: ToString, V: ToString>(mut self, key: K, value: V) -> Self {
+        self.query.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// Check that the parameters of the route can't point out of the route.
+    pub fn validate(&self) -> MithrilResult<()> {
+        for segment in &self.segments {
+            if let RouteSegment::Parameter(parameter) = segment {
+                if parameter.is_empty() || parameter == "." || parameter == ".." {
+                    return Err(anyhow!(
+                        "Invalid route parameter '{parameter}' in route '{self}'"
+                    ));
+                }
             }
-            AggregatorRequest::ListSnapshots => "artifact/snapshots".to_string(),
         }
+
+        Ok(())
+    }
+
+    /// Validate the route then resolve it against the given aggregator endpoint.
+    pub fn to_url(&self, aggregator_endpoint: &Url) -> MithrilResult<Url> {
+        self.validate()?;
+        aggregator_endpoint.join(&self.to_string()).with_context(|| {
+            format!("Invalid url when joining route '{self}' to aggregator url '{aggregator_endpoint}'")
+        })
+    }
+}
+
+impl Display for AggregatorRoute {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (index, segment) in self.segments.iter().enumerate() {
+            if index > 0 {
+                f.write_str("/")?;
+            }
+            match segment {
+                RouteSegment::Static(segment) => f.write_str(segment)?,
+                RouteSegment::Parameter(parameter) => write!(
+                    f,
+                    "{}",
+                    utf8_percent_encode(parameter, ROUTE_PARAMETER_ENCODE_SET)
+                )?,
+            }
+        }
+        for (index, (key, value)) in self.query.iter().enumerate() {
+            write!(
+                f,
+                "{}{}={}",
+                if index == 0 { '?' } else { '&' },
+                utf8_percent_encode(key, ROUTE_PARAMETER_ENCODE_SET),
+                utf8_percent_encode(value, ROUTE_PARAMETER_ENCODE_SET)
+            )?;
+        }
+
+        Ok(())
     }
 }
 
@@ -101,16 +371,159 @@ pub trait AggregatorClient: Sync + Send {
         &self,
         request: AggregatorRequest,
     ) -> Result<String, AggregatorClientError>;
+
+    /// Get the content back from the Aggregator as a reader, so large contents can be
+    /// deserialized while being downloaded instead of being buffered as a string first.
+    ///
+    /// The reader may block while waiting for the content to be downloaded: it must be read
+    /// from a blocking thread, ie: using `tokio::task::spawn_blocking`.
+    ///
+    /// By default the content is fully retrieved using [get_content][Self::get_content] before
+    /// being read.
+    async fn get_content_reader(
+        &self,
+        request: AggregatorRequest,
+    ) -> Result<Box<dyn Read + Send>, AggregatorClientError> {
+        let content = self.get_content(request).await?;
+
+        Ok(Box::new(Cursor::new(content.into_bytes())))
+    }
+}
+
+/// Result of the [probe][AggregatorHTTPClient::probe] of an aggregator.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AggregatorProbe {
+    /// Endpoint of the probed aggregator
+    pub endpoint: String,
+
+    /// Reason why the aggregator could not be probed, `None` if it's reachable
+    pub error: Option<String>,
+
+    /// API version negotiated with the version advertised by the aggregator, or the preferred
+    /// version of the client if the aggregator doesn't advertise it
+    pub api_version: Option<Version>,
+
+    /// Time taken by the aggregator to list its certificates
+    pub latency: Option<Duration>,
+
+    /// Beacon of the latest certificate issued by the aggregator
+    pub latest_certificate_beacon: Option<Beacon>,
+
+    /// Date at which the latest certificate issued by the aggregator was sealed
+    pub latest_certificate_sealed_at: Option<DateTime<Utc>>,
+}
+
+impl AggregatorProbe {
+    /// Constructs the probe of an aggregator that could not be probed.
+    pub fn failure(endpoint: &str, error: MithrilError) -> Self {
+        Self {
+            endpoint: endpoint.to_string(),
+            error: Some(format!("{error:?}")),
+            api_version: None,
+            latency: None,
+            latest_certificate_beacon: None,
+            latest_certificate_sealed_at: None,
+        }
+    }
+
+    /// Returns `true` if the aggregator could be probed.
+    pub fn is_reachable(&self) -> bool {
+        self.error.is_none()
+    }
+
+    /// Time elapsed since the latest certificate issued by the aggregator was sealed.
+    pub fn latest_certificate_age(&self) -> Option<chrono::Duration> {
+        self.latest_certificate_sealed_at
+            .map(|sealed_at| Utc::now() - sealed_at)
+    }
+}
+
+/// Ranked results of the probe of a list of aggregators, see
+/// [Client::probe_aggregators][crate::Client::probe_aggregators].
+#[derive(Debug, Clone, PartialEq)]
+pub struct AggregatorProbeReport {
+    /// Probes ranked from the best aggregator to the worst: reachable aggregators first, then
+    /// the ones with the most recent latest certificate, then the ones with the lowest latency.
+    pub probes: Vec<AggregatorProbe>,
+}
+
+impl AggregatorProbeReport {
+    /// Constructs a new `AggregatorProbeReport` ranking the given probes.
+    pub fn new(probes: Vec<AggregatorProbe>) -> Self {
+        let mut probes = probes;
+        probes.sort_by_key(|probe| {
+            (
+                !probe.is_reachable(),
+                Reverse(
+                    probe
+                        .latest_certificate_beacon
+                        .as_ref()
+                        .map(|beacon| (beacon.epoch, beacon.immutable_file_number)),
+                ),
+                probe.latency,
+            )
+        });
+
+        Self { probes }
+    }
+
+    /// Get the best reachable aggregator, if any.
+    pub fn best(&self) -> Option<&AggregatorProbe> {
+        self.probes.first().filter(|probe| probe.is_reachable())
+    }
 }
 
 /// Responsible of HTTP transport and API version check.
 pub struct AggregatorHTTPClient {
     http_client: reqwest::Client,
+    #[cfg(not(target_family = "wasm"))]
+    http_client_settings: HttpClientSettings,
+    #[cfg(not(target_family = "wasm"))]
+    spki_pins: Option<SpkiPins>,
     aggregator_endpoint: Url,
     api_versions: Arc<RwLock<Vec<Version>>>,
+    request_timeout: Option<Duration>,
+    compression: bool,
+    max_decoded_body_size: u64,
+    conditional_requests: bool,
+    cached_responses: Arc<RwLock<HashMap<String, CachedResponse>>>,
+    metrics_recorder: Option<Arc<dyn MetricsRecorder>>,
     logger: Logger,
 }
 
+/// A response kept with the validators that allow to ask the aggregator if it changed.
+#[derive(Debug, Clone)]
+struct CachedResponse {
+    etag: Option<HeaderValue>,
+    last_modified: Option<HeaderValue>,
+    content: String,
+}
+
+impl CachedResponse {
+    fn from_headers(headers: &HeaderMap, content: &str) -> Option<Self> {
+        let etag = headers.get(ETAG).cloned();
+        let last_modified = headers.get(LAST_MODIFIED).cloned();
+
+        (etag.is_some() || last_modified.is_some()).then(|| Self {
+            etag,
+            last_modified,
+            content: content.to_string(),
+        })
+    }
+
+    /// Headers asking the aggregator to send the content only if it changed.
+    fn conditional_headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        match (&self.etag, &self.last_modified) {
+            (Some(etag), _) => headers.insert(IF_NONE_MATCH, etag.clone()),
+            (None, Some(last_modified)) => headers.insert(IF_MODIFIED_SINCE, last_modified.clone()),
+            (None, None) => None,
+        };
+
+        headers
+    }
+}
+
 impl AggregatorHTTPClient {
     /// Constructs a new `AggregatorHTTPClient`
     pub fn new(
@@ -135,17 +548,196 @@ impl AggregatorHTTPClient {
 
         Ok(Self {
             http_client,
+            #[cfg(not(target_family = "wasm"))]
+            http_client_settings: HttpClientSettings::default(),
+            #[cfg(not(target_family = "wasm"))]
+            spki_pins: None,
             aggregator_endpoint,
             api_versions: Arc::new(RwLock::new(api_versions)),
+            request_timeout: None,
+            compression: true,
+            max_decoded_body_size: DEFAULT_MAX_DECODED_BODY_SIZE,
+            conditional_requests: true,
+            cached_responses: Arc::new(RwLock::new(HashMap::new())),
+            metrics_recorder: None,
             logger,
         })
     }
 
+    /// Send the requests through the given proxy, see [proxy][crate::proxy].
+    #[cfg(not(target_family = "wasm"))]
+    pub fn with_proxy(mut self, proxy: &ProxyConfig) -> MithrilResult<Self> {
+        self.http_client_settings.proxy = Some(proxy.clone());
+        self.rebuild_http_client()
+    }
+
+    /// Connect to the aggregator using the given TLS settings, see [tls][crate::tls].
+    #[cfg(not(target_family = "wasm"))]
+    pub fn with_tls(mut self, tls: &TlsConfig) -> MithrilResult<Self> {
+        self.http_client_settings.tls = Some(tls.clone());
+        self.rebuild_http_client()
+    }
+
+    /// Tune the pool of the connections to the aggregator, see
+    /// [connection_pool][crate::connection_pool].
+    #[cfg(not(target_family = "wasm"))]
+    pub fn with_connection_pool(
+        mut self,
+        connection_pool: &ConnectionPoolConfig,
+    ) -> MithrilResult<Self> {
+        self.http_client_settings.connection_pool = Some(connection_pool.clone());
+        self.rebuild_http_client()
+    }
+
+    /// Resolve the name of the aggregator using the given settings, see [dns][crate::dns].
+    #[cfg(not(target_family = "wasm"))]
+    pub fn with_dns(mut self, dns: &DnsConfig) -> MithrilResult<Self> {
+        self.http_client_settings.dns = Some(dns.clone());
+        self.rebuild_http_client()
+    }
+
+    /// Reject the connections to the aggregator if the public key of its TLS certificate matches
+    /// none of the given pins, see [SpkiPins].
+    ///
+    /// The pins are checked during the TLS handshake, before any request is sent, and the
+    /// requests to an aggregator reached over plain http are rejected.
+    #[cfg(not(target_family = "wasm"))]
+    pub fn with_spki_pins(mut self, spki_pins: &SpkiPins) -> MithrilResult<Self> {
+        if spki_pins.is_empty() {
+            return Err(anyhow!("At least one SPKI pin must be given"));
+        }
+        self.spki_pins = Some(spki_pins.clone());
+        self.http_client_settings.spki_pins = Some(spki_pins.clone());
+        self.rebuild_http_client()
+    }
+
+    #[cfg(not(target_family = "wasm"))]
+    fn rebuild_http_client(mut self) -> MithrilResult<Self> {
+        self.http_client = self
+            .http_client_settings
+            .build()
+            .with_context(|| "Building http client for Aggregator client failed")?;
+        Ok(self)
+    }
+
+    /// Enable or disable the compression of the responses (enabled by default).
+    ///
+    /// When enabled the aggregator is asked to compress its responses with gzip or deflate, they
+    /// are decompressed transparently.
+    ///
+    /// Note: on wasm targets the compression is negotiated by the browser.
+    pub fn with_compression(mut self, compression: bool) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Set the maximum size of a decompressed response body (defaults to
+    /// [DEFAULT_MAX_DECODED_BODY_SIZE]), reading a larger body fails so a small compressed
+    /// response can't be inflated until the memory is exhausted.
+    pub fn with_max_decoded_body_size(mut self, max_decoded_body_size: u64) -> Self {
+        self.max_decoded_body_size = max_decoded_body_size;
+        self
+    }
+
+    /// Enable or disable the conditional list requests (enabled by default).
+    ///
+    /// When enabled the last response of each list request is cached with its `ETag` or
+    /// `Last-Modified` validators, and the aggregator is asked to answer `304 Not Modified` if
+    /// the list did not change since, in which case the cached response is returned.
+    pub fn with_conditional_requests(mut self, conditional_requests: bool) -> Self {
+        self.conditional_requests = conditional_requests;
+        self
+    }
+
+    /// Set the [MetricsRecorder] that will record the size of the responses, before and after
+    /// their decompression.
+    pub fn with_metrics_recorder(mut self, metrics_recorder: Arc<dyn MetricsRecorder>) -> Self {
+        self.metrics_recorder = Some(metrics_recorder);
+        self
+    }
+
+    /// Fail the requests that don't complete within the given timeout.
+    ///
+    /// Note: the timeout is not enforced on wasm targets.
+    pub fn with_request_timeout(mut self, request_timeout: Duration) -> Self {
+        self.request_timeout = Some(request_timeout);
+        self
+    }
+
+    /// Measure the reachability, API version, latest certificate freshness and latency of the
+    /// aggregator.
+    pub async fn probe(&self) -> AggregatorProbe {
+        let endpoint = self.aggregator_endpoint.as_str();
+        let start = Instant::now();
+        let response = self.get_content(AggregatorRequest::ListCertificates).await;
+        let latency = start.elapsed();
+
+        let certificates = match response {
+            Ok(content) => serde_json::from_str::<Vec<MithrilCertificateListItem>>(&content)
+                .with_context(|| "Could not deserialize aggregator certificate list"),
+            Err(error) => Err(anyhow!(error)),
+        };
+
+        match certificates {
+            Ok(certificates) => AggregatorProbe {
+                endpoint: endpoint.to_string(),
+                error: None,
+                api_version: self.compute_current_api_version().await,
+                latency: Some(latency),
+                latest_certificate_beacon: certificates
+                    .first()
+                    .map(|certificate| certificate.beacon.clone()),
+                latest_certificate_sealed_at: certificates
+                    .first()
+                    .map(|certificate| certificate.metadata.sealed_at),
+            },
+            Err(error) => AggregatorProbe::failure(endpoint, error),
+        }
+    }
+
     /// Computes the current api version
     async fn compute_current_api_version(&self) -> Option<Version> {
         self.api_versions.read().await.first().cloned()
     }
 
+    /// Negotiates the api version with the version advertised by the aggregator in its
+    /// response, keeping only the versions compatible with it so the next requests are sent with
+    /// a version it supports.
+    async fn negotiate_api_version(
+        &self,
+        response: &Response,
+    ) -> Result<(), AggregatorClientError> {
+        let Some(aggregator_api_version) = response
+            .headers()
+            .get(MITHRIL_API_VERSION_HEADER)
+            .and_then(|version| version.to_str().ok())
+            .and_then(|version| Version::parse(version).ok())
+        else {
+            return Ok(());
+        };
+        let requirement = api_version_requirement(&aggregator_api_version);
+
+        let mut api_versions = self.api_versions.write().await;
+        let compatible_api_versions: Vec<Version> = api_versions
+            .iter()
+            .filter(|version| requirement.matches(version))
+            .cloned()
+            .collect();
+        if compatible_api_versions.is_empty() {
+            return Err(AggregatorClientError::ApiVersionMismatch(anyhow!(
+                "server version: '{aggregator_api_version}', client versions: '{}'",
+                api_versions
+                    .iter()
+                    .map(Version::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )));
+        }
+        *api_versions = compatible_api_versions;
+
+        Ok(())
+    }
+
     /// Discards the current api version
     /// It discards the current version if and only if there is at least 2 versions available
     async fn discard_current_api_version(&self) -> Option<Version> {
@@ -168,9 +760,25 @@ impl AggregatorHTTPClient {
     /// Perform a HTTP GET request on the Aggregator and return the given JSON
     #[cfg_attr(target_family = "wasm", async_recursion(?Send))]
     #[cfg_attr(not(target_family = "wasm"), async_recursion)]
-    async fn get(&self, url: Url) -> Result<Response, AggregatorClientError> {
+    async fn get(
+        &self,
+        url: Url,
+        conditional_headers: HeaderMap,
+    ) -> Result<Response, AggregatorClientError> {
         debug!(self.logger, "GET url='{url}'.");
-        let request_builder = self.http_client.get(url.clone());
+        // Pins are checked during the TLS handshake, there is none over plain http
+        #[cfg(not(target_family = "wasm"))]
+        match &self.spki_pins {
+            Some(spki_pins) if url.scheme() != "https" => spki_pins
+                .check(url.host_str().unwrap_or_default(), None)
+                .map_err(|e| AggregatorClientError::TlsPinMismatch(anyhow!(e)))?,
+            _ => {}
+        }
+        let is_conditional = !conditional_headers.is_empty();
+        let request_builder = self
+            .http_client
+            .get(url.clone())
+            .headers(conditional_headers.clone());
         let current_api_version = self
             .compute_current_api_version()
             .await
@@ -182,19 +790,38 @@ impl AggregatorHTTPClient {
         );
         let request_builder =
             request_builder.header(MITHRIL_API_VERSION_HEADER, current_api_version);
+        #[cfg(not(target_family = "wasm"))]
+        let request_builder = if self.compression {
+            request_builder.header(reqwest::header::ACCEPT_ENCODING, "gzip, deflate")
+        } else {
+            request_builder
+        };
+        #[cfg(not(target_family = "wasm"))]
+        let request_builder = match self.request_timeout {
+            Some(request_timeout) => request_builder.timeout(request_timeout),
+            None => request_builder,
+        };
         let response = request_builder.send().await.map_err(|e| {
+            #[cfg(not(target_family = "wasm"))]
+            if let Some(mismatch) = crate::tls::find_pin_mismatch(&e) {
+                return AggregatorClientError::TlsPinMismatch(anyhow!(mismatch));
+            }
             AggregatorClientError::SubsystemError(anyhow!(e).context(format!(
                 "Cannot perform a GET against the Aggregator HTTP server (url='{url}')"
             )))
         })?;
 
         match response.status() {
-            StatusCode::OK => Ok(response),
+            StatusCode::OK => {
+                self.negotiate_api_version(&response).await?;
+                Self::ensure_json_response(response).await
+            }
+            StatusCode::NOT_MODIFIED if is_conditional => Ok(response),
             StatusCode::PRECONDITION_FAILED => {
                 if self.discard_current_api_version().await.is_some()
                     && !self.api_versions.read().await.is_empty()
                 {
-                    return self.get(url).await;
+                    return self.get(url, conditional_headers).await;
                 }
 
                 Err(self.handle_api_error(&response).await)
@@ -202,12 +829,87 @@ impl AggregatorHTTPClient {
             StatusCode::NOT_FOUND => Err(AggregatorClientError::RemoteServerLogical(anyhow!(
                 "Url='{url} not found"
             ))),
+            StatusCode::TOO_MANY_REQUESTS => Err(AggregatorClientError::RateLimited {
+                retry_after: response
+                    .headers()
+                    .get(reqwest::header::RETRY_AFTER)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(|value| parse_retry_after(value, Utc::now())),
+            }),
             status_code => Err(AggregatorClientError::RemoteServerTechnical(anyhow!(
                 "Unhandled error {status_code}"
             ))),
         }
     }
 
+    /// Stream the body of the response, through a bounded channel, to the returned reader.
+    ///
+    /// The chunks are received by a task spawned on the current runtime, the reader blocks
+    /// while waiting for them.
+    #[cfg(all(feature = "fs", not(target_family = "wasm")))]
+    async fn read_body(
+        response: Response,
+        transferred_bytes: Arc<AtomicU64>,
+        _content: &str,
+    ) -> Result<Box<dyn Read + Send>, AggregatorClientError> {
+        let (sender, receiver) = flume::bounded(STREAMED_BODY_BUFFER_CHUNKS);
+        tokio::spawn(async move {
+            let mut body = response.bytes_stream();
+            while let Some(chunk) = body.next().await {
+                let chunk = chunk
+                    .map(|chunk| {
+                        transferred_bytes.fetch_add(chunk.len() as u64, Ordering::Relaxed);
+                        chunk.to_vec()
+                    })
+                    .map_err(std::io::Error::other);
+                let is_error = chunk.is_err();
+                if sender.send_async(chunk).await.is_err() || is_error {
+                    break;
+                }
+            }
+        });
+
+        Ok(Box::new(StreamedBodyReader::new(receiver)))
+    }
+
+    /// Read the whole body of the response, it can't be streamed to a blocking reader without
+    /// the `fs` feature or on wasm targets.
+    #[cfg(not(all(feature = "fs", not(target_family = "wasm"))))]
+    async fn read_body(
+        response: Response,
+        transferred_bytes: Arc<AtomicU64>,
+        content: &str,
+    ) -> Result<Box<dyn Read + Send>, AggregatorClientError> {
+        let body = response.bytes().await.map_err(|e| {
+            AggregatorClientError::SubsystemError(anyhow!(e).context(format!(
+                "Could not find a JSON body in the response '{content}'."
+            )))
+        })?;
+        transferred_bytes.store(body.len() as u64, Ordering::Relaxed);
+
+        Ok(Box::new(Cursor::new(body)))
+    }
+
+    /// Reject the responses which content type is not JSON, a response without content type
+    /// is accepted.
+    async fn ensure_json_response(response: Response) -> Result<Response, AggregatorClientError> {
+        let content_type = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .map(|content_type| String::from_utf8_lossy(content_type.as_bytes()).to_string());
+
+        match content_type {
+            Some(content_type) if !is_json_content_type(&content_type) => {
+                let body = response.bytes().await.unwrap_or_default();
+                Err(AggregatorClientError::NonJsonResponse {
+                    content_type,
+                    snippet: response_snippet(&body),
+                })
+            }
+            _ => Ok(response),
+        }
+    }
+
     /// API version error handling
     async fn handle_api_error(&self, response: &Response) -> AggregatorClientError {
         if let Some(version) = response.headers().get(MITHRIL_API_VERSION_HEADER) {
@@ -223,18 +925,6 @@ impl AggregatorHTTPClient {
             ))
         }
     }
-
-    fn get_url_for_route(&self, endpoint: &str) -> Result<Url, AggregatorClientError> {
-        self.aggregator_endpoint
-            .join(endpoint)
-            .with_context(|| {
-                format!(
-                    "Invalid url when joining given endpoint, '{endpoint}', to aggregator url '{}'",
-                    self.aggregator_endpoint
-                )
-            })
-            .map_err(AggregatorClientError::SubsystemError)
-    }
 }
 
 #[cfg_attr(test, automock)]
@@ -245,21 +935,484 @@ impl AggregatorClient for AggregatorHTTPClient {
         &self,
         request: AggregatorRequest,
     ) -> Result<String, AggregatorClientError> {
-        let response = self.get(self.get_url_for_route(&request.route())?).await?;
+        let aggregator_route = request.aggregator_route();
+        let url = aggregator_route
+            .to_url(&self.aggregator_endpoint)
+            .map_err(AggregatorClientError::SubsystemError)?;
+        let route = aggregator_route.to_string();
+        let use_cache = self.conditional_requests && request.is_list();
+        let cached_response = match use_cache {
+            true => self.cached_responses.read().await.get(&route).cloned(),
+            false => None,
+        };
+        let conditional_headers = cached_response
+            .as_ref()
+            .map(CachedResponse::conditional_headers)
+            .unwrap_or_default();
+        let response = self.get(url, conditional_headers).await?;
+        if let (StatusCode::NOT_MODIFIED, Some(cached_response)) =
+            (response.status(), cached_response)
+        {
+            debug!(
+                self.logger,
+                "Content of route '{route}' not modified, using cached response"
+            );
+            return Ok(cached_response.content);
+        }
+        let headers = response.headers().clone();
         let content = format!("{response:?}");
+        let content_encoding = headers
+            .get(CONTENT_ENCODING)
+            .and_then(|encoding| encoding.to_str().ok())
+            .map(|encoding| encoding.trim().to_lowercase());
 
-        response.text().await.map_err(|e| {
+        let body = response.bytes().await.map_err(|e| {
             AggregatorClientError::SubsystemError(anyhow!(e).context(format!(
                 "Could not find a JSON body in the response '{content}'."
             )))
-        })
+        })?;
+        let mut decoded_body = vec![];
+        decoding_reader(
+            content_encoding.as_deref(),
+            Cursor::new(body.clone()),
+            self.max_decoded_body_size,
+        )
+        .and_then(|mut reader| Ok(reader.read_to_end(&mut decoded_body)?))
+        .map_err(|e| {
+            AggregatorClientError::SubsystemError(e.context(format!(
+                "Could not decode the body of the response '{content}'."
+            )))
+        })?;
+        if let Some(metrics_recorder) = &self.metrics_recorder {
+            metrics_recorder.record_aggregator_response_size(
+                &route,
+                body.len() as u64,
+                decoded_body.len() as u64,
+            );
+        }
+
+        let body = String::from_utf8(decoded_body).map_err(|e| {
+            AggregatorClientError::SubsystemError(anyhow!(e).context(format!(
+                "Could not find a JSON body in the response '{content}'."
+            )))
+        })?;
+        if use_cache {
+            let mut cached_responses = self.cached_responses.write().await;
+            match CachedResponse::from_headers(&headers, &body) {
+                Some(cached_response) => cached_responses.insert(route, cached_response),
+                None => cached_responses.remove(&route),
+            };
+        }
+
+        Ok(body)
+    }
+
+    async fn get_content_reader(
+        &self,
+        request: AggregatorRequest,
+    ) -> Result<Box<dyn Read + Send>, AggregatorClientError> {
+        // Cached responses are kept as strings, there is nothing to stream
+        if self.conditional_requests && request.is_list() {
+            let content = self.get_content(request).await?;
+            return Ok(Box::new(Cursor::new(content.into_bytes())));
+        }
+
+        let aggregator_route = request.aggregator_route();
+        let url = aggregator_route
+            .to_url(&self.aggregator_endpoint)
+            .map_err(AggregatorClientError::SubsystemError)?;
+        let response = self.get(url, HeaderMap::new()).await?;
+        let content = format!("{response:?}");
+        let content_encoding = response
+            .headers()
+            .get(CONTENT_ENCODING)
+            .and_then(|encoding| encoding.to_str().ok())
+            .map(|encoding| encoding.trim().to_lowercase());
+
+        let transferred_bytes = Arc::new(AtomicU64::new(0));
+        let body = Self::read_body(response, transferred_bytes.clone(), &content).await?;
+        let reader = decoding_reader(
+            content_encoding.as_deref(),
+            body,
+            self.max_decoded_body_size,
+        )
+        .map_err(|e| {
+            AggregatorClientError::SubsystemError(e.context(format!(
+                "Could not decode the body of the response '{content}'."
+            )))
+        })?;
+
+        match &self.metrics_recorder {
+            Some(metrics_recorder) => Ok(Box::new(MeteredResponseReader {
+                reader,
+                route: aggregator_route.to_string(),
+                transferred_bytes,
+                content_bytes: 0,
+                metrics_recorder: metrics_recorder.clone(),
+            })),
+            None => Ok(reader),
+        }
+    }
+}
+
+/// Reader of a streamed response body that records its size once dropped, as the size of the
+/// decompressed content is only known once it has been read.
+struct MeteredResponseReader {
+    reader: Box<dyn Read + Send>,
+    route: String,
+    transferred_bytes: Arc<AtomicU64>,
+    content_bytes: u64,
+    metrics_recorder: Arc<dyn MetricsRecorder>,
+}
+
+impl Read for MeteredResponseReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read_bytes = self.reader.read(buf)?;
+        self.content_bytes += read_bytes as u64;
+
+        Ok(read_bytes)
+    }
+}
+
+impl Drop for MeteredResponseReader {
+    fn drop(&mut self) {
+        self.metrics_recorder.record_aggregator_response_size(
+            &self.route,
+            self.transferred_bytes.load(Ordering::Relaxed),
+            self.content_bytes,
+        );
+    }
+}
+
+/// Reader of a response body streamed through a channel, see
+/// [read_body][AggregatorHTTPClient::read_body].
+#[cfg(all(feature = "fs", not(target_family = "wasm")))]
+struct StreamedBodyReader {
+    receiver: flume::Receiver<std::io::Result<Vec<u8>>>,
+    current: Cursor<Vec<u8>>,
+}
+
+#[cfg(all(feature = "fs", not(target_family = "wasm")))]
+impl StreamedBodyReader {
+    fn new(receiver: flume::Receiver<std::io::Result<Vec<u8>>>) -> Self {
+        Self {
+            receiver,
+            current: Cursor::new(vec![]),
+        }
+    }
+}
+
+#[cfg(all(feature = "fs", not(target_family = "wasm")))]
+impl Read for StreamedBodyReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        while self.current.position() == self.current.get_ref().len() as u64 {
+            match self.receiver.recv() {
+                Ok(chunk) => self.current = Cursor::new(chunk?),
+                // The sender is dropped once the whole body is received
+                Err(_) => return Ok(0),
+            }
+        }
+
+        self.current.read(buf)
+    }
+}
+
+/// Deserialize the JSON content read from a reader returned by
+/// [AggregatorClient::get_content_reader], on a blocking thread when the reader can block.
+pub(crate) async fn deserialize_content_reader<T>(reader: Box<dyn Read + Send>) -> MithrilResult<T>
+where
+    T: DeserializeOwned + Send + 'static,
+{
+    #[cfg(all(feature = "fs", not(target_family = "wasm")))]
+    let content = tokio::task::spawn_blocking(move || serde_json::from_reader(reader))
+        .await
+        .with_context(|| "Could not deserialize the content on a blocking thread")?;
+    #[cfg(not(all(feature = "fs", not(target_family = "wasm"))))]
+    let content = serde_json::from_reader(reader);
+
+    Ok(content?)
+}
+
+/// Reader calling `on_content` with the whole content of the inner reader once it has been
+/// read, an error returned by `on_content` is returned by the last read.
+///
+/// Used by the [AggregatorClient] wrappers that need the whole content of a response to
+/// forward [get_content_reader][AggregatorClient::get_content_reader].
+pub(crate) struct ContentInspectingReader<F> {
+    reader: Box<dyn Read + Send>,
+    content: Vec<u8>,
+    on_content: Option<F>,
+}
+
+impl<F> ContentInspectingReader<F>
+where
+    F: FnOnce(&[u8]) -> MithrilResult<()> + Send,
+{
+    pub(crate) fn new(reader: Box<dyn Read + Send>, on_content: F) -> Self {
+        Self {
+            reader,
+            content: vec![],
+            on_content: Some(on_content),
+        }
+    }
+}
+
+impl<F> Read for ContentInspectingReader<F>
+where
+    F: FnOnce(&[u8]) -> MithrilResult<()> + Send,
+{
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read_bytes = self.reader.read(buf)?;
+        self.content.extend_from_slice(&buf[..read_bytes]);
+        if read_bytes == 0 {
+            if let Some(on_content) = self.on_content.take() {
+                on_content(&self.content).map_err(|e| {
+                    std::io::Error::new(std::io::ErrorKind::InvalidData, format!("{e:?}"))
+                })?;
+            }
+        }
+
+        Ok(read_bytes)
+    }
+}
+
+/// Wrap the body of a response in a reader decompressing it according to its
+/// `Content-Encoding`, so the decompressed content is never fully held in memory.
+///
+/// Reading more than `max_size` decompressed bytes fails.
+fn decoding_reader<R>(
+    content_encoding: Option<&str>,
+    body: R,
+    max_size: u64,
+) -> MithrilResult<Box<dyn Read + Send>>
+where
+    R: Read + Send + 'static,
+{
+    let compression = match content_encoding {
+        None | Some("identity") => {
+            return Ok(Box::new(SizeLimitedReader::new(body, max_size)));
+        }
+        Some("gzip" | "x-gzip") => ContentCompression::Gzip,
+        Some("deflate") => ContentCompression::Deflate,
+        Some(encoding) => return Err(anyhow!("Unsupported content encoding '{encoding}'")),
+    };
+
+    Ok(Box::new(SizeLimitedReader::new(
+        DecodingReader::Pending(Some((compression, BufReader::new(body)))),
+        max_size,
+    )))
+}
+
+/// Reader failing once more than `max_size` bytes are read from its inner reader.
+struct SizeLimitedReader<R> {
+    reader: R,
+    remaining: u64,
+    max_size: u64,
+}
+
+impl<R: Read> SizeLimitedReader<R> {
+    fn new(reader: R, max_size: u64) -> Self {
+        Self {
+            reader,
+            remaining: max_size,
+            max_size,
+        }
+    }
+}
+
+impl<R: Read> Read for SizeLimitedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        // Allow to read one byte more than the remaining ones to detect an oversized content
+        let max_read = buf.len().min(self.remaining.saturating_add(1) as usize);
+        let read_bytes = self.reader.read(&mut buf[..max_read])?;
+        if read_bytes as u64 > self.remaining {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!(
+                    "Decompressed content is larger than the maximum of {} bytes",
+                    self.max_size
+                ),
+            ));
+        }
+        self.remaining -= read_bytes as u64;
+
+        Ok(read_bytes)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum ContentCompression {
+    Gzip,
+    Deflate,
+}
+
+/// Reader decompressing a content, its decoder is created on the first read so a streamed
+/// body is not read before the reader is: the gzip decoder parses the header when created and
+/// the deflate content should be zlib wrapped but is sent raw by some servers, which is
+/// detected from the content.
+enum DecodingReader<R: Read> {
+    Pending(Option<(ContentCompression, BufReader<R>)>),
+    Gzip(GzDecoder<BufReader<R>>),
+    Zlib(ZlibDecoder<BufReader<R>>),
+    Raw(DeflateDecoder<BufReader<R>>),
+}
+
+impl<R: Read> Read for DecodingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if let Self::Pending(pending) = self {
+            // Checked before taking the body so a failed read can be retried
+            let is_zlib_wrapped = match pending {
+                Some((ContentCompression::Deflate, body)) => is_zlib_wrapped(body.fill_buf()?),
+                _ => false,
+            };
+            let (compression, body) = pending
+                .take()
+                .expect("decoding reader body is set until its decoder is created");
+            *self = match compression {
+                ContentCompression::Gzip => Self::Gzip(GzDecoder::new(body)),
+                ContentCompression::Deflate if is_zlib_wrapped => {
+                    Self::Zlib(ZlibDecoder::new(body))
+                }
+                ContentCompression::Deflate => Self::Raw(DeflateDecoder::new(body)),
+            };
+        }
+
+        match self {
+            Self::Pending(_) => unreachable!("the decoder is created above"),
+            Self::Gzip(decoder) => decoder.read(buf),
+            Self::Zlib(decoder) => decoder.read(buf),
+            Self::Raw(decoder) => decoder.read(buf),
+        }
+    }
+}
+
+/// Check if the given content starts with a zlib header (RFC 1950): a deflate compression
+/// method and a header checksum multiple of 31.
+fn is_zlib_wrapped(content: &[u8]) -> bool {
+    match content {
+        [cmf, flg, ..] => cmf & 0x0F == 8 && (u16::from(*cmf) << 8 | u16::from(*flg)) % 31 == 0,
+        _ => false,
     }
 }
 
+/// Requirement on the api version of a client to be compatible with the given aggregator api
+/// version: the same major version, or the same minor version while the major version is `0`.
+fn api_version_requirement(aggregator_api_version: &Version) -> VersionReq {
+    let requirement = match aggregator_api_version.major {
+        0 => format!("=0.{}", aggregator_api_version.minor),
+        major => format!("={major}"),
+    };
+
+    VersionReq::parse(&requirement).expect("api version requirement is valid")
+}
+
+/// Parse the value of a `Retry-After` header, either a number of seconds or a HTTP date.
+fn parse_retry_after(value: &str, now: DateTime<Utc>) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    DateTime::parse_from_rfc2822(value).ok().map(|date| {
+        (date.with_timezone(&Utc) - now)
+            .to_std()
+            .unwrap_or(Duration::ZERO)
+    })
+}
+
 #[cfg(test)]
 mod tests {
+    use crate::MithrilStakeDistribution;
+
     use super::*;
 
+    #[test]
+    fn routes_of_the_requests() {
+        for (expected, request) in [
+            (
+                "certificate/abc",
+                AggregatorRequest::GetCertificate {
+                    hash: "abc".to_string(),
+                },
+            ),
+            (
+                "certificate-chain/abc",
+                AggregatorRequest::GetCertificateChain {
+                    from_hash: "abc".to_string(),
+                },
+            ),
+            ("certificates", AggregatorRequest::ListCertificates),
+            (
+                "artifact/mithril-stake-distribution/abc",
+                AggregatorRequest::GetMithrilStakeDistribution {
+                    hash: "abc".to_string(),
+                },
+            ),
+            (
+                "artifact/mithril-stake-distributions",
+                AggregatorRequest::ListMithrilStakeDistributions,
+            ),
+            (
+                "artifact/snapshot/abc",
+                AggregatorRequest::GetSnapshot {
+                    digest: "abc".to_string(),
+                },
+            ),
+            ("artifact/snapshots", AggregatorRequest::ListSnapshots),
+            ("epoch-settings", AggregatorRequest::GetEpochSettings),
+            ("", AggregatorRequest::GetAggregatorFeatures),
+            (
+                "certificates?limit=5&before=abc",
+                AggregatorRequest::ListCertificatesPage {
+                    limit: 5,
+                    before: Some("abc".to_string()),
+                },
+            ),
+        ] {
+            assert_eq!(expected, request.route());
+        }
+    }
+
+    #[test]
+    fn route_parameters_are_percent_encoded() {
+        let route = AggregatorRoute::new("artifact/snapshot")
+            .with_parameter("a/b c?d#e%")
+            .with_query_parameter("from epoch", "4&5")
+            .with_query_parameter("limit", 10);
+
+        assert_eq!(
+            "artifact/snapshot/a%2Fb%20c%3Fd%23e%25?from%20epoch=4%265&limit=10",
+            route.to_string()
+        );
+    }
+
+    #[test]
+    fn route_resolved_against_the_aggregator_endpoint() {
+        let endpoint = Url::parse("http://www.test.net/aggregator/").unwrap();
+
+        let url = AggregatorRoute::new("certificate")
+            .with_parameter("../../root")
+            .to_url(&endpoint)
+            .unwrap();
+
+        assert_eq!(
+            "http://www.test.net/aggregator/certificate/..%2F..%2Froot",
+            url.as_str()
+        );
+    }
+
+    #[test]
+    fn route_with_a_parameter_pointing_out_of_the_route_is_invalid() {
+        let endpoint = Url::parse("http://www.test.net/aggregator/").unwrap();
+
+        for parameter in ["", ".", ".."] {
+            AggregatorRoute::new("certificate")
+                .with_parameter(parameter)
+                .to_url(&endpoint)
+                .expect_err("route parameter should be rejected");
+        }
+    }
+
     #[test]
     fn always_append_trailing_slash_at_build() {
         for (expected, url) in [
@@ -281,4 +1434,694 @@ mod tests {
             assert_eq!(expected, client.aggregator_endpoint.as_str());
         }
     }
+
+    #[tokio::test]
+    async fn reject_responses_of_an_aggregator_which_key_is_not_pinned() {
+        let server = httpmock::MockServer::start();
+        server.mock(|when, then| {
+            when.path("/artifact/snapshots");
+            then.status(200).body("[]");
+        });
+        let spki_pins = SpkiPins::new()
+            .with_pin("sha256/HHCMrb1YsQ/krfXE6GfPYTUB2EZnRr/rbujt6UocXC4=")
+            .unwrap();
+        let client = AggregatorHTTPClient::new(
+            Url::parse(&server.base_url()).unwrap(),
+            crate::common::api_version::APIVersionProvider::compute_all_versions_sorted().unwrap(),
+            crate::test_utils::test_logger(),
+        )
+        .unwrap()
+        .with_spki_pins(&spki_pins)
+        .unwrap();
+
+        let error = client
+            .get_content(AggregatorRequest::ListSnapshots)
+            .await
+            .expect_err("a server without a pinned key should be rejected");
+
+        let AggregatorClientError::TlsPinMismatch(source) = error else {
+            panic!("Expected a TLS pin mismatch, got: {error:?}");
+        };
+        let mismatch = source
+            .downcast_ref::<crate::tls::PinMismatch>()
+            .expect("source should be a PinMismatch");
+        assert_eq!(None, mismatch.presented_pin);
+    }
+
+    #[test]
+    fn pinning_without_any_pin_fails() {
+        AggregatorHTTPClient::new(
+            Url::parse("https://aggregator.invalid").unwrap(),
+            vec![],
+            crate::test_utils::test_logger(),
+        )
+        .unwrap()
+        .with_spki_pins(&SpkiPins::new())
+        .err()
+        .expect("pinning without any pin should fail");
+    }
+
+    #[tokio::test]
+    async fn send_requests_through_the_proxy() {
+        let proxy_server = httpmock::MockServer::start();
+        let proxy_mock = proxy_server.mock(|when, then| {
+            when.path("/aggregator/artifact/snapshots");
+            then.status(200).body("[]");
+        });
+        let client = AggregatorHTTPClient::new(
+            Url::parse("http://aggregator.invalid/aggregator").unwrap(),
+            crate::common::api_version::APIVersionProvider::compute_all_versions_sorted().unwrap(),
+            crate::test_utils::test_logger(),
+        )
+        .unwrap()
+        .with_proxy(&ProxyConfig::new(&proxy_server.base_url()))
+        .unwrap();
+
+        let content = client
+            .get_content(AggregatorRequest::ListSnapshots)
+            .await
+            .unwrap();
+
+        assert_eq!("[]", content);
+        proxy_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn keep_sending_requests_through_the_proxy_when_tls_settings_are_given() {
+        let proxy_server = httpmock::MockServer::start();
+        let proxy_mock = proxy_server.mock(|when, then| {
+            when.path("/aggregator/artifact/snapshots");
+            then.status(200).body("[]");
+        });
+        let client = AggregatorHTTPClient::new(
+            Url::parse("http://aggregator.invalid/aggregator").unwrap(),
+            crate::common::api_version::APIVersionProvider::compute_all_versions_sorted().unwrap(),
+            crate::test_utils::test_logger(),
+        )
+        .unwrap()
+        .with_proxy(&ProxyConfig::new(&proxy_server.base_url()))
+        .unwrap()
+        .with_tls(&TlsConfig::new())
+        .unwrap();
+
+        client
+            .get_content(AggregatorRequest::ListSnapshots)
+            .await
+            .unwrap();
+
+        proxy_mock.assert();
+    }
+
+    fn probe(endpoint: &str, epoch: Option<u64>, latency_ms: u64) -> AggregatorProbe {
+        AggregatorProbe {
+            endpoint: endpoint.to_string(),
+            error: None,
+            api_version: None,
+            latency: Some(Duration::from_millis(latency_ms)),
+            latest_certificate_beacon: epoch
+                .map(|epoch| Beacon::new("testnet".to_string(), epoch, 1)),
+            latest_certificate_sealed_at: None,
+        }
+    }
+
+    #[test]
+    fn probe_report_rank_reachable_then_freshest_then_fastest_aggregators() {
+        let report = AggregatorProbeReport::new(vec![
+            AggregatorProbe::failure("unreachable", anyhow!("error")),
+            probe("slow_fresh", Some(10), 500),
+            probe("no_certificate", None, 1),
+            probe("stale", Some(9), 1),
+            probe("fast_fresh", Some(10), 100),
+        ]);
+
+        assert_eq!(
+            vec![
+                "fast_fresh",
+                "slow_fresh",
+                "stale",
+                "no_certificate",
+                "unreachable"
+            ],
+            report
+                .probes
+                .iter()
+                .map(|probe| probe.endpoint.as_str())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!("fast_fresh", report.best().unwrap().endpoint);
+    }
+
+    #[test]
+    fn probe_report_has_no_best_aggregator_if_none_is_reachable() {
+        let report = AggregatorProbeReport::new(vec![AggregatorProbe::failure(
+            "unreachable",
+            anyhow!("error"),
+        )]);
+
+        assert_eq!(None, report.best());
+    }
+
+    #[tokio::test]
+    async fn probe_reachable_aggregator() {
+        let server = httpmock::MockServer::start_async().await;
+        let certificate = MithrilCertificateListItem::dummy();
+        server
+            .mock_async(|when, then| {
+                when.path("/certificates");
+                then.status(200)
+                    .body(serde_json::to_string(&vec![certificate.clone()]).unwrap());
+            })
+            .await;
+        let client = AggregatorHTTPClient::new(
+            Url::parse(&server.base_url()).unwrap(),
+            vec![Version::new(1, 0, 0)],
+            crate::test_utils::test_logger(),
+        )
+        .unwrap();
+
+        let probe = client.probe().await;
+
+        assert!(probe.is_reachable(), "Unexpected probe: {probe:?}");
+        assert_eq!(Some(Version::new(1, 0, 0)), probe.api_version);
+        assert!(probe.latency.is_some());
+        assert_eq!(Some(certificate.beacon), probe.latest_certificate_beacon);
+        assert_eq!(
+            Some(certificate.metadata.sealed_at),
+            probe.latest_certificate_sealed_at
+        );
+    }
+
+    #[tokio::test]
+    async fn probe_aggregator_returning_an_error() {
+        let server = httpmock::MockServer::start_async().await;
+        server
+            .mock_async(|when, then| {
+                when.path("/certificates");
+                then.status(500);
+            })
+            .await;
+        let client = AggregatorHTTPClient::new(
+            Url::parse(&server.base_url()).unwrap(),
+            vec![Version::new(1, 0, 0)],
+            crate::test_utils::test_logger(),
+        )
+        .unwrap();
+
+        let probe = client.probe().await;
+
+        assert!(!probe.is_reachable());
+        assert_eq!(None, probe.latency);
+    }
+
+    #[tokio::test]
+    async fn probe_negotiates_the_api_version_advertised_by_the_aggregator() {
+        let server = httpmock::MockServer::start_async().await;
+        server
+            .mock_async(|when, then| {
+                when.path("/certificates");
+                then.status(200)
+                    .header(MITHRIL_API_VERSION_HEADER, "0.1.14")
+                    .body("[]");
+            })
+            .await;
+        let client = AggregatorHTTPClient::new(
+            Url::parse(&server.base_url()).unwrap(),
+            vec![
+                Version::new(0, 2, 0),
+                Version::new(0, 1, 10),
+                Version::new(0, 1, 2),
+            ],
+            crate::test_utils::test_logger(),
+        )
+        .unwrap();
+
+        let probe = client.probe().await;
+
+        assert_eq!(Some(Version::new(0, 1, 10)), probe.api_version);
+        assert_eq!(
+            Some(Version::new(0, 1, 10)),
+            client.compute_current_api_version().await
+        );
+    }
+
+    #[tokio::test]
+    async fn probe_fails_if_no_api_version_is_compatible_with_the_aggregator() {
+        let server = httpmock::MockServer::start_async().await;
+        server
+            .mock_async(|when, then| {
+                when.path("/certificates");
+                then.status(200)
+                    .header(MITHRIL_API_VERSION_HEADER, "2.0.0")
+                    .body("[]");
+            })
+            .await;
+        let client = AggregatorHTTPClient::new(
+            Url::parse(&server.base_url()).unwrap(),
+            vec![Version::new(1, 0, 0)],
+            crate::test_utils::test_logger(),
+        )
+        .unwrap();
+
+        let probe = client.probe().await;
+
+        assert!(!probe.is_reachable());
+        assert_eq!(
+            Some(Version::new(1, 0, 0)),
+            client.compute_current_api_version().await
+        );
+    }
+
+    #[tokio::test]
+    async fn html_responses_are_rejected_with_a_snippet_of_their_body() {
+        let server = httpmock::MockServer::start_async().await;
+        server
+            .mock_async(|when, then| {
+                when.path("/certificates");
+                then.status(200)
+                    .header("content-type", "text/html; charset=utf-8")
+                    .body("<html>\n  <body>Please   log in to the Wi-Fi</body>\n</html>");
+            })
+            .await;
+        let client = AggregatorHTTPClient::new(
+            Url::parse(&server.base_url()).unwrap(),
+            vec![Version::new(1, 0, 0)],
+            crate::test_utils::test_logger(),
+        )
+        .unwrap();
+
+        let error = client
+            .get_content(AggregatorRequest::ListCertificates)
+            .await
+            .expect_err("an HTML response should be rejected");
+
+        match error {
+            AggregatorClientError::NonJsonResponse {
+                content_type,
+                snippet,
+            } => {
+                assert_eq!("text/html; charset=utf-8", content_type);
+                assert_eq!(
+                    "<html> <body>Please log in to the Wi-Fi</body> </html>",
+                    snippet
+                );
+            }
+            error => panic!("Unexpected error: {error:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn json_responses_with_content_type_parameters_are_accepted() {
+        let server = httpmock::MockServer::start_async().await;
+        server
+            .mock_async(|when, then| {
+                when.path("/certificates");
+                then.status(200)
+                    .header("content-type", "Application/JSON; charset=utf-8")
+                    .body("[]");
+            })
+            .await;
+        let client = AggregatorHTTPClient::new(
+            Url::parse(&server.base_url()).unwrap(),
+            vec![Version::new(1, 0, 0)],
+            crate::test_utils::test_logger(),
+        )
+        .unwrap();
+
+        let content = client
+            .get_content(AggregatorRequest::ListCertificates)
+            .await
+            .unwrap();
+
+        assert_eq!("[]", content);
+    }
+
+    #[test]
+    fn response_snippet_is_truncated() {
+        let body = "a".repeat(NON_JSON_RESPONSE_SNIPPET_LENGTH * 2);
+
+        assert_eq!(
+            NON_JSON_RESPONSE_SNIPPET_LENGTH,
+            response_snippet(body.as_bytes()).len()
+        );
+    }
+
+    async fn get_compressed_content(
+        encoding: &str,
+        compressed_body: Vec<u8>,
+    ) -> (String, Vec<(String, u64, u64)>) {
+        let server = httpmock::MockServer::start_async().await;
+        server
+            .mock_async(|when, then| {
+                when.path("/artifact/snapshots")
+                    .header("accept-encoding", "gzip, deflate");
+                then.status(200)
+                    .header("content-encoding", encoding)
+                    .body(compressed_body);
+            })
+            .await;
+        let metrics_recorder =
+            Arc::new(crate::metrics::test_utils::StackMetricsRecorder::default());
+        let client = AggregatorHTTPClient::new(
+            Url::parse(&server.base_url()).unwrap(),
+            vec![Version::new(1, 0, 0)],
+            crate::test_utils::test_logger(),
+        )
+        .unwrap()
+        .with_metrics_recorder(metrics_recorder.clone());
+
+        let content = client
+            .get_content(AggregatorRequest::ListSnapshots)
+            .await
+            .unwrap();
+        let sizes = metrics_recorder
+            .aggregator_response_sizes
+            .lock()
+            .unwrap()
+            .clone();
+
+        (content, sizes)
+    }
+
+    #[tokio::test]
+    async fn decompress_gzip_responses() {
+        use flate2::{write::GzEncoder, Compression};
+        use std::io::Write;
+
+        let body = serde_json::to_string(&vec![MithrilCertificateListItem::dummy(); 10]).unwrap();
+        let mut encoder = GzEncoder::new(vec![], Compression::default());
+        encoder.write_all(body.as_bytes()).unwrap();
+        let compressed_body = encoder.finish().unwrap();
+        let compressed_size = compressed_body.len() as u64;
+
+        let (content, sizes) = get_compressed_content("gzip", compressed_body).await;
+
+        assert_eq!(body, content);
+        assert_eq!(
+            vec![(
+                "artifact/snapshots".to_string(),
+                compressed_size,
+                body.len() as u64
+            )],
+            sizes
+        );
+        assert!(compressed_size < body.len() as u64);
+    }
+
+    #[tokio::test]
+    async fn decompress_deflate_responses() {
+        use flate2::{write::ZlibEncoder, Compression};
+        use std::io::Write;
+
+        let body = serde_json::to_string(&vec![MithrilCertificateListItem::dummy(); 10]).unwrap();
+        let mut encoder = ZlibEncoder::new(vec![], Compression::default());
+        encoder.write_all(body.as_bytes()).unwrap();
+
+        let (content, _) = get_compressed_content("deflate", encoder.finish().unwrap()).await;
+
+        assert_eq!(body, content);
+    }
+
+    #[test]
+    fn decoding_reader_fails_when_the_decompressed_content_is_too_large() {
+        use flate2::{write::GzEncoder, Compression};
+        use std::io::Write;
+
+        let mut encoder = GzEncoder::new(vec![], Compression::default());
+        encoder.write_all(&[0; 1024]).unwrap();
+        let compressed_body = encoder.finish().unwrap();
+        let read_to_end = |content_encoding, body: Vec<u8>, max_size| {
+            let mut content = vec![];
+            decoding_reader(content_encoding, Cursor::new(body), max_size)
+                .unwrap()
+                .read_to_end(&mut content)
+                .map(|_| content)
+        };
+
+        assert_eq!(
+            vec![0; 1024],
+            read_to_end(Some("gzip"), compressed_body.clone(), 1024).unwrap()
+        );
+        read_to_end(Some("gzip"), compressed_body, 1023)
+            .expect_err("content larger than the maximum size should fail");
+        read_to_end(None, vec![0; 1024], 1023)
+            .expect_err("content larger than the maximum size should fail");
+    }
+
+    #[tokio::test]
+    async fn stream_and_decompress_gzip_responses_while_reading() {
+        use flate2::{write::GzEncoder, Compression};
+        use std::io::Write;
+
+        let body = serde_json::to_string(&MithrilStakeDistribution::dummy()).unwrap();
+        let mut encoder = GzEncoder::new(vec![], Compression::default());
+        encoder.write_all(body.as_bytes()).unwrap();
+        let compressed_body = encoder.finish().unwrap();
+        let compressed_size = compressed_body.len() as u64;
+        let server = httpmock::MockServer::start_async().await;
+        server
+            .mock_async(|when, then| {
+                when.path("/artifact/mithril-stake-distribution/hash");
+                then.status(200)
+                    .header("content-encoding", "gzip")
+                    .body(compressed_body);
+            })
+            .await;
+        let metrics_recorder =
+            Arc::new(crate::metrics::test_utils::StackMetricsRecorder::default());
+        let client = AggregatorHTTPClient::new(
+            Url::parse(&server.base_url()).unwrap(),
+            vec![Version::new(1, 0, 0)],
+            crate::test_utils::test_logger(),
+        )
+        .unwrap()
+        .with_metrics_recorder(metrics_recorder.clone());
+
+        let reader = client
+            .get_content_reader(AggregatorRequest::GetMithrilStakeDistribution {
+                hash: "hash".to_string(),
+            })
+            .await
+            .unwrap();
+        let mithril_stake_distribution: MithrilStakeDistribution =
+            deserialize_content_reader(reader).await.unwrap();
+
+        assert_eq!(
+            MithrilStakeDistribution::dummy(),
+            mithril_stake_distribution
+        );
+        assert_eq!(
+            vec![(
+                "artifact/mithril-stake-distribution/hash".to_string(),
+                compressed_size,
+                body.len() as u64
+            )],
+            metrics_recorder
+                .aggregator_response_sizes
+                .lock()
+                .unwrap()
+                .clone()
+        );
+    }
+
+    #[test]
+    fn decoding_reader_detects_zlib_wrapped_and_raw_deflate_content() {
+        use flate2::{
+            write::{DeflateEncoder, ZlibEncoder},
+            Compression,
+        };
+        use std::io::Write;
+
+        let read_to_end = |body: Vec<u8>| {
+            let mut content = vec![];
+            decoding_reader(Some("deflate"), Cursor::new(body), u64::MAX)
+                .unwrap()
+                .read_to_end(&mut content)
+                .unwrap();
+            content
+        };
+        let mut zlib_encoder = ZlibEncoder::new(vec![], Compression::default());
+        zlib_encoder.write_all(b"content").unwrap();
+        let mut deflate_encoder = DeflateEncoder::new(vec![], Compression::default());
+        deflate_encoder.write_all(b"content").unwrap();
+
+        assert_eq!(
+            b"content".to_vec(),
+            read_to_end(zlib_encoder.finish().unwrap())
+        );
+        assert_eq!(
+            b"content".to_vec(),
+            read_to_end(deflate_encoder.finish().unwrap())
+        );
+        assert!(decoding_reader(Some("br"), Cursor::new(b"content"), u64::MAX).is_err());
+    }
+
+    #[test]
+    fn parse_retry_after_header() {
+        let now = DateTime::parse_from_rfc3339("2015-10-21T07:28:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        assert_eq!(
+            Some(Duration::from_secs(120)),
+            parse_retry_after(" 120 ", now)
+        );
+        assert_eq!(
+            Some(Duration::from_secs(30)),
+            parse_retry_after("Wed, 21 Oct 2015 07:28:30 GMT", now)
+        );
+        assert_eq!(
+            Some(Duration::ZERO),
+            parse_retry_after("Wed, 21 Oct 2015 07:27:00 GMT", now)
+        );
+        assert_eq!(None, parse_retry_after("soon", now));
+    }
+
+    #[tokio::test]
+    async fn too_many_requests_response_is_a_rate_limited_error() {
+        let server = httpmock::MockServer::start_async().await;
+        server
+            .mock_async(|when, then| {
+                when.path("/certificates");
+                then.status(429).header("Retry-After", "7");
+            })
+            .await;
+        let client = AggregatorHTTPClient::new(
+            Url::parse(&server.base_url()).unwrap(),
+            vec![Version::new(1, 0, 0)],
+            crate::test_utils::test_logger(),
+        )
+        .unwrap();
+
+        let error = client
+            .get_content(AggregatorRequest::ListCertificates)
+            .await
+            .expect_err("get_content should fail");
+
+        assert!(
+            matches!(
+                error,
+                AggregatorClientError::RateLimited {
+                    retry_after: Some(retry_after)
+                } if retry_after == Duration::from_secs(7)
+            ),
+            "Unexpected error: {error:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn unchanged_list_is_served_from_the_cache() {
+        let server = httpmock::MockServer::start_async().await;
+        let not_modified_mock = server
+            .mock_async(|when, then| {
+                when.path("/artifact/snapshots")
+                    .header("if-none-match", "\"v1\"");
+                then.status(304);
+            })
+            .await;
+        let list_mock = server
+            .mock_async(|when, then| {
+                when.path("/artifact/snapshots");
+                then.status(200).header("ETag", "\"v1\"").body("[]");
+            })
+            .await;
+        let client = AggregatorHTTPClient::new(
+            Url::parse(&server.base_url()).unwrap(),
+            vec![Version::new(1, 0, 0)],
+            crate::test_utils::test_logger(),
+        )
+        .unwrap();
+
+        for _ in 0..3 {
+            let content = client
+                .get_content(AggregatorRequest::ListSnapshots)
+                .await
+                .unwrap();
+            assert_eq!("[]", content);
+        }
+
+        list_mock.assert_hits_async(1).await;
+        not_modified_mock.assert_hits_async(2).await;
+    }
+
+    #[tokio::test]
+    async fn list_is_revalidated_with_its_last_modified_date_without_etag() {
+        let last_modified = "Wed, 21 Oct 2015 07:28:00 GMT";
+        let server = httpmock::MockServer::start_async().await;
+        let not_modified_mock = server
+            .mock_async(|when, then| {
+                when.path("/certificates")
+                    .header("if-modified-since", last_modified);
+                then.status(304);
+            })
+            .await;
+        server
+            .mock_async(|when, then| {
+                when.path("/certificates");
+                then.status(200)
+                    .header("Last-Modified", last_modified)
+                    .body("[]");
+            })
+            .await;
+        let client = AggregatorHTTPClient::new(
+            Url::parse(&server.base_url()).unwrap(),
+            vec![Version::new(1, 0, 0)],
+            crate::test_utils::test_logger(),
+        )
+        .unwrap();
+
+        for _ in 0..2 {
+            let content = client
+                .get_content(AggregatorRequest::ListCertificates)
+                .await
+                .unwrap();
+            assert_eq!("[]", content);
+        }
+
+        not_modified_mock.assert_hits_async(1).await;
+    }
+
+    #[tokio::test]
+    async fn no_conditional_request_if_disabled_or_not_a_list() {
+        let server = httpmock::MockServer::start_async().await;
+        let conditional_mock = server
+            .mock_async(|when, then| {
+                when.header_exists("if-none-match");
+                then.status(304);
+            })
+            .await;
+        server
+            .mock_async(|when, then| {
+                when.any_request();
+                then.status(200).header("ETag", "\"v1\"").body("{}");
+            })
+            .await;
+        let client = AggregatorHTTPClient::new(
+            Url::parse(&server.base_url()).unwrap(),
+            vec![Version::new(1, 0, 0)],
+            crate::test_utils::test_logger(),
+        )
+        .unwrap();
+        let client_without_cache = AggregatorHTTPClient::new(
+            Url::parse(&server.base_url()).unwrap(),
+            vec![Version::new(1, 0, 0)],
+            crate::test_utils::test_logger(),
+        )
+        .unwrap()
+        .with_conditional_requests(false);
+
+        for _ in 0..2 {
+            client
+                .get_content(AggregatorRequest::GetSnapshot {
+                    digest: "digest".to_string(),
+                })
+                .await
+                .unwrap();
+            client_without_cache
+                .get_content(AggregatorRequest::ListSnapshots)
+                .await
+                .unwrap();
+        }
+
+        conditional_mock.assert_hits_async(0).await;
+    }
 }
@@ -0,0 +1,237 @@
+//! A blocking facade of the [Client][crate::Client], for programs that don't run an async runtime.
+//!
+//! The blocking [Client] wraps an asynchronous client and drives its requests on its own
+//! [tokio] runtime, each call blocks the current thread until its completion.
+//!
+//! **Warning:** the methods of the blocking client must not be called from within an async
+//! runtime, they would panic.
+//!
+//! **Note:** _Available using crate feature_ **blocking**.
+//!
+//! # Verify the certificate chain of the latest snapshot
+//!
+//! ```no_run
+//! # fn run() -> mithril_client::MithrilResult<()> {
+//! use mithril_client::{blocking, ClientBuilder};
+//!
+//! let client = ClientBuilder::aggregator("YOUR_AGGREGATOR_ENDPOINT", "YOUR_GENESIS_VERIFICATION_KEY").build()?;
+//! let client = blocking::Client::new(client)?;
+//!
+//! let snapshots = client.snapshot().list()?;
+//! let snapshot = client.snapshot().get(&snapshots[0].digest)?.unwrap();
+//! let certificate = client.certificate().verify_chain(&snapshot.certificate_hash)?;
+//!
+//! println!("Snapshot {} is certified by {}", snapshot.digest, certificate.hash);
+//! #    Ok(())
+//! # }
+//! ```
+
+use anyhow::Context;
+use std::sync::Arc;
+use tokio::runtime::Runtime;
+
+use crate::{
+    MithrilCertificate, MithrilCertificateListItem, MithrilResult, MithrilStakeDistribution,
+    MithrilStakeDistributionListItem, Snapshot, SnapshotListItem,
+};
+
+/// A blocking wrapper of a [Client][crate::Client].
+pub struct Client {
+    client: crate::Client,
+    runtime: Arc<Runtime>,
+}
+
+impl Client {
+    /// Constructs a new blocking `Client` that wraps the given client.
+    pub fn new(client: crate::Client) -> MithrilResult<Self> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .with_context(|| "Could not build the runtime of the blocking client")?;
+
+        Ok(Self {
+            client,
+            runtime: Arc::new(runtime),
+        })
+    }
+
+    /// Get the blocking client that fetches and verifies Mithril certificates.
+    pub fn certificate(&self) -> CertificateClient {
+        CertificateClient {
+            client: self.client.certificate(),
+            runtime: self.runtime.clone(),
+        }
+    }
+
+    /// Get the blocking client that fetches Mithril stake distributions.
+    pub fn mithril_stake_distribution(&self) -> MithrilStakeDistributionClient {
+        MithrilStakeDistributionClient {
+            client: self.client.mithril_stake_distribution(),
+            runtime: self.runtime.clone(),
+        }
+    }
+
+    /// Get the blocking client that fetches and downloads Mithril snapshots.
+    pub fn snapshot(&self) -> SnapshotClient {
+        SnapshotClient {
+            client: self.client.snapshot(),
+            runtime: self.runtime.clone(),
+        }
+    }
+
+    cfg_fs! {
+    /// Get the [message builder of the client][crate::Client::message_builder].
+    pub fn message_builder(&self) -> crate::MessageBuilder {
+        self.client.message_builder()
+    }
+
+    /// Compute the message of a snapshot unpacked in the given directory with the
+    /// [message builder of the client][crate::Client::message_builder], see
+    /// [MessageBuilder::compute_snapshot_message][crate::MessageBuilder::compute_snapshot_message].
+    pub fn compute_snapshot_message(
+        &self,
+        snapshot_certificate: &MithrilCertificate,
+        unpacked_snapshot_directory: &std::path::Path,
+    ) -> MithrilResult<crate::common::entities::ProtocolMessage> {
+        self.runtime.block_on(
+            self.client
+                .message_builder()
+                .compute_snapshot_message(snapshot_certificate, unpacked_snapshot_directory),
+        )
+    }
+    }
+}
+
+/// Blocking version of the [CertificateClient][crate::certificate_client::CertificateClient].
+pub struct CertificateClient {
+    client: Arc<crate::certificate_client::CertificateClient>,
+    runtime: Arc<Runtime>,
+}
+
+impl CertificateClient {
+    /// Fetch a list of certificates
+    pub fn list(&self) -> MithrilResult<Vec<MithrilCertificateListItem>> {
+        self.runtime.block_on(self.client.list())
+    }
+
+    /// Get a single certificate full information from the aggregator.
+    pub fn get(&self, certificate_hash: &str) -> MithrilResult<Option<MithrilCertificate>> {
+        self.runtime.block_on(self.client.get(certificate_hash))
+    }
+
+    /// Validate the chain starting with the certificate with given `certificate_hash`, return the
+    /// certificate if the chain is valid.
+    pub fn verify_chain(&self, certificate_hash: &str) -> MithrilResult<MithrilCertificate> {
+        self.runtime
+            .block_on(self.client.verify_chain(certificate_hash))
+    }
+}
+
+/// Blocking version of the
+/// [MithrilStakeDistributionClient][crate::mithril_stake_distribution_client::MithrilStakeDistributionClient].
+pub struct MithrilStakeDistributionClient {
+    client: Arc<crate::mithril_stake_distribution_client::MithrilStakeDistributionClient>,
+    runtime: Arc<Runtime>,
+}
+
+impl MithrilStakeDistributionClient {
+    /// Fetch a list of signed MithrilStakeDistribution
+    pub fn list(&self) -> MithrilResult<Vec<MithrilStakeDistributionListItem>> {
+        self.runtime.block_on(self.client.list())
+    }
+
+    /// Get the given stake distribution data. If it cannot be found, a None is returned.
+    pub fn get(&self, hash: &str) -> MithrilResult<Option<MithrilStakeDistribution>> {
+        self.runtime.block_on(self.client.get(hash))
+    }
+}
+
+/// Blocking version of the [SnapshotClient][crate::snapshot_client::SnapshotClient].
+pub struct SnapshotClient {
+    client: Arc<crate::snapshot_client::SnapshotClient>,
+    runtime: Arc<Runtime>,
+}
+
+impl SnapshotClient {
+    /// Return a list of available snapshots
+    pub fn list(&self) -> MithrilResult<Vec<SnapshotListItem>> {
+        self.runtime.block_on(self.client.list())
+    }
+
+    /// Get the given snapshot data. If it cannot be found, a None is returned.
+    pub fn get(&self, digest: &str) -> MithrilResult<Option<Snapshot>> {
+        self.runtime.block_on(self.client.get(digest))
+    }
+
+    cfg_fs! {
+    /// Download and unpack the given snapshot to the given directory, see
+    /// [SnapshotClient::download_unpack][crate::snapshot_client::SnapshotClient::download_unpack].
+    pub fn download_unpack(
+        &self,
+        snapshot: &Snapshot,
+        target_dir: &std::path::Path,
+    ) -> MithrilResult<()> {
+        self.runtime.block_on(self.client.download_unpack(snapshot, target_dir))
+    }
+
+    /// Download and unpack the given snapshot in a staging directory, then promote it to the
+    /// given directory once verified against the given certificate, see
+    /// [SnapshotClient::download_unpack_staged][crate::snapshot_client::SnapshotClient::download_unpack_staged].
+    pub fn download_unpack_staged(
+        &self,
+        snapshot: &Snapshot,
+        certificate: &MithrilCertificate,
+        message_builder: &crate::MessageBuilder,
+        target_dir: &std::path::Path,
+    ) -> MithrilResult<()> {
+        self.runtime.block_on(self.client.download_unpack_staged(
+            snapshot,
+            certificate,
+            message_builder,
+            target_dir,
+        ))
+    }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::aggregator_client::{AggregatorRequest, MockAggregatorHTTPClient};
+    use crate::certificate_client::MockCertificateVerifier;
+    use crate::ClientBuilder;
+
+    use super::*;
+
+    fn blocking_client(aggregator_client: MockAggregatorHTTPClient) -> Client {
+        let client = ClientBuilder::new("genesis_verification_key")
+            .with_aggregator_client(Arc::new(aggregator_client))
+            .with_certificate_verifier(Arc::new(MockCertificateVerifier::new()))
+            .build()
+            .unwrap();
+
+        Client::new(client).unwrap()
+    }
+
+    #[test]
+    fn list_and_get_without_async_runtime() {
+        let mut aggregator_client = MockAggregatorHTTPClient::new();
+        aggregator_client
+            .expect_get_content()
+            .returning(|request| match request {
+                AggregatorRequest::ListSnapshots => {
+                    Ok(serde_json::to_string(&vec![SnapshotListItem::dummy()]).unwrap())
+                }
+                AggregatorRequest::GetSnapshot { .. } => {
+                    Ok(serde_json::to_string(&Snapshot::dummy()).unwrap())
+                }
+                _ => panic!("unexpected request: {request:?}"),
+            });
+        let client = blocking_client(aggregator_client);
+
+        let snapshots = client.snapshot().list().unwrap();
+        let snapshot = client.snapshot().get(&snapshots[0].digest).unwrap();
+
+        assert_eq!(vec![SnapshotListItem::dummy()], snapshots);
+        assert_eq!(Some(Snapshot::dummy()), snapshot);
+    }
+}
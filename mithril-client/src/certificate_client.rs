@@ -3,7 +3,21 @@
 //! In order to do so it defines a [CertificateClient] exposes the following features:
 //!  - [get][CertificateClient::get]: get a certificate data from its hash
 //!  - [list][CertificateClient::list]: get the list of available certificates
+//!  - [list_page][CertificateClient::list_page]: get a page of the list of certificates
+//!  - [list_all][CertificateClient::list_all]: stream all the certificates, page by page
+//!  - [watch][CertificateClient::watch]: stream the newly issued certificates
 //!  - [verify_chain][CertificateClient::verify_chain]: verify a certificate chain
+//!  - [verify_chain_matching][CertificateClient::verify_chain_matching]: verify a certificate
+//!    chain and that its latest certificate signed an expected
+//!    [protocol message][crate::protocol_message_matcher]
+//!  - [verify_participation][CertificateClient::verify_participation]: verify a certificate chain
+//!    and report the participation of the signers to its latest certificate
+//!  - [verify_chain_step][CertificateClient::verify_chain_step]: verify a certificate chain
+//!    incrementally, a bounded number of certificates at a time
+//!  - [attest_timestamp][CertificateClient::attest_timestamp]: anchor arbitrary data with a claimed
+//!    timestamp to the verified certificate chain
+//!  - [export_proof_bundle][CertificateClient::export_proof_bundle]: export a verified certificate
+//!    chain as a [proof bundle][crate::proof_bundle] that can be verified offline
 //!
 //! # Get a certificate
 //!
@@ -39,6 +53,49 @@
 //! # }
 //! ```
 //!
+//! # Iterate the whole list of certificates
+//!
+//! To stream all the certificates of the aggregator, fetched by pages, using the [ClientBuilder][crate::client::ClientBuilder].
+//!
+//! ```no_run
+//! # async fn run() -> mithril_client::MithrilResult<()> {
+//! use futures::StreamExt;
+//! use mithril_client::ClientBuilder;
+//!
+//! let client = ClientBuilder::aggregator("YOUR_AGGREGATOR_ENDPOINT", "YOUR_GENESIS_VERIFICATION_KEY").build()?;
+//! let certificate_client = client.certificate();
+//! let mut certificates = Box::pin(certificate_client.list_all(100));
+//!
+//! while let Some(certificate) = certificates.next().await {
+//!     let certificate = certificate?;
+//!     println!("Certificate hash={}, epoch={}", certificate.hash, certificate.beacon.epoch);
+//! }
+//! #    Ok(())
+//! # }
+//! ```
+//!
+//! # Watch newly issued certificates
+//!
+//! To be notified of the certificates issued by the aggregator using the [ClientBuilder][crate::client::ClientBuilder].
+//!
+//! ```no_run
+//! # async fn run() -> mithril_client::MithrilResult<()> {
+//! use futures::StreamExt;
+//! use mithril_client::ClientBuilder;
+//! use std::time::Duration;
+//!
+//! let client = ClientBuilder::aggregator("YOUR_AGGREGATOR_ENDPOINT", "YOUR_GENESIS_VERIFICATION_KEY").build()?;
+//! let certificate_client = client.certificate();
+//! let mut certificates = Box::pin(certificate_client.watch(Duration::from_secs(60)));
+//!
+//! while let Some(certificate) = certificates.next().await {
+//!     let certificate = certificate?;
+//!     println!("New certificate hash={}, signed_message={}", certificate.hash, certificate.signed_message);
+//! }
+//! #    Ok(())
+//! # }
+//! ```
+//!
 //! # Validate a certificate chain
 //!
 //! To validate a certificate using the [ClientBuilder][crate::client::ClientBuilder].
@@ -54,27 +111,172 @@
 //! #    Ok(())
 //! # }
 //! ```
+//!
+//! Or to validate the chain of the latest certificate issued by the aggregator:
+//!
+//! ```no_run
+//! # async fn run() -> mithril_client::MithrilResult<()> {
+//! use mithril_client::ClientBuilder;
+//!
+//! let client = ClientBuilder::aggregator("YOUR_AGGREGATOR_ENDPOINT", "YOUR_GENESIS_VERIFICATION_KEY").build()?;
+//! let certificate = client.certificate().verify_chain_from_latest().await?;
+//!
+//! println!("Latest certificate (hash: {}) is valid", certificate.hash);
+//! #    Ok(())
+//! # }
+//! ```
+//!
+//! # Monitor the participation of the signers
+//!
+//! To report which signers contributed to a verified certificate, and how far the protocol is
+//! from its quorum, using the [ClientBuilder][crate::client::ClientBuilder].
+//!
+//! ```no_run
+//! # async fn run() -> mithril_client::MithrilResult<()> {
+//! use mithril_client::ClientBuilder;
+//!
+//! let client = ClientBuilder::aggregator("YOUR_AGGREGATOR_ENDPOINT", "YOUR_GENESIS_VERIFICATION_KEY").build()?;
+//! let report = client.certificate().verify_participation("CERTIFICATE_HASH").await?;
+//!
+//! println!(
+//!     "{} signers signed {:.2}% of the stake, quorum margin: {:.1} lotteries",
+//!     report.signers_count(),
+//!     report.signed_stake_ratio() * 100.0,
+//!     report.quorum_margin()
+//! );
+//! #    Ok(())
+//! # }
+//! ```
+//!
+//! # Attest the timestamp of external data
+//!
+//! To anchor some data with a claimed timestamp to the first certificate sealed after it that
+//! signed its hash, using the [ClientBuilder][crate::client::ClientBuilder].
+//!
+//! ```no_run
+//! # async fn run() -> mithril_client::MithrilResult<()> {
+//! use mithril_client::ClientBuilder;
+//! use mithril_client::protocol_message_matcher::ProtocolMessagePartKey;
+//! use chrono::{DateTime, Utc};
+//!
+//! let client = ClientBuilder::aggregator("YOUR_AGGREGATOR_ENDPOINT", "YOUR_GENESIS_VERIFICATION_KEY").build()?;
+//! let claimed_at = DateTime::parse_from_rfc3339("2024-02-12T13:00:00Z")?.with_timezone(&Utc);
+//! let attestation = client
+//!     .certificate()
+//!     .attest_timestamp(b"SOME_DATA", claimed_at, ProtocolMessagePartKey::SnapshotDigest)
+//!     .await?;
+//!
+//! println!(
+//!     "Data (hash: {}) is anchored to certificate (hash: {}) of epoch {}",
+//!     attestation.data_hash, attestation.certificate.hash, attestation.certificate.beacon.epoch
+//! );
+//! #    Ok(())
+//! # }
+//! ```
+//!
+//! # Validate a certificate chain incrementally
+//!
+//! To validate a certificate chain a few certificates at a time, for example to interleave the
+//! validation with the rendering of an user interface without spawning threads.
+//!
+//! ```no_run
+//! # async fn run() -> mithril_client::MithrilResult<()> {
+//! use mithril_client::ClientBuilder;
+//! use mithril_client::certificate_client::{CertificateChainVerificationState, StepResult};
+//!
+//! let client = ClientBuilder::aggregator("YOUR_AGGREGATOR_ENDPOINT", "YOUR_GENESIS_VERIFICATION_KEY").build()?;
+//! let mut state = CertificateChainVerificationState::new("CERTIFICATE_HASH", 5);
+//!
+//! loop {
+//!     match client.certificate().verify_chain_step(state).await? {
+//!         StepResult::InProgress(next_state) => {
+//!             println!("{} certificates verified so far", next_state.verified_certificates);
+//!             state = next_state;
+//!         }
+//!         StepResult::Completed(final_state) => {
+//!             println!("Chain of Certificate (hash: {}) is valid", final_state.certificate_hash);
+//!             break;
+//!         }
+//!     }
+//! }
+//! #    Ok(())
+//! # }
+//! ```
+//!
+//! # Validate a certificate chain from a custom store
+//!
+//! To validate certificate chains kept in a database, an IPFS gateway or a local archive instead
+//! of fetching them from the aggregator, implement the [CertificateRetriever] trait and give it to
+//! the [ClientBuilder][crate::client::ClientBuilder].
+//!
+//! ```no_run
+//! # async fn run() -> mithril_client::MithrilResult<()> {
+//! use std::sync::Arc;
+//! use mithril_client::ClientBuilder;
+//! use mithril_client::certificate_client::{CertificateRetriever, CertificateRetrieverError};
+//! use mithril_client::common::entities::Certificate;
+//!
+//! struct ArchiveRetriever;
+//!
+//! #[async_trait::async_trait]
+//! impl CertificateRetriever for ArchiveRetriever {
+//!     async fn get_certificate_details(
+//!         &self,
+//!         certificate_hash: &str,
+//!     ) -> Result<Certificate, CertificateRetrieverError> {
+//!         todo!("read the certificate from the archive")
+//!     }
+//! }
+//!
+//! let client = ClientBuilder::aggregator("YOUR_AGGREGATOR_ENDPOINT", "YOUR_GENESIS_VERIFICATION_KEY")
+//!     .with_certificate_retriever(Arc::new(ArchiveRetriever))
+//!     .build()?;
+//! let certificate = client.certificate().verify_chain("CERTIFICATE_HASH").await?;
+//! #    Ok(())
+//! # }
+//! ```
 
 use std::sync::Arc;
 
 use anyhow::{anyhow, Context};
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures::{stream, Stream, StreamExt};
+use rand::Rng;
+use rand_chacha::ChaCha20Rng;
+use rand_core::SeedableRng;
+#[cfg(feature = "compute_threads")]
+use rayon::prelude::*;
+use serde::{Deserialize, Deserializer, Serialize};
+use sha2::{Digest, Sha256};
 use slog::{crit, debug, Logger};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use thiserror::Error;
 
 use crate::aggregator_client::{AggregatorClient, AggregatorClientError, AggregatorRequest};
-use crate::common::crypto_helper::ProtocolGenesisVerificationKey;
+use crate::common::crypto_helper::{
+    ProtocolAggregateVerificationKey, ProtocolGenesisVerificationKey,
+};
 use crate::common::{
     certificate_chain::{
-        CertificateRetriever, CertificateRetrieverError,
-        CertificateVerifier as CommonCertificateVerifier,
-        MithrilCertificateVerifier as CommonMithrilCertificateVerifier,
+        CertificateVerifier as CommonCertificateVerifier, CertificateVerifierError,
+        MithrilCertificateVerifier as CommonMithrilCertificateVerifier, MultiSignaturesBatchError,
     },
-    entities::Certificate,
+    entities::{Certificate, Epoch, ProtocolMessagePartKey, Stake, StakeDistributionParty},
     messages::CertificateMessage,
 };
+use crate::compute::ComputeExecutor;
 use crate::feedback::{FeedbackSender, MithrilEvent};
+use crate::metrics::MetricsRecorder;
+use crate::proof_bundle::{verify_bundle, ProofBundle};
+use crate::protocol_message_matcher::ProtocolMessageMatcher;
+use crate::utils::watch_list;
 use crate::{MithrilCertificate, MithrilCertificateListItem, MithrilResult};
 
+pub use crate::common::certificate_chain::{CertificateRetriever, CertificateRetrieverError};
+
 #[cfg(test)]
 use mockall::automock;
 
@@ -83,6 +285,7 @@ pub struct CertificateClient {
     aggregator_client: Arc<dyn AggregatorClient>,
     retriever: Arc<InternalCertificateRetriever>,
     verifier: Arc<dyn CertificateVerifier>,
+    genesis_verification_key: Option<String>,
 }
 
 /// API that defines how to validate certificates.
@@ -92,6 +295,314 @@ pub struct CertificateClient {
 pub trait CertificateVerifier: Sync + Send {
     /// Validate the chain starting with the given certificate.
     async fn verify_chain(&self, certificate: &MithrilCertificate) -> MithrilResult<()>;
+
+    /// Validate the chain starting with the given certificate, like
+    /// [verify_chain][Self::verify_chain], and return the verified certificates of the chain,
+    /// starting with the given certificate.
+    ///
+    /// The default implementation only returns the given certificate, as it can't retrieve the
+    /// certificates of the chain.
+    async fn verify_chain_certificates(
+        &self,
+        certificate: &MithrilCertificate,
+    ) -> MithrilResult<Vec<MithrilCertificate>> {
+        self.verify_chain(certificate).await?;
+
+        Ok(vec![certificate.clone()])
+    }
+
+    /// Validate at most [max_certificates_per_step][CertificateChainVerificationState::max_certificates_per_step]
+    /// certificates of a chain, resuming from the given state.
+    ///
+    /// The default implementation fails as it can't retrieve the certificates of the chain.
+    async fn verify_chain_step(
+        &self,
+        state: CertificateChainVerificationState,
+    ) -> MithrilResult<StepResult> {
+        Err(anyhow!(
+            "Step by step verification is not supported by this certificate verifier, could not \
+            verify certificate '{}'",
+            state.next_certificate_hash.unwrap_or_default()
+        ))
+    }
+}
+
+/// A page of the list of certificates, see [CertificateClient::list_page].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CertificateListPage {
+    /// Maximum number of certificates of the page, at least one
+    pub limit: u64,
+
+    /// Hash of the certificate preceding the page, `None` for the first page
+    pub before: Option<String>,
+}
+
+impl CertificateListPage {
+    /// The first page, with the latest certificates.
+    ///
+    /// Note: a `limit` of zero is treated as one.
+    pub fn first(limit: u64) -> Self {
+        Self {
+            limit: limit.max(1),
+            before: None,
+        }
+    }
+}
+
+/// The certificates of a [CertificateListPage].
+#[derive(Debug, Clone, PartialEq)]
+pub struct CertificatePage {
+    /// The certificates of the page, latest first
+    pub certificates: Vec<MithrilCertificateListItem>,
+
+    /// The following page, `None` if this page is the last one
+    pub next_page: Option<CertificateListPage>,
+}
+
+/// Current version of the serialization schema of [CertificateChainVerificationState].
+pub const CERTIFICATE_CHAIN_VERIFICATION_STATE_SCHEMA_VERSION: u32 = 1;
+
+/// Resumable state of an incremental certificate chain verification.
+///
+/// See [CertificateClient::verify_chain_step].
+///
+/// The state can be serialized, for example to persist the verification progress across
+/// restarts of an application. Deserializing a state with a schema version newer than
+/// [CERTIFICATE_CHAIN_VERIFICATION_STATE_SCHEMA_VERSION] fails.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CertificateChainVerificationState {
+    /// Version of the serialization schema of the state
+    #[serde(deserialize_with = "deserialize_schema_version")]
+    pub schema_version: u32,
+
+    /// Hash of the certificate the chain verification started from
+    pub certificate_hash: String,
+
+    /// Identifier of the chain verification used in the feedback events
+    pub certificate_chain_validation_id: String,
+
+    /// Hash of the next certificate to verify, `None` when the whole chain has been verified
+    pub next_certificate_hash: Option<String>,
+
+    /// Number of certificates verified so far
+    pub verified_certificates: u64,
+
+    /// Maximum number of certificates verified by a single step
+    pub max_certificates_per_step: u64,
+
+    /// Sampling of the signers metadata checks used by the verification, `None` if the metadata
+    /// of the signers are not checked
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signer_sampling: Option<SignerSampling>,
+
+    /// Number of signers which metadata were checked so far
+    #[serde(default)]
+    pub checked_signers: u64,
+}
+
+impl CertificateChainVerificationState {
+    /// Constructs a new `CertificateChainVerificationState` starting the verification at the
+    /// certificate with the given `certificate_hash`.
+    ///
+    /// Note: a `max_certificates_per_step` of zero is treated as one.
+    pub fn new(certificate_hash: &str, max_certificates_per_step: u64) -> Self {
+        Self {
+            schema_version: CERTIFICATE_CHAIN_VERIFICATION_STATE_SCHEMA_VERSION,
+            certificate_hash: certificate_hash.to_string(),
+            certificate_chain_validation_id: MithrilEvent::new_certificate_chain_validation_id(),
+            next_certificate_hash: Some(certificate_hash.to_string()),
+            verified_certificates: 0,
+            max_certificates_per_step: max_certificates_per_step.max(1),
+            signer_sampling: None,
+            checked_signers: 0,
+        }
+    }
+
+    /// Returns `true` if the whole chain has been verified.
+    pub fn is_completed(&self) -> bool {
+        self.next_certificate_hash.is_none()
+    }
+}
+
+fn deserialize_schema_version<'de, D>(deserializer: D) -> Result<u32, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let schema_version = u32::deserialize(deserializer)?;
+    if schema_version > CERTIFICATE_CHAIN_VERIFICATION_STATE_SCHEMA_VERSION {
+        return Err(serde::de::Error::custom(format!(
+            "unsupported certificate chain verification state schema version: {schema_version}, \
+            latest supported: {CERTIFICATE_CHAIN_VERIFICATION_STATE_SCHEMA_VERSION}"
+        )));
+    }
+
+    Ok(schema_version)
+}
+
+/// Opt-in consistency checks of the signers metadata of the verified certificates, done on a
+/// random sample of the signers of each certificate.
+///
+/// The multi-signature of every certificate is always fully verified, the signers metadata
+/// consistency checks (non empty and unique party id, non zero stake) are only done if a sampling
+/// is set, use a percentage of 100 to check every signer.
+///
+/// The signers of a certificate are picked using a random generator seeded with the
+/// [seed][SignerSampling::seed] and the certificate hash, so a verification can be reproduced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignerSampling {
+    /// Percentage, from 1 to 100, of the signers of each certificate which metadata are checked
+    pub percentage: u8,
+
+    /// Seed of the random generator used to pick the checked signers
+    pub seed: u64,
+}
+
+impl SignerSampling {
+    /// Constructs a new `SignerSampling`.
+    ///
+    /// Note: the percentage is clamped between 1 and 100.
+    pub fn new(percentage: u8, seed: u64) -> Self {
+        Self {
+            percentage: percentage.clamp(1, 100),
+            seed,
+        }
+    }
+
+    fn rng_for(&self, certificate_hash: &str) -> ChaCha20Rng {
+        let mut hasher = Sha256::new();
+        hasher.update(self.seed.to_be_bytes());
+        hasher.update(certificate_hash.as_bytes());
+
+        ChaCha20Rng::from_seed(hasher.finalize().into())
+    }
+}
+
+/// Outcome of a [step][CertificateClient::verify_chain_step] of an incremental certificate chain
+/// verification.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StepResult {
+    /// Some certificates of the chain remain to be verified, the state must be given to the next
+    /// step to resume the verification.
+    InProgress(CertificateChainVerificationState),
+
+    /// The whole chain has been verified.
+    Completed(CertificateChainVerificationState),
+}
+
+/// Attestation bundle anchoring some data with a claimed timestamp to the certificate chain.
+///
+/// See [CertificateClient::attest_timestamp].
+///
+/// The bundle binds the hash of the data to the first certificate sealed at or after the claimed
+/// timestamp that signed this hash in one of the parts of its protocol message, so the data
+/// "existed before this certificate". The whole chain of certificates down to the genesis
+/// certificate is included so the bundle can be verified again without an aggregator.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimestampAttestation {
+    /// Hex encoded SHA256 hash of the attested data
+    pub data_hash: String,
+
+    /// Timestamp claimed for the attested data
+    pub claimed_at: DateTime<Utc>,
+
+    /// Part of the protocol message of the attesting certificate which value is the data hash
+    pub message_part: ProtocolMessagePartKey,
+
+    /// First certificate of the chain sealed at or after the claimed timestamp
+    pub certificate: MithrilCertificate,
+
+    /// Certificates chain of the attesting certificate, from the attesting certificate (included)
+    /// to the genesis certificate
+    pub certificate_chain: Vec<MithrilCertificate>,
+}
+
+/// Participation of the signers to a certificate.
+///
+/// See [CertificateClient::verify_participation].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CertificateParticipationReport {
+    /// Hash of the certificate
+    pub certificate_hash: String,
+
+    /// Epoch of the certificate
+    pub epoch: Epoch,
+
+    /// Parties that contributed to the multi-signature of the certificate
+    pub signers: Vec<StakeDistributionParty>,
+
+    /// Stake of the parties that contributed to the multi-signature
+    pub signed_stake: Stake,
+
+    /// Stake of all the parties registered for the epoch of the certificate
+    pub total_stake: Stake,
+
+    /// Number of lotteries that must be won to reach the quorum (`k` protocol parameter)
+    pub quorum: u64,
+
+    /// Expected number of distinct lotteries won by the signers given their stake, out of the
+    /// `m` lotteries of the protocol
+    pub expected_won_lotteries: f64,
+}
+
+impl CertificateParticipationReport {
+    /// Compute the participation report of the given certificate.
+    ///
+    /// The certificate is not verified: use [CertificateClient::verify_participation] to get the
+    /// report of a verified certificate.
+    pub fn from_certificate(certificate: &MithrilCertificate) -> MithrilResult<Self> {
+        if !certificate.genesis_signature.is_empty() {
+            return Err(anyhow!(
+                "Certificate '{}' is a genesis certificate: it has no signers",
+                certificate.hash
+            ));
+        }
+        let aggregate_verification_key = ProtocolAggregateVerificationKey::from_json_hex(
+            &certificate.aggregate_verification_key,
+        )
+        .with_context(|| {
+            format!(
+                "Invalid aggregate verification key in certificate '{}'",
+                certificate.hash
+            )
+        })?;
+        let total_stake = aggregate_verification_key.total_stake();
+        let signed_stake = certificate
+            .metadata
+            .signers
+            .iter()
+            .map(|signer| signer.stake)
+            .sum();
+        let protocol_parameters = &certificate.metadata.protocol_parameters;
+        let signed_stake_ratio = signed_stake as f64 / total_stake as f64;
+        let expected_won_lotteries = protocol_parameters.m as f64
+            * (1.0 - (1.0 - protocol_parameters.phi_f).powf(signed_stake_ratio));
+
+        Ok(Self {
+            certificate_hash: certificate.hash.clone(),
+            epoch: certificate.beacon.epoch,
+            signers: certificate.metadata.signers.clone(),
+            signed_stake,
+            total_stake,
+            quorum: protocol_parameters.k,
+            expected_won_lotteries,
+        })
+    }
+
+    /// Number of parties that contributed to the multi-signature
+    pub fn signers_count(&self) -> usize {
+        self.signers.len()
+    }
+
+    /// Ratio of the total stake that contributed to the multi-signature
+    pub fn signed_stake_ratio(&self) -> f64 {
+        self.signed_stake as f64 / self.total_stake as f64
+    }
+
+    /// Margin between the expected number of won lotteries and the quorum: the lower it is, the
+    /// more likely a drop in participation prevents the next certificates from being issued.
+    pub fn quorum_margin(&self) -> f64 {
+        self.expected_won_lotteries - self.quorum as f64
+    }
 }
 
 impl CertificateClient {
@@ -101,18 +612,40 @@ impl CertificateClient {
         verifier: Arc<dyn CertificateVerifier>,
         logger: Logger,
     ) -> Self {
-        let retriever = Arc::new(InternalCertificateRetriever {
-            aggregator_client: aggregator_client.clone(),
+        let retriever = Arc::new(InternalCertificateRetriever::new(
+            aggregator_client.clone(),
             logger,
-        });
+        ));
 
         Self {
             aggregator_client,
             retriever,
             verifier,
+            genesis_verification_key: None,
         }
     }
 
+    /// Retrieve the certificates to [get][CertificateClient::get] and to verify from the given
+    /// [CertificateRetriever] instead of the aggregator, the certificates are still listed from
+    /// the aggregator.
+    ///
+    /// The retriever can't tell a missing certificate apart from a failure: getting a certificate
+    /// it doesn't have fails instead of returning `None`.
+    pub fn with_certificate_retriever(
+        mut self,
+        certificate_retriever: Arc<dyn CertificateRetriever>,
+    ) -> Self {
+        self.retriever = Arc::new(self.retriever.with_custom_retriever(certificate_retriever));
+        self
+    }
+
+    /// Set the genesis verification key embedded in the exported
+    /// [proof bundles][CertificateClient::export_proof_bundle].
+    pub fn with_genesis_verification_key(mut self, genesis_verification_key: &str) -> Self {
+        self.genesis_verification_key = Some(genesis_verification_key.to_string());
+        self
+    }
+
     /// Fetch a list of certificates
     pub async fn list(&self) -> MithrilResult<Vec<MithrilCertificateListItem>> {
         let response = self
@@ -126,6 +659,91 @@ impl CertificateClient {
         Ok(items)
     }
 
+    /// Fetch a page of certificates, latest first.
+    ///
+    /// The aggregators that don't support pagination send their latest certificates whatever the
+    /// page asked, the page is then cut from them client side.
+    pub async fn list_page(&self, page: &CertificateListPage) -> MithrilResult<CertificatePage> {
+        let response = self
+            .aggregator_client
+            .get_content(AggregatorRequest::ListCertificatesPage {
+                limit: page.limit,
+                before: page.before.clone(),
+            })
+            .await
+            .with_context(|| "CertificateClient can not get the certificate list page")?;
+        let mut certificates =
+            serde_json::from_str::<Vec<MithrilCertificateListItem>>(&response)
+                .with_context(|| "CertificateClient can not deserialize certificate list page")?;
+
+        if let Some(before) = &page.before {
+            if let Some(position) = certificates.iter().position(|c| &c.hash == before) {
+                certificates.drain(..=position);
+            }
+        }
+        certificates.truncate(page.limit as usize);
+        let next_page = match certificates.last() {
+            Some(last) if certificates.len() as u64 == page.limit => Some(CertificateListPage {
+                limit: page.limit,
+                before: Some(last.hash.clone()),
+            }),
+            _ => None,
+        };
+
+        Ok(CertificatePage {
+            certificates,
+            next_page,
+        })
+    }
+
+    /// Stream all the certificates of the aggregator, latest first, fetching them by pages of
+    /// `page_size` certificates.
+    ///
+    /// The stream ends once a page brings no certificate that was not already streamed, or on
+    /// the first listing error which is streamed.
+    pub fn list_all(
+        &self,
+        page_size: u64,
+    ) -> impl Stream<Item = MithrilResult<MithrilCertificateListItem>> + '_ {
+        let state: (Option<CertificateListPage>, HashSet<String>) =
+            (Some(CertificateListPage::first(page_size)), HashSet::new());
+
+        stream::unfold(state, move |(page, mut streamed_hashes)| async move {
+            let page = page?;
+            match self.list_page(&page).await {
+                Ok(result) => {
+                    let certificates: Vec<_> = result
+                        .certificates
+                        .into_iter()
+                        .filter(|certificate| streamed_hashes.insert(certificate.hash.clone()))
+                        .map(Ok)
+                        .collect();
+                    let next_page = result.next_page.filter(|_| !certificates.is_empty());
+
+                    Some((certificates, (next_page, streamed_hashes)))
+                }
+                Err(error) => Some((vec![Err(error)], (None, streamed_hashes))),
+            }
+        })
+        .flat_map(stream::iter)
+    }
+
+    /// Poll the aggregator every `interval` and stream the certificates issued since the
+    /// previous poll, oldest first.
+    ///
+    /// The certificates already available when the first poll is done are not streamed. Listing
+    /// errors are streamed and the polling goes on.
+    pub fn watch(
+        &self,
+        interval: Duration,
+    ) -> impl Stream<Item = MithrilResult<MithrilCertificateListItem>> + '_ {
+        watch_list(
+            interval,
+            move || self.list(),
+            |certificate| certificate.hash.clone(),
+        )
+    }
+
     /// Get a single certificate full information from the aggregator.
     pub async fn get(&self, certificate_hash: &str) -> MithrilResult<Option<MithrilCertificate>> {
         self.retriever.get(certificate_hash).await
@@ -134,42 +752,319 @@ impl CertificateClient {
     /// Validate the chain starting with the certificate with given `certificate_hash`, return the certificate if
     /// the chain is valid.
     ///
-    /// This method will fail if no certificate exists for the given `certificate_hash`.
+    /// This method will fail if no certificate exists for the given `certificate_hash`, the errors
+    /// raised by an invalid certificate carry its [CertificateVerificationDiagnostics].
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
     pub async fn verify_chain(&self, certificate_hash: &str) -> MithrilResult<MithrilCertificate> {
         let certificate = self.retriever.get(certificate_hash).await?.ok_or(anyhow!(
             "No certificate exist for hash '{certificate_hash}'"
         ))?;
+        self.verify_chain_of(&certificate).await?;
+
+        Ok(certificate)
+    }
 
+    /// Validate the chain starting with the given certificate, ie: a certificate already
+    /// retrieved with [get][CertificateClient::get].
+    pub async fn verify_chain_of(&self, certificate: &MithrilCertificate) -> MithrilResult<()> {
         self.verifier
-            .verify_chain(&certificate)
+            .verify_chain(certificate)
+            .await
+            .with_context(|| {
+                format!(
+                    "Certicate chain of certificate '{}' is invalid",
+                    certificate.hash
+                )
+            })
+    }
+
+    /// Validate the chain starting with the latest certificate issued by the aggregator, return
+    /// this certificate if the chain is valid.
+    ///
+    /// This method will fail if the aggregator has not issued any certificate.
+    pub async fn verify_chain_from_latest(&self) -> MithrilResult<MithrilCertificate> {
+        let page = self
+            .list_page(&CertificateListPage::first(1))
+            .await
+            .with_context(|| "CertificateClient can not get the latest certificate")?;
+        let latest_certificate = page
+            .certificates
+            .first()
+            .ok_or(anyhow!("No certificate issued by the aggregator"))?;
+
+        self.verify_chain(&latest_certificate.hash).await
+    }
+
+    /// Validate the chain starting with the certificate with given `certificate_hash`, return the
+    /// verified certificates of the chain, starting with this certificate, if the chain is valid.
+    ///
+    /// The verifiers that can't retrieve the certificates of the chain only return the certificate
+    /// with the given `certificate_hash`, see [CertificateVerifier::verify_chain_certificates].
+    pub async fn verify_chain_certificates(
+        &self,
+        certificate_hash: &str,
+    ) -> MithrilResult<Vec<MithrilCertificate>> {
+        let certificate = self.retriever.get(certificate_hash).await?.ok_or(anyhow!(
+            "No certificate exist for hash '{certificate_hash}'"
+        ))?;
+
+        self.verifier
+            .verify_chain_certificates(&certificate)
             .await
             .with_context(|| {
                 format!("Certicate chain of certificate '{certificate_hash}' is invalid")
-            })?;
+            })
+    }
+
+    /// Validate the chain starting with the certificate with given `certificate_hash` then check
+    /// that this certificate signed the protocol message expected by the given `matcher`, return
+    /// the certificate if both are valid.
+    pub async fn verify_chain_matching(
+        &self,
+        certificate_hash: &str,
+        matcher: &ProtocolMessageMatcher,
+    ) -> MithrilResult<MithrilCertificate> {
+        let certificate = self.verify_chain(certificate_hash).await?;
+        matcher.matches(&certificate)?;
 
         Ok(certificate)
     }
-}
 
-/// Internal type to implement the [InternalCertificateRetriever] trait and avoid a circular
-/// dependency between the [CertificateClient] and the [CommonMithrilCertificateVerifier] that need
-/// a [CertificateRetriever] as a dependency.
-struct InternalCertificateRetriever {
-    aggregator_client: Arc<dyn AggregatorClient>,
-    logger: Logger,
-}
+    /// Validate the chain starting with the certificate with given `certificate_hash` then report
+    /// the [participation][CertificateParticipationReport] of the signers to this certificate.
+    pub async fn verify_participation(
+        &self,
+        certificate_hash: &str,
+    ) -> MithrilResult<CertificateParticipationReport> {
+        let certificate = self.verify_chain(certificate_hash).await?;
 
-impl InternalCertificateRetriever {
-    async fn get(&self, certificate_hash: &str) -> MithrilResult<Option<MithrilCertificate>> {
-        let response = self
-            .aggregator_client
-            .get_content(AggregatorRequest::GetCertificate {
-                hash: certificate_hash.to_string(),
-            })
-            .await;
+        CertificateParticipationReport::from_certificate(&certificate)
+    }
 
-        match response {
-            Err(AggregatorClientError::RemoteServerLogical(_)) => Ok(None),
+    /// Build a [TimestampAttestation] that anchors the given `data` with its `claimed_at` timestamp
+    /// to the earliest certificate sealed at or after this timestamp which signed the hash of the
+    /// data in the given `message_part`.
+    ///
+    /// The chain of the latest certificate listed by the aggregator is verified, then the
+    /// attesting certificate is searched among the verified certificates.
+    ///
+    /// This method will fail if no certificate sealed after the claimed timestamp signed the hash
+    /// of the data.
+    pub async fn attest_timestamp(
+        &self,
+        data: &[u8],
+        claimed_at: DateTime<Utc>,
+        message_part: ProtocolMessagePartKey,
+    ) -> MithrilResult<TimestampAttestation> {
+        let latest_certificate = self
+            .list()
+            .await?
+            .into_iter()
+            .max_by_key(|certificate| certificate.metadata.sealed_at)
+            .filter(|certificate| certificate.metadata.sealed_at >= claimed_at)
+            .ok_or(anyhow!(
+                "No certificate sealed at or after the claimed timestamp '{claimed_at}'"
+            ))?;
+        let data_hash = hex::encode(Sha256::digest(data));
+
+        let mut chain = self
+            .verify_chain_certificates(&latest_certificate.hash)
+            .await?;
+        let attesting_index = chain
+            .iter()
+            .rposition(|certificate| {
+                certificate.metadata.sealed_at >= claimed_at
+                    && certificate.match_message(&certificate.protocol_message)
+                    && certificate.protocol_message.get_message_part(&message_part)
+                        == Some(&data_hash)
+            })
+            .ok_or(anyhow!(
+                "No certificate sealed at or after the claimed timestamp '{claimed_at}' signed \
+                the data hash '{data_hash}' as its '{message_part}' message part"
+            ))?;
+        let certificate_chain = chain.split_off(attesting_index);
+
+        Ok(TimestampAttestation {
+            data_hash,
+            claimed_at,
+            message_part,
+            certificate: certificate_chain[0].clone(),
+            certificate_chain,
+        })
+    }
+
+    /// Export the chain starting with the certificate with the given `certificate_hash` as a
+    /// [ProofBundle] that can be verified without an aggregator.
+    ///
+    /// The whole chain is retrieved then the bundle is verified with the genesis verification
+    /// key of this client, so an exported bundle is always valid.
+    ///
+    /// This method will fail if no genesis verification key is set or if no certificate exists
+    /// for the given `certificate_hash`.
+    pub async fn export_proof_bundle(&self, certificate_hash: &str) -> MithrilResult<ProofBundle> {
+        let genesis_verification_key = self.genesis_verification_key.as_ref().ok_or(anyhow!(
+            "No genesis verification key set, it's required to export a proof bundle"
+        ))?;
+        let certificate_chain = self.retrieve_chain(certificate_hash).await?;
+        let bundle = ProofBundle::new(genesis_verification_key, certificate_chain);
+
+        verify_bundle(&bundle, genesis_verification_key).await?;
+
+        Ok(bundle)
+    }
+
+    /// Retrieve the chain of certificates starting with the given certificate and ending with
+    /// the genesis certificate.
+    async fn retrieve_chain(
+        &self,
+        certificate_hash: &str,
+    ) -> MithrilResult<Vec<MithrilCertificate>> {
+        let mut chain = vec![];
+        let mut next_hash = Some(certificate_hash.to_string());
+
+        while let Some(hash) = next_hash {
+            let certificate = self
+                .retriever
+                .get(&hash)
+                .await?
+                .ok_or(anyhow!("No certificate exist for hash '{hash}'"))?;
+            next_hash = if certificate.genesis_signature.is_empty()
+                && !certificate.previous_hash.is_empty()
+            {
+                Some(certificate.previous_hash.clone())
+            } else {
+                None
+            };
+            chain.push(certificate);
+        }
+
+        Ok(chain)
+    }
+
+    /// Validate at most [max_certificates_per_step][CertificateChainVerificationState::max_certificates_per_step]
+    /// certificates of a chain, resuming from the given `state`.
+    ///
+    /// Calling this method repeatedly with the state returned in [StepResult::InProgress] allows
+    /// to verify a whole certificate chain while keeping control between each step.
+    pub async fn verify_chain_step(
+        &self,
+        state: CertificateChainVerificationState,
+    ) -> MithrilResult<StepResult> {
+        let certificate_hash = state.certificate_hash.clone();
+
+        self.verifier
+            .verify_chain_step(state)
+            .await
+            .with_context(|| {
+                format!("Certicate chain of certificate '{certificate_hash}' is invalid")
+            })
+    }
+}
+
+/// Internal type to implement the [InternalCertificateRetriever] trait and avoid a circular
+/// dependency between the [CertificateClient] and the [CommonMithrilCertificateVerifier] that need
+/// a [CertificateRetriever] as a dependency.
+///
+/// If a custom [CertificateRetriever] is set the certificates are retrieved from it instead of
+/// the aggregator.
+struct InternalCertificateRetriever {
+    aggregator_client: Arc<dyn AggregatorClient>,
+    custom_retriever: Option<Arc<dyn CertificateRetriever>>,
+    prefetched_certificates: HashMap<String, MithrilCertificate>,
+    chain_bundle_unsupported: AtomicBool,
+    logger: Logger,
+}
+
+impl InternalCertificateRetriever {
+    fn new(aggregator_client: Arc<dyn AggregatorClient>, logger: Logger) -> Self {
+        Self {
+            aggregator_client,
+            custom_retriever: None,
+            prefetched_certificates: HashMap::new(),
+            chain_bundle_unsupported: AtomicBool::new(false),
+            logger,
+        }
+    }
+
+    /// A new retriever, with nothing prefetched, retrieving the certificates from the given
+    /// [CertificateRetriever].
+    fn with_custom_retriever(&self, custom_retriever: Arc<dyn CertificateRetriever>) -> Self {
+        Self {
+            custom_retriever: Some(custom_retriever),
+            ..Self::new(self.aggregator_client.clone(), self.logger.clone())
+        }
+    }
+
+    /// Fetch the certificate with the given hash and all its previous certificates in a single
+    /// request, returns a new retriever serving them from memory, to use for the verification
+    /// of this chain only.
+    ///
+    /// Returns `None` without prefetching anything if the aggregator doesn't serve certificate
+    /// chains bundles, the certificates being then fetched one by one. The aggregator is not
+    /// asked for a bundle again once it answered that it doesn't serve them.
+    async fn prefetch_chain(&self, from_hash: &str) -> MithrilResult<Option<Self>> {
+        if self.custom_retriever.is_some() || self.chain_bundle_unsupported.load(Ordering::Relaxed)
+        {
+            return Ok(None);
+        }
+
+        let response = self
+            .aggregator_client
+            .get_content(AggregatorRequest::GetCertificateChain {
+                from_hash: from_hash.to_string(),
+            })
+            .await;
+        let certificates = match response {
+            Err(AggregatorClientError::RemoteServerLogical(error)) => {
+                debug!(
+                    self.logger,
+                    "Aggregator does not serve certificate chain bundles, fetching certificates one by one";
+                    "error" => ?error
+                );
+                self.chain_bundle_unsupported.store(true, Ordering::Relaxed);
+                return Ok(None);
+            }
+            Err(e) => return Err(e.into()),
+            Ok(response) => serde_json::from_str::<Vec<CertificateMessage>>(&response)
+                .with_context(|| {
+                    format!("Could not deserialize the certificate chain bundle of '{from_hash}'")
+                })?,
+        };
+
+        Ok(Some(Self {
+            prefetched_certificates: certificates
+                .into_iter()
+                .map(|certificate| (certificate.hash.clone(), certificate))
+                .collect(),
+            ..Self::new(self.aggregator_client.clone(), self.logger.clone())
+        }))
+    }
+
+    async fn get(&self, certificate_hash: &str) -> MithrilResult<Option<MithrilCertificate>> {
+        if let Some(certificate) = self.prefetched_certificates.get(certificate_hash) {
+            return Ok(Some(certificate.clone()));
+        }
+
+        if let Some(custom_retriever) = &self.custom_retriever {
+            let certificate = custom_retriever
+                .get_certificate_details(certificate_hash)
+                .await?;
+            let message = CertificateMessage::try_from(certificate).with_context(|| {
+                format!("Could not create the message of certificate '{certificate_hash}'")
+            })?;
+
+            return Ok(Some(message));
+        }
+
+        let response = self
+            .aggregator_client
+            .get_content(AggregatorRequest::GetCertificate {
+                hash: certificate_hash.to_string(),
+            })
+            .await;
+
+        match response {
+            Err(AggregatorClientError::RemoteServerLogical(_)) => Ok(None),
             Err(e) => Err(e.into()),
             Ok(response) => {
                 let message =
@@ -188,12 +1083,294 @@ impl InternalCertificateRetriever {
     }
 }
 
+/// Error raised when a certificate is chained to a previous certificate further in the past than
+/// allowed, or more recent than itself, see
+/// [MithrilCertificateVerifier::with_max_epoch_gap].
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error(
+    "Certificate '{certificate_hash}' of epoch {certificate_epoch} is chained to certificate \
+    '{previous_certificate_hash}' of epoch {previous_certificate_epoch}, expected at most \
+    {max_epoch_gap} epochs between them"
+)]
+pub struct CertificateChainEpochGap {
+    /// Hash of the certificate
+    pub certificate_hash: String,
+
+    /// Epoch of the certificate
+    pub certificate_epoch: Epoch,
+
+    /// Hash of the previous certificate the certificate is chained to
+    pub previous_certificate_hash: String,
+
+    /// Epoch of the previous certificate
+    pub previous_certificate_epoch: Epoch,
+
+    /// Maximum number of epochs allowed between two chained certificates
+    pub max_epoch_gap: u64,
+}
+
+/// Error raised when the aggregate verification key of a certificate is not the one announced
+/// by the certificate of the previous epoch, see [check_next_aggregate_verification_key].
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum AggregateVerificationKeyContinuityError {
+    /// The certificates are not on two consecutive epochs
+    #[error(
+        "Certificate '{next_certificate_hash}' of epoch {next_epoch} is not on the epoch \
+        following certificate '{certificate_hash}' of epoch {epoch}"
+    )]
+    NonConsecutiveEpochs {
+        /// Hash of the certificate of the epoch N
+        certificate_hash: String,
+
+        /// Epoch of the certificate of the epoch N
+        epoch: Epoch,
+
+        /// Hash of the certificate of the epoch N+1
+        next_certificate_hash: String,
+
+        /// Epoch of the certificate of the epoch N+1
+        next_epoch: Epoch,
+    },
+
+    /// The protocol message of the certificate of the epoch N is not the one it signed
+    #[error("The protocol message of certificate '{certificate_hash}' is not the one it signed")]
+    UnsignedProtocolMessage {
+        /// Hash of the certificate of the epoch N
+        certificate_hash: String,
+    },
+
+    /// The certificate of the epoch N did not sign a next aggregate verification key
+    #[error("Certificate '{certificate_hash}' did not sign a next aggregate verification key")]
+    MissingNextAggregateVerificationKey {
+        /// Hash of the certificate of the epoch N
+        certificate_hash: String,
+    },
+
+    /// The aggregate verification key of the certificate of the epoch N+1 is not the next one
+    /// signed by the certificate of the epoch N
+    #[error(
+        "Certificate '{next_certificate_hash}' aggregate verification key is not the next \
+        aggregate verification key signed by certificate '{certificate_hash}'"
+    )]
+    AggregateVerificationKeyMismatch {
+        /// Hash of the certificate of the epoch N
+        certificate_hash: String,
+
+        /// Hash of the certificate of the epoch N+1
+        next_certificate_hash: String,
+    },
+}
+
+/// Check that a certificate of an epoch N+1 is signed with the next aggregate verification key
+/// signed by a certificate of the epoch N, without traversing their certificate chain.
+///
+/// It's the check done by the chain verification when it crosses an epoch boundary, usable on
+/// its own to monitor the certificates issued by an aggregator. The multi-signatures of the
+/// certificates are not verified.
+pub fn check_next_aggregate_verification_key(
+    certificate: &MithrilCertificate,
+    next_certificate: &MithrilCertificate,
+) -> Result<(), AggregateVerificationKeyContinuityError> {
+    let epoch = certificate.beacon.epoch;
+    let next_epoch = next_certificate.beacon.epoch;
+    if epoch.0.checked_add(1) != Some(next_epoch.0) {
+        return Err(
+            AggregateVerificationKeyContinuityError::NonConsecutiveEpochs {
+                certificate_hash: certificate.hash.clone(),
+                epoch,
+                next_certificate_hash: next_certificate.hash.clone(),
+                next_epoch,
+            },
+        );
+    }
+
+    if !certificate.match_message(&certificate.protocol_message) {
+        return Err(
+            AggregateVerificationKeyContinuityError::UnsignedProtocolMessage {
+                certificate_hash: certificate.hash.clone(),
+            },
+        );
+    }
+
+    match certificate
+        .protocol_message
+        .get_message_part(&ProtocolMessagePartKey::NextAggregateVerificationKey)
+    {
+        Some(next_aggregate_verification_key)
+            if next_aggregate_verification_key == &next_certificate.aggregate_verification_key =>
+        {
+            Ok(())
+        }
+        Some(_) => Err(
+            AggregateVerificationKeyContinuityError::AggregateVerificationKeyMismatch {
+                certificate_hash: certificate.hash.clone(),
+                next_certificate_hash: next_certificate.hash.clone(),
+            },
+        ),
+        None => Err(
+            AggregateVerificationKeyContinuityError::MissingNextAggregateVerificationKey {
+                certificate_hash: certificate.hash.clone(),
+            },
+        ),
+    }
+}
+
+/// Check of a certificate verification, see [CertificateVerificationDiagnostics].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CertificateVerificationCheck {
+    /// The hash of the certificate doesn't match its content
+    CertificateHash,
+
+    /// The certificate is chained to itself
+    InfiniteLoop,
+
+    /// The genesis signature of the certificate is invalid
+    GenesisSignature,
+
+    /// The multi-signature of the certificate is invalid
+    MultiSignature,
+
+    /// The previous certificate could not be retrieved
+    PreviousCertificateRetrieval,
+
+    /// The hash of the previous certificate doesn't match the `previous_hash` of the certificate
+    PreviousHash,
+
+    /// The aggregate verification key of the certificate is not the one registered in its
+    /// previous certificate
+    AggregateVerificationKey,
+
+    /// The certificate is chained to a certificate too far in the past, see
+    /// [CertificateChainEpochGap]
+    EpochGap,
+
+    /// The metadata of the signers of the certificate are inconsistent
+    SignersMetadata,
+
+    /// Any other failure
+    Other,
+}
+
+impl CertificateVerificationCheck {
+    /// Find the check that failed from an error raised by the internal verifier.
+    fn of_verifier_error(error: &anyhow::Error) -> Self {
+        let mut errors = error.chain();
+        if errors
+            .clone()
+            .any(|e| e.downcast_ref::<CertificateRetrieverError>().is_some())
+        {
+            return Self::PreviousCertificateRetrieval;
+        }
+
+        match errors.find_map(|e| e.downcast_ref::<CertificateVerifierError>()) {
+            Some(CertificateVerifierError::VerifyMultiSignature(_)) => Self::MultiSignature,
+            Some(
+                CertificateVerifierError::CertificateGenesis(_)
+                | CertificateVerifierError::InvalidGenesisCertificateProvided,
+            ) => Self::GenesisSignature,
+            Some(CertificateVerifierError::CertificateHashUnmatch) => Self::CertificateHash,
+            Some(CertificateVerifierError::CertificateChainPreviousHashUnmatch) => {
+                Self::PreviousHash
+            }
+            Some(CertificateVerifierError::CertificateChainAVKUnmatch) => {
+                Self::AggregateVerificationKey
+            }
+            Some(CertificateVerifierError::CertificateChainInfiniteLoop) => Self::InfiniteLoop,
+            None => Self::Other,
+        }
+    }
+}
+
+/// Machine readable description of a failed certificate verification.
+///
+/// It is attached to the errors raised by the verification of a certificate chain, and can be
+/// retrieved with a downcast:
+///
+/// ```no_run
+/// # async fn run() -> mithril_client::MithrilResult<()> {
+/// use mithril_client::certificate_client::CertificateVerificationDiagnostics;
+/// use mithril_client::ClientBuilder;
+///
+/// let client = ClientBuilder::aggregator("YOUR_AGGREGATOR_ENDPOINT", "YOUR_GENESIS_VERIFICATION_KEY").build()?;
+///
+/// if let Err(error) = client.certificate().verify_chain("CERTIFICATE_HASH").await {
+///     if let Some(diagnostics) = error.downcast_ref::<CertificateVerificationDiagnostics>() {
+///         println!("{}", serde_json::to_string(diagnostics)?);
+///     }
+/// }
+/// #    Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Error)]
+#[error(
+    "Verification of certificate '{certificate_hash}' of epoch {epoch} failed: {failed_check:?}"
+)]
+pub struct CertificateVerificationDiagnostics {
+    /// Hash of the certificate
+    pub certificate_hash: String,
+
+    /// Epoch of the certificate
+    pub epoch: Epoch,
+
+    /// Check that failed
+    pub failed_check: CertificateVerificationCheck,
+
+    /// Hash of the previous certificate the certificate is chained to
+    pub previous_certificate_hash: String,
+
+    /// Hex encoded aggregate verification key of the certificate
+    pub aggregate_verification_key: Option<String>,
+
+    /// Hex encoded aggregate verification key of the previous certificate, only set when the
+    /// [AggregateVerificationKey][CertificateVerificationCheck::AggregateVerificationKey] check
+    /// failed
+    pub previous_aggregate_verification_key: Option<String>,
+
+    /// Hex encoded next aggregate verification key registered in the previous certificate, only
+    /// set when the
+    /// [AggregateVerificationKey][CertificateVerificationCheck::AggregateVerificationKey] check
+    /// failed
+    pub previous_next_aggregate_verification_key: Option<String>,
+}
+
+impl CertificateVerificationDiagnostics {
+    fn new(
+        certificate: &Certificate,
+        failed_check: CertificateVerificationCheck,
+        previous_certificate: Option<&Certificate>,
+    ) -> Self {
+        Self {
+            certificate_hash: certificate.hash.clone(),
+            epoch: certificate.beacon.epoch,
+            failed_check,
+            previous_certificate_hash: certificate.previous_hash.clone(),
+            aggregate_verification_key: certificate.aggregate_verification_key.to_json_hex().ok(),
+            previous_aggregate_verification_key: previous_certificate
+                .and_then(|previous| previous.aggregate_verification_key.to_json_hex().ok()),
+            previous_next_aggregate_verification_key: previous_certificate.and_then(|previous| {
+                previous
+                    .protocol_message
+                    .get_message_part(&ProtocolMessagePartKey::NextAggregateVerificationKey)
+                    .cloned()
+            }),
+        }
+    }
+}
+
 /// Implementation of a [CertificateVerifier] that can send feedbacks using
 /// the [feedback][crate::feedback] mechanism.
 pub struct MithrilCertificateVerifier {
+    retriever: Arc<InternalCertificateRetriever>,
     internal_verifier: Arc<dyn CommonCertificateVerifier>,
     genesis_verification_key: ProtocolGenesisVerificationKey,
     feedback_sender: FeedbackSender,
+    signer_sampling: Option<SignerSampling>,
+    max_epoch_gap: Option<u64>,
+    parallel_signature_verification: bool,
+    certificate_chain_bundle: bool,
+    metrics_recorder: Option<Arc<dyn MetricsRecorder>>,
+    compute_executor: ComputeExecutor,
 }
 
 impl MithrilCertificateVerifier {
@@ -204,10 +1381,10 @@ impl MithrilCertificateVerifier {
         feedback_sender: FeedbackSender,
         logger: Logger,
     ) -> MithrilResult<MithrilCertificateVerifier> {
-        let retriever = Arc::new(InternalCertificateRetriever {
-            aggregator_client: aggregator_client.clone(),
-            logger: logger.clone(),
-        });
+        let retriever = Arc::new(InternalCertificateRetriever::new(
+            aggregator_client.clone(),
+            logger.clone(),
+        ));
         let internal_verifier = Arc::new(CommonMithrilCertificateVerifier::new(
             logger,
             retriever.clone(),
@@ -217,17 +1394,470 @@ impl MithrilCertificateVerifier {
                 .with_context(|| "Invalid genesis verification key")?;
 
         Ok(Self {
+            retriever,
             internal_verifier,
             genesis_verification_key,
             feedback_sender,
+            signer_sampling: None,
+            max_epoch_gap: None,
+            parallel_signature_verification: false,
+            certificate_chain_bundle: false,
+            metrics_recorder: None,
+            compute_executor: ComputeExecutor::default(),
         })
     }
+
+    /// Retrieve the certificates of the chains to verify from the given [CertificateRetriever]
+    /// instead of the aggregator, ie: to verify chains kept in a database or a local archive.
+    ///
+    /// Note: the chains are not fetched as [bundles][Self::with_certificate_chain_bundle] from a
+    /// custom retriever.
+    pub fn with_certificate_retriever(
+        mut self,
+        certificate_retriever: Arc<dyn CertificateRetriever>,
+    ) -> Self {
+        self.retriever = Arc::new(self.retriever.with_custom_retriever(certificate_retriever));
+        self.internal_verifier = Arc::new(CommonMithrilCertificateVerifier::new(
+            self.retriever.logger.clone(),
+            self.retriever.clone(),
+        ));
+        self
+    }
+
+    /// A copy of this verifier retrieving the certificates from the given retriever.
+    fn with_internal_retriever(&self, retriever: Arc<InternalCertificateRetriever>) -> Self {
+        Self {
+            internal_verifier: Arc::new(CommonMithrilCertificateVerifier::new(
+                retriever.logger.clone(),
+                retriever.clone(),
+            )),
+            retriever,
+            genesis_verification_key: self.genesis_verification_key,
+            feedback_sender: self.feedback_sender.clone(),
+            signer_sampling: self.signer_sampling,
+            max_epoch_gap: self.max_epoch_gap,
+            parallel_signature_verification: self.parallel_signature_verification,
+            certificate_chain_bundle: self.certificate_chain_bundle,
+            metrics_recorder: self.metrics_recorder.clone(),
+            compute_executor: self.compute_executor.clone(),
+        }
+    }
+
+    /// Set the [ComputeExecutor] that will run the verification of the multi-signatures.
+    pub fn with_compute_executor(mut self, compute_executor: ComputeExecutor) -> Self {
+        self.compute_executor = compute_executor;
+        self
+    }
+
+    /// Set the [MetricsRecorder] that will count the verified certificates.
+    pub fn with_metrics_recorder(mut self, metrics_recorder: Arc<dyn MetricsRecorder>) -> Self {
+        self.metrics_recorder = Some(metrics_recorder);
+        self
+    }
+
+    /// Check the metadata of a random sample of the signers of each certificate, see
+    /// [SignerSampling], those checks are not done by default.
+    ///
+    /// The sampling used is recorded in the [CertificateChainVerificationState] of incremental
+    /// verifications.
+    pub fn with_signer_sampling(mut self, signer_sampling: SignerSampling) -> Self {
+        self.signer_sampling = Some(signer_sampling);
+        self
+    }
+
+    /// Fail the verification of a chain if a certificate is chained to a previous certificate
+    /// more than `max_epoch_gap` epochs in the past, ie: `1` requires a certificate for every
+    /// epoch of the chain.
+    ///
+    /// The error raised can be downcast to a [CertificateChainEpochGap].
+    pub fn with_max_epoch_gap(mut self, max_epoch_gap: u64) -> Self {
+        self.max_epoch_gap = Some(max_epoch_gap);
+        self
+    }
+
+    /// Verify the chains in two phases: the whole chain is first walked to check the hash links,
+    /// the aggregate verification keys and the signers metadata of its certificates, then the
+    /// multi-signatures of its epochs are verified in parallel.
+    ///
+    /// The multi-signatures run on the threads of the [ComputeExecutor], or on the global
+    /// `rayon` thread pool if the executor runs the computations inline. Without the crate
+    /// feature **compute_threads** they are verified one epoch after the other. The whole chain
+    /// is kept in memory until its multi-signatures are verified.
+    ///
+    /// Note: only [verify_chain][CertificateVerifier::verify_chain] verifies in two phases, the
+    /// [steps][CertificateVerifier::verify_chain_step] of an incremental verification are too
+    /// short to benefit from it.
+    pub fn with_parallel_signature_verification(mut self, enabled: bool) -> Self {
+        self.parallel_signature_verification = enabled;
+        self
+    }
+
+    /// Fetch the whole chain to verify in a single request to the aggregator, instead of one
+    /// request per certificate.
+    ///
+    /// The certificates of the chain are verified the same way whatever the way they were
+    /// fetched. If the aggregator doesn't serve certificate chain bundles the certificates are
+    /// fetched one by one.
+    ///
+    /// Note: only [verify_chain][CertificateVerifier::verify_chain] fetches the chain in a single
+    /// request.
+    pub fn with_certificate_chain_bundle(mut self, enabled: bool) -> Self {
+        self.certificate_chain_bundle = enabled;
+        self
+    }
+
+    /// Check that the given certificate and its previous certificate are at most
+    /// [max_epoch_gap][Self::with_max_epoch_gap] epochs apart.
+    fn check_epoch_continuity(
+        &self,
+        certificate: &Certificate,
+        previous_certificate: Option<&Certificate>,
+    ) -> Result<(), CertificateChainEpochGap> {
+        let (Some(max_epoch_gap), Some(previous_certificate)) =
+            (self.max_epoch_gap, previous_certificate)
+        else {
+            return Ok(());
+        };
+
+        let certificate_epoch = certificate.beacon.epoch;
+        let previous_certificate_epoch = previous_certificate.beacon.epoch;
+        match certificate_epoch
+            .0
+            .checked_sub(previous_certificate_epoch.0)
+        {
+            Some(epoch_gap) if epoch_gap <= max_epoch_gap => Ok(()),
+            _ => Err(CertificateChainEpochGap {
+                certificate_hash: certificate.hash.clone(),
+                certificate_epoch,
+                previous_certificate_hash: previous_certificate.hash.clone(),
+                previous_certificate_epoch,
+                max_epoch_gap,
+            }),
+        }
+    }
+
+    /// Check the metadata of a sample of the signers of the given certificate, returns the number
+    /// of checked signers.
+    fn check_signers_metadata(
+        certificate: &Certificate,
+        signer_sampling: &SignerSampling,
+    ) -> MithrilResult<u64> {
+        let signers = &certificate.metadata.signers;
+        let mut rng = signer_sampling.rng_for(&certificate.hash);
+        let mut party_ids = HashSet::with_capacity(signers.len());
+        let mut checked_signers = 0;
+
+        for signer in signers {
+            if !party_ids.insert(signer.party_id.as_str()) {
+                return Err(anyhow!(
+                    "Certificate '{}' lists the signer '{}' more than once",
+                    certificate.hash,
+                    signer.party_id
+                ));
+            }
+            if rng.gen_range(0..100) >= signer_sampling.percentage {
+                continue;
+            }
+
+            if signer.party_id.is_empty() {
+                return Err(anyhow!(
+                    "Certificate '{}' lists a signer with an empty party id",
+                    certificate.hash
+                ));
+            }
+            if signer.stake == 0 {
+                return Err(anyhow!(
+                    "Certificate '{}' lists the signer '{}' with a zero stake",
+                    certificate.hash,
+                    signer.party_id
+                ));
+            }
+            checked_signers += 1;
+        }
+
+        Ok(checked_signers)
+    }
+
+    /// Verify the given certificate, returns its previous certificate (if any) and the number of
+    /// signers which metadata were checked.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            fields(certificate_hash = %certificate.hash, epoch = %certificate.beacon.epoch)
+        )
+    )]
+    async fn verify_certificate(
+        &self,
+        certificate_chain_validation_id: &str,
+        certificate: &Certificate,
+        signer_sampling: Option<&SignerSampling>,
+    ) -> MithrilResult<(Option<Certificate>, u64)> {
+        let (previous_or_none, checked_signers) = self
+            .verify_certificate_deferring_multi_signature(certificate, signer_sampling)
+            .await?;
+        self.verify_certificates_batch(
+            certificate_chain_validation_id,
+            std::slice::from_ref(certificate),
+        )
+        .await?;
+
+        Ok((previous_or_none, checked_signers))
+    }
+
+    /// Verify the given certificate except for its multi-signature, returns its previous
+    /// certificate (if any) and the number of signers which metadata were checked.
+    ///
+    /// The errors raised carry the [CertificateVerificationDiagnostics] of the failure.
+    async fn verify_certificate_deferring_multi_signature(
+        &self,
+        certificate: &Certificate,
+        signer_sampling: Option<&SignerSampling>,
+    ) -> MithrilResult<(Option<Certificate>, u64)> {
+        let previous_or_none = match self
+            .internal_verifier
+            .verify_certificate_deferring_multi_signature(
+                certificate,
+                &self.genesis_verification_key,
+            )
+            .await
+        {
+            Ok(previous_or_none) => previous_or_none,
+            Err(error) => {
+                let failed_check = CertificateVerificationCheck::of_verifier_error(&error);
+                return Err(self.diagnose(error, certificate, failed_check).await);
+            }
+        };
+        if let Err(error) = self.check_epoch_continuity(certificate, previous_or_none.as_ref()) {
+            return Err(self
+                .diagnose(
+                    error.into(),
+                    certificate,
+                    CertificateVerificationCheck::EpochGap,
+                )
+                .await);
+        }
+        let checked_signers = match signer_sampling
+            .map(|sampling| Self::check_signers_metadata(certificate, sampling))
+            .transpose()
+        {
+            Ok(checked_signers) => checked_signers.unwrap_or_default(),
+            Err(error) => {
+                return Err(self
+                    .diagnose(
+                        error,
+                        certificate,
+                        CertificateVerificationCheck::SignersMetadata,
+                    )
+                    .await);
+            }
+        };
+
+        Ok((previous_or_none, checked_signers))
+    }
+
+    /// Attach the [CertificateVerificationDiagnostics] of the failed verification of the given
+    /// certificate to its error.
+    async fn diagnose(
+        &self,
+        error: anyhow::Error,
+        certificate: &Certificate,
+        failed_check: CertificateVerificationCheck,
+    ) -> anyhow::Error {
+        let previous_certificate = match failed_check {
+            CertificateVerificationCheck::AggregateVerificationKey => self
+                .retriever
+                .get_certificate_details(&certificate.previous_hash)
+                .await
+                .ok(),
+            _ => None,
+        };
+
+        error.context(CertificateVerificationDiagnostics::new(
+            certificate,
+            failed_check,
+            previous_certificate.as_ref(),
+        ))
+    }
+
+    /// Verify the multi-signatures of the given certificates in a single batch, then notify their
+    /// validation.
+    async fn verify_certificates_batch(
+        &self,
+        certificate_chain_validation_id: &str,
+        certificates: &[Certificate],
+    ) -> MithrilResult<()> {
+        let internal_verifier = self.internal_verifier.clone();
+        let batch = certificates.to_vec();
+        if let Err(error) = self
+            .compute_executor
+            .run(move || internal_verifier.verify_multi_signatures_batch(&batch))
+            .await?
+        {
+            return Err(self.diagnose_batch(error, certificates).await);
+        }
+        for certificate in certificates {
+            self.notify_certificate_validated(certificate_chain_validation_id, certificate)
+                .await;
+        }
+
+        Ok(())
+    }
+
+    /// Verify the multi-signatures of the given epochs batches in parallel, then notify the
+    /// validation of their certificates in the chain order.
+    ///
+    /// If several batches are invalid, the error targets the latest of them.
+    async fn verify_certificates_batches_in_parallel(
+        &self,
+        certificate_chain_validation_id: &str,
+        batches: Vec<Vec<Certificate>>,
+    ) -> MithrilResult<Vec<Vec<Certificate>>> {
+        let internal_verifier = self.internal_verifier.clone();
+        let (batches, results) = self
+            .compute_executor
+            .run(move || {
+                #[cfg(feature = "compute_threads")]
+                let batches_iter = batches.par_iter();
+                #[cfg(not(feature = "compute_threads"))]
+                let batches_iter = batches.iter();
+                let results: Vec<_> = batches_iter
+                    .map(|batch| internal_verifier.verify_multi_signatures_batch(batch))
+                    .collect();
+                (batches, results)
+            })
+            .await?;
+
+        for (batch, result) in batches.iter().zip(results) {
+            if let Err(error) = result {
+                return Err(self.diagnose_batch(error, batch).await);
+            }
+        }
+        for certificate in batches.iter().flatten() {
+            self.notify_certificate_validated(certificate_chain_validation_id, certificate)
+                .await;
+        }
+
+        Ok(batches)
+    }
+
+    /// Attach the [CertificateVerificationDiagnostics] of the certificate of the given batch with
+    /// an invalid multi-signature to the error of the batch.
+    async fn diagnose_batch(
+        &self,
+        error: MultiSignaturesBatchError,
+        certificates: &[Certificate],
+    ) -> anyhow::Error {
+        self.diagnose(
+            error.error.into(),
+            &certificates[error.index],
+            CertificateVerificationCheck::MultiSignature,
+        )
+        .await
+    }
+
+    /// Walk the whole chain starting with the given certificate, returns its certificates
+    /// grouped by epoch, latest first.
+    ///
+    /// If a `certificate_chain_validation_id` is given, the multi-signatures of the certificates
+    /// of an epoch are verified in a single batch once all of them were chained, else they must
+    /// be verified afterward.
+    async fn walk_chain(
+        &self,
+        certificate: Certificate,
+        certificate_chain_validation_id: Option<&str>,
+    ) -> MithrilResult<Vec<Vec<Certificate>>> {
+        let mut current_certificate = certificate;
+        let mut batches = vec![];
+        let mut epoch_certificates = vec![];
+        loop {
+            let (previous_or_none, _) = self
+                .verify_certificate_deferring_multi_signature(
+                    &current_certificate,
+                    self.signer_sampling.as_ref(),
+                )
+                .await?;
+            let is_last_of_epoch = previous_or_none
+                .as_ref()
+                .is_none_or(|previous| previous.beacon.epoch != current_certificate.beacon.epoch);
+            epoch_certificates.push(current_certificate);
+            if is_last_of_epoch {
+                if let Some(certificate_chain_validation_id) = certificate_chain_validation_id {
+                    self.verify_certificates_batch(
+                        certificate_chain_validation_id,
+                        &epoch_certificates,
+                    )
+                    .await?;
+                }
+                batches.push(std::mem::take(&mut epoch_certificates));
+            }
+
+            match previous_or_none {
+                Some(previous_certificate) => current_certificate = previous_certificate,
+                None => return Ok(batches),
+            }
+        }
+    }
+
+    /// Verify the chain starting with the given certificate, in two phases if the
+    /// [parallel signature verification][Self::with_parallel_signature_verification] is enabled,
+    /// returns the verified certificates, latest first.
+    async fn verify_chain_from(
+        &self,
+        certificate_chain_validation_id: &str,
+        certificate: Certificate,
+    ) -> MithrilResult<Vec<Certificate>> {
+        let batches = if self.parallel_signature_verification {
+            let batches = self.walk_chain(certificate, None).await?;
+            self.verify_certificates_batches_in_parallel(certificate_chain_validation_id, batches)
+                .await?
+        } else {
+            self.walk_chain(certificate, Some(certificate_chain_validation_id))
+                .await?
+        };
+
+        Ok(batches.into_iter().flatten().collect())
+    }
+
+    async fn notify_certificate_validated(
+        &self,
+        certificate_chain_validation_id: &str,
+        certificate: &Certificate,
+    ) {
+        if let Some(metrics_recorder) = &self.metrics_recorder {
+            metrics_recorder.record_certificate_verified();
+        }
+
+        self.feedback_sender
+            .send_event(MithrilEvent::CertificateValidated {
+                certificate_hash: certificate.hash.clone(),
+                certificate_chain_validation_id: certificate_chain_validation_id.to_string(),
+            })
+            .await;
+    }
 }
 
 #[cfg_attr(target_family = "wasm", async_trait(?Send))]
 #[cfg_attr(not(target_family = "wasm"), async_trait)]
 impl CertificateVerifier for MithrilCertificateVerifier {
     async fn verify_chain(&self, certificate: &MithrilCertificate) -> MithrilResult<()> {
+        self.verify_chain_certificates(certificate).await?;
+
+        Ok(())
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "verify_certificate_chain",
+            skip_all,
+            fields(certificate_hash = %certificate.hash)
+        )
+    )]
+    async fn verify_chain_certificates(
+        &self,
+        certificate: &MithrilCertificate,
+    ) -> MithrilResult<Vec<MithrilCertificate>> {
         // Todo: move most of this code in the `mithril_common` verifier by defining
         // a new `verify_chain` method that take a callback called when a certificate is
         // validated.
@@ -238,33 +1868,94 @@ impl CertificateVerifier for MithrilCertificateVerifier {
             })
             .await;
 
-        let mut current_certificate = certificate.clone().try_into()?;
-        loop {
-            let previous_or_none = self
-                .internal_verifier
-                .verify_certificate(&current_certificate, &self.genesis_verification_key)
-                .await?;
+        let certificate: Certificate = certificate.clone().try_into()?;
+        let prefetched_chain_verifier = match self.certificate_chain_bundle {
+            true => self
+                .retriever
+                .prefetch_chain(&certificate.hash)
+                .await?
+                .map(|retriever| self.with_internal_retriever(Arc::new(retriever))),
+            false => None,
+        };
+        let chain = prefetched_chain_verifier
+            .as_ref()
+            .unwrap_or(self)
+            .verify_chain_from(&certificate_chain_validation_id, certificate)
+            .await?;
+
+        self.feedback_sender
+            .send_event(MithrilEvent::CertificateChainValidated {
+                certificate_chain_validation_id,
+            })
+            .await;
+
+        chain
+            .into_iter()
+            .map(|certificate| {
+                let certificate_hash = certificate.hash.clone();
+                CertificateMessage::try_from(certificate).with_context(|| {
+                    format!("Could not create the message of certificate '{certificate_hash}'")
+                })
+            })
+            .collect()
+    }
+
+    async fn verify_chain_step(
+        &self,
+        state: CertificateChainVerificationState,
+    ) -> MithrilResult<StepResult> {
+        let mut state = state;
+        let Some(next_certificate_hash) = state.next_certificate_hash.clone() else {
+            return Ok(StepResult::Completed(state));
+        };
 
+        if state.verified_certificates == 0 {
+            state.signer_sampling = self.signer_sampling;
             self.feedback_sender
-                .send_event(MithrilEvent::CertificateValidated {
-                    certificate_hash: current_certificate.hash.clone(),
-                    certificate_chain_validation_id: certificate_chain_validation_id.clone(),
+                .send_event(MithrilEvent::CertificateChainValidationStarted {
+                    certificate_chain_validation_id: state.certificate_chain_validation_id.clone(),
                 })
                 .await;
+        }
+
+        let mut current_certificate = self
+            .retriever
+            .get_certificate_details(&next_certificate_hash)
+            .await?;
+        for _ in 0..state.max_certificates_per_step {
+            let (previous_or_none, checked_signers) = self
+                .verify_certificate(
+                    &state.certificate_chain_validation_id,
+                    &current_certificate,
+                    state.signer_sampling.as_ref(),
+                )
+                .await?;
+            state.verified_certificates += 1;
+            state.checked_signers += checked_signers;
 
             match previous_or_none {
-                Some(previous_certificate) => current_certificate = previous_certificate,
-                None => break,
+                Some(previous_certificate) => {
+                    state.next_certificate_hash = Some(previous_certificate.hash.clone());
+                    current_certificate = previous_certificate;
+                }
+                None => {
+                    state.next_certificate_hash = None;
+                    break;
+                }
             }
         }
 
-        self.feedback_sender
-            .send_event(MithrilEvent::CertificateChainValidated {
-                certificate_chain_validation_id,
-            })
-            .await;
+        if state.is_completed() {
+            self.feedback_sender
+                .send_event(MithrilEvent::CertificateChainValidated {
+                    certificate_chain_validation_id: state.certificate_chain_validation_id.clone(),
+                })
+                .await;
 
-        Ok(())
+            Ok(StepResult::Completed(state))
+        } else {
+            Ok(StepResult::InProgress(state))
+        }
     }
 }
 
@@ -275,6 +1966,12 @@ impl CertificateRetriever for InternalCertificateRetriever {
         &self,
         certificate_hash: &str,
     ) -> Result<Certificate, CertificateRetrieverError> {
+        if let Some(custom_retriever) = &self.custom_retriever {
+            return custom_retriever
+                .get_certificate_details(certificate_hash)
+                .await;
+        }
+
         self.get(certificate_hash)
             .await
             .map_err(CertificateRetrieverError)?
@@ -287,3 +1984,1349 @@ impl CertificateRetriever for InternalCertificateRetriever {
             ))))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use mithril_common::crypto_helper::tests_setup::setup_certificate_chain;
+    use mithril_common::messages::CertificateMessage as CommonCertificateMessage;
+    use std::collections::HashMap;
+
+    use crate::aggregator_client::MockAggregatorHTTPClient;
+    use crate::feedback::StackFeedbackReceiver;
+    use crate::metrics::test_utils::StackMetricsRecorder;
+    use crate::protocol_message_matcher::ProtocolMessageMismatch;
+    use crate::test_tools::certificate_chain::{
+        CertificateChainBuilder, CertificateChainTampering,
+    };
+    use crate::test_tools::FakeAggregatorClient;
+    use crate::test_utils;
+    use crate::ClientBuilder;
+
+    use super::*;
+
+    /// Build a verifier serving a valid chain of `total_certificates` certificates, returns it with
+    /// the hash of the latest certificate of the chain.
+    fn verifier_with_valid_chain(
+        total_certificates: u64,
+        feedback_receiver: Arc<StackFeedbackReceiver>,
+    ) -> (MithrilCertificateVerifier, String) {
+        let (certificates, genesis_verifier) = setup_certificate_chain(total_certificates, 1);
+
+        verifier_serving_chain(certificates, genesis_verifier, feedback_receiver)
+    }
+
+    /// Build a verifier serving the given chain, returns it with the hash of the latest certificate
+    /// of the chain.
+    fn verifier_serving_chain(
+        certificates: Vec<mithril_common::entities::Certificate>,
+        genesis_verifier: mithril_common::crypto_helper::ProtocolGenesisVerifier,
+        feedback_receiver: Arc<StackFeedbackReceiver>,
+    ) -> (MithrilCertificateVerifier, String) {
+        let last_certificate_hash = certificates[0].hash.clone();
+        let messages: HashMap<String, String> = certificates
+            .into_iter()
+            .map(|certificate| {
+                let message = CommonCertificateMessage::try_from(certificate).unwrap();
+                (
+                    message.hash.clone(),
+                    serde_json::to_string(&message).unwrap(),
+                )
+            })
+            .collect();
+
+        let mut aggregator_client = MockAggregatorHTTPClient::new();
+        aggregator_client
+            .expect_get_content()
+            .returning(move |request| match request {
+                AggregatorRequest::GetCertificate { hash } => Ok(messages[&hash].clone()),
+                _ => panic!("unexpected request: {request:?}"),
+            });
+        let verifier = MithrilCertificateVerifier::new(
+            Arc::new(aggregator_client),
+            &genesis_verifier
+                .to_verification_key()
+                .to_json_hex()
+                .unwrap(),
+            FeedbackSender::new(&[feedback_receiver]),
+            test_utils::test_logger(),
+        )
+        .unwrap();
+
+        (verifier, last_certificate_hash)
+    }
+
+    #[tokio::test]
+    async fn verify_multi_signatures_of_an_epoch_in_a_single_batch() {
+        let (certificates, genesis_verifier) = setup_certificate_chain(5, 3);
+        let epoch_certificates: Vec<Certificate> = certificates[0..3]
+            .iter()
+            .map(|certificate| {
+                let message = CommonCertificateMessage::try_from(certificate.clone()).unwrap();
+                let message: MithrilCertificate =
+                    serde_json::from_str(&serde_json::to_string(&message).unwrap()).unwrap();
+                message.try_into().unwrap()
+            })
+            .collect();
+        let (verifier, _) = verifier_serving_chain(
+            certificates,
+            genesis_verifier,
+            Arc::new(StackFeedbackReceiver::new()),
+        );
+
+        verifier
+            .internal_verifier
+            .verify_multi_signatures_batch(&epoch_certificates)
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn verify_multi_signatures_batch_reports_the_index_of_the_invalid_certificate() {
+        let (certificates, genesis_verifier) = setup_certificate_chain(5, 3);
+        let mut epoch_certificates: Vec<Certificate> = certificates[0..3]
+            .iter()
+            .map(|certificate| {
+                let message = CommonCertificateMessage::try_from(certificate.clone()).unwrap();
+                let message: MithrilCertificate =
+                    serde_json::from_str(&serde_json::to_string(&message).unwrap()).unwrap();
+                message.try_into().unwrap()
+            })
+            .collect();
+        epoch_certificates[1].signed_message = "tampered".to_string();
+        let (verifier, _) = verifier_serving_chain(
+            certificates,
+            genesis_verifier,
+            Arc::new(StackFeedbackReceiver::new()),
+        );
+
+        let error = verifier
+            .internal_verifier
+            .verify_multi_signatures_batch(&epoch_certificates)
+            .expect_err("verify_multi_signatures_batch should fail");
+
+        assert_eq!(1, error.index);
+    }
+
+    #[cfg(feature = "compute_threads")]
+    #[tokio::test]
+    async fn verify_chain_on_a_compute_thread_pool() {
+        let (verifier, certificate_hash) =
+            verifier_with_valid_chain(3, Arc::new(StackFeedbackReceiver::new()));
+        let verifier = verifier.with_compute_executor(ComputeExecutor::thread_pool(1).unwrap());
+        let certificate = verifier
+            .retriever
+            .get(&certificate_hash)
+            .await
+            .unwrap()
+            .unwrap();
+
+        verifier.verify_chain(&certificate).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn verify_chain_with_parallel_signature_verification_notify_in_chain_order() {
+        let feedback_receiver = Arc::new(StackFeedbackReceiver::new());
+        let (certificates, genesis_verifier) = setup_certificate_chain(7, 2);
+        let certificate_hashes: Vec<String> = certificates
+            .iter()
+            .map(|certificate| certificate.hash.clone())
+            .collect();
+        let (verifier, certificate_hash) =
+            verifier_serving_chain(certificates, genesis_verifier, feedback_receiver.clone());
+        let verifier = verifier.with_parallel_signature_verification(true);
+        #[cfg(feature = "compute_threads")]
+        let verifier = verifier.with_compute_executor(ComputeExecutor::thread_pool(2).unwrap());
+        let certificate = verifier
+            .retriever
+            .get(&certificate_hash)
+            .await
+            .unwrap()
+            .unwrap();
+
+        verifier.verify_chain(&certificate).await.unwrap();
+
+        let validated_hashes: Vec<String> = feedback_receiver
+            .stacked_events()
+            .into_iter()
+            .filter_map(|event| match event {
+                MithrilEvent::CertificateValidated {
+                    certificate_hash, ..
+                } => Some(certificate_hash),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(certificate_hashes, validated_hashes);
+    }
+
+    #[tokio::test]
+    async fn verify_chain_with_parallel_signature_verification_pinpoint_the_invalid_certificate() {
+        let (mut certificates, genesis_verifier) = setup_certificate_chain(7, 2);
+        certificates[2].signature = certificates[0].signature.clone();
+        certificates[2].hash = certificates[2].compute_hash();
+        certificates[1].previous_hash = certificates[2].hash.clone();
+        certificates[1].hash = certificates[1].compute_hash();
+        certificates[0].previous_hash = certificates[1].hash.clone();
+        certificates[0].hash = certificates[0].compute_hash();
+        let invalid_certificate_hash = certificates[2].hash.clone();
+        let (verifier, certificate_hash) = verifier_serving_chain(
+            certificates,
+            genesis_verifier,
+            Arc::new(StackFeedbackReceiver::new()),
+        );
+        let verifier = verifier.with_parallel_signature_verification(true);
+        let certificate = verifier
+            .retriever
+            .get(&certificate_hash)
+            .await
+            .unwrap()
+            .unwrap();
+
+        let error = verifier
+            .verify_chain(&certificate)
+            .await
+            .expect_err("verify_chain should fail");
+
+        let diagnostics = error
+            .downcast_ref::<CertificateVerificationDiagnostics>()
+            .unwrap_or_else(|| panic!("Expected diagnostics, got: {error:?}"));
+        assert_eq!(
+            CertificateVerificationCheck::MultiSignature,
+            diagnostics.failed_check
+        );
+        assert_eq!(invalid_certificate_hash, diagnostics.certificate_hash);
+    }
+
+    #[tokio::test]
+    async fn verify_chain_with_a_certificate_for_every_epoch() {
+        let (verifier, certificate_hash) =
+            verifier_with_valid_chain(4, Arc::new(StackFeedbackReceiver::new()));
+        let verifier = verifier.with_max_epoch_gap(1);
+        let certificate = verifier
+            .retriever
+            .get(&certificate_hash)
+            .await
+            .unwrap()
+            .unwrap();
+
+        verifier.verify_chain(&certificate).await.unwrap();
+    }
+
+    /// A [CertificateRetriever] serving the certificates of a map, like a custom store would.
+    struct MapCertificateRetriever(HashMap<String, Certificate>);
+
+    #[cfg_attr(target_family = "wasm", async_trait(?Send))]
+    #[cfg_attr(not(target_family = "wasm"), async_trait)]
+    impl CertificateRetriever for MapCertificateRetriever {
+        async fn get_certificate_details(
+            &self,
+            certificate_hash: &str,
+        ) -> Result<Certificate, CertificateRetrieverError> {
+            self.0
+                .get(certificate_hash)
+                .cloned()
+                .ok_or(CertificateRetrieverError(anyhow!(
+                    "Certificate does not exist: '{certificate_hash}'"
+                )))
+        }
+    }
+
+    #[tokio::test]
+    async fn verify_chain_retrieved_from_a_custom_certificate_retriever() {
+        let (certificates, genesis_verifier) = setup_certificate_chain(3, 1);
+        let last_certificate_hash = certificates[0].hash.clone();
+        let certificates: HashMap<String, Certificate> = certificates
+            .into_iter()
+            .map(|certificate| {
+                let message = CommonCertificateMessage::try_from(certificate).unwrap();
+                let message: CertificateMessage =
+                    serde_json::from_str(&serde_json::to_string(&message).unwrap()).unwrap();
+                (message.hash.clone(), message.try_into().unwrap())
+            })
+            .collect();
+        let retriever = Arc::new(MapCertificateRetriever(certificates));
+        // Without expectations the aggregator client panics if a certificate is requested to it
+        let aggregator_client = Arc::new(MockAggregatorHTTPClient::new());
+        let verifier = MithrilCertificateVerifier::new(
+            aggregator_client.clone(),
+            &genesis_verifier
+                .to_verification_key()
+                .to_json_hex()
+                .unwrap(),
+            FeedbackSender::new(&[]),
+            test_utils::test_logger(),
+        )
+        .unwrap()
+        .with_certificate_chain_bundle(true)
+        .with_certificate_retriever(retriever.clone());
+        let client = CertificateClient::new(
+            aggregator_client,
+            Arc::new(verifier),
+            test_utils::test_logger(),
+        )
+        .with_certificate_retriever(retriever);
+
+        let certificate = client.verify_chain(&last_certificate_hash).await.unwrap();
+
+        assert_eq!(last_certificate_hash, certificate.hash);
+    }
+
+    #[tokio::test]
+    async fn verify_chain_report_the_certificates_further_apart_than_the_max_epoch_gap() {
+        let (verifier, certificate_hash) =
+            verifier_with_valid_chain(4, Arc::new(StackFeedbackReceiver::new()));
+        let verifier = verifier.with_max_epoch_gap(0);
+        let certificate = verifier
+            .retriever
+            .get(&certificate_hash)
+            .await
+            .unwrap()
+            .unwrap();
+
+        let error = verifier
+            .verify_chain(&certificate)
+            .await
+            .expect_err("verify_chain should fail");
+
+        let epoch_gap = error
+            .downcast_ref::<CertificateChainEpochGap>()
+            .unwrap_or_else(|| panic!("Expected an epoch gap error, got: {error:?}"));
+        assert_eq!(certificate.hash, epoch_gap.certificate_hash);
+        assert_eq!(
+            certificate.previous_hash,
+            epoch_gap.previous_certificate_hash
+        );
+        assert_eq!(
+            epoch_gap.certificate_epoch,
+            epoch_gap.previous_certificate_epoch + 1
+        );
+    }
+
+    #[tokio::test]
+    async fn verify_chain_step_report_the_certificates_further_apart_than_the_max_epoch_gap() {
+        let (verifier, certificate_hash) =
+            verifier_with_valid_chain(4, Arc::new(StackFeedbackReceiver::new()));
+        let verifier = verifier.with_max_epoch_gap(0);
+
+        let error = verifier
+            .verify_chain_step(CertificateChainVerificationState::new(
+                &certificate_hash,
+                10,
+            ))
+            .await
+            .expect_err("verify_chain_step should fail");
+
+        assert!(
+            error.downcast_ref::<CertificateChainEpochGap>().is_some(),
+            "Expected an epoch gap error, got: {error:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn verify_chain_pinpoint_the_certificate_with_an_invalid_multi_signature() {
+        let feedback_receiver = Arc::new(StackFeedbackReceiver::new());
+        let (mut certificates, genesis_verifier) = setup_certificate_chain(5, 3);
+        certificates[0].signature = certificates[1].signature.clone();
+        certificates[0].hash = certificates[0].compute_hash();
+        let invalid_certificate_hash = certificates[0].hash.clone();
+        let message = CommonCertificateMessage::try_from(certificates[0].clone()).unwrap();
+        let certificate: MithrilCertificate =
+            serde_json::from_str(&serde_json::to_string(&message).unwrap()).unwrap();
+        let (verifier, _) =
+            verifier_serving_chain(certificates, genesis_verifier, feedback_receiver);
+
+        let error = verifier
+            .verify_chain(&certificate)
+            .await
+            .expect_err("verify_chain should fail");
+
+        assert!(
+            error.to_string().contains(&invalid_certificate_hash),
+            "Unexpected error: {error:?}"
+        );
+    }
+
+    /// Verify the chain which latest certificate is tampered by `tamper`, returns the diagnostics
+    /// of the failure with the hash of the tampered certificate.
+    async fn diagnose_tampered_chain<F: Fn(&mut Vec<mithril_common::entities::Certificate>)>(
+        tamper: F,
+    ) -> (CertificateVerificationDiagnostics, String) {
+        let (mut certificates, genesis_verifier) = setup_certificate_chain(5, 1);
+        tamper(&mut certificates);
+        certificates[0].hash = certificates[0].compute_hash();
+        let tampered_certificate_hash = certificates[0].hash.clone();
+        let (verifier, _) = verifier_serving_chain(
+            certificates,
+            genesis_verifier,
+            Arc::new(StackFeedbackReceiver::new()),
+        );
+        let certificate = verifier
+            .retriever
+            .get(&tampered_certificate_hash)
+            .await
+            .unwrap()
+            .unwrap();
+
+        let error = verifier
+            .verify_chain(&certificate)
+            .await
+            .expect_err("verify_chain should fail");
+
+        let diagnostics = error
+            .downcast_ref::<CertificateVerificationDiagnostics>()
+            .unwrap_or_else(|| panic!("Expected diagnostics, got: {error:?}"))
+            .clone();
+        (diagnostics, tampered_certificate_hash)
+    }
+
+    #[tokio::test]
+    async fn verify_chain_attach_diagnostics_of_an_invalid_multi_signature() {
+        let (diagnostics, tampered_certificate_hash) = diagnose_tampered_chain(|certificates| {
+            certificates[0].signature = certificates[1].signature.clone();
+        })
+        .await;
+
+        assert_eq!(
+            CertificateVerificationCheck::MultiSignature,
+            diagnostics.failed_check
+        );
+        assert_eq!(tampered_certificate_hash, diagnostics.certificate_hash);
+        assert!(diagnostics.aggregate_verification_key.is_some());
+        assert_eq!(None, diagnostics.previous_aggregate_verification_key);
+    }
+
+    #[tokio::test]
+    async fn verify_chain_attach_diagnostics_of_an_aggregate_verification_key_mismatch() {
+        let (diagnostics, tampered_certificate_hash) = diagnose_tampered_chain(|certificates| {
+            certificates[0].aggregate_verification_key = certificates
+                .iter()
+                .map(|certificate| certificate.aggregate_verification_key.clone())
+                .find(|avk| *avk != certificates[0].aggregate_verification_key)
+                .unwrap();
+        })
+        .await;
+
+        assert_eq!(
+            CertificateVerificationCheck::AggregateVerificationKey,
+            diagnostics.failed_check
+        );
+        assert_eq!(tampered_certificate_hash, diagnostics.certificate_hash);
+        assert!(diagnostics.previous_aggregate_verification_key.is_some());
+        assert!(diagnostics
+            .previous_next_aggregate_verification_key
+            .is_some());
+        assert_ne!(
+            diagnostics.aggregate_verification_key,
+            diagnostics.previous_aggregate_verification_key
+        );
+    }
+
+    #[tokio::test]
+    async fn verify_chain_attach_diagnostics_of_an_epoch_gap() {
+        let (verifier, certificate_hash) =
+            verifier_with_valid_chain(4, Arc::new(StackFeedbackReceiver::new()));
+        let verifier = verifier.with_max_epoch_gap(0);
+        let certificate = verifier
+            .retriever
+            .get(&certificate_hash)
+            .await
+            .unwrap()
+            .unwrap();
+
+        let error = verifier
+            .verify_chain(&certificate)
+            .await
+            .expect_err("verify_chain should fail");
+
+        let diagnostics = error
+            .downcast_ref::<CertificateVerificationDiagnostics>()
+            .unwrap_or_else(|| panic!("Expected diagnostics, got: {error:?}"));
+        assert_eq!(
+            CertificateVerificationCheck::EpochGap,
+            diagnostics.failed_check
+        );
+        assert_eq!(
+            certificate.previous_hash,
+            diagnostics.previous_certificate_hash
+        );
+    }
+
+    #[tokio::test]
+    async fn verify_chain_step_verify_a_bounded_number_of_certificates_per_step() {
+        let feedback_receiver = Arc::new(StackFeedbackReceiver::new());
+        let (verifier, certificate_hash) = verifier_with_valid_chain(5, feedback_receiver.clone());
+        let state = CertificateChainVerificationState::new(&certificate_hash, 2);
+
+        let mut verified_certificates_per_step = vec![];
+        let mut step_result = verifier.verify_chain_step(state).await.unwrap();
+        while let StepResult::InProgress(state) = step_result {
+            verified_certificates_per_step.push(state.verified_certificates);
+            step_result = verifier.verify_chain_step(state).await.unwrap();
+        }
+
+        let StepResult::Completed(final_state) = step_result else {
+            panic!("chain verification should be completed");
+        };
+        assert_eq!(vec![2, 4], verified_certificates_per_step);
+        assert_eq!(5, final_state.verified_certificates);
+        assert!(final_state.is_completed());
+
+        let events = feedback_receiver.stacked_events();
+        assert_eq!(
+            MithrilEvent::CertificateChainValidationStarted {
+                certificate_chain_validation_id: final_state
+                    .certificate_chain_validation_id
+                    .clone()
+            },
+            events[0]
+        );
+        assert_eq!(
+            MithrilEvent::CertificateChainValidated {
+                certificate_chain_validation_id: final_state.certificate_chain_validation_id
+            },
+            events[events.len() - 1]
+        );
+        assert_eq!(7, events.len());
+    }
+
+    #[tokio::test]
+    async fn verify_chain_step_on_a_completed_state_does_nothing() {
+        let feedback_receiver = Arc::new(StackFeedbackReceiver::new());
+        let (verifier, certificate_hash) = verifier_with_valid_chain(2, feedback_receiver.clone());
+        let mut state = CertificateChainVerificationState::new(&certificate_hash, 1);
+        state.next_certificate_hash = None;
+
+        let step_result = verifier.verify_chain_step(state.clone()).await.unwrap();
+
+        assert_eq!(StepResult::Completed(state), step_result);
+        assert!(feedback_receiver.stacked_events().is_empty());
+    }
+
+    #[tokio::test]
+    async fn verify_chain_step_resume_from_a_deserialized_state() {
+        let feedback_receiver = Arc::new(StackFeedbackReceiver::new());
+        let (verifier, certificate_hash) = verifier_with_valid_chain(3, feedback_receiver.clone());
+        let state = CertificateChainVerificationState::new(&certificate_hash, 2);
+
+        let StepResult::InProgress(state) = verifier.verify_chain_step(state).await.unwrap() else {
+            panic!("chain verification should be in progress");
+        };
+        let json = serde_json::to_string(&state).unwrap();
+        let restored_state: CertificateChainVerificationState =
+            serde_json::from_str(&json).unwrap();
+        assert_eq!(state, restored_state);
+
+        let step_result = verifier.verify_chain_step(restored_state).await.unwrap();
+        let StepResult::Completed(final_state) = step_result else {
+            panic!("chain verification should be completed");
+        };
+        assert_eq!(3, final_state.verified_certificates);
+    }
+
+    #[test]
+    fn deserialize_state_with_unsupported_schema_version_fails() {
+        let mut state = CertificateChainVerificationState::new("certificate_hash", 2);
+        state.schema_version = CERTIFICATE_CHAIN_VERIFICATION_STATE_SCHEMA_VERSION + 1;
+        let json = serde_json::to_string(&state).unwrap();
+
+        serde_json::from_str::<CertificateChainVerificationState>(&json)
+            .expect_err("deserializing a state with an unsupported schema version should fail");
+    }
+
+    /// Build a certificate client serving a chain of certificates sealed at the given times,
+    /// from the latest to the genesis certificate, returns it with the certificates hashes.
+    ///
+    /// The certificates at the `certifying` indexes sign the hash of `data` as their snapshot
+    /// digest.
+    fn certificate_client_with_chain(
+        sealed_at: &[&str],
+        certifying: &[usize],
+        data: &[u8],
+    ) -> (CertificateClient, Vec<String>) {
+        let hashes: Vec<String> = (0..sealed_at.len())
+            .map(|index| format!("certificate-{index}"))
+            .collect();
+        let data_hash = hex::encode(Sha256::digest(data));
+        let certificates: HashMap<String, MithrilCertificate> = sealed_at
+            .iter()
+            .enumerate()
+            .map(|(index, sealed_at)| {
+                let mut certificate = MithrilCertificate::dummy();
+                certificate.hash = hashes[index].clone();
+                certificate.metadata.sealed_at = DateTime::parse_from_rfc3339(sealed_at)
+                    .unwrap()
+                    .with_timezone(&Utc);
+                match hashes.get(index + 1) {
+                    Some(previous_hash) => certificate.previous_hash = previous_hash.clone(),
+                    None => {
+                        certificate.previous_hash = String::new();
+                        certificate.genesis_signature = "genesis_signature".to_string();
+                    }
+                }
+                if certifying.contains(&index) {
+                    certificate.protocol_message.set_message_part(
+                        ProtocolMessagePartKey::SnapshotDigest,
+                        data_hash.clone(),
+                    );
+                    certificate.signed_message = certificate.protocol_message.compute_hash();
+                }
+                (certificate.hash.clone(), certificate)
+            })
+            .collect();
+        let mut latest_certificate = MithrilCertificateListItem::dummy();
+        latest_certificate.hash = hashes[0].clone();
+        latest_certificate.metadata.sealed_at = certificates[&hashes[0]].metadata.sealed_at;
+
+        let mut aggregator_client = MockAggregatorHTTPClient::new();
+        let served_certificates = certificates.clone();
+        aggregator_client
+            .expect_get_content()
+            .returning(move |request| match request {
+                AggregatorRequest::ListCertificates => {
+                    Ok(serde_json::to_string(&vec![latest_certificate.clone()]).unwrap())
+                }
+                AggregatorRequest::GetCertificate { hash } => {
+                    Ok(serde_json::to_string(&served_certificates[&hash]).unwrap())
+                }
+                _ => panic!("unexpected request: {request:?}"),
+            });
+        let mut verifier = MockCertificateVerifier::new();
+        verifier
+            .expect_verify_chain_certificates()
+            .times(1)
+            .returning(move |certificate| {
+                let mut chain = vec![certificate.clone()];
+                while let Some(previous) = certificates.get(&chain.last().unwrap().previous_hash) {
+                    chain.push(previous.clone());
+                }
+                Ok(chain)
+            });
+        let client = CertificateClient::new(
+            Arc::new(aggregator_client),
+            Arc::new(verifier),
+            test_utils::test_logger(),
+        );
+
+        (client, hashes)
+    }
+
+    #[tokio::test]
+    async fn export_proof_bundle_of_a_valid_chain() {
+        let (certificates, genesis_verifier) = setup_certificate_chain(3, 1);
+        let genesis_verification_key = genesis_verifier
+            .to_verification_key()
+            .to_json_hex()
+            .unwrap();
+        let last_certificate_hash = certificates[0].hash.clone();
+        let messages: HashMap<String, String> = certificates
+            .into_iter()
+            .map(|certificate| {
+                let message = CommonCertificateMessage::try_from(certificate).unwrap();
+                (
+                    message.hash.clone(),
+                    serde_json::to_string(&message).unwrap(),
+                )
+            })
+            .collect();
+        let mut aggregator_client = MockAggregatorHTTPClient::new();
+        aggregator_client
+            .expect_get_content()
+            .returning(move |request| match request {
+                AggregatorRequest::GetCertificate { hash } => Ok(messages[&hash].clone()),
+                _ => panic!("unexpected request: {request:?}"),
+            });
+        let client = CertificateClient::new(
+            Arc::new(aggregator_client),
+            Arc::new(MockCertificateVerifier::new()),
+            test_utils::test_logger(),
+        );
+
+        client
+            .export_proof_bundle(&last_certificate_hash)
+            .await
+            .expect_err("export without genesis verification key should fail");
+
+        let client = client.with_genesis_verification_key(&genesis_verification_key);
+        let bundle = client
+            .export_proof_bundle(&last_certificate_hash)
+            .await
+            .unwrap();
+
+        assert_eq!(3, bundle.certificate_chain.len());
+        assert_eq!(
+            last_certificate_hash,
+            verify_bundle(&bundle, &genesis_verification_key)
+                .await
+                .unwrap()
+                .hash
+        );
+    }
+
+    #[tokio::test]
+    async fn attest_timestamp_anchor_data_to_the_earliest_certificate_sealed_after_it_that_signed_it(
+    ) {
+        let (client, hashes) = certificate_client_with_chain(
+            &[
+                "2024-02-12T16:00:00Z",
+                "2024-02-12T15:00:00Z",
+                "2024-02-12T14:00:00Z",
+                "2024-02-12T13:00:00Z",
+            ],
+            &[0, 1, 3],
+            b"data",
+        );
+        let claimed_at = DateTime::parse_from_rfc3339("2024-02-12T13:30:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        let attestation = client
+            .attest_timestamp(b"data", claimed_at, ProtocolMessagePartKey::SnapshotDigest)
+            .await
+            .unwrap();
+
+        assert_eq!(hashes[1], attestation.certificate.hash);
+        assert_eq!(
+            hashes[1..].to_vec(),
+            attestation
+                .certificate_chain
+                .iter()
+                .map(|certificate| certificate.hash.clone())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(claimed_at, attestation.claimed_at);
+        assert_eq!(hex::encode(Sha256::digest(b"data")), attestation.data_hash);
+        assert_eq!(
+            ProtocolMessagePartKey::SnapshotDigest,
+            attestation.message_part
+        );
+    }
+
+    #[tokio::test]
+    async fn attest_timestamp_fails_if_no_certificate_sealed_after_the_claimed_timestamp_signed_the_data(
+    ) {
+        let (client, _) = certificate_client_with_chain(
+            &[
+                "2024-02-12T15:00:00Z",
+                "2024-02-12T14:00:00Z",
+                "2024-02-12T13:00:00Z",
+            ],
+            &[2],
+            b"data",
+        );
+        let claimed_at = DateTime::parse_from_rfc3339("2024-02-12T13:30:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        client
+            .attest_timestamp(b"data", claimed_at, ProtocolMessagePartKey::SnapshotDigest)
+            .await
+            .expect_err("attest_timestamp should fail");
+    }
+
+    #[tokio::test]
+    async fn attest_timestamp_fails_if_the_data_hash_is_signed_in_another_message_part() {
+        let (client, _) = certificate_client_with_chain(
+            &["2024-02-12T15:00:00Z", "2024-02-12T13:00:00Z"],
+            &[0],
+            b"data",
+        );
+        let claimed_at = DateTime::parse_from_rfc3339("2024-02-12T13:30:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        client
+            .attest_timestamp(
+                b"data",
+                claimed_at,
+                ProtocolMessagePartKey::NextAggregateVerificationKey,
+            )
+            .await
+            .expect_err("attest_timestamp should fail");
+    }
+
+    #[tokio::test]
+    async fn attest_timestamp_fails_if_no_certificate_was_sealed_after_the_claimed_timestamp() {
+        let mut aggregator_client = MockAggregatorHTTPClient::new();
+        aggregator_client
+            .expect_get_content()
+            .returning(|request| match request {
+                AggregatorRequest::ListCertificates => {
+                    let mut latest_certificate = MithrilCertificateListItem::dummy();
+                    latest_certificate.metadata.sealed_at =
+                        DateTime::parse_from_rfc3339("2024-02-12T14:00:00Z")
+                            .unwrap()
+                            .with_timezone(&Utc);
+                    Ok(serde_json::to_string(&vec![latest_certificate]).unwrap())
+                }
+                _ => panic!("unexpected request: {request:?}"),
+            });
+        let client = CertificateClient::new(
+            Arc::new(aggregator_client),
+            Arc::new(MockCertificateVerifier::new()),
+            test_utils::test_logger(),
+        );
+        let claimed_at = DateTime::parse_from_rfc3339("2024-02-12T14:30:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        client
+            .attest_timestamp(b"data", claimed_at, ProtocolMessagePartKey::SnapshotDigest)
+            .await
+            .expect_err("attest_timestamp should fail");
+    }
+
+    /// Verify the whole chain step by step, returns the final state.
+    async fn verify_chain_by_steps(
+        verifier: &MithrilCertificateVerifier,
+        certificate_hash: &str,
+    ) -> CertificateChainVerificationState {
+        let mut state = CertificateChainVerificationState::new(certificate_hash, 2);
+        loop {
+            match verifier.verify_chain_step(state).await.unwrap() {
+                StepResult::InProgress(next_state) => state = next_state,
+                StepResult::Completed(final_state) => return final_state,
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn verify_chain_step_does_not_check_signers_metadata_by_default() {
+        let (verifier, certificate_hash) =
+            verifier_with_valid_chain(4, Arc::new(StackFeedbackReceiver::new()));
+
+        let final_state = verify_chain_by_steps(&verifier, &certificate_hash).await;
+
+        assert_eq!(None, final_state.signer_sampling);
+        assert_eq!(0, final_state.checked_signers);
+    }
+
+    #[tokio::test]
+    async fn verify_chain_step_with_a_full_signer_sampling_check_every_signer_metadata() {
+        let (certificates, _) = setup_certificate_chain(4, 1);
+        let total_signers: u64 = certificates
+            .iter()
+            .map(|certificate| certificate.metadata.signers.len() as u64)
+            .sum();
+        let (verifier, certificate_hash) =
+            verifier_with_valid_chain(4, Arc::new(StackFeedbackReceiver::new()));
+        let verifier = verifier.with_signer_sampling(SignerSampling::new(100, 42));
+
+        let final_state = verify_chain_by_steps(&verifier, &certificate_hash).await;
+
+        assert_eq!(total_signers, final_state.checked_signers);
+    }
+
+    #[tokio::test]
+    async fn verify_chain_step_record_verified_certificates_metrics() {
+        let metrics_recorder = Arc::new(StackMetricsRecorder::default());
+        let (verifier, certificate_hash) =
+            verifier_with_valid_chain(3, Arc::new(StackFeedbackReceiver::new()));
+        let verifier = verifier.with_metrics_recorder(metrics_recorder.clone());
+
+        verify_chain_by_steps(&verifier, &certificate_hash).await;
+
+        assert_eq!(3, *metrics_recorder.verified_certificates.lock().unwrap());
+    }
+
+    #[tokio::test]
+    async fn verify_chain_step_with_signer_sampling_is_reproducible_and_recorded_in_the_state() {
+        let signer_sampling = SignerSampling::new(40, 42);
+        let (certificates, genesis_verifier) = setup_certificate_chain(4, 1);
+        let (full_verifier, certificate_hash) = verifier_serving_chain(
+            certificates.clone(),
+            genesis_verifier.clone(),
+            Arc::new(StackFeedbackReceiver::new()),
+        );
+        let full_verifier = full_verifier.with_signer_sampling(SignerSampling::new(100, 42));
+        let full_state = verify_chain_by_steps(&full_verifier, &certificate_hash).await;
+        let (verifier, _) = verifier_serving_chain(
+            certificates,
+            genesis_verifier,
+            Arc::new(StackFeedbackReceiver::new()),
+        );
+        let verifier = verifier.with_signer_sampling(signer_sampling);
+
+        let first_state = verify_chain_by_steps(&verifier, &certificate_hash).await;
+        let second_state = verify_chain_by_steps(&verifier, &certificate_hash).await;
+
+        assert_eq!(Some(signer_sampling), first_state.signer_sampling);
+        assert_eq!(first_state.checked_signers, second_state.checked_signers);
+        assert!(first_state.checked_signers < full_state.checked_signers);
+    }
+
+    #[test]
+    fn check_signers_metadata_fails_on_duplicated_or_zero_stake_signers() {
+        let every_signer = SignerSampling::new(100, 42);
+        let (certificates, _) = setup_certificate_chain(2, 1);
+        let message = CommonCertificateMessage::try_from(certificates[0].clone()).unwrap();
+        let certificate: Certificate =
+            serde_json::from_str::<CertificateMessage>(&serde_json::to_string(&message).unwrap())
+                .unwrap()
+                .try_into()
+                .unwrap();
+        MithrilCertificateVerifier::check_signers_metadata(&certificate, &every_signer)
+            .expect("valid certificate signers should pass the checks");
+
+        let mut duplicated_signer = certificate.clone();
+        let signer = duplicated_signer.metadata.signers[0].clone();
+        duplicated_signer.metadata.signers.push(signer);
+        MithrilCertificateVerifier::check_signers_metadata(&duplicated_signer, &every_signer)
+            .expect_err("a duplicated signer should fail the checks");
+
+        let mut zero_stake_signer = certificate;
+        zero_stake_signer.metadata.signers[0].stake = 0;
+        MithrilCertificateVerifier::check_signers_metadata(&zero_stake_signer, &every_signer)
+            .expect_err("a signer with a zero stake should fail the checks");
+    }
+
+    fn to_mithril_certificate(
+        certificate: mithril_common::entities::Certificate,
+    ) -> MithrilCertificate {
+        let message = CommonCertificateMessage::try_from(certificate).unwrap();
+        serde_json::from_str(&serde_json::to_string(&message).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn check_next_aggregate_verification_key_across_an_epoch_boundary() {
+        let (certificates, _) = setup_certificate_chain(3, 1);
+        let next_certificate = to_mithril_certificate(certificates[0].clone());
+        let certificate = to_mithril_certificate(certificates[1].clone());
+
+        check_next_aggregate_verification_key(&certificate, &next_certificate).unwrap();
+        assert!(matches!(
+            check_next_aggregate_verification_key(&next_certificate, &certificate),
+            Err(AggregateVerificationKeyContinuityError::NonConsecutiveEpochs { .. })
+        ));
+
+        let mut other_key_certificate = next_certificate.clone();
+        other_key_certificate.aggregate_verification_key = "other-avk".to_string();
+        assert_eq!(
+            Err(
+                AggregateVerificationKeyContinuityError::AggregateVerificationKeyMismatch {
+                    certificate_hash: certificate.hash.clone(),
+                    next_certificate_hash: other_key_certificate.hash.clone(),
+                }
+            ),
+            check_next_aggregate_verification_key(&certificate, &other_key_certificate)
+        );
+
+        let mut unsigned_certificate = certificate.clone();
+        unsigned_certificate.signed_message = "tampered".to_string();
+        assert!(matches!(
+            check_next_aggregate_verification_key(&unsigned_certificate, &next_certificate),
+            Err(AggregateVerificationKeyContinuityError::UnsignedProtocolMessage { .. })
+        ));
+    }
+
+    #[test]
+    fn participation_report_of_a_certificate() {
+        let (certificates, _) = setup_certificate_chain(3, 1);
+        let certificate = to_mithril_certificate(certificates[0].clone());
+
+        let report = CertificateParticipationReport::from_certificate(&certificate).unwrap();
+
+        let parameters = &certificate.metadata.protocol_parameters;
+        assert_eq!(certificate.hash, report.certificate_hash);
+        assert_eq!(certificate.beacon.epoch, report.epoch);
+        assert_eq!(certificate.metadata.signers.len(), report.signers_count());
+        assert_eq!(
+            certificate
+                .metadata
+                .signers
+                .iter()
+                .map(|s| s.stake)
+                .sum::<Stake>(),
+            report.signed_stake
+        );
+        assert_eq!(parameters.k, report.quorum);
+        // All the registered parties signed in the test setup
+        assert_eq!(report.total_stake, report.signed_stake);
+        assert_eq!(1.0, report.signed_stake_ratio());
+        let expected_won_lotteries = parameters.m as f64 * parameters.phi_f;
+        assert!((report.expected_won_lotteries - expected_won_lotteries).abs() < 1e-9);
+        assert!(
+            (report.quorum_margin() - (expected_won_lotteries - parameters.k as f64)).abs() < 1e-9
+        );
+    }
+
+    #[test]
+    fn participation_report_with_a_partial_participation() {
+        let (certificates, _) = setup_certificate_chain(3, 1);
+        let mut certificate = to_mithril_certificate(certificates[0].clone());
+        let full_report = CertificateParticipationReport::from_certificate(&certificate).unwrap();
+        certificate.metadata.signers.truncate(1);
+
+        let report = CertificateParticipationReport::from_certificate(&certificate).unwrap();
+
+        assert_eq!(1, report.signers_count());
+        assert!(report.signed_stake < report.total_stake);
+        assert!(report.expected_won_lotteries < full_report.expected_won_lotteries);
+        assert!(report.quorum_margin() < full_report.quorum_margin());
+    }
+
+    #[test]
+    fn participation_report_of_a_genesis_certificate_fails() {
+        let (certificates, _) = setup_certificate_chain(3, 1);
+        let genesis_certificate = to_mithril_certificate(certificates.last().unwrap().clone());
+
+        CertificateParticipationReport::from_certificate(&genesis_certificate)
+            .expect_err("a genesis certificate has no signers");
+    }
+
+    #[tokio::test]
+    async fn verify_participation_verifies_the_chain_before_reporting() {
+        let (certificates, _) = setup_certificate_chain(3, 1);
+        let certificate = to_mithril_certificate(certificates[0].clone());
+        let certificate_hash = certificate.hash.clone();
+        let mut aggregator_client = MockAggregatorHTTPClient::new();
+        aggregator_client
+            .expect_get_content()
+            .returning(move |_| Ok(serde_json::to_string(&certificate).unwrap()));
+        let mut verifier = MockCertificateVerifier::new();
+        verifier.expect_verify_chain().once().returning(|_| Ok(()));
+        let client = CertificateClient::new(
+            Arc::new(aggregator_client),
+            Arc::new(verifier),
+            test_utils::test_logger(),
+        );
+
+        let report = client
+            .verify_participation(&certificate_hash)
+            .await
+            .unwrap();
+
+        assert_eq!(certificate_hash, report.certificate_hash);
+    }
+
+    #[tokio::test]
+    async fn verify_chain_matching_checks_the_signed_protocol_message() {
+        let (certificates, _) = setup_certificate_chain(3, 1);
+        let certificate = to_mithril_certificate(certificates[0].clone());
+        let certificate_hash = certificate.hash.clone();
+        let signed_digest = certificate
+            .protocol_message
+            .get_message_part(&ProtocolMessagePartKey::SnapshotDigest)
+            .unwrap()
+            .clone();
+        let mut aggregator_client = MockAggregatorHTTPClient::new();
+        aggregator_client
+            .expect_get_content()
+            .returning(move |_| Ok(serde_json::to_string(&certificate).unwrap()));
+        let mut verifier = MockCertificateVerifier::new();
+        verifier
+            .expect_verify_chain()
+            .times(2)
+            .returning(|_| Ok(()));
+        let client = CertificateClient::new(
+            Arc::new(aggregator_client),
+            Arc::new(verifier),
+            test_utils::test_logger(),
+        );
+
+        client
+            .verify_chain_matching(
+                &certificate_hash,
+                &ProtocolMessageMatcher::SnapshotDigest(signed_digest),
+            )
+            .await
+            .unwrap();
+        let error = client
+            .verify_chain_matching(
+                &certificate_hash,
+                &ProtocolMessageMatcher::SnapshotDigest("other-digest".to_string()),
+            )
+            .await
+            .expect_err("a certificate signing another digest should not match");
+
+        assert!(
+            matches!(
+                error.downcast_ref::<ProtocolMessageMismatch>(),
+                Some(ProtocolMessageMismatch::PartMismatch { .. })
+            ),
+            "Unexpected error: {error:?}"
+        );
+    }
+
+    fn certificate_list_items(total: usize) -> Vec<MithrilCertificateListItem> {
+        (0..total)
+            .map(|index| MithrilCertificateListItem {
+                hash: format!("hash-{index}"),
+                ..MithrilCertificateListItem::dummy()
+            })
+            .collect()
+    }
+
+    fn client_listing_certificates(
+        certificates: Vec<MithrilCertificateListItem>,
+        supports_pagination: bool,
+    ) -> CertificateClient {
+        let mut aggregator_client = MockAggregatorHTTPClient::new();
+        aggregator_client
+            .expect_get_content()
+            .returning(move |request| match request {
+                AggregatorRequest::ListCertificatesPage { limit, before }
+                    if supports_pagination =>
+                {
+                    let start = before
+                        .and_then(|before| certificates.iter().position(|c| c.hash == before))
+                        .map(|position| position + 1)
+                        .unwrap_or_default();
+                    let page: Vec<_> = certificates
+                        .iter()
+                        .skip(start)
+                        .take(limit as usize)
+                        .cloned()
+                        .collect();
+                    Ok(serde_json::to_string(&page).unwrap())
+                }
+                AggregatorRequest::ListCertificatesPage { .. } => {
+                    Ok(serde_json::to_string(&certificates).unwrap())
+                }
+                _ => panic!("unexpected request: {request:?}"),
+            });
+
+        CertificateClient::new(
+            Arc::new(aggregator_client),
+            Arc::new(MockCertificateVerifier::new()),
+            test_utils::test_logger(),
+        )
+    }
+
+    #[tokio::test]
+    async fn list_page_cut_the_page_when_the_aggregator_does_not_paginate() {
+        let client = client_listing_certificates(certificate_list_items(5), false);
+
+        let page = client
+            .list_page(&CertificateListPage {
+                limit: 2,
+                before: Some("hash-1".to_string()),
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(
+            vec!["hash-2".to_string(), "hash-3".to_string()],
+            page.certificates
+                .iter()
+                .map(|c| c.hash.clone())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            Some(CertificateListPage {
+                limit: 2,
+                before: Some("hash-3".to_string())
+            }),
+            page.next_page
+        );
+    }
+
+    #[tokio::test]
+    async fn list_all_streams_every_certificate_once_with_or_without_aggregator_pagination() {
+        for supports_pagination in [true, false] {
+            let client =
+                client_listing_certificates(certificate_list_items(5), supports_pagination);
+
+            let hashes: Vec<String> = client
+                .list_all(2)
+                .map(|certificate| certificate.unwrap().hash)
+                .collect()
+                .await;
+
+            assert_eq!(
+                certificate_list_items(5)
+                    .into_iter()
+                    .map(|c| c.hash)
+                    .collect::<Vec<_>>(),
+                hashes,
+                "supports_pagination: {supports_pagination}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn verify_chain_from_latest_verifies_the_latest_certificate() {
+        let (certificates, _) = setup_certificate_chain(3, 1);
+        let certificate = to_mithril_certificate(certificates[0].clone());
+        let certificate_hash = certificate.hash.clone();
+        let list_items = vec![
+            MithrilCertificateListItem {
+                hash: certificate_hash.clone(),
+                ..MithrilCertificateListItem::dummy()
+            },
+            MithrilCertificateListItem::dummy(),
+        ];
+        let mut aggregator_client = MockAggregatorHTTPClient::new();
+        aggregator_client
+            .expect_get_content()
+            .returning(move |request| match request {
+                AggregatorRequest::ListCertificatesPage { .. } => {
+                    Ok(serde_json::to_string(&list_items).unwrap())
+                }
+                AggregatorRequest::GetCertificate { .. } => {
+                    Ok(serde_json::to_string(&certificate).unwrap())
+                }
+                _ => panic!("unexpected request: {request:?}"),
+            });
+        let mut verifier = MockCertificateVerifier::new();
+        verifier.expect_verify_chain().once().returning(|_| Ok(()));
+        let client = CertificateClient::new(
+            Arc::new(aggregator_client),
+            Arc::new(verifier),
+            test_utils::test_logger(),
+        );
+
+        let verified_certificate = client.verify_chain_from_latest().await.unwrap();
+
+        assert_eq!(certificate_hash, verified_certificate.hash);
+    }
+
+    #[tokio::test]
+    async fn verify_chain_from_latest_fails_without_any_certificate() {
+        let client = client_listing_certificates(vec![], true);
+
+        client
+            .verify_chain_from_latest()
+            .await
+            .expect_err("verify_chain_from_latest should fail without any certificate");
+    }
+
+    fn count_requests(
+        aggregator_client: &FakeAggregatorClient,
+        is_counted: fn(&AggregatorRequest) -> bool,
+    ) -> usize {
+        aggregator_client
+            .received_requests()
+            .iter()
+            .filter(|request| is_counted(request))
+            .count()
+    }
+
+    #[tokio::test]
+    async fn verify_chain_fetch_the_chain_in_a_single_request_when_bundles_are_served() {
+        let chain = CertificateChainBuilder::new()
+            .with_total_certificates(5)
+            .build()
+            .unwrap();
+        let aggregator_client = Arc::new(
+            FakeAggregatorClient::new()
+                .with_certificate(chain.latest_certificate())
+                .with_certificate_chain_bundle(&chain.certificates),
+        );
+        let client = ClientBuilder::new(&chain.genesis_verification_key)
+            .with_aggregator_client(aggregator_client.clone())
+            .with_certificate_chain_bundle(true)
+            .build()
+            .unwrap();
+
+        client
+            .certificate()
+            .verify_chain(&chain.latest_certificate().hash)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            1,
+            count_requests(&aggregator_client, |request| matches!(
+                request,
+                AggregatorRequest::GetCertificate { .. }
+            ))
+        );
+        assert_eq!(
+            1,
+            count_requests(&aggregator_client, |request| matches!(
+                request,
+                AggregatorRequest::GetCertificateChain { .. }
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn concurrent_verify_chain_calls_each_use_their_own_prefetched_bundle() {
+        let chain = CertificateChainBuilder::new()
+            .with_total_certificates(5)
+            .build()
+            .unwrap();
+        let aggregator_client = Arc::new(
+            FakeAggregatorClient::new()
+                .with_certificate(chain.latest_certificate())
+                .with_certificate_chain_bundle(&chain.certificates),
+        );
+        let client = ClientBuilder::new(&chain.genesis_verification_key)
+            .with_aggregator_client(aggregator_client.clone())
+            .with_certificate_chain_bundle(true)
+            .build()
+            .unwrap();
+        let certificate_client = client.certificate();
+
+        let (first, second) = tokio::join!(
+            certificate_client.verify_chain(&chain.latest_certificate().hash),
+            certificate_client.verify_chain(&chain.latest_certificate().hash)
+        );
+        first.unwrap();
+        second.unwrap();
+
+        assert_eq!(
+            2,
+            count_requests(&aggregator_client, |request| matches!(
+                request,
+                AggregatorRequest::GetCertificate { .. }
+            )),
+            "only the latest certificate should be fetched by each call, the rest of the chain being served from its bundle"
+        );
+    }
+
+    #[tokio::test]
+    async fn verify_chain_fetch_certificates_one_by_one_when_bundles_are_not_served() {
+        let chain = CertificateChainBuilder::new()
+            .with_total_certificates(5)
+            .build()
+            .unwrap();
+        let aggregator_client =
+            Arc::new(FakeAggregatorClient::new().with_certificate_chain(&chain.certificates));
+        let client = ClientBuilder::new(&chain.genesis_verification_key)
+            .with_aggregator_client(aggregator_client.clone())
+            .with_certificate_chain_bundle(true)
+            .build()
+            .unwrap();
+
+        for _ in 0..2 {
+            client
+                .certificate()
+                .verify_chain(&chain.latest_certificate().hash)
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(
+            1,
+            count_requests(&aggregator_client, |request| matches!(
+                request,
+                AggregatorRequest::GetCertificateChain { .. }
+            )),
+            "the aggregator should not be asked again for a bundle once it answered it doesn't serve them"
+        );
+    }
+
+    #[tokio::test]
+    async fn verify_chain_reject_a_tampered_certificate_of_a_bundle() {
+        let chain = CertificateChainBuilder::new()
+            .with_total_certificates(5)
+            .with_tampering(CertificateChainTampering::MultiSignature { position: 2 })
+            .build()
+            .unwrap();
+        let aggregator_client = Arc::new(
+            FakeAggregatorClient::new()
+                .with_certificate(chain.latest_certificate())
+                .with_certificate_chain_bundle(&chain.certificates),
+        );
+        let client = ClientBuilder::new(&chain.genesis_verification_key)
+            .with_aggregator_client(aggregator_client)
+            .with_certificate_chain_bundle(true)
+            .build()
+            .unwrap();
+
+        client
+            .certificate()
+            .verify_chain(&chain.latest_certificate().hash)
+            .await
+            .expect_err("a chain bundle with a tampered certificate should be rejected");
+    }
+}
@@ -0,0 +1,596 @@
+//! Watch the certificate chain of an aggregator over time.
+//!
+//! A [ChainWatcher] periodically fetches the latest certificate issued by the aggregator and
+//! verifies only the segment of the chain issued since the last verified certificate. The hash of
+//! the last verified certificate is persisted to disk so a restarted watcher resumes where it
+//! stopped, the first run verifies the whole chain down to the genesis certificate.
+//!
+//! Each newly verified certificate is reported with a
+//! [NewCertificateVerified][MithrilEvent::NewCertificateVerified] event, oldest first. A latest
+//! certificate whose chain is invalid or doesn't include the last verified certificate is a
+//! [regression][ChainRegression], reported with a
+//! [CertificateChainRegression][MithrilEvent::CertificateChainRegression] event: the last
+//! verified certificate is kept until a valid chain is seen again.
+//!
+//! ```no_run
+//! # async fn run() -> mithril_client::MithrilResult<()> {
+//! use mithril_client::ClientBuilder;
+//! use std::path::Path;
+//! use std::time::Duration;
+//!
+//! let client = ClientBuilder::aggregator("YOUR_AGGREGATOR_ENDPOINT", "YOUR_GENESIS_VERIFICATION_KEY").build()?;
+//! let watcher = client
+//!     .chain_watcher(Path::new("/var/lib/mithril/chain_watcher.json"))
+//!     .with_interval(Duration::from_secs(60));
+//!
+//! watcher.run().await?;
+//! #    Ok(())
+//! # }
+//! ```
+
+use anyhow::{anyhow, Context};
+use serde::{Deserialize, Serialize};
+use slog::{warn, Logger};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::certificate_client::{
+    CertificateChainVerificationState, CertificateClient, CertificateListPage,
+    CertificateVerificationDiagnostics, StepResult,
+};
+use crate::feedback::{FeedbackSender, MithrilEvent};
+use crate::utils::time;
+use crate::MithrilResult;
+
+/// Default interval between two polls of a [ChainWatcher]
+pub const DEFAULT_CHAIN_WATCHER_INTERVAL: Duration = Duration::from_secs(60);
+
+/// State of a [ChainWatcher], persisted to disk between two polls.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainWatcherState {
+    /// Hash of the last verified certificate, `None` if no certificate was verified yet
+    pub last_verified_certificate_hash: Option<String>,
+}
+
+impl ChainWatcherState {
+    /// Load the state from the given file, a default state is returned if the file doesn't
+    /// exist.
+    pub fn load(path: &Path) -> MithrilResult<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = std::fs::read(path).with_context(|| {
+            format!(
+                "Could not read chain watcher state from '{}'",
+                path.display()
+            )
+        })?;
+        serde_json::from_slice(&content).with_context(|| {
+            format!(
+                "Could not deserialize chain watcher state from '{}'",
+                path.display()
+            )
+        })
+    }
+
+    /// Persist the state to the given file.
+    pub fn save(&self, path: &Path) -> MithrilResult<()> {
+        let temp_path = path.with_extension("json.tmp");
+        std::fs::write(&temp_path, serde_json::to_vec(self)?).with_context(|| {
+            format!(
+                "Could not write chain watcher state to '{}'",
+                temp_path.display()
+            )
+        })?;
+        std::fs::rename(&temp_path, path).with_context(|| {
+            format!(
+                "Could not write chain watcher state to '{}'",
+                path.display()
+            )
+        })
+    }
+}
+
+/// A latest certificate that can't be trusted given the last verified certificate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChainRegression {
+    /// A certificate of the new segment of the chain is invalid
+    InvalidCertificate {
+        /// Hash of the latest certificate
+        latest_certificate_hash: String,
+
+        /// Diagnostics of the invalid certificate
+        diagnostics: CertificateVerificationDiagnostics,
+    },
+
+    /// The chain of the latest certificate reaches the genesis certificate without including the
+    /// last verified certificate
+    Unchained {
+        /// Hash of the latest certificate
+        latest_certificate_hash: String,
+
+        /// Hash of the last verified certificate
+        last_verified_certificate_hash: String,
+    },
+}
+
+impl ChainRegression {
+    fn latest_certificate_hash(&self) -> &str {
+        match self {
+            Self::InvalidCertificate {
+                latest_certificate_hash,
+                ..
+            }
+            | Self::Unchained {
+                latest_certificate_hash,
+                ..
+            } => latest_certificate_hash,
+        }
+    }
+
+    fn reason(&self) -> String {
+        match self {
+            Self::InvalidCertificate { diagnostics, .. } => diagnostics.to_string(),
+            Self::Unchained {
+                last_verified_certificate_hash,
+                ..
+            } => format!(
+                "The chain does not include the last verified certificate '{last_verified_certificate_hash}'"
+            ),
+        }
+    }
+}
+
+/// Outcome of a [poll][ChainWatcher::poll] of a [ChainWatcher].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChainWatcherOutcome {
+    /// No certificate was issued since the last verified certificate
+    UpToDate {
+        /// Hash of the last verified certificate
+        certificate_hash: String,
+    },
+
+    /// The certificates issued since the last verified certificate were verified
+    Verified {
+        /// Hashes of the newly verified certificates, oldest first
+        certificate_hashes: Vec<String>,
+    },
+
+    /// The latest certificate can't be trusted
+    Regression(ChainRegression),
+}
+
+/// Long-running component verifying the new certificates issued by an aggregator.
+pub struct ChainWatcher {
+    certificate_client: Arc<CertificateClient>,
+    feedback_sender: FeedbackSender,
+    state_path: PathBuf,
+    interval: Duration,
+    logger: Logger,
+}
+
+impl ChainWatcher {
+    /// Constructs a new `ChainWatcher` persisting its state in the given file.
+    pub fn new(
+        certificate_client: Arc<CertificateClient>,
+        feedback_sender: FeedbackSender,
+        state_path: &Path,
+        logger: Logger,
+    ) -> Self {
+        Self {
+            certificate_client,
+            feedback_sender,
+            state_path: state_path.to_path_buf(),
+            interval: DEFAULT_CHAIN_WATCHER_INTERVAL,
+            logger,
+        }
+    }
+
+    /// Set the interval between two polls of the aggregator.
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Load the persisted state of the watcher.
+    pub fn state(&self) -> MithrilResult<ChainWatcherState> {
+        ChainWatcherState::load(&self.state_path)
+    }
+
+    /// Poll the aggregator every [interval][Self::with_interval], forever.
+    ///
+    /// The errors of a poll (ie: the aggregator being unreachable) are logged and the polling
+    /// goes on, only an error to persist the state stops the watcher.
+    pub async fn run(&self) -> MithrilResult<()> {
+        loop {
+            if let Err(error) = self.poll().await {
+                if error.downcast_ref::<StatePersistenceError>().is_some() {
+                    return Err(error);
+                }
+                warn!(self.logger, "Chain watcher poll failed"; "error" => ?error);
+            }
+            time::sleep(self.interval).await;
+        }
+    }
+
+    /// Fetch the latest certificate and verify the segment of its chain issued since the last
+    /// verified certificate.
+    pub async fn poll(&self) -> MithrilResult<ChainWatcherOutcome> {
+        let state = self.state().map_err(StatePersistenceError)?;
+        let latest_certificate_hash = self.fetch_latest_certificate_hash().await?;
+        if state.last_verified_certificate_hash.as_ref() == Some(&latest_certificate_hash) {
+            return Ok(ChainWatcherOutcome::UpToDate {
+                certificate_hash: latest_certificate_hash,
+            });
+        }
+
+        let outcome = match self
+            .verify_segment(&latest_certificate_hash, &state)
+            .await?
+        {
+            Ok(mut certificate_hashes) => {
+                certificate_hashes.reverse();
+                ChainWatcherState {
+                    last_verified_certificate_hash: Some(latest_certificate_hash),
+                }
+                .save(&self.state_path)
+                .map_err(StatePersistenceError)?;
+                for certificate_hash in &certificate_hashes {
+                    self.feedback_sender
+                        .send_event(MithrilEvent::NewCertificateVerified {
+                            certificate_hash: certificate_hash.clone(),
+                        })
+                        .await;
+                }
+
+                ChainWatcherOutcome::Verified { certificate_hashes }
+            }
+            Err(regression) => {
+                self.feedback_sender
+                    .send_event(MithrilEvent::CertificateChainRegression {
+                        certificate_hash: regression.latest_certificate_hash().to_string(),
+                        last_verified_certificate_hash: state.last_verified_certificate_hash,
+                        reason: regression.reason(),
+                    })
+                    .await;
+
+                ChainWatcherOutcome::Regression(regression)
+            }
+        };
+
+        Ok(outcome)
+    }
+
+    async fn fetch_latest_certificate_hash(&self) -> MithrilResult<String> {
+        let page = self
+            .certificate_client
+            .list_page(&CertificateListPage::first(1))
+            .await
+            .with_context(|| "Chain watcher can not get the latest certificate")?;
+
+        page.certificates
+            .first()
+            .map(|certificate| certificate.hash.clone())
+            .ok_or(anyhow!("No certificate issued by the aggregator"))
+    }
+
+    /// Verify the chain from the given latest certificate down to the last verified certificate,
+    /// returns the hashes of the verified certificates, latest first.
+    async fn verify_segment(
+        &self,
+        latest_certificate_hash: &str,
+        state: &ChainWatcherState,
+    ) -> MithrilResult<Result<Vec<String>, ChainRegression>> {
+        let mut verification = CertificateChainVerificationState::new(latest_certificate_hash, 1);
+        let mut certificate_hashes = vec![];
+
+        loop {
+            if verification.next_certificate_hash == state.last_verified_certificate_hash {
+                return Ok(Ok(certificate_hashes));
+            }
+            if let Some(next_certificate_hash) = &verification.next_certificate_hash {
+                certificate_hashes.push(next_certificate_hash.clone());
+            }
+
+            match self
+                .certificate_client
+                .verify_chain_step(verification)
+                .await
+            {
+                Ok(StepResult::InProgress(next_verification)) => verification = next_verification,
+                Ok(StepResult::Completed(_)) => {
+                    return Ok(match &state.last_verified_certificate_hash {
+                        Some(last_verified_certificate_hash) => Err(ChainRegression::Unchained {
+                            latest_certificate_hash: latest_certificate_hash.to_string(),
+                            last_verified_certificate_hash: last_verified_certificate_hash.clone(),
+                        }),
+                        None => Ok(certificate_hashes),
+                    });
+                }
+                Err(error) => {
+                    return match error.downcast_ref::<CertificateVerificationDiagnostics>() {
+                        Some(diagnostics) => Ok(Err(ChainRegression::InvalidCertificate {
+                            latest_certificate_hash: latest_certificate_hash.to_string(),
+                            diagnostics: diagnostics.clone(),
+                        })),
+                        None => Err(error),
+                    };
+                }
+            }
+        }
+    }
+}
+
+/// Error raised when the state of a [ChainWatcher] can't be loaded or persisted.
+#[derive(Debug, thiserror::Error)]
+#[error("Chain watcher state persistence failed")]
+struct StatePersistenceError(#[source] anyhow::Error);
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use crate::aggregator_client::{AggregatorRequest, MockAggregatorHTTPClient};
+    use crate::certificate_client::{CertificateVerificationCheck, MockCertificateVerifier};
+    use crate::common::entities::Epoch;
+    use crate::feedback::StackFeedbackReceiver;
+    use crate::{test_utils, MithrilCertificateListItem};
+
+    use super::*;
+
+    fn get_test_state_path(test_name: &str) -> PathBuf {
+        test_utils::get_test_directory("chain_watcher", test_name).join("state.json")
+    }
+
+    /// A fake aggregator serving a chain of certificates, given as `(hash, previous_hash)` latest
+    /// first, the previous hash of the genesis certificate being empty.
+    #[derive(Clone, Default)]
+    struct FakeChain {
+        certificates: Arc<Mutex<Vec<(String, String)>>>,
+        verified_certificates: Arc<Mutex<Vec<String>>>,
+        invalid_certificate: Arc<Mutex<Option<String>>>,
+    }
+
+    impl FakeChain {
+        fn new(hashes: &[&str]) -> Self {
+            let chain = Self::default();
+            chain.set_chain(hashes);
+            chain
+        }
+
+        /// Chain the given hashes, latest first, down to a genesis certificate.
+        fn set_chain(&self, hashes: &[&str]) {
+            let mut certificates = self.certificates.lock().unwrap();
+            *certificates = hashes
+                .iter()
+                .enumerate()
+                .map(|(index, hash)| {
+                    let previous_hash = hashes.get(index + 1).copied().unwrap_or_default();
+                    (hash.to_string(), previous_hash.to_string())
+                })
+                .collect();
+        }
+
+        fn verified_certificates(&self) -> Vec<String> {
+            self.verified_certificates.lock().unwrap().clone()
+        }
+
+        fn certificate_client(&self) -> Arc<CertificateClient> {
+            let chain = self.clone();
+            let mut aggregator_client = MockAggregatorHTTPClient::new();
+            aggregator_client
+                .expect_get_content()
+                .returning(move |request| match request {
+                    AggregatorRequest::ListCertificatesPage { .. } => {
+                        let items: Vec<_> = chain
+                            .certificates
+                            .lock()
+                            .unwrap()
+                            .iter()
+                            .map(|(hash, _)| MithrilCertificateListItem {
+                                hash: hash.clone(),
+                                ..MithrilCertificateListItem::dummy()
+                            })
+                            .collect();
+                        Ok(serde_json::to_string(&items).unwrap())
+                    }
+                    _ => panic!("unexpected request: {request:?}"),
+                });
+
+            let chain = self.clone();
+            let mut verifier = MockCertificateVerifier::new();
+            verifier
+                .expect_verify_chain_step()
+                .returning(move |mut state| {
+                    let hash = state.next_certificate_hash.clone().unwrap();
+                    if chain.invalid_certificate.lock().unwrap().as_ref() == Some(&hash) {
+                        return Err(anyhow!("invalid multi-signature").context(
+                            CertificateVerificationDiagnostics {
+                                certificate_hash: hash,
+                                epoch: Epoch(1),
+                                failed_check: CertificateVerificationCheck::MultiSignature,
+                                previous_certificate_hash: String::new(),
+                                aggregate_verification_key: None,
+                                previous_aggregate_verification_key: None,
+                                previous_next_aggregate_verification_key: None,
+                            },
+                        ));
+                    }
+                    let previous_hash = chain
+                        .certificates
+                        .lock()
+                        .unwrap()
+                        .iter()
+                        .find(|(certificate_hash, _)| certificate_hash == &hash)
+                        .map(|(_, previous_hash)| previous_hash.clone())
+                        .unwrap();
+                    chain.verified_certificates.lock().unwrap().push(hash);
+                    state.verified_certificates += 1;
+                    if previous_hash.is_empty() {
+                        state.next_certificate_hash = None;
+                        Ok(StepResult::Completed(state))
+                    } else {
+                        state.next_certificate_hash = Some(previous_hash);
+                        Ok(StepResult::InProgress(state))
+                    }
+                });
+
+            Arc::new(CertificateClient::new(
+                Arc::new(aggregator_client),
+                Arc::new(verifier),
+                test_utils::test_logger(),
+            ))
+        }
+    }
+
+    fn build_watcher(
+        chain: &FakeChain,
+        state_path: &Path,
+        feedback_receiver: Arc<StackFeedbackReceiver>,
+    ) -> ChainWatcher {
+        ChainWatcher::new(
+            chain.certificate_client(),
+            FeedbackSender::new(&[feedback_receiver]),
+            state_path,
+            test_utils::test_logger(),
+        )
+    }
+
+    fn new_certificate_events(hashes: &[&str]) -> Vec<MithrilEvent> {
+        hashes
+            .iter()
+            .map(|hash| MithrilEvent::NewCertificateVerified {
+                certificate_hash: hash.to_string(),
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn first_poll_verifies_the_whole_chain_and_persists_the_latest_certificate() {
+        let state_path =
+            get_test_state_path("first_poll_verifies_the_whole_chain_and_persists_the_latest");
+        let chain = FakeChain::new(&["c3", "c2", "c1"]);
+        let feedback_receiver = Arc::new(StackFeedbackReceiver::new());
+        let watcher = build_watcher(&chain, &state_path, feedback_receiver.clone());
+
+        let outcome = watcher.poll().await.unwrap();
+
+        assert_eq!(
+            ChainWatcherOutcome::Verified {
+                certificate_hashes: vec!["c1".to_string(), "c2".to_string(), "c3".to_string()]
+            },
+            outcome
+        );
+        assert_eq!(
+            ChainWatcherState {
+                last_verified_certificate_hash: Some("c3".to_string())
+            },
+            ChainWatcherState::load(&state_path).unwrap()
+        );
+        assert_eq!(
+            new_certificate_events(&["c1", "c2", "c3"]),
+            feedback_receiver.stacked_events()
+        );
+
+        let outcome = watcher.poll().await.unwrap();
+
+        assert_eq!(
+            ChainWatcherOutcome::UpToDate {
+                certificate_hash: "c3".to_string()
+            },
+            outcome
+        );
+    }
+
+    #[tokio::test]
+    async fn poll_verifies_only_the_segment_issued_since_the_last_verified_certificate() {
+        let state_path = get_test_state_path("poll_verifies_only_the_new_segment");
+        ChainWatcherState {
+            last_verified_certificate_hash: Some("c3".to_string()),
+        }
+        .save(&state_path)
+        .unwrap();
+        let chain = FakeChain::new(&["c5", "c4", "c3", "c2", "c1"]);
+        let feedback_receiver = Arc::new(StackFeedbackReceiver::new());
+        let watcher = build_watcher(&chain, &state_path, feedback_receiver.clone());
+
+        let outcome = watcher.poll().await.unwrap();
+
+        assert_eq!(
+            ChainWatcherOutcome::Verified {
+                certificate_hashes: vec!["c4".to_string(), "c5".to_string()]
+            },
+            outcome
+        );
+        assert_eq!(
+            vec!["c5".to_string(), "c4".to_string()],
+            chain.verified_certificates()
+        );
+        assert_eq!(
+            new_certificate_events(&["c4", "c5"]),
+            feedback_receiver.stacked_events()
+        );
+    }
+
+    #[tokio::test]
+    async fn a_chain_not_including_the_last_verified_certificate_is_a_regression() {
+        let state_path = get_test_state_path("a_chain_not_including_the_last_verified");
+        let chain = FakeChain::new(&["c2", "c1"]);
+        let feedback_receiver = Arc::new(StackFeedbackReceiver::new());
+        let watcher = build_watcher(&chain, &state_path, feedback_receiver.clone());
+        watcher.poll().await.unwrap();
+
+        chain.set_chain(&["fork2", "fork1", "c1"]);
+        let outcome = watcher.poll().await.unwrap();
+
+        assert_eq!(
+            ChainWatcherOutcome::Regression(ChainRegression::Unchained {
+                latest_certificate_hash: "fork2".to_string(),
+                last_verified_certificate_hash: "c2".to_string(),
+            }),
+            outcome
+        );
+        assert_eq!(
+            Some("c2".to_string()),
+            watcher.state().unwrap().last_verified_certificate_hash
+        );
+        assert!(
+            matches!(
+                feedback_receiver.stacked_events().last(),
+                Some(MithrilEvent::CertificateChainRegression { certificate_hash, .. })
+                    if certificate_hash == "fork2"
+            ),
+            "Unexpected events: {:?}",
+            feedback_receiver.stacked_events()
+        );
+    }
+
+    #[tokio::test]
+    async fn an_invalid_certificate_in_the_new_segment_is_a_regression() {
+        let state_path = get_test_state_path("an_invalid_certificate_in_the_new_segment");
+        let chain = FakeChain::new(&["c2", "c1"]);
+        let watcher = build_watcher(&chain, &state_path, Arc::new(StackFeedbackReceiver::new()));
+        watcher.poll().await.unwrap();
+
+        chain.set_chain(&["c4", "c3", "c2", "c1"]);
+        *chain.invalid_certificate.lock().unwrap() = Some("c3".to_string());
+        let outcome = watcher.poll().await.unwrap();
+
+        match outcome {
+            ChainWatcherOutcome::Regression(ChainRegression::InvalidCertificate {
+                latest_certificate_hash,
+                diagnostics,
+            }) => {
+                assert_eq!("c4", latest_certificate_hash);
+                assert_eq!("c3", diagnostics.certificate_hash);
+            }
+            outcome => panic!("Unexpected outcome: {outcome:?}"),
+        }
+        assert_eq!(
+            Some("c2".to_string()),
+            watcher.state().unwrap().last_verified_certificate_hash
+        );
+    }
+}
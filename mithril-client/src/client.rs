@@ -1,54 +1,521 @@
-use crate::aggregator_client::{AggregatorClient, AggregatorHTTPClient};
+use crate::aggregator_client::{
+    AggregatorClient, AggregatorClientError, AggregatorHTTPClient, AggregatorProbe,
+    AggregatorProbeReport, AggregatorRequest,
+};
 use crate::certificate_client::{
-    CertificateClient, CertificateVerifier, MithrilCertificateVerifier,
+    CertificateClient, CertificateRetriever, CertificateVerifier, MithrilCertificateVerifier,
+    SignerSampling,
 };
+#[cfg(feature = "fs")]
+use crate::chain_watcher::ChainWatcher;
 use crate::common::api_version::APIVersionProvider;
+#[cfg(feature = "fs")]
+use crate::common::digesters::cache::ImmutableFileDigestCacheProvider;
+#[cfg(feature = "fs")]
+use crate::common::digesters::{CardanoImmutableDigester, ImmutableDigester};
+use crate::common::era::{EraReader, EraReaderAdapter};
+use crate::common::messages::AggregatorFeaturesMessage;
+use crate::compute::ComputeExecutor;
+#[cfg(not(target_family = "wasm"))]
+use crate::connection_pool::ConnectionPoolConfig;
+#[cfg(not(target_family = "wasm"))]
+use crate::dns::DnsConfig;
+use crate::epoch_settings_client::EpochSettingsClient;
+use crate::era::EraCompatibility;
+use crate::failover::FailoverAggregatorClient;
 use crate::feedback::{FeedbackReceiver, FeedbackSender};
+#[cfg(feature = "logging")]
+use crate::logging::LogOptions;
+use crate::metrics::{MeteredAggregatorClient, MetricsRecorder};
 use crate::mithril_stake_distribution_client::MithrilStakeDistributionClient;
+use crate::offline_store::{
+    store_verified, unpin, OfflineAggregatorClient, OfflineStore, PrefetchPlan, PrefetchReport,
+};
+use crate::protocol_message_matcher::ProtocolMessageMatcher;
+#[cfg(not(target_family = "wasm"))]
+use crate::proxy::ProxyConfig;
+#[cfg(feature = "fs")]
+use crate::recording::RecordingAggregatorClient;
+use crate::retry::{RetryPolicy, RetryingAggregatorClient};
+use crate::schema_validation::{OpenApiSchemaValidator, SchemaValidatingAggregatorClient};
+use crate::signed_entity::SignedEntityRegistry;
 use crate::snapshot_client::SnapshotClient;
 #[cfg(feature = "fs")]
-use crate::snapshot_downloader::{HttpSnapshotDownloader, SnapshotDownloader};
-use crate::MithrilResult;
+use crate::snapshot_downloader::{HttpSnapshotDownloader, SnapshotDownloader, UnpackOptions};
+use crate::snapshot_validation::SnapshotValidationPolicy;
+#[cfg(not(target_family = "wasm"))]
+use crate::tls::{SpkiPins, TlsConfig};
+use crate::{MessageBuilder, MithrilCertificate, MithrilResult};
 use anyhow::{anyhow, Context};
 use reqwest::Url;
-use slog::{o, Logger};
+use serde::de::DeserializeOwned;
+use slog::{o, warn, Logger};
+use std::collections::HashMap;
+#[cfg(feature = "fs")]
+use std::path::Path;
 use std::sync::Arc;
+use std::time::Duration;
 
 /// Structure that aggregates the available clients for each of the Mithril types of certified data.
 ///
 /// Use the [ClientBuilder] to instantiate it easily.
+///
+/// A `Client` is cheap to clone and can be sent across tasks and threads: its clones share the
+/// same state, including the pooled connections to the aggregator, so a single client can serve
+/// many simultaneous verifications, ie: in a multi-tenant service. The pool can be tuned with
+/// [ClientBuilder::with_connection_pool].
+#[derive(Clone)]
 pub struct Client {
+    inner: Arc<ClientInner>,
+}
+
+struct ClientInner {
     certificate_client: Arc<CertificateClient>,
     mithril_stake_distribution_client: Arc<MithrilStakeDistributionClient>,
     snapshot_client: Arc<SnapshotClient>,
+    epoch_settings_client: Arc<EpochSettingsClient>,
+    aggregator_client: Arc<dyn AggregatorClient>,
+    era_reader: Option<EraReader>,
+    offline_store: Option<Arc<dyn OfflineStore>>,
+    signed_entity_registry: SignedEntityRegistry,
+    compute_executor: ComputeExecutor,
+    #[cfg(feature = "fs")]
+    feedback_sender: FeedbackSender,
+    #[cfg(feature = "fs")]
+    immutable_digester: Arc<dyn ImmutableDigester>,
+    #[cfg(feature = "fs")]
+    metrics_recorder: Option<Arc<dyn MetricsRecorder>>,
+    logger: Logger,
 }
 
 impl Client {
     /// Get the client that fetches and verifies Mithril certificates.
     pub fn certificate(&self) -> Arc<CertificateClient> {
-        self.certificate_client.clone()
+        self.inner.certificate_client.clone()
     }
 
     /// Get the client that fetches Mithril stake distributions.
     pub fn mithril_stake_distribution(&self) -> Arc<MithrilStakeDistributionClient> {
-        self.mithril_stake_distribution_client.clone()
+        self.inner.mithril_stake_distribution_client.clone()
     }
 
     /// Get the client that fetches and downloads Mithril snapshots.
     pub fn snapshot(&self) -> Arc<SnapshotClient> {
-        self.snapshot_client.clone()
+        self.inner.snapshot_client.clone()
+    }
+
+    /// Get the client that fetches the epoch settings and watches the changes of the protocol
+    /// parameters.
+    pub fn epoch_settings(&self) -> Arc<EpochSettingsClient> {
+        self.inner.epoch_settings_client.clone()
+    }
+
+    /// Get a [ChainWatcher] verifying the new certificates issued by the aggregator, persisting
+    /// its state in the given file.
+    #[cfg(feature = "fs")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "fs")))]
+    pub fn chain_watcher(&self, state_path: &Path) -> ChainWatcher {
+        ChainWatcher::new(
+            self.inner.certificate_client.clone(),
+            self.inner.feedback_sender.clone(),
+            state_path,
+            self.inner.logger.clone(),
+        )
+    }
+
+    /// Get a [MessageBuilder] configured like this client: it uses its logger, metrics recorder,
+    /// feedback receivers, [signed entity registry][Client::signed_entity_registry] and the
+    /// [ImmutableDigester] set with [ClientBuilder::with_immutable_digester].
+    #[cfg_attr(not(feature = "fs"), allow(clippy::let_and_return))]
+    pub fn message_builder(&self) -> MessageBuilder {
+        let message_builder = MessageBuilder::new()
+            .with_logger(self.inner.logger.clone())
+            .with_signed_entity_registry(self.inner.signed_entity_registry.clone())
+            .with_compute_executor(self.inner.compute_executor.clone());
+        #[cfg(feature = "fs")]
+        let message_builder =
+            message_builder.with_immutable_digester(self.inner.immutable_digester.clone());
+        #[cfg(feature = "fs")]
+        let message_builder = match &self.inner.metrics_recorder {
+            Some(metrics_recorder) => {
+                message_builder.with_metrics_recorder(metrics_recorder.clone())
+            }
+            None => message_builder,
+        };
+        #[cfg(feature = "fs")]
+        let message_builder =
+            message_builder.with_feedback_sender(self.inner.feedback_sender.clone());
+
+        message_builder
+    }
+
+    /// Get the features advertised by the aggregator: the version of its Open API specification
+    /// and its [capabilities][crate::common::messages::AggregatorCapabilities] (enabled signed entity types, served
+    /// routes, Cardano node version, ...).
+    ///
+    /// Applications can use it to adapt to the aggregator, ie: hide the Cardano transactions
+    /// features when they are not [supported][crate::common::messages::AggregatorCapabilities::supports_cardano_transactions].
+    pub async fn aggregator_capabilities(&self) -> MithrilResult<AggregatorFeaturesMessage> {
+        let content = self
+            .inner
+            .aggregator_client
+            .get_content(AggregatorRequest::GetAggregatorFeatures)
+            .await
+            .with_context(|| "Client can not get the aggregator capabilities")?;
+
+        serde_json::from_str(&content)
+            .with_context(|| "Client can not deserialize the aggregator capabilities")
+    }
+
+    /// Check that this version of the client supports the current era of the aggregator and the
+    /// next era if one is announced, see [era][crate::era].
+    ///
+    /// Fails if the client was built without an era reader adapter (see
+    /// [ClientBuilder::with_era_reader_adapter]).
+    pub async fn check_era_compatibility(&self) -> MithrilResult<EraCompatibility> {
+        let era_reader = self.inner.era_reader.as_ref().ok_or(anyhow!(
+            "Can not check era compatibility: no era reader adapter set, use `ClientBuilder::with_era_reader_adapter`"
+        ))?;
+
+        crate::era::check_era_compatibility(self.inner.aggregator_client.as_ref(), era_reader).await
+    }
+
+    /// Get the registry of the signed entities known by the client.
+    pub fn signed_entity_registry(&self) -> &SignedEntityRegistry {
+        &self.inner.signed_entity_registry
+    }
+
+    /// List the artifacts of the registered [signed entity][crate::signed_entity] with the given
+    /// name.
+    ///
+    /// Fails if the signed entity is not registered or if its artifacts are not published.
+    pub async fn list_signed_entity_artifacts<T: DeserializeOwned>(
+        &self,
+        signed_entity_name: &str,
+    ) -> MithrilResult<Vec<T>> {
+        let route = self
+            .inner
+            .signed_entity_registry
+            .get_or_fail(signed_entity_name)?
+            .artifacts_route()
+            .ok_or_else(|| {
+                anyhow!("The artifacts of signed entity '{signed_entity_name}' are not published")
+            })?;
+        let content = self
+            .inner
+            .aggregator_client
+            .get_content(AggregatorRequest::ListSignedEntityArtifacts { route })
+            .await?;
+
+        serde_json::from_str(&content).with_context(|| {
+            format!("Could not deserialize the artifacts of signed entity '{signed_entity_name}'")
+        })
+    }
+
+    /// Get the artifact with the given identifier of the registered
+    /// [signed entity][crate::signed_entity] with the given name, `None` if it doesn't exist.
+    ///
+    /// Fails if the signed entity is not registered or if its artifacts are not published.
+    pub async fn get_signed_entity_artifact<T: DeserializeOwned>(
+        &self,
+        signed_entity_name: &str,
+        artifact_id: &str,
+    ) -> MithrilResult<Option<T>> {
+        let route = self
+            .inner
+            .signed_entity_registry
+            .get_or_fail(signed_entity_name)?
+            .artifact_route(artifact_id)
+            .ok_or_else(|| {
+                anyhow!("The artifacts of signed entity '{signed_entity_name}' are not published")
+            })?;
+        match self
+            .inner
+            .aggregator_client
+            .get_content(AggregatorRequest::GetSignedEntityArtifact { route })
+            .await
+        {
+            Ok(content) => serde_json::from_str(&content).map(Some).with_context(|| {
+                format!(
+                    "Could not deserialize the artifact of signed entity '{signed_entity_name}'"
+                )
+            }),
+            Err(AggregatorClientError::RemoteServerLogical(_)) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Probe the aggregators at the given endpoints and rank them by availability, freshness of
+    /// their latest certificate and latency.
+    ///
+    /// The aggregators are probed concurrently, an invalid endpoint is reported as unreachable.
+    pub async fn probe_aggregators(&self, endpoints: &[&str]) -> AggregatorProbeReport {
+        let probes = endpoints.iter().map(|endpoint| async move {
+            let aggregator_client = Url::parse(endpoint)
+                .with_context(|| format!("Invalid aggregator endpoint: '{endpoint}'"))
+                .and_then(|url| {
+                    AggregatorHTTPClient::new(
+                        url,
+                        APIVersionProvider::compute_all_versions_sorted()
+                            .with_context(|| "Could not compute aggregator api versions")?,
+                        self.inner.logger.clone(),
+                    )
+                });
+
+            match aggregator_client {
+                Ok(aggregator_client) => aggregator_client.probe().await,
+                Err(error) => AggregatorProbe::failure(endpoint, error),
+            }
+        });
+
+        AggregatorProbeReport::new(futures::future::join_all(probes).await)
+    }
+
+    /// Download and verify the artifacts declared in the given [PrefetchPlan], then pin them in
+    /// the [OfflineStore] so they can be served while the aggregator can't be reached.
+    ///
+    /// The certificate chains of the prefetched artifacts are verified once and their
+    /// certificates are pinned with them. The Mithril stake distributions and the snapshots are
+    /// also checked against the message signed by their certificate. The artifacts are only
+    /// stored once verified. The lists of artifacts are stored unpinned, so they are only served
+    /// while they are younger than the
+    /// [maximum age][ClientBuilder::with_offline_store_max_age] of the store contents.
+    ///
+    /// Fails if the client was built without an offline store (see
+    /// [ClientBuilder::with_offline_store]).
+    pub async fn prefetch(&self, plan: PrefetchPlan) -> MithrilResult<PrefetchReport> {
+        let store = self.offline_store()?;
+        let mut report = PrefetchReport::default();
+        let mut verified_certificates = HashMap::new();
+
+        if plan.latest_certificates > 0 {
+            let certificates = self.inner.certificate_client.list().await?;
+            for certificate in certificates.iter().take(plan.latest_certificates) {
+                self.prefetch_certificate_chain(
+                    store,
+                    &certificate.hash,
+                    &mut verified_certificates,
+                    &mut report,
+                )
+                .await
+                .with_context(|| {
+                    format!("Prefetch of certificate '{}' failed", certificate.hash)
+                })?;
+            }
+            store_verified(
+                store,
+                &AggregatorRequest::ListCertificates,
+                &certificates,
+                false,
+            )?;
+        }
+
+        if plan.latest_mithril_stake_distributions > 0 {
+            let mithril_stake_distributions =
+                self.inner.mithril_stake_distribution_client.list().await?;
+            for item in mithril_stake_distributions
+                .iter()
+                .take(plan.latest_mithril_stake_distributions)
+            {
+                let mithril_stake_distribution = self
+                    .inner
+                    .mithril_stake_distribution_client
+                    .get(&item.hash)
+                    .await?
+                    .ok_or(anyhow!(
+                        "Prefetch failed: no Mithril stake distribution exist for hash '{}'",
+                        item.hash
+                    ))?;
+                let certificate = self
+                    .prefetch_certificate_chain(
+                        store,
+                        &mithril_stake_distribution.certificate_hash,
+                        &mut verified_certificates,
+                        &mut report,
+                    )
+                    .await
+                    .with_context(|| {
+                        format!(
+                            "Prefetch of Mithril stake distribution '{}' failed",
+                            item.hash
+                        )
+                    })?;
+                self.inner
+                    .mithril_stake_distribution_client
+                    .verify(&mithril_stake_distribution, &certificate)
+                    .with_context(|| "Prefetch failed")?;
+                store_verified(
+                    store,
+                    &AggregatorRequest::GetMithrilStakeDistribution {
+                        hash: item.hash.clone(),
+                    },
+                    &mithril_stake_distribution,
+                    true,
+                )?;
+                report.mithril_stake_distributions.push(item.hash.clone());
+            }
+            store_verified(
+                store,
+                &AggregatorRequest::ListMithrilStakeDistributions,
+                &mithril_stake_distributions,
+                false,
+            )?;
+        }
+
+        if plan.latest_snapshots > 0 {
+            let snapshots = self.inner.snapshot_client.list().await?;
+            for item in snapshots.iter().take(plan.latest_snapshots) {
+                let snapshot =
+                    self.inner
+                        .snapshot_client
+                        .get(&item.digest)
+                        .await?
+                        .ok_or(anyhow!(
+                            "Prefetch failed: no snapshot exist for digest '{}'",
+                            item.digest
+                        ))?;
+                let certificate = self
+                    .prefetch_certificate_chain(
+                        store,
+                        &snapshot.certificate_hash,
+                        &mut verified_certificates,
+                        &mut report,
+                    )
+                    .await
+                    .with_context(|| format!("Prefetch of snapshot '{}' failed", item.digest))?;
+                ProtocolMessageMatcher::snapshot(&snapshot)
+                    .matches(&certificate)
+                    .with_context(|| format!("Prefetch of snapshot '{}' failed", item.digest))?;
+                store_verified(
+                    store,
+                    &AggregatorRequest::GetSnapshot {
+                        digest: item.digest.clone(),
+                    },
+                    &snapshot,
+                    true,
+                )?;
+                report.snapshots.push(item.digest.clone());
+            }
+            store_verified(store, &AggregatorRequest::ListSnapshots, &snapshots, false)?;
+        }
+
+        Ok(report)
+    }
+
+    /// Unpin the artifacts of the given [PrefetchReport] in the [OfflineStore], so they are only
+    /// served while they are younger than the
+    /// [maximum age][ClientBuilder::with_offline_store_max_age] of the store contents.
+    ///
+    /// Fails if the client was built without an offline store (see
+    /// [ClientBuilder::with_offline_store]).
+    pub fn unpin(&self, report: &PrefetchReport) -> MithrilResult<()> {
+        let store = self.offline_store()?;
+        for request in report.pinned_requests() {
+            unpin(store, &request)?;
+        }
+
+        Ok(())
+    }
+
+    fn offline_store(&self) -> MithrilResult<&dyn OfflineStore> {
+        self.inner.offline_store.as_deref().ok_or(anyhow!(
+            "No offline store set, use `ClientBuilder::with_offline_store`"
+        ))
+    }
+
+    /// Get the certificate with the given hash if it was already verified, else verify its chain
+    /// and pin the certificates of the chain in the store.
+    async fn prefetch_certificate_chain(
+        &self,
+        store: &dyn OfflineStore,
+        certificate_hash: &str,
+        verified_certificates: &mut HashMap<String, MithrilCertificate>,
+        report: &mut PrefetchReport,
+    ) -> MithrilResult<MithrilCertificate> {
+        if let Some(certificate) = verified_certificates.get(certificate_hash) {
+            return Ok(certificate.clone());
+        }
+
+        let chain = self
+            .inner
+            .certificate_client
+            .verify_chain_certificates(certificate_hash)
+            .await?;
+        for certificate in chain {
+            if verified_certificates.contains_key(&certificate.hash) {
+                continue;
+            }
+            store_verified(
+                store,
+                &AggregatorRequest::GetCertificate {
+                    hash: certificate.hash.clone(),
+                },
+                &certificate,
+                true,
+            )?;
+            report.certificates.push(certificate.hash.clone());
+            verified_certificates.insert(certificate.hash.clone(), certificate);
+        }
+
+        verified_certificates
+            .get(certificate_hash)
+            .cloned()
+            .ok_or(anyhow!(
+                "The verified chain of certificate '{certificate_hash}' does not contain it"
+            ))
     }
 }
 
 /// Builder than can be used to create a [Client] easily or with custom dependencies.
 pub struct ClientBuilder {
     aggregator_endpoint: Option<String>,
+    fallback_aggregator_endpoints: Vec<String>,
     genesis_verification_key: String,
     aggregator_client: Option<Arc<dyn AggregatorClient>>,
     certificate_verifier: Option<Arc<dyn CertificateVerifier>>,
+    certificate_retriever: Option<Arc<dyn CertificateRetriever>>,
+    signer_sampling: Option<SignerSampling>,
+    max_epoch_gap: Option<u64>,
+    parallel_signature_verification: bool,
+    certificate_chain_bundle: bool,
+    strict_schema_validation: bool,
     #[cfg(feature = "fs")]
     snapshot_downloader: Option<Arc<dyn SnapshotDownloader>>,
+    #[cfg(feature = "fs")]
+    unpack_options: UnpackOptions,
+    #[cfg(feature = "fs")]
+    resumable_downloads: bool,
+    #[cfg(feature = "fs")]
+    immutable_digest_cache: Option<Arc<dyn ImmutableFileDigestCacheProvider>>,
+    #[cfg(feature = "fs")]
+    immutable_digester: Option<Arc<dyn ImmutableDigester>>,
+    #[cfg(feature = "fs")]
+    recording_directory: Option<std::path::PathBuf>,
+    offline_store: Option<Arc<dyn OfflineStore>>,
+    offline_store_max_age: Option<Duration>,
+    era_reader_adapter: Option<Arc<dyn EraReaderAdapter>>,
+    signed_entity_registry: SignedEntityRegistry,
+    snapshot_validation_policy: SnapshotValidationPolicy,
+    metrics_recorder: Option<Arc<dyn MetricsRecorder>>,
+    #[cfg(feature = "compute_threads")]
+    compute_threads: Option<usize>,
+    request_timeout: Option<Duration>,
+    compression: bool,
+    conditional_requests: bool,
+    retry_policy: Option<RetryPolicy>,
+    #[cfg(not(target_family = "wasm"))]
+    proxy: Option<ProxyConfig>,
+    #[cfg(not(target_family = "wasm"))]
+    tls: Option<TlsConfig>,
+    #[cfg(not(target_family = "wasm"))]
+    connection_pool: Option<ConnectionPoolConfig>,
+    #[cfg(not(target_family = "wasm"))]
+    dns: Option<DnsConfig>,
+    #[cfg(not(target_family = "wasm"))]
+    aggregator_spki_pins: Option<SpkiPins>,
     logger: Option<Logger>,
+    #[cfg(feature = "logging")]
+    log_options: Option<LogOptions>,
     feedback_receivers: Vec<Arc<dyn FeedbackReceiver>>,
 }
 
@@ -58,16 +525,71 @@ impl ClientBuilder {
     pub fn aggregator(endpoint: &str, genesis_verification_key: &str) -> ClientBuilder {
         Self {
             aggregator_endpoint: Some(endpoint.to_string()),
+            fallback_aggregator_endpoints: vec![],
             genesis_verification_key: genesis_verification_key.to_string(),
             aggregator_client: None,
             certificate_verifier: None,
+            certificate_retriever: None,
+            signer_sampling: None,
+            max_epoch_gap: None,
+            parallel_signature_verification: false,
+            certificate_chain_bundle: false,
+            strict_schema_validation: false,
             #[cfg(feature = "fs")]
             snapshot_downloader: None,
+            #[cfg(feature = "fs")]
+            unpack_options: UnpackOptions::default(),
+            #[cfg(feature = "fs")]
+            resumable_downloads: false,
+            #[cfg(feature = "fs")]
+            immutable_digest_cache: None,
+            #[cfg(feature = "fs")]
+            immutable_digester: None,
+            #[cfg(feature = "fs")]
+            recording_directory: None,
+            offline_store: None,
+            offline_store_max_age: None,
+            era_reader_adapter: None,
+            signed_entity_registry: SignedEntityRegistry::default(),
+            snapshot_validation_policy: SnapshotValidationPolicy::default(),
+            metrics_recorder: None,
+            #[cfg(feature = "compute_threads")]
+            compute_threads: None,
+            request_timeout: None,
+            compression: true,
+            conditional_requests: true,
+            retry_policy: None,
+            #[cfg(not(target_family = "wasm"))]
+            proxy: None,
+            #[cfg(not(target_family = "wasm"))]
+            tls: None,
+            #[cfg(not(target_family = "wasm"))]
+            connection_pool: None,
+            #[cfg(not(target_family = "wasm"))]
+            dns: None,
+            #[cfg(not(target_family = "wasm"))]
+            aggregator_spki_pins: None,
             logger: None,
+            #[cfg(feature = "logging")]
+            log_options: None,
             feedback_receivers: vec![],
         }
     }
 
+    /// Constructs a new `ClientBuilder` that fetches data from the official aggregator of the
+    /// given network and with its genesis verification key, see [network][crate::network].
+    ///
+    /// Those can be overridden with [ClientBuilder::with_aggregator_endpoint] and
+    /// [ClientBuilder::with_genesis_verification_key].
+    #[cfg(feature = "network_presets")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "network_presets")))]
+    pub fn for_network(network: crate::network::Network) -> ClientBuilder {
+        Self::aggregator(
+            network.aggregator_endpoint(),
+            network.genesis_verification_key(),
+        )
+    }
+
     /// Constructs a new `ClientBuilder` without any dependency set.
     ///
     /// Use [ClientBuilder::aggregator] if you don't need to set a custom [AggregatorClient]
@@ -75,12 +597,53 @@ impl ClientBuilder {
     pub fn new(genesis_verification_key: &str) -> ClientBuilder {
         Self {
             aggregator_endpoint: None,
+            fallback_aggregator_endpoints: vec![],
             genesis_verification_key: genesis_verification_key.to_string(),
             aggregator_client: None,
             certificate_verifier: None,
+            certificate_retriever: None,
+            signer_sampling: None,
+            max_epoch_gap: None,
+            parallel_signature_verification: false,
+            certificate_chain_bundle: false,
+            strict_schema_validation: false,
             #[cfg(feature = "fs")]
             snapshot_downloader: None,
+            #[cfg(feature = "fs")]
+            unpack_options: UnpackOptions::default(),
+            #[cfg(feature = "fs")]
+            resumable_downloads: false,
+            #[cfg(feature = "fs")]
+            immutable_digest_cache: None,
+            #[cfg(feature = "fs")]
+            immutable_digester: None,
+            #[cfg(feature = "fs")]
+            recording_directory: None,
+            offline_store: None,
+            offline_store_max_age: None,
+            era_reader_adapter: None,
+            signed_entity_registry: SignedEntityRegistry::default(),
+            snapshot_validation_policy: SnapshotValidationPolicy::default(),
+            metrics_recorder: None,
+            #[cfg(feature = "compute_threads")]
+            compute_threads: None,
+            request_timeout: None,
+            compression: true,
+            conditional_requests: true,
+            retry_policy: None,
+            #[cfg(not(target_family = "wasm"))]
+            proxy: None,
+            #[cfg(not(target_family = "wasm"))]
+            tls: None,
+            #[cfg(not(target_family = "wasm"))]
+            connection_pool: None,
+            #[cfg(not(target_family = "wasm"))]
+            dns: None,
+            #[cfg(not(target_family = "wasm"))]
+            aggregator_spki_pins: None,
             logger: None,
+            #[cfg(feature = "logging")]
+            log_options: None,
             feedback_receivers: vec![],
         }
     }
@@ -90,82 +653,354 @@ impl ClientBuilder {
     /// The builder will try to create the missing dependencies using default implementations
     /// if possible.
     pub fn build(self) -> MithrilResult<Client> {
-        let logger = match self.logger {
-            Some(logger) => logger,
-            None => Logger::root(slog::Discard, o!()),
+        #[cfg(feature = "logging")]
+        let logger = match (self.logger, self.log_options) {
+            (Some(logger), Some(log_options)) => log_options.apply_level(logger),
+            (Some(logger), None) => logger,
+            (None, Some(log_options)) => log_options.build_logger(),
+            (None, None) => Logger::root(slog::Discard, o!()),
         };
+        #[cfg(not(feature = "logging"))]
+        let logger = self
+            .logger
+            .unwrap_or_else(|| Logger::root(slog::Discard, o!()));
 
         let feedback_sender = FeedbackSender::new(&self.feedback_receivers);
 
+        let build_aggregator_http_client =
+            |endpoint: &str| -> MithrilResult<Arc<dyn AggregatorClient>> {
+                let endpoint_url = Url::parse(endpoint)
+                .with_context(|| format!("Invalid aggregator endpoint, it must be a correctly formed url: '{endpoint}'"))?;
+
+                let aggregator_client = AggregatorHTTPClient::new(
+                    endpoint_url,
+                    APIVersionProvider::compute_all_versions_sorted()
+                        .with_context(|| "Could not compute aggregator api versions")?,
+                    logger.clone(),
+                )
+                .with_context(|| "Building aggregator client failed")?;
+                #[cfg(not(target_family = "wasm"))]
+                let aggregator_client = match &self.proxy {
+                    Some(proxy) => aggregator_client
+                        .with_proxy(proxy)
+                        .with_context(|| "Building aggregator client failed")?,
+                    None => aggregator_client,
+                };
+                #[cfg(not(target_family = "wasm"))]
+                let aggregator_client = match &self.tls {
+                    Some(tls) => aggregator_client
+                        .with_tls(tls)
+                        .with_context(|| "Building aggregator client failed")?,
+                    None => aggregator_client,
+                };
+                #[cfg(not(target_family = "wasm"))]
+                let aggregator_client = match &self.connection_pool {
+                    Some(connection_pool) => aggregator_client
+                        .with_connection_pool(connection_pool)
+                        .with_context(|| "Building aggregator client failed")?,
+                    None => aggregator_client,
+                };
+                #[cfg(not(target_family = "wasm"))]
+                let aggregator_client = match &self.dns {
+                    Some(dns) => aggregator_client
+                        .with_dns(dns)
+                        .with_context(|| "Building aggregator client failed")?,
+                    None => aggregator_client,
+                };
+                #[cfg(not(target_family = "wasm"))]
+                let aggregator_client = match &self.aggregator_spki_pins {
+                    Some(spki_pins) => aggregator_client
+                        .with_spki_pins(spki_pins)
+                        .with_context(|| "Building aggregator client failed")?,
+                    None => aggregator_client,
+                };
+
+                let aggregator_client = aggregator_client
+                    .with_compression(self.compression)
+                    .with_conditional_requests(self.conditional_requests);
+                let aggregator_client = match &self.metrics_recorder {
+                    Some(metrics_recorder) => {
+                        aggregator_client.with_metrics_recorder(metrics_recorder.clone())
+                    }
+                    None => aggregator_client,
+                };
+
+                Ok(Arc::new(match self.request_timeout {
+                    Some(request_timeout) => {
+                        aggregator_client.with_request_timeout(request_timeout)
+                    }
+                    None => aggregator_client,
+                }))
+            };
         let aggregator_client = match self.aggregator_client {
             None => {
                 let endpoint = self
                     .aggregator_endpoint
+                    .as_deref()
                     .ok_or(anyhow!("No aggregator endpoint set: \
                     You must either provide an aggregator endpoint or your own AggregatorClient implementation"))?;
-                let endpoint_url = Url::parse(&endpoint)
-                    .with_context(|| format!("Invalid aggregator endpoint, it must be a correctly formed url: '{endpoint}'"))?;
+                let aggregator_client = build_aggregator_http_client(endpoint)?;
 
-                Arc::new(
-                    AggregatorHTTPClient::new(
-                        endpoint_url,
-                        APIVersionProvider::compute_all_versions_sorted()
-                            .with_context(|| "Could not compute aggregator api versions")?,
+                if self.fallback_aggregator_endpoints.is_empty() {
+                    aggregator_client
+                } else {
+                    let mut aggregator_clients = vec![aggregator_client];
+                    for endpoint in &self.fallback_aggregator_endpoints {
+                        aggregator_clients.push(build_aggregator_http_client(endpoint)?);
+                    }
+                    Arc::new(FailoverAggregatorClient::new(
+                        aggregator_clients,
                         logger.clone(),
-                    )
-                    .with_context(|| "Building aggregator client failed")?,
-                )
+                    ))
+                }
             }
             Some(client) => client,
         };
+        let aggregator_client: Arc<dyn AggregatorClient> = Arc::new(
+            RetryingAggregatorClient::new(
+                aggregator_client,
+                self.retry_policy
+                    .unwrap_or_else(RetryPolicy::rate_limit_only),
+                logger.clone(),
+            )
+            .with_feedback_sender(feedback_sender.clone()),
+        );
+        #[cfg(feature = "fs")]
+        let aggregator_client: Arc<dyn AggregatorClient> = match &self.recording_directory {
+            Some(directory) => Arc::new(
+                RecordingAggregatorClient::new(aggregator_client, directory)
+                    .with_context(|| "Building recording aggregator client failed")?,
+            ),
+            None => aggregator_client,
+        };
+        let aggregator_client: Arc<dyn AggregatorClient> = match (
+            self.strict_schema_validation,
+            OpenApiSchemaValidator::has_embedded_specification(),
+        ) {
+            (true, true) => Arc::new(
+                SchemaValidatingAggregatorClient::new(aggregator_client)
+                    .with_context(|| "Building schema validating aggregator client failed")?,
+            ),
+            (true, false) => {
+                warn!(
+                    logger,
+                    "No Open API specification embedded in the library, the strict schema validation is disabled"
+                );
+                aggregator_client
+            }
+            (false, _) => aggregator_client,
+        };
+        let aggregator_client: Arc<dyn AggregatorClient> = match &self.metrics_recorder {
+            Some(metrics_recorder) => Arc::new(MeteredAggregatorClient::new(
+                aggregator_client,
+                metrics_recorder.clone(),
+            )),
+            None => aggregator_client,
+        };
+        let aggregator_client: Arc<dyn AggregatorClient> = match &self.offline_store {
+            Some(store) => {
+                let offline_client =
+                    OfflineAggregatorClient::new(aggregator_client, store.clone(), logger.clone());
+                let offline_client = match self.offline_store_max_age {
+                    Some(max_age) => offline_client.with_max_age(max_age),
+                    None => offline_client,
+                };
+                Arc::new(match &self.metrics_recorder {
+                    Some(metrics_recorder) => {
+                        offline_client.with_metrics_recorder(metrics_recorder.clone())
+                    }
+                    None => offline_client,
+                })
+            }
+            None => aggregator_client,
+        };
 
         #[cfg(feature = "fs")]
         let snapshot_downloader = match self.snapshot_downloader {
-            None => Arc::new(
-                HttpSnapshotDownloader::new(feedback_sender.clone(), logger.clone())
-                    .with_context(|| "Building snapshot downloader failed")?,
-            ),
+            None => {
+                let snapshot_downloader =
+                    HttpSnapshotDownloader::new(feedback_sender.clone(), logger.clone())
+                        .with_context(|| "Building snapshot downloader failed")?
+                        .with_unpack_options(self.unpack_options)
+                        .with_resumable_downloads(self.resumable_downloads)
+                        .with_immutable_files_digests(self.immutable_digest_cache.is_some());
+                let snapshot_downloader = match &self.proxy {
+                    Some(proxy) => snapshot_downloader
+                        .with_proxy(proxy)
+                        .with_context(|| "Building snapshot downloader failed")?,
+                    None => snapshot_downloader,
+                };
+                let snapshot_downloader = match &self.tls {
+                    Some(tls) => snapshot_downloader
+                        .with_tls(tls)
+                        .with_context(|| "Building snapshot downloader failed")?,
+                    None => snapshot_downloader,
+                };
+                let snapshot_downloader = match &self.connection_pool {
+                    Some(connection_pool) => snapshot_downloader
+                        .with_connection_pool(connection_pool)
+                        .with_context(|| "Building snapshot downloader failed")?,
+                    None => snapshot_downloader,
+                };
+                let snapshot_downloader = match &self.dns {
+                    Some(dns) => snapshot_downloader
+                        .with_dns(dns)
+                        .with_context(|| "Building snapshot downloader failed")?,
+                    None => snapshot_downloader,
+                };
+
+                Arc::new(match &self.metrics_recorder {
+                    Some(metrics_recorder) => {
+                        snapshot_downloader.with_metrics_recorder(metrics_recorder.clone())
+                    }
+                    None => snapshot_downloader,
+                })
+            }
             Some(snapshot_downloader) => snapshot_downloader,
         };
 
+        #[cfg(feature = "compute_threads")]
+        let compute_executor = match self.compute_threads {
+            Some(threads) => ComputeExecutor::thread_pool(threads)
+                .with_context(|| "Building compute executor failed")?,
+            None => ComputeExecutor::default(),
+        };
+        #[cfg(not(feature = "compute_threads"))]
+        let compute_executor = ComputeExecutor::default();
+
         let certificate_verifier = match self.certificate_verifier {
-            None => Arc::new(
-                MithrilCertificateVerifier::new(
+            None => {
+                let verifier = MithrilCertificateVerifier::new(
                     aggregator_client.clone(),
                     &self.genesis_verification_key,
                     feedback_sender.clone(),
                     logger.clone(),
                 )
-                .with_context(|| "Building certificate verifier failed")?,
-            ),
+                .with_context(|| "Building certificate verifier failed")?;
+
+                let verifier = match &self.certificate_retriever {
+                    Some(certificate_retriever) => {
+                        verifier.with_certificate_retriever(certificate_retriever.clone())
+                    }
+                    None => verifier,
+                };
+                let verifier = match self.signer_sampling {
+                    Some(signer_sampling) => verifier.with_signer_sampling(signer_sampling),
+                    None => verifier,
+                };
+                let verifier = match self.max_epoch_gap {
+                    Some(max_epoch_gap) => verifier.with_max_epoch_gap(max_epoch_gap),
+                    None => verifier,
+                };
+                let verifier = verifier
+                    .with_parallel_signature_verification(self.parallel_signature_verification)
+                    .with_certificate_chain_bundle(self.certificate_chain_bundle)
+                    .with_compute_executor(compute_executor.clone());
+                Arc::new(match &self.metrics_recorder {
+                    Some(metrics_recorder) => {
+                        verifier.with_metrics_recorder(metrics_recorder.clone())
+                    }
+                    None => verifier,
+                })
+            }
             Some(verifier) => verifier,
         };
-        let certificate_client = Arc::new(CertificateClient::new(
+        let certificate_client = CertificateClient::new(
             aggregator_client.clone(),
             certificate_verifier,
             logger.clone(),
-        ));
+        )
+        .with_genesis_verification_key(&self.genesis_verification_key);
+        let certificate_client = Arc::new(match self.certificate_retriever {
+            Some(certificate_retriever) => {
+                certificate_client.with_certificate_retriever(certificate_retriever)
+            }
+            None => certificate_client,
+        });
 
-        let mithril_stake_distribution_client = Arc::new(MithrilStakeDistributionClient::new(
+        let mithril_stake_distribution_client = Arc::new(
+            MithrilStakeDistributionClient::new(aggregator_client.clone())
+                .with_compute_executor(compute_executor.clone()),
+        );
+        let snapshot_client = SnapshotClient::new(
             aggregator_client.clone(),
-        ));
-        let snapshot_client = Arc::new(SnapshotClient::new(
-            aggregator_client,
             #[cfg(feature = "fs")]
             snapshot_downloader,
             #[cfg(feature = "fs")]
-            feedback_sender,
+            feedback_sender.clone(),
             #[cfg(feature = "fs")]
-            logger,
-        ));
+            logger.clone(),
+        )
+        .with_validation_policy(self.snapshot_validation_policy);
+        #[cfg(feature = "fs")]
+        let snapshot_client = match &self.immutable_digest_cache {
+            Some(immutable_digest_cache) => {
+                snapshot_client.with_immutable_digest_cache(immutable_digest_cache.clone())
+            }
+            None => snapshot_client,
+        };
+        let snapshot_client = Arc::new(snapshot_client);
+        let epoch_settings_client = Arc::new(EpochSettingsClient::new(aggregator_client.clone()));
+        #[cfg(feature = "fs")]
+        let immutable_digester = match self.immutable_digester {
+            Some(immutable_digester) => immutable_digester,
+            None => Arc::new(
+                CardanoImmutableDigester::new(self.immutable_digest_cache.clone(), logger.clone())
+                    .with_compute_executor(compute_executor.clone()),
+            ),
+        };
 
         Ok(Client {
-            certificate_client,
-            mithril_stake_distribution_client,
-            snapshot_client,
+            inner: Arc::new(ClientInner {
+                certificate_client,
+                mithril_stake_distribution_client,
+                snapshot_client,
+                epoch_settings_client,
+                aggregator_client,
+                era_reader: self.era_reader_adapter.map(EraReader::new),
+                offline_store: self.offline_store,
+                signed_entity_registry: self.signed_entity_registry,
+                compute_executor,
+                #[cfg(feature = "fs")]
+                feedback_sender,
+                #[cfg(feature = "fs")]
+                immutable_digester,
+                #[cfg(feature = "fs")]
+                metrics_recorder: self.metrics_recorder,
+                logger,
+            }),
         })
     }
 
+    /// Set the endpoint of the aggregator to request data from.
+    ///
+    /// Note: this endpoint is not used if a custom [AggregatorClient] is set.
+    pub fn with_aggregator_endpoint(mut self, endpoint: &str) -> ClientBuilder {
+        self.aggregator_endpoint = Some(endpoint.to_string());
+        self
+    }
+
+    /// Set the endpoints of the aggregators that the requests fail over to, in order, when
+    /// the aggregator can't be used, see [failover][crate::failover].
+    ///
+    /// Note: those endpoints are not used if a custom [AggregatorClient] is set.
+    pub fn with_fallback_aggregator_endpoints(mut self, endpoints: &[&str]) -> ClientBuilder {
+        self.fallback_aggregator_endpoints = endpoints
+            .iter()
+            .map(|endpoint| endpoint.to_string())
+            .collect();
+        self
+    }
+
+    /// Set the genesis verification key that the certificate chains are verified with.
+    pub fn with_genesis_verification_key(
+        mut self,
+        genesis_verification_key: &str,
+    ) -> ClientBuilder {
+        self.genesis_verification_key = genesis_verification_key.to_string();
+        self
+    }
+
     /// Set the [AggregatorClient] that will be used to request data to the aggregator.
     pub fn with_aggregator_client(
         mut self,
@@ -184,6 +1019,165 @@ impl ClientBuilder {
         self
     }
 
+    /// Set the [CertificateRetriever] from which the certificates are retrieved to be validated,
+    /// ie: to validate the chains kept in a database, an IPFS gateway or a local archive, instead
+    /// of the aggregator.
+    ///
+    /// Note: if a custom [CertificateVerifier] is set it is not given this retriever.
+    pub fn with_certificate_retriever(
+        mut self,
+        certificate_retriever: Arc<dyn CertificateRetriever>,
+    ) -> ClientBuilder {
+        self.certificate_retriever = Some(certificate_retriever);
+        self
+    }
+
+    /// Check the metadata of a random sample of the signers of each certificate when validating
+    /// a certificate chain, see [SignerSampling], those checks are not done by default.
+    ///
+    /// Note: this sampling is not used if a custom [CertificateVerifier] is set.
+    pub fn with_signer_sampling(mut self, signer_sampling: SignerSampling) -> ClientBuilder {
+        self.signer_sampling = Some(signer_sampling);
+        self
+    }
+
+    /// Fail the validation of a certificate chain if a certificate is chained to a previous
+    /// certificate more than `max_epoch_gap` epochs in the past, see
+    /// [MithrilCertificateVerifier::with_max_epoch_gap].
+    ///
+    /// Note: this check is not done if a custom [CertificateVerifier] is set.
+    pub fn with_max_epoch_gap(mut self, max_epoch_gap: u64) -> ClientBuilder {
+        self.max_epoch_gap = Some(max_epoch_gap);
+        self
+    }
+
+    /// Verify the multi-signatures of the epochs of a certificate chain in parallel, once the
+    /// whole chain was walked, see
+    /// [MithrilCertificateVerifier::with_parallel_signature_verification].
+    ///
+    /// Note: the multi-signatures are not verified in parallel if a custom [CertificateVerifier]
+    /// is set.
+    pub fn with_parallel_signature_verification(mut self, enabled: bool) -> ClientBuilder {
+        self.parallel_signature_verification = enabled;
+        self
+    }
+
+    /// Fetch a certificate chain to validate in a single request to the aggregator if it serves
+    /// certificate chains bundles, see
+    /// [MithrilCertificateVerifier::with_certificate_chain_bundle].
+    ///
+    /// Note: the chain is fetched certificate by certificate if a custom [CertificateVerifier]
+    /// is set.
+    pub fn with_certificate_chain_bundle(mut self, enabled: bool) -> ClientBuilder {
+        self.certificate_chain_bundle = enabled;
+        self
+    }
+
+    /// Run the CPU heavy computations (multi-signatures verification of the certificates,
+    /// aggregate verification keys and snapshot digests) on a dedicated pool of the given number
+    /// of threads instead of the async executor threads, see [compute][crate::compute].
+    ///
+    /// Note: this pool is not used by a custom [CertificateVerifier] or [ImmutableDigester].
+    #[cfg(feature = "compute_threads")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "compute_threads")))]
+    pub fn with_compute_threads(mut self, threads: usize) -> ClientBuilder {
+        self.compute_threads = Some(threads);
+        self
+    }
+
+    /// Fail the requests to the aggregator that don't complete within the given timeout.
+    ///
+    /// Note: this timeout is not used if a custom [AggregatorClient] is set.
+    pub fn with_request_timeout(mut self, request_timeout: Duration) -> ClientBuilder {
+        self.request_timeout = Some(request_timeout);
+        self
+    }
+
+    /// Enable or disable the gzip/deflate compression of the responses of the aggregator
+    /// (enabled by default).
+    ///
+    /// Note: this setting is not used if a custom [AggregatorClient] is set.
+    pub fn with_compression(mut self, compression: bool) -> ClientBuilder {
+        self.compression = compression;
+        self
+    }
+
+    /// Enable or disable the conditional list requests to the aggregator (enabled by default):
+    /// an unchanged list is served from a cache instead of being downloaded again, see
+    /// [AggregatorHTTPClient::with_conditional_requests].
+    ///
+    /// Note: this setting is not used if a custom [AggregatorClient] is set.
+    pub fn with_conditional_requests(mut self, conditional_requests: bool) -> ClientBuilder {
+        self.conditional_requests = conditional_requests;
+        self
+    }
+
+    /// Send again the requests to the aggregator that failed because of a transient error,
+    /// see [retry][crate::retry].
+    ///
+    /// Without retry policy only the requests rate limited by the aggregator are sent again,
+    /// following [RetryPolicy::rate_limit_only].
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> ClientBuilder {
+        self.retry_policy = Some(retry_policy);
+        self
+    }
+
+    /// Reach the aggregator and download the snapshots through the given proxy, see
+    /// [proxy][crate::proxy].
+    ///
+    /// Note: this proxy is not used by a custom [AggregatorClient] or [SnapshotDownloader].
+    #[cfg(not(target_family = "wasm"))]
+    pub fn with_proxy(mut self, proxy: ProxyConfig) -> ClientBuilder {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Reach the aggregator and download the snapshots using the given TLS settings, ie: to
+    /// trust a private certificate authority or authenticate with a client certificate, see
+    /// [tls][crate::tls].
+    ///
+    /// Note: these settings are not used by a custom [AggregatorClient] or [SnapshotDownloader].
+    #[cfg(not(target_family = "wasm"))]
+    pub fn with_tls(mut self, tls: TlsConfig) -> ClientBuilder {
+        self.tls = Some(tls);
+        self
+    }
+
+    /// Reject the connections to the aggregator if the public key of its TLS certificate
+    /// matches none of the given pins, see [SpkiPins][crate::tls::SpkiPins].
+    ///
+    /// The requests then fail with an
+    /// [AggregatorClientError::TlsPinMismatch][crate::aggregator_client::AggregatorClientError::TlsPinMismatch].
+    ///
+    /// Note: these pins are not used by a custom [AggregatorClient].
+    #[cfg(not(target_family = "wasm"))]
+    pub fn with_aggregator_spki_pins(mut self, spki_pins: SpkiPins) -> ClientBuilder {
+        self.aggregator_spki_pins = Some(spki_pins);
+        self
+    }
+
+    /// Tune the pool of the connections to the aggregator and to the snapshot locations, ie: to
+    /// keep more idle connections alive for a client shared by many tasks, see
+    /// [connection_pool][crate::connection_pool].
+    ///
+    /// Note: these settings are not used by a custom [AggregatorClient] or [SnapshotDownloader].
+    #[cfg(not(target_family = "wasm"))]
+    pub fn with_connection_pool(mut self, connection_pool: ConnectionPoolConfig) -> ClientBuilder {
+        self.connection_pool = Some(connection_pool);
+        self
+    }
+
+    /// Resolve the names of the aggregator and of the snapshot locations using the given
+    /// settings, ie: to only connect using IPv4 or to give static addresses to hosts that can't
+    /// be resolved, see [dns][crate::dns].
+    ///
+    /// Note: these settings are not used by a custom [AggregatorClient] or [SnapshotDownloader].
+    #[cfg(not(target_family = "wasm"))]
+    pub fn with_dns(mut self, dns: DnsConfig) -> ClientBuilder {
+        self.dns = Some(dns);
+        self
+    }
+
     cfg_fs! {
     /// Set the [SnapshotDownloader] that will be used to download snapshots.
     pub fn with_snapshot_downloader(
@@ -193,6 +1187,134 @@ impl ClientBuilder {
         self.snapshot_downloader = Some(snapshot_downloader);
         self
     }
+
+    /// Set the [UnpackOptions] honored when unpacking snapshots.
+    ///
+    /// Note: those options are not used if a custom [SnapshotDownloader] is set.
+    pub fn with_unpack_options(mut self, unpack_options: UnpackOptions) -> ClientBuilder {
+        self.unpack_options = unpack_options;
+        self
+    }
+
+    /// Make the snapshot downloads resumable across process restarts: the archive is persisted
+    /// in the download directory alongside the progress of its download, see
+    /// [HttpSnapshotDownloader::with_resumable_downloads].
+    ///
+    /// Note: this option is not used if a custom [SnapshotDownloader] is set.
+    pub fn with_resumable_downloads(mut self, resumable_downloads: bool) -> ClientBuilder {
+        self.resumable_downloads = resumable_downloads;
+        self
+    }
+
+    /// Store the digests of the immutable files, computed while unpacking the snapshots, in the
+    /// given cache once the snapshots are verified, see
+    /// [HttpSnapshotDownloader::with_immutable_files_digests] and
+    /// [SnapshotClient::with_immutable_digest_cache].
+    ///
+    /// Note: the digests are not computed while unpacking if a custom [SnapshotDownloader] is
+    /// set.
+    pub fn with_immutable_digest_cache(
+        mut self,
+        immutable_digest_cache: Arc<dyn ImmutableFileDigestCacheProvider>,
+    ) -> ClientBuilder {
+        self.immutable_digest_cache = Some(immutable_digest_cache);
+        self
+    }
+
+    /// Set the [ImmutableDigester] used to compute the digest of the downloaded snapshots, ie: one
+    /// reading the digests from a trusted sidecar service, see [Client::message_builder].
+    ///
+    /// If not set a [CardanoImmutableDigester] is used, backed by the
+    /// [immutable digest cache][ClientBuilder::with_immutable_digest_cache] if one is set.
+    pub fn with_immutable_digester(
+        mut self,
+        immutable_digester: Arc<dyn ImmutableDigester>,
+    ) -> ClientBuilder {
+        self.immutable_digester = Some(immutable_digester);
+        self
+    }
+
+    /// Set the [SnapshotValidationPolicy] that the snapshots must comply with to be downloaded.
+    pub fn with_snapshot_validation_policy(
+        mut self,
+        snapshot_validation_policy: SnapshotValidationPolicy,
+    ) -> ClientBuilder {
+        self.snapshot_validation_policy = snapshot_validation_policy;
+        self
+    }
+
+    /// Record every interaction with the aggregator in the given directory, so they can be
+    /// replayed later with a [ReplayingAggregatorClient][crate::recording::ReplayingAggregatorClient].
+    ///
+    /// See [recording][crate::recording].
+    pub fn with_interactions_recording<P: AsRef<std::path::Path>>(
+        mut self,
+        directory: P,
+    ) -> ClientBuilder {
+        self.recording_directory = Some(directory.as_ref().to_path_buf());
+        self
+    }
+    }
+
+    /// Set the [OfflineStore] in which the [prefetched][Client::prefetch] artifacts are kept and
+    /// from which they are served back when the aggregator can't be reached.
+    ///
+    /// Required to [prefetch][Client::prefetch] artifacts.
+    pub fn with_offline_store(mut self, offline_store: Arc<dyn OfflineStore>) -> ClientBuilder {
+        self.offline_store = Some(offline_store);
+        self
+    }
+
+    /// Set the maximum age of the unpinned contents of the [OfflineStore], ie: the lists of
+    /// artifacts, served when the aggregator can't be reached.
+    ///
+    /// Default to [DEFAULT_OFFLINE_STORE_MAX_AGE][crate::offline_store::DEFAULT_OFFLINE_STORE_MAX_AGE].
+    pub fn with_offline_store_max_age(mut self, max_age: Duration) -> ClientBuilder {
+        self.offline_store_max_age = Some(max_age);
+        self
+    }
+
+    /// Set the [EraReaderAdapter] reading the era markers used by
+    /// [Client::check_era_compatibility].
+    pub fn with_era_reader_adapter(
+        mut self,
+        era_reader_adapter: Arc<dyn EraReaderAdapter>,
+    ) -> ClientBuilder {
+        self.era_reader_adapter = Some(era_reader_adapter);
+        self
+    }
+
+    /// Set the [SignedEntityRegistry] describing the signed entities whose artifacts can be
+    /// fetched with [Client::list_signed_entity_artifacts] and
+    /// [Client::get_signed_entity_artifact], see [signed_entity][crate::signed_entity].
+    pub fn with_signed_entity_registry(
+        mut self,
+        signed_entity_registry: SignedEntityRegistry,
+    ) -> ClientBuilder {
+        self.signed_entity_registry = signed_entity_registry;
+        self
+    }
+
+    /// Enable or disable the validation of the aggregator responses against their Open API
+    /// schemas, see [schema validation][crate::schema_validation].
+    ///
+    /// Note: the validation stays disabled if the library was built without the Open API
+    /// specification.
+    pub fn with_strict_schema_validation(mut self, enabled: bool) -> ClientBuilder {
+        self.strict_schema_validation = enabled;
+        self
+    }
+
+    /// Set the [MetricsRecorder] that will receive the measures of the client operations.
+    ///
+    /// Note: the verified certificates and downloaded bytes are not measured if a custom
+    /// [CertificateVerifier] or [SnapshotDownloader] is set.
+    pub fn with_metrics_recorder(
+        mut self,
+        metrics_recorder: Arc<dyn MetricsRecorder>,
+    ) -> ClientBuilder {
+        self.metrics_recorder = Some(metrics_recorder);
+        self
     }
 
     /// Set the [Logger] to use.
@@ -201,6 +1323,17 @@ impl ClientBuilder {
         self
     }
 
+    /// Set the [LogOptions] used to build the logger given to every component of the client.
+    ///
+    /// If a [Logger] is also set using [with_logger][Self::with_logger], only the level of the
+    /// options is applied to it.
+    #[cfg(feature = "logging")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "logging")))]
+    pub fn with_logging(mut self, log_options: LogOptions) -> Self {
+        self.log_options = Some(log_options);
+        self
+    }
+
     /// Add a [feedback receiver][FeedbackReceiver] to receive [events][crate::feedback::MithrilEvent]
     /// for tasks that can have a long duration (ie: snapshot download or a long certificate chain
     /// validation).
@@ -209,3 +1342,359 @@ impl ClientBuilder {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::aggregator_client::{AggregatorRoute, MockAggregatorHTTPClient};
+    use crate::certificate_client::MockCertificateVerifier;
+    use crate::common::entities::ProtocolMessagePartKey;
+    use crate::offline_store::MemoryOfflineStore;
+    use crate::{MithrilCertificate, MithrilCertificateListItem, Snapshot, SnapshotListItem};
+
+    use super::*;
+
+    fn client_builder(aggregator_client: MockAggregatorHTTPClient) -> ClientBuilder {
+        let mut certificate_verifier = MockCertificateVerifier::new();
+        certificate_verifier
+            .expect_verify_chain()
+            .returning(|_| Ok(()));
+        certificate_verifier
+            .expect_verify_chain_certificates()
+            .returning(|certificate| Ok(vec![certificate.clone()]));
+
+        ClientBuilder::new("genesis_verification_key")
+            .with_aggregator_client(Arc::new(aggregator_client))
+            .with_certificate_verifier(Arc::new(certificate_verifier))
+    }
+
+    #[test]
+    fn client_clones_share_their_state_across_threads() {
+        fn assert_shareable<T: Clone + Send + Sync + 'static>() {}
+        assert_shareable::<Client>();
+
+        let client = client_builder(MockAggregatorHTTPClient::new())
+            .build()
+            .unwrap();
+        let certificate_client = std::thread::spawn({
+            let client = client.clone();
+            move || client.certificate()
+        })
+        .join()
+        .unwrap();
+
+        assert!(Arc::ptr_eq(&client.certificate(), &certificate_client));
+    }
+
+    #[cfg(not(target_family = "wasm"))]
+    #[test]
+    fn build_client_with_a_tuned_connection_pool() {
+        ClientBuilder::aggregator(
+            "http://localhost:8080/aggregator",
+            crate::test_tools::fake_keys::genesis_verification_key()[0],
+        )
+        .with_connection_pool(
+            ConnectionPoolConfig::new()
+                .with_max_idle_per_host(4)
+                .with_idle_timeout(Duration::from_secs(10))
+                .with_tcp_keepalive(Duration::from_secs(5)),
+        )
+        .build()
+        .expect("building a client with a tuned connection pool should not fail");
+    }
+
+    #[cfg(feature = "network_presets")]
+    #[test]
+    fn network_preset_can_be_overridden() {
+        use crate::network::Network;
+
+        let builder = ClientBuilder::for_network(Network::Preview)
+            .with_aggregator_endpoint("http://localhost:8080/aggregator");
+
+        assert_eq!(
+            Some("http://localhost:8080/aggregator".to_string()),
+            builder.aggregator_endpoint
+        );
+        assert_eq!(
+            Network::Preview.genesis_verification_key(),
+            builder.genesis_verification_key
+        );
+    }
+
+    #[tokio::test]
+    async fn prefetch_fails_without_offline_store() {
+        let client = client_builder(MockAggregatorHTTPClient::new())
+            .build()
+            .unwrap();
+
+        client
+            .prefetch(PrefetchPlan::new().with_latest_snapshots(1))
+            .await
+            .expect_err("prefetch should fail without an offline store");
+    }
+
+    /// Certificate of [Snapshot::dummy] which signs its digest.
+    fn snapshot_certificate() -> MithrilCertificate {
+        let snapshot = Snapshot::dummy();
+        let mut certificate = MithrilCertificate {
+            hash: snapshot.certificate_hash.clone(),
+            ..MithrilCertificate::dummy()
+        };
+        certificate
+            .protocol_message
+            .set_message_part(ProtocolMessagePartKey::SnapshotDigest, snapshot.digest);
+        certificate.signed_message = certificate.protocol_message.compute_hash();
+
+        certificate
+    }
+
+    /// Aggregator serving a single snapshot and its certificate, `fetches` times before becoming
+    /// unreachable.
+    fn aggregator_serving_a_snapshot(fetches: usize) -> MockAggregatorHTTPClient {
+        let mut aggregator_client = MockAggregatorHTTPClient::new();
+        aggregator_client
+            .expect_get_content()
+            .times(fetches)
+            .returning(|request| match request {
+                AggregatorRequest::ListCertificates => {
+                    Ok(serde_json::to_string(&vec![MithrilCertificateListItem {
+                        hash: snapshot_certificate().hash,
+                        ..MithrilCertificateListItem::dummy()
+                    }])
+                    .unwrap())
+                }
+                AggregatorRequest::GetCertificate { .. } => {
+                    Ok(serde_json::to_string(&snapshot_certificate()).unwrap())
+                }
+                AggregatorRequest::ListSnapshots => {
+                    Ok(serde_json::to_string(&vec![SnapshotListItem::dummy()]).unwrap())
+                }
+                AggregatorRequest::GetSnapshot { .. } => {
+                    Ok(serde_json::to_string(&Snapshot::dummy()).unwrap())
+                }
+                request => panic!("Unexpected request: {request:?}"),
+            });
+        aggregator_client.expect_get_content().returning(|_| {
+            Err(AggregatorClientError::SubsystemError(anyhow!(
+                "aggregator unreachable"
+            )))
+        });
+
+        aggregator_client
+    }
+
+    #[tokio::test]
+    async fn prefetched_artifacts_are_served_while_offline() {
+        // The chain of the snapshot certificate, already verified as a listed certificate, is
+        // not fetched and verified again.
+        let store = Arc::new(MemoryOfflineStore::new());
+        let client = client_builder(aggregator_serving_a_snapshot(4))
+            .with_offline_store(store)
+            .build()
+            .unwrap();
+
+        let report = client
+            .prefetch(
+                PrefetchPlan::new()
+                    .with_latest_certificates(1)
+                    .with_latest_snapshots(1),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            PrefetchReport {
+                certificates: vec![snapshot_certificate().hash],
+                mithril_stake_distributions: vec![],
+                snapshots: vec![SnapshotListItem::dummy().digest],
+            },
+            report
+        );
+        let snapshot = client
+            .snapshot()
+            .get(&Snapshot::dummy().digest)
+            .await
+            .unwrap();
+        assert_eq!(Some(Snapshot::dummy()), snapshot);
+        let certificate = client
+            .certificate()
+            .get(&snapshot_certificate().hash)
+            .await
+            .unwrap();
+        assert_eq!(Some(snapshot_certificate()), certificate);
+    }
+
+    #[tokio::test]
+    async fn prefetch_does_not_store_a_snapshot_not_signed_by_its_certificate() {
+        let mut aggregator_client = MockAggregatorHTTPClient::new();
+        aggregator_client
+            .expect_get_content()
+            .returning(|request| match request {
+                AggregatorRequest::ListSnapshots => {
+                    Ok(serde_json::to_string(&vec![SnapshotListItem::dummy()]).unwrap())
+                }
+                AggregatorRequest::GetSnapshot { .. } => Ok(serde_json::to_string(&Snapshot {
+                    digest: "other_digest".to_string(),
+                    ..Snapshot::dummy()
+                })
+                .unwrap()),
+                AggregatorRequest::GetCertificate { .. } => {
+                    Ok(serde_json::to_string(&snapshot_certificate()).unwrap())
+                }
+                request => panic!("Unexpected request: {request:?}"),
+            });
+        let store = Arc::new(MemoryOfflineStore::new());
+        let client = client_builder(aggregator_client)
+            .with_offline_store(store.clone())
+            .build()
+            .unwrap();
+
+        client
+            .prefetch(PrefetchPlan::new().with_latest_snapshots(1))
+            .await
+            .expect_err("prefetch should fail");
+
+        let snapshot_route = AggregatorRequest::GetSnapshot {
+            digest: SnapshotListItem::dummy().digest,
+        }
+        .route();
+        assert_eq!(None, store.get(&snapshot_route).unwrap());
+    }
+
+    #[tokio::test]
+    async fn unpin_prefetched_artifacts() {
+        let store = Arc::new(MemoryOfflineStore::new());
+        let client = client_builder(aggregator_serving_a_snapshot(4))
+            .with_offline_store(store.clone())
+            .build()
+            .unwrap();
+        let report = client
+            .prefetch(
+                PrefetchPlan::new()
+                    .with_latest_certificates(1)
+                    .with_latest_snapshots(1),
+            )
+            .await
+            .unwrap();
+
+        client.unpin(&report).unwrap();
+
+        let snapshot_route = AggregatorRequest::GetSnapshot {
+            digest: SnapshotListItem::dummy().digest,
+        }
+        .route();
+        assert!(!store.get(&snapshot_route).unwrap().unwrap().pinned);
+    }
+
+    #[tokio::test]
+    async fn probe_aggregators_report_invalid_endpoints_as_unreachable() {
+        let client = client_builder(MockAggregatorHTTPClient::new())
+            .build()
+            .unwrap();
+
+        let report = client.probe_aggregators(&["not an url"]).await;
+
+        assert_eq!(1, report.probes.len());
+        assert!(!report.probes[0].is_reachable());
+        assert_eq!(None, report.best());
+    }
+
+    #[tokio::test]
+    async fn fetch_the_artifacts_of_a_registered_signed_entity() {
+        use crate::signed_entity::SignedEntity;
+
+        struct FakeSignedEntity;
+
+        impl SignedEntity for FakeSignedEntity {
+            fn name(&self) -> &'static str {
+                "Fake"
+            }
+
+            fn artifacts_route(&self) -> Option<AggregatorRoute> {
+                Some(AggregatorRoute::new("artifact/fakes"))
+            }
+
+            fn artifact_route(&self, artifact_id: &str) -> Option<AggregatorRoute> {
+                Some(AggregatorRoute::new("artifact/fake").with_parameter(artifact_id))
+            }
+
+            fn required_message_parts(&self) -> &'static [ProtocolMessagePartKey] {
+                &[]
+            }
+        }
+
+        let mut aggregator_client = MockAggregatorHTTPClient::new();
+        aggregator_client.expect_get_content().returning(|request| {
+            match request.route().as_str() {
+                "artifact/fakes" => Ok(r#"[{"id":"1"},{"id":"2"}]"#.to_string()),
+                "artifact/fake/1" => Ok(r#"{"id":"1"}"#.to_string()),
+                _ => Err(AggregatorClientError::RemoteServerLogical(anyhow!(
+                    "not found"
+                ))),
+            }
+        });
+        let client = client_builder(aggregator_client)
+            .with_signed_entity_registry(
+                SignedEntityRegistry::default().with_signed_entity(Arc::new(FakeSignedEntity)),
+            )
+            .build()
+            .unwrap();
+
+        let artifacts: Vec<serde_json::Value> =
+            client.list_signed_entity_artifacts("Fake").await.unwrap();
+        let artifact: Option<serde_json::Value> = client
+            .get_signed_entity_artifact("Fake", "1")
+            .await
+            .unwrap();
+        let missing_artifact: Option<serde_json::Value> = client
+            .get_signed_entity_artifact("Fake", "3")
+            .await
+            .unwrap();
+
+        assert_eq!(2, artifacts.len());
+        assert_eq!(Some(serde_json::json!({"id": "1"})), artifact);
+        assert_eq!(None, missing_artifact);
+        client
+            .list_signed_entity_artifacts::<serde_json::Value>("CardanoStakeDistribution")
+            .await
+            .expect_err("the artifacts of the Cardano stake distributions are not published");
+        client
+            .list_signed_entity_artifacts::<serde_json::Value>("Unknown")
+            .await
+            .expect_err("the signed entity is not registered");
+    }
+
+    #[tokio::test]
+    async fn get_the_aggregator_capabilities() {
+        let mut aggregator_client = MockAggregatorHTTPClient::new();
+        aggregator_client
+            .expect_get_content()
+            .withf(|request| *request == AggregatorRequest::GetAggregatorFeatures)
+            .returning(|_| Ok(serde_json::to_string(&AggregatorFeaturesMessage::dummy()).unwrap()));
+        let client = client_builder(aggregator_client).build().unwrap();
+
+        let features = client.aggregator_capabilities().await.unwrap();
+
+        assert_eq!(AggregatorFeaturesMessage::dummy(), features);
+        assert!(!features.capabilities.supports_cardano_transactions());
+    }
+
+    #[cfg(feature = "fs")]
+    #[tokio::test]
+    async fn message_builder_uses_the_immutable_digester_set_in_the_builder() {
+        use crate::common::digesters::DumbImmutableDigester;
+        use crate::common::entities::Beacon;
+
+        let client = client_builder(MockAggregatorHTTPClient::new())
+            .with_immutable_digester(Arc::new(DumbImmutableDigester::new("sidecar-digest", true)))
+            .build()
+            .unwrap();
+
+        let digest = client
+            .message_builder()
+            .compute_snapshot_digest(&Beacon::default(), std::path::Path::new("whatever"))
+            .await
+            .unwrap();
+
+        assert_eq!("sidecar-digest", digest);
+    }
+}
@@ -15,7 +15,9 @@ use crate::common::crypto_helper::{
 use crate::common::entities::{
     Certificate, CertificateSignature, ProtocolMessage, ProtocolMessagePartKey, ProtocolParameters,
 };
+use crate::common::messages::CertificateMessage;
 use crate::common::StdResult;
+use crate::protocol_message_matcher::ProtocolMessageMatcher;
 
 #[cfg(test)]
 use mockall::automock;
@@ -57,6 +59,19 @@ pub enum CertificateVerifierError {
     InvalidGenesisCertificateProvided,
 }
 
+/// Error raised by [CertificateVerifier::verify_multi_signatures_batch] when a multi
+/// signature of the batch is invalid.
+#[derive(Error, Debug)]
+#[error("invalid multi signature of the certificate at index {index} of the batch")]
+pub struct MultiSignaturesBatchError {
+    /// Index, in the verified batch, of the first certificate with an invalid multi signature
+    pub index: usize,
+
+    /// Error raised by the verification of its multi signature
+    #[source]
+    pub error: CertificateVerifierError,
+}
+
 /// CertificateVerifier is the cryptographic engine in charge of verifying multi signatures and
 /// [certificates](Certificate)
 #[cfg_attr(test, automock)]
@@ -80,6 +95,32 @@ pub trait CertificateVerifier: Send + Sync {
         genesis_verification_key: &ProtocolGenesisVerificationKey,
     ) -> StdResult<Option<Certificate>>;
 
+    /// Verify a certificate like [verify_certificate][Self::verify_certificate] except for its
+    /// multi-signature, that must be verified afterward with
+    /// [verify_multi_signatures_batch][Self::verify_multi_signatures_batch].
+    ///
+    /// The default implementation verifies the whole certificate, multi-signature included.
+    async fn verify_certificate_deferring_multi_signature(
+        &self,
+        certificate: &Certificate,
+        genesis_verification_key: &ProtocolGenesisVerificationKey,
+    ) -> StdResult<Option<Certificate>> {
+        self.verify_certificate(certificate, genesis_verification_key)
+            .await
+    }
+
+    /// Verify the multi-signatures of the given certificates at once, genesis certificates are
+    /// ignored.
+    ///
+    /// The default implementation does nothing, the multi-signatures being already verified by
+    /// the default [verify_certificate_deferring_multi_signature][Self::verify_certificate_deferring_multi_signature].
+    fn verify_multi_signatures_batch(
+        &self,
+        _certificates: &[Certificate],
+    ) -> Result<(), MultiSignaturesBatchError> {
+        Ok(())
+    }
+
     /// Verify that the Certificate Chain associated to a Certificate is valid
     /// TODO: see if we can borrow the certificate instead.
     async fn verify_certificate_chain(
@@ -98,15 +139,19 @@ pub trait CertificateVerifier: Send + Sync {
         Ok(())
     }
 
-    /// still a dirty hack to mock the protocol message
-    /// verify that the protocol message is equal to the signed message of the certificate.
-    /// TODO: Remove this method.
+    /// Verify that the protocol message is equal to the signed message of the certificate.
+    // Not deprecated in tests as the mock generated for this trait doesn't support it
+    #[cfg_attr(not(test), deprecated(note = "use ProtocolMessageMatcher"))]
     fn verify_protocol_message(
         &self,
         protocol_message: &ProtocolMessage,
         certificate: &Certificate,
     ) -> bool {
-        protocol_message.compute_hash() == certificate.signed_message
+        CertificateMessage::try_from(certificate.clone()).is_ok_and(|certificate| {
+            ProtocolMessageMatcher::ProtocolMessage(protocol_message.clone())
+                .matches(&certificate)
+                .is_ok()
+        })
     }
 }
 
@@ -150,18 +195,55 @@ impl MithrilCertificateVerifier {
             .map_err(|e| CertificateVerifierError::VerifyMultiSignature(e.to_string()))
     }
 
-    /// Verify Standard certificate
+    async fn verify_certificate_with(
+        &self,
+        certificate: &Certificate,
+        genesis_verification_key: &ProtocolGenesisVerificationKey,
+        verify_multi_signature: bool,
+    ) -> StdResult<Option<Certificate>> {
+        debug!(
+            self.logger,
+            "Verifying certificate";
+            "certificate_hash" => &certificate.hash,
+            "certificate_previous_hash" => &certificate.previous_hash,
+            "certificate_beacon" => ?certificate.beacon
+        );
+
+        certificate
+            .verify_hash()
+            .map_err(|_| CertificateVerifierError::CertificateHashUnmatch)?;
+
+        if certificate.is_chaining_to_itself() {
+            Err(anyhow!(
+                CertificateVerifierError::CertificateChainInfiniteLoop
+            ))
+        } else {
+            match &certificate.signature {
+                CertificateSignature::GenesisSignature(_signature) => {
+                    self.verify_genesis_certificate(certificate, genesis_verification_key)
+                        .await?;
+                    Ok(None)
+                }
+                CertificateSignature::MultiSignature(signature) => {
+                    if verify_multi_signature {
+                        self.verify_multi_signature(
+                            certificate.signed_message.as_bytes(),
+                            signature,
+                            &certificate.aggregate_verification_key,
+                            &certificate.metadata.protocol_parameters,
+                        )?;
+                    }
+                    self.verify_standard_certificate(certificate).await
+                }
+            }
+        }
+    }
+
+    /// Verify that a Standard certificate is chained to its previous certificate
     async fn verify_standard_certificate(
         &self,
         certificate: &Certificate,
-        signature: &ProtocolMultiSignature,
     ) -> StdResult<Option<Certificate>> {
-        self.verify_multi_signature(
-            certificate.signed_message.as_bytes(),
-            signature,
-            &certificate.aggregate_verification_key,
-            &certificate.metadata.protocol_parameters,
-        )?;
         let previous_certificate = self
             .certificate_retriever
             .get_certificate_details(&certificate.previous_hash)
@@ -263,35 +345,111 @@ impl CertificateVerifier for MithrilCertificateVerifier {
         certificate: &Certificate,
         genesis_verification_key: &ProtocolGenesisVerificationKey,
     ) -> StdResult<Option<Certificate>> {
+        self.verify_certificate_with(certificate, genesis_verification_key, true)
+            .await
+    }
+
+    /// Verify the multi-signatures of the given certificates at once, aggregating their pairing
+    /// checks, genesis certificates are ignored.
+    ///
+    /// If the batch is invalid, the returned error targets the first certificate which
+    /// multi-signature is invalid.
+    fn verify_multi_signatures_batch(
+        &self,
+        certificates: &[Certificate],
+    ) -> Result<(), MultiSignaturesBatchError> {
+        let indexed_certificates: Vec<(usize, &Certificate, &ProtocolMultiSignature)> =
+            certificates
+                .iter()
+                .enumerate()
+                .filter_map(|(index, certificate)| match &certificate.signature {
+                    CertificateSignature::MultiSignature(signature) => {
+                        Some((index, certificate, signature))
+                    }
+                    CertificateSignature::GenesisSignature(_) => None,
+                })
+                .collect();
+        let certificates: Vec<(&Certificate, &ProtocolMultiSignature)> = indexed_certificates
+            .iter()
+            .map(|(_, certificate, signature)| (*certificate, *signature))
+            .collect();
         debug!(
             self.logger,
-            "Verifying certificate";
-            "certificate_hash" => &certificate.hash,
-            "certificate_previous_hash" => &certificate.previous_hash,
-            "certificate_beacon" => ?certificate.beacon
+            "Verify a batch of {} multi signatures",
+            certificates.len()
         );
 
-        certificate
-            .hash
-            .eq(&certificate.compute_hash())
-            .then(|| certificate.hash.clone())
-            .ok_or(CertificateVerifierError::CertificateHashUnmatch)?;
+        let multi_signatures: Vec<ProtocolMultiSignature> = certificates
+            .iter()
+            .map(|(_, signature)| (*signature).to_owned())
+            .collect();
+        let messages: Vec<Vec<u8>> = certificates
+            .iter()
+            .map(|(certificate, _)| certificate.signed_message.as_bytes().to_vec())
+            .collect();
+        let aggregate_verification_keys: Vec<ProtocolAggregateVerificationKey> = certificates
+            .iter()
+            .map(|(certificate, _)| certificate.aggregate_verification_key.to_owned())
+            .collect();
+        let parameters: Vec<_> = certificates
+            .iter()
+            .map(|(certificate, _)| certificate.metadata.protocol_parameters.to_owned().into())
+            .collect();
 
-        if certificate.is_chaining_to_itself() {
-            Err(anyhow!(
-                CertificateVerifierError::CertificateChainInfiniteLoop
-            ))
-        } else {
-            match &certificate.signature {
-                CertificateSignature::GenesisSignature(_signature) => {
-                    self.verify_genesis_certificate(certificate, genesis_verification_key)
-                        .await?;
-                    Ok(None)
-                }
-                CertificateSignature::MultiSignature(signature) => {
-                    self.verify_standard_certificate(certificate, signature)
-                        .await
-                }
+        ProtocolMultiSignature::batch_verify(
+            &multi_signatures,
+            &messages,
+            &aggregate_verification_keys,
+            &parameters,
+        )
+        .map_err(|(index, e)| MultiSignaturesBatchError {
+            index: indexed_certificates[index].0,
+            error: CertificateVerifierError::VerifyMultiSignature(format!(
+                "certificate '{}': {e}",
+                certificates[index].0.hash
+            )),
+        })
+    }
+
+    /// Verify a certificate except for its multi-signature
+    async fn verify_certificate_deferring_multi_signature(
+        &self,
+        certificate: &Certificate,
+        genesis_verification_key: &ProtocolGenesisVerificationKey,
+    ) -> StdResult<Option<Certificate>> {
+        self.verify_certificate_with(certificate, genesis_verification_key, false)
+            .await
+    }
+
+    /// Verify a certificate chain, the multi-signatures of the certificates of a same epoch are
+    /// verified in a single batch once all of them were chained.
+    async fn verify_certificate_chain(
+        &self,
+        certificate: Certificate,
+        genesis_verification_key: &ProtocolGenesisVerificationKey,
+    ) -> StdResult<()> {
+        let mut certificate = certificate;
+        let mut epoch_certificates = vec![];
+        loop {
+            let previous_or_none = self
+                .verify_certificate_deferring_multi_signature(
+                    &certificate,
+                    genesis_verification_key,
+                )
+                .await?;
+            let is_last_of_epoch = previous_or_none
+                .as_ref()
+                .is_none_or(|previous| previous.beacon.epoch != certificate.beacon.epoch);
+            epoch_certificates.push(certificate);
+            if is_last_of_epoch {
+                self.verify_multi_signatures_batch(&epoch_certificates)
+                    .map_err(|batch_error| batch_error.error)?;
+                epoch_certificates.clear();
+            }
+
+            match previous_or_none {
+                Some(previous_certificate) => certificate = previous_certificate,
+                None => return Ok(()),
             }
         }
     }
@@ -15,6 +15,7 @@ use anyhow::{anyhow, Context};
 use hex::FromHex;
 use kes_summed_ed25519::kes::Sum6Kes;
 use kes_summed_ed25519::traits::KesSk;
+use secrecy::SecretBox;
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use serde_with::{As, Bytes};
@@ -22,6 +23,7 @@ use std::fs;
 use std::io::Write;
 use std::path::Path;
 use thiserror::Error;
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
 
 use crate::common::StdError;
 
@@ -31,13 +33,49 @@ use crate::common::StdError;
 // We need this helper structure, because we are currently getting the key
 // from a file, instead of directly consuming a buffer.
 // todo: create the KES key directly from a buffer instead of deserialising from disk
-#[derive(Clone, Serialize, Deserialize)]
+//
+// The bytes are zeroized on drop and the structure is not `Clone` so the secret key isn't
+// silently duplicated.
+#[derive(Serialize, Deserialize)]
 pub struct Sum6KesBytes(#[serde(with = "As::<Bytes>")] pub [u8; 612]);
 
+impl Sum6KesBytes {
+    /// Read a KES secret key file into a [SecretBox]: the key bytes live in a single heap
+    /// allocation, zeroized on drop, and are only reachable through explicit
+    /// [expose_secret][secrecy::ExposeSecret] calls.
+    pub fn secret_from_file<P: AsRef<Path>>(path: P) -> Result<SecretBox<Self>, CodecParseError> {
+        let key = Self::from_file(path)?;
+
+        Ok(SecretBox::init_with_mut(|secret: &mut Self| {
+            secret.0.copy_from_slice(&key.0)
+        }))
+    }
+}
+
+impl Default for Sum6KesBytes {
+    fn default() -> Self {
+        Self([0; 612])
+    }
+}
+
+impl Zeroize for Sum6KesBytes {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl Drop for Sum6KesBytes {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for Sum6KesBytes {}
+
 /// Parse error
 #[derive(Error, Debug)]
 #[error("Codec parse error")]
-pub struct CodecParseError(#[source] StdError);
+pub struct CodecParseError(#[source] pub(super) StdError);
 
 /// Fields for a shelley formatted file (holds for vkeys, skeys or certs)
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
@@ -49,6 +87,13 @@ struct ShelleyFileFormat {
     cbor_hex: String,
 }
 
+// The cbor may encode a secret key
+impl Drop for ShelleyFileFormat {
+    fn drop(&mut self) {
+        self.cbor_hex.zeroize();
+    }
+}
+
 /// Trait that allows any structure that implements Serialize and DeserializeOwned to
 /// be serialized and deserialized following the Shelly json format.
 pub trait SerDeShelleyFileFormat: Serialize + DeserializeOwned {
@@ -60,16 +105,10 @@ pub trait SerDeShelleyFileFormat: Serialize + DeserializeOwned {
 
     /// Deserialize a type `T: Serialize + DeserializeOwned` from file following Cardano
     /// Shelley file format.
+    ///
+    /// The buffers holding the file content are zeroized once it's deserialized.
     fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, CodecParseError> {
-        let data = fs::read_to_string(path)
-            .with_context(|| "SerDeShelleyFileFormat can not read data from file {}")
-            .map_err(|e| CodecParseError(anyhow!(e)))?;
-        let file: ShelleyFileFormat = serde_json::from_str(&data)
-            .with_context(|| "SerDeShelleyFileFormat can not unserialize json data")
-            .map_err(|e| CodecParseError(anyhow!(e)))?;
-        let hex_vector = Vec::from_hex(file.cbor_hex)
-            .with_context(|| "SerDeShelleyFileFormat can not unserialize hex data")
-            .map_err(|e| CodecParseError(anyhow!(e)))?;
+        let hex_vector = Self::cbor_from_file(path)?;
         let a: Self = serde_cbor::from_slice(&hex_vector)
             .with_context(|| "SerDeShelleyFileFormat can not unserialize cbor data")
             .map_err(|e| CodecParseError(anyhow!(e)))?;
@@ -77,14 +116,34 @@ pub trait SerDeShelleyFileFormat: Serialize + DeserializeOwned {
         Ok(a)
     }
 
+    /// Read the CBOR bytes of a file following Cardano Shelley file format.
+    ///
+    /// The buffers holding the file content are zeroized once they are dropped.
+    fn cbor_from_file<P: AsRef<Path>>(path: P) -> Result<Zeroizing<Vec<u8>>, CodecParseError> {
+        let data = Zeroizing::new(
+            fs::read_to_string(path)
+                .with_context(|| "SerDeShelleyFileFormat can not read data from file {}")
+                .map_err(|e| CodecParseError(anyhow!(e)))?,
+        );
+        let file: ShelleyFileFormat = serde_json::from_str(&data)
+            .with_context(|| "SerDeShelleyFileFormat can not unserialize json data")
+            .map_err(|e| CodecParseError(anyhow!(e)))?;
+
+        Ok(Zeroizing::new(
+            Vec::from_hex(file.cbor_hex.as_bytes())
+                .with_context(|| "SerDeShelleyFileFormat can not unserialize hex data")
+                .map_err(|e| CodecParseError(anyhow!(e)))?,
+        ))
+    }
+
     /// Serialize a type `T: Serialize + DeserializeOwned` to file following Cardano
     /// Shelley file format.
     fn to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), CodecParseError> {
-        let cbor_string = hex::encode(
+        let cbor_string = hex::encode(Zeroizing::new(
             serde_cbor::to_vec(&self)
                 .with_context(|| "SerDeShelleyFileFormat can not serialize data to cbor")
                 .map_err(|e| CodecParseError(anyhow!(e)))?,
-        );
+        ));
 
         let file_format = ShelleyFileFormat {
             file_type: Self::TYPE.to_string(),
@@ -95,11 +154,13 @@ pub trait SerDeShelleyFileFormat: Serialize + DeserializeOwned {
         let mut file = fs::File::create(path)
             .with_context(|| "SerDeShelleyFileFormat can not create file")
             .map_err(|e| CodecParseError(anyhow!(e)))?;
-        let json_str = serde_json::to_string(&file_format)
-            .with_context(|| "SerDeShelleyFileFormat can not serialize data to json")
-            .map_err(|e| CodecParseError(anyhow!(e)))?;
+        let json_str = Zeroizing::new(
+            serde_json::to_string(&file_format)
+                .with_context(|| "SerDeShelleyFileFormat can not serialize data to json")
+                .map_err(|e| CodecParseError(anyhow!(e)))?,
+        );
 
-        write!(file, "{json_str}")
+        write!(file, "{}", *json_str)
             .with_context(|| "SerDeShelleyFileFormat can not write data to file")
             .map_err(|e| CodecParseError(anyhow!(e)))?;
         Ok(())
@@ -114,13 +175,19 @@ impl SerDeShelleyFileFormat for Sum6KesBytes {
     /// contain the period (it is always zero). Therefore we need to include it in the
     /// deserialisation.
     fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, CodecParseError> {
-        let data = fs::read_to_string(path)
-            .with_context(|| "Sum6KesBytes can not read data from file")
-            .map_err(|e| CodecParseError(anyhow!(e)))?;
+        let data = Zeroizing::new(
+            fs::read_to_string(path)
+                .with_context(|| "Sum6KesBytes can not read data from file")
+                .map_err(|e| CodecParseError(anyhow!(e)))?,
+        );
         let file: ShelleyFileFormat = serde_json::from_str(&data)
             .with_context(|| "Sum6KesBytes can not unserialize json data")
             .map_err(|e| CodecParseError(anyhow!(e)))?;
-        let mut hex_vector = Vec::from_hex(file.cbor_hex)
+        // The buffer is allocated with room for the period, so appending it never reallocates
+        // and leaves a copy of the key behind
+        let decoded_len = file.cbor_hex.len() / 2;
+        let mut hex_vector = Zeroizing::new(vec![0u8; decoded_len + 4]);
+        hex::decode_to_slice(&file.cbor_hex, &mut hex_vector[..decoded_len])
             .with_context(|| "Sum6KesBytes can not unserialize hex data")
             .map_err(|e| CodecParseError(anyhow!(e)))?;
 
@@ -128,8 +195,9 @@ impl SerDeShelleyFileFormat for Sum6KesBytes {
         if (hex_vector[2] & 4u8) == 0 {
             // First we need to change the cbor format to notify about the extra 4 bytes:
             hex_vector[2] |= 4u8;
-            // Then we append the bytes representing the period = 0
-            hex_vector.extend_from_slice(&[0u8; 4]);
+            // Then we keep the zeroed bytes representing the period = 0
+        } else {
+            hex_vector.truncate(decoded_len);
         }
 
         let a: Self = serde_cbor::from_slice(&hex_vector)
@@ -176,4 +244,36 @@ mod test {
 
         assert!(Sum6Kes::try_from(&mut kes_sk_bytes).is_ok());
     }
+
+    #[test]
+    fn zeroize_kes_secret_key_bytes() {
+        let mut kes_sk_bytes = Sum6KesBytes([7; 612]);
+
+        kes_sk_bytes.zeroize();
+
+        assert_eq!([0; 612], kes_sk_bytes.0);
+    }
+
+    #[test]
+    fn read_kes_secret_key_file_as_secret() {
+        use secrecy::ExposeSecretMut;
+
+        let temp_dir = std::env::temp_dir()
+            .join("mithril_test")
+            .join("codec")
+            .join("read_kes_secret_key_file_as_secret");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let sk_path = temp_dir.join("kes.skey");
+        let mut seed = [1u8; 32];
+        let mut key_buffer = [0u8; Sum6Kes::SIZE + 4];
+        let (secret_key, _) = Sum6Kes::keygen(&mut key_buffer, &mut seed);
+        let mut expected_bytes = [0u8; 612];
+        expected_bytes.copy_from_slice(secret_key.as_bytes());
+        Sum6KesBytes(expected_bytes).to_file(&sk_path).unwrap();
+
+        let mut secret = Sum6KesBytes::secret_from_file(&sk_path).unwrap();
+
+        assert_eq!(expected_bytes, secret.expose_secret_mut().0);
+        assert!(Sum6Kes::try_from(secret.expose_secret_mut()).is_ok());
+    }
 }
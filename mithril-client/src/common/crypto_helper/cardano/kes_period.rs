@@ -0,0 +1,176 @@
+//! Compute the KES period to sign with from the Cardano genesis parameters.
+//!
+//! The KES period given to [StmInitializerWrapper::setup][super::StmInitializerWrapper::setup] is
+//! the number of evolutions of the KES key since the start KES period of its operational
+//! certificate, not the KES period of the chain: a [KesPeriodCalculator] computes it from the
+//! current slot so it doesn't have to be computed by hand.
+
+use thiserror::Error;
+
+use crate::common::crypto_helper::cardano::{KESPeriod, OpCert};
+
+/// [KesPeriodCalculator] related errors.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum KesPeriodError {
+    /// Error raised when the genesis parameters give KES periods of zero slot.
+    #[error("the number of slots per KES period must be greater than zero")]
+    ZeroSlotsPerKesPeriod,
+
+    /// Error raised when the operational certificate starts after the current KES period.
+    #[error(
+        "operational certificate starts at KES period {start_kes_period}, after the current KES period {current_kes_period}"
+    )]
+    OpCertNotStarted {
+        /// KES period of the chain
+        current_kes_period: u64,
+        /// Start KES period of the operational certificate
+        start_kes_period: u64,
+    },
+
+    /// Error raised when the KES key of the operational certificate can't be evolved to the
+    /// current KES period anymore, a new operational certificate must be issued.
+    #[error(
+        "operational certificate started at KES period {start_kes_period} is expired at KES period {current_kes_period}, after {max_kes_evolutions} evolutions"
+    )]
+    OpCertExpired {
+        /// KES period of the chain
+        current_kes_period: u64,
+        /// Start KES period of the operational certificate
+        start_kes_period: u64,
+        /// Maximum number of evolutions of a KES key
+        max_kes_evolutions: u64,
+    },
+}
+
+/// Compute KES periods from the `slotsPerKESPeriod` and `maxKESEvolutions` parameters of the
+/// Shelley genesis of a Cardano network.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KesPeriodCalculator {
+    slots_per_kes_period: u64,
+    max_kes_evolutions: u64,
+}
+
+impl KesPeriodCalculator {
+    /// Constructs a new `KesPeriodCalculator` from the Shelley genesis parameters.
+    pub fn new(slots_per_kes_period: u64, max_kes_evolutions: u64) -> Result<Self, KesPeriodError> {
+        if slots_per_kes_period == 0 {
+            return Err(KesPeriodError::ZeroSlotsPerKesPeriod);
+        }
+
+        Ok(Self {
+            slots_per_kes_period,
+            max_kes_evolutions,
+        })
+    }
+
+    /// `KesPeriodCalculator` of the Cardano mainnet, preprod and preview networks, which share
+    /// the same genesis parameters: 129600 slots per KES period and 62 evolutions.
+    pub fn cardano() -> Self {
+        Self {
+            slots_per_kes_period: 129600,
+            max_kes_evolutions: 62,
+        }
+    }
+
+    /// KES period of the chain at the given slot.
+    pub fn current_kes_period(&self, current_slot: u64) -> u64 {
+        current_slot / self.slots_per_kes_period
+    }
+
+    /// KES period to sign with at the given slot using the KES key of the given operational
+    /// certificate, ie: the number of evolutions of the key since the certificate start.
+    pub fn kes_period_for(
+        &self,
+        current_slot: u64,
+        opcert: &OpCert,
+    ) -> Result<KESPeriod, KesPeriodError> {
+        let current_kes_period = self.current_kes_period(current_slot);
+        let start_kes_period = opcert.start_kes_period;
+        let evolutions = current_kes_period.checked_sub(start_kes_period).ok_or(
+            KesPeriodError::OpCertNotStarted {
+                current_kes_period,
+                start_kes_period,
+            },
+        )?;
+
+        if evolutions >= self.max_kes_evolutions {
+            return Err(KesPeriodError::OpCertExpired {
+                current_kes_period,
+                start_kes_period,
+                max_kes_evolutions: self.max_kes_evolutions,
+            });
+        }
+
+        KESPeriod::try_from(evolutions).map_err(|_| KesPeriodError::OpCertExpired {
+            current_kes_period,
+            start_kes_period,
+            max_kes_evolutions: self.max_kes_evolutions,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ed25519_dalek::SigningKey;
+    use kes_summed_ed25519::kes::Sum6Kes;
+    use kes_summed_ed25519::traits::KesSk;
+
+    use super::*;
+
+    fn opcert_starting_at(start_kes_period: u64) -> OpCert {
+        let (_, kes_vk) = Sum6Kes::keygen(&mut [0u8; Sum6Kes::SIZE + 4], &mut [1u8; 32]);
+
+        OpCert::new(
+            kes_vk,
+            0,
+            start_kes_period,
+            SigningKey::from_bytes(&[2u8; 32]),
+        )
+    }
+
+    #[test]
+    fn reject_kes_periods_of_zero_slot() {
+        assert_eq!(
+            Err(KesPeriodError::ZeroSlotsPerKesPeriod),
+            KesPeriodCalculator::new(0, 62)
+        );
+    }
+
+    #[test]
+    fn compute_the_kes_period_relative_to_the_opcert_start() {
+        let calculator = KesPeriodCalculator::new(100, 62).unwrap();
+        let opcert = opcert_starting_at(10);
+
+        assert_eq!(14, calculator.current_kes_period(1450));
+        assert_eq!(Ok(0), calculator.kes_period_for(1000, &opcert));
+        assert_eq!(Ok(4), calculator.kes_period_for(1450, &opcert));
+        assert_eq!(Ok(61), calculator.kes_period_for(7199, &opcert));
+    }
+
+    #[test]
+    fn reject_an_opcert_not_started_yet() {
+        let calculator = KesPeriodCalculator::new(100, 62).unwrap();
+
+        assert_eq!(
+            Err(KesPeriodError::OpCertNotStarted {
+                current_kes_period: 9,
+                start_kes_period: 10,
+            }),
+            calculator.kes_period_for(999, &opcert_starting_at(10))
+        );
+    }
+
+    #[test]
+    fn reject_an_expired_opcert() {
+        let calculator = KesPeriodCalculator::new(100, 62).unwrap();
+
+        assert_eq!(
+            Err(KesPeriodError::OpCertExpired {
+                current_kes_period: 72,
+                start_kes_period: 10,
+                max_kes_evolutions: 62,
+            }),
+            calculator.kes_period_for(7200, &opcert_starting_at(10))
+        );
+    }
+}
@@ -0,0 +1,118 @@
+//! Sign messages with a KES secret key, wherever it lives.
+
+use anyhow::{anyhow, Context};
+use kes_summed_ed25519::kes::{Sum6Kes, Sum6KesSig};
+use kes_summed_ed25519::traits::KesSk;
+use secrecy::ExposeSecretMut;
+use std::path::{Path, PathBuf};
+
+use crate::common::crypto_helper::cardano::{
+    KESPeriod, ProtocolInitializerErrorWrapper, Sum6KesBytes,
+};
+use crate::common::StdResult;
+
+/// A KES signer signs messages at a given KES period.
+///
+/// Implementing it allows to use KES secret keys held by a HSM or an enclave without exposing
+/// their raw bytes to the library.
+pub trait KesSigner: Send + Sync {
+    /// Sign the given message with the KES secret key evolved to the given KES period.
+    fn sign(&self, message: &[u8], kes_period: KESPeriod) -> StdResult<Sum6KesSig>;
+}
+
+/// Default [KesSigner] reading its KES secret key from a Cardano Shelley file.
+#[derive(Debug, Clone)]
+pub struct KesSignerStandard {
+    kes_sk_path: PathBuf,
+}
+
+impl KesSignerStandard {
+    /// [KesSignerStandard] factory
+    pub fn new<P: AsRef<Path>>(kes_sk_path: P) -> Self {
+        Self {
+            kes_sk_path: kes_sk_path.as_ref().to_path_buf(),
+        }
+    }
+}
+
+impl KesSigner for KesSignerStandard {
+    fn sign(&self, message: &[u8], kes_period: KESPeriod) -> StdResult<Sum6KesSig> {
+        let mut kes_sk_bytes = Sum6KesBytes::secret_from_file(&self.kes_sk_path)
+            .map_err(|e| anyhow!(e))
+            .with_context(|| "KesSignerStandard can not read KES secret key from file")?;
+        let mut kes_sk = Sum6Kes::try_from(kes_sk_bytes.expose_secret_mut())
+            .map_err(|e| ProtocolInitializerErrorWrapper::ProtocolInitializer(anyhow!(e)))
+            .with_context(|| "KesSignerStandard can not use KES secret key")?;
+        let kes_sk_period = kes_sk.get_period();
+        if kes_sk_period > kes_period {
+            return Err(anyhow!(ProtocolInitializerErrorWrapper::KesMismatch(
+                kes_sk_period,
+                kes_period,
+            )));
+        }
+
+        // We need to perform the evolutions
+        for period in kes_sk_period..kes_period {
+            kes_sk
+                .update()
+                .map_err(|_| ProtocolInitializerErrorWrapper::KesUpdate(period))?;
+        }
+
+        Ok(kes_sk.sign(message))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use kes_summed_ed25519::traits::KesSig;
+    use kes_summed_ed25519::PublicKey;
+
+    use crate::common::crypto_helper::cardano::SerDeShelleyFileFormat;
+
+    use super::*;
+
+    fn create_kes_secret_key_file(test_name: &str) -> (PathBuf, PublicKey) {
+        let temp_dir = std::env::temp_dir()
+            .join("mithril_test")
+            .join("kes_signer")
+            .join(test_name);
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let kes_sk_path = temp_dir.join("kes.skey");
+        let mut seed = [3u8; 32];
+        let mut key_buffer = [0u8; Sum6Kes::SIZE + 4];
+        let (kes_sk, kes_vk) = Sum6Kes::keygen(&mut key_buffer, &mut seed);
+        let mut kes_sk_bytes = Sum6KesBytes::default();
+        kes_sk_bytes.0.copy_from_slice(kes_sk.as_bytes());
+        kes_sk_bytes.to_file(&kes_sk_path).unwrap();
+
+        (kes_sk_path, kes_vk)
+    }
+
+    #[test]
+    fn sign_with_a_key_evolved_to_the_given_period() {
+        let (kes_sk_path, kes_vk) =
+            create_kes_secret_key_file("sign_with_a_key_evolved_to_the_given_period");
+        let signer = KesSignerStandard::new(kes_sk_path);
+
+        let signature = signer.sign(b"message", 3).unwrap();
+
+        signature.verify(3, &kes_vk, b"message").unwrap();
+        signature
+            .verify(0, &kes_vk, b"message")
+            .expect_err("signature should not be valid for another period");
+    }
+
+    #[test]
+    fn sign_fails_if_the_key_file_is_missing() {
+        let signer = KesSignerStandard::new(
+            std::env::temp_dir()
+                .join("mithril_test")
+                .join("kes_signer")
+                .join("does_not_exist.skey"),
+        );
+
+        signer
+            .sign(b"message", 0)
+            .expect_err("sign should fail without KES secret key file");
+    }
+}
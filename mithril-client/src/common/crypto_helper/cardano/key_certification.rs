@@ -5,7 +5,6 @@
 
 use crate::common::{
     crypto_helper::{
-        cardano::SerDeShelleyFileFormat,
         types::{
             ProtocolParameters, ProtocolPartyId, ProtocolSignerVerificationKey,
             ProtocolSignerVerificationKeySignature, ProtocolStakeDistribution,
@@ -19,17 +18,19 @@ use mithril_stm::key_reg::{ClosedKeyReg, KeyReg};
 use mithril_stm::stm::{Stake, StmInitializer, StmParameters, StmSigner, StmVerificationKeyPoP};
 use mithril_stm::RegisterError;
 
-use crate::common::crypto_helper::cardano::Sum6KesBytes;
+use crate::common::crypto_helper::cardano::{KesSigner, KesSignerStandard};
 use anyhow::{anyhow, Context};
 use blake2::{
     digest::{consts::U32, FixedOutput},
     Blake2b, Digest,
 };
-use kes_summed_ed25519::kes::{Sum6Kes, Sum6KesSig};
-use kes_summed_ed25519::traits::{KesSig, KesSk};
+use kes_summed_ed25519::kes::Sum6KesSig;
+use kes_summed_ed25519::traits::KesSig;
 use rand_core::{CryptoRng, RngCore};
 use serde::{Deserialize, Serialize};
+use slog::{o, warn, Logger};
 use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
 use std::path::Path;
 use thiserror::Error;
 
@@ -94,16 +95,70 @@ pub enum ProtocolInitializerErrorWrapper {
     /// Period of key file does not match with period provided by user
     #[error("Period of key file, {0}, does not match with period provided by user, {1}")]
     KesMismatch(KESPeriod, KESPeriod),
+
+    /// Error raised in strict mode when no KES secret key is provided to certify the registration
+    #[error("missing KES secret key: non certified signer registration is refused in strict mode")]
+    UncertifiedRegistration,
+}
+
+/// Options of [StmInitializerWrapper::setup_with_options].
+///
+/// By default the warnings are discarded and a non certified registration, without KES secret
+/// key, is allowed.
+#[derive(Debug, Clone)]
+pub struct StmInitializerSetupOptions {
+    logger: Logger,
+    strict: bool,
+}
+
+impl Default for StmInitializerSetupOptions {
+    fn default() -> Self {
+        Self {
+            logger: Logger::root(slog::Discard, o!()),
+            strict: false,
+        }
+    }
+}
+
+impl StmInitializerSetupOptions {
+    /// Set the [Logger] that receives the warnings of the setup.
+    pub fn with_logger(mut self, logger: Logger) -> Self {
+        self.logger = logger;
+        self
+    }
+
+    /// Refuse non certified registrations with a
+    /// [ProtocolInitializerErrorWrapper::UncertifiedRegistration] error instead of logging a
+    /// warning.
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
 }
 /// Wrapper structure for [MithrilStm:StmInitializer](mithril_stm::stm::StmInitializer).
 /// It now obtains a KES signature over the Mithril key. This allows the signers prove
 /// their correct identity with respect to a Cardano PoolID.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// The wrapper holds the signer secret key: it is not `Clone` so the key is never silently
+/// duplicated, and its `Debug` output redacts it. The secret key itself is zeroized on drop.
+#[derive(Serialize, Deserialize)]
 pub struct StmInitializerWrapper {
     stm_initializer: StmInitializer,
     kes_signature: Option<Sum6KesSig>, // todo: The option is ONLY for a smooth transition. We have to remove this.
 }
 
+impl Debug for StmInitializerWrapper {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StmInitializerWrapper")
+            .field("stake", &self.stm_initializer.stake)
+            .field("params", &self.stm_initializer.params)
+            .field("secret_key", &"[REDACTED]")
+            .field("verification_key", &self.stm_initializer.verification_key())
+            .field("kes_signature", &self.kes_signature.is_some())
+            .finish()
+    }
+}
+
 /// Wrapper structure for [MithrilStm:KeyReg](mithril_stm::key_reg::KeyReg).
 /// The wrapper not only contains a map between `Mithril vkey <-> Stake`, but also
 /// a map `PoolID <-> Stake`. This information is recovered from the node state, and
@@ -120,6 +175,9 @@ impl StmInitializerWrapper {
     /// Builds an `StmInitializer` that is ready to register with the key registration service.
     /// This function generates the signing and verification key with a PoP, signs the verification
     /// key with a provided KES signing key, and initializes the structure.
+    ///
+    /// The `kes_period` is relative to the start of the operational certificate of the KES key,
+    /// use a [KesPeriodCalculator][super::KesPeriodCalculator] to compute it from the current slot.
     pub fn setup<R: RngCore + CryptoRng, P: AsRef<Path>>(
         params: StmParameters,
         kes_sk_path: Option<P>,
@@ -127,33 +185,68 @@ impl StmInitializerWrapper {
         stake: Stake,
         rng: &mut R,
     ) -> StdResult<Self> {
-        let stm_initializer = StmInitializer::setup(params, stake, rng);
-        let kes_signature = if let Some(kes_sk_path) = kes_sk_path {
-            let mut kes_sk_bytes = Sum6KesBytes::from_file(kes_sk_path)
-                .map_err(|e| anyhow!(e))
-                .with_context(|| "StmInitializerWrapper can not read KES secret key from file")?;
-            let mut kes_sk = Sum6Kes::try_from(&mut kes_sk_bytes)
-                .map_err(|e| ProtocolInitializerErrorWrapper::ProtocolInitializer(anyhow!(e)))
-                .with_context(|| "StmInitializerWrapper can not use KES secret key")?;
-            let kes_sk_period = kes_sk.get_period();
-            let provided_period = kes_period.unwrap_or_default();
-            if kes_sk_period > provided_period {
-                return Err(anyhow!(ProtocolInitializerErrorWrapper::KesMismatch(
-                    kes_sk_period,
-                    provided_period,
-                )));
-            }
+        Self::setup_with_options(
+            params,
+            kes_sk_path,
+            kes_period,
+            stake,
+            rng,
+            &StmInitializerSetupOptions::default(),
+        )
+    }
 
-            // We need to perform the evolutions
-            for period in kes_sk_period..provided_period {
-                kes_sk
-                    .update()
-                    .map_err(|_| ProtocolInitializerErrorWrapper::KesUpdate(period))?;
-            }
+    /// Same as [setup][Self::setup], honoring the given [StmInitializerSetupOptions].
+    pub fn setup_with_options<R: RngCore + CryptoRng, P: AsRef<Path>>(
+        params: StmParameters,
+        kes_sk_path: Option<P>,
+        kes_period: Option<KESPeriod>,
+        stake: Stake,
+        rng: &mut R,
+        options: &StmInitializerSetupOptions,
+    ) -> StdResult<Self> {
+        let kes_signer = kes_sk_path.map(KesSignerStandard::new);
+
+        Self::setup_with_kes_signer(
+            params,
+            kes_signer.as_ref().map(|s| s as &dyn KesSigner),
+            kes_period,
+            stake,
+            rng,
+            options,
+        )
+    }
 
-            Some(kes_sk.sign(&stm_initializer.verification_key().to_bytes()))
+    /// Same as [setup_with_options][Self::setup_with_options], the verification key being signed
+    /// by the given [KesSigner] instead of a KES secret key read from a file.
+    pub fn setup_with_kes_signer<R: RngCore + CryptoRng>(
+        params: StmParameters,
+        kes_signer: Option<&dyn KesSigner>,
+        kes_period: Option<KESPeriod>,
+        stake: Stake,
+        rng: &mut R,
+        options: &StmInitializerSetupOptions,
+    ) -> StdResult<Self> {
+        if kes_signer.is_none() && options.strict {
+            return Err(anyhow!(
+                ProtocolInitializerErrorWrapper::UncertifiedRegistration
+            ));
+        }
+        let stm_initializer = StmInitializer::setup(params, stake, rng);
+        let kes_signature = if let Some(kes_signer) = kes_signer {
+            let signature = kes_signer
+                .sign(
+                    &stm_initializer.verification_key().to_bytes(),
+                    kes_period.unwrap_or_default(),
+                )
+                .with_context(|| "StmInitializerWrapper can not sign the verification key")?;
+
+            Some(signature)
         } else {
-            println!("WARNING: Non certified signer registration by providing only a Pool Id is decommissionned and must be used for tests only!");
+            warn!(
+                options.logger,
+                "Non certified signer registration by providing only a Pool Id is decommissionned and must be used for tests only!";
+                "stake" => stake
+            );
             None
         };
 
@@ -183,6 +276,16 @@ impl StmInitializerWrapper {
         self.stm_initializer.stake
     }
 
+    /// Explicitly duplicate the initializer, including its secret key.
+    ///
+    /// The initializer isn't `Clone` so that copies of the secret key are only made on purpose.
+    pub fn duplicate_secret(&self) -> Self {
+        Self {
+            stm_initializer: self.stm_initializer.clone(),
+            kes_signature: self.kes_signature,
+        }
+    }
+
     /// Build the `avk` for the given list of parties.
     ///
     /// Note that if this StmInitializer was modified *between* the last call to `register`,
@@ -300,3 +403,119 @@ impl KeyRegWrapper {
         self.stm_key_reg.close()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use kes_summed_ed25519::kes::Sum6Kes;
+    use kes_summed_ed25519::traits::KesSk;
+    use rand_chacha::ChaCha20Rng;
+    use rand_core::SeedableRng;
+
+    use super::*;
+
+    fn setup(options: &StmInitializerSetupOptions) -> StdResult<StmInitializerWrapper> {
+        StmInitializerWrapper::setup_with_options(
+            StmParameters {
+                m: 10,
+                k: 5,
+                phi_f: 0.65,
+            },
+            None::<&Path>,
+            None,
+            10,
+            &mut ChaCha20Rng::from_seed([0; 32]),
+            options,
+        )
+    }
+
+    #[test]
+    fn allow_non_certified_registration_by_default() {
+        let initializer = setup(
+            &StmInitializerSetupOptions::default().with_logger(crate::test_utils::test_logger()),
+        )
+        .unwrap();
+
+        assert_eq!(None, initializer.verification_key_signature());
+    }
+
+    #[test]
+    fn refuse_non_certified_registration_in_strict_mode() {
+        let error = setup(&StmInitializerSetupOptions::default().with_strict(true))
+            .expect_err("setup without KES secret key should fail in strict mode");
+
+        assert!(
+            matches!(
+                error.downcast_ref::<ProtocolInitializerErrorWrapper>(),
+                Some(ProtocolInitializerErrorWrapper::UncertifiedRegistration)
+            ),
+            "Unexpected error: {error:?}"
+        );
+    }
+
+    #[test]
+    fn debug_output_redacts_the_secret_key() {
+        let initializer = setup(&StmInitializerSetupOptions::default()).unwrap();
+        let secret_key_bytes = initializer.to_bytes()[32..64].to_vec();
+
+        let debug = format!("{initializer:?}");
+
+        assert!(debug.contains("[REDACTED]"), "{debug}");
+        assert!(!debug.contains(&hex::encode(&secret_key_bytes)), "{debug}");
+        assert!(!debug.contains(&format!("{secret_key_bytes:?}")), "{debug}");
+    }
+
+    #[test]
+    fn duplicate_secret_keeps_the_same_keys() {
+        let initializer = setup(&StmInitializerSetupOptions::default()).unwrap();
+
+        let duplicate = initializer.duplicate_secret();
+
+        assert_eq!(initializer.to_bytes(), duplicate.to_bytes());
+    }
+
+    #[test]
+    fn setup_signs_the_verification_key_with_the_given_kes_signer() {
+        struct InMemoryKesSigner {
+            seed: [u8; 32],
+        }
+
+        impl KesSigner for InMemoryKesSigner {
+            fn sign(&self, message: &[u8], kes_period: KESPeriod) -> StdResult<Sum6KesSig> {
+                let mut seed = self.seed;
+                let mut key_buffer = [0u8; Sum6Kes::SIZE + 4];
+                let (mut kes_sk, _) = Sum6Kes::keygen(&mut key_buffer, &mut seed);
+                for _ in 0..kes_period {
+                    kes_sk.update().unwrap();
+                }
+
+                Ok(kes_sk.sign(message))
+            }
+        }
+
+        let kes_signer = InMemoryKesSigner { seed: [5; 32] };
+        let mut seed = kes_signer.seed;
+        let (_, kes_vk) = Sum6Kes::keygen(&mut [0u8; Sum6Kes::SIZE + 4], &mut seed);
+
+        let initializer = StmInitializerWrapper::setup_with_kes_signer(
+            StmParameters {
+                m: 10,
+                k: 5,
+                phi_f: 0.65,
+            },
+            Some(&kes_signer),
+            Some(2),
+            10,
+            &mut ChaCha20Rng::from_seed([0; 32]),
+            &StmInitializerSetupOptions::default().with_strict(true),
+        )
+        .unwrap();
+
+        let signature: Sum6KesSig = initializer
+            .verification_key_signature()
+            .expect("verification key should be signed")
+            .into();
+        signature
+            .verify(2, &kes_vk, &initializer.verification_key().to_bytes())
+            .unwrap();
+    }
+}
@@ -1,11 +1,15 @@
 mod codec;
 #[cfg(feature = "random")]
 mod cold_key;
+mod kes_period;
+mod kes_signer;
 mod key_certification;
 mod opcert;
 
 pub use codec::*;
 #[cfg(feature = "random")]
 pub use cold_key::*;
+pub use kes_period::*;
+pub use kes_signer::*;
 pub use key_certification::*;
 pub use opcert::*;
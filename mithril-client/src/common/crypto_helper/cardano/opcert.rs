@@ -1,9 +1,10 @@
 //! Module to (de)serialise, OpCert using the same structure as used in Cardano.  
 
-use super::SerDeShelleyFileFormat;
+use super::{CodecParseError, SerDeShelleyFileFormat};
 use crate::common::crypto_helper::cardano::ProtocolRegistrationErrorWrapper;
 use crate::common::crypto_helper::ProtocolPartyId;
 
+use anyhow::anyhow;
 use bech32::{self, ToBase32, Variant};
 use blake2::{digest::consts::U28, Blake2b, Digest};
 use ed25519_dalek::{
@@ -15,6 +16,8 @@ use nom::AsBytes;
 use serde::de::Error;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use sha2::Sha256;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
 use thiserror::Error;
 
 /// Operational certificate error
@@ -23,6 +26,52 @@ pub enum OpCertError {
     /// Error raised when a pool address encoding fails
     #[error("pool address encoding error")]
     PoolAddressEncoding,
+
+    /// Error raised when an operational certificate can not be decoded, the offset is the position
+    /// in the CBOR bytes where the malformed field starts
+    #[error("malformed operational certificate {field} at byte offset {offset}: {reason}")]
+    Malformed {
+        /// Malformed field
+        field: OpCertField,
+        /// Position of the malformed field in the CBOR bytes
+        offset: usize,
+        /// Why the field is malformed
+        reason: String,
+    },
+}
+
+/// Fields of an operational certificate, as laid out in its CBOR encoding
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpCertField {
+    /// The outer array holding the certificate body and the cold verification key
+    Envelope,
+    /// The array holding the KES verification key, the issue number, the start KES period and
+    /// the signature
+    Body,
+    /// The KES verification key
+    KesVerificationKey,
+    /// The issue number
+    IssueNumber,
+    /// The KES period at which the KES key is initialized
+    StartKesPeriod,
+    /// The signature of the body by the cold key
+    Signature,
+    /// The cold verification key
+    ColdVerificationKey,
+}
+
+impl Display for OpCertField {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Envelope => write!(f, "envelope"),
+            Self::Body => write!(f, "body"),
+            Self::KesVerificationKey => write!(f, "KES verification key"),
+            Self::IssueNumber => write!(f, "issue number"),
+            Self::StartKesPeriod => write!(f, "start KES period"),
+            Self::Signature => write!(f, "signature"),
+            Self::ColdVerificationKey => write!(f, "cold verification key"),
+        }
+    }
 }
 
 /// Raw Fields of the operational certificates (without including the cold VK)
@@ -52,6 +101,14 @@ pub struct OpCert {
 impl SerDeShelleyFileFormat for OpCert {
     const TYPE: &'static str = "NodeOperationalCertificate";
     const DESCRIPTION: &'static str = "";
+
+    /// Deserialize an operational certificate from file, the errors tell which field of the
+    /// certificate is malformed.
+    fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, CodecParseError> {
+        let cbor = Self::cbor_from_file(path)?;
+
+        Self::from_cbor_bytes(&cbor).map_err(|e| CodecParseError(anyhow!(e)))
+    }
 }
 
 impl OpCert {
@@ -78,6 +135,25 @@ impl OpCert {
         }
     }
 
+    /// Decode an operational certificate from its CBOR encoding.
+    ///
+    /// If the serde decoding fails the bytes are walked field by field to report which one is
+    /// malformed and where it starts.
+    pub fn from_cbor_bytes(bytes: &[u8]) -> Result<Self, OpCertError> {
+        match serde_cbor::from_slice(bytes) {
+            Ok(opcert) => Ok(opcert),
+            Err(serde_error) => match OpCertCborParser::new(bytes).parse() {
+                Err(diagnostic) => Err(diagnostic),
+                // The diagnostic parser is less strict than serde, keep serde verdict
+                Ok(_) => Err(OpCertError::Malformed {
+                    field: OpCertField::Envelope,
+                    offset: 0,
+                    reason: serde_error.to_string(),
+                }),
+            },
+        }
+    }
+
     /// Compute message to sign
     pub(crate) fn compute_message_to_sign(
         kes_vk: &KesPublicKey,
@@ -140,6 +216,176 @@ impl OpCert {
     }
 }
 
+/// Minimal CBOR reader walking the fields of an operational certificate to locate the first
+/// malformed one.
+struct OpCertCborParser<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> OpCertCborParser<'a> {
+    const MAJOR_TYPE_UNSIGNED_INTEGER: u8 = 0;
+    const MAJOR_TYPE_BYTES: u8 = 2;
+    const MAJOR_TYPE_ARRAY: u8 = 4;
+
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, offset: 0 }
+    }
+
+    fn parse(mut self) -> Result<OpCert, OpCertError> {
+        self.read_array(OpCertField::Envelope, 2)?;
+        self.read_array(OpCertField::Body, 4)?;
+
+        let field = OpCertField::KesVerificationKey;
+        let start = self.offset;
+        let kes_vk = KesPublicKey::from_bytes(self.read_bytes(field, 32)?)
+            .map_err(|e| Self::malformed(field, start, format!("{e:?}")))?;
+        let issue_number = self.read_unsigned_integer(OpCertField::IssueNumber)?;
+        let start_kes_period = self.read_unsigned_integer(OpCertField::StartKesPeriod)?;
+
+        let field = OpCertField::Signature;
+        let start = self.offset;
+        let cert_sig = EdSignature::from_slice(self.read_bytes(field, 64)?)
+            .map_err(|e| Self::malformed(field, start, e.to_string()))?;
+
+        let field = OpCertField::ColdVerificationKey;
+        let start = self.offset;
+        let cold_vk_bytes: [u8; 32] = self.read_bytes(field, 32)?.try_into().unwrap();
+        let cold_vk = EdVerificationKey::from_bytes(&cold_vk_bytes)
+            .map_err(|e| Self::malformed(field, start, e.to_string()))?;
+
+        if self.offset != self.bytes.len() {
+            return Err(Self::malformed(
+                OpCertField::Envelope,
+                self.offset,
+                format!(
+                    "{} unexpected trailing bytes",
+                    self.bytes.len() - self.offset
+                ),
+            ));
+        }
+
+        Ok(OpCert {
+            kes_vk,
+            issue_number,
+            start_kes_period,
+            cert_sig,
+            cold_vk,
+        })
+    }
+
+    fn malformed(field: OpCertField, offset: usize, reason: String) -> OpCertError {
+        OpCertError::Malformed {
+            field,
+            offset,
+            reason,
+        }
+    }
+
+    fn take(
+        &mut self,
+        field: OpCertField,
+        start: usize,
+        len: usize,
+    ) -> Result<&'a [u8], OpCertError> {
+        let end = self
+            .offset
+            .checked_add(len)
+            .filter(|end| *end <= self.bytes.len());
+        match end {
+            Some(end) => {
+                let taken = &self.bytes[self.offset..end];
+                self.offset = end;
+                Ok(taken)
+            }
+            None => Err(Self::malformed(
+                field,
+                start,
+                format!(
+                    "expected {len} bytes at offset {} but only {} remain",
+                    self.offset,
+                    self.bytes.len() - self.offset
+                ),
+            )),
+        }
+    }
+
+    /// Read the header of a CBOR item and returns its argument, ie: the length of a byte string
+    /// or an array, or the value of an unsigned integer.
+    fn read_header(
+        &mut self,
+        field: OpCertField,
+        expected_major_type: u8,
+    ) -> Result<u64, OpCertError> {
+        let start = self.offset;
+        let initial_byte = self.take(field, start, 1)?[0];
+        let major_type = initial_byte >> 5;
+        if major_type != expected_major_type {
+            return Err(Self::malformed(
+                field,
+                start,
+                format!("expected CBOR major type {expected_major_type} but found {major_type}"),
+            ));
+        }
+
+        let argument_len = match initial_byte & 0x1f {
+            value @ 0..=23 => return Ok(value as u64),
+            24 => 1,
+            25 => 2,
+            26 => 4,
+            27 => 8,
+            additional_info => {
+                return Err(Self::malformed(
+                    field,
+                    start,
+                    format!("unsupported CBOR additional information {additional_info}"),
+                ))
+            }
+        };
+
+        Ok(self
+            .take(field, start, argument_len)?
+            .iter()
+            .fold(0u64, |value, byte| (value << 8) | *byte as u64))
+    }
+
+    fn read_array(&mut self, field: OpCertField, expected_len: u64) -> Result<(), OpCertError> {
+        let start = self.offset;
+        let len = self.read_header(field, Self::MAJOR_TYPE_ARRAY)?;
+        if len != expected_len {
+            return Err(Self::malformed(
+                field,
+                start,
+                format!("expected an array of {expected_len} items but found {len} items"),
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn read_bytes(
+        &mut self,
+        field: OpCertField,
+        expected_len: usize,
+    ) -> Result<&'a [u8], OpCertError> {
+        let start = self.offset;
+        let len = self.read_header(field, Self::MAJOR_TYPE_BYTES)?;
+        if len != expected_len as u64 {
+            return Err(Self::malformed(
+                field,
+                start,
+                format!("expected {expected_len} bytes but found {len} bytes"),
+            ));
+        }
+
+        self.take(field, start, expected_len)
+    }
+
+    fn read_unsigned_integer(&mut self, field: OpCertField) -> Result<u64, OpCertError> {
+        self.read_header(field, Self::MAJOR_TYPE_UNSIGNED_INTEGER)
+    }
+}
+
 impl Serialize for OpCert {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -176,3 +422,77 @@ impl<'de> Deserialize<'de> for OpCert {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use kes_summed_ed25519::kes::Sum6Kes;
+    use kes_summed_ed25519::traits::KesSk;
+
+    use super::*;
+
+    fn dummy_opcert() -> OpCert {
+        let (_, kes_vk) = Sum6Kes::keygen(&mut [0u8; Sum6Kes::SIZE + 4], &mut [1u8; 32]);
+
+        OpCert::new(kes_vk, 3, 12, EdSecretKey::from_bytes(&[2u8; 32]))
+    }
+
+    fn malformed_field_and_offset(bytes: &[u8]) -> (OpCertField, usize) {
+        match OpCert::from_cbor_bytes(bytes) {
+            Err(OpCertError::Malformed { field, offset, .. }) => (field, offset),
+            result => panic!("expected a malformed operational certificate, got {result:?}"),
+        }
+    }
+
+    #[test]
+    fn diagnostic_parser_decodes_a_valid_operational_certificate() {
+        let opcert = dummy_opcert();
+        let bytes = serde_cbor::to_vec(&opcert).unwrap();
+
+        assert_eq!(opcert, OpCertCborParser::new(&bytes).parse().unwrap());
+        assert_eq!(opcert, OpCert::from_cbor_bytes(&bytes).unwrap());
+    }
+
+    #[test]
+    fn report_the_malformed_field_and_its_offset() {
+        let bytes = serde_cbor::to_vec(&dummy_opcert()).unwrap();
+        // Layout: envelope header (1), body header (1), KES vk (2 + 32), issue number (1),
+        // start KES period (1), signature (2 + 64), cold vk (2 + 32)
+        let kes_vk_offset = 2;
+        let issue_number_offset = kes_vk_offset + 34;
+        let signature_offset = issue_number_offset + 2;
+        let cold_vk_offset = signature_offset + 66;
+
+        let mut truncated_kes_vk = bytes.clone();
+        truncated_kes_vk[kes_vk_offset + 1] = 31;
+        assert_eq!(
+            (OpCertField::KesVerificationKey, kes_vk_offset),
+            malformed_field_and_offset(&truncated_kes_vk)
+        );
+
+        let mut negative_issue_number = bytes.clone();
+        negative_issue_number[issue_number_offset] = 0x20;
+        assert_eq!(
+            (OpCertField::IssueNumber, issue_number_offset),
+            malformed_field_and_offset(&negative_issue_number)
+        );
+
+        assert_eq!(
+            (OpCertField::Signature, signature_offset),
+            malformed_field_and_offset(&bytes[..signature_offset + 10])
+        );
+
+        let mut missing_cold_vk = bytes[..cold_vk_offset].to_vec();
+        missing_cold_vk[0] = 0x81;
+        assert_eq!(
+            (OpCertField::Envelope, 0),
+            malformed_field_and_offset(&missing_cold_vk)
+        );
+
+        let mut trailing_bytes = bytes.clone();
+        trailing_bytes.push(0);
+        assert_eq!(
+            (OpCertField::Envelope, bytes.len()),
+            malformed_field_and_offset(&trailing_bytes)
+        );
+    }
+}
@@ -13,8 +13,9 @@ mod types;
 // pub use cardano::ColdKeyGenerator;
 
 pub use cardano::{
-    KESPeriod, OpCert, ProtocolInitializerErrorWrapper, ProtocolRegistrationErrorWrapper,
-    SerDeShelleyFileFormat, Sum6KesBytes,
+    KESPeriod, KesPeriodCalculator, KesPeriodError, KesSigner, KesSignerStandard, OpCert,
+    ProtocolInitializerErrorWrapper, ProtocolRegistrationErrorWrapper, SerDeShelleyFileFormat,
+    StmInitializerSetupOptions, Sum6KesBytes,
 };
 pub use codec::*;
 // pub use era::{
@@ -2,8 +2,9 @@ use anyhow::Context;
 use hex::{FromHex, ToHex};
 use kes_summed_ed25519::kes::Sum6KesSig;
 use mithril_stm::stm::{StmAggrSig, StmAggrVerificationKey, StmSig, StmVerificationKeyPoP};
+use mithril_stm::StmAggregateSignatureError;
 
-use crate::common::crypto_helper::{OpCert, ProtocolKey, ProtocolKeyCodec, D};
+use crate::common::crypto_helper::{OpCert, ProtocolKey, ProtocolKeyCodec, ProtocolParameters, D};
 use crate::common::StdResult;
 
 /// Wrapper of [MithrilStm:StmVerificationKeyPoP](type@StmVerificationKeyPoP) to add serialization
@@ -35,6 +36,46 @@ pub type ProtocolGenesisSecretKey = ProtocolKey<ed25519_dalek::SigningKey>;
 /// Wrapper of [MithrilStm:StmAggrVerificationKey](struct@StmAggrVerificationKey).
 pub type ProtocolAggregateVerificationKey = ProtocolKey<StmAggrVerificationKey<D>>;
 
+impl ProtocolMultiSignature {
+    /// Verify a batch of multi-signatures at once, each one against the message, aggregate
+    /// verification key and parameters at the same index, aggregating their pairing checks.
+    ///
+    /// If the batch is invalid each multi-signature is verified on its own to pinpoint the first
+    /// invalid one, which index in the batch is returned with its error.
+    ///
+    /// # Panics
+    ///
+    /// If the given slices don't have the same length.
+    pub fn batch_verify(
+        multi_signatures: &[ProtocolMultiSignature],
+        messages: &[Vec<u8>],
+        aggregate_verification_keys: &[ProtocolAggregateVerificationKey],
+        parameters: &[ProtocolParameters],
+    ) -> Result<(), (usize, StmAggregateSignatureError<D>)> {
+        let signatures: Vec<StmAggrSig<D>> = multi_signatures
+            .iter()
+            .map(|signature| signature.key.clone())
+            .collect();
+        let avks: Vec<StmAggrVerificationKey<D>> = aggregate_verification_keys
+            .iter()
+            .map(|avk| avk.key.clone())
+            .collect();
+        if signatures.is_empty()
+            || StmAggrSig::batch_verify(&signatures, messages, &avks, parameters).is_ok()
+        {
+            return Ok(());
+        }
+
+        for (index, signature) in signatures.iter().enumerate() {
+            signature
+                .verify(&messages[index], &avks[index], &parameters[index])
+                .map_err(|error| (index, error))?;
+        }
+
+        Ok(())
+    }
+}
+
 impl ProtocolGenesisSignature {
     /// Create an instance from a bytes hex representation
     pub fn from_bytes_hex(hex_string: &str) -> StdResult<Self> {
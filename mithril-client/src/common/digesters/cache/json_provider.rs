@@ -13,7 +13,7 @@ use std::{
     collections::BTreeMap,
     path::{Path, PathBuf},
 };
-#[cfg(feature = "fs")]
+#[cfg(feature = "digesters")]
 use tokio::{
     fs,
     fs::File,
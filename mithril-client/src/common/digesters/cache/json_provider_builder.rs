@@ -5,7 +5,7 @@ use crate::common::{
 use anyhow::Context;
 use slog::{info, Logger};
 use std::path::Path;
-#[cfg(feature = "fs")]
+#[cfg(feature = "digesters")]
 use tokio::fs;
 
 /// A [JsonImmutableFileDigestCacheProvider] builder.
@@ -1,14 +1,15 @@
-use crate::common::{
-    digesters::{
-        cache::ImmutableFileDigestCacheProvider, ImmutableDigester, ImmutableDigesterError,
-        ImmutableFile,
+use crate::{
+    common::digesters::{
+        cache::ImmutableFileDigestCacheProvider, DigestProgress, DigestProgressListener,
+        ImmutableDigester, ImmutableDigesterError, ImmutableFile,
     },
-    entities::{Beacon, HexEncodedDigest, ImmutableFileName},
+    common::entities::{Beacon, HexEncodedDigest, ImmutableFileName},
+    compute::ComputeExecutor,
 };
 use async_trait::async_trait;
 use sha2::{Digest, Sha256};
 use slog::{debug, info, warn, Logger};
-use std::{collections::BTreeMap, io, path::Path, sync::Arc};
+use std::{collections::BTreeMap, io, path::Path, sync::Arc, time::Instant};
 
 /// Result of a cache computation, contains the digest and the list of new entries to add
 /// to the [ImmutableFileDigestCacheProvider].
@@ -20,6 +21,9 @@ pub struct CardanoImmutableDigester {
     /// A [ImmutableFileDigestCacheProvider] instance
     cache_provider: Option<Arc<dyn ImmutableFileDigestCacheProvider>>,
 
+    /// The [ComputeExecutor] on which the digests are computed
+    compute_executor: ComputeExecutor,
+
     /// The logger where the logs should be written
     logger: Logger,
 }
@@ -32,9 +36,17 @@ impl CardanoImmutableDigester {
     ) -> Self {
         Self {
             cache_provider,
+            compute_executor: ComputeExecutor::default(),
             logger,
         }
     }
+
+    /// Set the [ComputeExecutor] on which the digests are computed, by default they are
+    /// computed on the blocking threads of the [tokio] runtime.
+    pub fn with_compute_executor(mut self, compute_executor: ComputeExecutor) -> Self {
+        self.compute_executor = compute_executor;
+        self
+    }
 }
 
 #[async_trait]
@@ -43,6 +55,31 @@ impl ImmutableDigester for CardanoImmutableDigester {
         &self,
         dirpath: &Path,
         beacon: &Beacon,
+    ) -> Result<String, ImmutableDigesterError> {
+        self.compute_digest_notifying(dirpath, beacon, None).await
+    }
+
+    async fn compute_digest_with_progress(
+        &self,
+        dirpath: &Path,
+        beacon: &Beacon,
+        on_progress: DigestProgressListener,
+    ) -> Result<String, ImmutableDigesterError> {
+        self.compute_digest_notifying(dirpath, beacon, Some(on_progress))
+            .await
+    }
+}
+
+impl CardanoImmutableDigester {
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(immutable_file_number = beacon.immutable_file_number))
+    )]
+    async fn compute_digest_notifying(
+        &self,
+        dirpath: &Path,
+        beacon: &Beacon,
+        on_progress: Option<DigestProgressListener>,
     ) -> Result<String, ImmutableDigesterError> {
         let up_to_file_number = beacon.immutable_file_number;
         let immutables = ImmutableFile::list_completed_in_dir(dirpath)?
@@ -83,12 +120,15 @@ impl ImmutableDigester for CardanoImmutableDigester {
                 // digest is done in a separate thread because it is blocking the whole task
                 let logger = self.logger.clone();
                 let thread_beacon = beacon.clone();
-                let (hash, new_cache_entries) =
-                    tokio::task::spawn_blocking(move || -> CacheComputationResult {
-                        compute_hash(logger, &thread_beacon, cached_values)
+                let (hash, new_cache_entries) = self
+                    .compute_executor
+                    .run_blocking(move || -> CacheComputationResult {
+                        compute_hash(logger, &thread_beacon, cached_values, on_progress)
                     })
                     .await
-                    .map_err(|e| ImmutableDigesterError::DigestComputationError(e.into()))??;
+                    .map_err(|e| {
+                        ImmutableDigesterError::DigestComputationError(io::Error::other(e))
+                    })??;
                 let digest = hex::encode(hash);
 
                 debug!(self.logger, "#computed digest: {:?}", digest);
@@ -112,6 +152,7 @@ fn compute_hash(
     logger: Logger,
     beacon: &Beacon,
     entries: BTreeMap<ImmutableFile, Option<HexEncodedDigest>>,
+    on_progress: Option<DigestProgressListener>,
 ) -> CacheComputationResult {
     let mut hasher = Sha256::new();
     let mut new_cached_entries = Vec::new();
@@ -119,6 +160,8 @@ fn compute_hash(
         index: 0,
         total: entries.len(),
     };
+    let start = Instant::now();
+    let mut bytes_hashed = 0;
 
     hasher.update(beacon.compute_hash().as_bytes());
 
@@ -128,6 +171,9 @@ fn compute_hash(
                 let data = hex::encode(entry.compute_raw_hash::<Sha256>()?);
                 hasher.update(&data);
                 new_cached_entries.push((entry.filename.clone(), data));
+                if on_progress.is_some() {
+                    bytes_hashed += std::fs::metadata(&entry.path)?.len();
+                }
             }
             Some(digest) => {
                 hasher.update(digest);
@@ -137,6 +183,14 @@ fn compute_hash(
         if progress.report(ix) {
             info!(logger, "hashing: {}", &progress);
         }
+        if let Some(on_progress) = &on_progress {
+            on_progress(DigestProgress {
+                files_hashed: ix + 1,
+                total_files: progress.total,
+                bytes_hashed,
+                elapsed: start.elapsed(),
+            });
+        }
     }
 
     Ok((hasher.finalize().into(), new_cached_entries))
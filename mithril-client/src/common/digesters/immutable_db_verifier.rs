@@ -0,0 +1,525 @@
+use crate::common::{
+    digesters::ImmutableFile,
+    entities::{Beacon, HexEncodedDigest, ImmutableFileName, ImmutableFileNumber},
+    StdResult,
+};
+use anyhow::Context;
+use sha2::{Digest, Sha256};
+use slog::{warn, Logger};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ops::RangeInclusive,
+    path::Path,
+};
+use walkdir::WalkDir;
+
+/// Extensions of the trio of files that make an immutable in a Cardano node database.
+const IMMUTABLE_FILE_EXTENSIONS: [&str; 3] = ["chunk", "primary", "secondary"];
+
+/// Status of a file in an [ImmutableDbIntegrityReport].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImmutableFileStatus {
+    /// The file is present and, if a reference digest is known, matches it.
+    Valid,
+
+    /// The file digest differs from its reference digest.
+    Corrupt {
+        /// Reference digest of the file
+        expected_digest: HexEncodedDigest,
+    },
+
+    /// The file should be in the database but wasn't found.
+    Missing,
+
+    /// The file exists but could not be read.
+    Unreadable {
+        /// Reason of the read failure
+        error: String,
+    },
+
+    /// The file is beyond the verified beacon, it's not part of the digest.
+    Extra,
+}
+
+/// Integrity of a single immutable file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImmutableFileReport {
+    /// The immutable file number
+    pub number: ImmutableFileNumber,
+
+    /// The filename
+    pub filename: ImmutableFileName,
+
+    /// Digest computed from the file content, if it could be read
+    pub computed_digest: Option<HexEncodedDigest>,
+
+    /// Status of the file
+    pub status: ImmutableFileStatus,
+}
+
+/// Per file result of [verify_immutable_db].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImmutableDbIntegrityReport {
+    /// Digest that the database was expected to have
+    pub expected_digest: String,
+
+    /// Digest computed from the database, `None` if a file was missing or unreadable
+    pub computed_digest: Option<String>,
+
+    /// Report of every expected or found immutable file, ordered by number
+    pub files: Vec<ImmutableFileReport>,
+}
+
+impl ImmutableDbIntegrityReport {
+    /// Returns `true` if the computed digest matches the expected digest.
+    pub fn is_valid(&self) -> bool {
+        self.computed_digest.as_ref() == Some(&self.expected_digest)
+    }
+
+    /// Reports of the files that are not [valid][ImmutableFileStatus::Valid] nor
+    /// [extra][ImmutableFileStatus::Extra].
+    pub fn invalid_files(&self) -> Vec<&ImmutableFileReport> {
+        self.files
+            .iter()
+            .filter(|file| {
+                !matches!(
+                    file.status,
+                    ImmutableFileStatus::Valid | ImmutableFileStatus::Extra
+                )
+            })
+            .collect()
+    }
+
+    /// Numbers of the immutables that need to be fetched again to repair the database.
+    ///
+    /// Note: corrupt files can only be pinpointed if reference digests were given, see
+    /// [verify_immutable_db_with_reference]. If the database is invalid but this list is empty,
+    /// the corrupt files are unknown.
+    pub fn immutables_to_refetch(&self) -> Vec<ImmutableFileNumber> {
+        self.invalid_files()
+            .into_iter()
+            .map(|file| file.number)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+}
+
+/// Re-hash the immutable files of an already present Cardano database and produce a per file
+/// report of missing, unreadable and extra immutable files.
+///
+/// Since only the digest of the whole database is certified, corrupt files can't be pinpointed
+/// by this function, use [verify_immutable_db_with_reference] for that.
+///
+/// Files with an immutable extension but an invalid name are skipped and logged.
+pub async fn verify_immutable_db(
+    db_dir: &Path,
+    expected_digest: &str,
+    beacon: &Beacon,
+    logger: &Logger,
+) -> StdResult<ImmutableDbIntegrityReport> {
+    verify_immutable_db_with_reference(db_dir, expected_digest, beacon, &BTreeMap::new(), logger)
+        .await
+}
+
+/// Same as [verify_immutable_db] but also compare each file to the given reference digests,
+/// ie: obtained from a trusted copy of the database or a
+/// [digest cache][crate::common::digesters::cache], to report the corrupt files.
+pub async fn verify_immutable_db_with_reference(
+    db_dir: &Path,
+    expected_digest: &str,
+    beacon: &Beacon,
+    reference_digests: &BTreeMap<ImmutableFileName, HexEncodedDigest>,
+    logger: &Logger,
+) -> StdResult<ImmutableDbIntegrityReport> {
+    let db_dir = db_dir.to_path_buf();
+    let expected_digest = expected_digest.to_string();
+    let beacon = beacon.clone();
+    let reference_digests = reference_digests.clone();
+    let logger = logger.clone();
+
+    // hashing is done in a separate thread because it is blocking the whole task
+    tokio::task::spawn_blocking(move || {
+        compute_report(
+            &db_dir,
+            expected_digest,
+            &beacon,
+            &reference_digests,
+            &logger,
+        )
+    })
+    .await
+    .with_context(|| "Immutable database verification task failed")?
+}
+
+fn compute_report(
+    db_dir: &Path,
+    expected_digest: String,
+    beacon: &Beacon,
+    reference_digests: &BTreeMap<ImmutableFileName, HexEncodedDigest>,
+    logger: &Logger,
+) -> StdResult<ImmutableDbIntegrityReport> {
+    let mut found_files = list_immutable_files(db_dir, logger)?;
+    let mut files = vec![];
+    let mut hasher = Some(Sha256::new());
+    if let Some(hasher) = hasher.as_mut() {
+        hasher.update(beacon.compute_hash().as_bytes());
+    }
+
+    for number in 0..=beacon.immutable_file_number {
+        for extension in IMMUTABLE_FILE_EXTENSIONS {
+            let report = file_report(
+                number,
+                extension,
+                found_files.remove(&(number, extension.to_string())),
+                reference_digests,
+            );
+            match (&report.computed_digest, hasher.as_mut()) {
+                (Some(digest), Some(hasher)) => hasher.update(digest),
+                (Some(_), None) => {}
+                (None, _) => hasher = None,
+            }
+            files.push(report);
+        }
+    }
+
+    files.extend(found_files.into_values().map(extra_file_report));
+
+    Ok(ImmutableDbIntegrityReport {
+        expected_digest,
+        computed_digest: hasher.map(|hasher| hex::encode(hasher.finalize())),
+        files,
+    })
+}
+
+/// Re-hash the immutable files of the given range, ie: unpacked with the `immutable_files` range
+/// of the unpack options, and produce a per file report of missing, unreadable and extra
+/// immutable files.
+///
+/// Since only the digest of the whole database is certified, each file can only be verified
+/// individually against the given reference digests, ie: obtained from a trusted copy of the
+/// database or a [digest cache][crate::common::digesters::cache]. Files without reference digest
+/// are reported as [valid][ImmutableFileStatus::Valid] if they can be read.
+///
+/// Files with an immutable extension but an invalid name are skipped and logged.
+pub async fn verify_immutable_range(
+    db_dir: &Path,
+    immutable_files: RangeInclusive<ImmutableFileNumber>,
+    reference_digests: &BTreeMap<ImmutableFileName, HexEncodedDigest>,
+    logger: &Logger,
+) -> StdResult<Vec<ImmutableFileReport>> {
+    let db_dir = db_dir.to_path_buf();
+    let reference_digests = reference_digests.clone();
+    let logger = logger.clone();
+
+    // hashing is done in a separate thread because it is blocking the whole task
+    tokio::task::spawn_blocking(move || {
+        let mut found_files = list_immutable_files(&db_dir, &logger)?;
+        let mut files = vec![];
+
+        for number in immutable_files {
+            for extension in IMMUTABLE_FILE_EXTENSIONS {
+                files.push(file_report(
+                    number,
+                    extension,
+                    found_files.remove(&(number, extension.to_string())),
+                    &reference_digests,
+                ));
+            }
+        }
+        files.extend(found_files.into_values().map(extra_file_report));
+
+        Ok(files)
+    })
+    .await
+    .with_context(|| "Immutable range verification task failed")?
+}
+
+fn file_report(
+    number: ImmutableFileNumber,
+    extension: &str,
+    file: Option<ImmutableFile>,
+    reference_digests: &BTreeMap<ImmutableFileName, HexEncodedDigest>,
+) -> ImmutableFileReport {
+    let Some(file) = file else {
+        return ImmutableFileReport {
+            number,
+            filename: format!("{number:05}.{extension}"),
+            computed_digest: None,
+            status: ImmutableFileStatus::Missing,
+        };
+    };
+
+    match file.compute_raw_hash::<Sha256>() {
+        Ok(hash) => {
+            let digest = hex::encode(hash);
+            let status = match reference_digests.get(&file.filename) {
+                Some(reference) if reference != &digest => ImmutableFileStatus::Corrupt {
+                    expected_digest: reference.clone(),
+                },
+                _ => ImmutableFileStatus::Valid,
+            };
+            ImmutableFileReport {
+                number,
+                filename: file.filename,
+                computed_digest: Some(digest),
+                status,
+            }
+        }
+        Err(error) => ImmutableFileReport {
+            number,
+            filename: file.filename,
+            computed_digest: None,
+            status: ImmutableFileStatus::Unreadable {
+                error: error.to_string(),
+            },
+        },
+    }
+}
+
+fn extra_file_report(file: ImmutableFile) -> ImmutableFileReport {
+    ImmutableFileReport {
+        number: file.number,
+        filename: file.filename,
+        computed_digest: None,
+        status: ImmutableFileStatus::Extra,
+    }
+}
+
+/// List every immutable file in the given directory, indexed by number and extension.
+///
+/// The files with an immutable extension which name is not a valid immutable file name are
+/// skipped and logged.
+fn list_immutable_files(
+    db_dir: &Path,
+    logger: &Logger,
+) -> StdResult<BTreeMap<(ImmutableFileNumber, String), ImmutableFile>> {
+    let mut files = BTreeMap::new();
+
+    for path in WalkDir::new(db_dir)
+        .into_iter()
+        .filter_map(|file| file.ok())
+        .map(|f| f.path().to_owned())
+    {
+        let is_immutable = path.iter().any(|component| component == "immutable");
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .filter(|extension| IMMUTABLE_FILE_EXTENSIONS.contains(extension))
+            .map(|extension| extension.to_string());
+
+        if let (true, Some(extension)) = (is_immutable && path.is_file(), extension) {
+            match ImmutableFile::new(path.clone()) {
+                Ok(file) => {
+                    files.insert((file.number, extension), file);
+                }
+                Err(error) => {
+                    warn!(
+                        logger, "Skipping file with an invalid immutable file name";
+                        "path" => %path.display(), "error" => ?error
+                    );
+                }
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::digesters::{
+        CardanoImmutableDigester, DummyImmutablesDbBuilder, ImmutableDigester,
+    };
+    use crate::test_utils;
+    use std::fs;
+
+    use super::*;
+
+    async fn expected_digest(db_dir: &Path, beacon: &Beacon) -> String {
+        CardanoImmutableDigester::new(None, test_utils::test_logger())
+            .compute_digest(db_dir, beacon)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn valid_database_has_the_expected_digest_and_extra_files_are_reported() {
+        let immutable_db = DummyImmutablesDbBuilder::new("verify_immutable_db_valid")
+            .with_immutables(&[0, 1, 2])
+            .append_immutable_trio()
+            .build();
+        let beacon = Beacon::new("devnet".to_string(), 1, 2);
+        let digest = expected_digest(&immutable_db.dir, &beacon).await;
+
+        let report = verify_immutable_db(
+            &immutable_db.dir,
+            &digest,
+            &beacon,
+            &test_utils::test_logger(),
+        )
+        .await
+        .unwrap();
+
+        assert!(report.is_valid(), "Unexpected report: {report:?}");
+        assert!(report.invalid_files().is_empty());
+        assert_eq!(
+            vec![3, 3, 3],
+            report
+                .files
+                .iter()
+                .filter(|file| file.status == ImmutableFileStatus::Extra)
+                .map(|file| file.number)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[tokio::test]
+    async fn missing_files_are_reported() {
+        let immutable_db = DummyImmutablesDbBuilder::new("verify_immutable_db_missing")
+            .with_immutables(&[0, 1, 2])
+            .build();
+        fs::remove_file(immutable_db.dir.join("00001.primary")).unwrap();
+        let beacon = Beacon::new("devnet".to_string(), 1, 2);
+
+        let report = verify_immutable_db(
+            &immutable_db.dir,
+            "digest",
+            &beacon,
+            &test_utils::test_logger(),
+        )
+        .await
+        .unwrap();
+
+        assert!(!report.is_valid());
+        assert_eq!(None, report.computed_digest);
+        assert_eq!(
+            vec![&ImmutableFileReport {
+                number: 1,
+                filename: "00001.primary".to_string(),
+                computed_digest: None,
+                status: ImmutableFileStatus::Missing,
+            }],
+            report.invalid_files()
+        );
+        assert_eq!(vec![1], report.immutables_to_refetch());
+    }
+
+    #[tokio::test]
+    async fn corrupt_files_are_reported_when_comparing_to_reference_digests() {
+        let immutable_db = DummyImmutablesDbBuilder::new("verify_immutable_db_corrupt")
+            .with_immutables(&[0, 1, 2])
+            .append_immutable_trio()
+            .build();
+        let beacon = Beacon::new("devnet".to_string(), 1, 2);
+        let digest = expected_digest(&immutable_db.dir, &beacon).await;
+        let reference_digests: BTreeMap<ImmutableFileName, HexEncodedDigest> = verify_immutable_db(
+            &immutable_db.dir,
+            &digest,
+            &beacon,
+            &test_utils::test_logger(),
+        )
+        .await
+        .unwrap()
+        .files
+        .into_iter()
+        .filter_map(|file| file.computed_digest.map(|digest| (file.filename, digest)))
+        .collect();
+        fs::write(immutable_db.dir.join("00002.chunk"), "corrupted").unwrap();
+
+        let report = verify_immutable_db_with_reference(
+            &immutable_db.dir,
+            &digest,
+            &beacon,
+            &reference_digests,
+            &test_utils::test_logger(),
+        )
+        .await
+        .unwrap();
+
+        assert!(!report.is_valid());
+        assert_eq!(
+            vec![ImmutableFileStatus::Corrupt {
+                expected_digest: reference_digests["00002.chunk"].clone()
+            }],
+            report
+                .invalid_files()
+                .into_iter()
+                .map(|file| file.status.clone())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(vec![2], report.immutables_to_refetch());
+    }
+
+    #[test]
+    fn list_immutable_files_skip_the_files_with_an_invalid_name() {
+        let immutable_db = DummyImmutablesDbBuilder::new("list_immutable_files_invalid_name")
+            .with_immutables(&[1])
+            .build();
+        fs::write(immutable_db.dir.join("not_a_number.chunk"), "chunk").unwrap();
+
+        let files = list_immutable_files(&immutable_db.dir, &test_utils::test_logger()).unwrap();
+
+        assert_eq!(
+            vec![
+                (1, "chunk".to_string()),
+                (1, "primary".to_string()),
+                (1, "secondary".to_string())
+            ],
+            files.into_keys().collect::<Vec<_>>()
+        );
+    }
+
+    #[tokio::test]
+    async fn verify_immutable_range_check_each_file_of_the_range() {
+        let immutable_db = DummyImmutablesDbBuilder::new("verify_immutable_range")
+            .with_immutables(&[1, 2, 3])
+            .build();
+        let reference_digests = BTreeMap::from([
+            ("00001.chunk".to_string(), "wrong_digest".to_string()),
+            (
+                "00002.chunk".to_string(),
+                hex::encode(
+                    ImmutableFile::new(immutable_db.dir.join("00002.chunk"))
+                        .unwrap()
+                        .compute_raw_hash::<Sha256>()
+                        .unwrap(),
+                ),
+            ),
+        ]);
+        fs::remove_file(immutable_db.dir.join("00002.primary")).unwrap();
+
+        let files = verify_immutable_range(
+            &immutable_db.dir,
+            1..=2,
+            &reference_digests,
+            &test_utils::test_logger(),
+        )
+        .await
+        .unwrap();
+
+        let statuses: Vec<(String, ImmutableFileStatus)> = files
+            .into_iter()
+            .map(|file| (file.filename, file.status))
+            .collect();
+        assert_eq!(
+            vec![
+                (
+                    "00001.chunk".to_string(),
+                    ImmutableFileStatus::Corrupt {
+                        expected_digest: "wrong_digest".to_string()
+                    }
+                ),
+                ("00001.primary".to_string(), ImmutableFileStatus::Valid),
+                ("00001.secondary".to_string(), ImmutableFileStatus::Valid),
+                ("00002.chunk".to_string(), ImmutableFileStatus::Valid),
+                ("00002.primary".to_string(), ImmutableFileStatus::Missing),
+                ("00002.secondary".to_string(), ImmutableFileStatus::Valid),
+                ("00003.chunk".to_string(), ImmutableFileStatus::Extra),
+                ("00003.primary".to_string(), ImmutableFileStatus::Extra),
+                ("00003.secondary".to_string(), ImmutableFileStatus::Extra),
+            ],
+            statuses
+        );
+    }
+}
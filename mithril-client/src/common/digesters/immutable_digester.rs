@@ -6,9 +6,43 @@ use async_trait::async_trait;
 use std::{
     io,
     path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
 };
 use thiserror::Error;
 
+/// Progress of a digest computation, see [ImmutableDigester::compute_digest_with_progress].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DigestProgress {
+    /// Number of immutable files hashed so far, including the ones which digest was cached
+    pub files_hashed: usize,
+
+    /// Number of immutable files to hash
+    pub total_files: usize,
+
+    /// Number of bytes read and hashed so far, the files which digest was cached are not read
+    pub bytes_hashed: u64,
+
+    /// Time elapsed since the start of the computation
+    pub elapsed: Duration,
+}
+
+impl DigestProgress {
+    /// Estimated time before the end of the computation, extrapolated from the files hashed so
+    /// far, `None` until a first file is hashed.
+    pub fn estimated_remaining(&self) -> Option<Duration> {
+        if self.files_hashed == 0 {
+            return None;
+        }
+        let remaining_files = self.total_files.saturating_sub(self.files_hashed) as u32;
+
+        Some(self.elapsed / self.files_hashed as u32 * remaining_files)
+    }
+}
+
+/// Callback notified of the [progress][DigestProgress] of a digest computation.
+pub type DigestProgressListener = Arc<dyn Fn(DigestProgress) + Send + Sync>;
+
 /// A digester than can compute the digest used for mithril signatures
 ///
 /// If you want to mock it using mockall:
@@ -54,6 +88,20 @@ pub trait ImmutableDigester: Sync + Send {
         dirpath: &Path,
         beacon: &Beacon,
     ) -> Result<String, ImmutableDigesterError>;
+
+    /// Compute the digest like [compute_digest][ImmutableDigester::compute_digest], notifying
+    /// `on_progress` each time an immutable file is hashed.
+    ///
+    /// The default implementation doesn't notify any progress.
+    async fn compute_digest_with_progress(
+        &self,
+        dirpath: &Path,
+        beacon: &Beacon,
+        on_progress: DigestProgressListener,
+    ) -> Result<String, ImmutableDigesterError> {
+        let _ = on_progress;
+        self.compute_digest(dirpath, beacon).await
+    }
 }
 
 /// [ImmutableDigester] related Errors.
@@ -3,10 +3,12 @@ use crate::common::entities::{ImmutableFileName, ImmutableFileNumber};
 use digest::{Digest, Output};
 use std::{
     cmp::Ordering,
+    collections::{BTreeMap, HashSet},
     ffi::OsStr,
     fs::File,
     io,
     num::ParseIntError,
+    ops::RangeInclusive,
     path::{Path, PathBuf},
 };
 use thiserror::Error;
@@ -17,6 +19,42 @@ fn is_immutable(path: &Path) -> bool {
     path.iter().any(|component| component == immutable)
 }
 
+/// Options of the listing of the immutable files of a directory, see
+/// [ImmutableFile::list_completed_in_dir_with_options].
+///
+/// By default the whole tree of the directory is listed without following the symbolic links to
+/// directories.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImmutableListingOptions {
+    /// Follow the symbolic links to directories, ie: when the immutable files are sharded over
+    /// several mounts.
+    ///
+    /// A symbolic link to one of its own ancestors is not followed, and a file reachable through
+    /// several links is only listed once.
+    pub follow_symlinks: bool,
+
+    /// Maximum depth of the listed entries, the listed directory being at depth `0` and its
+    /// entries at depth `1`, `None` to list the whole tree.
+    pub max_depth: Option<usize>,
+}
+
+impl ImmutableListingOptions {
+    /// Paths of the entries of the given directory, the entries that can't be read (ie: a
+    /// symbolic link to one of its ancestors) being skipped.
+    fn walk(&self, dir: &Path) -> impl Iterator<Item = PathBuf> {
+        let walker = WalkDir::new(dir).follow_links(self.follow_symlinks);
+        let walker = match self.max_depth {
+            Some(max_depth) => walker.max_depth(max_depth),
+            None => walker,
+        };
+
+        walker
+            .into_iter()
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path().to_owned())
+    }
+}
+
 /// Represent an immutable file in a Cardano node database directory
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct ImmutableFile {
@@ -30,6 +68,205 @@ pub struct ImmutableFile {
     pub filename: ImmutableFileName,
 }
 
+/// Size of the version header of a primary index file.
+const PRIMARY_INDEX_HEADER_SIZE: u64 = 1;
+
+/// Size of a slot offset in a primary index file.
+const PRIMARY_INDEX_OFFSET_SIZE: u64 = 4;
+
+/// Size of an entry of a secondary index file: block offset (8), header offset (2), header size
+/// (2), checksum (4), header hash (32) and block or EBB (8).
+const SECONDARY_INDEX_ENTRY_SIZE: u64 = 56;
+
+/// Kind of a file of an immutable trio.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ImmutableFileKind {
+    /// Blocks of the immutable (`.chunk`)
+    Chunk,
+
+    /// Index of the slots of the immutable (`.primary`)
+    Primary,
+
+    /// Index of the blocks of the immutable (`.secondary`)
+    Secondary,
+}
+
+impl ImmutableFileKind {
+    fn from_extension(extension: &OsStr) -> Option<Self> {
+        match extension.to_str()? {
+            "chunk" => Some(Self::Chunk),
+            "primary" => Some(Self::Primary),
+            "secondary" => Some(Self::Secondary),
+            _ => None,
+        }
+    }
+}
+
+/// Heuristic sign that an immutable trio is incomplete or corrupt, see
+/// [ImmutableFile::inspect_dir].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImmutableTrioAnomaly {
+    /// A file of the trio is missing.
+    Missing(ImmutableFileKind),
+
+    /// The primary index is empty, while it always holds at least its version header.
+    EmptyPrimaryIndex,
+
+    /// The size of an index file doesn't match the size of its entries.
+    MalformedIndexSize {
+        /// Kind of the index file
+        kind: ImmutableFileKind,
+
+        /// Size of the file, in bytes
+        size: u64,
+    },
+
+    /// The chunk is empty while the secondary index lists blocks, or the other way around.
+    ChunkIndexMismatch {
+        /// Size of the chunk, in bytes
+        chunk_size: u64,
+
+        /// Size of the secondary index, in bytes
+        secondary_size: u64,
+    },
+}
+
+/// A file of an [ImmutableTrio] and its size.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImmutableTrioFile {
+    /// The immutable file
+    pub file: ImmutableFile,
+
+    /// Size of the file, in bytes
+    pub size: u64,
+}
+
+/// The chunk, primary index and secondary index files sharing an immutable file number.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImmutableTrio {
+    /// The immutable file number
+    pub number: ImmutableFileNumber,
+
+    /// The chunk file, if present
+    pub chunk: Option<ImmutableTrioFile>,
+
+    /// The primary index file, if present
+    pub primary: Option<ImmutableTrioFile>,
+
+    /// The secondary index file, if present
+    pub secondary: Option<ImmutableTrioFile>,
+
+    /// Anomalies found in the trio
+    pub anomalies: Vec<ImmutableTrioAnomaly>,
+}
+
+impl ImmutableTrio {
+    fn new(
+        number: ImmutableFileNumber,
+        mut files: BTreeMap<ImmutableFileKind, ImmutableTrioFile>,
+    ) -> Self {
+        let chunk = files.remove(&ImmutableFileKind::Chunk);
+        let primary = files.remove(&ImmutableFileKind::Primary);
+        let secondary = files.remove(&ImmutableFileKind::Secondary);
+        let mut anomalies = vec![];
+
+        for (kind, file) in [
+            (ImmutableFileKind::Chunk, &chunk),
+            (ImmutableFileKind::Primary, &primary),
+            (ImmutableFileKind::Secondary, &secondary),
+        ] {
+            if file.is_none() {
+                anomalies.push(ImmutableTrioAnomaly::Missing(kind));
+            }
+        }
+        if let Some(primary) = &primary {
+            if primary.size == 0 {
+                anomalies.push(ImmutableTrioAnomaly::EmptyPrimaryIndex);
+            } else if primary.size < PRIMARY_INDEX_HEADER_SIZE + PRIMARY_INDEX_OFFSET_SIZE
+                || !(primary.size - PRIMARY_INDEX_HEADER_SIZE)
+                    .is_multiple_of(PRIMARY_INDEX_OFFSET_SIZE)
+            {
+                anomalies.push(ImmutableTrioAnomaly::MalformedIndexSize {
+                    kind: ImmutableFileKind::Primary,
+                    size: primary.size,
+                });
+            }
+        }
+        if let Some(secondary) = &secondary {
+            if !secondary.size.is_multiple_of(SECONDARY_INDEX_ENTRY_SIZE) {
+                anomalies.push(ImmutableTrioAnomaly::MalformedIndexSize {
+                    kind: ImmutableFileKind::Secondary,
+                    size: secondary.size,
+                });
+            }
+        }
+        // An immutable can cover slots without any block, then both its chunk and its
+        // secondary index are empty
+        if let (Some(chunk), Some(secondary)) = (&chunk, &secondary) {
+            if (chunk.size == 0) != (secondary.size == 0) {
+                anomalies.push(ImmutableTrioAnomaly::ChunkIndexMismatch {
+                    chunk_size: chunk.size,
+                    secondary_size: secondary.size,
+                });
+            }
+        }
+
+        Self {
+            number,
+            chunk,
+            primary,
+            secondary,
+            anomalies,
+        }
+    }
+
+    /// Returns `true` if no anomaly was found in the trio.
+    pub fn is_sound(&self) -> bool {
+        self.anomalies.is_empty()
+    }
+}
+
+/// Result of [ImmutableFile::inspect_dir].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImmutableDirInspection {
+    /// Every trio found, ordered by number, including the last one that is still written by the
+    /// Cardano node
+    pub trios: Vec<ImmutableTrio>,
+
+    /// Ranges of immutable file numbers, from 0 to the last found, without any file
+    pub gaps: Vec<RangeInclusive<ImmutableFileNumber>>,
+
+    /// Files of the immutable directories that are not part of a trio
+    pub unrecognized_files: Vec<PathBuf>,
+}
+
+impl ImmutableDirInspection {
+    /// The trios that are completed, ie: all but the last one.
+    pub fn completed_trios(&self) -> &[ImmutableTrio] {
+        &self.trios[..self.trios.len().saturating_sub(1)]
+    }
+
+    /// Number of the last completed trio.
+    pub fn last_completed_number(&self) -> Option<ImmutableFileNumber> {
+        self.completed_trios().last().map(|trio| trio.number)
+    }
+
+    /// The completed trios with at least one anomaly.
+    pub fn suspicious_trios(&self) -> Vec<&ImmutableTrio> {
+        self.completed_trios()
+            .iter()
+            .filter(|trio| !trio.is_sound())
+            .collect()
+    }
+
+    /// Returns `true` if the numbering has no gap and no completed trio has an anomaly.
+    ///
+    /// Anomalies of the last trio are not taken into account since its files are still written.
+    pub fn is_healthy(&self) -> bool {
+        self.gaps.is_empty() && self.suspicious_trios().is_empty()
+    }
+}
+
 /// [ImmutableFile::new] related errors.
 #[derive(Error, Debug)]
 pub enum ImmutableFileCreationError {
@@ -52,7 +289,7 @@ pub enum ImmutableFileCreationError {
     FileNumberParsing(#[from] ParseIntError),
 }
 
-/// [ImmutableFile::list_completed_in_dir] related errors.
+/// [ImmutableFile::list_completed_in_dir] and [ImmutableFile::inspect_dir] related errors.
 #[derive(Error, Debug)]
 pub enum ImmutableFileListingError {
     /// Raised when the metadata of a file could not be read.
@@ -115,16 +352,28 @@ impl ImmutableFile {
     /// complete.
     pub fn list_completed_in_dir(
         dir: &Path,
+    ) -> Result<Vec<ImmutableFile>, ImmutableFileListingError> {
+        Self::list_completed_in_dir_with_options(dir, &ImmutableListingOptions::default())
+    }
+
+    /// List all [`ImmutableFile`] in a given directory, following the symbolic links and up to
+    /// the depth set in the given [options][ImmutableListingOptions].
+    ///
+    /// Like [ImmutableFile::list_completed_in_dir] the last trio is skipped, whatever the
+    /// subdirectory its files are in.
+    pub fn list_completed_in_dir_with_options(
+        dir: &Path,
+        options: &ImmutableListingOptions,
     ) -> Result<Vec<ImmutableFile>, ImmutableFileListingError> {
         let mut files: Vec<ImmutableFile> = vec![];
+        let mut listed_paths = HashSet::new();
 
-        for path in WalkDir::new(dir)
-            .into_iter()
-            .filter_map(|file| file.ok())
-            .map(|f| f.path().to_owned())
-        {
+        for path in options.walk(dir) {
             let metadata = path.metadata()?;
             if metadata.is_file() && is_immutable(&path) {
+                if options.follow_symlinks && !listed_paths.insert(path.canonicalize()?) {
+                    continue;
+                }
                 let immutable_file = ImmutableFile::new(path)?;
                 files.push(immutable_file);
             }
@@ -134,6 +383,64 @@ impl ImmutableFile {
         // @todo: make the skip of the last 'trio' more robust
         Ok(files.into_iter().rev().skip(3).rev().collect())
     }
+
+    /// Inspect the immutable files in a given directory, without reading their content.
+    ///
+    /// Unlike [ImmutableFile::list_completed_in_dir] every trio is reported, with the anomalies
+    /// detected by heuristics on the presence and size of its files, alongside the gaps in the
+    /// numbering and the files that aren't part of a trio. Bootstrap tooling can use it to decide,
+    /// cheaply, if a snapshot needs to be downloaded again.
+    pub fn inspect_dir(dir: &Path) -> Result<ImmutableDirInspection, ImmutableFileListingError> {
+        let mut trios: BTreeMap<
+            ImmutableFileNumber,
+            BTreeMap<ImmutableFileKind, ImmutableTrioFile>,
+        > = BTreeMap::new();
+        let mut unrecognized_files = vec![];
+
+        for path in WalkDir::new(dir)
+            .into_iter()
+            .filter_map(|file| file.ok())
+            .map(|f| f.path().to_owned())
+        {
+            let metadata = path.metadata()?;
+            if !metadata.is_file() || !is_immutable(&path) {
+                continue;
+            }
+
+            let kind = path.extension().and_then(ImmutableFileKind::from_extension);
+            match (kind, ImmutableFile::new(path.clone())) {
+                (Some(kind), Ok(file)) => {
+                    trios.entry(file.number).or_default().insert(
+                        kind,
+                        ImmutableTrioFile {
+                            file,
+                            size: metadata.len(),
+                        },
+                    );
+                }
+                _ => unrecognized_files.push(path),
+            }
+        }
+        unrecognized_files.sort();
+
+        let mut gaps = vec![];
+        let mut expected_number = 0;
+        for number in trios.keys() {
+            if *number > expected_number {
+                gaps.push(expected_number..=number - 1);
+            }
+            expected_number = number + 1;
+        }
+
+        Ok(ImmutableDirInspection {
+            trios: trios
+                .into_iter()
+                .map(|(number, files)| ImmutableTrio::new(number, files))
+                .collect(),
+            gaps,
+            unrecognized_files,
+        })
+    }
 }
 
 impl PartialOrd for ImmutableFile {
@@ -152,11 +459,9 @@ impl Ord for ImmutableFile {
 
 #[cfg(test)]
 mod tests {
-    use super::ImmutableFile;
+    use super::*;
     use std::fs;
-    use std::fs::File;
     use std::io::prelude::*;
-    use std::path::{Path, PathBuf};
 
     fn get_test_dir(subdir_name: &str) -> PathBuf {
         let parent_dir = std::env::temp_dir().join("mithril_test").join(subdir_name);
@@ -268,4 +573,222 @@ mod tests {
         let expected: Vec<&str> = entries.into_iter().rev().skip(3).rev().collect();
         assert_eq!(expected, immutables_names);
     }
+
+    fn create_trios(parent_dir: &Path, numbers: &[u64]) {
+        fs::create_dir_all(parent_dir).unwrap();
+        let filenames: Vec<String> = numbers
+            .iter()
+            .flat_map(|number| {
+                ["chunk", "primary", "secondary"]
+                    .map(|extension| format!("{number:05}.{extension}"))
+            })
+            .collect();
+        create_fake_files(
+            parent_dir,
+            &filenames.iter().map(String::as_str).collect::<Vec<_>>(),
+        );
+    }
+
+    fn listed_numbers(files: &[ImmutableFile]) -> Vec<u64> {
+        let mut numbers: Vec<u64> = files.iter().map(|file| file.number).collect();
+        numbers.dedup();
+        numbers
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn list_immutable_files_of_symlinked_shards_when_following_symlinks() {
+        let dir = get_test_dir("list_immutable_files_of_symlinked_shards_when_following_symlinks");
+        let db_dir = dir.join("db");
+        create_trios(&db_dir.join("immutable").join("shard-0"), &[1, 2]);
+        create_trios(&dir.join("mount").join("shard-1"), &[3, 4]);
+        std::os::unix::fs::symlink(
+            dir.join("mount").join("shard-1"),
+            db_dir.join("immutable").join("shard-1"),
+        )
+        .unwrap();
+
+        let not_followed = ImmutableFile::list_completed_in_dir(&db_dir).unwrap();
+        let followed = ImmutableFile::list_completed_in_dir_with_options(
+            &db_dir,
+            &ImmutableListingOptions {
+                follow_symlinks: true,
+                ..ImmutableListingOptions::default()
+            },
+        )
+        .unwrap();
+
+        assert_eq!(vec![1], listed_numbers(&not_followed));
+        assert_eq!(vec![1, 2, 3], listed_numbers(&followed));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn list_immutable_files_once_with_a_symlink_cycle() {
+        let dir = get_test_dir("list_immutable_files_once_with_a_symlink_cycle");
+        let immutable_dir = dir.join("shards").join("immutable");
+        create_trios(&immutable_dir, &[1, 2, 3]);
+        std::os::unix::fs::symlink(&immutable_dir, immutable_dir.join("loop")).unwrap();
+        std::os::unix::fs::symlink(&immutable_dir, dir.join("immutable")).unwrap();
+
+        let files = ImmutableFile::list_completed_in_dir_with_options(
+            &dir,
+            &ImmutableListingOptions {
+                follow_symlinks: true,
+                ..ImmutableListingOptions::default()
+            },
+        )
+        .unwrap();
+
+        assert_eq!(6, files.len());
+        assert_eq!(vec![1, 2], listed_numbers(&files));
+    }
+
+    #[test]
+    fn list_immutable_files_up_to_the_max_depth() {
+        let dir = get_test_dir("list_immutable_files_up_to_the_max_depth");
+        create_trios(&dir.join("immutable"), &[1, 2]);
+        create_trios(&dir.join("immutable").join("shard"), &[3, 4]);
+        let list_with_max_depth = |max_depth| {
+            ImmutableFile::list_completed_in_dir_with_options(
+                &dir,
+                &ImmutableListingOptions {
+                    max_depth: Some(max_depth),
+                    ..ImmutableListingOptions::default()
+                },
+            )
+            .unwrap()
+        };
+
+        assert_eq!(Vec::<u64>::new(), listed_numbers(&list_with_max_depth(1)));
+        assert_eq!(vec![1], listed_numbers(&list_with_max_depth(2)));
+        assert_eq!(vec![1, 2, 3], listed_numbers(&list_with_max_depth(3)));
+    }
+
+    fn create_sized_files(parent_dir: &Path, child_files: &[(&str, u64)]) {
+        for (filename, size) in child_files {
+            let file = File::create(parent_dir.join(filename)).unwrap();
+            file.set_len(*size).unwrap();
+        }
+    }
+
+    #[test]
+    fn inspect_sound_immutable_dir() {
+        let target_dir = get_test_dir("inspect_sound_immutable_dir/immutable");
+        create_sized_files(
+            &target_dir,
+            &[
+                ("00000.chunk", 1000),
+                ("00000.primary", 17),
+                ("00000.secondary", 112),
+                ("00001.chunk", 0),
+                ("00001.primary", 17),
+                ("00001.secondary", 0),
+                ("00002.chunk", 10),
+                ("00002.primary", 1),
+            ],
+        );
+
+        let inspection = ImmutableFile::inspect_dir(target_dir.parent().unwrap()).unwrap();
+
+        assert_eq!(
+            vec![0, 1, 2],
+            inspection
+                .trios
+                .iter()
+                .map(|t| t.number)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(Some(1), inspection.last_completed_number());
+        assert_eq!(
+            Some(1000),
+            inspection.trios[0].chunk.as_ref().map(|f| f.size)
+        );
+        assert!(inspection.gaps.is_empty());
+        assert!(inspection.unrecognized_files.is_empty());
+        assert!(
+            inspection.is_healthy(),
+            "anomalies of the last trio should be ignored: {inspection:?}"
+        );
+        assert!(!inspection.trios[2].is_sound());
+    }
+
+    #[test]
+    fn inspect_immutable_dir_with_anomalies() {
+        let target_dir = get_test_dir("inspect_immutable_dir_with_anomalies/immutable");
+        create_sized_files(
+            &target_dir,
+            &[
+                ("00001.chunk", 1000),
+                ("00001.primary", 0),
+                ("00001.secondary", 100),
+                ("00002.chunk", 0),
+                ("00002.primary", 16),
+                ("00002.secondary", 56),
+                ("00005.chunk", 1000),
+                ("00005.secondary", 56),
+                ("00006.chunk", 1000),
+                ("00006.primary", 17),
+                ("00006.secondary", 56),
+                ("00006.tmp", 12),
+                ("clean", 3),
+            ],
+        );
+
+        let inspection = ImmutableFile::inspect_dir(target_dir.parent().unwrap()).unwrap();
+
+        assert!(!inspection.is_healthy());
+        assert_eq!(vec![0..=0, 3..=4], inspection.gaps);
+        assert_eq!(
+            vec![target_dir.join("00006.tmp"), target_dir.join("clean")],
+            inspection.unrecognized_files
+        );
+        assert_eq!(
+            vec![
+                (
+                    1,
+                    vec![
+                        ImmutableTrioAnomaly::EmptyPrimaryIndex,
+                        ImmutableTrioAnomaly::MalformedIndexSize {
+                            kind: ImmutableFileKind::Secondary,
+                            size: 100
+                        },
+                    ]
+                ),
+                (
+                    2,
+                    vec![
+                        ImmutableTrioAnomaly::MalformedIndexSize {
+                            kind: ImmutableFileKind::Primary,
+                            size: 16
+                        },
+                        ImmutableTrioAnomaly::ChunkIndexMismatch {
+                            chunk_size: 0,
+                            secondary_size: 56
+                        },
+                    ]
+                ),
+                (
+                    5,
+                    vec![ImmutableTrioAnomaly::Missing(ImmutableFileKind::Primary)]
+                ),
+            ],
+            inspection
+                .suspicious_trios()
+                .into_iter()
+                .map(|t| (t.number, t.anomalies.clone()))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn inspect_empty_immutable_dir() {
+        let target_dir = get_test_dir("inspect_empty_immutable_dir/immutable");
+
+        let inspection = ImmutableFile::inspect_dir(target_dir.parent().unwrap()).unwrap();
+
+        assert!(inspection.trios.is_empty());
+        assert_eq!(None, inspection.last_completed_number());
+        assert!(inspection.is_healthy());
+    }
 }
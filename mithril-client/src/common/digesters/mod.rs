@@ -4,13 +4,24 @@ pub mod cache;
 mod cardano_immutable_digester;
 mod dumb_immutable_observer;
 mod dummy_immutable_db_builder;
+mod immutable_db_verifier;
 mod immutable_digester;
 mod immutable_file;
 mod immutable_file_observer;
 
 pub use cardano_immutable_digester::CardanoImmutableDigester;
-pub use immutable_digester::{ImmutableDigester, ImmutableDigesterError};
-pub use immutable_file::{ImmutableFile, ImmutableFileCreationError, ImmutableFileListingError};
+pub use immutable_db_verifier::{
+    verify_immutable_db, verify_immutable_db_with_reference, verify_immutable_range,
+    ImmutableDbIntegrityReport, ImmutableFileReport, ImmutableFileStatus,
+};
+pub use immutable_digester::{
+    DigestProgress, DigestProgressListener, ImmutableDigester, ImmutableDigesterError,
+};
+pub use immutable_file::{
+    ImmutableDirInspection, ImmutableFile, ImmutableFileCreationError, ImmutableFileKind,
+    ImmutableFileListingError, ImmutableListingOptions, ImmutableTrio, ImmutableTrioAnomaly,
+    ImmutableTrioFile,
+};
 pub use immutable_file_observer::{
     DumbImmutableFileObserver, ImmutableFileObserver, ImmutableFileObserverError,
     ImmutableFileSystemObserver,
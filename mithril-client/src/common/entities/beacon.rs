@@ -1,4 +1,5 @@
-use crate::common::entities::{Epoch, ImmutableFileNumber};
+use crate::common::entities::{Epoch, EpochError, EpochOffset, ImmutableFileNumber};
+use crate::common::era::SupportedEra;
 use crate::common::signable_builder::Beacon as Beaconable;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
@@ -113,6 +114,27 @@ impl Beacon {
         hex::encode(hasher.finalize())
     }
 
+    /// Number of epochs between the other beacon and this beacon, negative if the other beacon is
+    /// newer.
+    ///
+    /// Fails if the beacons are issued by different networks.
+    pub fn epoch_offset_from(&self, other: &Beacon) -> Result<i64, BeaconComparisonError> {
+        if self.network != other.network {
+            return Err(BeaconComparisonError::NetworkNotMatch(
+                self.network.clone(),
+                other.network.clone(),
+            ));
+        }
+
+        Ok(*self.epoch as i64 - *other.epoch as i64)
+    }
+
+    /// Epoch at which the signers that signed at this beacon registered, according to the
+    /// [signing offset][EpochOffset::SignerSigning] of the given era.
+    pub fn signers_registration_epoch(&self, era: SupportedEra) -> Result<Epoch, EpochError> {
+        self.epoch.revert_offset(EpochOffset::SignerSigning, era)
+    }
+
     /// This method returns a BeaconOrdering between self and the other beacon.
     ///
     /// This method should be called using the newest beacon available as it will fails if
@@ -371,4 +393,37 @@ mod tests {
             Beacon::new("testnet".to_string(), 10, 200).compute_hash()
         );
     }
+
+    #[test]
+    fn epoch_offset_from_older_and_newer_beacons() {
+        let beacon = Beacon::new("testnet".to_string(), 10, 100);
+
+        assert_eq!(
+            Ok(2),
+            beacon.epoch_offset_from(&Beacon::new("testnet".to_string(), 8, 90))
+        );
+        assert_eq!(
+            Ok(-1),
+            beacon.epoch_offset_from(&Beacon::new("testnet".to_string(), 11, 110))
+        );
+        assert_eq!(
+            Err(BeaconComparisonError::NetworkNotMatch(
+                "testnet".to_string(),
+                "mainnet".to_string()
+            )),
+            beacon.epoch_offset_from(&Beacon::new("mainnet".to_string(), 10, 100))
+        );
+    }
+
+    #[test]
+    fn signers_registration_epoch_revert_the_signing_offset() {
+        let beacon = Beacon::new("testnet".to_string(), 10, 100);
+
+        assert_eq!(
+            Epoch(8),
+            beacon
+                .signers_registration_epoch(SupportedEra::Thales)
+                .unwrap()
+        );
+    }
 }
@@ -0,0 +1,24 @@
+use serde::{Deserialize, Serialize};
+
+use crate::common::entities::BlockNumber;
+
+/// Cardano transactions signing configuration, used by the aggregator to compute the block number
+/// up to which the Cardano transactions are signed.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CardanoTransactionsSigningConfig {
+    /// Number of blocks to discard from the tip of the chain when importing transactions.
+    pub security_parameter: BlockNumber,
+
+    /// The number of blocks between signature of the transactions.
+    pub step: BlockNumber,
+}
+
+impl CardanoTransactionsSigningConfig {
+    /// Dummy instance for test purposes.
+    pub fn dummy() -> Self {
+        Self {
+            security_parameter: 100,
+            step: 15,
+        }
+    }
+}
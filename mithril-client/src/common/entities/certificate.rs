@@ -2,6 +2,8 @@ use crate::common::crypto_helper::{
     ProtocolAggregateVerificationKey, ProtocolGenesisSignature, ProtocolMultiSignature,
 };
 use crate::common::entities::{Beacon, CertificateMetadata, ProtocolMessage};
+use crate::common::StdResult;
+use anyhow::{anyhow, Context};
 use std::cmp::Ordering;
 use std::fmt::{Debug, Formatter};
 
@@ -19,6 +21,28 @@ pub enum CertificateSignature {
     MultiSignature(ProtocolMultiSignature),
 }
 
+/// Version of the scheme used to compute the hash of a [Certificate]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CertificateHashVersion {
+    /// Hash of the hex and JSON hex encodings of the fields, the scheme used by the aggregators
+    /// so far
+    Legacy,
+
+    /// Hash of a version prefix followed by a canonical byte encoding of each field, each one
+    /// prefixed by its length
+    V1,
+}
+
+impl CertificateHashVersion {
+    /// All the known versions
+    pub const ALL: [Self; 2] = [Self::Legacy, Self::V1];
+
+    /// Versions supported by the aggregators, the ones a certificate hash is verified against
+    pub const SUPPORTED: [Self; 1] = [Self::Legacy];
+
+    const V1_PREFIX: &'static [u8] = b"mithril-certificate-hash-v1";
+}
+
 /// Certificate represents a Mithril certificate embedding a Mithril STM multisignature
 #[derive(Clone)]
 pub struct Certificate {
@@ -83,7 +107,8 @@ impl Certificate {
         certificate
     }
 
-    /// Computes the hash of a Certificate
+    /// Computes the hash of a Certificate, with the [legacy][CertificateHashVersion::Legacy]
+    /// scheme used by the aggregators.
     pub fn compute_hash(&self) -> String {
         let mut hasher = Sha256::new();
         hasher.update(self.previous_hash.as_bytes());
@@ -102,12 +127,87 @@ impl Certificate {
                 hasher.update(signature.to_bytes_hex());
             }
             CertificateSignature::MultiSignature(signature) => {
-                hasher.update(&signature.to_json_hex().unwrap());
+                hasher.update(signature.to_json_hex().unwrap());
             }
         };
+
         hex::encode(hasher.finalize())
     }
 
+    /// Computes the hash of a Certificate with the given version of the hashing scheme.
+    pub fn compute_hash_with_version(&self, version: CertificateHashVersion) -> StdResult<String> {
+        match version {
+            CertificateHashVersion::Legacy => Ok(self.compute_hash()),
+            CertificateHashVersion::V1 => self.compute_v1_hash(),
+        }
+    }
+
+    /// Check that the hash of the certificate matches its hash computed with one of the
+    /// [supported][CertificateHashVersion::SUPPORTED] versions of the hashing scheme, returning
+    /// the matching version.
+    pub fn verify_hash(&self) -> StdResult<CertificateHashVersion> {
+        self.verify_hash_with_versions(&CertificateHashVersion::SUPPORTED)
+    }
+
+    /// Check that the hash of the certificate matches its hash computed with one of the given
+    /// versions of the hashing scheme, returning the matching version.
+    ///
+    /// A hash computed with a version that is not in `versions` is rejected.
+    pub fn verify_hash_with_versions(
+        &self,
+        versions: &[CertificateHashVersion],
+    ) -> StdResult<CertificateHashVersion> {
+        for version in versions {
+            let hash = self.compute_hash_with_version(*version).with_context(|| {
+                format!(
+                    "Could not compute the {version:?} hash of certificate '{}'",
+                    self.hash
+                )
+            })?;
+            if hash == self.hash {
+                return Ok(*version);
+            }
+        }
+
+        Err(anyhow!(
+            "Certificate hash '{}' does not match its hash computed with any of the versions {versions:?}",
+            self.hash
+        ))
+    }
+
+    fn compute_v1_hash(&self) -> StdResult<String> {
+        let mut hasher = Sha256::new();
+        let mut update_field = |field: &[u8]| {
+            hasher.update((field.len() as u64).to_be_bytes());
+            hasher.update(field);
+        };
+        update_field(CertificateHashVersion::V1_PREFIX);
+        update_field(self.previous_hash.as_bytes());
+        update_field(self.beacon.network.as_bytes());
+        update_field(&self.beacon.epoch.0.to_be_bytes());
+        update_field(&self.beacon.immutable_file_number.to_be_bytes());
+        update_field(self.metadata.compute_hash().as_bytes());
+        update_field(self.protocol_message.compute_hash().as_bytes());
+        update_field(self.signed_message.as_bytes());
+        // The aggregate verification key has no byte encoding, its JSON encoding is used instead
+        update_field(
+            &serde_json::to_vec(&*self.aggregate_verification_key)
+                .with_context(|| "Could not encode the aggregate verification key")?,
+        );
+        match &self.signature {
+            CertificateSignature::GenesisSignature(signature) => {
+                update_field(&[0]);
+                update_field(&signature.to_bytes());
+            }
+            CertificateSignature::MultiSignature(signature) => {
+                update_field(&[1]);
+                update_field(&signature.to_bytes());
+            }
+        };
+
+        Ok(hex::encode(hasher.finalize()))
+    }
+
     /// Tell if the certificate is a genesis certificate
     pub fn is_genesis(&self) -> bool {
         matches!(self.signature, CertificateSignature::GenesisSignature(_))
@@ -171,3 +271,74 @@ impl Debug for Certificate {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::test_tools::certificate_chain::CertificateChainBuilder;
+
+    use super::*;
+
+    fn setup_certificate_chain(total_certificates: usize) -> Vec<Certificate> {
+        CertificateChainBuilder::new()
+            .with_total_certificates(total_certificates)
+            .build()
+            .unwrap()
+            .certificates
+            .into_iter()
+            .map(|certificate| certificate.try_into().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn verify_the_hash_of_certificates_computed_with_a_supported_version() {
+        let certificates = setup_certificate_chain(3);
+
+        for certificate in certificates {
+            assert_eq!(
+                Ok(CertificateHashVersion::Legacy),
+                certificate.verify_hash().map_err(|e| e.to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn reject_the_hash_of_certificates_computed_with_an_unsupported_version() {
+        let certificates = setup_certificate_chain(3);
+
+        for mut certificate in certificates {
+            certificate.hash = certificate
+                .compute_hash_with_version(CertificateHashVersion::V1)
+                .unwrap();
+            certificate
+                .verify_hash()
+                .expect_err("a V1 hash should be rejected while V1 is not supported");
+            certificate
+                .verify_hash_with_versions(&[CertificateHashVersion::Legacy])
+                .expect_err("a V1 hash should be rejected when only Legacy is accepted");
+            assert_eq!(
+                Ok(CertificateHashVersion::V1),
+                certificate
+                    .verify_hash_with_versions(&CertificateHashVersion::ALL)
+                    .map_err(|e| e.to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn hash_versions_differ_and_detect_tampering() {
+        let certificates = setup_certificate_chain(2);
+        let mut certificate = certificates[0].clone();
+
+        assert_ne!(
+            certificate.compute_hash(),
+            certificate
+                .compute_hash_with_version(CertificateHashVersion::V1)
+                .unwrap()
+        );
+
+        certificate.beacon.immutable_file_number += 1;
+        certificate
+            .verify_hash()
+            .expect_err("a tampered certificate should not match its hash");
+    }
+}
@@ -7,6 +7,7 @@ use std::{
 };
 use thiserror::Error;
 
+use crate::common::era::SupportedEra;
 use crate::common::signable_builder::Beacon as SignableBeacon;
 
 /// Epoch represents a Cardano epoch
@@ -69,6 +70,37 @@ impl Epoch {
         *self + Self::SIGNER_SIGNING_OFFSET
     }
 
+    /// Epoch at which a signer registered at this epoch can send single signatures.
+    pub fn next_signing_epoch(&self) -> Self {
+        self.offset_to_signer_signing_offset()
+    }
+
+    /// Apply the given [EpochOffset], as defined in the given era, to this epoch.
+    ///
+    /// Will fail if the computed epoch is negative.
+    pub fn apply_offset(&self, offset: EpochOffset, era: SupportedEra) -> Result<Self, EpochError> {
+        self.offset_by(offset.value_in_era(era))
+    }
+
+    /// Revert the given [EpochOffset], as defined in the given era, ie: compute the epoch to which
+    /// the offset was applied to obtain this epoch.
+    ///
+    /// Will fail if the computed epoch is negative.
+    pub fn revert_offset(
+        &self,
+        offset: EpochOffset,
+        era: SupportedEra,
+    ) -> Result<Self, EpochError> {
+        self.offset_by(-offset.value_in_era(era))
+    }
+
+    /// Check if this epoch is the result of applying the given [EpochOffset], as defined in the
+    /// given era, to the `base` epoch.
+    pub fn is_offset_of(&self, base: &Epoch, offset: EpochOffset, era: SupportedEra) -> bool {
+        base.apply_offset(offset, era)
+            .is_ok_and(|offset_epoch| offset_epoch == *self)
+    }
+
     /// Computes the next Epoch
     pub fn next(&self) -> Self {
         *self + 1
@@ -203,6 +235,45 @@ impl TryInto<i64> for &Epoch {
     }
 }
 
+/// The offsets applied to epochs by the Mithril protocol.
+///
+/// Their values depend on the era, use [EpochOffset::value_in_era] or [Epoch::apply_offset]
+/// instead of hard-coding them.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum EpochOffset {
+    /// See [Epoch::SIGNER_RETRIEVAL_OFFSET]
+    SignerRetrieval,
+
+    /// See [Epoch::NEXT_SIGNER_RETRIEVAL_OFFSET]
+    NextSignerRetrieval,
+
+    /// See [Epoch::SIGNER_RECORDING_OFFSET]
+    SignerRecording,
+
+    /// See [Epoch::PROTOCOL_PARAMETERS_RECORDING_OFFSET]
+    ProtocolParametersRecording,
+
+    /// See [Epoch::SIGNER_SIGNING_OFFSET]
+    SignerSigning,
+}
+
+impl EpochOffset {
+    /// Value of this offset in the given era.
+    pub fn value_in_era(&self, era: SupportedEra) -> i64 {
+        match era {
+            SupportedEra::Thales => match self {
+                Self::SignerRetrieval => Epoch::SIGNER_RETRIEVAL_OFFSET,
+                Self::NextSignerRetrieval => Epoch::NEXT_SIGNER_RETRIEVAL_OFFSET as i64,
+                Self::SignerRecording => Epoch::SIGNER_RECORDING_OFFSET as i64,
+                Self::ProtocolParametersRecording => {
+                    Epoch::PROTOCOL_PARAMETERS_RECORDING_OFFSET as i64
+                }
+                Self::SignerSigning => Epoch::SIGNER_SIGNING_OFFSET as i64,
+            },
+        }
+    }
+}
+
 /// EpochError is an error triggerred by an [Epoch]
 #[derive(Error, Debug)]
 pub enum EpochError {
@@ -268,6 +339,61 @@ mod tests {
         assert_eq!(6, &Epoch(6));
     }
 
+    #[test]
+    fn apply_and_revert_offsets_of_an_era() {
+        let era = SupportedEra::Thales;
+
+        assert_eq!(
+            Epoch(9),
+            Epoch(10)
+                .apply_offset(EpochOffset::SignerRetrieval, era)
+                .unwrap()
+        );
+        assert_eq!(
+            Epoch(12),
+            Epoch(10)
+                .apply_offset(EpochOffset::SignerSigning, era)
+                .unwrap()
+        );
+        assert_eq!(
+            Epoch(10),
+            Epoch(12)
+                .revert_offset(EpochOffset::SignerSigning, era)
+                .unwrap()
+        );
+        assert!(Epoch(0)
+            .apply_offset(EpochOffset::SignerRetrieval, era)
+            .is_err());
+        assert!(Epoch(1)
+            .revert_offset(EpochOffset::ProtocolParametersRecording, era)
+            .is_err());
+    }
+
+    #[test]
+    fn offsets_match_the_epoch_constants() {
+        let epoch = Epoch(10);
+
+        assert_eq!(
+            epoch.offset_to_signer_signing_offset(),
+            epoch.next_signing_epoch()
+        );
+        assert!(epoch.next_signing_epoch().is_offset_of(
+            &epoch,
+            EpochOffset::SignerSigning,
+            SupportedEra::Thales
+        ));
+        assert!(epoch.offset_to_recording_epoch().is_offset_of(
+            &epoch,
+            EpochOffset::SignerRecording,
+            SupportedEra::Thales
+        ));
+        assert!(!epoch.next().is_offset_of(
+            &epoch,
+            EpochOffset::ProtocolParametersRecording,
+            SupportedEra::Thales
+        ));
+    }
+
     #[test]
     fn test_has_gap_ok() {
         assert!(Epoch(3).has_gap_with(&Epoch(5)));
@@ -1,6 +1,7 @@
 //! The entities used by, and exchanged between, the aggregator, signers and client.
 
 mod beacon;
+mod cardano_transactions_signing_config;
 // mod cardano_network;
 mod certificate;
 mod certificate_metadata;
@@ -19,16 +20,17 @@ mod snapshot;
 mod type_alias;
 
 pub use beacon::{Beacon, BeaconComparison, BeaconComparisonError};
+pub use cardano_transactions_signing_config::CardanoTransactionsSigningConfig;
 // pub use cardano_network::CardanoNetwork;
-pub use certificate::{Certificate, CertificateSignature};
+pub use certificate::{Certificate, CertificateHashVersion, CertificateSignature};
 pub use certificate_metadata::{CertificateMetadata, StakeDistributionParty};
 // pub use certificate_pending::CertificatePending;
-pub use epoch::{Epoch, EpochError};
+pub use epoch::{Epoch, EpochError, EpochOffset};
 // pub use epoch_settings::EpochSettings;
 // pub use http_server_error::{ClientError, InternalServerError};
 // pub use mithril_stake_distribution::MithrilStakeDistribution;
 pub use protocol_message::{ProtocolMessage, ProtocolMessagePartKey, ProtocolMessagePartValue};
-pub use protocol_parameters::ProtocolParameters;
+pub use protocol_parameters::{ProtocolParameters, ProtocolParametersError};
 // pub use signed_entity::*;
 pub use signed_entity_type::*;
 pub use signer::{Signer, SignerWithStake};
@@ -14,6 +14,11 @@ pub enum ProtocolMessagePartKey {
     /// aka AVK(n-1)
     #[serde(rename = "next_aggregate_verification_key")]
     NextAggregateVerificationKey,
+
+    /// The ProtocolMessage part key associated to the digest of the ancillary files archive
+    /// (ie: the ledger state) of a snapshot, only set by aggregators that certify it
+    #[serde(rename = "ancillary_digest")]
+    AncillaryDigest,
 }
 
 impl Display for ProtocolMessagePartKey {
@@ -21,6 +26,7 @@ impl Display for ProtocolMessagePartKey {
         match *self {
             Self::SnapshotDigest => write!(f, "snapshot_digest"),
             Self::NextAggregateVerificationKey => write!(f, "next_aggregate_verification_key"),
+            Self::AncillaryDigest => write!(f, "ancillary_digest"),
         }
     }
 }
@@ -1,6 +1,31 @@
 use fixed::types::U8F24;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+/// [ProtocolParameters::validate] related errors.
+#[derive(Error, Debug, PartialEq)]
+pub enum ProtocolParametersError {
+    /// Error raised when the quorum parameter is zero.
+    #[error("invalid protocol parameters: the quorum parameter k must not be zero")]
+    ZeroQuorum,
+
+    /// Error raised when the quorum parameter is greater than the number of lotteries.
+    #[error("invalid protocol parameters: the quorum parameter k ({k}) must not be greater than the number of lotteries m ({m})")]
+    QuorumGreaterThanLotteries {
+        /// Quorum parameter
+        k: u64,
+
+        /// Number of lotteries
+        m: u64,
+    },
+
+    /// Error raised when phi_f is not in the `]0, 1]` interval.
+    #[error(
+        "invalid protocol parameters: phi_f ({0}) must be greater than 0 and lower or equal to 1"
+    )]
+    PhiFOutOfRange(f64),
+}
 
 /// Protocol cryptographic parameters
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
@@ -27,6 +52,51 @@ impl ProtocolParameters {
         U8F24::from_num(self.phi_f)
     }
 
+    /// Check that these parameters can be used by the protocol: `k` is not zero and not greater
+    /// than `m`, and `phi_f` is in the `]0, 1]` interval.
+    pub fn validate(&self) -> Result<(), ProtocolParametersError> {
+        if self.k == 0 {
+            return Err(ProtocolParametersError::ZeroQuorum);
+        }
+        if self.k > self.m {
+            return Err(ProtocolParametersError::QuorumGreaterThanLotteries {
+                k: self.k,
+                m: self.m,
+            });
+        }
+        if !(self.phi_f > 0.0 && self.phi_f <= 1.0) {
+            return Err(ProtocolParametersError::PhiFOutOfRange(self.phi_f));
+        }
+
+        Ok(())
+    }
+
+    /// Deserialize protocol parameters, failing if they are not [valid][Self::validate].
+    ///
+    /// To use with `#[serde(deserialize_with = "ProtocolParameters::deserialize_valid")]`.
+    pub fn deserialize_valid<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let parameters = Self::deserialize(deserializer)?;
+        parameters.validate().map_err(serde::de::Error::custom)?;
+
+        Ok(parameters)
+    }
+
+    /// Optional version of [deserialize_valid][Self::deserialize_valid].
+    pub fn deserialize_valid_option<'de, D>(deserializer: D) -> Result<Option<Self>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let parameters = Option::<Self>::deserialize(deserializer)?;
+        if let Some(parameters) = &parameters {
+            parameters.validate().map_err(serde::de::Error::custom)?;
+        }
+
+        Ok(parameters)
+    }
+
     /// Computes the hash of ProtocolParameters
     pub fn compute_hash(&self) -> String {
         let mut hasher = Sha256::new();
@@ -71,6 +141,31 @@ mod tests {
         );
     }
 
+    #[test]
+    fn validate_protocol_parameters() {
+        assert_eq!(Ok(()), ProtocolParameters::new(5, 100, 0.65).validate());
+        assert_eq!(Ok(()), ProtocolParameters::new(100, 100, 1.0).validate());
+        assert_eq!(
+            Err(ProtocolParametersError::ZeroQuorum),
+            ProtocolParameters::new(0, 100, 0.65).validate()
+        );
+        assert_eq!(
+            Err(ProtocolParametersError::QuorumGreaterThanLotteries { k: 101, m: 100 }),
+            ProtocolParameters::new(101, 100, 0.65).validate()
+        );
+        assert_eq!(
+            Err(ProtocolParametersError::PhiFOutOfRange(0.0)),
+            ProtocolParameters::new(5, 100, 0.0).validate()
+        );
+        assert_eq!(
+            Err(ProtocolParametersError::PhiFOutOfRange(1.5)),
+            ProtocolParameters::new(5, 100, 1.5).validate()
+        );
+        assert!(ProtocolParameters::new(5, 100, f64::NAN)
+            .validate()
+            .is_err());
+    }
+
     #[test]
     fn test_protocol_parameters_compute_hash() {
         let hash_expected = "ace019657cd995b0dfbb1ce8721a1092715972c4ae0171cc636ab4a44e6e4279";
@@ -56,3 +56,6 @@ pub type HexEncodedDigest = HexEncodedKey;
 
 /// Hex encoded Era Markers Secret Key
 pub type HexEncodedEraMarkersSecretKey = HexEncodedKey;
+
+/// BlockNumber is the block number of a Cardano transaction
+pub type BlockNumber = u64;
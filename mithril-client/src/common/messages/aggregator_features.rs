@@ -0,0 +1,143 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// Name of the Cardano transactions signed entity type
+const CARDANO_TRANSACTIONS_SIGNED_ENTITY_TYPE: &str = "CardanoTransactions";
+
+/// Message advertising the features of an aggregator
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct AggregatorFeaturesMessage {
+    /// Version of the Open API specification implemented by the aggregator
+    pub open_api_version: String,
+
+    /// Url of the documentation of the aggregator
+    pub documentation_url: String,
+
+    /// Capabilities of the aggregator
+    pub capabilities: AggregatorCapabilities,
+}
+
+impl AggregatorFeaturesMessage {
+    /// Return a dummy test entity (test-only).
+    pub fn dummy() -> Self {
+        Self {
+            open_api_version: "0.1.13".to_string(),
+            documentation_url: "https://mithril.network/doc".to_string(),
+            capabilities: AggregatorCapabilities {
+                signed_entity_types: BTreeSet::from([
+                    "CardanoImmutableFilesFull".to_string(),
+                    "MithrilStakeDistribution".to_string(),
+                ]),
+                cardano_transactions_prover: None,
+                cardano_node_version: Some("8.9.0".to_string()),
+                routes: vec![],
+            },
+        }
+    }
+}
+
+/// Capabilities advertised by an aggregator
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct AggregatorCapabilities {
+    /// Names of the signed entity types enabled on the aggregator
+    pub signed_entity_types: BTreeSet<String>,
+
+    /// Capabilities of the Cardano transactions prover, not provided if the aggregator can't
+    /// prove Cardano transactions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cardano_transactions_prover: Option<CardanoTransactionsProverCapabilities>,
+
+    /// Version of the Cardano node used by the aggregator, not provided by older aggregators
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cardano_node_version: Option<String>,
+
+    /// Routes served by the aggregator, empty if not provided by the aggregator
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub routes: Vec<String>,
+}
+
+impl AggregatorCapabilities {
+    /// Check if the signed entity type with the given name, ie: `MithrilStakeDistribution`, is
+    /// enabled on the aggregator.
+    pub fn supports_signed_entity_type(&self, signed_entity_type: &str) -> bool {
+        self.signed_entity_types.contains(signed_entity_type)
+    }
+
+    /// Check if the aggregator certifies Cardano transactions.
+    pub fn supports_cardano_transactions(&self) -> bool {
+        self.supports_signed_entity_type(CARDANO_TRANSACTIONS_SIGNED_ENTITY_TYPE)
+    }
+}
+
+/// Capabilities of the Cardano transactions prover of an aggregator
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct CardanoTransactionsProverCapabilities {
+    /// Maximum number of transaction hashes that can be proven by a single request
+    pub max_hashes_allowed_by_request: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn golden_message_v1() -> AggregatorFeaturesMessage {
+        AggregatorFeaturesMessage {
+            open_api_version: "0.1.13".to_string(),
+            documentation_url: "https://mithril.network/doc".to_string(),
+            capabilities: AggregatorCapabilities {
+                signed_entity_types: BTreeSet::from([
+                    "CardanoTransactions".to_string(),
+                    "MithrilStakeDistribution".to_string(),
+                ]),
+                cardano_transactions_prover: Some(CardanoTransactionsProverCapabilities {
+                    max_hashes_allowed_by_request: 100,
+                }),
+                cardano_node_version: Some("8.9.0".to_string()),
+                routes: vec!["/artifact/snapshots".to_string()],
+            },
+        }
+    }
+
+    // Test the retro compatibility with possible future upgrades.
+    #[test]
+    fn test_v1() {
+        let json = r#"{
+"open_api_version": "0.1.13",
+"documentation_url": "https://mithril.network/doc",
+"capabilities": {
+  "signed_entity_types": ["MithrilStakeDistribution", "CardanoTransactions"],
+  "cardano_transactions_prover": {
+    "max_hashes_allowed_by_request": 100
+  },
+  "cardano_node_version": "8.9.0",
+  "routes": ["/artifact/snapshots"]
+}
+}
+"#;
+        let message: AggregatorFeaturesMessage = serde_json::from_str(json).expect(
+            "This JSON is expected to be succesfully parsed into a AggregatorFeaturesMessage instance.",
+        );
+
+        assert_eq!(golden_message_v1(), message);
+        assert!(message.capabilities.supports_cardano_transactions());
+    }
+
+    #[test]
+    fn optional_capabilities_are_not_required() {
+        let json = r#"{
+"open_api_version": "0.1.13",
+"documentation_url": "https://mithril.network/doc",
+"capabilities": {
+  "signed_entity_types": ["MithrilStakeDistribution"]
+}
+}
+"#;
+        let message: AggregatorFeaturesMessage = serde_json::from_str(json).unwrap();
+
+        assert_eq!(None, message.capabilities.cardano_node_version);
+        assert!(message
+            .capabilities
+            .supports_signed_entity_type("MithrilStakeDistribution"));
+        assert!(!message.capabilities.supports_cardano_transactions());
+    }
+}
@@ -4,8 +4,9 @@ use std::fmt::{Debug, Formatter};
 
 use crate::common::entities::{
     Beacon, Certificate, CertificateMetadata, CertificateSignature, ProtocolMessage,
+    ProtocolMessagePartKey,
 };
-use crate::common::messages::CertificateMetadataMessagePart;
+use crate::common::messages::{CertificateMetadataMessagePart, SignerStatistics};
 use crate::common::StdError;
 
 /// Message structure of a certificate
@@ -53,10 +54,40 @@ pub struct CertificateMessage {
 }
 
 impl CertificateMessage {
+    /// Return a dummy test entity (test-only).
+    pub fn dummy() -> Self {
+        let mut protocol_message = ProtocolMessage::new();
+        protocol_message.set_message_part(
+            ProtocolMessagePartKey::SnapshotDigest,
+            "snapshot-digest-123".to_string(),
+        );
+        protocol_message.set_message_part(
+            ProtocolMessagePartKey::NextAggregateVerificationKey,
+            "next-avk-123".to_string(),
+        );
+        Self {
+            hash: "hash".to_string(),
+            previous_hash: "previous_hash".to_string(),
+            beacon: Beacon::new("testnet".to_string(), 10, 100),
+            metadata: CertificateMetadataMessagePart::dummy(),
+            protocol_message: protocol_message.clone(),
+            signed_message: "signed_message".to_string(),
+            aggregate_verification_key: "aggregate_verification_key".to_string(),
+            multi_signature: "multi_signature".to_string(),
+            genesis_signature: String::new(),
+        }
+    }
+
     /// Check that the certificate signed message match the given protocol message.
     pub fn match_message(&self, message: &ProtocolMessage) -> bool {
         message.compute_hash() == self.signed_message
     }
+
+    /// Compute the [statistics][SignerStatistics] of the stakes of the signers of the
+    /// certificate.
+    pub fn signer_statistics(&self) -> SignerStatistics {
+        self.metadata.signer_statistics()
+    }
 }
 
 impl Debug for CertificateMessage {
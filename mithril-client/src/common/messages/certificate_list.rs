@@ -20,7 +20,10 @@ pub struct CertificateListItemMessageMetadata {
 
     /// Protocol parameters
     /// part of METADATA(p,n)
-    #[serde(rename = "parameters")]
+    #[serde(
+        rename = "parameters",
+        deserialize_with = "ProtocolParameters::deserialize_valid"
+    )]
     pub protocol_parameters: ProtocolParameters,
 
     /// Date and time when the certificate was initiated
@@ -92,7 +95,7 @@ impl CertificateListItemMessage {
             beacon: Beacon::new("testnet".to_string(), 10, 100),
             metadata: CertificateListItemMessageMetadata {
                 protocol_version: "0.1.0".to_string(),
-                protocol_parameters: ProtocolParameters::new(1000, 100, 0.123),
+                protocol_parameters: ProtocolParameters::new(100, 1000, 0.123),
                 initiated_at: DateTime::parse_from_rfc3339("2024-02-12T13:11:47Z")
                     .unwrap()
                     .with_timezone(&Utc),
@@ -155,7 +158,7 @@ mod tests {
             beacon: Beacon::new("testnet".to_string(), 10, 100),
             metadata: CertificateListItemMessageMetadata {
                 protocol_version: "0.1.0".to_string(),
-                protocol_parameters: ProtocolParameters::new(1000, 100, 0.123),
+                protocol_parameters: ProtocolParameters::new(100, 1000, 0.123),
                 initiated_at: DateTime::parse_from_rfc3339("2024-02-12T13:11:47Z")
                     .unwrap()
                     .with_timezone(&Utc),
@@ -184,8 +187,8 @@ mod tests {
 "metadata": {
     "version": "0.1.0",
     "parameters": {
-        "k": 1000,
-        "m": 100,
+        "k": 100,
+        "m": 1000,
         "phi_f": 0.123
     },
     "initiated_at": "2024-02-12T13:11:47Z",
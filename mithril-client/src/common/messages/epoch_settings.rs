@@ -1,4 +1,8 @@
-use crate::common::entities::{Epoch, ProtocolParameters};
+use crate::common::entities::{
+    CardanoTransactionsSigningConfig, Epoch, EpochError, EpochOffset, ProtocolParameters,
+};
+use crate::common::era::SupportedEra;
+use crate::common::messages::SignerMessagePart;
 use serde::{Deserialize, Serialize};
 
 /// EpochSettings represents the settings of an epoch
@@ -8,15 +12,103 @@ pub struct EpochSettingsMessage {
     pub epoch: Epoch,
 
     /// Current Protocol parameters
-    #[serde(rename = "protocol")]
+    #[serde(
+        rename = "protocol",
+        deserialize_with = "ProtocolParameters::deserialize_valid"
+    )]
     pub protocol_parameters: ProtocolParameters,
 
     /// Next Protocol parameters
-    #[serde(rename = "next_protocol")]
+    #[serde(
+        rename = "next_protocol",
+        deserialize_with = "ProtocolParameters::deserialize_valid"
+    )]
     pub next_protocol_parameters: ProtocolParameters,
+
+    /// Signer Registration Protocol parameters, not provided by older aggregators
+    #[serde(
+        rename = "signer_registration_protocol",
+        default,
+        deserialize_with = "ProtocolParameters::deserialize_valid_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub signer_registration_protocol_parameters: Option<ProtocolParameters>,
+
+    /// Current Signers, empty if not provided by the aggregator
+    #[serde(default)]
+    pub current_signers: Vec<SignerMessagePart>,
+
+    /// Signers that will be able to sign on the next epoch, empty if not provided by the aggregator
+    #[serde(default)]
+    pub next_signers: Vec<SignerMessagePart>,
+
+    /// Cardano transactions signing configuration for the current epoch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cardano_transactions_signing_config: Option<CardanoTransactionsSigningConfig>,
+
+    /// Cardano transactions signing configuration for the next epoch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_cardano_transactions_signing_config: Option<CardanoTransactionsSigningConfig>,
 }
 
 impl EpochSettingsMessage {
+    /// Epoch of the stake distribution and verification keys of the [current
+    /// signers][Self::current_signers], the ones signing at the current epoch.
+    ///
+    /// Will fail at epoch zero.
+    pub fn signer_retrieval_epoch(&self, era: SupportedEra) -> Result<Epoch, EpochError> {
+        self.epoch.apply_offset(EpochOffset::SignerRetrieval, era)
+    }
+
+    /// Epoch for which the signers registering during the current epoch are recorded.
+    pub fn signer_recording_epoch(&self, era: SupportedEra) -> Result<Epoch, EpochError> {
+        self.epoch.apply_offset(EpochOffset::SignerRecording, era)
+    }
+
+    /// Epoch from which the signers registering during the current epoch can send single
+    /// signatures.
+    pub fn registered_signers_signing_epoch(&self, era: SupportedEra) -> Result<Epoch, EpochError> {
+        self.epoch.apply_offset(EpochOffset::SignerSigning, era)
+    }
+
+    /// Epoch from which the [next protocol parameters][Self::next_protocol_parameters] apply.
+    ///
+    /// They were recorded during the previous epoch, so they apply the epoch before the
+    /// [signer registration protocol parameters][Self::signer_registration_protocol_parameters].
+    pub fn next_protocol_parameters_epoch(&self, era: SupportedEra) -> Result<Epoch, EpochError> {
+        self.epoch
+            .previous()?
+            .apply_offset(EpochOffset::ProtocolParametersRecording, era)
+    }
+
+    /// Epoch from which the [signer registration protocol
+    /// parameters][Self::signer_registration_protocol_parameters] apply.
+    pub fn signer_registration_protocol_parameters_epoch(
+        &self,
+        era: SupportedEra,
+    ) -> Result<Epoch, EpochError> {
+        self.epoch
+            .apply_offset(EpochOffset::ProtocolParametersRecording, era)
+    }
+
+    /// Protocol parameters that apply at the given epoch, `None` if they are not known from
+    /// these settings: before the current epoch or too far ahead.
+    pub fn protocol_parameters_at(
+        &self,
+        epoch: Epoch,
+        era: SupportedEra,
+    ) -> Option<&ProtocolParameters> {
+        if epoch == self.epoch {
+            Some(&self.protocol_parameters)
+        } else if self.next_protocol_parameters_epoch(era).ok() == Some(epoch) {
+            Some(&self.next_protocol_parameters)
+        } else if self.signer_registration_protocol_parameters_epoch(era).ok() == Some(epoch) {
+            self.signer_registration_protocol_parameters.as_ref()
+        } else {
+            None
+        }
+    }
+
     /// Dummy instance for test purposes.
     pub fn dummy() -> Self {
         Self {
@@ -31,6 +123,17 @@ impl EpochSettingsMessage {
                 m: 100,
                 phi_f: 0.65,
             },
+            signer_registration_protocol_parameters: Some(ProtocolParameters {
+                k: 5,
+                m: 100,
+                phi_f: 0.65,
+            }),
+            current_signers: vec![],
+            next_signers: vec![],
+            cardano_transactions_signing_config: Some(CardanoTransactionsSigningConfig::dummy()),
+            next_cardano_transactions_signing_config: Some(
+                CardanoTransactionsSigningConfig::dummy(),
+            ),
         }
     }
 }
@@ -39,7 +142,7 @@ impl EpochSettingsMessage {
 mod tests {
     use super::*;
 
-    fn golden_message() -> EpochSettingsMessage {
+    fn golden_message_v1() -> EpochSettingsMessage {
         EpochSettingsMessage {
             epoch: Epoch(10),
             protocol_parameters: ProtocolParameters {
@@ -52,9 +155,101 @@ mod tests {
                 m: 1000,
                 phi_f: 0.65,
             },
+            signer_registration_protocol_parameters: None,
+            current_signers: vec![],
+            next_signers: vec![],
+            cardano_transactions_signing_config: None,
+            next_cardano_transactions_signing_config: None,
         }
     }
 
+    fn golden_message_v2() -> EpochSettingsMessage {
+        EpochSettingsMessage {
+            signer_registration_protocol_parameters: Some(ProtocolParameters {
+                k: 500,
+                m: 10000,
+                phi_f: 0.65,
+            }),
+            current_signers: vec![SignerMessagePart {
+                party_id: "123".to_string(),
+                verification_key: "key_123".to_string(),
+                verification_key_signature: Some("signature_123".to_string()),
+                operational_certificate: Some("certificate_123".to_string()),
+                kes_period: Some(12),
+            }],
+            next_signers: vec![SignerMessagePart {
+                party_id: "456".to_string(),
+                verification_key: "key_456".to_string(),
+                verification_key_signature: None,
+                operational_certificate: None,
+                kes_period: None,
+            }],
+            cardano_transactions_signing_config: Some(CardanoTransactionsSigningConfig {
+                security_parameter: 70,
+                step: 20,
+            }),
+            next_cardano_transactions_signing_config: Some(CardanoTransactionsSigningConfig {
+                security_parameter: 50,
+                step: 10,
+            }),
+            ..golden_message_v1()
+        }
+    }
+
+    #[test]
+    fn compute_the_epochs_from_the_current_epoch_offsets() {
+        let message = golden_message_v2();
+        let era = SupportedEra::Thales;
+
+        assert_eq!(Epoch(9), message.signer_retrieval_epoch(era).unwrap());
+        assert_eq!(Epoch(11), message.signer_recording_epoch(era).unwrap());
+        assert_eq!(
+            Epoch(12),
+            message.registered_signers_signing_epoch(era).unwrap()
+        );
+        assert_eq!(
+            Epoch(11),
+            message.next_protocol_parameters_epoch(era).unwrap()
+        );
+        assert_eq!(
+            Epoch(12),
+            message
+                .signer_registration_protocol_parameters_epoch(era)
+                .unwrap()
+        );
+        assert!(EpochSettingsMessage {
+            epoch: Epoch(0),
+            ..message
+        }
+        .signer_retrieval_epoch(era)
+        .is_err());
+    }
+
+    #[test]
+    fn get_the_protocol_parameters_that_apply_at_an_epoch() {
+        let message = golden_message_v2();
+        let era = SupportedEra::Thales;
+
+        assert_eq!(None, message.protocol_parameters_at(Epoch(9), era));
+        assert_eq!(
+            Some(&message.protocol_parameters),
+            message.protocol_parameters_at(Epoch(10), era)
+        );
+        assert_eq!(
+            Some(&message.next_protocol_parameters),
+            message.protocol_parameters_at(Epoch(11), era)
+        );
+        assert_eq!(
+            message.signer_registration_protocol_parameters.as_ref(),
+            message.protocol_parameters_at(Epoch(12), era)
+        );
+        assert_eq!(None, message.protocol_parameters_at(Epoch(13), era));
+        assert_eq!(
+            None,
+            golden_message_v1().protocol_parameters_at(Epoch(12), era)
+        );
+    }
+
     // Test the retro compatibility with possible future upgrades.
     #[test]
     fn test_v1() {
@@ -67,6 +262,34 @@ mod tests {
             "This JSON is expected to be succesfully parsed into a EpochSettingsMessage instance.",
         );
 
-        assert_eq!(golden_message(), message);
+        assert_eq!(golden_message_v1(), message);
+    }
+
+    #[test]
+    fn test_v2() {
+        let json = r#"{
+"epoch": 10,
+"protocol":  { "k": 5, "m": 100, "phi_f": 0.65 },
+"next_protocol":  { "k": 50, "m": 1000, "phi_f": 0.65 },
+"signer_registration_protocol":  { "k": 500, "m": 10000, "phi_f": 0.65 },
+"current_signers":[{
+    "party_id":"123",
+    "verification_key":"key_123",
+    "verification_key_signature":"signature_123",
+    "operational_certificate":"certificate_123",
+    "kes_period":12
+}],
+"next_signers":[{
+    "party_id":"456",
+    "verification_key":"key_456"
+}],
+"cardano_transactions_signing_config": { "security_parameter": 70, "step": 20 },
+"next_cardano_transactions_signing_config": { "security_parameter": 50, "step": 10 }
+}"#;
+        let message: EpochSettingsMessage = serde_json::from_str(json).expect(
+            "This JSON is expected to be succesfully parsed into a EpochSettingsMessage instance.",
+        );
+
+        assert_eq!(golden_message_v2(), message);
     }
 }
@@ -1,4 +1,6 @@
-use crate::common::entities::{ProtocolParameters, ProtocolVersion, StakeDistributionParty};
+use crate::common::entities::{
+    PartyId, ProtocolParameters, ProtocolVersion, Stake, StakeDistributionParty,
+};
 
 use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
@@ -14,7 +16,10 @@ pub struct CertificateMetadataMessagePart {
 
     /// Protocol parameters
     /// part of METADATA(p,n)
-    #[serde(rename = "parameters")]
+    #[serde(
+        rename = "parameters",
+        deserialize_with = "ProtocolParameters::deserialize_valid"
+    )]
     pub protocol_parameters: ProtocolParameters,
 
     /// Date and time when the certificate was initiated
@@ -32,7 +37,86 @@ pub struct CertificateMetadataMessagePart {
     pub signers: Vec<StakeDistributionParty>,
 }
 
+/// Aggregate statistics of the stakes of the signers listed in a certificate metadata
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct SignerStatistics {
+    /// Number of signers
+    pub signers_count: usize,
+
+    /// Sum of the stakes of the signers, in lovelace
+    ///
+    /// Computed on 128 bits so it can't overflow whatever the number of signers.
+    pub total_stake: u128,
+
+    /// Gini coefficient of the stakes of the signers, between 0 (all the signers have the same
+    /// stake) and 1 (a single signer owns all the stake)
+    pub stake_gini_coefficient: f64,
+
+    /// Party id and stake of the signer with the largest stake, `None` if there's no signer
+    pub largest_signer: Option<(PartyId, Stake)>,
+
+    /// Share of the total stake owned by the signer with the largest stake, between 0 and 1
+    pub largest_signer_share: f64,
+}
+
+impl SignerStatistics {
+    /// Compute the statistics of the given signers.
+    pub fn compute(signers: &[StakeDistributionParty]) -> Self {
+        let total_stake: u128 = signers.iter().map(|signer| signer.stake as u128).sum();
+        let largest_signer = signers
+            .iter()
+            .max_by(|a, b| {
+                a.stake
+                    .cmp(&b.stake)
+                    .then_with(|| b.party_id.cmp(&a.party_id))
+            })
+            .map(|signer| (signer.party_id.clone(), signer.stake));
+        if total_stake == 0 {
+            return Self {
+                signers_count: signers.len(),
+                largest_signer,
+                ..Self::default()
+            };
+        }
+
+        let largest_signer_share = largest_signer
+            .as_ref()
+            .map_or(0.0, |(_, stake)| *stake as f64 / total_stake as f64);
+
+        Self {
+            signers_count: signers.len(),
+            total_stake,
+            stake_gini_coefficient: gini_coefficient(signers, total_stake),
+            largest_signer,
+            largest_signer_share,
+        }
+    }
+}
+
+/// Gini coefficient computed on the stakes sorted in ascending order `x_1..x_n`:
+/// `G = Σ (2i - n - 1) x_i / (n Σ x_i)`.
+///
+/// The weighted sum is accumulated on 128 bits integers so the only rounding happens in the
+/// final division.
+fn gini_coefficient(signers: &[StakeDistributionParty], total_stake: u128) -> f64 {
+    let mut stakes: Vec<Stake> = signers.iter().map(|signer| signer.stake).collect();
+    stakes.sort_unstable();
+    let n = stakes.len() as i128;
+    let weighted_sum: i128 = stakes
+        .iter()
+        .enumerate()
+        .map(|(index, stake)| (2 * (index as i128 + 1) - n - 1) * *stake as i128)
+        .sum();
+
+    weighted_sum as f64 / (n as f64 * total_stake as f64)
+}
+
 impl CertificateMetadataMessagePart {
+    /// Compute the [statistics][SignerStatistics] of the stakes of the signers.
+    pub fn signer_statistics(&self) -> SignerStatistics {
+        SignerStatistics::compute(&self.signers)
+    }
+
     /// CertificateMetadata factory
     pub fn dummy() -> Self {
         let initiated_at = DateTime::parse_from_rfc3339("2024-02-12T13:11:47Z")
@@ -41,7 +125,7 @@ impl CertificateMetadataMessagePart {
 
         Self {
             protocol_version: "0.1.0".to_string(),
-            protocol_parameters: ProtocolParameters::new(1000, 100, 0.123),
+            protocol_parameters: ProtocolParameters::new(100, 1000, 0.123),
             initiated_at,
             sealed_at: initiated_at + Duration::seconds(100),
             signers: vec![
@@ -62,10 +146,58 @@ impl CertificateMetadataMessagePart {
 mod tests {
     use super::*;
 
+    fn party(party_id: &str, stake: Stake) -> StakeDistributionParty {
+        StakeDistributionParty {
+            party_id: party_id.to_string(),
+            stake,
+        }
+    }
+
+    #[test]
+    fn signer_statistics_of_equal_stakes() {
+        let statistics =
+            SignerStatistics::compute(&[party("1", 10), party("2", 10), party("3", 10)]);
+
+        assert_eq!(3, statistics.signers_count);
+        assert_eq!(30, statistics.total_stake);
+        assert_eq!(0.0, statistics.stake_gini_coefficient);
+        assert_eq!(Some(("1".to_string(), 10)), statistics.largest_signer);
+        assert!((statistics.largest_signer_share - 1.0 / 3.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn signer_statistics_of_unequal_stakes() {
+        let statistics = SignerStatistics::compute(&[
+            party("1", 0),
+            party("2", 0),
+            party("3", 0),
+            party("4", 40),
+        ]);
+
+        assert_eq!(40, statistics.total_stake);
+        assert_eq!(0.75, statistics.stake_gini_coefficient);
+        assert_eq!(Some(("4".to_string(), 40)), statistics.largest_signer);
+        assert_eq!(1.0, statistics.largest_signer_share);
+    }
+
+    #[test]
+    fn signer_statistics_total_stake_does_not_overflow() {
+        let statistics = SignerStatistics::compute(&[party("1", u64::MAX), party("2", u64::MAX)]);
+
+        assert_eq!(2 * u64::MAX as u128, statistics.total_stake);
+        assert_eq!(0.0, statistics.stake_gini_coefficient);
+        assert_eq!(0.5, statistics.largest_signer_share);
+    }
+
+    #[test]
+    fn signer_statistics_without_signers() {
+        assert_eq!(SignerStatistics::default(), SignerStatistics::compute(&[]));
+    }
+
     fn golden_message() -> CertificateMetadataMessagePart {
         CertificateMetadataMessagePart {
             protocol_version: "0.1.0".to_string(),
-            protocol_parameters: ProtocolParameters::new(1000, 100, 0.123),
+            protocol_parameters: ProtocolParameters::new(100, 1000, 0.123),
             initiated_at: DateTime::parse_from_rfc3339("2024-02-12T13:11:47Z")
                 .unwrap()
                 .with_timezone(&Utc),
@@ -91,8 +223,8 @@ mod tests {
         let json = r#"{
             "version": "0.1.0",
             "parameters": {
-                "k": 1000,
-                "m": 100,
+                "k": 100,
+                "m": 1000,
                 "phi_f": 0.123
             },
             "initiated_at": "2024-02-12T13:11:47Z",
@@ -123,8 +255,8 @@ mod tests {
         let json = r#"{
             "version": "0.1.0",
             "parameters": {
-                "k": 1000,
-                "m": 100,
+                "k": 100,
+                "m": 1000,
                 "phi_f": 0.123
             },
             "initiated_at": "2024-02-12T13:11:47Z",
@@ -146,4 +278,26 @@ mod tests {
 
         assert_eq!(golden_message(), message);
     }
+
+    #[test]
+    fn deserializing_invalid_protocol_parameters_fails() {
+        let json = r#"{
+            "version": "0.1.0",
+            "parameters": {
+                "k": 1000,
+                "m": 100,
+                "phi_f": 0.123
+            },
+            "initiated_at": "2024-02-12T13:11:47Z",
+            "sealed_at": "2024-02-12T13:12:57Z",
+            "signers": []
+        }"#;
+
+        let error = serde_json::from_str::<CertificateMetadataMessagePart>(json).unwrap_err();
+
+        assert!(
+            error.to_string().contains("invalid protocol parameters"),
+            "Unexpected error: {error}"
+        );
+    }
 }
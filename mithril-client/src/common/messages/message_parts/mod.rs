@@ -1,5 +1,5 @@
 mod certificate_metadata;
 mod signer;
 
-pub use certificate_metadata::CertificateMetadataMessagePart;
-pub use signer::{SignerMessagePart, SignerWithStakeMessagePart};
+pub use certificate_metadata::{CertificateMetadataMessagePart, SignerStatistics};
+pub use signer::{SignerMessagePart, SignerWithStakeMessagePart, SignerWithStakeMessagePartRef};
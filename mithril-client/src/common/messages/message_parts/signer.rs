@@ -4,12 +4,13 @@ use crate::common::{
         HexEncodedOpCert, HexEncodedVerificationKey, HexEncodedVerificationKeySignature, PartyId,
         SignerWithStake, Stake,
     },
-    StdResult,
+    StdError, StdResult,
 };
-#[cfg(feature = "test_tools")]
-use crate::test_utils::fake_keys;
+#[cfg(any(test, feature = "test_tools"))]
+use crate::test_tools::fake_keys;
 use anyhow::Context;
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::fmt::{Debug, Formatter};
 
 /// Signer with Stake Message
@@ -47,9 +48,24 @@ pub struct SignerWithStakeMessagePart {
 }
 
 impl SignerWithStakeMessagePart {
+    /// Return a dummy test entity (test-only).
+    #[cfg(any(test, feature = "test_tools"))]
+    pub fn dummy() -> Self {
+        Self {
+            party_id: "pool1m8crhnqj5k2kyszf5j2scshupystyxc887zdfrpzh6ty6eun4fx".to_string(),
+            verification_key: fake_keys::signer_verification_key()[0].to_string(),
+            verification_key_signature: Some(
+                fake_keys::signer_verification_key_signature()[0].to_string(),
+            ),
+            operational_certificate: Some(fake_keys::operational_certificate()[0].to_string()),
+            kes_period: Some(6),
+            stake: 234,
+        }
+    }
+
     /// Convert a set of signers into message parts
-    pub fn from_signers(signers: Vec<SignerWithStake>) -> Vec<Self> {
-        signers.into_iter().map(|signer| signer.into()).collect()
+    pub fn try_from_signers(signers: Vec<SignerWithStake>) -> StdResult<Vec<Self>> {
+        signers.into_iter().map(Self::try_from).collect()
     }
 
     /// Convert a set of signer message parts into a set of signers with stake
@@ -86,23 +102,143 @@ impl SignerWithStakeMessagePart {
     }
 }
 
-impl From<SignerWithStake> for SignerWithStakeMessagePart {
-    fn from(value: SignerWithStake) -> Self {
+/// Signer with Stake Message borrowing its hex encoded keys from the deserialized content.
+///
+/// Verifying a large stake distribution only needs the keys once, this avoids copying all of
+/// them, use a [SignerWithStakeMessagePart] to retain them instead.
+#[derive(Clone, PartialEq, Eq, Deserialize)]
+pub struct SignerWithStakeMessagePartRef<'a> {
+    /// The unique identifier of the signer
+    #[serde(borrow)]
+    pub party_id: Cow<'a, str>,
+
+    /// The public key used to authenticate signer signature
+    #[serde(borrow)]
+    pub verification_key: Cow<'a, str>,
+
+    /// The encoded signer 'Mithril verification key' signature (signed by the
+    /// Cardano node KES secret key).
+    #[serde(borrow, default)]
+    pub verification_key_signature: Option<Cow<'a, str>>,
+
+    /// The encoded operational certificate of stake pool operator attached to
+    /// the signer node.
+    #[serde(borrow, default)]
+    pub operational_certificate: Option<Cow<'a, str>>,
+
+    /// The KES period used to compute the verification key signature
+    #[serde(default)]
+    pub kes_period: Option<KESPeriod>,
+
+    /// The signer stake
+    pub stake: Stake,
+}
+
+impl<'a> SignerWithStakeMessagePartRef<'a> {
+    /// Convert a set of borrowed signer message parts into a set of signers with stake
+    pub fn try_into_signers(messages: &[Self]) -> StdResult<Vec<SignerWithStake>> {
+        messages.iter().map(Self::try_to_signer).collect()
+    }
+
+    fn try_to_signer(&self) -> StdResult<SignerWithStake> {
+        let verification_key_signature: Option<ProtocolSignerVerificationKeySignature> = self
+            .verification_key_signature
+            .as_deref()
+            .map(|f| f.try_into())
+            .transpose()
+            .with_context(|| {
+                format!(
+                    "Error while parsing verification key signature message, party_id = '{}'",
+                    self.party_id
+                )
+            })?;
+        let operational_certificate: Option<ProtocolOpCert> = self
+            .operational_certificate
+            .as_deref()
+            .map(|f| f.try_into())
+            .transpose()
+            .with_context(|| {
+                format!(
+                    "Error while parsing operational certificate message, party_id = '{}'.",
+                    self.party_id
+                )
+            })?;
+
+        Ok(SignerWithStake {
+            party_id: self.party_id.to_string(),
+            verification_key: self.verification_key.as_ref().try_into()?,
+            verification_key_signature,
+            kes_period: self.kes_period,
+            operational_certificate,
+            stake: self.stake,
+        })
+    }
+}
+
+impl<'a> From<&'a SignerWithStakeMessagePart> for SignerWithStakeMessagePartRef<'a> {
+    fn from(value: &'a SignerWithStakeMessagePart) -> Self {
         Self {
-            party_id: value.party_id,
-            verification_key: value.verification_key.try_into().unwrap(),
-            verification_key_signature: value
-                .verification_key_signature
-                .map(|k| k.try_into().unwrap()),
-            operational_certificate: value
-                .operational_certificate
-                .map(|op_cert| (op_cert.try_into().unwrap())),
+            party_id: Cow::Borrowed(&value.party_id),
+            verification_key: Cow::Borrowed(&value.verification_key),
+            verification_key_signature: value.verification_key_signature.as_deref().map(Cow::from),
+            operational_certificate: value.operational_certificate.as_deref().map(Cow::from),
             kes_period: value.kes_period,
             stake: value.stake,
         }
     }
 }
 
+impl TryFrom<SignerWithStake> for SignerWithStakeMessagePart {
+    type Error = StdError;
+
+    fn try_from(value: SignerWithStake) -> Result<Self, Self::Error> {
+        let verification_key = value.verification_key.try_into().with_context(|| {
+            format!(
+                "Error while encoding verification key, party_id = '{}'",
+                value.party_id
+            )
+        })?;
+        let verification_key_signature = value
+            .verification_key_signature
+            .map(|k| k.try_into())
+            .transpose()
+            .with_context(|| {
+                format!(
+                    "Error while encoding verification key signature, party_id = '{}'",
+                    value.party_id
+                )
+            })?;
+        let operational_certificate = value
+            .operational_certificate
+            .map(|op_cert| op_cert.try_into())
+            .transpose()
+            .with_context(|| {
+                format!(
+                    "Error while encoding operational certificate, party_id = '{}'",
+                    value.party_id
+                )
+            })?;
+
+        Ok(Self {
+            party_id: value.party_id,
+            verification_key,
+            verification_key_signature,
+            operational_certificate,
+            kes_period: value.kes_period,
+            stake: value.stake,
+        })
+    }
+}
+
+impl Debug for SignerWithStakeMessagePartRef<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Signer")
+            .field("party_id", &self.party_id)
+            .field("stake", &self.stake)
+            .finish_non_exhaustive()
+    }
+}
+
 impl Debug for SignerMessagePart {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let should_be_exhaustive = f.alternate();
@@ -189,3 +325,20 @@ impl Debug for SignerWithStakeMessagePart {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn convert_signers_to_message_parts_and_back() {
+        let message_parts = vec![SignerWithStakeMessagePart::dummy()];
+
+        let signers = SignerWithStakeMessagePart::try_into_signers(message_parts.clone()).unwrap();
+
+        assert_eq!(
+            message_parts,
+            SignerWithStakeMessagePart::try_from_signers(signers).unwrap()
+        );
+    }
+}
@@ -28,3 +28,20 @@ pub struct MithrilStakeDistributionMessage {
     /// Protocol parameters used to compute AVK
     pub protocol_parameters: ProtocolParameters,
 }
+
+impl MithrilStakeDistributionMessage {
+    /// Return a dummy test entity (test-only).
+    #[cfg(any(test, feature = "test_tools"))]
+    pub fn dummy() -> Self {
+        Self {
+            epoch: Epoch(1),
+            signers_with_stake: vec![SignerWithStakeMessagePart::dummy()],
+            hash: "hash-123".to_string(),
+            certificate_hash: "certificate-hash-123".to_string(),
+            created_at: DateTime::parse_from_rfc3339("2023-01-19T13:43:05.618857482Z")
+                .unwrap()
+                .with_timezone(&Utc),
+            protocol_parameters: ProtocolParameters::new(5, 100, 0.65),
+        }
+    }
+}
@@ -1,5 +1,6 @@
 //! Messages module
 //! This module aims at providing shared structures for API communications.
+mod aggregator_features;
 mod certificate;
 mod certificate_list;
 mod epoch_settings;
@@ -11,6 +12,9 @@ mod snapshot;
 mod snapshot_download;
 mod snapshot_list;
 
+pub use aggregator_features::{
+    AggregatorCapabilities, AggregatorFeaturesMessage, CardanoTransactionsProverCapabilities,
+};
 pub use certificate::CertificateMessage;
 pub use certificate_list::{
     CertificateListItemMessage, CertificateListItemMessageMetadata, CertificateListMessage,
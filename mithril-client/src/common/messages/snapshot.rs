@@ -31,6 +31,18 @@ pub struct SnapshotMessage {
     /// Cardano node version
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cardano_node_version: Option<String>,
+
+    /// Locations where the ancillary files (ie: the ledger state) archive can be retrieved
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ancillary_locations: Option<Vec<String>>,
+
+    /// Size of the ancillary files archive in Bytes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ancillary_size: Option<u64>,
+
+    /// Compression algorithm of the ancillary files archive
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ancillary_compression_algorithm: Option<CompressionAlgorithm>,
 }
 
 impl SnapshotMessage {
@@ -52,6 +64,9 @@ impl SnapshotMessage {
             locations: vec!["https://host/certificate.tar.gz".to_string()],
             compression_algorithm: Some(CompressionAlgorithm::Gzip),
             cardano_node_version: Some("0.0.1".to_string()),
+            ancillary_locations: Some(vec!["https://host/ancillary.tar.gz".to_string()]),
+            ancillary_size: Some(2_048_000),
+            ancillary_compression_algorithm: Some(CompressionAlgorithm::Zstandard),
         }
     }
 }
@@ -77,6 +92,9 @@ mod tests {
             locations: vec!["https://host/certificate.tar.gz".to_string()],
             compression_algorithm: None,
             cardano_node_version: None,
+            ancillary_locations: None,
+            ancillary_size: None,
+            ancillary_compression_algorithm: None,
         }
     }
 
@@ -97,6 +115,24 @@ mod tests {
             locations: vec!["https://host/certificate.tar.gz".to_string()],
             compression_algorithm: Some(CompressionAlgorithm::Gzip),
             cardano_node_version: Some("0.0.1".to_string()),
+            ancillary_locations: None,
+            ancillary_size: None,
+            ancillary_compression_algorithm: None,
+        }
+    }
+
+    fn golden_message_v3() -> SnapshotMessage {
+        SnapshotMessage {
+            ancillary_locations: Some(vec!["https://host/ancillary.tar.gz".to_string()]),
+            ancillary_size: Some(2048000),
+            ..golden_message_v2()
+        }
+    }
+
+    fn golden_message_v4() -> SnapshotMessage {
+        SnapshotMessage {
+            ancillary_compression_algorithm: Some(CompressionAlgorithm::Zstandard),
+            ..golden_message_v3()
         }
     }
 
@@ -148,4 +184,63 @@ mod tests {
 
         assert_eq!(golden_message_v2(), message);
     }
+
+    #[test]
+    fn test_v3() {
+        let json = r#"{
+"digest": "0b9f5ad7f33cc523775c82249294eb8a1541d54f08eb3107cafc5638403ec7c6",
+"beacon": {
+  "network": "preview",
+  "epoch": 86,
+  "immutable_file_number": 1728
+},
+"certificate_hash": "d5daf6c03ace4a9c074e951844075b9b373bafc4e039160e3e2af01823e9abfb",
+"size": 807803196,
+"created_at": "2023-01-19T13:43:05.618857482Z",
+"locations": [
+  "https://host/certificate.tar.gz"
+],
+"compression_algorithm": "gzip",
+"cardano_node_version": "0.0.1",
+"ancillary_locations": [
+  "https://host/ancillary.tar.gz"
+],
+"ancillary_size": 2048000
+}"#;
+        let message: SnapshotMessage = serde_json::from_str(json).expect(
+            "This JSON is expected to be succesfully parsed into a SnapshotMessage instance.",
+        );
+
+        assert_eq!(golden_message_v3(), message);
+    }
+
+    #[test]
+    fn test_v4() {
+        let json = r#"{
+"digest": "0b9f5ad7f33cc523775c82249294eb8a1541d54f08eb3107cafc5638403ec7c6",
+"beacon": {
+  "network": "preview",
+  "epoch": 86,
+  "immutable_file_number": 1728
+},
+"certificate_hash": "d5daf6c03ace4a9c074e951844075b9b373bafc4e039160e3e2af01823e9abfb",
+"size": 807803196,
+"created_at": "2023-01-19T13:43:05.618857482Z",
+"locations": [
+  "https://host/certificate.tar.gz"
+],
+"compression_algorithm": "gzip",
+"cardano_node_version": "0.0.1",
+"ancillary_locations": [
+  "https://host/ancillary.tar.gz"
+],
+"ancillary_size": 2048000,
+"ancillary_compression_algorithm": "zstandard"
+}"#;
+        let message: SnapshotMessage = serde_json::from_str(json).expect(
+            "This JSON is expected to be succesfully parsed into a SnapshotMessage instance.",
+        );
+
+        assert_eq!(golden_message_v4(), message);
+    }
 }
@@ -1,6 +1,8 @@
 pub mod api_version;
 pub mod certificate_chain;
 pub mod crypto_helper;
+#[cfg(feature = "digesters")]
+#[cfg_attr(docsrs, doc(cfg(feature = "digesters")))]
 pub mod digesters;
 pub mod entities;
 pub mod era;
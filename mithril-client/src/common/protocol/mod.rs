@@ -5,9 +5,30 @@
 //! aggregate verification keys.
 
 mod multi_signer;
+#[cfg(any(test, feature = "signer_tools"))]
+mod preflight;
+#[cfg(any(test, feature = "signer_tools"))]
+mod protocol_initializer_store;
 mod signer_builder;
+#[cfg(any(test, feature = "signer_tools"))]
 mod single_signer;
 
 pub use multi_signer::MultiSigner;
-pub use signer_builder::{SignerBuilder, SignerBuilderError};
-pub use single_signer::SingleSigner;
+#[cfg(any(test, feature = "signer_tools"))]
+#[cfg_attr(docsrs, doc(cfg(feature = "signer_tools")))]
+pub use preflight::{
+    PreflightCheck, PreflightCheckOutcome, RegistrationPreflight, RegistrationPreflightReport,
+};
+#[cfg(any(test, feature = "signer_tools"))]
+#[cfg_attr(docsrs, doc(cfg(feature = "signer_tools")))]
+pub use protocol_initializer_store::{
+    ProtocolInitializerStore, ProtocolInitializerStoreError,
+    PROTOCOL_INITIALIZER_STORE_FORMAT_VERSION,
+};
+pub use signer_builder::{
+    AggregateVerificationKeyComputation, AggregateVerificationKeyProgress, SignerBuilder,
+    SignerBuilderError,
+};
+#[cfg(any(test, feature = "signer_tools"))]
+#[cfg_attr(docsrs, doc(cfg(feature = "signer_tools")))]
+pub use single_signer::{SingleSignaturesBatch, SingleSigner};
@@ -1,5 +1,6 @@
 use anyhow::{anyhow, Context};
-use mithril_stm::stm::StmParameters;
+use mithril_stm::stm::{Index, Stake, StmParameters, StmVerificationKey};
+use std::sync::OnceLock;
 
 use crate::common::{
     crypto_helper::{
@@ -14,6 +15,8 @@ use crate::common::{
 pub struct MultiSigner {
     protocol_clerk: ProtocolClerk,
     protocol_parameters: StmParameters,
+    aggregate_verification_key: OnceLock<ProtocolAggregateVerificationKey>,
+    registered_parties: OnceLock<Vec<(StmVerificationKey, Stake)>>,
 }
 
 impl MultiSigner {
@@ -21,6 +24,8 @@ impl MultiSigner {
         Self {
             protocol_clerk,
             protocol_parameters,
+            aggregate_verification_key: OnceLock::new(),
+            registered_parties: OnceLock::new(),
         }
     }
 
@@ -49,20 +54,37 @@ impl MultiSigner {
     }
 
     /// Verify a single signature
+    ///
+    /// The aggregate verification key is computed on the first call and reused afterward.
     pub fn verify_single_signature(
         &self,
         message: &ProtocolMessage,
         single_signature: &SingleSignatures,
     ) -> StdResult<()> {
-        let protocol_signature = single_signature.to_protocol_signature();
+        let avk = self
+            .aggregate_verification_key
+            .get_or_init(|| self.compute_aggregate_verification_key());
+
+        self.verify_single_signature_with_avk(message, single_signature, avk)
+    }
 
-        let avk = self.compute_aggregate_verification_key();
+    /// Verify a single signature against the given precomputed aggregate verification key,
+    /// ie: the one of a certificate, instead of computing it from the registered parties.
+    ///
+    /// The registered parties are looked up in a cache built on the first call, so verifying
+    /// many signatures only costs their cryptographic verification.
+    pub fn verify_single_signature_with_avk(
+        &self,
+        message: &ProtocolMessage,
+        single_signature: &SingleSignatures,
+        avk: &ProtocolAggregateVerificationKey,
+    ) -> StdResult<()> {
+        let protocol_signature = single_signature.to_protocol_signature();
 
         // If there is no reg_party, then we simply received a signature from a non-registered
         // party, and we can ignore the request.
         let (vk, stake) = self
-            .protocol_clerk
-            .get_reg_party(&protocol_signature.signer_index)
+            .get_registered_party(protocol_signature.signer_index)
             .ok_or_else(|| {
                 anyhow!(format!(
                     "Unregistered party: '{}'",
@@ -73,9 +95,9 @@ impl MultiSigner {
         protocol_signature
             .verify(
                 &self.protocol_parameters,
-                &vk,
-                &stake,
-                &avk,
+                vk,
+                stake,
+                avk,
                 message.compute_hash().as_bytes(),
             )
             .with_context(|| {
@@ -87,4 +109,14 @@ impl MultiSigner {
 
         Ok(())
     }
+
+    fn get_registered_party(&self, signer_index: Index) -> Option<&(StmVerificationKey, Stake)> {
+        self.registered_parties
+            .get_or_init(|| {
+                (0..)
+                    .map_while(|index| self.protocol_clerk.get_reg_party(&index))
+                    .collect()
+            })
+            .get(signer_index as usize)
+    }
 }
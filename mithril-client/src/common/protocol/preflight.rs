@@ -0,0 +1,378 @@
+//! Simulate the registration of a signer before the registration window of an epoch opens.
+//!
+//! A [RegistrationPreflight] goes through the same steps as a real registration: it sets up a
+//! protocol initializer signed with the KES key of the operational certificate, then registers
+//! it against the given stake distribution like an aggregator would. Each step is reported in a
+//! [RegistrationPreflightReport] so that a SPO can fix its setup before it matters.
+
+use kes_summed_ed25519::traits::KesSig;
+use rand_core::{CryptoRng, RngCore};
+
+use crate::common::{
+    crypto_helper::{
+        KESPeriod, KesSigner, OpCert, ProtocolInitializer, ProtocolKeyRegistration,
+        ProtocolPartyId, ProtocolRegistrationErrorWrapper, ProtocolStake,
+        ProtocolStakeDistribution, StmInitializerSetupOptions,
+    },
+    entities::ProtocolParameters,
+};
+
+/// A step of a [RegistrationPreflight].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PreflightCheck {
+    /// The operational certificate is signed by the cold key of the pool.
+    OpCertValidity,
+
+    /// The pool id can be computed from the cold key of the operational certificate.
+    PoolId,
+
+    /// The pool is part of the stake distribution with a non zero stake.
+    StakeDistributionPresence,
+
+    /// The protocol initializer can be set up, its verification key being signed by the KES key.
+    InitializerSetup,
+
+    /// The KES signature of the verification key is valid for the KES verification key of the
+    /// operational certificate at the given KES period.
+    KesSignature,
+
+    /// The signer is registered by a key registration built from the stake distribution, and is
+    /// identified as the pool of the operational certificate.
+    Registration,
+}
+
+/// Outcome of a [PreflightCheck].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PreflightCheckOutcome {
+    /// The check passed.
+    Passed,
+
+    /// The check failed for the given reason.
+    Failed(String),
+
+    /// The check was not run because a check it depends on failed.
+    Skipped,
+}
+
+/// Report of a [RegistrationPreflight], listing the outcome of each of its checks in the order
+/// they were run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegistrationPreflightReport {
+    /// Pool id computed from the operational certificate
+    pub pool_id: Option<ProtocolPartyId>,
+
+    /// Stake of the pool in the stake distribution
+    pub stake: Option<ProtocolStake>,
+
+    /// KES period used to sign the verification key
+    pub kes_period: KESPeriod,
+
+    /// Outcome of each check
+    pub checks: Vec<(PreflightCheck, PreflightCheckOutcome)>,
+}
+
+impl RegistrationPreflightReport {
+    /// Check if every check passed, ie: the signer can register with this setup.
+    pub fn is_ready(&self) -> bool {
+        self.checks
+            .iter()
+            .all(|(_, outcome)| *outcome == PreflightCheckOutcome::Passed)
+    }
+
+    /// Outcome of the given check, `None` if it was not part of the preflight.
+    pub fn outcome(&self, check: PreflightCheck) -> Option<&PreflightCheckOutcome> {
+        self.checks
+            .iter()
+            .find(|(c, _)| *c == check)
+            .map(|(_, outcome)| outcome)
+    }
+
+    /// Failed checks with their reason.
+    pub fn failures(&self) -> Vec<(PreflightCheck, &str)> {
+        self.checks
+            .iter()
+            .filter_map(|(check, outcome)| match outcome {
+                PreflightCheckOutcome::Failed(reason) => Some((*check, reason.as_str())),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn record(&mut self, check: PreflightCheck, result: Result<(), String>) -> bool {
+        let outcome = match result {
+            Ok(()) => PreflightCheckOutcome::Passed,
+            Err(reason) => PreflightCheckOutcome::Failed(reason),
+        };
+        let passed = outcome == PreflightCheckOutcome::Passed;
+        self.checks.push((check, outcome));
+
+        passed
+    }
+
+    fn skip(&mut self, check: PreflightCheck) {
+        self.checks.push((check, PreflightCheckOutcome::Skipped));
+    }
+}
+
+/// Simulate a full signer registration from an operational certificate and its KES key.
+#[derive(Debug, Clone)]
+pub struct RegistrationPreflight {
+    protocol_parameters: ProtocolParameters,
+    opcert: OpCert,
+    kes_period: KESPeriod,
+}
+
+impl RegistrationPreflight {
+    /// [RegistrationPreflight] factory
+    ///
+    /// The `kes_period` is relative to the start of the operational certificate, use a
+    /// [KesPeriodCalculator][crate::common::crypto_helper::KesPeriodCalculator] to compute it
+    /// from the current slot.
+    pub fn new(
+        protocol_parameters: &ProtocolParameters,
+        opcert: OpCert,
+        kes_period: KESPeriod,
+    ) -> Self {
+        Self {
+            protocol_parameters: protocol_parameters.clone(),
+            opcert,
+            kes_period,
+        }
+    }
+
+    /// Run the preflight, signing with the given [KesSigner] and registering against the given
+    /// stake distribution.
+    ///
+    /// The checks that depend on a failed check are skipped, the other ones always run so the
+    /// report gives as much information as possible.
+    pub fn run<R: RngCore + CryptoRng>(
+        &self,
+        kes_signer: &dyn KesSigner,
+        stake_distribution: &ProtocolStakeDistribution,
+        rng: &mut R,
+    ) -> RegistrationPreflightReport {
+        let mut report = RegistrationPreflightReport {
+            pool_id: None,
+            stake: None,
+            kes_period: self.kes_period,
+            checks: vec![],
+        };
+
+        let opcert_valid = report.record(
+            PreflightCheck::OpCertValidity,
+            self.opcert.validate().map_err(|e| e.to_string()),
+        );
+
+        match self.opcert.compute_protocol_party_id() {
+            Ok(pool_id) => {
+                report.record(PreflightCheck::PoolId, Ok(()));
+                report.pool_id = Some(pool_id);
+            }
+            Err(error) => {
+                report.record(PreflightCheck::PoolId, Err(error.to_string()));
+            }
+        }
+
+        match &report.pool_id {
+            Some(pool_id) => {
+                let stake = stake_distribution
+                    .iter()
+                    .find(|(party_id, _)| party_id == pool_id)
+                    .map(|(_, stake)| *stake)
+                    .filter(|stake| *stake > 0);
+                let result = stake.map(|_| ()).ok_or_else(|| {
+                    format!("pool '{pool_id}' has no stake in the given stake distribution")
+                });
+                report.stake = stake;
+                report.record(PreflightCheck::StakeDistributionPresence, result);
+            }
+            None => report.skip(PreflightCheck::StakeDistributionPresence),
+        }
+
+        let initializer = match ProtocolInitializer::setup_with_kes_signer(
+            self.protocol_parameters.clone().into(),
+            Some(kes_signer),
+            Some(self.kes_period),
+            report.stake.unwrap_or_default(),
+            rng,
+            &StmInitializerSetupOptions::default().with_strict(true),
+        ) {
+            Ok(initializer) => {
+                report.record(PreflightCheck::InitializerSetup, Ok(()));
+                Some(initializer)
+            }
+            Err(error) => {
+                report.record(PreflightCheck::InitializerSetup, Err(format!("{error:#}")));
+                None
+            }
+        };
+
+        let kes_signature_valid = match &initializer {
+            Some(initializer) => {
+                let verification_key = initializer.verification_key().to_bytes();
+                let result = match initializer.verification_key_signature() {
+                    Some(signature) => signature
+                        .verify(self.kes_period, &self.opcert.kes_vk, &verification_key)
+                        .map_err(|_| {
+                            ProtocolRegistrationErrorWrapper::KesSignatureInvalid(
+                                self.kes_period,
+                                self.opcert.start_kes_period,
+                            )
+                            .to_string()
+                        }),
+                    None => Err(ProtocolRegistrationErrorWrapper::KesSignatureMissing.to_string()),
+                };
+                report.record(PreflightCheck::KesSignature, result)
+            }
+            None => {
+                report.skip(PreflightCheck::KesSignature);
+                false
+            }
+        };
+
+        match (&initializer, &report.pool_id, report.stake) {
+            (Some(initializer), Some(pool_id), Some(_)) if opcert_valid && kes_signature_valid => {
+                let mut key_registration = ProtocolKeyRegistration::init(stake_distribution);
+                let result = key_registration
+                    .register(
+                        None,
+                        Some(self.opcert.clone().into()),
+                        initializer.verification_key_signature(),
+                        Some(self.kes_period),
+                        initializer.verification_key().into(),
+                    )
+                    .map_err(|e| e.to_string())
+                    .and_then(|registered_party_id| {
+                        if &registered_party_id == pool_id {
+                            Ok(())
+                        } else {
+                            Err(format!(
+                                "signer registered as '{registered_party_id}' instead of pool '{pool_id}'"
+                            ))
+                        }
+                    });
+                report.record(PreflightCheck::Registration, result);
+            }
+            _ => report.skip(PreflightCheck::Registration),
+        }
+
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ed25519_dalek::SigningKey;
+    use kes_summed_ed25519::kes::{Sum6Kes, Sum6KesSig};
+    use kes_summed_ed25519::traits::KesSk;
+    use rand_chacha::ChaCha20Rng;
+    use rand_core::SeedableRng;
+
+    use crate::common::StdResult;
+
+    use super::*;
+
+    struct InMemoryKesSigner {
+        seed: [u8; 32],
+    }
+
+    impl InMemoryKesSigner {
+        fn opcert(&self, start_kes_period: u64) -> OpCert {
+            let mut seed = self.seed;
+            let (_, kes_vk) = Sum6Kes::keygen(&mut [0u8; Sum6Kes::SIZE + 4], &mut seed);
+
+            OpCert::new(
+                kes_vk,
+                0,
+                start_kes_period,
+                SigningKey::from_bytes(&[7u8; 32]),
+            )
+        }
+    }
+
+    impl KesSigner for InMemoryKesSigner {
+        fn sign(&self, message: &[u8], kes_period: KESPeriod) -> StdResult<Sum6KesSig> {
+            let mut seed = self.seed;
+            let mut key_buffer = [0u8; Sum6Kes::SIZE + 4];
+            let (mut kes_sk, _) = Sum6Kes::keygen(&mut key_buffer, &mut seed);
+            for _ in 0..kes_period {
+                kes_sk.update().unwrap();
+            }
+
+            Ok(kes_sk.sign(message))
+        }
+    }
+
+    fn run_preflight(
+        kes_signer: &InMemoryKesSigner,
+        opcert: OpCert,
+        stake_distribution: &ProtocolStakeDistribution,
+    ) -> RegistrationPreflightReport {
+        RegistrationPreflight::new(&ProtocolParameters::new(5, 100, 0.65), opcert, 2).run(
+            kes_signer,
+            stake_distribution,
+            &mut ChaCha20Rng::from_seed([0; 32]),
+        )
+    }
+
+    #[test]
+    fn report_ready_for_a_pool_of_the_stake_distribution() {
+        let kes_signer = InMemoryKesSigner { seed: [3; 32] };
+        let opcert = kes_signer.opcert(0);
+        let pool_id = opcert.compute_protocol_party_id().unwrap();
+        let stake_distribution = vec![(pool_id.clone(), 100), ("pool-other".to_string(), 50)];
+
+        let report = run_preflight(&kes_signer, opcert, &stake_distribution);
+
+        assert!(report.is_ready(), "{report:?}");
+        assert_eq!(Some(pool_id), report.pool_id);
+        assert_eq!(Some(100), report.stake);
+        assert_eq!(6, report.checks.len());
+    }
+
+    #[test]
+    fn report_a_pool_missing_from_the_stake_distribution() {
+        let kes_signer = InMemoryKesSigner { seed: [3; 32] };
+        let opcert = kes_signer.opcert(0);
+
+        let report = run_preflight(&kes_signer, opcert, &vec![("pool-other".to_string(), 50)]);
+
+        assert!(!report.is_ready());
+        assert_eq!(None, report.stake);
+        assert_eq!(
+            vec![PreflightCheck::StakeDistributionPresence],
+            report
+                .failures()
+                .into_iter()
+                .map(|(check, _)| check)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            Some(&PreflightCheckOutcome::Passed),
+            report.outcome(PreflightCheck::KesSignature)
+        );
+        assert_eq!(
+            Some(&PreflightCheckOutcome::Skipped),
+            report.outcome(PreflightCheck::Registration)
+        );
+    }
+
+    #[test]
+    fn report_a_kes_key_that_does_not_match_the_opcert() {
+        let kes_signer = InMemoryKesSigner { seed: [3; 32] };
+        let opcert = InMemoryKesSigner { seed: [4; 32] }.opcert(0);
+        let pool_id = opcert.compute_protocol_party_id().unwrap();
+
+        let report = run_preflight(&kes_signer, opcert, &vec![(pool_id, 100)]);
+
+        assert!(!report.is_ready());
+        assert!(matches!(
+            report.outcome(PreflightCheck::KesSignature),
+            Some(PreflightCheckOutcome::Failed(_))
+        ));
+        assert_eq!(
+            Some(&PreflightCheckOutcome::Skipped),
+            report.outcome(PreflightCheck::Registration)
+        );
+    }
+}
@@ -0,0 +1,506 @@
+use anyhow::{anyhow, Context};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use rand_core::{OsRng, RngCore};
+use secrecy::{ExposeSecret, SecretString};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use zeroize::Zeroizing;
+
+use crate::common::{crypto_helper::ProtocolInitializer, entities::Epoch, StdResult};
+
+/// Current version of the format of the files of a [ProtocolInitializerStore].
+///
+/// Version `1`: the protocol initializer is serialized to JSON then, if a passphrase is set,
+/// encrypted with ChaCha20-Poly1305 using a key derived from the passphrase with
+/// PBKDF2-HMAC-SHA256, the epoch being authenticated along the ciphertext.
+pub const PROTOCOL_INITIALIZER_STORE_FORMAT_VERSION: u32 = 1;
+
+/// Number of PBKDF2 iterations used to derive the encryption key of a new file.
+const DEFAULT_KDF_ITERATIONS: u32 = 600_000;
+
+const FILE_PREFIX: &str = "protocol_initializer-";
+const FILE_EXTENSION: &str = "json";
+const SALT_SIZE: usize = 16;
+const NONCE_SIZE: usize = 12;
+
+/// [ProtocolInitializerStore] specific errors
+#[derive(Debug, Error)]
+pub enum ProtocolInitializerStoreError {
+    /// Error raised when a file was written by a newer version of the store.
+    #[error(
+        "unsupported protocol initializer file format version {version}, \
+        latest supported: {PROTOCOL_INITIALIZER_STORE_FORMAT_VERSION}"
+    )]
+    UnsupportedVersion {
+        /// Version of the file format
+        version: u32,
+    },
+
+    /// Error raised when an encrypted file is loaded by a store without passphrase.
+    #[error("protocol initializer of epoch {0} is encrypted but no passphrase was given")]
+    MissingPassphrase(Epoch),
+
+    /// Error raised when a file is not encrypted but the store has a passphrase: it was not
+    /// written by this store and may have been swapped.
+    #[error("protocol initializer of epoch {0} is not encrypted but a passphrase was given")]
+    NotEncrypted(Epoch),
+
+    /// Error raised when a file can't be decrypted: the passphrase is wrong or the file was
+    /// tampered with.
+    #[error(
+        "protocol initializer of epoch {0} can not be decrypted: wrong passphrase or tampered file"
+    )]
+    Decryption(Epoch),
+}
+
+/// Parameters of the encryption of a stored protocol initializer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct EncryptionParameters {
+    /// Number of PBKDF2 iterations
+    kdf_iterations: u32,
+
+    /// Hex encoded salt of the key derivation
+    salt: String,
+
+    /// Hex encoded nonce of the encryption
+    nonce: String,
+}
+
+/// Content of a file of a [ProtocolInitializerStore].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct StoredProtocolInitializer {
+    version: u32,
+    epoch: Epoch,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    encryption: Option<EncryptionParameters>,
+
+    /// Hex encoded JSON of the protocol initializer, encrypted if encryption parameters are set
+    payload: String,
+}
+
+/// File based store of the [protocol initializers][ProtocolInitializer] of a signer, one per
+/// epoch.
+///
+/// The protocol initializer created when a signer registers at an epoch is needed to
+/// [restore its single signer][crate::common::protocol::SignerBuilder::restore_signer_from_initializer]
+/// at a later epoch, it holds the signer secret key: the store can encrypt it with a
+/// passphrase, and the files are only readable by their owner on unix platforms.
+pub struct ProtocolInitializerStore {
+    directory: PathBuf,
+    passphrase: Option<SecretString>,
+    retained_epochs: u64,
+    kdf_iterations: u32,
+}
+
+impl ProtocolInitializerStore {
+    /// Constructs a new `ProtocolInitializerStore` writing its files in the given directory,
+    /// without encryption and keeping the protocol initializers of the 3 last epochs when
+    /// [rotated][Self::rotate].
+    pub fn new(directory: &Path) -> Self {
+        Self {
+            directory: directory.to_path_buf(),
+            passphrase: None,
+            retained_epochs: 3,
+            kdf_iterations: DEFAULT_KDF_ITERATIONS,
+        }
+    }
+
+    /// Encrypt the saved protocol initializers with the given passphrase, and decrypt the
+    /// loaded ones with it.
+    ///
+    /// The store then refuses to load a file that is not encrypted, the files of a store without
+    /// passphrase must be loaded then saved again by a store with a passphrase to be encrypted.
+    pub fn with_passphrase(mut self, passphrase: &str) -> Self {
+        self.passphrase = Some(SecretString::from(passphrase.to_string()));
+        self
+    }
+
+    /// Set the number of epochs, up to the current epoch, which protocol initializers are kept
+    /// when the store is [rotated][Self::rotate].
+    pub fn with_retained_epochs(mut self, retained_epochs: u64) -> Self {
+        self.retained_epochs = retained_epochs;
+        self
+    }
+
+    #[cfg(test)]
+    fn with_kdf_iterations(mut self, kdf_iterations: u32) -> Self {
+        self.kdf_iterations = kdf_iterations;
+        self
+    }
+
+    /// Save the protocol initializer of the given epoch, replacing any previous one.
+    pub fn save(&self, epoch: Epoch, protocol_initializer: &ProtocolInitializer) -> StdResult<()> {
+        let json = Zeroizing::new(
+            serde_json::to_vec(protocol_initializer)
+                .with_context(|| "Could not serialize the protocol initializer")?,
+        );
+        let (encryption, payload) = match &self.passphrase {
+            None => (None, hex::encode(json.as_slice())),
+            Some(passphrase) => {
+                let mut salt = [0u8; SALT_SIZE];
+                let mut nonce = [0u8; NONCE_SIZE];
+                OsRng.fill_bytes(&mut salt);
+                OsRng.fill_bytes(&mut nonce);
+                let cipher = Self::cipher(passphrase, &salt, self.kdf_iterations);
+                let ciphertext = cipher
+                    .encrypt(
+                        Nonce::from_slice(&nonce),
+                        Payload {
+                            msg: &json,
+                            aad: &epoch.to_be_bytes(),
+                        },
+                    )
+                    .map_err(|_| anyhow!("Could not encrypt the protocol initializer"))?;
+                let encryption = EncryptionParameters {
+                    kdf_iterations: self.kdf_iterations,
+                    salt: hex::encode(salt),
+                    nonce: hex::encode(nonce),
+                };
+
+                (Some(encryption), hex::encode(ciphertext))
+            }
+        };
+        let content = serde_json::to_vec(&StoredProtocolInitializer {
+            version: PROTOCOL_INITIALIZER_STORE_FORMAT_VERSION,
+            epoch,
+            encryption,
+            payload,
+        })?;
+
+        std::fs::create_dir_all(&self.directory).with_context(|| {
+            format!("Could not create directory '{}'", self.directory.display())
+        })?;
+        let path = self.file_path(epoch);
+        let temporary_path = path.with_extension("tmp");
+        write_owner_only(&temporary_path, &content)?;
+        std::fs::rename(&temporary_path, &path)
+            .with_context(|| format!("Could not write '{}'", path.display()))
+    }
+
+    /// Load the protocol initializer of the given epoch, `None` if none was saved.
+    pub fn load(&self, epoch: Epoch) -> StdResult<Option<ProtocolInitializer>> {
+        let path = self.file_path(epoch);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content =
+            std::fs::read(&path).with_context(|| format!("Could not read '{}'", path.display()))?;
+        let stored: StoredProtocolInitializer = serde_json::from_slice(&content)
+            .with_context(|| format!("Could not deserialize '{}'", path.display()))?;
+        if stored.version > PROTOCOL_INITIALIZER_STORE_FORMAT_VERSION {
+            return Err(anyhow!(ProtocolInitializerStoreError::UnsupportedVersion {
+                version: stored.version
+            }));
+        }
+        if stored.epoch != epoch {
+            return Err(anyhow!(
+                "File '{}' holds the protocol initializer of epoch {}, expected epoch {epoch}",
+                path.display(),
+                stored.epoch
+            ));
+        }
+
+        let payload = hex::decode(&stored.payload)
+            .with_context(|| format!("Invalid payload in '{}'", path.display()))?;
+        let json = match &stored.encryption {
+            None if self.passphrase.is_some() => {
+                return Err(anyhow!(ProtocolInitializerStoreError::NotEncrypted(epoch)));
+            }
+            None => Zeroizing::new(payload),
+            Some(encryption) => {
+                let passphrase = self
+                    .passphrase
+                    .as_ref()
+                    .ok_or(ProtocolInitializerStoreError::MissingPassphrase(epoch))?;
+                let salt = hex::decode(&encryption.salt)
+                    .with_context(|| format!("Invalid salt in '{}'", path.display()))?;
+                let nonce = hex::decode(&encryption.nonce)
+                    .with_context(|| format!("Invalid nonce in '{}'", path.display()))?;
+                if nonce.len() != NONCE_SIZE {
+                    return Err(anyhow!("Invalid nonce size in '{}'", path.display()));
+                }
+                let cipher = Self::cipher(passphrase, &salt, encryption.kdf_iterations);
+
+                Zeroizing::new(
+                    cipher
+                        .decrypt(
+                            Nonce::from_slice(&nonce),
+                            Payload {
+                                msg: &payload,
+                                aad: &epoch.to_be_bytes(),
+                            },
+                        )
+                        .map_err(|_| ProtocolInitializerStoreError::Decryption(epoch))?,
+                )
+            }
+        };
+        let protocol_initializer = serde_json::from_slice(&json).with_context(|| {
+            format!("Could not deserialize the protocol initializer of epoch {epoch}")
+        })?;
+
+        Ok(Some(protocol_initializer))
+    }
+
+    /// List the epochs which protocol initializers are saved, in ascending order.
+    pub fn epochs(&self) -> StdResult<Vec<Epoch>> {
+        if !self.directory.exists() {
+            return Ok(vec![]);
+        }
+        let mut epochs = vec![];
+        for entry in std::fs::read_dir(&self.directory)
+            .with_context(|| format!("Could not list '{}'", self.directory.display()))?
+        {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some(FILE_EXTENSION) {
+                continue;
+            }
+            let epoch = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| stem.strip_prefix(FILE_PREFIX))
+                .and_then(|epoch| epoch.parse::<u64>().ok());
+            if let Some(epoch) = epoch {
+                epochs.push(Epoch(epoch));
+            }
+        }
+        epochs.sort();
+
+        Ok(epochs)
+    }
+
+    /// Remove the protocol initializers of the epochs before the
+    /// [retained epochs][Self::with_retained_epochs] up to the given current epoch, returns the
+    /// epochs which protocol initializers were removed.
+    pub fn rotate(&self, current_epoch: Epoch) -> StdResult<Vec<Epoch>> {
+        let oldest_retained_epoch =
+            Epoch((current_epoch.0 + 1).saturating_sub(self.retained_epochs));
+        let mut removed_epochs = vec![];
+        for epoch in self.epochs()? {
+            if epoch < oldest_retained_epoch {
+                let path = self.file_path(epoch);
+                std::fs::remove_file(&path)
+                    .with_context(|| format!("Could not remove '{}'", path.display()))?;
+                removed_epochs.push(epoch);
+            }
+        }
+
+        Ok(removed_epochs)
+    }
+
+    fn file_path(&self, epoch: Epoch) -> PathBuf {
+        self.directory
+            .join(format!("{FILE_PREFIX}{epoch}.{FILE_EXTENSION}"))
+    }
+
+    fn cipher(passphrase: &SecretString, salt: &[u8], kdf_iterations: u32) -> ChaCha20Poly1305 {
+        let mut key = Zeroizing::new([0u8; 32]);
+        pbkdf2::pbkdf2_hmac::<Sha256>(
+            passphrase.expose_secret().as_bytes(),
+            salt,
+            kdf_iterations,
+            key.as_mut(),
+        );
+
+        ChaCha20Poly1305::new(Key::from_slice(key.as_ref()))
+    }
+}
+
+#[cfg(unix)]
+fn write_owner_only(path: &Path, content: &[u8]) -> StdResult<()> {
+    use std::io::Write;
+    use std::os::unix::fs::OpenOptionsExt;
+
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)
+        .with_context(|| format!("Could not create '{}'", path.display()))?;
+    file.write_all(content)
+        .with_context(|| format!("Could not write '{}'", path.display()))
+}
+
+#[cfg(not(unix))]
+fn write_owner_only(path: &Path, content: &[u8]) -> StdResult<()> {
+    std::fs::write(path, content).with_context(|| format!("Could not write '{}'", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use rand_chacha::ChaCha20Rng;
+    use rand_core::SeedableRng;
+
+    use crate::common::crypto_helper::ProtocolParameters;
+    use crate::test_utils;
+
+    use super::*;
+
+    fn protocol_initializer(seed: u8) -> ProtocolInitializer {
+        ProtocolInitializer::setup(
+            ProtocolParameters {
+                m: 10,
+                k: 5,
+                phi_f: 0.65,
+            },
+            None::<&Path>,
+            None,
+            100,
+            &mut ChaCha20Rng::from_seed([seed; 32]),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn load_a_saved_protocol_initializer() {
+        let store = ProtocolInitializerStore::new(&test_utils::get_test_directory(
+            "protocol_initializer_store",
+            "load_a_saved_protocol_initializer",
+        ));
+        let protocol_initializer = protocol_initializer(1);
+
+        store.save(Epoch(5), &protocol_initializer).unwrap();
+
+        assert_eq!(
+            protocol_initializer.to_bytes(),
+            store.load(Epoch(5)).unwrap().unwrap().to_bytes()
+        );
+        assert!(store.load(Epoch(6)).unwrap().is_none());
+    }
+
+    #[test]
+    fn load_an_encrypted_protocol_initializer_with_its_passphrase() {
+        let directory = test_utils::get_test_directory(
+            "protocol_initializer_store",
+            "load_an_encrypted_protocol_initializer",
+        );
+        let store = ProtocolInitializerStore::new(&directory)
+            .with_passphrase("passphrase")
+            .with_kdf_iterations(10);
+        let protocol_initializer = protocol_initializer(1);
+
+        store.save(Epoch(5), &protocol_initializer).unwrap();
+
+        let content =
+            std::fs::read_to_string(directory.join("protocol_initializer-5.json")).unwrap();
+        assert!(!content.contains(&hex::encode(
+            serde_json::to_vec(&protocol_initializer).unwrap()
+        )));
+        assert_eq!(
+            protocol_initializer.to_bytes(),
+            store.load(Epoch(5)).unwrap().unwrap().to_bytes()
+        );
+    }
+
+    #[test]
+    fn fail_to_load_an_encrypted_protocol_initializer_without_its_passphrase() {
+        let directory = test_utils::get_test_directory(
+            "protocol_initializer_store",
+            "fail_to_load_without_its_passphrase",
+        );
+        ProtocolInitializerStore::new(&directory)
+            .with_passphrase("passphrase")
+            .with_kdf_iterations(10)
+            .save(Epoch(5), &protocol_initializer(1))
+            .unwrap();
+
+        let error = ProtocolInitializerStore::new(&directory)
+            .load(Epoch(5))
+            .expect_err("Loading without passphrase should fail");
+        assert!(matches!(
+            error.downcast_ref::<ProtocolInitializerStoreError>(),
+            Some(ProtocolInitializerStoreError::MissingPassphrase(Epoch(5)))
+        ));
+
+        let error = ProtocolInitializerStore::new(&directory)
+            .with_passphrase("wrong passphrase")
+            .load(Epoch(5))
+            .expect_err("Loading with a wrong passphrase should fail");
+        assert!(matches!(
+            error.downcast_ref::<ProtocolInitializerStoreError>(),
+            Some(ProtocolInitializerStoreError::Decryption(Epoch(5)))
+        ));
+    }
+
+    #[test]
+    fn fail_to_load_an_unencrypted_protocol_initializer_with_a_passphrase() {
+        let directory = test_utils::get_test_directory(
+            "protocol_initializer_store",
+            "fail_to_load_an_unencrypted_protocol_initializer_with_a_passphrase",
+        );
+        ProtocolInitializerStore::new(&directory)
+            .save(Epoch(5), &protocol_initializer(1))
+            .unwrap();
+
+        let error = ProtocolInitializerStore::new(&directory)
+            .with_passphrase("passphrase")
+            .with_kdf_iterations(10)
+            .load(Epoch(5))
+            .expect_err("Loading an unencrypted file with a passphrase should fail");
+        assert!(matches!(
+            error.downcast_ref::<ProtocolInitializerStoreError>(),
+            Some(ProtocolInitializerStoreError::NotEncrypted(Epoch(5)))
+        ));
+    }
+
+    #[test]
+    fn fail_to_load_a_protocol_initializer_moved_to_another_epoch() {
+        let directory = test_utils::get_test_directory(
+            "protocol_initializer_store",
+            "fail_to_load_a_moved_protocol_initializer",
+        );
+        let store = ProtocolInitializerStore::new(&directory);
+        store.save(Epoch(5), &protocol_initializer(1)).unwrap();
+        std::fs::rename(
+            directory.join("protocol_initializer-5.json"),
+            directory.join("protocol_initializer-6.json"),
+        )
+        .unwrap();
+
+        store
+            .load(Epoch(6))
+            .expect_err("Loading a protocol initializer of another epoch should fail");
+    }
+
+    #[test]
+    fn fail_to_load_a_file_of_a_newer_format_version() {
+        let directory = test_utils::get_test_directory(
+            "protocol_initializer_store",
+            "fail_to_load_a_file_of_a_newer_format_version",
+        );
+        std::fs::create_dir_all(&directory).unwrap();
+        std::fs::write(
+            directory.join("protocol_initializer-5.json"),
+            r#"{"version":99,"epoch":5,"payload":""}"#,
+        )
+        .unwrap();
+
+        let error = ProtocolInitializerStore::new(&directory)
+            .load(Epoch(5))
+            .expect_err("Loading a file of a newer version should fail");
+        assert!(matches!(
+            error.downcast_ref::<ProtocolInitializerStoreError>(),
+            Some(ProtocolInitializerStoreError::UnsupportedVersion { version: 99 })
+        ));
+    }
+
+    #[test]
+    fn rotate_remove_the_protocol_initializers_older_than_the_retained_epochs() {
+        let store = ProtocolInitializerStore::new(&test_utils::get_test_directory(
+            "protocol_initializer_store",
+            "rotate_remove_the_protocol_initializers_older_than_the_retained_epochs",
+        ))
+        .with_retained_epochs(2);
+        for epoch in 1..=5 {
+            store.save(Epoch(epoch), &protocol_initializer(1)).unwrap();
+        }
+
+        let removed_epochs = store.rotate(Epoch(5)).unwrap();
+
+        assert_eq!(vec![Epoch(1), Epoch(2), Epoch(3)], removed_epochs);
+        assert_eq!(vec![Epoch(4), Epoch(5)], store.epochs().unwrap());
+    }
+}
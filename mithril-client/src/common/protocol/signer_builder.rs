@@ -1,26 +1,37 @@
-use anyhow::Context;
-use rand_chacha::ChaCha20Rng;
-use rand_core::{CryptoRng, RngCore, SeedableRng};
-use std::path::Path;
+use anyhow::{anyhow, Context};
 use thiserror::Error;
 
+#[cfg(any(test, feature = "signer_tools"))]
+use crate::common::{
+    crypto_helper::{
+        KesSigner, KesSignerStandard, ProtocolInitializer, StmInitializerSetupOptions,
+    },
+    entities::PartyId,
+    protocol::SingleSigner,
+};
 use crate::common::{
     crypto_helper::{
         ProtocolAggregateVerificationKey, ProtocolClerk, ProtocolClosedKeyRegistration,
-        ProtocolInitializer, ProtocolKeyRegistration, ProtocolStakeDistribution,
+        ProtocolKeyRegistration, ProtocolStakeDistribution,
     },
-    entities::{PartyId, ProtocolParameters, SignerWithStake},
+    entities::{ProtocolParameters, SignerWithStake},
     protocol::MultiSigner,
     StdResult,
 };
-
-use super::SingleSigner;
+#[cfg(any(test, feature = "signer_tools"))]
+use rand_chacha::ChaCha20Rng;
+#[cfg(any(test, feature = "signer_tools"))]
+use rand_core::{CryptoRng, RngCore, SeedableRng};
+#[cfg(any(test, feature = "signer_tools"))]
+use std::path::Path;
 
 /// Allow to build Single Or Multi signers to generate a single signature or aggregate them
 #[derive(Debug)]
 pub struct SignerBuilder {
     protocol_parameters: ProtocolParameters,
     closed_key_registration: ProtocolClosedKeyRegistration,
+    #[cfg(any(test, feature = "signer_tools"))]
+    initializer_setup_options: StmInitializerSetupOptions,
 }
 
 /// [SignerBuilder] specific errors
@@ -48,17 +59,7 @@ impl SignerBuilder {
         let mut key_registration = ProtocolKeyRegistration::init(&stake_distribution);
 
         for signer in registered_signers {
-            key_registration
-                .register(
-                    Some(signer.party_id.to_owned()),
-                    signer.operational_certificate.clone(),
-                    signer.verification_key_signature,
-                    signer.kes_period,
-                    signer.verification_key,
-                )
-                .with_context(|| {
-                    format!("Registration failed for signer: '{}'", signer.party_id)
-                })?;
+            register_signer(&mut key_registration, signer)?;
         }
 
         let closed_registration = key_registration.close();
@@ -66,9 +67,23 @@ impl SignerBuilder {
         Ok(Self {
             protocol_parameters: protocol_parameters.clone(),
             closed_key_registration: closed_registration,
+            #[cfg(any(test, feature = "signer_tools"))]
+            initializer_setup_options: StmInitializerSetupOptions::default(),
         })
     }
 
+    /// Set the [StmInitializerSetupOptions] used to set up the protocol initializers of the
+    /// single signers, ie: to log their warnings or refuse non certified registrations.
+    #[cfg(any(test, feature = "signer_tools"))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "signer_tools")))]
+    pub fn with_initializer_setup_options(
+        mut self,
+        initializer_setup_options: StmInitializerSetupOptions,
+    ) -> Self {
+        self.initializer_setup_options = initializer_setup_options;
+        self
+    }
+
     /// Build a [MultiSigner] based on the registered parties
     pub fn build_multi_signer(&self) -> MultiSigner {
         let stm_parameters = self.protocol_parameters.clone().into();
@@ -87,18 +102,20 @@ impl SignerBuilder {
         clerk.compute_avk().into()
     }
 
+    #[cfg(any(test, feature = "signer_tools"))]
     fn build_single_signer_with_rng<R: RngCore + CryptoRng>(
         &self,
         signer_with_stake: SignerWithStake,
-        kes_secret_key_path: Option<&Path>,
+        kes_signer: Option<&dyn KesSigner>,
         rng: &mut R,
     ) -> StdResult<(SingleSigner, ProtocolInitializer)> {
-        let protocol_initializer = ProtocolInitializer::setup(
+        let protocol_initializer = ProtocolInitializer::setup_with_kes_signer(
             self.protocol_parameters.clone().into(),
-            kes_secret_key_path,
+            kes_signer,
             signer_with_stake.kes_period,
             signer_with_stake.stake,
             rng,
+            &self.initializer_setup_options,
         )
         .with_context(|| {
             format!(
@@ -107,8 +124,9 @@ impl SignerBuilder {
             )
         })?;
 
+        // The signer consumes its initializer while the initializer is also returned to be stored
         let protocol_signer = protocol_initializer
-            .clone()
+            .duplicate_secret()
             .new_signer(self.closed_key_registration.clone())
             .with_context(|| {
                 format!(
@@ -124,23 +142,38 @@ impl SignerBuilder {
     }
 
     /// Build non deterministic [SingleSigner] and [ProtocolInitializer] based on the registered parties.
-    #[cfg(feature = "random")]
-    #[cfg_attr(docsrs, doc(cfg(feature = "random")))]
+    #[cfg(all(feature = "random", feature = "signer_tools"))]
+    #[cfg_attr(docsrs, doc(cfg(all(feature = "random", feature = "signer_tools"))))]
     pub fn build_single_signer(
         &self,
         signer_with_stake: SignerWithStake,
         kes_secret_key_path: Option<&Path>,
     ) -> StdResult<(SingleSigner, ProtocolInitializer)> {
-        self.build_single_signer_with_rng(
+        let kes_signer = kes_secret_key_path.map(KesSignerStandard::new);
+
+        self.build_single_signer_with_kes_signer(
             signer_with_stake,
-            kes_secret_key_path,
-            &mut rand_core::OsRng,
+            kes_signer.as_ref().map(|s| s as &dyn KesSigner),
         )
     }
 
+    /// Build non deterministic [SingleSigner] and [ProtocolInitializer] based on the registered
+    /// parties, the verification key being signed by the given [KesSigner].
+    #[cfg(all(feature = "random", feature = "signer_tools"))]
+    #[cfg_attr(docsrs, doc(cfg(all(feature = "random", feature = "signer_tools"))))]
+    pub fn build_single_signer_with_kes_signer(
+        &self,
+        signer_with_stake: SignerWithStake,
+        kes_signer: Option<&dyn KesSigner>,
+    ) -> StdResult<(SingleSigner, ProtocolInitializer)> {
+        self.build_single_signer_with_rng(signer_with_stake, kes_signer, &mut rand_core::OsRng)
+    }
+
     /// Build deterministic [SingleSigner] and [ProtocolInitializer] based on the registered parties.
     ///
     /// Use for **TEST ONLY**.
+    #[cfg(any(test, feature = "signer_tools"))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "signer_tools")))]
     pub fn build_test_single_signer(
         &self,
         signer_with_stake: SignerWithStake,
@@ -150,9 +183,11 @@ impl SignerBuilder {
             .try_into()
             .unwrap();
 
+        let kes_signer = kes_secret_key_path.map(KesSignerStandard::new);
+
         self.build_single_signer_with_rng(
             signer_with_stake,
-            kes_secret_key_path,
+            kes_signer.as_ref().map(|s| s as &dyn KesSigner),
             &mut ChaCha20Rng::from_seed(protocol_initializer_seed),
         )
     }
@@ -168,6 +203,8 @@ impl SignerBuilder {
     /// The [SignerBuilder] used must be tied to the key registration, stake distribution
     /// and protocol parameters of the epoch during which the given protocol initializer
     /// was created.
+    #[cfg(any(test, feature = "signer_tools"))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "signer_tools")))]
     pub fn restore_signer_from_initializer(
         &self,
         party_id: PartyId,
@@ -182,3 +219,127 @@ impl SignerBuilder {
         Ok(SingleSigner::new(party_id, single_signer))
     }
 }
+
+fn register_signer(
+    key_registration: &mut ProtocolKeyRegistration,
+    signer: &SignerWithStake,
+) -> StdResult<()> {
+    key_registration
+        .register(
+            Some(signer.party_id.to_owned()),
+            signer.operational_certificate.clone(),
+            signer.verification_key_signature,
+            signer.kes_period,
+            signer.verification_key,
+        )
+        .with_context(|| format!("Registration failed for signer: '{}'", signer.party_id))?;
+
+    Ok(())
+}
+
+/// Progress of an [AggregateVerificationKeyComputation].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AggregateVerificationKeyProgress {
+    /// Number of signers registered so far
+    pub registered_signers: usize,
+
+    /// Number of signers of the stake distribution
+    pub total_signers: usize,
+}
+
+/// Incremental computation of the aggregate verification key of a stake distribution.
+///
+/// Unlike [SignerBuilder::compute_aggregate_verification_key], the signers are registered one at
+/// a time so the caller can report progress: registering a signer checks its operational
+/// certificate and KES signature, which is where most of the time goes for large distributions.
+///
+/// # Memory
+///
+/// The computation keeps the stake distribution and the verification key of each registered
+/// signer, a few hundred bytes per signer: the [SignerWithStake] given to
+/// [register_signer][AggregateVerificationKeyComputation::register_signer] can be dropped, or
+/// streamed, as soon as it is registered. [finish][AggregateVerificationKeyComputation::finish]
+/// then builds a Merkle tree with one leaf per signer, so the peak memory usage is linear in the
+/// number of signers.
+#[derive(Debug)]
+pub struct AggregateVerificationKeyComputation {
+    protocol_parameters: ProtocolParameters,
+    key_registration: ProtocolKeyRegistration,
+    progress: AggregateVerificationKeyProgress,
+}
+
+impl AggregateVerificationKeyComputation {
+    /// Start the computation of the aggregate verification key of the given stake distribution.
+    pub fn new(
+        stake_distribution: &ProtocolStakeDistribution,
+        protocol_parameters: &ProtocolParameters,
+    ) -> StdResult<Self> {
+        if stake_distribution.is_empty() {
+            return Err(SignerBuilderError::EmptySigners.into());
+        }
+
+        Ok(Self {
+            protocol_parameters: protocol_parameters.clone(),
+            key_registration: ProtocolKeyRegistration::init(stake_distribution),
+            progress: AggregateVerificationKeyProgress {
+                registered_signers: 0,
+                total_signers: stake_distribution.len(),
+            },
+        })
+    }
+
+    /// Register a signer of the stake distribution and return the updated progress.
+    pub fn register_signer(
+        &mut self,
+        signer: &SignerWithStake,
+    ) -> StdResult<AggregateVerificationKeyProgress> {
+        register_signer(&mut self.key_registration, signer)?;
+        self.progress.registered_signers += 1;
+
+        Ok(self.progress)
+    }
+
+    /// Current progress of the computation.
+    pub fn progress(&self) -> AggregateVerificationKeyProgress {
+        self.progress
+    }
+
+    /// Compute the aggregate verification key, fails if some signers of the stake distribution
+    /// were not registered.
+    pub fn finish(self) -> StdResult<ProtocolAggregateVerificationKey> {
+        let AggregateVerificationKeyProgress {
+            registered_signers,
+            total_signers,
+        } = self.progress;
+        if registered_signers != total_signers {
+            return Err(anyhow!(
+                "Only {registered_signers} of the {total_signers} signers of the stake distribution were registered"
+            ));
+        }
+
+        let stm_parameters = self.protocol_parameters.into();
+        let closed_key_registration: ProtocolClosedKeyRegistration = self.key_registration.close();
+        let clerk = ProtocolClerk::from_registration(&stm_parameters, &closed_key_registration);
+
+        Ok(clerk.compute_avk().into())
+    }
+
+    /// Compute the aggregate verification key of the given signers, calling `on_progress` after
+    /// each registration.
+    pub fn compute<F: FnMut(AggregateVerificationKeyProgress)>(
+        signers: &[SignerWithStake],
+        protocol_parameters: &ProtocolParameters,
+        mut on_progress: F,
+    ) -> StdResult<ProtocolAggregateVerificationKey> {
+        let stake_distribution = signers
+            .iter()
+            .map(|s| s.into())
+            .collect::<ProtocolStakeDistribution>();
+        let mut computation = Self::new(&stake_distribution, protocol_parameters)?;
+        for signer in signers {
+            on_progress(computation.register_signer(signer)?);
+        }
+
+        computation.finish()
+    }
+}
@@ -4,6 +4,45 @@ use crate::common::{
     StdResult,
 };
 
+/// Single signatures issued by a [SingleSigner] for a batch of messages, see
+/// [SingleSigner::sign_batch].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SingleSignaturesBatch {
+    /// Single signature of each message, in the order of the signed messages, or `None` if no
+    /// lottery was won for the message
+    pub signatures: Vec<Option<SingleSignatures>>,
+}
+
+impl SingleSignaturesBatch {
+    /// Number of messages in the batch
+    pub fn len(&self) -> usize {
+        self.signatures.len()
+    }
+
+    /// Check if the batch contains no message
+    pub fn is_empty(&self) -> bool {
+        self.signatures.is_empty()
+    }
+
+    /// Number of messages for which at least one lottery was won
+    pub fn signed_messages(&self) -> usize {
+        self.signatures.iter().flatten().count()
+    }
+
+    /// Number of lotteries won for each message, in the order of the signed messages
+    pub fn won_lotteries_per_message(&self) -> Vec<usize> {
+        self.signatures
+            .iter()
+            .map(|signature| signature.as_ref().map_or(0, |s| s.won_indexes.len()))
+            .collect()
+    }
+
+    /// Total number of lotteries won over all the messages of the batch
+    pub fn total_won_lotteries(&self) -> usize {
+        self.won_lotteries_per_message().iter().sum()
+    }
+}
+
 /// The SingleSigner is the structure responsible for issuing SingleSignatures.
 #[cfg_attr(test, derive(Debug))]
 pub struct SingleSigner {
@@ -37,6 +76,20 @@ impl SingleSigner {
         }
     }
 
+    /// Issue a single signature for each of the given messages, ie: the messages of several
+    /// signed entity types of the same epoch.
+    ///
+    /// The messages are signed with the same signer state, the returned batch keeps the order of
+    /// the given messages.
+    pub fn sign_batch(&self, messages: &[ProtocolMessage]) -> StdResult<SingleSignaturesBatch> {
+        let signatures = messages
+            .iter()
+            .map(|message| self.sign(message))
+            .collect::<StdResult<Vec<_>>>()?;
+
+        Ok(SingleSignaturesBatch { signatures })
+    }
+
     /// Return the partyId associated with this Signer.
     pub fn get_party_id(&self) -> PartyId {
         self.party_id.clone()
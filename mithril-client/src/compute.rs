@@ -0,0 +1,190 @@
+//! Offloading of the CPU heavy computations out of the async executor.
+//!
+//! Verifying the multi-signatures of a certificate chain, computing the aggregate verification
+//! key of a stake distribution or the digest of a snapshot keeps a thread busy for a while, when
+//! the client is embedded in an application running other tasks on the same async executor
+//! these tasks would be stalled until the computation completes.
+//!
+//! A [ComputeExecutor] backed by a dedicated thread pool runs those computations out of the
+//! executor threads, set it using
+//! [ClientBuilder::with_compute_threads][crate::ClientBuilder::with_compute_threads] (requires
+//! the crate feature **compute_threads**).
+//!
+//! **Note:** without a thread pool the digest computation of a snapshot is offloaded to the
+//! blocking threads of the [tokio] runtime.
+//!
+//! # Verify certificate chains on two dedicated threads
+//!
+//! ```no_run
+//! # async fn run() -> mithril_client::MithrilResult<()> {
+//! use mithril_client::ClientBuilder;
+//!
+//! let client = ClientBuilder::aggregator("YOUR_AGGREGATOR_ENDPOINT", "YOUR_GENESIS_VERIFICATION_KEY")
+//!     .with_compute_threads(2)
+//!     .build()?;
+//!
+//! let certificate = client.certificate().verify_chain("CERTIFICATE_HASH").await?;
+//! #    Ok(())
+//! # }
+//! ```
+
+#[cfg(feature = "compute_threads")]
+use anyhow::{anyhow, Context};
+#[cfg(feature = "compute_threads")]
+use std::panic::AssertUnwindSafe;
+#[cfg(feature = "compute_threads")]
+use std::sync::Arc;
+
+use crate::MithrilResult;
+
+/// Run the CPU heavy computations of the client.
+#[derive(Debug, Clone, Default)]
+pub enum ComputeExecutor {
+    /// Run the computations on the calling task (default).
+    #[default]
+    Inline,
+
+    /// Run the computations on a dedicated thread pool.
+    #[cfg(feature = "compute_threads")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "compute_threads")))]
+    ThreadPool(Arc<rayon::ThreadPool>),
+}
+
+impl ComputeExecutor {
+    /// Constructs a `ComputeExecutor` backed by a new thread pool of the given number of threads.
+    #[cfg(feature = "compute_threads")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "compute_threads")))]
+    pub fn thread_pool(threads: usize) -> MithrilResult<Self> {
+        if threads == 0 {
+            return Err(anyhow!("A compute thread pool needs at least one thread"));
+        }
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .thread_name(|index| format!("mithril-compute-{index}"))
+            .build()
+            .with_context(|| "Could not build the compute thread pool")?;
+
+        Ok(Self::ThreadPool(Arc::new(pool)))
+    }
+
+    /// Run the given computation and return its result.
+    ///
+    /// A computation that panics on the thread pool is reported as an error.
+    pub async fn run<T, F>(&self, computation: F) -> MithrilResult<T>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        match self {
+            Self::Inline => Ok(computation()),
+            #[cfg(feature = "compute_threads")]
+            Self::ThreadPool(pool) => Self::run_on_pool(pool, computation).await,
+        }
+    }
+
+    /// Run the given computation, that may block on IO, and return its result.
+    ///
+    /// Unlike [run][Self::run] the computation is never run on the calling task: it runs on the
+    /// thread pool, or on the blocking threads of the [tokio] runtime if the executor runs the
+    /// computations inline.
+    #[cfg(feature = "digesters")]
+    pub async fn run_blocking<T, F>(&self, computation: F) -> MithrilResult<T>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        match self {
+            Self::Inline => Ok(tokio::task::spawn_blocking(computation).await?),
+            #[cfg(feature = "compute_threads")]
+            Self::ThreadPool(pool) => Self::run_on_pool(pool, computation).await,
+        }
+    }
+
+    #[cfg(feature = "compute_threads")]
+    async fn run_on_pool<T, F>(pool: &rayon::ThreadPool, computation: F) -> MithrilResult<T>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let (sender, receiver) = tokio::sync::oneshot::channel();
+        pool.spawn(move || {
+            let result = std::panic::catch_unwind(AssertUnwindSafe(computation));
+            let _ = sender.send(result);
+        });
+
+        receiver
+            .await
+            .with_context(|| "Compute thread pool dropped the computation")?
+            .map_err(|_| anyhow!("Computation panicked on the compute thread pool"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn run_inline_on_the_calling_thread() {
+        let executor = ComputeExecutor::default();
+        let calling_thread = std::thread::current().id();
+
+        let thread = executor.run(|| std::thread::current().id()).await.unwrap();
+
+        assert_eq!(calling_thread, thread);
+    }
+
+    #[cfg(feature = "digesters")]
+    #[tokio::test]
+    async fn run_blocking_inline_on_a_blocking_thread() {
+        let executor = ComputeExecutor::default();
+        let calling_thread = std::thread::current().id();
+
+        let thread = executor
+            .run_blocking(|| std::thread::current().id())
+            .await
+            .unwrap();
+
+        assert_ne!(calling_thread, thread);
+    }
+
+    #[cfg(feature = "compute_threads")]
+    #[tokio::test]
+    async fn run_on_the_thread_pool() {
+        let executor = ComputeExecutor::thread_pool(2).unwrap();
+
+        let thread_index = executor.run(rayon::current_thread_index).await.unwrap();
+
+        assert!(thread_index.is_some());
+    }
+
+    #[cfg(all(feature = "compute_threads", feature = "digesters"))]
+    #[tokio::test]
+    async fn run_blocking_on_the_thread_pool() {
+        let executor = ComputeExecutor::thread_pool(2).unwrap();
+
+        let thread_index = executor
+            .run_blocking(rayon::current_thread_index)
+            .await
+            .unwrap();
+
+        assert!(thread_index.is_some());
+    }
+
+    #[cfg(feature = "compute_threads")]
+    #[tokio::test]
+    async fn computation_panicking_on_the_thread_pool_fails() {
+        let executor = ComputeExecutor::thread_pool(1).unwrap();
+
+        executor
+            .run(|| panic!("computation failure"))
+            .await
+            .map(|_: ()| ())
+            .expect_err("a panicking computation should fail");
+    }
+
+    #[cfg(feature = "compute_threads")]
+    #[test]
+    fn thread_pool_without_threads_fails() {
+        ComputeExecutor::thread_pool(0).expect_err("a thread pool without threads should fail");
+    }
+}
@@ -0,0 +1,371 @@
+//! Loading of the client settings from a configuration file or from environment variables.
+//!
+//! A [ClientConfig] holds the settings commonly exposed by the programs embedding the client,
+//! it can be read from a TOML or JSON file (chosen by the file extension) or from the
+//! environment, then turned into a [ClientBuilder] which can still be customized before building
+//! the client:
+//!
+//! | Setting                    | Environment variable                | Builder method                                        |
+//! |----------------------------|-------------------------------------|-------------------------------------------------------|
+//! | `aggregator_endpoint`      | `MITHRIL_AGGREGATOR_ENDPOINT`       | [with_aggregator_endpoint][ClientBuilder::with_aggregator_endpoint] |
+//! | `genesis_verification_key` | `MITHRIL_GENESIS_VERIFICATION_KEY`  | [with_genesis_verification_key][ClientBuilder::with_genesis_verification_key] |
+//! | `request_timeout_ms`       | `MITHRIL_REQUEST_TIMEOUT_MS`        | [with_request_timeout][ClientBuilder::with_request_timeout] |
+//! | `retry_max_attempts`       | `MITHRIL_RETRY_MAX_ATTEMPTS`        | [with_retry_policy][ClientBuilder::with_retry_policy] |
+//! | `retry_initial_delay_ms`   | `MITHRIL_RETRY_INITIAL_DELAY_MS`    | [with_retry_policy][ClientBuilder::with_retry_policy] |
+//! | `strict_schema_validation` | `MITHRIL_STRICT_SCHEMA_VALIDATION`  | [with_strict_schema_validation][ClientBuilder::with_strict_schema_validation] |
+//! | `compute_threads`          | `MITHRIL_COMPUTE_THREADS`           | [with_compute_threads][ClientBuilder::with_compute_threads] |
+//! | `proxy_url`                | `MITHRIL_PROXY_URL`                 | [with_proxy][ClientBuilder::with_proxy]               |
+//! | `no_proxy`                 | `MITHRIL_NO_PROXY`                  | [with_proxy][ClientBuilder::with_proxy], comma separated hosts |
+//! | `download_directory`       | `MITHRIL_DOWNLOAD_DIRECTORY`        | _none, read it from the [ClientConfig]_               |
+//!
+//! Only the genesis verification key is mandatory, the `compute_threads` setting requires the
+//! crate feature **compute_threads**.
+//!
+//! **Note:** _Available using crate feature_ **config**.
+//!
+//! # Build a client from a configuration file
+//!
+//! ```no_run
+//! # async fn run() -> mithril_client::MithrilResult<()> {
+//! use mithril_client::ClientBuilder;
+//!
+//! // mithril.toml:
+//! // aggregator_endpoint = "YOUR_AGGREGATOR_ENDPOINT"
+//! // genesis_verification_key = "YOUR_GENESIS_VERIFICATION_KEY"
+//! // request_timeout_ms = 30000
+//! // retry_max_attempts = 5
+//! let client = ClientBuilder::from_config_file("mithril.toml")?.build()?;
+//!
+//! let snapshots = client.snapshot().list().await?;
+//! #    Ok(())
+//! # }
+//! ```
+//!
+//! # Merge a configuration file with the environment
+//!
+//! ```no_run
+//! # async fn run() -> mithril_client::MithrilResult<()> {
+//! use mithril_client::config::ClientConfig;
+//!
+//! let config = ClientConfig::from_file("mithril.json")?.merge(ClientConfig::from_env()?);
+//! let download_directory = config.download_directory.clone();
+//! let client = config.into_builder()?.build()?;
+//! #    Ok(())
+//! # }
+//! ```
+
+use anyhow::{anyhow, Context};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+#[cfg(not(target_family = "wasm"))]
+use crate::proxy::ProxyConfig;
+use crate::retry::RetryPolicy;
+use crate::{ClientBuilder, MithrilResult};
+
+const ENV_PREFIX: &str = "MITHRIL_";
+
+/// Settings of a client, see [config][crate::config].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ClientConfig {
+    /// Endpoint of the aggregator
+    pub aggregator_endpoint: Option<String>,
+
+    /// Genesis verification key of the certificate chain
+    pub genesis_verification_key: Option<String>,
+
+    /// Timeout of the requests to the aggregator, in milliseconds
+    pub request_timeout_ms: Option<u64>,
+
+    /// Maximum number of attempts of a request to the aggregator
+    pub retry_max_attempts: Option<u32>,
+
+    /// Delay before the first retry of a request to the aggregator, in milliseconds
+    pub retry_initial_delay_ms: Option<u64>,
+
+    /// Validate the aggregator responses against their Open API schemas
+    pub strict_schema_validation: Option<bool>,
+
+    /// Number of threads dedicated to the CPU heavy computations, requires the crate feature
+    /// **compute_threads**
+    pub compute_threads: Option<usize>,
+
+    /// Url of the proxy to reach the network through, credentials can be embedded in it
+    pub proxy_url: Option<String>,
+
+    /// Comma separated hosts that are reached without the proxy
+    pub no_proxy: Option<String>,
+
+    /// Directory where the snapshots are downloaded
+    pub download_directory: Option<PathBuf>,
+}
+
+impl ClientConfig {
+    /// Read the settings from a TOML (`.toml` extension) or JSON (any other extension) file.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> MithrilResult<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Could not read config file '{}'", path.display()))?;
+
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("toml") => toml::from_str(&content)
+                .with_context(|| format!("Could not parse TOML config file '{}'", path.display())),
+            _ => serde_json::from_str(&content)
+                .with_context(|| format!("Could not parse JSON config file '{}'", path.display())),
+        }
+    }
+
+    /// Read the settings from the `MITHRIL_` prefixed environment variables.
+    pub fn from_env() -> MithrilResult<Self> {
+        Self::from_vars(|name| std::env::var(name).ok())
+    }
+
+    fn from_vars<F: Fn(&str) -> Option<String>>(var: F) -> MithrilResult<Self> {
+        fn parse<T: FromStr, F: Fn(&str) -> Option<String>>(
+            var: &F,
+            setting: &str,
+        ) -> MithrilResult<Option<T>>
+        where
+            T::Err: std::error::Error + Send + Sync + 'static,
+        {
+            let name = format!("{ENV_PREFIX}{}", setting.to_uppercase());
+            var(&name)
+                .map(|value| value.parse::<T>())
+                .transpose()
+                .with_context(|| format!("Invalid value for environment variable '{name}'"))
+        }
+
+        Ok(Self {
+            aggregator_endpoint: parse(&var, "aggregator_endpoint")?,
+            genesis_verification_key: parse(&var, "genesis_verification_key")?,
+            request_timeout_ms: parse(&var, "request_timeout_ms")?,
+            retry_max_attempts: parse(&var, "retry_max_attempts")?,
+            retry_initial_delay_ms: parse(&var, "retry_initial_delay_ms")?,
+            strict_schema_validation: parse(&var, "strict_schema_validation")?,
+            compute_threads: parse(&var, "compute_threads")?,
+            proxy_url: parse(&var, "proxy_url")?,
+            no_proxy: parse(&var, "no_proxy")?,
+            download_directory: parse(&var, "download_directory")?,
+        })
+    }
+
+    /// Merge the given settings into these settings, the given ones take precedence.
+    pub fn merge(self, other: ClientConfig) -> Self {
+        Self {
+            aggregator_endpoint: other.aggregator_endpoint.or(self.aggregator_endpoint),
+            genesis_verification_key: other
+                .genesis_verification_key
+                .or(self.genesis_verification_key),
+            request_timeout_ms: other.request_timeout_ms.or(self.request_timeout_ms),
+            retry_max_attempts: other.retry_max_attempts.or(self.retry_max_attempts),
+            retry_initial_delay_ms: other.retry_initial_delay_ms.or(self.retry_initial_delay_ms),
+            strict_schema_validation: other
+                .strict_schema_validation
+                .or(self.strict_schema_validation),
+            compute_threads: other.compute_threads.or(self.compute_threads),
+            proxy_url: other.proxy_url.or(self.proxy_url),
+            no_proxy: other.no_proxy.or(self.no_proxy),
+            download_directory: other.download_directory.or(self.download_directory),
+        }
+    }
+
+    /// Returns a [ClientBuilder] configured with these settings.
+    pub fn into_builder(self) -> MithrilResult<ClientBuilder> {
+        let genesis_verification_key = self.genesis_verification_key.ok_or(anyhow!(
+            "No genesis verification key set in the configuration"
+        ))?;
+        let mut builder = ClientBuilder::new(&genesis_verification_key);
+
+        if let Some(endpoint) = &self.aggregator_endpoint {
+            builder = builder.with_aggregator_endpoint(endpoint);
+        }
+        if let Some(request_timeout_ms) = self.request_timeout_ms {
+            builder = builder.with_request_timeout(Duration::from_millis(request_timeout_ms));
+        }
+        if self.retry_max_attempts.is_some() || self.retry_initial_delay_ms.is_some() {
+            let default_policy = RetryPolicy::default();
+            builder = builder.with_retry_policy(RetryPolicy::new(
+                self.retry_max_attempts
+                    .unwrap_or(default_policy.max_attempts),
+                self.retry_initial_delay_ms
+                    .map(Duration::from_millis)
+                    .unwrap_or(default_policy.initial_delay),
+            ));
+        }
+        if let Some(enabled) = self.strict_schema_validation {
+            builder = builder.with_strict_schema_validation(enabled);
+        }
+        if let Some(threads) = self.compute_threads {
+            #[cfg(feature = "compute_threads")]
+            {
+                builder = builder.with_compute_threads(threads);
+            }
+            #[cfg(not(feature = "compute_threads"))]
+            return Err(anyhow!(
+                "Setting {threads} compute threads requires the crate feature 'compute_threads'"
+            ));
+        }
+        #[cfg(not(target_family = "wasm"))]
+        if let Some(proxy_url) = &self.proxy_url {
+            let no_proxy = self.no_proxy.as_deref().unwrap_or_default();
+            builder = builder.with_proxy(
+                ProxyConfig::new(proxy_url).with_no_proxy(
+                    no_proxy
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|host| !host.is_empty()),
+                ),
+            );
+        }
+
+        Ok(builder)
+    }
+}
+
+impl ClientBuilder {
+    /// Constructs a new `ClientBuilder` configured with the `MITHRIL_` prefixed environment
+    /// variables, see [config][crate::config].
+    #[cfg_attr(docsrs, doc(cfg(feature = "config")))]
+    pub fn from_env() -> MithrilResult<ClientBuilder> {
+        ClientConfig::from_env()?.into_builder()
+    }
+
+    /// Constructs a new `ClientBuilder` configured with the given TOML or JSON file, see
+    /// [config][crate::config].
+    #[cfg_attr(docsrs, doc(cfg(feature = "config")))]
+    pub fn from_config_file<P: AsRef<Path>>(path: P) -> MithrilResult<ClientBuilder> {
+        ClientConfig::from_file(path)?.into_builder()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::test_utils;
+
+    use super::*;
+
+    #[test]
+    fn read_toml_and_json_config_files() {
+        let dir = test_utils::get_test_directory("config", "read_toml_and_json_config_files");
+        let toml_file = dir.join("mithril.toml");
+        std::fs::write(
+            &toml_file,
+            r#"
+aggregator_endpoint = "http://aggregator"
+genesis_verification_key = "genesis_key"
+retry_max_attempts = 5
+download_directory = "/tmp/db"
+"#,
+        )
+        .unwrap();
+        let json_file = dir.join("mithril.json");
+        std::fs::write(
+            &json_file,
+            r#"{"genesis_verification_key": "genesis_key", "request_timeout_ms": 3000}"#,
+        )
+        .unwrap();
+
+        let toml_config = ClientConfig::from_file(toml_file).unwrap();
+        let json_config = ClientConfig::from_file(json_file).unwrap();
+
+        assert_eq!(
+            ClientConfig {
+                aggregator_endpoint: Some("http://aggregator".to_string()),
+                genesis_verification_key: Some("genesis_key".to_string()),
+                retry_max_attempts: Some(5),
+                download_directory: Some(PathBuf::from("/tmp/db")),
+                ..ClientConfig::default()
+            },
+            toml_config
+        );
+        assert_eq!(
+            ClientConfig {
+                genesis_verification_key: Some("genesis_key".to_string()),
+                request_timeout_ms: Some(3000),
+                ..ClientConfig::default()
+            },
+            json_config
+        );
+    }
+
+    #[test]
+    fn config_file_with_an_unknown_setting_fails() {
+        let dir =
+            test_utils::get_test_directory("config", "config_file_with_an_unknown_setting_fails");
+        let file = dir.join("mithril.json");
+        std::fs::write(&file, r#"{"aggregator_url": "http://aggregator"}"#).unwrap();
+
+        ClientConfig::from_file(file).expect_err("an unknown setting should be rejected");
+    }
+
+    #[test]
+    fn read_config_from_environment_variables() {
+        let vars = HashMap::from([
+            ("MITHRIL_GENESIS_VERIFICATION_KEY", "genesis_key"),
+            ("MITHRIL_STRICT_SCHEMA_VALIDATION", "true"),
+            ("MITHRIL_COMPUTE_THREADS", "2"),
+        ]);
+
+        let config = ClientConfig::from_vars(|name| vars.get(name).map(|v| v.to_string())).unwrap();
+
+        assert_eq!(
+            ClientConfig {
+                genesis_verification_key: Some("genesis_key".to_string()),
+                strict_schema_validation: Some(true),
+                compute_threads: Some(2),
+                ..ClientConfig::default()
+            },
+            config
+        );
+    }
+
+    #[test]
+    fn invalid_environment_variable_fails() {
+        let error = ClientConfig::from_vars(|name| {
+            (name == "MITHRIL_REQUEST_TIMEOUT_MS").then(|| "ten seconds".to_string())
+        })
+        .expect_err("an invalid timeout should be rejected");
+
+        assert!(
+            error.to_string().contains("MITHRIL_REQUEST_TIMEOUT_MS"),
+            "Unexpected error: {error:?}"
+        );
+    }
+
+    #[test]
+    fn merged_settings_take_precedence() {
+        let file_config = ClientConfig {
+            aggregator_endpoint: Some("http://file-aggregator".to_string()),
+            genesis_verification_key: Some("genesis_key".to_string()),
+            ..ClientConfig::default()
+        };
+        let env_config = ClientConfig {
+            aggregator_endpoint: Some("http://env-aggregator".to_string()),
+            ..ClientConfig::default()
+        };
+
+        let config = file_config.merge(env_config);
+
+        assert_eq!(
+            ClientConfig {
+                aggregator_endpoint: Some("http://env-aggregator".to_string()),
+                genesis_verification_key: Some("genesis_key".to_string()),
+                ..ClientConfig::default()
+            },
+            config
+        );
+    }
+
+    #[test]
+    fn into_builder_without_genesis_verification_key_fails() {
+        ClientConfig::default()
+            .into_builder()
+            .map(|_| ())
+            .expect_err("a genesis verification key is mandatory");
+    }
+}
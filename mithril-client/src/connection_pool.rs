@@ -0,0 +1,92 @@
+//! Tuning of the pool of the connections of the client.
+//!
+//! The connections to the aggregator and to the snapshot locations are kept alive and reused by
+//! the requests that follow. The default settings suit a client used by a single task, a
+//! [ConnectionPoolConfig] given to the client using
+//! [ClientBuilder::with_connection_pool][crate::ClientBuilder::with_connection_pool] allows to
+//! tune them, ie: for a [Client][crate::Client] shared by a service handling many simultaneous
+//! verifications.
+//!
+//! **Note:** connection pool settings are not available on wasm targets, the browser manages the
+//! connections there.
+//!
+//! # Share a client between many tasks
+//!
+//! ```no_run
+//! # async fn run() -> mithril_client::MithrilResult<()> {
+//! use mithril_client::{connection_pool::ConnectionPoolConfig, ClientBuilder};
+//! use std::time::Duration;
+//!
+//! let client = ClientBuilder::aggregator("YOUR_AGGREGATOR_ENDPOINT", "YOUR_GENESIS_VERIFICATION_KEY")
+//!     .with_connection_pool(
+//!         ConnectionPoolConfig::new()
+//!             .with_max_idle_per_host(32)
+//!             .with_idle_timeout(Duration::from_secs(120))
+//!             .with_tcp_keepalive(Duration::from_secs(30)),
+//!     )
+//!     .build()?;
+//!
+//! let tasks: Vec<_> = ["CERTIFICATE_HASH_1", "CERTIFICATE_HASH_2"]
+//!     .into_iter()
+//!     .map(|hash| {
+//!         let client = client.clone();
+//!         tokio::spawn(async move { client.certificate().verify_chain(hash).await })
+//!     })
+//!     .collect();
+//! for task in tasks {
+//!     task.await??;
+//! }
+//! #    Ok(())
+//! # }
+//! ```
+
+use reqwest::ClientBuilder;
+use std::time::Duration;
+
+/// Settings of the pool of the connections of the client, unset settings keep their default.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConnectionPoolConfig {
+    max_idle_per_host: Option<usize>,
+    idle_timeout: Option<Duration>,
+    tcp_keepalive: Option<Duration>,
+}
+
+impl ConnectionPoolConfig {
+    /// Constructs a new `ConnectionPoolConfig` keeping the default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Maximum number of idle connections kept alive for each host, unbounded by default.
+    pub fn with_max_idle_per_host(mut self, max_idle_per_host: usize) -> Self {
+        self.max_idle_per_host = Some(max_idle_per_host);
+        self
+    }
+
+    /// Duration after which an idle connection is closed, 90 seconds by default.
+    pub fn with_idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.idle_timeout = Some(idle_timeout);
+        self
+    }
+
+    /// Interval of the TCP keepalive probes sent on the connections, disabled by default.
+    pub fn with_tcp_keepalive(mut self, interval: Duration) -> Self {
+        self.tcp_keepalive = Some(interval);
+        self
+    }
+
+    /// Apply the settings to a [reqwest::ClientBuilder].
+    pub(crate) fn apply_to(&self, mut builder: ClientBuilder) -> ClientBuilder {
+        if let Some(max_idle_per_host) = self.max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max_idle_per_host);
+        }
+        if let Some(idle_timeout) = self.idle_timeout {
+            builder = builder.pool_idle_timeout(idle_timeout);
+        }
+        if let Some(interval) = self.tcp_keepalive {
+            builder = builder.tcp_keepalive(interval);
+        }
+
+        builder
+    }
+}
@@ -0,0 +1,189 @@
+//! Name resolution settings of the client.
+//!
+//! A [DnsConfig] given to the client using [ClientBuilder::with_dns][crate::ClientBuilder::with_dns]
+//! is used by both the requests to the aggregator and the snapshot downloads, it allows to:
+//! - only connect using IPv4 or IPv6 addresses, ie: on a host whose IPv6 connectivity is broken,
+//! - resolve some hosts to static addresses, ie: in an air-gapped or split-horizon network where
+//!   the public names of the aggregator or of the snapshot locations can't be resolved.
+//!
+//! Static addresses are used as they are, whatever the IP family.
+//!
+//! **Note:** name resolution settings are not available on wasm targets, the browser resolves
+//! the names there.
+//!
+//! # Reach an aggregator on a private network
+//!
+//! ```no_run
+//! # async fn run() -> mithril_client::MithrilResult<()> {
+//! use mithril_client::{dns::{DnsConfig, IpFamily}, ClientBuilder};
+//! use std::net::{IpAddr, Ipv4Addr};
+//!
+//! let client = ClientBuilder::aggregator("YOUR_AGGREGATOR_ENDPOINT", "YOUR_GENESIS_VERIFICATION_KEY")
+//!     .with_dns(
+//!         DnsConfig::new()
+//!             .with_ip_family(IpFamily::V4)
+//!             .with_override("aggregator.example.com", IpAddr::V4(Ipv4Addr::new(10, 0, 0, 12))),
+//!     )
+//!     .build()?;
+//!
+//! let snapshots = client.snapshot().list().await?;
+//! #    Ok(())
+//! # }
+//! ```
+
+use hyper::client::connect::dns::Name;
+use reqwest::dns::{Addrs, Resolve, Resolving};
+use reqwest::ClientBuilder;
+use std::collections::BTreeMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+
+/// IP family of the addresses the client connects to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IpFamily {
+    /// Connect to both IPv4 and IPv6 addresses
+    #[default]
+    Any,
+
+    /// Only connect to IPv4 addresses
+    V4,
+
+    /// Only connect to IPv6 addresses
+    V6,
+}
+
+impl IpFamily {
+    fn matches(&self, address: &SocketAddr) -> bool {
+        match self {
+            IpFamily::Any => true,
+            IpFamily::V4 => address.is_ipv4(),
+            IpFamily::V6 => address.is_ipv6(),
+        }
+    }
+}
+
+/// Name resolution settings of the client, unset settings keep their default.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DnsConfig {
+    ip_family: IpFamily,
+    overrides: BTreeMap<String, Vec<IpAddr>>,
+}
+
+impl DnsConfig {
+    /// Constructs a new `DnsConfig` keeping the default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only connect to the addresses of the given IP family, both by default.
+    pub fn with_ip_family(mut self, ip_family: IpFamily) -> Self {
+        self.ip_family = ip_family;
+        self
+    }
+
+    /// Resolve the given host to the given address instead of querying the DNS, can be called
+    /// many times for the same host to give it many addresses.
+    pub fn with_override(mut self, host: &str, address: IpAddr) -> Self {
+        self.overrides
+            .entry(host.to_lowercase())
+            .or_default()
+            .push(address);
+        self
+    }
+
+    /// Apply the settings to a [reqwest::ClientBuilder].
+    pub(crate) fn apply_to(&self, mut builder: ClientBuilder) -> ClientBuilder {
+        if self.ip_family != IpFamily::Any {
+            builder = builder.dns_resolver(Arc::new(IpFamilyResolver {
+                ip_family: self.ip_family,
+            }));
+        }
+        for (host, addresses) in &self.overrides {
+            // The port is ignored, the one of the requested url is used
+            let addresses: Vec<SocketAddr> = addresses
+                .iter()
+                .map(|address| SocketAddr::new(*address, 0))
+                .collect();
+            builder = builder.resolve_to_addrs(host, &addresses);
+        }
+
+        builder
+    }
+}
+
+/// Resolves the names with the system resolver, keeping only the addresses of an IP family.
+struct IpFamilyResolver {
+    ip_family: IpFamily,
+}
+
+impl Resolve for IpFamilyResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let ip_family = self.ip_family;
+        Box::pin(async move {
+            let host = name.as_str().to_string();
+            let addresses: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), 0))
+                .await?
+                .filter(|address| ip_family.matches(address))
+                .collect();
+            if addresses.is_empty() {
+                return Err(format!("No {ip_family:?} address found for host '{host}'").into());
+            }
+
+            Ok(Box::new(addresses.into_iter()) as Addrs)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use httpmock::MockServer;
+    use std::net::Ipv4Addr;
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn ip_family_resolver_only_keeps_the_addresses_of_its_family() {
+        let resolver = IpFamilyResolver {
+            ip_family: IpFamily::V4,
+        };
+
+        let addresses: Vec<SocketAddr> = resolver
+            .resolve(Name::from_str("localhost").unwrap())
+            .await
+            .unwrap()
+            .collect();
+
+        assert!(!addresses.is_empty());
+        assert!(addresses.iter().all(|address| address.is_ipv4()));
+    }
+
+    #[tokio::test]
+    async fn overridden_hosts_are_resolved_to_their_static_address() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.path("/aggregator");
+            then.status(200).body("{}");
+        });
+        let client = DnsConfig::new()
+            .with_ip_family(IpFamily::V4)
+            .with_override(
+                "Aggregator.Invalid",
+                IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)),
+            )
+            .apply_to(reqwest::ClientBuilder::new())
+            .build()
+            .unwrap();
+
+        client
+            .get(format!(
+                "http://aggregator.invalid:{}/aggregator",
+                server.port()
+            ))
+            .send()
+            .await
+            .unwrap();
+
+        mock.assert();
+    }
+}
@@ -0,0 +1,522 @@
+//! Garbage collection of the directories where the snapshots are downloaded.
+//!
+//! Interrupted downloads leave files behind them in their download directory: the partially
+//! downloaded archives and checkpoints of the [resumable downloads][crate::ClientBuilder::with_resumable_downloads],
+//! the staging directories of
+//! [download_unpack_staged][crate::snapshot_client::SnapshotClient::download_unpack_staged] and
+//! the temporary copies of the [moves across filesystems][crate::download_layout::DownloadLayout::move_path].
+//!
+//! A [DownloadDirectoryCollector] scans a download directory for those leftovers, and for the
+//! fully downloaded archives that are not kept by its [RetentionPolicy], then removes them and
+//! reports what was removed.
+//!
+//! Leftovers modified less than [min_age][RetentionPolicy::min_age] ago are kept since they may
+//! belong to a download still in progress.
+//!
+//! ```no_run
+//! # fn run() -> mithril_client::MithrilResult<()> {
+//! use mithril_client::download_gc::{DownloadDirectoryCollector, RetentionPolicy};
+//! use std::path::Path;
+//!
+//! let collector = DownloadDirectoryCollector::new(Path::new("/home/user/download/"))
+//!     .with_retention_policy(RetentionPolicy::default().with_keep_last(2));
+//! let report = collector.collect()?;
+//!
+//! println!("{} bytes freed", report.freed_bytes());
+//! #    Ok(())
+//! # }
+//! ```
+
+use anyhow::Context;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::utils::DownloadCheckpoint;
+use crate::MithrilResult;
+
+/// Which fully downloaded archives are kept and how old the leftovers of interrupted downloads
+/// must be to be removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetentionPolicy {
+    /// Number of the most recently downloaded archives kept
+    pub keep_last: usize,
+
+    /// Maximum total size of the kept archives, the oldest ones are removed first
+    pub max_bytes: Option<u64>,
+
+    /// Minimum time since the last modification of a leftover before it can be removed
+    pub min_age: Duration,
+}
+
+impl RetentionPolicy {
+    /// Default value of [min_age][Self::min_age]
+    pub const DEFAULT_MIN_AGE: Duration = Duration::from_secs(3600);
+
+    /// Set the number of the most recently downloaded archives kept.
+    pub fn with_keep_last(mut self, keep_last: usize) -> Self {
+        self.keep_last = keep_last;
+        self
+    }
+
+    /// Set the maximum total size of the kept archives.
+    pub fn with_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Set the minimum time since the last modification of a leftover before it can be removed.
+    pub fn with_min_age(mut self, min_age: Duration) -> Self {
+        self.min_age = min_age;
+        self
+    }
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            keep_last: 1,
+            max_bytes: None,
+            min_age: Self::DEFAULT_MIN_AGE,
+        }
+    }
+}
+
+/// Kind of a [GarbageEntry]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum GarbageKind {
+    /// The partially downloaded archive and the checkpoint of an interrupted download
+    StalePartialDownload,
+
+    /// The staging directory of an interrupted staged download
+    OrphanedStagingDirectory,
+
+    /// The temporary copy of an interrupted move across filesystems
+    OrphanedMoveCopy,
+
+    /// A fully downloaded archive that is not kept by the retention policy
+    SupersededArchive,
+}
+
+/// Files of a download directory that can be removed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GarbageEntry {
+    /// Kind of the entry
+    pub kind: GarbageKind,
+
+    /// Files and directories of the entry
+    pub paths: Vec<PathBuf>,
+
+    /// Total size of the entry
+    pub bytes: u64,
+}
+
+/// A file or directory of a [GarbageEntry] that could not be removed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GarbageRemovalFailure {
+    /// Path that could not be removed
+    pub path: PathBuf,
+
+    /// Description of the removal error
+    pub error: String,
+}
+
+/// Entries removed by a [DownloadDirectoryCollector].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GarbageCollectionReport {
+    /// The removed entries
+    pub removed: Vec<GarbageEntry>,
+
+    /// The entries which files could not all be removed, with the removal errors
+    pub failed: Vec<(GarbageEntry, Vec<GarbageRemovalFailure>)>,
+}
+
+impl GarbageCollectionReport {
+    /// Total size of the removed entries.
+    pub fn freed_bytes(&self) -> u64 {
+        self.removed.iter().map(|entry| entry.bytes).sum()
+    }
+
+    /// Returns `true` if every entry was removed.
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+/// Scans a download directory and removes the leftovers of the interrupted downloads and the
+/// superseded archives, see [module documentation][self].
+pub struct DownloadDirectoryCollector {
+    download_dir: PathBuf,
+    retention_policy: RetentionPolicy,
+}
+
+struct ScannedEntry {
+    kind: GarbageKind,
+    paths: Vec<PathBuf>,
+    bytes: u64,
+    modified: SystemTime,
+}
+
+impl DownloadDirectoryCollector {
+    /// Constructs a new `DownloadDirectoryCollector` for the given download directory.
+    pub fn new(download_dir: &Path) -> Self {
+        Self {
+            download_dir: download_dir.to_path_buf(),
+            retention_policy: RetentionPolicy::default(),
+        }
+    }
+
+    /// Set the [RetentionPolicy] applied to the download directory.
+    pub fn with_retention_policy(mut self, retention_policy: RetentionPolicy) -> Self {
+        self.retention_policy = retention_policy;
+        self
+    }
+
+    /// List the entries that [collect][Self::collect] would remove, without removing them.
+    pub fn scan(&self) -> MithrilResult<Vec<GarbageEntry>> {
+        let now = SystemTime::now();
+        let is_stale = |modified: SystemTime| {
+            now.duration_since(modified).unwrap_or_default() >= self.retention_policy.min_age
+        };
+        let mut garbage = vec![];
+        let mut archives = vec![];
+
+        for entry in self.scan_entries()? {
+            match entry.kind {
+                GarbageKind::SupersededArchive => archives.push(entry),
+                _ if is_stale(entry.modified) => garbage.push(entry),
+                _ => {}
+            }
+        }
+
+        archives.sort_by_key(|archive| std::cmp::Reverse(archive.modified));
+        let mut kept_bytes = 0;
+        for (index, archive) in archives.into_iter().enumerate() {
+            kept_bytes += archive.bytes;
+            let within_max_bytes = self
+                .retention_policy
+                .max_bytes
+                .is_none_or(|max_bytes| kept_bytes <= max_bytes);
+            if index >= self.retention_policy.keep_last || !within_max_bytes {
+                garbage.push(archive);
+            }
+        }
+
+        let mut garbage: Vec<GarbageEntry> = garbage
+            .into_iter()
+            .map(|entry| GarbageEntry {
+                kind: entry.kind,
+                paths: entry.paths,
+                bytes: entry.bytes,
+            })
+            .collect();
+        garbage.sort_by(|a, b| (a.kind, &a.paths).cmp(&(b.kind, &b.paths)));
+
+        Ok(garbage)
+    }
+
+    /// Remove the leftovers of the interrupted downloads and the superseded archives from the
+    /// download directory, returning the removed entries.
+    ///
+    /// An entry that can not be removed does not stop the collection, it is reported in the
+    /// [failed][GarbageCollectionReport::failed] entries of the report.
+    pub fn collect(&self) -> MithrilResult<GarbageCollectionReport> {
+        Ok(remove_entries(self.scan()?))
+    }
+
+    fn scan_entries(&self) -> MithrilResult<Vec<ScannedEntry>> {
+        let mut download_stems = BTreeSet::new();
+        let mut entries = vec![];
+
+        for dir_entry in fs::read_dir(&self.download_dir).with_context(|| {
+            format!(
+                "Could not read download directory '{}'",
+                self.download_dir.display()
+            )
+        })? {
+            let path = dir_entry?.path();
+            let file_name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+
+            if let Some(stem) = DownloadCheckpoint::download_file_stem(&file_name) {
+                download_stems.insert(stem.to_string());
+            } else if file_name.starts_with('.') && path.is_dir() {
+                let kind = if file_name.contains(".staging-") {
+                    GarbageKind::OrphanedStagingDirectory
+                } else if file_name.contains(".moving-") {
+                    GarbageKind::OrphanedMoveCopy
+                } else {
+                    continue;
+                };
+                entries.push(scanned_entry(kind, vec![path])?);
+            }
+        }
+
+        for stem in download_stems {
+            let is_complete = DownloadCheckpoint::read_from_stem(&self.download_dir, &stem)
+                .is_some_and(|checkpoint| checkpoint.is_complete());
+            let kind = if is_complete {
+                GarbageKind::SupersededArchive
+            } else {
+                GarbageKind::StalePartialDownload
+            };
+            let paths = DownloadCheckpoint::download_file_paths(&self.download_dir, &stem)
+                .into_iter()
+                .filter(|path| path.exists())
+                .collect();
+            entries.push(scanned_entry(kind, paths)?);
+        }
+
+        Ok(entries)
+    }
+}
+
+fn scanned_entry(kind: GarbageKind, paths: Vec<PathBuf>) -> MithrilResult<ScannedEntry> {
+    let mut bytes = 0;
+    let mut modified = SystemTime::UNIX_EPOCH;
+    for path in &paths {
+        let (path_bytes, path_modified) = size_and_last_modification(path)
+            .with_context(|| format!("Could not read metadata of '{}'", path.display()))?;
+        bytes += path_bytes;
+        modified = modified.max(path_modified);
+    }
+
+    Ok(ScannedEntry {
+        kind,
+        paths,
+        bytes,
+        modified,
+    })
+}
+
+/// Total size and most recent modification of the given file or of the files of the given
+/// directory.
+fn size_and_last_modification(path: &Path) -> std::io::Result<(u64, SystemTime)> {
+    let metadata = fs::symlink_metadata(path)?;
+    let mut bytes = metadata.len();
+    let mut modified = metadata.modified()?;
+
+    if metadata.is_dir() {
+        bytes = 0;
+        for entry in fs::read_dir(path)? {
+            let (entry_bytes, entry_modified) = size_and_last_modification(&entry?.path())?;
+            bytes += entry_bytes;
+            modified = modified.max(entry_modified);
+        }
+    }
+
+    Ok((bytes, modified))
+}
+
+fn remove_entries(entries: Vec<GarbageEntry>) -> GarbageCollectionReport {
+    let mut report = GarbageCollectionReport::default();
+    for entry in entries {
+        let failures: Vec<GarbageRemovalFailure> = entry
+            .paths
+            .iter()
+            .filter_map(|path| {
+                remove_path(path).err().map(|error| GarbageRemovalFailure {
+                    path: path.clone(),
+                    error: error.to_string(),
+                })
+            })
+            .collect();
+        if failures.is_empty() {
+            report.removed.push(entry);
+        } else {
+            report.failed.push((entry, failures));
+        }
+    }
+
+    report
+}
+
+fn remove_path(path: &Path) -> std::io::Result<()> {
+    if fs::symlink_metadata(path)?.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils;
+    use crate::utils::DownloadCheckpoint;
+
+    use super::*;
+
+    fn create_download(dir: &Path, location: &str, archive_size: u64, downloaded_bytes: u64) {
+        fs::write(
+            DownloadCheckpoint::archive_path(dir, location),
+            vec![0; downloaded_bytes as usize],
+        )
+        .unwrap();
+        DownloadCheckpoint {
+            downloaded_bytes,
+            ..DownloadCheckpoint::new(location, archive_size)
+        }
+        .save(dir)
+        .unwrap();
+    }
+
+    fn no_min_age() -> RetentionPolicy {
+        RetentionPolicy::default().with_min_age(Duration::ZERO)
+    }
+
+    fn kinds(entries: &[GarbageEntry]) -> Vec<GarbageKind> {
+        entries.iter().map(|entry| entry.kind).collect()
+    }
+
+    #[test]
+    fn collect_removes_the_leftovers_of_interrupted_downloads() {
+        let dir = test_utils::get_test_directory(
+            "download_gc",
+            "collect_removes_the_leftovers_of_interrupted_downloads",
+        );
+        create_download(&dir, "http://host/interrupted.tar.zst", 10, 4);
+        let staging_dir = dir.join(".db.staging-1234");
+        fs::create_dir_all(staging_dir.join("immutable")).unwrap();
+        fs::write(staging_dir.join("immutable").join("00001.chunk"), "ab").unwrap();
+        fs::create_dir(dir.join(".db.moving-5678")).unwrap();
+        fs::create_dir(dir.join(".db.quarantine")).unwrap();
+        fs::create_dir(dir.join("db")).unwrap();
+
+        let report = DownloadDirectoryCollector::new(&dir)
+            .with_retention_policy(no_min_age())
+            .collect()
+            .unwrap();
+
+        assert_eq!(
+            vec![
+                GarbageKind::StalePartialDownload,
+                GarbageKind::OrphanedStagingDirectory,
+                GarbageKind::OrphanedMoveCopy,
+            ],
+            kinds(&report.removed)
+        );
+        assert_eq!(2, report.removed[0].paths.len());
+        assert_eq!(2, report.removed[1].bytes);
+        assert!(report.freed_bytes() > 4 + 2);
+        assert!(!staging_dir.exists());
+        assert!(!dir.join(".db.moving-5678").exists());
+        assert!(dir.join(".db.quarantine").exists());
+        assert!(dir.join("db").exists());
+        assert!(fs::read_dir(&dir).unwrap().all(|entry| {
+            DownloadCheckpoint::download_file_stem(&entry.unwrap().file_name().to_string_lossy())
+                .is_none()
+        }));
+    }
+
+    #[test]
+    fn collect_keeps_going_when_an_entry_can_not_be_removed() {
+        let dir = test_utils::get_test_directory(
+            "download_gc",
+            "collect_keeps_going_when_an_entry_can_not_be_removed",
+        );
+        let missing_path = dir.join("missing.tar.zst");
+        let removable_path = dir.join("removable.tar.zst");
+        fs::write(dir.join("partial.tar.zst"), "ab").unwrap();
+        fs::write(&removable_path, "abc").unwrap();
+        let entry = |paths: Vec<PathBuf>, bytes| GarbageEntry {
+            kind: GarbageKind::StalePartialDownload,
+            paths,
+            bytes,
+        };
+
+        let report = remove_entries(vec![
+            entry(vec![missing_path.clone(), dir.join("partial.tar.zst")], 2),
+            entry(vec![removable_path.clone()], 3),
+        ]);
+
+        assert!(!report.is_complete());
+        assert_eq!(vec![entry(vec![removable_path.clone()], 3)], report.removed);
+        assert_eq!(3, report.freed_bytes());
+        assert_eq!(1, report.failed.len());
+        assert_eq!(
+            vec![missing_path],
+            report.failed[0]
+                .1
+                .iter()
+                .map(|failure| failure.path.clone())
+                .collect::<Vec<_>>()
+        );
+        assert!(!dir.join("partial.tar.zst").exists());
+        assert!(!removable_path.exists());
+    }
+
+    #[test]
+    fn recent_leftovers_are_kept() {
+        let dir = test_utils::get_test_directory("download_gc", "recent_leftovers_are_kept");
+        create_download(&dir, "http://host/in_progress.tar.zst", 10, 4);
+        fs::create_dir(dir.join(".db.staging-1234")).unwrap();
+
+        let garbage = DownloadDirectoryCollector::new(&dir)
+            .with_retention_policy(RetentionPolicy::default())
+            .scan()
+            .unwrap();
+
+        assert_eq!(Vec::<GarbageEntry>::new(), garbage);
+    }
+
+    #[test]
+    fn archives_not_kept_by_the_retention_policy_are_superseded() {
+        let dir = test_utils::get_test_directory(
+            "download_gc",
+            "archives_not_kept_by_the_retention_policy_are_superseded",
+        );
+        for (index, location) in [
+            "http://host/1.tar.zst",
+            "http://host/2.tar.zst",
+            "http://host/3.tar.zst",
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            create_download(
+                &dir,
+                location,
+                1000 * (index as u64 + 1),
+                1000 * (index as u64 + 1),
+            );
+            // Ensure distinct modification times
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        let superseded_archives = |retention_policy: RetentionPolicy| {
+            DownloadDirectoryCollector::new(&dir)
+                .with_retention_policy(retention_policy)
+                .scan()
+                .unwrap()
+                .into_iter()
+                .map(|entry| {
+                    assert_eq!(GarbageKind::SupersededArchive, entry.kind);
+                    entry.paths[0].clone()
+                })
+                .collect::<BTreeSet<_>>()
+        };
+        let archive = |location| DownloadCheckpoint::archive_path(&dir, location);
+
+        assert_eq!(
+            BTreeSet::from([
+                archive("http://host/1.tar.zst"),
+                archive("http://host/2.tar.zst")
+            ]),
+            superseded_archives(no_min_age())
+        );
+        assert_eq!(
+            BTreeSet::from([archive("http://host/1.tar.zst")]),
+            superseded_archives(no_min_age().with_keep_last(3).with_max_bytes(5500))
+        );
+        assert_eq!(
+            BTreeSet::from([
+                archive("http://host/1.tar.zst"),
+                archive("http://host/2.tar.zst"),
+                archive("http://host/3.tar.zst")
+            ]),
+            superseded_archives(no_min_age().with_keep_last(3).with_max_bytes(2000))
+        );
+    }
+}
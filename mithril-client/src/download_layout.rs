@@ -0,0 +1,380 @@
+//! Layout of the directories where the snapshots are downloaded.
+//!
+//! A [DownloadLayout] lists the entries that an unpacked snapshot must contain, ie: the
+//! `immutable` directory of a Cardano node database, and [verifies][DownloadLayout::verify] that
+//! a directory matches it.
+//!
+//! It also [moves][DownloadLayout::move_path] the unpacked directories to their final location:
+//! renaming fails when the source and the target are not on the same filesystem (`EXDEV`), in
+//! that case the source is copied next to the target, synced to the disk, then renamed to the
+//! target so the target is never partially written.
+//!
+//! ```no_run
+//! # fn run() -> mithril_client::MithrilResult<()> {
+//! use mithril_client::download_layout::DownloadLayout;
+//! use std::path::Path;
+//!
+//! let layout = DownloadLayout::cardano_db();
+//! layout.verify(Path::new("/tmp/unpacked_snapshot"))?;
+//! DownloadLayout::move_path(Path::new("/tmp/unpacked_snapshot"), Path::new("/mnt/cardano/db"))?;
+//! #    Ok(())
+//! # }
+//! ```
+
+use anyhow::Context;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+use crate::MithrilResult;
+
+/// Kind of an entry of a [DownloadLayout]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayoutEntryKind {
+    /// The entry is a directory
+    Directory,
+
+    /// The entry is a file
+    File,
+}
+
+/// Error raised when a directory does not match a [DownloadLayout]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("The directory '{}' does not match the expected layout: {}.", dir.display(), problems.join("; "))]
+pub struct DownloadLayoutError {
+    /// the verified directory
+    pub dir: PathBuf,
+
+    /// all the problems found
+    pub problems: Vec<String>,
+}
+
+/// How a path was moved by [DownloadLayout::move_path]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveMethod {
+    /// The path was renamed, the source and the target are on the same filesystem
+    Rename,
+
+    /// The path was copied then renamed, the source and the target are on different filesystems
+    CopyAcrossFilesystems,
+}
+
+/// Entries that a downloaded snapshot directory must contain
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DownloadLayout {
+    required_entries: Vec<(String, LayoutEntryKind)>,
+}
+
+impl DownloadLayout {
+    /// Layout of a Cardano node database: it must contain an `immutable` directory.
+    pub fn cardano_db() -> Self {
+        Self::default().with_required_directory("immutable")
+    }
+
+    /// Require a directory with the given relative path.
+    pub fn with_required_directory(mut self, path: &str) -> Self {
+        self.required_entries
+            .push((path.to_string(), LayoutEntryKind::Directory));
+        self
+    }
+
+    /// Require a file with the given relative path.
+    pub fn with_required_file(mut self, path: &str) -> Self {
+        self.required_entries
+            .push((path.to_string(), LayoutEntryKind::File));
+        self
+    }
+
+    /// Verify that the given directory contains all the required entries, returning all the
+    /// problems found.
+    pub fn verify(&self, dir: &Path) -> Result<(), DownloadLayoutError> {
+        let problems: Vec<String> =
+            self.required_entries
+                .iter()
+                .filter_map(|(path, kind)| {
+                    let entry = dir.join(path);
+                    match (kind, entry.is_dir(), entry.is_file()) {
+                        (LayoutEntryKind::Directory, true, _)
+                        | (LayoutEntryKind::File, _, true) => None,
+                        (LayoutEntryKind::Directory, _, true) => {
+                            Some(format!("'{path}' is a file instead of a directory"))
+                        }
+                        (LayoutEntryKind::File, true, _) => {
+                            Some(format!("'{path}' is a directory instead of a file"))
+                        }
+                        _ => Some(format!("'{path}' is missing")),
+                    }
+                })
+                .collect();
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(DownloadLayoutError {
+                dir: dir.to_path_buf(),
+                problems,
+            })
+        }
+    }
+
+    /// Move the given file or directory to the given target path, that must not exist.
+    ///
+    /// When the source and the target are on different filesystems the source is copied to a
+    /// temporary path next to the target, synced to the disk, renamed to the target, then
+    /// removed.
+    pub fn move_path(source: &Path, target: &Path) -> MithrilResult<MoveMethod> {
+        if !Self::is_cross_filesystem(source, target)? {
+            match fs::rename(source, target) {
+                Ok(()) => return Ok(MoveMethod::Rename),
+                Err(e) if is_cross_device_error(&e) => {}
+                Err(e) => {
+                    return Err(e).with_context(|| {
+                        format!(
+                            "Could not move '{}' to '{}'",
+                            source.display(),
+                            target.display()
+                        )
+                    })
+                }
+            }
+        }
+
+        Self::copy_then_rename(source, target)?;
+        Ok(MoveMethod::CopyAcrossFilesystems)
+    }
+
+    /// Check if the given source and the parent directory of the given target are on different
+    /// filesystems.
+    ///
+    /// **NOTE**: Only detected on unix, elsewhere a failing rename is used to detect it.
+    pub fn is_cross_filesystem(source: &Path, target: &Path) -> MithrilResult<bool> {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+
+            let target_parent = existing_parent(target);
+            let source_device = fs::symlink_metadata(source)
+                .with_context(|| format!("Could not read metadata of '{}'", source.display()))?
+                .dev();
+            let target_device = fs::metadata(target_parent)
+                .with_context(|| {
+                    format!("Could not read metadata of '{}'", target_parent.display())
+                })?
+                .dev();
+
+            Ok(source_device != target_device)
+        }
+
+        #[cfg(not(unix))]
+        {
+            let _ = (source, target);
+            Ok(false)
+        }
+    }
+
+    fn copy_then_rename(source: &Path, target: &Path) -> MithrilResult<()> {
+        let target_parent = existing_parent(target);
+        let target_name = target
+            .file_name()
+            .with_context(|| format!("Invalid target path '{}'", target.display()))?
+            .to_string_lossy();
+        let temp_target =
+            target_parent.join(format!(".{target_name}.moving-{}", uuid::Uuid::new_v4()));
+
+        let copy = copy_synced(source, &temp_target)
+            .and_then(|()| fs::rename(&temp_target, target))
+            .and_then(|()| sync_dir(target_parent));
+        if let Err(e) = copy {
+            let _ = remove_path(&temp_target);
+            return Err(e).with_context(|| {
+                format!(
+                    "Could not copy '{}' to '{}'",
+                    source.display(),
+                    target.display()
+                )
+            });
+        }
+
+        remove_path(source)
+            .with_context(|| format!("Could not remove moved path '{}'", source.display()))
+    }
+}
+
+fn existing_parent(path: &Path) -> &Path {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    }
+}
+
+fn is_cross_device_error(error: &io::Error) -> bool {
+    // EXDEV on unix, ERROR_NOT_SAME_DEVICE on windows
+    #[cfg(unix)]
+    const CROSS_DEVICE_ERROR_CODE: i32 = 18;
+    #[cfg(windows)]
+    const CROSS_DEVICE_ERROR_CODE: i32 = 17;
+    #[cfg(not(any(unix, windows)))]
+    const CROSS_DEVICE_ERROR_CODE: i32 = -1;
+
+    error.raw_os_error() == Some(CROSS_DEVICE_ERROR_CODE)
+}
+
+/// Copy recursively the given path, syncing every copied file and directory to the disk.
+fn copy_synced(source: &Path, target: &Path) -> io::Result<()> {
+    let file_type = fs::symlink_metadata(source)?.file_type();
+
+    if file_type.is_dir() {
+        fs::create_dir(target)?;
+        for entry in fs::read_dir(source)? {
+            let entry = entry?;
+            copy_synced(&entry.path(), &target.join(entry.file_name()))?;
+        }
+        sync_dir(target)
+    } else if file_type.is_symlink() {
+        copy_symlink(source, target)
+    } else {
+        fs::copy(source, target)?;
+        fs::File::open(target)?.sync_all()
+    }
+}
+
+#[cfg(unix)]
+fn copy_symlink(source: &Path, target: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(fs::read_link(source)?, target)
+}
+
+#[cfg(not(unix))]
+fn copy_symlink(source: &Path, target: &Path) -> io::Result<()> {
+    fs::copy(source, target).map(|_| ())
+}
+
+#[cfg(unix)]
+fn sync_dir(dir: &Path) -> io::Result<()> {
+    fs::File::open(dir)?.sync_all()
+}
+
+#[cfg(not(unix))]
+fn sync_dir(_dir: &Path) -> io::Result<()> {
+    // Directories can not be opened to be synced outside unix
+    Ok(())
+}
+
+fn remove_path(path: &Path) -> io::Result<()> {
+    if fs::symlink_metadata(path)?.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils;
+
+    use super::*;
+
+    fn create_db(dir: &Path) {
+        fs::create_dir_all(dir.join("immutable")).unwrap();
+        fs::write(dir.join("immutable").join("00000.chunk"), "chunk").unwrap();
+        fs::write(dir.join("protocolMagicId"), "2").unwrap();
+    }
+
+    #[test]
+    fn verify_report_all_the_missing_or_mistyped_entries() {
+        let dir = test_utils::get_test_directory(
+            "download_layout",
+            "verify_report_all_the_missing_or_mistyped_entries",
+        );
+        create_db(&dir);
+        let layout = DownloadLayout::cardano_db()
+            .with_required_directory("protocolMagicId")
+            .with_required_file("immutable")
+            .with_required_directory("ledger");
+
+        let error = layout.verify(&dir).expect_err("verify should fail");
+
+        assert_eq!(
+            vec![
+                "'protocolMagicId' is a file instead of a directory".to_string(),
+                "'immutable' is a directory instead of a file".to_string(),
+                "'ledger' is missing".to_string(),
+            ],
+            error.problems
+        );
+        DownloadLayout::cardano_db()
+            .with_required_file("protocolMagicId")
+            .verify(&dir)
+            .expect("verify should succeed");
+    }
+
+    #[test]
+    fn move_path_on_the_same_filesystem_rename_it() {
+        let dir = test_utils::get_test_directory(
+            "download_layout",
+            "move_path_on_the_same_filesystem_rename_it",
+        );
+        create_db(&dir.join("source"));
+
+        let method = DownloadLayout::move_path(&dir.join("source"), &dir.join("target")).unwrap();
+
+        assert_eq!(MoveMethod::Rename, method);
+        assert!(!dir.join("source").exists());
+        DownloadLayout::cardano_db()
+            .verify(&dir.join("target"))
+            .unwrap();
+    }
+
+    #[test]
+    fn copy_then_rename_move_the_whole_tree_and_remove_the_source() {
+        let dir = test_utils::get_test_directory(
+            "download_layout",
+            "copy_then_rename_move_the_whole_tree_and_remove_the_source",
+        );
+        create_db(&dir.join("source"));
+        #[cfg(unix)]
+        std::os::unix::fs::symlink("immutable", dir.join("source").join("link")).unwrap();
+
+        DownloadLayout::copy_then_rename(&dir.join("source"), &dir.join("target")).unwrap();
+
+        assert!(!dir.join("source").exists());
+        assert_eq!(
+            "chunk",
+            fs::read_to_string(dir.join("target").join("immutable").join("00000.chunk")).unwrap()
+        );
+        #[cfg(unix)]
+        assert_eq!(
+            Path::new("immutable"),
+            fs::read_link(dir.join("target").join("link")).unwrap()
+        );
+        assert_eq!(
+            vec!["target".to_string()],
+            fs::read_dir(&dir)
+                .unwrap()
+                .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+                .collect::<Vec<_>>(),
+            "the temporary copy should have been renamed"
+        );
+    }
+
+    #[test]
+    fn copy_then_rename_remove_the_partial_copy_on_failure() {
+        let dir = test_utils::get_test_directory(
+            "download_layout",
+            "copy_then_rename_remove_the_partial_copy_on_failure",
+        );
+        create_db(&dir.join("source"));
+        fs::create_dir_all(dir.join("target").join("not_empty")).unwrap();
+
+        DownloadLayout::copy_then_rename(&dir.join("source"), &dir.join("target"))
+            .expect_err("renaming over a non empty directory should fail");
+
+        assert!(dir.join("source").exists());
+        let mut entries: Vec<String> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        entries.sort();
+        assert_eq!(vec!["source".to_string(), "target".to_string()], entries);
+    }
+}
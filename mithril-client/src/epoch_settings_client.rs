@@ -0,0 +1,262 @@
+//! A client to retrieve the settings of the current epoch from an Aggregator.
+//!
+//! In order to do so it defines a [EpochSettingsClient] which exposes the following features:
+//!  - [get][EpochSettingsClient::get]: get the settings of the current epoch
+//!  - [get_protocol_parameters_at][EpochSettingsClient::get_protocol_parameters_at]: get the
+//!    protocol parameters that apply at an epoch, following the Mithril epoch offsets
+//!  - [watch_protocol_parameters_changes][EpochSettingsClient::watch_protocol_parameters_changes]:
+//!    be notified when the protocol parameters of the next epoch differ from the current ones
+//!
+//! # Watch the changes of protocol parameters
+//!
+//! The protocol parameters of the next epoch are announced one epoch in advance, a change of
+//! them changes the odds of the signers to win the lotteries.
+//!
+//! ```no_run
+//! # async fn run() -> mithril_client::MithrilResult<()> {
+//! use futures::StreamExt;
+//! use mithril_client::ClientBuilder;
+//! use std::time::Duration;
+//!
+//! let client = ClientBuilder::aggregator("YOUR_AGGREGATOR_ENDPOINT", "YOUR_GENESIS_VERIFICATION_KEY").build()?;
+//! let epoch_settings_client = client.epoch_settings();
+//! let mut changes =
+//!     Box::pin(epoch_settings_client.watch_protocol_parameters_changes(Duration::from_secs(600)));
+//!
+//! while let Some(change) = changes.next().await {
+//!     let change = change?;
+//!     let (current, next) = change.expected_won_lotteries(0.001);
+//!     println!(
+//!         "Protocol parameters change at epoch {}: {:?} -> {:?}, expected won lotteries {current:.2} -> {next:.2}",
+//!         change.epoch + 1, change.current, change.next
+//!     );
+//! }
+//! #    Ok(())
+//! # }
+//! ```
+
+use anyhow::{anyhow, Context};
+use futures::{stream, Stream};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::aggregator_client::{AggregatorClient, AggregatorRequest};
+use crate::common::entities::{Epoch, ProtocolParameters};
+use crate::common::era::SupportedEra;
+use crate::common::messages::EpochSettingsMessage;
+use crate::utils::time;
+use crate::MithrilResult;
+
+/// The protocol parameters of the next epoch differ from the current ones.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProtocolParametersChange {
+    /// Current epoch, the next protocol parameters apply from the following epoch
+    pub epoch: Epoch,
+
+    /// Protocol parameters of the current epoch
+    pub current: ProtocolParameters,
+
+    /// Protocol parameters of the next epoch
+    pub next: ProtocolParameters,
+}
+
+impl ProtocolParametersChange {
+    fn from_epoch_settings(epoch_settings: &EpochSettingsMessage) -> Option<Self> {
+        (epoch_settings.protocol_parameters != epoch_settings.next_protocol_parameters).then(|| {
+            Self {
+                epoch: epoch_settings.epoch,
+                current: epoch_settings.protocol_parameters.clone(),
+                next: epoch_settings.next_protocol_parameters.clone(),
+            }
+        })
+    }
+
+    /// Number of lotteries that a signer holding the given ratio of the total stake is expected
+    /// to win per signature, with the current then the next protocol parameters.
+    pub fn expected_won_lotteries(&self, stake_ratio: f64) -> (f64, f64) {
+        let expected_won_lotteries = |parameters: &ProtocolParameters| {
+            parameters.m as f64 * (1.0 - (1.0 - parameters.phi_f).powf(stake_ratio))
+        };
+
+        (
+            expected_won_lotteries(&self.current),
+            expected_won_lotteries(&self.next),
+        )
+    }
+}
+
+/// HTTP client for the epoch settings API from the Aggregator
+pub struct EpochSettingsClient {
+    aggregator_client: Arc<dyn AggregatorClient>,
+}
+
+impl EpochSettingsClient {
+    /// Constructs a new `EpochSettingsClient`.
+    pub fn new(aggregator_client: Arc<dyn AggregatorClient>) -> Self {
+        Self { aggregator_client }
+    }
+
+    /// Get the settings of the current epoch.
+    pub async fn get(&self) -> MithrilResult<EpochSettingsMessage> {
+        let content = self
+            .aggregator_client
+            .get_content(AggregatorRequest::GetEpochSettings)
+            .await
+            .with_context(|| "EpochSettings Client can not get the epoch settings")?;
+
+        serde_json::from_str(&content)
+            .with_context(|| "EpochSettings Client can not deserialize the epoch settings")
+    }
+
+    /// Get the protocol parameters that apply at the given epoch, which must be the current epoch
+    /// or one of the two following ones, with the epoch offsets of the given era, see
+    /// [EpochSettingsMessage::protocol_parameters_at].
+    pub async fn get_protocol_parameters_at(
+        &self,
+        epoch: Epoch,
+        era: SupportedEra,
+    ) -> MithrilResult<ProtocolParameters> {
+        let epoch_settings = self.get().await?;
+
+        epoch_settings
+            .protocol_parameters_at(epoch, era)
+            .cloned()
+            .ok_or_else(|| {
+                anyhow!(
+                    "The protocol parameters of epoch {epoch} are not known at epoch {}",
+                    epoch_settings.epoch
+                )
+            })
+    }
+
+    /// Poll the aggregator every `interval` and stream a [ProtocolParametersChange] when the
+    /// protocol parameters of the next epoch differ from the current ones.
+    ///
+    /// A change is streamed once, including a change already announced when the first poll is
+    /// done. Polling errors are streamed and the polling goes on.
+    pub fn watch_protocol_parameters_changes(
+        &self,
+        interval: Duration,
+    ) -> impl Stream<Item = MithrilResult<ProtocolParametersChange>> + '_ {
+        let state: (bool, Option<ProtocolParametersChange>) = (true, None);
+
+        stream::unfold(
+            state,
+            move |(mut is_first_poll, mut last_change)| async move {
+                loop {
+                    if !is_first_poll {
+                        time::sleep(interval).await;
+                    }
+                    is_first_poll = false;
+
+                    match self.get().await {
+                        Ok(epoch_settings) => {
+                            let change =
+                                ProtocolParametersChange::from_epoch_settings(&epoch_settings);
+                            if change.is_some() && change != last_change {
+                                last_change = change.clone();
+                                return change
+                                    .map(|change| (Ok(change), (is_first_poll, last_change)));
+                            }
+                        }
+                        Err(error) => return Some((Err(error), (is_first_poll, last_change))),
+                    }
+                }
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::anyhow;
+    use futures::StreamExt;
+    use std::collections::VecDeque;
+    use std::sync::Mutex;
+
+    use crate::aggregator_client::{AggregatorClientError, MockAggregatorHTTPClient};
+
+    use super::*;
+
+    fn epoch_settings(epoch: u64, next_m: u64) -> EpochSettingsMessage {
+        let mut epoch_settings = EpochSettingsMessage::dummy();
+        epoch_settings.epoch = Epoch(epoch);
+        epoch_settings.next_protocol_parameters.m = next_m;
+        epoch_settings
+    }
+
+    fn client_serving(responses: Vec<Option<EpochSettingsMessage>>) -> EpochSettingsClient {
+        let responses = Mutex::new(VecDeque::from(responses));
+        let mut aggregator_client = MockAggregatorHTTPClient::new();
+        aggregator_client.expect_get_content().returning(move |_| {
+            match responses.lock().unwrap().pop_front() {
+                Some(Some(epoch_settings)) => Ok(serde_json::to_string(&epoch_settings).unwrap()),
+                _ => Err(AggregatorClientError::RemoteServerTechnical(anyhow!(
+                    "unavailable"
+                ))),
+            }
+        });
+
+        EpochSettingsClient::new(Arc::new(aggregator_client))
+    }
+
+    #[test]
+    fn expected_won_lotteries_with_the_current_and_next_parameters() {
+        let change = ProtocolParametersChange {
+            epoch: Epoch(10),
+            current: ProtocolParameters::new(5, 100, 0.5),
+            next: ProtocolParameters::new(5, 200, 0.5),
+        };
+
+        let (current, next) = change.expected_won_lotteries(1.0);
+
+        assert_eq!((50.0, 100.0), (current, next));
+    }
+
+    #[tokio::test]
+    async fn stream_each_change_of_the_next_protocol_parameters_once() {
+        let client = client_serving(vec![
+            Some(epoch_settings(10, 100)),
+            Some(epoch_settings(10, 150)),
+            Some(epoch_settings(10, 150)),
+            None,
+            Some(epoch_settings(11, 150)),
+            Some(epoch_settings(12, 150)),
+            Some(epoch_settings(12, 100)),
+        ]);
+
+        let changes: Vec<MithrilResult<ProtocolParametersChange>> = client
+            .watch_protocol_parameters_changes(Duration::from_millis(1))
+            .take(3)
+            .collect()
+            .await;
+
+        assert_eq!(
+            Epoch(10),
+            changes[0].as_ref().unwrap().epoch,
+            "the change announced at epoch 10 should be streamed first"
+        );
+        assert_eq!(150, changes[0].as_ref().unwrap().next.m);
+        assert!(changes[1].is_err(), "polling errors should be streamed");
+        assert_eq!(Epoch(11), changes[2].as_ref().unwrap().epoch);
+    }
+
+    #[tokio::test]
+    async fn get_the_protocol_parameters_that_apply_at_an_epoch() {
+        let client = client_serving(vec![
+            Some(epoch_settings(10, 150)),
+            Some(epoch_settings(10, 150)),
+        ]);
+
+        let parameters = client
+            .get_protocol_parameters_at(Epoch(11), SupportedEra::Thales)
+            .await
+            .unwrap();
+        client
+            .get_protocol_parameters_at(Epoch(9), SupportedEra::Thales)
+            .await
+            .expect_err("the protocol parameters of a past epoch should not be known");
+
+        assert_eq!(150, parameters.m);
+    }
+}
@@ -0,0 +1,229 @@
+//! Check that the client supports the eras of the aggregator.
+//!
+//! The Mithril protocol evolves by eras: each era is announced by an [EraMarker] read, by the
+//! signers and the aggregator, from the Cardano chain before its activation. A client that
+//! doesn't support an era can't verify the certificates issued in it.
+//!
+//! [check_era_compatibility][crate::Client::check_era_compatibility] reads the era markers using
+//! the [EraReaderAdapter] given to
+//! [ClientBuilder::with_era_reader_adapter][crate::ClientBuilder::with_era_reader_adapter] and
+//! reports whether the current era of the aggregator and the announced next era are in the
+//! [supported eras][SupportedEra::eras] of this version of the client, so orchestration tools can
+//! warn before an era switch breaks verification.
+//!
+//! # Check the eras before upgrading a deployment
+//!
+//! ```no_run
+//! # async fn run(era_reader_adapter: std::sync::Arc<dyn mithril_client::common::era::EraReaderAdapter>) -> mithril_client::MithrilResult<()> {
+//! use mithril_client::ClientBuilder;
+//!
+//! let client = ClientBuilder::aggregator("YOUR_AGGREGATOR_ENDPOINT", "YOUR_GENESIS_VERIFICATION_KEY")
+//!     .with_era_reader_adapter(era_reader_adapter)
+//!     .build()?;
+//!
+//! let compatibility = client.check_era_compatibility().await?;
+//! if !compatibility.is_compatible() {
+//!     println!(
+//!         "Upgrade required, unsupported eras: {}",
+//!         compatibility.unsupported_eras().join(", ")
+//!     );
+//! }
+//! #    Ok(())
+//! # }
+//! ```
+
+use anyhow::Context;
+use serde::Serialize;
+use std::str::FromStr;
+
+use crate::aggregator_client::{AggregatorClient, AggregatorRequest};
+use crate::common::entities::Epoch;
+use crate::common::era::{EraMarker, EraReader, SupportedEra};
+use crate::common::messages::EpochSettingsMessage;
+use crate::MithrilResult;
+
+/// Support of an era by the client.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EraSupport {
+    /// Marker of the era
+    pub marker: EraMarker,
+
+    /// The era supported by the client, `None` if it is not supported
+    pub supported_era: Option<SupportedEra>,
+}
+
+impl EraSupport {
+    fn new(marker: EraMarker) -> Self {
+        let supported_era = SupportedEra::from_str(&marker.name).ok();
+
+        Self {
+            marker,
+            supported_era,
+        }
+    }
+
+    /// Returns `true` if the client supports the era.
+    pub fn is_supported(&self) -> bool {
+        self.supported_era.is_some()
+    }
+}
+
+/// Result of [check_era_compatibility][crate::Client::check_era_compatibility].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EraCompatibility {
+    /// Current epoch of the aggregator
+    pub epoch: Epoch,
+
+    /// Current era
+    pub current_era: EraSupport,
+
+    /// Next era, if one is announced
+    pub next_era: Option<EraSupport>,
+
+    /// Eras supported by this version of the client
+    pub supported_eras: Vec<SupportedEra>,
+}
+
+impl EraCompatibility {
+    /// Returns `true` if the client supports both the current era and the next era, if any.
+    pub fn is_compatible(&self) -> bool {
+        self.current_era.is_supported()
+            && self.next_era.as_ref().is_none_or(EraSupport::is_supported)
+    }
+
+    /// Names of the current and next eras that the client doesn't support.
+    pub fn unsupported_eras(&self) -> Vec<String> {
+        std::iter::once(&self.current_era)
+            .chain(self.next_era.as_ref())
+            .filter(|era| !era.is_supported())
+            .map(|era| era.marker.name.clone())
+            .collect()
+    }
+}
+
+/// Read the eras at the current epoch of the aggregator and check their support.
+pub(crate) async fn check_era_compatibility(
+    aggregator_client: &dyn AggregatorClient,
+    era_reader: &EraReader,
+) -> MithrilResult<EraCompatibility> {
+    let content = aggregator_client
+        .get_content(AggregatorRequest::GetEpochSettings)
+        .await
+        .with_context(|| "Era compatibility check can not get the epoch settings")?;
+    let epoch_settings: EpochSettingsMessage = serde_json::from_str(&content)
+        .with_context(|| "Era compatibility check can not deserialize the epoch settings")?;
+
+    let token = era_reader
+        .read_era_epoch_token(epoch_settings.epoch)
+        .await
+        .with_context(|| {
+            format!(
+                "Era compatibility check can not read the eras at epoch {}",
+                epoch_settings.epoch
+            )
+        })?;
+
+    Ok(EraCompatibility {
+        epoch: token.get_current_epoch(),
+        current_era: EraSupport::new(token.get_current_era_marker().clone()),
+        next_era: token.get_next_era_marker().cloned().map(EraSupport::new),
+        supported_eras: SupportedEra::eras(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use async_trait::async_trait;
+
+    use crate::aggregator_client::MockAggregatorHTTPClient;
+    use crate::common::era::EraReaderAdapter;
+    use crate::common::StdResult;
+
+    use super::*;
+
+    struct StaticEraReaderAdapter(Vec<EraMarker>);
+
+    #[async_trait]
+    impl EraReaderAdapter for StaticEraReaderAdapter {
+        async fn read(&self) -> StdResult<Vec<EraMarker>> {
+            Ok(self.0.clone())
+        }
+    }
+
+    async fn check_at_epoch(epoch: Epoch, markers: Vec<EraMarker>) -> EraCompatibility {
+        let mut aggregator_client = MockAggregatorHTTPClient::new();
+        aggregator_client
+            .expect_get_content()
+            .withf(|request| *request == AggregatorRequest::GetEpochSettings)
+            .returning(move |_| {
+                Ok(serde_json::to_string(&EpochSettingsMessage {
+                    epoch,
+                    ..EpochSettingsMessage::dummy()
+                })
+                .unwrap())
+            });
+        let era_reader = EraReader::new(std::sync::Arc::new(StaticEraReaderAdapter(markers)));
+
+        check_era_compatibility(&aggregator_client, &era_reader)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn compatible_when_current_and_next_eras_are_supported() {
+        let supported = SupportedEra::dummy().to_string();
+
+        let compatibility = check_at_epoch(
+            Epoch(5),
+            vec![
+                EraMarker::new(&supported, Some(Epoch(1))),
+                EraMarker::new(&supported, Some(Epoch(10))),
+            ],
+        )
+        .await;
+
+        assert_eq!(Epoch(5), compatibility.epoch);
+        assert_eq!(
+            Some(SupportedEra::dummy()),
+            compatibility.current_era.supported_era
+        );
+        assert!(compatibility.is_compatible());
+        assert!(compatibility.unsupported_eras().is_empty());
+    }
+
+    #[tokio::test]
+    async fn report_an_unsupported_announced_era() {
+        let compatibility = check_at_epoch(
+            Epoch(5),
+            vec![
+                EraMarker::new(&SupportedEra::dummy().to_string(), Some(Epoch(1))),
+                EraMarker::new("future-era", Some(Epoch(10))),
+            ],
+        )
+        .await;
+
+        assert!(compatibility.current_era.is_supported());
+        assert_eq!(
+            Some(Epoch(10)),
+            compatibility.next_era.as_ref().unwrap().marker.epoch
+        );
+        assert!(!compatibility.is_compatible());
+        assert_eq!(vec!["future-era"], compatibility.unsupported_eras());
+    }
+
+    #[tokio::test]
+    async fn report_an_unsupported_current_era() {
+        let compatibility = check_at_epoch(
+            Epoch(12),
+            vec![
+                EraMarker::new(&SupportedEra::dummy().to_string(), Some(Epoch(1))),
+                EraMarker::new("future-era", Some(Epoch(10))),
+            ],
+        )
+        .await;
+
+        assert!(!compatibility.current_era.is_supported());
+        assert_eq!(None, compatibility.next_era);
+        assert!(!compatibility.is_compatible());
+    }
+}
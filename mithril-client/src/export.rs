@@ -0,0 +1,384 @@
+//! Export stake distributions and certificate signers as tabular files for analytics.
+//!
+//! A [Mithril stake distribution][MithrilStakeDistribution] or the signers listed in the metadata
+//! of a [certificate][MithrilCertificate] are flattened to [SignerStakeRecord]s, one per signer,
+//! that can be written, using crate feature **csv**, as CSV with [write_csv] or, using crate
+//! feature **parquet**, as a Parquet file with [write_parquet].
+//!
+//! # Export the latest Mithril stake distribution as CSV
+//!
+//! ```no_run
+//! # #[cfg(feature = "csv")]
+//! # async fn run() -> mithril_client::MithrilResult<()> {
+//! use mithril_client::export::{write_csv, SignerStakeRecord};
+//! use mithril_client::ClientBuilder;
+//!
+//! let client = ClientBuilder::aggregator("YOUR_AGGREGATOR_ENDPOINT", "YOUR_GENESIS_VERIFICATION_KEY").build()?;
+//! let stake_distributions = client.mithril_stake_distribution().list().await?;
+//! let stake_distribution = client
+//!     .mithril_stake_distribution()
+//!     .get(&stake_distributions.first().unwrap().hash)
+//!     .await?
+//!     .unwrap();
+//!
+//! let records = SignerStakeRecord::from_stake_distribution(&stake_distribution);
+//! write_csv(&records, std::fs::File::create("stake_distribution.csv")?)?;
+//! #    Ok(())
+//! # }
+//! ```
+//!
+//! # Export the signers of a certificate
+//!
+//! ```no_run
+//! # #[cfg(feature = "csv")]
+//! # async fn run() -> mithril_client::MithrilResult<()> {
+//! use mithril_client::export::{write_csv, SignerStakeRecord};
+//! use mithril_client::ClientBuilder;
+//!
+//! let client = ClientBuilder::aggregator("YOUR_AGGREGATOR_ENDPOINT", "YOUR_GENESIS_VERIFICATION_KEY").build()?;
+//! let certificate = client.certificate().get("CERTIFICATE_HASH").await?.unwrap();
+//!
+//! let records = SignerStakeRecord::from_certificate(&certificate);
+//! write_csv(&records, std::io::stdout())?;
+//! #    Ok(())
+//! # }
+//! ```
+
+#[cfg(any(feature = "csv", feature = "parquet"))]
+use anyhow::Context;
+use serde::Serialize;
+#[cfg(any(feature = "csv", feature = "parquet"))]
+use std::io::Write;
+
+use crate::common::crypto_helper::KESPeriod;
+use crate::common::entities::{PartyId, Stake};
+#[cfg(any(feature = "csv", feature = "parquet"))]
+use crate::MithrilResult;
+use crate::{MithrilCertificate, MithrilStakeDistribution};
+
+/// The stake of a signer, as exported by the [export][crate::export] module.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SignerStakeRecord {
+    /// Epoch of the stake distribution or of the certificate
+    pub epoch: u64,
+
+    /// Hash of the certificate that certifies the stake distribution or lists the signer
+    pub certificate_hash: String,
+
+    /// Party identifier of the signer
+    pub party_id: PartyId,
+
+    /// Stake of the signer
+    pub stake: Stake,
+
+    /// Share of the stake of the signer among all the exported signers, between 0 and 1
+    pub stake_share: f64,
+
+    /// KES period of the registration of the signer, only known for stake distributions
+    pub kes_period: Option<KESPeriod>,
+}
+
+impl SignerStakeRecord {
+    /// Flatten the signers of a Mithril stake distribution.
+    pub fn from_stake_distribution(stake_distribution: &MithrilStakeDistribution) -> Vec<Self> {
+        let total_stake = stake_distribution
+            .signers_with_stake
+            .iter()
+            .map(|signer| signer.stake)
+            .sum();
+
+        stake_distribution
+            .signers_with_stake
+            .iter()
+            .map(|signer| Self {
+                epoch: *stake_distribution.epoch,
+                certificate_hash: stake_distribution.certificate_hash.clone(),
+                party_id: signer.party_id.clone(),
+                stake: signer.stake,
+                stake_share: stake_share(signer.stake, total_stake),
+                kes_period: signer.kes_period,
+            })
+            .collect()
+    }
+
+    /// Flatten the signers listed in the metadata of a certificate, the stake share is computed
+    /// among the signers of the certificate.
+    pub fn from_certificate(certificate: &MithrilCertificate) -> Vec<Self> {
+        let total_stake = certificate
+            .metadata
+            .signers
+            .iter()
+            .map(|signer| signer.stake)
+            .sum();
+
+        certificate
+            .metadata
+            .signers
+            .iter()
+            .map(|signer| Self {
+                epoch: *certificate.beacon.epoch,
+                certificate_hash: certificate.hash.clone(),
+                party_id: signer.party_id.clone(),
+                stake: signer.stake,
+                stake_share: stake_share(signer.stake, total_stake),
+                kes_period: None,
+            })
+            .collect()
+    }
+}
+
+fn stake_share(stake: Stake, total_stake: Stake) -> f64 {
+    if total_stake == 0 {
+        0.0
+    } else {
+        stake as f64 / total_stake as f64
+    }
+}
+
+/// Write the records as CSV, with a header line.
+#[cfg(feature = "csv")]
+#[cfg_attr(docsrs, doc(cfg(feature = "csv")))]
+pub fn write_csv<W: Write>(records: &[SignerStakeRecord], writer: W) -> MithrilResult<()> {
+    let mut csv_writer = csv::Writer::from_writer(writer);
+    for record in records {
+        csv_writer
+            .serialize(record)
+            .with_context(|| format!("Could not write the CSV record of '{}'", record.party_id))?;
+    }
+    csv_writer
+        .flush()
+        .with_context(|| "Could not flush the CSV records")?;
+
+    Ok(())
+}
+
+/// Write the records as a Parquet file with a single row group.
+#[cfg(feature = "parquet")]
+#[cfg_attr(docsrs, doc(cfg(feature = "parquet")))]
+pub fn write_parquet<W: Write + Send>(
+    records: &[SignerStakeRecord],
+    writer: W,
+) -> MithrilResult<()> {
+    use parquet::data_type::{ByteArray, ByteArrayType, DoubleType, Int32Type, Int64Type};
+    use parquet::file::{properties::WriterProperties, writer::SerializedFileWriter};
+    use parquet::schema::parser::parse_message_type;
+    use std::sync::Arc;
+
+    const SCHEMA: &str = "
+        message signer_stake {
+            REQUIRED INT64 epoch (INTEGER(64, false));
+            REQUIRED BYTE_ARRAY certificate_hash (UTF8);
+            REQUIRED BYTE_ARRAY party_id (UTF8);
+            REQUIRED INT64 stake (INTEGER(64, false));
+            REQUIRED DOUBLE stake_share;
+            OPTIONAL INT32 kes_period (INTEGER(32, false));
+        }
+    ";
+    let schema = Arc::new(parse_message_type(SCHEMA).with_context(|| "Invalid Parquet schema")?);
+    let mut file_writer = SerializedFileWriter::new(
+        writer,
+        schema,
+        Arc::new(WriterProperties::builder().build()),
+    )
+    .with_context(|| "Could not create the Parquet writer")?;
+
+    // Unsigned values are stored in signed physical types, as required by the Parquet format
+    let mut row_group_writer = file_writer
+        .next_row_group()
+        .with_context(|| "Could not create the Parquet row group")?;
+    let mut column_index = 0;
+    while let Some(mut column_writer) = row_group_writer
+        .next_column()
+        .with_context(|| "Could not create the Parquet column")?
+    {
+        let result = match column_index {
+            0 => column_writer.typed::<Int64Type>().write_batch(
+                &records.iter().map(|r| r.epoch as i64).collect::<Vec<_>>(),
+                None,
+                None,
+            ),
+            1 | 2 => column_writer.typed::<ByteArrayType>().write_batch(
+                &records
+                    .iter()
+                    .map(|r| {
+                        let value = if column_index == 1 {
+                            &r.certificate_hash
+                        } else {
+                            &r.party_id
+                        };
+                        ByteArray::from(value.as_str())
+                    })
+                    .collect::<Vec<_>>(),
+                None,
+                None,
+            ),
+            3 => column_writer.typed::<Int64Type>().write_batch(
+                &records.iter().map(|r| r.stake as i64).collect::<Vec<_>>(),
+                None,
+                None,
+            ),
+            4 => column_writer.typed::<DoubleType>().write_batch(
+                &records.iter().map(|r| r.stake_share).collect::<Vec<_>>(),
+                None,
+                None,
+            ),
+            _ => column_writer.typed::<Int32Type>().write_batch(
+                &records
+                    .iter()
+                    .filter_map(|r| r.kes_period.map(|period| period as i32))
+                    .collect::<Vec<_>>(),
+                Some(
+                    &records
+                        .iter()
+                        .map(|r| i16::from(r.kes_period.is_some()))
+                        .collect::<Vec<_>>(),
+                ),
+                None,
+            ),
+        };
+        result.with_context(|| format!("Could not write the Parquet column {column_index}"))?;
+        column_writer
+            .close()
+            .with_context(|| format!("Could not close the Parquet column {column_index}"))?;
+        column_index += 1;
+    }
+    row_group_writer
+        .close()
+        .with_context(|| "Could not close the Parquet row group")?;
+    file_writer
+        .close()
+        .with_context(|| "Could not close the Parquet writer")?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::entities::StakeDistributionParty;
+    use crate::common::messages::SignerWithStakeMessagePart;
+
+    use super::*;
+
+    fn stake_distribution() -> MithrilStakeDistribution {
+        MithrilStakeDistribution {
+            signers_with_stake: vec![
+                SignerWithStakeMessagePart {
+                    party_id: "pool1".to_string(),
+                    stake: 30,
+                    kes_period: Some(12),
+                    ..SignerWithStakeMessagePart::dummy()
+                },
+                SignerWithStakeMessagePart {
+                    party_id: "pool2".to_string(),
+                    stake: 10,
+                    kes_period: None,
+                    ..SignerWithStakeMessagePart::dummy()
+                },
+            ],
+            ..MithrilStakeDistribution::dummy()
+        }
+    }
+
+    #[test]
+    fn flatten_a_stake_distribution() {
+        let stake_distribution = stake_distribution();
+
+        let records = SignerStakeRecord::from_stake_distribution(&stake_distribution);
+
+        assert_eq!(
+            vec![
+                SignerStakeRecord {
+                    epoch: *stake_distribution.epoch,
+                    certificate_hash: stake_distribution.certificate_hash.clone(),
+                    party_id: "pool1".to_string(),
+                    stake: 30,
+                    stake_share: 0.75,
+                    kes_period: Some(12),
+                },
+                SignerStakeRecord {
+                    epoch: *stake_distribution.epoch,
+                    certificate_hash: stake_distribution.certificate_hash.clone(),
+                    party_id: "pool2".to_string(),
+                    stake: 10,
+                    stake_share: 0.25,
+                    kes_period: None,
+                },
+            ],
+            records
+        );
+    }
+
+    #[test]
+    fn flatten_the_signers_of_a_certificate() {
+        let mut certificate = MithrilCertificate::dummy();
+        certificate.metadata.signers = vec![
+            StakeDistributionParty {
+                party_id: "pool1".to_string(),
+                stake: 1,
+            },
+            StakeDistributionParty {
+                party_id: "pool2".to_string(),
+                stake: 3,
+            },
+        ];
+
+        let records = SignerStakeRecord::from_certificate(&certificate);
+
+        assert_eq!(
+            vec![("pool1", 0.25), ("pool2", 0.75)],
+            records
+                .iter()
+                .map(|r| (r.party_id.as_str(), r.stake_share))
+                .collect::<Vec<_>>()
+        );
+        assert!(records
+            .iter()
+            .all(|r| r.certificate_hash == certificate.hash && r.kes_period.is_none()));
+    }
+
+    #[cfg(feature = "csv")]
+    #[test]
+    fn write_records_as_csv() {
+        let mut records = SignerStakeRecord::from_stake_distribution(&stake_distribution());
+        records.iter_mut().for_each(|r| {
+            r.epoch = 7;
+            r.certificate_hash = "certificate-hash".to_string();
+        });
+        let mut csv = vec![];
+
+        write_csv(&records, &mut csv).unwrap();
+
+        assert_eq!(
+            "epoch,certificate_hash,party_id,stake,stake_share,kes_period\n\
+            7,certificate-hash,pool1,30,0.75,12\n\
+            7,certificate-hash,pool2,10,0.25,\n",
+            String::from_utf8(csv).unwrap()
+        );
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn write_records_as_parquet() {
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+        use parquet::record::RowAccessor;
+
+        let dir = crate::test_utils::get_test_directory("export", "write_records_as_parquet");
+        let path = dir.join("stake_distribution.parquet");
+        let records = SignerStakeRecord::from_stake_distribution(&stake_distribution());
+
+        write_parquet(&records, std::fs::File::create(&path).unwrap()).unwrap();
+
+        let reader = SerializedFileReader::new(std::fs::File::open(&path).unwrap()).unwrap();
+        let rows = reader
+            .get_row_iter(None)
+            .unwrap()
+            .map(|row| row.unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(2, rows.len());
+        assert_eq!("pool1", rows[0].get_string(2).unwrap());
+        assert_eq!(30, rows[0].get_ulong(3).unwrap());
+        assert_eq!(0.25, rows[1].get_double(4).unwrap());
+        assert_eq!(12, rows[0].get_uint(5).unwrap());
+        assert!(rows[1].get_uint(5).is_err());
+    }
+}
@@ -0,0 +1,228 @@
+//! Failover of the requests to other aggregator endpoints.
+//!
+//! When fallback endpoints are given to the client using
+//! [ClientBuilder::with_fallback_aggregator_endpoints][crate::ClientBuilder::with_fallback_aggregator_endpoints],
+//! each request that fails because the aggregator couldn't be reached, answered with a
+//! technical error or with a non JSON response (ie: a captive portal or a misconfigured proxy
+//! answering with an HTML page) is sent again to the next endpoint.
+//!
+//! The endpoint that answered last is kept for the following requests.
+//!
+//! # Fall back to a second aggregator
+//!
+//! ```no_run
+//! # async fn run() -> mithril_client::MithrilResult<()> {
+//! use mithril_client::ClientBuilder;
+//!
+//! let client = ClientBuilder::aggregator("YOUR_AGGREGATOR_ENDPOINT", "YOUR_GENESIS_VERIFICATION_KEY")
+//!     .with_fallback_aggregator_endpoints(&["YOUR_FALLBACK_AGGREGATOR_ENDPOINT"])
+//!     .build()?;
+//!
+//! let snapshots = client.snapshot().list().await?;
+//! #    Ok(())
+//! # }
+//! ```
+
+use async_trait::async_trait;
+use slog::{warn, Logger};
+use std::io::Read;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use crate::aggregator_client::{AggregatorClient, AggregatorClientError, AggregatorRequest};
+
+/// An [AggregatorClient] that sends the requests that failed because of an unusable aggregator
+/// to the next of its aggregator clients.
+pub struct FailoverAggregatorClient {
+    aggregator_clients: Vec<Arc<dyn AggregatorClient>>,
+    current: AtomicUsize,
+    logger: Logger,
+}
+
+impl FailoverAggregatorClient {
+    /// Constructs a new `FailoverAggregatorClient`, the aggregator clients are tried in the
+    /// given order.
+    pub fn new(aggregator_clients: Vec<Arc<dyn AggregatorClient>>, logger: Logger) -> Self {
+        Self {
+            aggregator_clients,
+            current: AtomicUsize::new(0),
+            logger,
+        }
+    }
+
+    fn should_fail_over(error: &AggregatorClientError) -> bool {
+        matches!(
+            error,
+            AggregatorClientError::NonJsonResponse { .. }
+                | AggregatorClientError::RemoteServerTechnical(_)
+                | AggregatorClientError::SubsystemError(_)
+        )
+    }
+
+    async fn send<T, F, Fut>(
+        &self,
+        request: &AggregatorRequest,
+        send_request: F,
+    ) -> Result<T, AggregatorClientError>
+    where
+        F: Fn(Arc<dyn AggregatorClient>) -> Fut,
+        Fut: std::future::Future<Output = Result<T, AggregatorClientError>>,
+    {
+        let first = self.current.load(Ordering::Relaxed);
+        let mut last_error = None;
+        for attempt in 0..self.aggregator_clients.len() {
+            let index = (first + attempt) % self.aggregator_clients.len();
+            match send_request(self.aggregator_clients[index].clone()).await {
+                Err(error) if Self::should_fail_over(&error) => {
+                    warn!(
+                        self.logger,
+                        "Request to the aggregator failed, failing over to the next endpoint";
+                        "route" => request.route(), "endpoint_index" => index, "error" => ?error
+                    );
+                    last_error = Some(error);
+                }
+                result => {
+                    self.current.store(index, Ordering::Relaxed);
+                    return result;
+                }
+            }
+        }
+
+        Err(last_error.unwrap_or_else(|| {
+            AggregatorClientError::SubsystemError(anyhow::anyhow!(
+                "No aggregator client configured for failover"
+            ))
+        }))
+    }
+}
+
+#[cfg_attr(target_family = "wasm", async_trait(?Send))]
+#[cfg_attr(not(target_family = "wasm"), async_trait)]
+impl AggregatorClient for FailoverAggregatorClient {
+    async fn get_content(
+        &self,
+        request: AggregatorRequest,
+    ) -> Result<String, AggregatorClientError> {
+        self.send(&request, |client| {
+            let request = request.clone();
+            async move { client.get_content(request).await }
+        })
+        .await
+    }
+
+    async fn get_content_reader(
+        &self,
+        request: AggregatorRequest,
+    ) -> Result<Box<dyn Read + Send>, AggregatorClientError> {
+        self.send(&request, |client| {
+            let request = request.clone();
+            async move { client.get_content_reader(request).await }
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::anyhow;
+
+    use crate::aggregator_client::MockAggregatorHTTPClient;
+    use crate::test_utils;
+
+    use super::*;
+
+    fn html_response() -> AggregatorClientError {
+        AggregatorClientError::NonJsonResponse {
+            content_type: "text/html".to_string(),
+            snippet: "<html>Please log in</html>".to_string(),
+        }
+    }
+
+    fn failover_client(clients: Vec<MockAggregatorHTTPClient>) -> FailoverAggregatorClient {
+        FailoverAggregatorClient::new(
+            clients
+                .into_iter()
+                .map(|client| Arc::new(client) as Arc<dyn AggregatorClient>)
+                .collect(),
+            test_utils::test_logger(),
+        )
+    }
+
+    #[tokio::test]
+    async fn non_json_responses_are_failed_over_to_the_next_endpoint() {
+        let mut captive_portal = MockAggregatorHTTPClient::new();
+        captive_portal
+            .expect_get_content()
+            .returning(|_| Err(html_response()))
+            .times(1);
+        let mut aggregator = MockAggregatorHTTPClient::new();
+        aggregator
+            .expect_get_content()
+            .returning(|_| Ok("content".to_string()))
+            .times(2);
+        let client = failover_client(vec![captive_portal, aggregator]);
+
+        for _ in 0..2 {
+            let content = client
+                .get_content(AggregatorRequest::ListCertificates)
+                .await
+                .unwrap();
+            assert_eq!("content", content);
+        }
+    }
+
+    #[tokio::test]
+    async fn logical_errors_are_not_failed_over() {
+        let mut aggregator = MockAggregatorHTTPClient::new();
+        aggregator
+            .expect_get_content()
+            .returning(|_| {
+                Err(AggregatorClientError::RemoteServerLogical(anyhow!(
+                    "not found"
+                )))
+            })
+            .times(1);
+        let mut fallback = MockAggregatorHTTPClient::new();
+        fallback.expect_get_content().never();
+        let client = failover_client(vec![aggregator, fallback]);
+
+        let error = client
+            .get_content(AggregatorRequest::ListCertificates)
+            .await
+            .expect_err("A logical error should be returned");
+
+        assert!(matches!(
+            error,
+            AggregatorClientError::RemoteServerLogical(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn the_last_error_is_returned_when_all_endpoints_fail() {
+        let mut captive_portal = MockAggregatorHTTPClient::new();
+        captive_portal
+            .expect_get_content()
+            .returning(|_| {
+                Err(AggregatorClientError::SubsystemError(anyhow!(
+                    "unreachable"
+                )))
+            })
+            .times(1);
+        let mut fallback = MockAggregatorHTTPClient::new();
+        fallback
+            .expect_get_content()
+            .returning(|_| Err(html_response()))
+            .times(1);
+        let client = failover_client(vec![captive_portal, fallback]);
+
+        let error = client
+            .get_content(AggregatorRequest::ListCertificates)
+            .await
+            .expect_err("All endpoints failing should return an error");
+
+        assert!(matches!(
+            error,
+            AggregatorClientError::NonJsonResponse { .. }
+        ));
+    }
+}
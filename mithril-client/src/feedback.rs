@@ -5,7 +5,11 @@
 //!
 //! Those tasks are:
 //! - Snapshot download
+//! - Snapshot digest computation, see [MessageBuilder::compute_snapshot_digest][crate::MessageBuilder::compute_snapshot_digest]
 //! - Certificate chain validation
+//! - Certificate chain watching, see [chain_watcher][crate::chain_watcher]
+//!
+//! Requests rate limited by the aggregator are also reported, see [retry][crate::retry].
 //!
 //! In order to have feedbacks for those tasks, a mechanism is available.
 //!
@@ -50,14 +54,73 @@
 //! Nov 08 14:42:05.477 INFO Certificate validated, certificate_chain_validation_id: ab623989-b0ac-4031-8522-1370958bbb4e, certificate_hash: 660b3d426a95303254bb255a56bed443616ea63c4d721ea77433b920d7ebdf62
 //! Nov 08 14:42:05.477 INFO Certificate chain validated, certificate_chain_validation_id: ab623989-b0ac-4031-8522-1370958bbb4e
 //! ```
+//!
+//! # Summarizing the progress of the operations
+//!
+//! A download can send thousands of progress events, a frontend only refreshing its display
+//! every second can use a [SummarizingFeedbackReceiver]: it aggregates the events into a
+//! [ProgressSummary] of the ongoing operations, delivered at most once per interval.
+//!
+//! ```no_run
+//! use std::sync::Arc;
+//! use std::time::Duration;
+//! # async fn run() -> mithril_client::MithrilResult<()> {
+//! use mithril_client::ClientBuilder;
+//! use mithril_client::feedback::SummarizingFeedbackReceiver;
+//!
+//! let receiver = SummarizingFeedbackReceiver::new(Duration::from_secs(1), |summary| {
+//!     for operation in summary.operations {
+//!         println!(
+//!             "{} {}: {:.1}%, {:.0}/s",
+//!             operation.kind,
+//!             operation.id,
+//!             operation.percent().unwrap_or_default(),
+//!             operation.rate
+//!         );
+//!     }
+//! });
+//! let client = ClientBuilder::aggregator("YOUR_AGGREGATOR_ENDPOINT", "YOUR_GENESIS_VERIFICATION_KEY")
+//!     .add_feedback_receiver(Arc::new(receiver))
+//!     .build()?;
+//! #    Ok(())
+//! # }
+//! ```
+//!
+//! # Correlating the events of an operation
+//!
+//! Events from the download, the unpacking and the verification of a snapshot each carry their
+//! own identifier. To group all the events belonging to one run, the run can be scoped in an
+//! [OperationContext]: the receivers are then called with
+//! [`handle_operation_event`][FeedbackReceiver::handle_operation_event] for every event sent
+//! while the run is ongoing, including by nested operations.
+//!
+//! ```no_run
+//! # async fn run() -> mithril_client::MithrilResult<()> {
+//! use mithril_client::feedback::OperationContext;
+//! use mithril_client::ClientBuilder;
+//!
+//! let client = ClientBuilder::aggregator("YOUR_AGGREGATOR_ENDPOINT", "YOUR_GENESIS_VERIFICATION_KEY").build()?;
+//! let operation = OperationContext::new();
+//!
+//! let certificate = operation
+//!     .scope(client.certificate().verify_chain("CERTIFICATE_HASH"))
+//!     .await?;
+//! #    Ok(())
+//! # }
+//! ```
 
 use async_trait::async_trait;
 use serde::Serialize;
-use slog::{info, Logger};
-use std::sync::{Arc, RwLock};
+use slog::{info, o, warn, Logger};
+use std::cell::RefCell;
+use std::future::Future;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
 use strum::Display;
 use uuid::Uuid;
 
+use crate::utils::time::Instant;
+
 /// Event that can be reported by a [FeedbackReceiver].
 #[derive(Debug, Clone, Eq, PartialEq, Display, Serialize)]
 #[strum(serialize_all = "PascalCase")]
@@ -86,6 +149,31 @@ pub enum MithrilEvent {
         /// Unique identifier used to track this specific snapshot download
         download_id: String,
     },
+    /// A snapshot digest computation has started
+    SnapshotDigestComputationStarted {
+        /// Unique identifier used to track this specific digest computation
+        digest_computation_id: String,
+        /// Number of the last immutable file included in the digest
+        immutable_file_number: u64,
+    },
+    /// A snapshot digest computation is in progress
+    SnapshotDigestComputationProgress {
+        /// Unique identifier used to track this specific digest computation
+        digest_computation_id: String,
+        /// Number of immutable files that have been hashed
+        files_hashed: usize,
+        /// Number of immutable files to hash
+        total_files: usize,
+        /// Number of bytes that have been read and hashed
+        bytes_hashed: u64,
+        /// Estimated time before the end of the computation, if it can be estimated yet
+        estimated_remaining: Option<Duration>,
+    },
+    /// A snapshot digest computation has completed
+    SnapshotDigestComputationCompleted {
+        /// Unique identifier used to track this specific digest computation
+        digest_computation_id: String,
+    },
     /// A certificate chain validation has started
     CertificateChainValidationStarted {
         /// Unique identifier used to track this specific certificate chain validation
@@ -103,6 +191,29 @@ pub enum MithrilEvent {
         /// Unique identifier used to track this specific certificate chain validation
         certificate_chain_validation_id: String,
     },
+    /// The aggregator rate limited a request, it will be sent again after a delay.
+    AggregatorRateLimited {
+        /// Route of the rate limited request
+        route: String,
+        /// Delay before the request is sent again
+        retry_after: Duration,
+    },
+    /// A new certificate has been verified by a [ChainWatcher][crate::chain_watcher::ChainWatcher].
+    NewCertificateVerified {
+        /// The verified certificate hash
+        certificate_hash: String,
+    },
+    /// The latest certificate chain seen by a
+    /// [ChainWatcher][crate::chain_watcher::ChainWatcher] is invalid or not chained to the last
+    /// verified certificate.
+    CertificateChainRegression {
+        /// Hash of the latest certificate of the regressed chain
+        certificate_hash: String,
+        /// Hash of the last verified certificate, if any
+        last_verified_certificate_hash: Option<String>,
+        /// Reason of the regression
+        reason: String,
+    },
 }
 
 impl MithrilEvent {
@@ -111,6 +222,11 @@ impl MithrilEvent {
         Uuid::new_v4().to_string()
     }
 
+    /// Generate a random unique identifier to identify a snapshot digest computation
+    pub fn new_digest_computation_id() -> String {
+        Uuid::new_v4().to_string()
+    }
+
     /// Generate a random unique identifier to identify a certificate chain validation
     pub fn new_certificate_chain_validation_id() -> String {
         Uuid::new_v4().to_string()
@@ -122,6 +238,17 @@ impl MithrilEvent {
             MithrilEvent::SnapshotDownloadStarted { download_id, .. } => download_id,
             MithrilEvent::SnapshotDownloadProgress { download_id, .. } => download_id,
             MithrilEvent::SnapshotDownloadCompleted { download_id } => download_id,
+            MithrilEvent::SnapshotDigestComputationStarted {
+                digest_computation_id,
+                ..
+            } => digest_computation_id,
+            MithrilEvent::SnapshotDigestComputationProgress {
+                digest_computation_id,
+                ..
+            } => digest_computation_id,
+            MithrilEvent::SnapshotDigestComputationCompleted {
+                digest_computation_id,
+            } => digest_computation_id,
             MithrilEvent::CertificateChainValidationStarted {
                 certificate_chain_validation_id,
             } => certificate_chain_validation_id,
@@ -132,10 +259,103 @@ impl MithrilEvent {
             MithrilEvent::CertificateChainValidated {
                 certificate_chain_validation_id,
             } => certificate_chain_validation_id,
+            MithrilEvent::AggregatorRateLimited { route, .. } => route,
+            MithrilEvent::NewCertificateVerified { certificate_hash } => certificate_hash,
+            MithrilEvent::CertificateChainRegression {
+                certificate_hash, ..
+            } => certificate_hash,
         }
     }
 }
 
+thread_local! {
+    static CURRENT_OPERATION: RefCell<Option<OperationContext>> = const { RefCell::new(None) };
+}
+
+/// Context of an operation, used to correlate all the [events][MithrilEvent] sent while the
+/// operation runs.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct OperationContext {
+    operation_id: String,
+    parent_operation_id: Option<String>,
+}
+
+impl OperationContext {
+    /// Create a new [OperationContext] with a random unique identifier.
+    pub fn new() -> Self {
+        Self::with_id(Uuid::new_v4().to_string())
+    }
+
+    /// Create a new [OperationContext] with the given identifier.
+    pub fn with_id<T: Into<String>>(operation_id: T) -> Self {
+        Self {
+            operation_id: operation_id.into(),
+            parent_operation_id: None,
+        }
+    }
+
+    /// Create the context of an operation nested in this one.
+    pub fn child(&self) -> Self {
+        Self {
+            parent_operation_id: Some(self.operation_id.clone()),
+            ..Self::new()
+        }
+    }
+
+    /// Unique identifier of the operation
+    pub fn operation_id(&self) -> &str {
+        &self.operation_id
+    }
+
+    /// Identifier of the operation this one is nested in, if any
+    pub fn parent_operation_id(&self) -> Option<&str> {
+        self.parent_operation_id.as_deref()
+    }
+
+    /// Context of the operation currently running, if any.
+    pub fn current() -> Option<OperationContext> {
+        CURRENT_OPERATION.with(|current| current.borrow().clone())
+    }
+
+    /// Run the given future in this context: the events sent while it is polled are correlated
+    /// to this operation.
+    pub async fn scope<F: Future>(&self, future: F) -> F::Output {
+        let mut future = std::pin::pin!(future);
+
+        futures::future::poll_fn(|cx| {
+            let _guard = OperationContextGuard::enter(self.clone());
+            future.as_mut().poll(cx)
+        })
+        .await
+    }
+}
+
+impl Default for OperationContext {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Make an [OperationContext] the current one until dropped, restoring the previous one.
+struct OperationContextGuard {
+    previous: Option<OperationContext>,
+}
+
+impl OperationContextGuard {
+    fn enter(context: OperationContext) -> Self {
+        let previous = CURRENT_OPERATION.with(|current| current.replace(Some(context)));
+
+        Self { previous }
+    }
+}
+
+impl Drop for OperationContextGuard {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        CURRENT_OPERATION.with(|current| *current.borrow_mut() = previous);
+    }
+}
+
 /// A sender of [MithrilEvent].
 ///
 /// It uses Arc internally so it can be cloned at will.
@@ -153,9 +373,21 @@ impl FeedbackSender {
     }
 
     /// Send the given event to the known receivers.
+    ///
+    /// If the event is sent in the scope of an [OperationContext] the receivers are given that
+    /// context too.
     pub async fn send_event(&self, event: MithrilEvent) {
+        let context = OperationContext::current();
+
         for receiver in &self.receivers {
-            receiver.handle_event(event.clone()).await;
+            match &context {
+                Some(context) => {
+                    receiver
+                        .handle_operation_event(context, event.clone())
+                        .await
+                }
+                None => receiver.handle_event(event.clone()).await,
+            }
         }
     }
 }
@@ -166,6 +398,16 @@ impl FeedbackSender {
 pub trait FeedbackReceiver: Sync + Send {
     /// Callback called by a [FeedbackSender] when it needs to send an [event][MithrilEvent].
     async fn handle_event(&self, event: MithrilEvent);
+
+    /// Callback called by a [FeedbackSender] when it needs to send an [event][MithrilEvent]
+    /// in the scope of an [operation][OperationContext].
+    ///
+    /// By default the context is ignored and the event is handled by
+    /// [`handle_event`][FeedbackReceiver::handle_event].
+    async fn handle_operation_event(&self, context: &OperationContext, event: MithrilEvent) {
+        let _ = context;
+        self.handle_event(event).await
+    }
 }
 
 /// A [FeedbackReceiver] that writes the event it receives in a [slog logger][Logger].
@@ -178,12 +420,8 @@ impl SlogFeedbackReceiver {
     pub fn new(logger: Logger) -> SlogFeedbackReceiver {
         Self { logger }
     }
-}
 
-#[cfg_attr(target_family = "wasm", async_trait(?Send))]
-#[cfg_attr(not(target_family = "wasm"), async_trait)]
-impl FeedbackReceiver for SlogFeedbackReceiver {
-    async fn handle_event(&self, event: MithrilEvent) {
+    fn log_event(logger: &Logger, event: MithrilEvent) {
         match event {
             MithrilEvent::SnapshotDownloadStarted {
                 digest,
@@ -191,7 +429,7 @@ impl FeedbackReceiver for SlogFeedbackReceiver {
                 size,
             } => {
                 info!(
-                    self.logger,
+                    logger,
                     "Snapshot download started";
                     "size" => size,
                     "digest" => digest,
@@ -204,7 +442,7 @@ impl FeedbackReceiver for SlogFeedbackReceiver {
                 size,
             } => {
                 info!(
-                    self.logger,
+                    logger,
                     "Snapshot download in progress ...";
                     "downloaded bytes" => downloaded_bytes,
                     "size" => size,
@@ -212,13 +450,50 @@ impl FeedbackReceiver for SlogFeedbackReceiver {
                 );
             }
             MithrilEvent::SnapshotDownloadCompleted { download_id } => {
-                info!(self.logger, "Snapshot download completed"; "download_id" => download_id);
+                info!(logger, "Snapshot download completed"; "download_id" => download_id);
+            }
+            MithrilEvent::SnapshotDigestComputationStarted {
+                digest_computation_id,
+                immutable_file_number,
+            } => {
+                info!(
+                    logger,
+                    "Snapshot digest computation started";
+                    "immutable_file_number" => immutable_file_number,
+                    "digest_computation_id" => digest_computation_id,
+                );
+            }
+            MithrilEvent::SnapshotDigestComputationProgress {
+                digest_computation_id,
+                files_hashed,
+                total_files,
+                bytes_hashed,
+                estimated_remaining,
+            } => {
+                info!(
+                    logger,
+                    "Snapshot digest computation in progress ...";
+                    "files hashed" => files_hashed,
+                    "total files" => total_files,
+                    "bytes hashed" => bytes_hashed,
+                    "estimated_remaining" => ?estimated_remaining,
+                    "digest_computation_id" => digest_computation_id,
+                );
+            }
+            MithrilEvent::SnapshotDigestComputationCompleted {
+                digest_computation_id,
+            } => {
+                info!(
+                    logger,
+                    "Snapshot digest computation completed";
+                    "digest_computation_id" => digest_computation_id,
+                );
             }
             MithrilEvent::CertificateChainValidationStarted {
                 certificate_chain_validation_id,
             } => {
                 info!(
-                    self.logger,
+                    logger,
                     "Certificate chain validation started";
                     "certificate_chain_validation_id" => certificate_chain_validation_id,
                 );
@@ -228,7 +503,7 @@ impl FeedbackReceiver for SlogFeedbackReceiver {
                 certificate_chain_validation_id,
             } => {
                 info!(
-                    self.logger,
+                    logger,
                     "Certificate validated";
                     "certificate_hash" => certificate_hash,
                     "certificate_chain_validation_id" => certificate_chain_validation_id,
@@ -238,12 +513,322 @@ impl FeedbackReceiver for SlogFeedbackReceiver {
                 certificate_chain_validation_id,
             } => {
                 info!(
-                    self.logger,
+                    logger,
                     "Certificate chain validated";
                     "certificate_chain_validation_id" => certificate_chain_validation_id,
                 );
             }
+            MithrilEvent::AggregatorRateLimited { route, retry_after } => {
+                warn!(
+                    logger,
+                    "Aggregator rate limited the request, retrying later";
+                    "route" => route,
+                    "retry_after" => ?retry_after,
+                );
+            }
+            MithrilEvent::NewCertificateVerified { certificate_hash } => {
+                info!(logger, "New certificate verified"; "certificate_hash" => certificate_hash);
+            }
+            MithrilEvent::CertificateChainRegression {
+                certificate_hash,
+                last_verified_certificate_hash,
+                reason,
+            } => {
+                warn!(
+                    logger,
+                    "Certificate chain regression";
+                    "certificate_hash" => certificate_hash,
+                    "last_verified_certificate_hash" => last_verified_certificate_hash,
+                    "reason" => reason,
+                );
+            }
+        };
+    }
+}
+
+#[cfg_attr(target_family = "wasm", async_trait(?Send))]
+#[cfg_attr(not(target_family = "wasm"), async_trait)]
+impl FeedbackReceiver for SlogFeedbackReceiver {
+    async fn handle_event(&self, event: MithrilEvent) {
+        Self::log_event(&self.logger, event);
+    }
+
+    async fn handle_operation_event(&self, context: &OperationContext, event: MithrilEvent) {
+        let logger = match context.parent_operation_id() {
+            Some(parent_operation_id) => self.logger.new(o!(
+                "operation_id" => context.operation_id().to_string(),
+                "parent_operation_id" => parent_operation_id.to_string(),
+            )),
+            None => self
+                .logger
+                .new(o!("operation_id" => context.operation_id().to_string())),
         };
+        Self::log_event(&logger, event);
+    }
+}
+
+/// Kind of an operation tracked by a [SummarizingFeedbackReceiver].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, Serialize)]
+#[strum(serialize_all = "PascalCase")]
+pub enum OperationKind {
+    /// A snapshot download, its progress is counted in bytes
+    SnapshotDownload,
+    /// A snapshot digest computation, its progress is counted in immutable files
+    SnapshotDigestComputation,
+    /// A certificate chain validation, its progress is counted in certificates
+    CertificateChainValidation,
+}
+
+/// Progress of an operation, see [ProgressSummary].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OperationProgress {
+    /// Unique identifier of the operation, ie: the download id of a snapshot download
+    pub id: String,
+
+    /// Kind of the operation
+    pub kind: OperationKind,
+
+    /// Number of units done: bytes, immutable files or certificates depending on the kind
+    pub done: u64,
+
+    /// Number of units to do, if known
+    pub total: Option<u64>,
+
+    /// Average number of units done per second since the operation started
+    pub rate: f64,
+
+    /// Time elapsed since the operation started
+    pub elapsed: Duration,
+
+    /// The operation has completed
+    pub completed: bool,
+}
+
+impl OperationProgress {
+    /// Percentage of the operation done, if its total is known.
+    pub fn percent(&self) -> Option<f64> {
+        match self.total {
+            _ if self.completed => Some(100.0),
+            Some(total) if total > 0 => Some(self.done as f64 * 100.0 / total as f64),
+            _ => None,
+        }
+    }
+
+    /// Estimated time before the end of the operation, from its total and its average rate.
+    pub fn estimated_remaining(&self) -> Option<Duration> {
+        let total = self.total?;
+        if self.rate <= 0.0 {
+            return None;
+        }
+
+        Some(Duration::from_secs_f64(
+            total.saturating_sub(self.done) as f64 / self.rate,
+        ))
+    }
+}
+
+/// Progress of the operations tracked by a [SummarizingFeedbackReceiver] when it's delivered.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProgressSummary {
+    /// Ongoing operations, and the ones completed since the previous summary, in the order they
+    /// started
+    pub operations: Vec<OperationProgress>,
+}
+
+struct TrackedOperation {
+    progress: OperationProgress,
+    started_at: Instant,
+}
+
+#[derive(Default)]
+struct SummaryState {
+    operations: Vec<TrackedOperation>,
+    last_delivery: Option<Instant>,
+}
+
+impl SummaryState {
+    fn start(&mut self, id: &str, kind: OperationKind, total: Option<u64>) {
+        self.operations
+            .retain(|operation| operation.progress.id != id);
+        self.operations.push(TrackedOperation {
+            progress: OperationProgress {
+                id: id.to_string(),
+                kind,
+                done: 0,
+                total,
+                rate: 0.0,
+                elapsed: Duration::ZERO,
+                completed: false,
+            },
+            started_at: Instant::now(),
+        });
+    }
+
+    fn get_mut(&mut self, id: &str) -> Option<&mut OperationProgress> {
+        self.operations
+            .iter_mut()
+            .find(|operation| operation.progress.id == id)
+            .map(|operation| &mut operation.progress)
+    }
+
+    /// Update the state with the given event, returns `true` if it started or completed an
+    /// operation.
+    fn update(&mut self, event: &MithrilEvent) -> bool {
+        match event {
+            MithrilEvent::SnapshotDownloadStarted {
+                download_id, size, ..
+            } => {
+                self.start(download_id, OperationKind::SnapshotDownload, Some(*size));
+                true
+            }
+            MithrilEvent::SnapshotDownloadProgress {
+                download_id,
+                downloaded_bytes,
+                size,
+            } => {
+                if let Some(progress) = self.get_mut(download_id) {
+                    progress.done = *downloaded_bytes;
+                    progress.total = Some(*size);
+                }
+                false
+            }
+            MithrilEvent::SnapshotDigestComputationStarted {
+                digest_computation_id,
+                ..
+            } => {
+                self.start(
+                    digest_computation_id,
+                    OperationKind::SnapshotDigestComputation,
+                    None,
+                );
+                true
+            }
+            MithrilEvent::SnapshotDigestComputationProgress {
+                digest_computation_id,
+                files_hashed,
+                total_files,
+                ..
+            } => {
+                if let Some(progress) = self.get_mut(digest_computation_id) {
+                    progress.done = *files_hashed as u64;
+                    progress.total = Some(*total_files as u64);
+                }
+                false
+            }
+            MithrilEvent::CertificateChainValidationStarted {
+                certificate_chain_validation_id,
+            } => {
+                self.start(
+                    certificate_chain_validation_id,
+                    OperationKind::CertificateChainValidation,
+                    None,
+                );
+                true
+            }
+            MithrilEvent::CertificateValidated {
+                certificate_chain_validation_id,
+                ..
+            } => {
+                if let Some(progress) = self.get_mut(certificate_chain_validation_id) {
+                    progress.done += 1;
+                }
+                false
+            }
+            MithrilEvent::SnapshotDownloadCompleted { download_id: id }
+            | MithrilEvent::SnapshotDigestComputationCompleted {
+                digest_computation_id: id,
+            }
+            | MithrilEvent::CertificateChainValidated {
+                certificate_chain_validation_id: id,
+            } => match self.get_mut(id) {
+                Some(progress) => {
+                    if let Some(total) = progress.total {
+                        progress.done = total;
+                    }
+                    progress.completed = true;
+                    true
+                }
+                None => false,
+            },
+            MithrilEvent::AggregatorRateLimited { .. }
+            | MithrilEvent::NewCertificateVerified { .. }
+            | MithrilEvent::CertificateChainRegression { .. } => false,
+        }
+    }
+
+    /// Summarize the progress of the operations and forget the completed ones.
+    fn summarize(&mut self, now: Instant) -> ProgressSummary {
+        self.last_delivery = Some(now);
+        let operations = self
+            .operations
+            .iter()
+            .map(|operation| {
+                let elapsed = now.saturating_duration_since(operation.started_at);
+                let rate = match elapsed.as_secs_f64() {
+                    seconds if seconds > 0.0 => operation.progress.done as f64 / seconds,
+                    _ => 0.0,
+                };
+
+                OperationProgress {
+                    rate,
+                    elapsed,
+                    ..operation.progress.clone()
+                }
+            })
+            .collect();
+        self.operations
+            .retain(|operation| !operation.progress.completed);
+
+        ProgressSummary { operations }
+    }
+}
+
+/// A [FeedbackReceiver] that aggregates the events it receives into a [ProgressSummary] of the
+/// ongoing operations, delivered to a callback at most once per interval.
+///
+/// A summary is also delivered each time an operation starts or completes, so the completion of
+/// every operation is reported. Events that are not part of an operation with a progress, ie:
+/// [AggregatorRateLimited][MithrilEvent::AggregatorRateLimited], are ignored.
+pub struct SummarizingFeedbackReceiver {
+    interval: Duration,
+    on_summary: Box<dyn Fn(ProgressSummary) + Send + Sync>,
+    state: Mutex<SummaryState>,
+}
+
+impl SummarizingFeedbackReceiver {
+    /// Create a new [SummarizingFeedbackReceiver] delivering the summaries to `on_summary` at
+    /// most once per `interval`.
+    pub fn new<F>(interval: Duration, on_summary: F) -> Self
+    where
+        F: Fn(ProgressSummary) + Send + Sync + 'static,
+    {
+        Self {
+            interval,
+            on_summary: Box::new(on_summary),
+            state: Mutex::new(SummaryState::default()),
+        }
+    }
+}
+
+#[cfg_attr(target_family = "wasm", async_trait(?Send))]
+#[cfg_attr(not(target_family = "wasm"), async_trait)]
+impl FeedbackReceiver for SummarizingFeedbackReceiver {
+    async fn handle_event(&self, event: MithrilEvent) {
+        let summary = {
+            let mut state = self.state.lock().unwrap();
+            let is_milestone = state.update(&event);
+            let now = Instant::now();
+            let is_due = match state.last_delivery {
+                Some(last) => now.saturating_duration_since(last) >= self.interval,
+                None => true,
+            };
+
+            (is_milestone || is_due).then(|| state.summarize(now))
+        };
+
+        if let Some(summary) = summary {
+            (self.on_summary)(summary);
+        }
     }
 }
 
@@ -572,4 +1157,188 @@ mod tests {
             ]
         );
     }
+
+    struct OperationStackFeedbackReceiver {
+        stacked_events: RwLock<Vec<(Option<OperationContext>, MithrilEvent)>>,
+    }
+
+    #[async_trait]
+    impl FeedbackReceiver for OperationStackFeedbackReceiver {
+        async fn handle_event(&self, event: MithrilEvent) {
+            self.stacked_events.write().unwrap().push((None, event));
+        }
+
+        async fn handle_operation_event(&self, context: &OperationContext, event: MithrilEvent) {
+            self.stacked_events
+                .write()
+                .unwrap()
+                .push((Some(context.clone()), event));
+        }
+    }
+
+    fn download_completed(download_id: &str) -> MithrilEvent {
+        SnapshotDownloadCompleted {
+            download_id: download_id.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn events_sent_in_the_scope_of_an_operation_are_correlated_to_it() {
+        let receiver = Arc::new(OperationStackFeedbackReceiver {
+            stacked_events: RwLock::new(vec![]),
+        });
+        let sender = FeedbackSender::new(&[receiver.clone()]);
+        let operation = OperationContext::with_id("operation");
+
+        sender.send_event(download_completed("before")).await;
+        operation
+            .scope(async {
+                sender.send_event(download_completed("download1")).await;
+                tokio::time::sleep(Duration::from_millis(1)).await;
+                sender.send_event(download_completed("download2")).await;
+            })
+            .await;
+        sender.send_event(download_completed("after")).await;
+
+        assert_eq!(
+            vec![
+                (None, download_completed("before")),
+                (Some(operation.clone()), download_completed("download1")),
+                (Some(operation.clone()), download_completed("download2")),
+                (None, download_completed("after")),
+            ],
+            receiver.stacked_events.read().unwrap().clone()
+        );
+        assert_eq!(None, OperationContext::current());
+    }
+
+    #[tokio::test]
+    async fn nested_operations_are_correlated_to_their_parent() {
+        let receiver = Arc::new(OperationStackFeedbackReceiver {
+            stacked_events: RwLock::new(vec![]),
+        });
+        let sender = FeedbackSender::new(&[receiver.clone()]);
+        let operation = OperationContext::with_id("operation");
+        let nested_operation = operation.child();
+
+        operation
+            .scope(async {
+                nested_operation
+                    .scope(sender.send_event(download_completed("nested")))
+                    .await;
+                sender.send_event(download_completed("parent")).await;
+            })
+            .await;
+
+        assert_eq!(Some("operation"), nested_operation.parent_operation_id());
+        assert_eq!(
+            vec![
+                (Some(nested_operation), download_completed("nested")),
+                (Some(operation), download_completed("parent")),
+            ],
+            receiver.stacked_events.read().unwrap().clone()
+        );
+    }
+
+    #[tokio::test]
+    async fn receivers_ignore_the_operation_context_by_default() {
+        let receiver = Arc::new(StackFeedbackReceiver::new());
+        let sender = FeedbackSender::new(&[receiver.clone()]);
+
+        OperationContext::new()
+            .scope(sender.send_event(download_completed("download1")))
+            .await;
+
+        assert_eq!(
+            vec![download_completed("download1")],
+            receiver.stacked_events()
+        );
+    }
+
+    #[tokio::test]
+    async fn summarize_the_progress_of_the_operations_at_most_once_per_interval() {
+        let summaries = Arc::new(RwLock::new(Vec::new()));
+        let receiver = SummarizingFeedbackReceiver::new(Duration::from_secs(3600), {
+            let summaries = summaries.clone();
+            move |summary| summaries.write().unwrap().push(summary)
+        });
+
+        receiver
+            .handle_event(SnapshotDownloadStarted {
+                digest: "digest".to_string(),
+                download_id: "download".to_string(),
+                size: 100,
+            })
+            .await;
+        for downloaded_bytes in [10, 20, 40] {
+            receiver
+                .handle_event(MithrilEvent::SnapshotDownloadProgress {
+                    download_id: "download".to_string(),
+                    downloaded_bytes,
+                    size: 100,
+                })
+                .await;
+        }
+        receiver
+            .handle_event(MithrilEvent::AggregatorRateLimited {
+                route: "route".to_string(),
+                retry_after: Duration::from_secs(1),
+            })
+            .await;
+        receiver
+            .handle_event(SnapshotDownloadCompleted {
+                download_id: "download".to_string(),
+            })
+            .await;
+
+        let summaries = summaries.read().unwrap();
+        assert_eq!(
+            2,
+            summaries.len(),
+            "only the start and completion should be delivered"
+        );
+
+        let started = &summaries[0].operations[0];
+        assert_eq!(
+            (OperationKind::SnapshotDownload, 0, Some(100), false),
+            (started.kind, started.done, started.total, started.completed)
+        );
+        assert_eq!(Some(0.0), started.percent());
+
+        let completed = &summaries[1].operations[0];
+        assert_eq!(
+            ("download", 100, true),
+            (completed.id.as_str(), completed.done, completed.completed)
+        );
+        assert_eq!(Some(100.0), completed.percent());
+    }
+
+    #[tokio::test]
+    async fn deliver_the_progress_when_the_interval_has_elapsed() {
+        let summaries = Arc::new(RwLock::new(Vec::new()));
+        let receiver = SummarizingFeedbackReceiver::new(Duration::ZERO, {
+            let summaries = summaries.clone();
+            move |summary| summaries.write().unwrap().push(summary)
+        });
+
+        receiver
+            .handle_event(MithrilEvent::CertificateChainValidationStarted {
+                certificate_chain_validation_id: "validation".to_string(),
+            })
+            .await;
+        receiver
+            .handle_event(MithrilEvent::CertificateValidated {
+                certificate_chain_validation_id: "validation".to_string(),
+                certificate_hash: "hash".to_string(),
+            })
+            .await;
+
+        let summaries = summaries.read().unwrap();
+        assert_eq!(2, summaries.len());
+        let progress = &summaries[1].operations[0];
+        assert_eq!(
+            (OperationKind::CertificateChainValidation, 1, None),
+            (progress.kind, progress.done, progress.percent())
+        );
+    }
 }
@@ -0,0 +1,414 @@
+//! C bindings of the client, to embed it in programs that are not written in Rust.
+//!
+//! The bindings expose an opaque [MithrilClientHandle] created with [mithril_client_new] and
+//! released with [mithril_client_free], and functions to verify a certificate chain and to
+//! download and verify a snapshot.
+//!
+//! Every function returns a [MithrilStatus], when it's not [MithrilStatus::Ok] the message of the
+//! error can be read with [mithril_last_error_message] from the same thread. A panic never unwinds
+//! into the caller, it is reported as a [MithrilStatus::Failure].
+//!
+//! Strings returned by the bindings are owned by the caller and must be released with
+//! [mithril_string_free].
+//!
+//! **Note:** _Available using crate feature_ **ffi**, the library must be built as a `cdylib`
+//! or a `staticlib`.
+//!
+//! ```c
+//! MithrilClientHandle *client = NULL;
+//! if (mithril_client_new("YOUR_AGGREGATOR_ENDPOINT", "YOUR_GENESIS_VERIFICATION_KEY", &client) != MITHRIL_STATUS_OK) {
+//!     fprintf(stderr, "%s\n", mithril_last_error_message());
+//!     return 1;
+//! }
+//!
+//! char *certificate_hash = NULL;
+//! if (mithril_client_download_snapshot(client, "SNAPSHOT_DIGEST", "/tmp/db", &certificate_hash) == MITHRIL_STATUS_OK) {
+//!     printf("Snapshot certified by %s\n", certificate_hash);
+//!     mithril_string_free(certificate_hash);
+//! }
+//!
+//! mithril_client_free(client);
+//! ```
+
+use anyhow::{anyhow, Context};
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::AssertUnwindSafe;
+use std::path::Path;
+use tokio::runtime::Runtime;
+
+use crate::snapshot_client::SnapshotClientError;
+use crate::{Client, ClientBuilder, MithrilResult};
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Outcome of a call to the bindings.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MithrilStatus {
+    /// The call succeeded.
+    Ok = 0,
+
+    /// An argument is null or is not a valid UTF-8 string.
+    InvalidArgument = 1,
+
+    /// The requested artifact doesn't exist.
+    NotFound = 2,
+
+    /// The call failed, ie: the aggregator couldn't be reached or a verification failed.
+    Failure = 3,
+}
+
+/// Opaque handle to a client, created with [mithril_client_new].
+pub struct MithrilClientHandle {
+    client: Client,
+    runtime: Runtime,
+}
+
+fn set_last_error(error: anyhow::Error) {
+    let message = CString::new(format!("{error:?}").replace('\0', " "))
+        .expect("the null characters were removed");
+    LAST_ERROR.with(|last_error| *last_error.borrow_mut() = Some(message));
+}
+
+fn status_of(result: MithrilResult<MithrilStatus>) -> MithrilStatus {
+    match result {
+        Ok(status) => status,
+        Err(error) => {
+            set_last_error(error);
+            MithrilStatus::Failure
+        }
+    }
+}
+
+/// Run the body of an exported function, returns `on_panic` and set the last error if it panics
+/// rather than unwinding into the caller.
+fn catch_panic<T>(on_panic: T, body: impl FnOnce() -> T) -> T {
+    match std::panic::catch_unwind(AssertUnwindSafe(body)) {
+        Ok(value) => value,
+        Err(payload) => {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|message| message.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_default();
+            set_last_error(anyhow!("The call panicked: {message}"));
+            on_panic
+        }
+    }
+}
+
+/// Read a C string argument, returns `None` and set the last error if it's invalid.
+unsafe fn str_argument<'a>(name: &str, value: *const c_char) -> Option<&'a str> {
+    if value.is_null() {
+        set_last_error(anyhow!("Argument '{name}' is null"));
+        return None;
+    }
+
+    match CStr::from_ptr(value).to_str() {
+        Ok(value) => Some(value),
+        Err(error) => {
+            set_last_error(anyhow!(error).context(format!("Argument '{name}' is not valid UTF-8")));
+            None
+        }
+    }
+}
+
+/// Give the ownership of the given string to the caller, through the given output pointer.
+unsafe fn write_string_output(output: *mut *mut c_char, value: &str) -> MithrilResult<()> {
+    let value = CString::new(value).with_context(|| "Output string contains a null character")?;
+    if !output.is_null() {
+        *output = value.into_raw();
+    }
+
+    Ok(())
+}
+
+/// Build a client that fetches data from the aggregator at the given endpoint and verifies them
+/// with the given genesis verification key.
+///
+/// On success the client is written in `client_out`, it must be released with
+/// [mithril_client_free].
+///
+/// # Safety
+///
+/// `aggregator_endpoint` and `genesis_verification_key` must be valid null terminated strings and
+/// `client_out` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn mithril_client_new(
+    aggregator_endpoint: *const c_char,
+    genesis_verification_key: *const c_char,
+    client_out: *mut *mut MithrilClientHandle,
+) -> MithrilStatus {
+    catch_panic(MithrilStatus::Failure, || {
+        let Some(aggregator_endpoint) = str_argument("aggregator_endpoint", aggregator_endpoint)
+        else {
+            return MithrilStatus::InvalidArgument;
+        };
+        let Some(genesis_verification_key) =
+            str_argument("genesis_verification_key", genesis_verification_key)
+        else {
+            return MithrilStatus::InvalidArgument;
+        };
+        if client_out.is_null() {
+            set_last_error(anyhow!("Argument 'client_out' is null"));
+            return MithrilStatus::InvalidArgument;
+        }
+
+        status_of((|| {
+            let client =
+                ClientBuilder::aggregator(aggregator_endpoint, genesis_verification_key).build()?;
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .with_context(|| "Could not build the runtime of the client")?;
+            *client_out = Box::into_raw(Box::new(MithrilClientHandle { client, runtime }));
+
+            Ok(MithrilStatus::Ok)
+        })())
+    })
+}
+
+/// Release a client built with [mithril_client_new].
+///
+/// # Safety
+///
+/// `client` must be null or a pointer returned by [mithril_client_new] that was not released yet.
+#[no_mangle]
+pub unsafe extern "C" fn mithril_client_free(client: *mut MithrilClientHandle) {
+    catch_panic((), || {
+        if !client.is_null() {
+            drop(Box::from_raw(client));
+        }
+    })
+}
+
+/// Verify the chain of the certificate with the given hash.
+///
+/// # Safety
+///
+/// `client` must be a pointer returned by [mithril_client_new] and `certificate_hash` a valid null
+/// terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn mithril_client_verify_certificate_chain(
+    client: *const MithrilClientHandle,
+    certificate_hash: *const c_char,
+) -> MithrilStatus {
+    catch_panic(MithrilStatus::Failure, || {
+        let Some(handle) = client.as_ref() else {
+            set_last_error(anyhow!("Argument 'client' is null"));
+            return MithrilStatus::InvalidArgument;
+        };
+        let Some(certificate_hash) = str_argument("certificate_hash", certificate_hash) else {
+            return MithrilStatus::InvalidArgument;
+        };
+
+        status_of(handle.runtime.block_on(async {
+            let Some(certificate) = handle.client.certificate().get(certificate_hash).await? else {
+                set_last_error(anyhow!(
+                    "No certificate exist for hash '{certificate_hash}'"
+                ));
+                return Ok(MithrilStatus::NotFound);
+            };
+            handle
+                .client
+                .certificate()
+                .verify_chain_of(&certificate)
+                .await?;
+
+            Ok(MithrilStatus::Ok)
+        }))
+    })
+}
+
+/// Verify the certificate chain of the snapshot with the given digest, then download it in a
+/// staging directory and promote it to the given directory, which must not exist or be empty,
+/// once its digest is verified against its certificate.
+///
+/// The snapshot is removed if its digest doesn't match its certificate, see
+/// [download_unpack_staged][crate::snapshot_client::SnapshotClient::download_unpack_staged].
+///
+/// On success the hash of the snapshot certificate is written in `certificate_hash_out` if it's
+/// not null, it must be released with [mithril_string_free].
+///
+/// # Safety
+///
+/// `client` must be a pointer returned by [mithril_client_new], `snapshot_digest` and
+/// `target_directory` valid null terminated strings and `certificate_hash_out` null or a valid
+/// pointer.
+#[no_mangle]
+pub unsafe extern "C" fn mithril_client_download_snapshot(
+    client: *const MithrilClientHandle,
+    snapshot_digest: *const c_char,
+    target_directory: *const c_char,
+    certificate_hash_out: *mut *mut c_char,
+) -> MithrilStatus {
+    catch_panic(MithrilStatus::Failure, || {
+        let Some(handle) = client.as_ref() else {
+            set_last_error(anyhow!("Argument 'client' is null"));
+            return MithrilStatus::InvalidArgument;
+        };
+        let Some(snapshot_digest) = str_argument("snapshot_digest", snapshot_digest) else {
+            return MithrilStatus::InvalidArgument;
+        };
+        let Some(target_directory) = str_argument("target_directory", target_directory) else {
+            return MithrilStatus::InvalidArgument;
+        };
+        let target_directory = Path::new(target_directory);
+
+        status_of(handle.runtime.block_on(async {
+            let Some(snapshot) = handle.client.snapshot().get(snapshot_digest).await? else {
+                set_last_error(anyhow!("No snapshot exist for digest '{snapshot_digest}'"));
+                return Ok(MithrilStatus::NotFound);
+            };
+            let certificate = handle
+                .client
+                .certificate()
+                .verify_chain(&snapshot.certificate_hash)
+                .await?;
+            if let Err(error) = handle
+                .client
+                .snapshot()
+                .download_unpack_staged(
+                    &snapshot,
+                    &certificate,
+                    &handle.client.message_builder(),
+                    target_directory,
+                )
+                .await
+            {
+                if let Some(SnapshotClientError::Quarantined { quarantine_dir, .. }) =
+                    error.downcast_ref::<SnapshotClientError>()
+                {
+                    std::fs::remove_dir_all(quarantine_dir).with_context(|| {
+                        format!("Could not remove directory '{}'", quarantine_dir.display())
+                    })?;
+                }
+                return Err(error);
+            }
+            write_string_output(certificate_hash_out, &certificate.hash)?;
+
+            Ok(MithrilStatus::Ok)
+        }))
+    })
+}
+
+/// Message of the last error that occurred in the calling thread, or null if there was none.
+///
+/// The returned string is owned by the bindings and is valid until the next call that fails on
+/// the same thread, it must not be released.
+#[no_mangle]
+pub extern "C" fn mithril_last_error_message() -> *const c_char {
+    catch_panic(std::ptr::null(), || {
+        LAST_ERROR.with(|last_error| match last_error.borrow().as_ref() {
+            Some(message) => message.as_ptr(),
+            None => std::ptr::null(),
+        })
+    })
+}
+
+/// Release a string returned by the bindings.
+///
+/// # Safety
+///
+/// `value` must be null or a string returned by the bindings that was not released yet.
+#[no_mangle]
+pub unsafe extern "C" fn mithril_string_free(value: *mut c_char) {
+    catch_panic((), || {
+        if !value.is_null() {
+            drop(CString::from_raw(value));
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use mithril_common::crypto_helper::tests_setup::setup_certificate_chain;
+    use mithril_common::messages::CertificateMessage;
+
+    use super::*;
+
+    fn last_error_message() -> String {
+        let message = mithril_last_error_message();
+        assert!(!message.is_null(), "an error should have been set");
+        unsafe { CStr::from_ptr(message) }
+            .to_string_lossy()
+            .to_string()
+    }
+
+    fn new_client(
+        aggregator_endpoint: &str,
+        genesis_verification_key: &str,
+    ) -> *mut MithrilClientHandle {
+        let aggregator_endpoint = CString::new(aggregator_endpoint).unwrap();
+        let genesis_verification_key = CString::new(genesis_verification_key).unwrap();
+        let mut client = std::ptr::null_mut();
+
+        let status = unsafe {
+            mithril_client_new(
+                aggregator_endpoint.as_ptr(),
+                genesis_verification_key.as_ptr(),
+                &mut client,
+            )
+        };
+        assert_eq!(MithrilStatus::Ok, status, "{}", last_error_message());
+
+        client
+    }
+
+    #[test]
+    fn new_client_with_null_argument_fails_and_set_the_last_error() {
+        let mut client = std::ptr::null_mut();
+
+        let status = unsafe { mithril_client_new(std::ptr::null(), std::ptr::null(), &mut client) };
+
+        assert_eq!(MithrilStatus::InvalidArgument, status);
+        assert!(client.is_null());
+        assert!(last_error_message().contains("aggregator_endpoint"));
+    }
+
+    #[test]
+    fn a_panic_is_reported_as_a_failure_and_set_the_last_error() {
+        let status = catch_panic(MithrilStatus::Failure, || -> MithrilStatus {
+            panic!("unexpected state")
+        });
+
+        assert_eq!(MithrilStatus::Failure, status);
+        assert!(last_error_message().contains("unexpected state"));
+    }
+
+    #[test]
+    fn verify_certificate_chain_served_by_an_aggregator() {
+        let (certificates, genesis_verifier) = setup_certificate_chain(3, 1);
+        let server = httpmock::MockServer::start();
+        for certificate in &certificates {
+            let message = CertificateMessage::try_from(certificate.clone()).unwrap();
+            server.mock(|when, then| {
+                when.path(format!("/certificate/{}", message.hash));
+                then.status(200)
+                    .body(serde_json::to_string(&message).unwrap());
+            });
+        }
+        let client = new_client(
+            &server.base_url(),
+            &genesis_verifier
+                .to_verification_key()
+                .to_json_hex()
+                .unwrap(),
+        );
+        let certificate_hash = CString::new(certificates[0].hash.clone()).unwrap();
+        let unknown_hash = CString::new("unknown").unwrap();
+
+        let status =
+            unsafe { mithril_client_verify_certificate_chain(client, certificate_hash.as_ptr()) };
+        assert_eq!(MithrilStatus::Ok, status, "{}", last_error_message());
+
+        let status =
+            unsafe { mithril_client_verify_certificate_chain(client, unknown_hash.as_ptr()) };
+        assert_eq!(MithrilStatus::NotFound, status);
+
+        unsafe { mithril_client_free(client) };
+    }
+}
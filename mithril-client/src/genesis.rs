@@ -0,0 +1,184 @@
+//! Fetch the published genesis verification key of a certificate chain.
+//!
+//! The genesis verification key is the root of trust of the certificate chain: it must come from
+//! a trusted source. Instead of copying it by hand, it can be fetched from the URL where it is
+//! published with [fetch_genesis_verification_key], which only returns it if it matches a
+//! [fingerprint][GenesisKeyFingerprint] pinned by the user, so the trust stays explicit: a
+//! compromised or changed URL is detected instead of silently trusted.
+//!
+//! The fingerprint of a key is the hex encoded SHA256 of its bytes, it can be computed once from a
+//! key obtained through another channel with [GenesisKeyFingerprint::of].
+//!
+//! # Build a client with a fetched genesis verification key
+//!
+//! ```no_run
+//! # async fn run() -> mithril_client::MithrilResult<()> {
+//! use mithril_client::genesis::{fetch_genesis_verification_key, GenesisKeyFingerprint};
+//! use mithril_client::ClientBuilder;
+//!
+//! let fingerprint: GenesisKeyFingerprint = "YOUR_PINNED_FINGERPRINT".parse()?;
+//! let genesis_verification_key =
+//!     fetch_genesis_verification_key("YOUR_GENESIS_VERIFICATION_KEY_URL", &fingerprint).await?;
+//!
+//! let client = ClientBuilder::aggregator("YOUR_AGGREGATOR_ENDPOINT", &genesis_verification_key).build()?;
+//! #    Ok(())
+//! # }
+//! ```
+
+use anyhow::{anyhow, Context};
+use sha2::{Digest, Sha256};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use thiserror::Error;
+
+use crate::common::crypto_helper::ProtocolGenesisVerificationKey;
+use crate::{MithrilError, MithrilResult};
+
+/// Fingerprint of a genesis verification key: the hex encoded SHA256 of its bytes.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GenesisKeyFingerprint(String);
+
+impl GenesisKeyFingerprint {
+    /// Compute the fingerprint of the given genesis verification key, encoded as in the Mithril
+    /// networks configuration.
+    pub fn of(genesis_verification_key: &str) -> MithrilResult<Self> {
+        let key = ProtocolGenesisVerificationKey::try_from(genesis_verification_key.trim())
+            .with_context(|| "Invalid genesis verification key")?;
+
+        Ok(Self(hex::encode(Sha256::digest(key.to_bytes()))))
+    }
+}
+
+impl FromStr for GenesisKeyFingerprint {
+    type Err = MithrilError;
+
+    fn from_str(fingerprint: &str) -> Result<Self, Self::Err> {
+        let fingerprint = fingerprint.trim().to_lowercase();
+        match hex::decode(&fingerprint) {
+            Ok(bytes) if bytes.len() == 32 => Ok(Self(fingerprint)),
+            _ => Err(anyhow!(
+                "Invalid genesis key fingerprint '{fingerprint}': expected the hex encoded SHA256 of the key"
+            )),
+        }
+    }
+}
+
+impl Display for GenesisKeyFingerprint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// The fetched genesis verification key doesn't match the pinned fingerprint.
+#[derive(Error, Debug)]
+#[error("The genesis verification key published at '{url}' has fingerprint '{computed}' instead of the pinned '{expected}'.")]
+pub struct GenesisKeyFingerprintMismatch {
+    /// URL of the fetched key
+    pub url: String,
+
+    /// Pinned fingerprint
+    pub expected: GenesisKeyFingerprint,
+
+    /// Fingerprint of the fetched key
+    pub computed: GenesisKeyFingerprint,
+}
+
+/// Fetch the genesis verification key published at the given URL and return it only if its
+/// fingerprint matches the pinned one, otherwise a [GenesisKeyFingerprintMismatch] error is
+/// returned.
+pub async fn fetch_genesis_verification_key(
+    url: &str,
+    pinned_fingerprint: &GenesisKeyFingerprint,
+) -> MithrilResult<String> {
+    let response = reqwest::get(url)
+        .await
+        .with_context(|| format!("Could not fetch the genesis verification key at '{url}'"))?;
+    if !response.status().is_success() {
+        return Err(anyhow!(
+            "Could not fetch the genesis verification key at '{url}': status {}",
+            response.status()
+        ));
+    }
+    let genesis_verification_key = response
+        .text()
+        .await
+        .with_context(|| format!("Could not read the genesis verification key at '{url}'"))?
+        .trim()
+        .to_string();
+
+    let computed = GenesisKeyFingerprint::of(&genesis_verification_key)
+        .with_context(|| format!("Invalid genesis verification key published at '{url}'"))?;
+    if &computed != pinned_fingerprint {
+        return Err(GenesisKeyFingerprintMismatch {
+            url: url.to_string(),
+            expected: pinned_fingerprint.clone(),
+            computed,
+        }
+        .into());
+    }
+
+    Ok(genesis_verification_key)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_tools::fake_keys;
+
+    use super::*;
+
+    fn serve_key(key: &str) -> httpmock::MockServer {
+        let server = httpmock::MockServer::start();
+        server.mock(|when, then| {
+            when.path("/genesis.vkey");
+            then.status(200).body(format!("{key}\n"));
+        });
+        server
+    }
+
+    #[test]
+    fn parse_fingerprint() {
+        let fingerprint =
+            GenesisKeyFingerprint::of(fake_keys::genesis_verification_key()[0]).unwrap();
+
+        assert_eq!(
+            fingerprint,
+            fingerprint.to_string().to_uppercase().parse().unwrap()
+        );
+        "sha256"
+            .parse::<GenesisKeyFingerprint>()
+            .expect_err("not hex");
+        "abcd"
+            .parse::<GenesisKeyFingerprint>()
+            .expect_err("not a SHA256");
+    }
+
+    #[tokio::test]
+    async fn fetch_key_matching_the_pinned_fingerprint() {
+        let key = fake_keys::genesis_verification_key()[0];
+        let server = serve_key(key);
+
+        let fetched = fetch_genesis_verification_key(
+            &server.url("/genesis.vkey"),
+            &GenesisKeyFingerprint::of(key).unwrap(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(key, fetched);
+    }
+
+    #[tokio::test]
+    async fn reject_key_not_matching_the_pinned_fingerprint() {
+        let server = serve_key(fake_keys::genesis_verification_key()[0]);
+        let pinned = GenesisKeyFingerprint::of(fake_keys::genesis_verification_key()[1]).unwrap();
+
+        let error = fetch_genesis_verification_key(&server.url("/genesis.vkey"), &pinned)
+            .await
+            .expect_err("a key with another fingerprint should be rejected");
+
+        let mismatch = error
+            .downcast_ref::<GenesisKeyFingerprintMismatch>()
+            .expect("error should be a GenesisKeyFingerprintMismatch");
+        assert_eq!(pinned, mismatch.expected);
+    }
+}
@@ -10,17 +10,33 @@
 //! - [Mithril stake distribution][mithril_stake_distribution_client] list and get.
 //! - [Certificates][certificate_client] list, get, and chain validation.
 //!
+//! Artifacts can be [prefetched][offline_store] ahead of an offline window.
+//!
 //! The [Client] aggregates the queries of all of those types.
 //!
 //! **NOTE:** Snapshot download and Certificate chain validation can take quite some time even with a fast
 //! computer and network.
 //! For those a feedback mechanism is available, more details on it in the [feedback] submodule.
 //!
+//! The client operations can also be observed using the [metrics] hooks.
+//!
+//! Stake distributions and certificate signers can be [exported][export] as CSV or Parquet files.
+//!
+//! The types needed by most users can be imported at once from the [prelude].
+//!
 //! # Example
 //!
 //! Below is an example describing the usage of most of the library's functions together:
 //!
 //! **Note:** _Snapshot download and the compute snapshot message functions are available using crate feature_ **fs**.
+//! _Verification-only users can disable the default features and only enable the immutable files
+//! [digesters][common::digesters] with crate feature_ **digesters**, _or the single signatures
+//! issuance with crate feature_ **signer_tools**.
+//!
+//! **Note:** _[tracing](https://docs.rs/tracing) spans around the certificate chain verification, the snapshot download and the digest computation are emitted using crate feature_ **tracing**.
+//!
+//! **Note:** _The CPU heavy computations can be run on a dedicated thread pool, see
+//! [compute], using crate feature_ **compute_threads**.
 //!
 //! ```no_run
 //! # async fn run() -> mithril_client::MithrilResult<()> {
@@ -66,14 +82,70 @@ macro_rules! cfg_fs {
 }
 
 pub mod aggregator_client;
+#[cfg(feature = "blocking")]
+#[cfg_attr(docsrs, doc(cfg(feature = "blocking")))]
+pub mod blocking;
 pub mod certificate_client;
+#[cfg(feature = "fs")]
+#[cfg_attr(docsrs, doc(cfg(feature = "fs")))]
+pub mod chain_watcher;
 mod client;
+pub mod compute;
+#[cfg(feature = "config")]
+#[cfg_attr(docsrs, doc(cfg(feature = "config")))]
+pub mod config;
+#[cfg(not(target_family = "wasm"))]
+pub mod connection_pool;
+#[cfg(not(target_family = "wasm"))]
+pub mod dns;
+#[cfg(feature = "fs")]
+#[cfg_attr(docsrs, doc(cfg(feature = "fs")))]
+pub mod download_gc;
+#[cfg(feature = "fs")]
+#[cfg_attr(docsrs, doc(cfg(feature = "fs")))]
+pub mod download_layout;
+pub mod epoch_settings_client;
+pub mod era;
+pub mod export;
+pub mod failover;
 pub mod feedback;
+#[cfg(feature = "ffi")]
+#[cfg_attr(docsrs, doc(cfg(feature = "ffi")))]
+pub mod ffi;
+pub mod genesis;
+#[cfg(feature = "logging")]
+#[cfg_attr(docsrs, doc(cfg(feature = "logging")))]
+pub mod logging;
 mod message;
+pub mod metrics;
 pub mod mithril_stake_distribution_client;
+#[cfg(feature = "network_presets")]
+#[cfg_attr(docsrs, doc(cfg(feature = "network_presets")))]
+pub mod network;
+pub mod offline_store;
+pub mod prelude;
+pub mod proof_bundle;
+pub mod protocol_message_matcher;
+#[cfg(not(target_family = "wasm"))]
+pub mod proxy;
+#[cfg(feature = "python")]
+#[cfg_attr(docsrs, doc(cfg(feature = "python")))]
+pub mod python;
+#[cfg(feature = "fs")]
+#[cfg_attr(docsrs, doc(cfg(feature = "fs")))]
+pub mod recording;
+pub mod retry;
+pub mod schema_validation;
+pub mod signed_entity;
 pub mod snapshot_client;
 #[cfg(feature = "fs")]
 pub mod snapshot_downloader;
+pub mod snapshot_validation;
+#[cfg(any(test, feature = "test_tools"))]
+#[cfg_attr(docsrs, doc(cfg(feature = "test_tools")))]
+pub mod test_tools;
+#[cfg(not(target_family = "wasm"))]
+pub mod tls;
 
 pub mod common;
 mod type_alias;
@@ -86,6 +158,7 @@ pub use type_alias::*;
 #[cfg(test)]
 pub(crate) mod test_utils {
     use slog::Drain;
+    use std::path::PathBuf;
     use std::sync::Arc;
 
     pub fn test_logger() -> slog::Logger {
@@ -94,4 +167,96 @@ pub(crate) mod test_utils {
         let drain = slog_async::Async::new(drain).build().fuse();
         slog::Logger::root(Arc::new(drain), slog::o!())
     }
+
+    /// Create an empty directory for the test with the given name, in the temporary directory of
+    /// the tests of the given module.
+    pub fn get_test_directory(module: &str, dir_name: &str) -> PathBuf {
+        let dir = std::env::temp_dir()
+            .join("mithril_test")
+            .join(module)
+            .join(dir_name);
+        if dir.exists() {
+            std::fs::remove_dir_all(&dir).unwrap();
+        }
+        std::fs::create_dir_all(&dir).unwrap();
+
+        dir
+    }
+
+    cfg_fs! {
+        use async_trait::async_trait;
+        use flate2::{write::GzEncoder, Compression};
+        use sha2::{Digest, Sha256};
+        use std::path::Path;
+        use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+        use crate::common::entities::CompressionAlgorithm;
+        use crate::snapshot_downloader::SnapshotDownloader;
+        use crate::MithrilResult;
+
+        /// Build a gzip compressed tar archive of the given files paths and contents.
+        pub fn gzip_archive(files: &[(&str, &str)]) -> Vec<u8> {
+            let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+            for (path, content) in files {
+                let mut header = tar::Header::new_gnu();
+                header.set_size(content.len() as u64);
+                header.set_mode(0o644);
+                header.set_cksum();
+                builder
+                    .append_data(&mut header, path, content.as_bytes())
+                    .unwrap();
+            }
+
+            builder.into_inner().unwrap().finish().unwrap()
+        }
+
+        /// Snapshot downloader that serves the given archive from any location but the failing
+        /// ones and relies on the default implementations of [SnapshotDownloader].
+        pub struct ArchiveSnapshotDownloader {
+            pub archive: Vec<u8>,
+            pub failing_locations: Vec<String>,
+        }
+
+        #[async_trait]
+        impl SnapshotDownloader for ArchiveSnapshotDownloader {
+            async fn download_unpack(
+                &self,
+                location: &str,
+                target_dir: &Path,
+                compression_algorithm: CompressionAlgorithm,
+                download_id: &str,
+                snapshot_size: u64,
+            ) -> MithrilResult<()> {
+                self.download_unpack_with_digest(
+                    location,
+                    target_dir,
+                    compression_algorithm,
+                    download_id,
+                    snapshot_size,
+                )
+                .await
+                .map(|_| ())
+            }
+
+            async fn download_to_writer(
+                &self,
+                location: &str,
+                writer: &mut (dyn AsyncWrite + Send + Unpin + 'static),
+                _download_id: &str,
+                _archive_size: u64,
+            ) -> MithrilResult<String> {
+                if self.failing_locations.iter().any(|failing| failing == location) {
+                    return Err(anyhow::anyhow!("Location='{location}' failed"));
+                }
+                writer.write_all(&self.archive).await?;
+                writer.flush().await?;
+
+                Ok(hex::encode(Sha256::digest(&self.archive)))
+            }
+
+            async fn probe(&self, _location: &str) -> MithrilResult<()> {
+                Ok(())
+            }
+        }
+    }
 }
@@ -0,0 +1,171 @@
+//! Configuration of the logs emitted by the client.
+//!
+//! By default the client doesn't log anything, use
+//! [ClientBuilder::with_logging][crate::ClientBuilder::with_logging] with [LogOptions] to output
+//! the logs on the standard error in a human readable or a JSON format, filtered by level.
+//!
+//! If a custom [Logger] is set using [ClientBuilder::with_logger][crate::ClientBuilder::with_logger],
+//! the level of the [LogOptions] is applied on top of it and their format is ignored.
+//!
+//! **Note:** _Available using crate feature_ **logging**.
+//!
+//! # Log the warnings as JSON
+//!
+//! ```no_run
+//! # async fn run() -> mithril_client::MithrilResult<()> {
+//! use mithril_client::{logging::{LogFormat, LogOptions}, ClientBuilder};
+//! use slog::Level;
+//!
+//! let client = ClientBuilder::aggregator("YOUR_AGGREGATOR_ENDPOINT", "YOUR_GENESIS_VERIFICATION_KEY")
+//!     .with_logging(LogOptions::new(LogFormat::Json).with_level(Level::Warning))
+//!     .build()?;
+//! #    Ok(())
+//! # }
+//! ```
+
+use slog::{o, Drain, Level, Logger};
+use std::io::Write;
+use std::panic::{RefUnwindSafe, UnwindSafe};
+use std::sync::Mutex;
+
+/// Output format of the logs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Discard every log.
+    #[default]
+    Discard,
+
+    /// Human readable logs, colored if the output is a terminal.
+    Terminal,
+
+    /// One JSON object per log.
+    Json,
+}
+
+/// Options of the logs emitted by the client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LogOptions {
+    /// Minimum level of the emitted logs
+    pub level: Level,
+
+    /// Output format of the logs
+    pub format: LogFormat,
+}
+
+impl Default for LogOptions {
+    fn default() -> Self {
+        Self {
+            level: Level::Info,
+            format: LogFormat::Discard,
+        }
+    }
+}
+
+impl LogOptions {
+    /// Constructs new `LogOptions` with the given format and the `Info` level.
+    pub fn new(format: LogFormat) -> Self {
+        Self {
+            format,
+            ..Self::default()
+        }
+    }
+
+    /// Set the minimum level of the emitted logs.
+    pub fn with_level(mut self, level: Level) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Build a [Logger] that writes on the standard error.
+    pub fn build_logger(&self) -> Logger {
+        match self.format {
+            LogFormat::Discard => Logger::root(slog::Discard, o!()),
+            LogFormat::Terminal => {
+                let decorator = slog_term::TermDecorator::new().stderr().build();
+                let drain = slog_term::FullFormat::new(decorator).build();
+                self.filtered_logger(Mutex::new(drain).fuse())
+            }
+            LogFormat::Json => self.build_json_logger(std::io::stderr()),
+        }
+    }
+
+    /// Apply the level of these options to the given logger, its format is kept.
+    pub fn apply_level(&self, logger: Logger) -> Logger {
+        self.filtered_logger(logger.fuse())
+    }
+
+    fn build_json_logger<W: Write + Send + 'static>(&self, writer: W) -> Logger {
+        let drain = slog_json::Json::default(writer);
+        self.filtered_logger(Mutex::new(drain).fuse())
+    }
+
+    fn filtered_logger<D>(&self, drain: D) -> Logger
+    where
+        D: Drain<Ok = (), Err = slog::Never> + Send + Sync + UnwindSafe + RefUnwindSafe + 'static,
+    {
+        Logger::root(drain.filter_level(self.level).fuse(), o!())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use slog::{info, warn};
+    use std::sync::Arc;
+
+    use super::*;
+
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl SharedBuffer {
+        fn lines(&self) -> Vec<String> {
+            String::from_utf8(self.0.lock().unwrap().clone())
+                .unwrap()
+                .lines()
+                .map(|line| line.to_string())
+                .collect()
+        }
+    }
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn json_logger_output_the_logs_above_the_level() {
+        let buffer = SharedBuffer::default();
+        let logger = LogOptions::new(LogFormat::Json)
+            .with_level(Level::Warning)
+            .build_json_logger(buffer.clone());
+
+        info!(logger, "filtered");
+        warn!(logger, "kept"; "key" => "value");
+
+        let lines = buffer.lines();
+        assert_eq!(1, lines.len());
+        let log: serde_json::Value = serde_json::from_str(&lines[0]).unwrap();
+        assert_eq!("kept", log["msg"]);
+        assert_eq!("value", log["key"]);
+    }
+
+    #[test]
+    fn apply_level_filters_a_custom_logger() {
+        let buffer = SharedBuffer::default();
+        let custom_logger = LogOptions::new(LogFormat::Json)
+            .with_level(Level::Trace)
+            .build_json_logger(buffer.clone());
+        let logger = LogOptions::default()
+            .with_level(Level::Error)
+            .apply_level(custom_logger);
+
+        warn!(logger, "filtered");
+
+        assert!(buffer.lines().is_empty());
+    }
+}
@@ -1,26 +1,76 @@
 #[cfg(feature = "fs")]
-use crate::common::digesters::{CardanoImmutableDigester, ImmutableDigester};
-use crate::common::entities::{ProtocolMessage, ProtocolMessagePartKey};
-use crate::common::messages::SignerWithStakeMessagePart;
-use crate::common::protocol::SignerBuilder;
+use crate::common::digesters::{
+    CardanoImmutableDigester, DigestProgress, DigestProgressListener, ImmutableDigester,
+    ImmutableDigesterError,
+};
+#[cfg(feature = "fs")]
+use crate::common::entities::Beacon;
+use crate::common::entities::SignerWithStake;
+use crate::common::entities::{
+    Epoch, ProtocolMessage, ProtocolMessagePartKey, ProtocolParameters, SignedEntityType,
+};
+use crate::common::messages::SignerWithStakeMessagePartRef;
+use crate::common::protocol::{
+    AggregateVerificationKeyComputation, AggregateVerificationKeyProgress,
+};
+use crate::compute::ComputeExecutor;
+#[cfg(feature = "fs")]
+use crate::feedback::{FeedbackSender, MithrilEvent};
+#[cfg(feature = "fs")]
+use crate::metrics::MetricsRecorder;
+use crate::signed_entity::SignedEntityRegistry;
 use anyhow::Context;
 use slog::{o, Logger};
+use std::collections::BTreeMap;
 #[cfg(feature = "fs")]
 use std::path::Path;
-#[cfg(feature = "fs")]
 use std::sync::Arc;
+#[cfg(feature = "fs")]
+use std::time::Instant;
 
 #[cfg(feature = "fs")]
 use crate::MithrilCertificate;
 use crate::{MithrilResult, MithrilStakeDistribution};
 
+/// Parts of a [ProtocolMessage] computed or retrieved locally, from which the message signed for a
+/// [SignedEntityType] is assembled, see [MessageBuilder::assemble_protocol_message].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LocalMessageParts {
+    /// Aggregate verification key of the signers of the next epoch, see
+    /// [MessageBuilder::compute_next_aggregate_verification_key]
+    pub next_aggregate_verification_key: String,
+
+    /// Digest of the immutable files of a snapshot, required for a
+    /// [CardanoImmutableFilesFull][SignedEntityType::CardanoImmutableFilesFull] message
+    pub snapshot_digest: Option<String>,
+
+    /// Digest of the ancillary files archive of a snapshot, only set in the messages of
+    /// aggregators that certify it
+    pub ancillary_digest: Option<String>,
+}
+
 /// A [MessageBuilder] can be used to compute the message of Mithril artifacts.
+///
+/// The messages can either be computed from an artifact and its certificate (ie:
+/// [compute_snapshot_message][MessageBuilder::compute_snapshot_message]) or assembled only from
+/// local data, the way signers do, using
+/// [assemble_protocol_message][MessageBuilder::assemble_protocol_message].
 pub struct MessageBuilder {
     #[cfg(feature = "fs")]
     immutable_digester: Option<Arc<dyn ImmutableDigester>>,
+    #[cfg(feature = "fs")]
+    metrics_recorder: Option<Arc<dyn MetricsRecorder>>,
+    #[cfg(feature = "fs")]
+    feedback_sender: Option<FeedbackSender>,
+    aggregate_verification_key_progress: Option<AggregateVerificationKeyProgressCallback>,
+    signed_entity_registry: SignedEntityRegistry,
+    compute_executor: ComputeExecutor,
     logger: Logger,
 }
 
+type AggregateVerificationKeyProgressCallback =
+    Arc<dyn Fn(AggregateVerificationKeyProgress) + Send + Sync>;
+
 impl MessageBuilder {
     /// Constructs a new `MessageBuilder`.
     pub fn new() -> MessageBuilder {
@@ -28,6 +78,13 @@ impl MessageBuilder {
         Self {
             #[cfg(feature = "fs")]
             immutable_digester: None,
+            #[cfg(feature = "fs")]
+            metrics_recorder: None,
+            #[cfg(feature = "fs")]
+            feedback_sender: None,
+            aggregate_verification_key_progress: None,
+            signed_entity_registry: SignedEntityRegistry::default(),
+            compute_executor: ComputeExecutor::default(),
             logger,
         }
     }
@@ -38,10 +95,43 @@ impl MessageBuilder {
         self
     }
 
+    /// Set a callback notified of the progress of the aggregate verification key computations,
+    /// once per registered signer.
+    ///
+    /// Computing the aggregate verification key of thousands of signers can take a few seconds,
+    /// see [AggregateVerificationKeyComputation] for its memory usage.
+    pub fn with_aggregate_verification_key_progress<F>(mut self, on_progress: F) -> Self
+    where
+        F: Fn(AggregateVerificationKeyProgress) + Send + Sync + 'static,
+    {
+        self.aggregate_verification_key_progress = Some(Arc::new(on_progress));
+        self
+    }
+
+    /// Set the [SignedEntityRegistry] that describes the messages of the signed entities, the
+    /// [default registry][SignedEntityRegistry::default] is used if not set.
+    pub fn with_signed_entity_registry(
+        mut self,
+        signed_entity_registry: SignedEntityRegistry,
+    ) -> Self {
+        self.signed_entity_registry = signed_entity_registry;
+        self
+    }
+
+    /// Set the [ComputeExecutor] on which the snapshot digests and, when the message is computed
+    /// from local data, the aggregate verification keys are computed.
+    pub fn with_compute_executor(mut self, compute_executor: ComputeExecutor) -> Self {
+        self.compute_executor = compute_executor;
+        self
+    }
+
     cfg_fs! {
     fn get_immutable_digester(&self) -> Arc<dyn ImmutableDigester> {
         match self.immutable_digester.as_ref() {
-            None => Arc::new(CardanoImmutableDigester::new(None, self.logger.clone())),
+            None => Arc::new(
+                CardanoImmutableDigester::new(None, self.logger.clone())
+                    .with_compute_executor(self.compute_executor.clone()),
+            ),
             Some(digester) => digester.clone(),
         }
     }
@@ -57,31 +147,175 @@ impl MessageBuilder {
         self
     }
 
+    /// Set the [MetricsRecorder] that will record the duration of the digest computations.
+    pub fn with_metrics_recorder(mut self, metrics_recorder: Arc<dyn MetricsRecorder>) -> Self {
+        self.metrics_recorder = Some(metrics_recorder);
+        self
+    }
+
+    /// Set the [FeedbackSender] that will be notified of the progress of the snapshot digest
+    /// computations.
+    pub fn with_feedback_sender(mut self, feedback_sender: FeedbackSender) -> Self {
+        self.feedback_sender = Some(feedback_sender);
+        self
+    }
+
     /// Compute message for a snapshot (based on the directory where it was unpacked).
     ///
     /// Warning: this operation can be quite long depending on the snapshot size.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            fields(certificate_hash = %snapshot_certificate.hash)
+        )
+    )]
     pub async fn compute_snapshot_message(
         &self,
         snapshot_certificate: &MithrilCertificate,
         unpacked_snapshot_directory: &Path,
     ) -> MithrilResult<ProtocolMessage> {
-        let digester = self.get_immutable_digester();
-
         let mut message = snapshot_certificate.protocol_message.clone();
-
-        let digest = digester
-            .compute_digest(unpacked_snapshot_directory, &snapshot_certificate.beacon)
-            .await
-            .with_context(|| {
-                format!(
-                    "Snapshot digest computation failed: unpacked_dir: '{}'",
-                    unpacked_snapshot_directory.display()
-                )
-            })?;
+        let digest = self
+            .compute_snapshot_digest(&snapshot_certificate.beacon, unpacked_snapshot_directory)
+            .await?;
         message.set_message_part(ProtocolMessagePartKey::SnapshotDigest, digest);
 
         Ok(message)
     }
+
+    /// Compute the message of a snapshot only from local data: the digest of the immutable files
+    /// of the directory where it was unpacked and the signers of the next epoch, as a signer
+    /// would.
+    ///
+    /// Warning: this operation can be quite long depending on the snapshot size.
+    pub async fn compute_snapshot_message_from_local_data(
+        &self,
+        beacon: &Beacon,
+        unpacked_snapshot_directory: &Path,
+        next_signers: &MithrilStakeDistribution,
+    ) -> MithrilResult<ProtocolMessage> {
+        let signers = Self::next_signers(next_signers)?;
+        let protocol_parameters = next_signers.protocol_parameters.clone();
+        let on_progress = self.aggregate_verification_key_progress.clone();
+        let next_aggregate_verification_key = self
+            .compute_executor
+            .run(move || {
+                compute_aggregate_verification_key(
+                    &signers,
+                    &protocol_parameters,
+                    on_progress.as_ref(),
+                )
+            })
+            .await??;
+
+        let parts = LocalMessageParts {
+            next_aggregate_verification_key,
+            snapshot_digest: Some(
+                self.compute_snapshot_digest(beacon, unpacked_snapshot_directory)
+                    .await?,
+            ),
+            ancillary_digest: None,
+        };
+
+        self.assemble_protocol_message(
+            &SignedEntityType::CardanoImmutableFilesFull(beacon.clone()),
+            parts,
+        )
+    }
+
+    /// Compute the digest of the immutable files, up to the given beacon, of the directory where
+    /// a snapshot was unpacked.
+    ///
+    /// Warning: this operation can be quite long depending on the snapshot size, if a
+    /// [FeedbackSender] is set its progress is reported with
+    /// [SnapshotDigestComputationProgress][MithrilEvent::SnapshotDigestComputationProgress]
+    /// events.
+    pub async fn compute_snapshot_digest(
+        &self,
+        beacon: &Beacon,
+        unpacked_snapshot_directory: &Path,
+    ) -> MithrilResult<String> {
+        let digester = self.get_immutable_digester();
+
+        let start = Instant::now();
+        let digest = match &self.feedback_sender {
+            Some(feedback_sender) => {
+                Self::compute_digest_with_feedback(
+                    digester,
+                    feedback_sender,
+                    beacon,
+                    unpacked_snapshot_directory,
+                )
+                .await
+            }
+            None => {
+                digester
+                    .compute_digest(unpacked_snapshot_directory, beacon)
+                    .await
+            }
+        }
+        .with_context(|| {
+            format!(
+                "Snapshot digest computation failed: unpacked_dir: '{}'",
+                unpacked_snapshot_directory.display()
+            )
+        })?;
+        if let Some(metrics_recorder) = &self.metrics_recorder {
+            metrics_recorder.record_digest_computation(start.elapsed());
+        }
+
+        Ok(digest)
+    }
+
+    async fn compute_digest_with_feedback(
+        digester: Arc<dyn ImmutableDigester>,
+        feedback_sender: &FeedbackSender,
+        beacon: &Beacon,
+        unpacked_snapshot_directory: &Path,
+    ) -> Result<String, ImmutableDigesterError> {
+        let digest_computation_id = MithrilEvent::new_digest_computation_id();
+        feedback_sender
+            .send_event(MithrilEvent::SnapshotDigestComputationStarted {
+                digest_computation_id: digest_computation_id.clone(),
+                immutable_file_number: beacon.immutable_file_number,
+            })
+            .await;
+
+        // The digester hashes the files in a blocking task: its progress is forwarded through a
+        // channel, closed when the digester drops the listener at the end of the computation.
+        let (sender, receiver) = flume::unbounded::<DigestProgress>();
+        let on_progress: DigestProgressListener = Arc::new(move |progress| {
+            let _ = sender.send(progress);
+        });
+        let forward_progress = async {
+            while let Ok(progress) = receiver.recv_async().await {
+                feedback_sender
+                    .send_event(MithrilEvent::SnapshotDigestComputationProgress {
+                        digest_computation_id: digest_computation_id.clone(),
+                        files_hashed: progress.files_hashed,
+                        total_files: progress.total_files,
+                        bytes_hashed: progress.bytes_hashed,
+                        estimated_remaining: progress.estimated_remaining(),
+                    })
+                    .await;
+            }
+        };
+        let (digest, _) = futures::join!(
+            digester.compute_digest_with_progress(unpacked_snapshot_directory, beacon, on_progress),
+            forward_progress
+        );
+
+        if digest.is_ok() {
+            feedback_sender
+                .send_event(MithrilEvent::SnapshotDigestComputationCompleted {
+                    digest_computation_id,
+                })
+                .await;
+        }
+
+        digest
+    }
     }
 
     /// Compute message for a Mithril stake distribution.
@@ -89,28 +323,115 @@ impl MessageBuilder {
         &self,
         mithril_stake_distribution: &MithrilStakeDistribution,
     ) -> MithrilResult<ProtocolMessage> {
-        let signers = SignerWithStakeMessagePart::try_into_signers(
-            mithril_stake_distribution.signers_with_stake.clone(),
+        let signers: Vec<SignerWithStakeMessagePartRef> = mithril_stake_distribution
+            .signers_with_stake
+            .iter()
+            .map(SignerWithStakeMessagePartRef::from)
+            .collect();
+
+        self.compute_mithril_stake_distribution_message_of_signers(
+            mithril_stake_distribution.epoch,
+            &signers,
+            &mithril_stake_distribution.protocol_parameters,
         )
-        .with_context(|| "Could not compute message: conversion failure")?;
+    }
 
-        let signer_builder =
-            SignerBuilder::new(&signers, &mithril_stake_distribution.protocol_parameters)
-                .with_context(|| {
-                    "Could not compute message: aggregate verification key computation failed"
-                })?;
+    /// Same as [compute_mithril_stake_distribution_message][Self::compute_mithril_stake_distribution_message]
+    /// but from borrowed signers, so a stake distribution can be verified without copying the
+    /// keys of its signers.
+    pub fn compute_mithril_stake_distribution_message_of_signers(
+        &self,
+        epoch: Epoch,
+        signers: &[SignerWithStakeMessagePartRef],
+        protocol_parameters: &ProtocolParameters,
+    ) -> MithrilResult<ProtocolMessage> {
+        let parts = LocalMessageParts {
+            next_aggregate_verification_key: self
+                .compute_aggregate_verification_key_of_signers(signers, protocol_parameters)?,
+            ..LocalMessageParts::default()
+        };
 
-        let avk = signer_builder
-            .compute_aggregate_verification_key()
-            .to_json_hex()
-            .with_context(|| {
-                "Could not compute message: aggregate verification key encoding failed"
-            })?;
+        self.assemble_protocol_message(&SignedEntityType::MithrilStakeDistribution(epoch), parts)
+    }
 
-        let mut message = ProtocolMessage::new();
-        message.set_message_part(ProtocolMessagePartKey::NextAggregateVerificationKey, avk);
+    /// Compute the hex encoded aggregate verification key of the given signers, using the
+    /// protocol parameters of their stake distribution.
+    pub fn compute_next_aggregate_verification_key(
+        &self,
+        next_signers: &MithrilStakeDistribution,
+    ) -> MithrilResult<String> {
+        compute_aggregate_verification_key(
+            &Self::next_signers(next_signers)?,
+            &next_signers.protocol_parameters,
+            self.aggregate_verification_key_progress.as_ref(),
+        )
+    }
 
-        Ok(message)
+    fn next_signers(
+        next_signers: &MithrilStakeDistribution,
+    ) -> MithrilResult<Vec<SignerWithStake>> {
+        let signers: Vec<SignerWithStakeMessagePartRef> = next_signers
+            .signers_with_stake
+            .iter()
+            .map(SignerWithStakeMessagePartRef::from)
+            .collect();
+
+        SignerWithStakeMessagePartRef::try_into_signers(&signers)
+            .with_context(|| "Could not compute message: conversion failure")
+    }
+
+    fn compute_aggregate_verification_key_of_signers(
+        &self,
+        signers: &[SignerWithStakeMessagePartRef],
+        protocol_parameters: &ProtocolParameters,
+    ) -> MithrilResult<String> {
+        let signers = SignerWithStakeMessagePartRef::try_into_signers(signers)
+            .with_context(|| "Could not compute message: conversion failure")?;
+
+        compute_aggregate_verification_key(
+            &signers,
+            protocol_parameters,
+            self.aggregate_verification_key_progress.as_ref(),
+        )
+    }
+
+    /// Assemble the [ProtocolMessage] signed for the given [SignedEntityType] from parts computed
+    /// locally, the way signers do.
+    ///
+    /// Fails if a part required by the signed entity type is missing, or if a part that it
+    /// doesn't sign is given.
+    pub fn assemble_protocol_message(
+        &self,
+        signed_entity_type: &SignedEntityType,
+        parts: LocalMessageParts,
+    ) -> MithrilResult<ProtocolMessage> {
+        let mut message_parts = BTreeMap::new();
+        if let Some(snapshot_digest) = parts.snapshot_digest {
+            message_parts.insert(ProtocolMessagePartKey::SnapshotDigest, snapshot_digest);
+        }
+        if let Some(ancillary_digest) = parts.ancillary_digest {
+            message_parts.insert(ProtocolMessagePartKey::AncillaryDigest, ancillary_digest);
+        }
+
+        self.assemble_signed_entity_message(
+            &signed_entity_type.to_string(),
+            parts.next_aggregate_verification_key,
+            message_parts,
+        )
+    }
+
+    /// Assemble the [ProtocolMessage] signed for the [signed entity][crate::signed_entity] with
+    /// the given name, that must be known by the registry of the builder (see
+    /// [with_signed_entity_registry][MessageBuilder::with_signed_entity_registry]).
+    pub fn assemble_signed_entity_message(
+        &self,
+        signed_entity_name: &str,
+        next_aggregate_verification_key: String,
+        parts: BTreeMap<ProtocolMessagePartKey, String>,
+    ) -> MithrilResult<ProtocolMessage> {
+        self.signed_entity_registry
+            .get_or_fail(signed_entity_name)?
+            .assemble_protocol_message(next_aggregate_verification_key, parts)
     }
 }
 
@@ -119,3 +440,231 @@ impl Default for MessageBuilder {
         Self::new()
     }
 }
+
+fn compute_aggregate_verification_key(
+    signers: &[SignerWithStake],
+    protocol_parameters: &ProtocolParameters,
+    on_progress: Option<&AggregateVerificationKeyProgressCallback>,
+) -> MithrilResult<String> {
+    let aggregate_verification_key =
+        AggregateVerificationKeyComputation::compute(signers, protocol_parameters, |progress| {
+            if let Some(on_progress) = on_progress {
+                on_progress(progress);
+            }
+        })
+        .with_context(|| {
+            "Could not compute message: aggregate verification key computation failed"
+        })?;
+
+    aggregate_verification_key
+        .to_json_hex()
+        .with_context(|| "Could not compute message: aggregate verification key encoding failed")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::entities::Beacon;
+    use crate::common::messages::SignerWithStakeMessagePart;
+    use crate::common::protocol::SignerBuilder;
+
+    use super::*;
+
+    fn local_parts(snapshot_digest: Option<&str>) -> LocalMessageParts {
+        LocalMessageParts {
+            next_aggregate_verification_key: "next-avk".to_string(),
+            snapshot_digest: snapshot_digest.map(str::to_string),
+            ancillary_digest: None,
+        }
+    }
+
+    #[test]
+    fn assemble_message_of_each_signed_entity_type() {
+        let builder = MessageBuilder::new();
+
+        let snapshot_message = builder
+            .assemble_protocol_message(
+                &SignedEntityType::CardanoImmutableFilesFull(Beacon::default()),
+                local_parts(Some("digest")),
+            )
+            .unwrap();
+        let stake_distribution_message = builder
+            .assemble_protocol_message(
+                &SignedEntityType::MithrilStakeDistribution(Epoch(3)),
+                local_parts(None),
+            )
+            .unwrap();
+
+        let mut expected_stake_distribution_message = ProtocolMessage::new();
+        expected_stake_distribution_message.set_message_part(
+            ProtocolMessagePartKey::NextAggregateVerificationKey,
+            "next-avk".to_string(),
+        );
+        let mut expected_snapshot_message = expected_stake_distribution_message.clone();
+        expected_snapshot_message
+            .set_message_part(ProtocolMessagePartKey::SnapshotDigest, "digest".to_string());
+        assert_eq!(expected_snapshot_message, snapshot_message);
+        assert_eq!(
+            expected_stake_distribution_message,
+            stake_distribution_message
+        );
+    }
+
+    #[test]
+    fn assemble_message_with_missing_or_unexpected_parts_fails() {
+        let builder = MessageBuilder::new();
+
+        builder
+            .assemble_protocol_message(
+                &SignedEntityType::CardanoImmutableFilesFull(Beacon::default()),
+                local_parts(None),
+            )
+            .expect_err("a snapshot message without digest should be rejected");
+        builder
+            .assemble_protocol_message(
+                &SignedEntityType::MithrilStakeDistribution(Epoch(3)),
+                local_parts(Some("digest")),
+            )
+            .expect_err("a stake distribution message with a digest should be rejected");
+    }
+
+    #[test]
+    fn report_progress_of_the_aggregate_verification_key_computation() {
+        let next_signers = MithrilStakeDistribution::dummy();
+        let total_signers = next_signers.signers_with_stake.len();
+        let reported = Arc::new(std::sync::Mutex::new(vec![]));
+        let builder = MessageBuilder::new().with_aggregate_verification_key_progress({
+            let reported = reported.clone();
+            move |progress| reported.lock().unwrap().push(progress)
+        });
+
+        let avk = builder
+            .compute_next_aggregate_verification_key(&next_signers)
+            .unwrap();
+
+        let signers =
+            SignerWithStakeMessagePart::try_into_signers(next_signers.signers_with_stake.clone())
+                .unwrap();
+        let expected_avk = SignerBuilder::new(&signers, &next_signers.protocol_parameters)
+            .unwrap()
+            .compute_aggregate_verification_key()
+            .to_json_hex()
+            .unwrap();
+        assert_eq!(expected_avk, avk);
+        assert_eq!(
+            (1..=total_signers)
+                .map(|registered_signers| AggregateVerificationKeyProgress {
+                    registered_signers,
+                    total_signers,
+                })
+                .collect::<Vec<_>>(),
+            *reported.lock().unwrap()
+        );
+    }
+
+    #[test]
+    fn aggregate_verification_key_computation_fails_if_signers_are_missing() {
+        let next_signers = MithrilStakeDistribution::dummy();
+        let signers =
+            SignerWithStakeMessagePart::try_into_signers(next_signers.signers_with_stake.clone())
+                .unwrap();
+        let stake_distribution = signers.iter().map(|s| s.into()).collect::<Vec<_>>();
+        let computation = AggregateVerificationKeyComputation::new(
+            &stake_distribution,
+            &next_signers.protocol_parameters,
+        )
+        .unwrap();
+
+        computation
+            .finish()
+            .expect_err("the computation should fail if some signers were not registered");
+    }
+
+    #[cfg(feature = "fs")]
+    #[tokio::test]
+    async fn compute_snapshot_message_from_local_data() {
+        let next_signers = MithrilStakeDistribution::dummy();
+        let builder = MessageBuilder::new().with_immutable_digester(Arc::new(
+            crate::common::digesters::DumbImmutableDigester::new("digest", true),
+        ));
+
+        let message = builder
+            .compute_snapshot_message_from_local_data(
+                &Beacon::default(),
+                Path::new("unpacked_snapshot"),
+                &next_signers,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            Some(&"digest".to_string()),
+            message.get_message_part(&ProtocolMessagePartKey::SnapshotDigest)
+        );
+        assert_eq!(
+            builder
+                .compute_mithril_stake_distribution_message(&next_signers)
+                .unwrap()
+                .get_message_part(&ProtocolMessagePartKey::NextAggregateVerificationKey),
+            message.get_message_part(&ProtocolMessagePartKey::NextAggregateVerificationKey)
+        );
+    }
+
+    #[cfg(feature = "fs")]
+    #[tokio::test]
+    async fn report_progress_of_the_snapshot_digest_computation() {
+        use crate::common::digesters::DummyImmutablesDbBuilder;
+        use crate::feedback::StackFeedbackReceiver;
+        use crate::test_utils::test_logger;
+
+        let immutable_db = DummyImmutablesDbBuilder::new("report_digest_computation_progress")
+            .with_immutables(&[0, 1, 2])
+            .append_immutable_trio()
+            .set_file_size(100)
+            .build();
+        let feedback_receiver = Arc::new(StackFeedbackReceiver::new());
+        let builder = MessageBuilder::new()
+            .with_immutable_digester(Arc::new(CardanoImmutableDigester::new(None, test_logger())))
+            .with_feedback_sender(FeedbackSender::new(&[feedback_receiver.clone()]));
+
+        builder
+            .compute_snapshot_digest(&Beacon::new("devnet".to_string(), 1, 2), &immutable_db.dir)
+            .await
+            .unwrap();
+
+        let events = feedback_receiver.stacked_events();
+        let digest_computation_id = events[0].event_id().to_string();
+        let progress = events[1..events.len() - 1]
+            .iter()
+            .map(|event| match event {
+                MithrilEvent::SnapshotDigestComputationProgress {
+                    files_hashed,
+                    total_files,
+                    bytes_hashed,
+                    ..
+                } => (*files_hashed, *total_files, *bytes_hashed),
+                _ => panic!("Expected a digest computation progress event, got: {event:?}"),
+            })
+            .collect::<Vec<_>>();
+        let total_files = immutable_db.immutables_files.len();
+
+        assert_eq!(
+            MithrilEvent::SnapshotDigestComputationStarted {
+                digest_computation_id: digest_computation_id.clone(),
+                immutable_file_number: 2,
+            },
+            events[0]
+        );
+        assert_eq!(
+            (1..=total_files)
+                .map(|files_hashed| (files_hashed, total_files, files_hashed as u64 * 100))
+                .collect::<Vec<_>>(),
+            progress
+        );
+        assert_eq!(
+            Some(&MithrilEvent::SnapshotDigestComputationCompleted {
+                digest_computation_id
+            }),
+            events.last()
+        );
+    }
+}
@@ -0,0 +1,238 @@
+//! Hooks to observe the client operations with metrics.
+//!
+//! Implement the [MetricsRecorder] trait to forward the client measures to your metrics system
+//! (ie: prometheus or statsd), then give it to the client using
+//! [ClientBuilder::with_metrics_recorder][crate::ClientBuilder::with_metrics_recorder].
+//!
+//! Every method of the trait has a default implementation that does nothing, so only the needed
+//! measures have to be implemented.
+//!
+//! The following operations are instrumented:
+//!  - requests sent to the aggregator: duration, outcome and size of the responses, before and
+//!    after their decompression
+//!  - snapshot download: number of downloaded bytes
+//!  - certificate chain validation: number of verified certificates
+//!  - snapshot message computation: duration of the digest computation
+//!  - [offline store][crate::offline_store]: hits and misses
+//!
+//! **Note:** the snapshot message computation is only measured if the recorder is given to the
+//! [MessageBuilder][crate::MessageBuilder] using its `with_metrics_recorder` method.
+//!
+//! # Count the verified certificates
+//!
+//! ```no_run
+//! # async fn run() -> mithril_client::MithrilResult<()> {
+//! use mithril_client::{metrics::MetricsRecorder, ClientBuilder};
+//! use std::sync::atomic::{AtomicU64, Ordering};
+//! use std::sync::Arc;
+//!
+//! #[derive(Default)]
+//! struct CertificateCounter {
+//!     verified_certificates: AtomicU64,
+//! }
+//!
+//! impl MetricsRecorder for CertificateCounter {
+//!     fn record_certificate_verified(&self) {
+//!         self.verified_certificates.fetch_add(1, Ordering::Relaxed);
+//!     }
+//! }
+//!
+//! let counter = Arc::new(CertificateCounter::default());
+//! let client = ClientBuilder::aggregator("YOUR_AGGREGATOR_ENDPOINT", "YOUR_GENESIS_VERIFICATION_KEY")
+//!     .with_metrics_recorder(counter.clone())
+//!     .build()?;
+//!
+//! client.certificate().verify_chain("CERTIFICATE_HASH").await?;
+//!
+//! println!("{} certificates verified", counter.verified_certificates.load(Ordering::Relaxed));
+//! #    Ok(())
+//! # }
+//! ```
+
+use async_trait::async_trait;
+use std::io::Read;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::aggregator_client::{AggregatorClient, AggregatorClientError, AggregatorRequest};
+use crate::utils::time::Instant;
+
+/// Receive the measures of the client operations.
+pub trait MetricsRecorder: Sync + Send {
+    /// Called when a request to the aggregator completes.
+    ///
+    /// The `route` is relative to the aggregator root endpoint, see [AggregatorRequest::route].
+    fn record_aggregator_request(&self, _route: &str, _duration: Duration, _success: bool) {}
+
+    /// Called when the content of a response of the aggregator is received, with its size as
+    /// transferred, ie: compressed, and once decompressed.
+    ///
+    /// Only called by the [AggregatorHTTPClient][crate::aggregator_client::AggregatorHTTPClient].
+    fn record_aggregator_response_size(
+        &self,
+        _route: &str,
+        _transferred_bytes: u64,
+        _content_bytes: u64,
+    ) {
+    }
+
+    /// Called each time a chunk of a snapshot archive is downloaded.
+    fn record_downloaded_bytes(&self, _bytes: u64) {}
+
+    /// Called each time a certificate of a chain is verified.
+    fn record_certificate_verified(&self) {}
+
+    /// Called when the digest of a Cardano database has been computed.
+    fn record_digest_computation(&self, _duration: Duration) {}
+
+    /// Called when a lookup in a cache is done, with `true` if the value was found.
+    fn record_cache_lookup(&self, _cache: &str, _hit: bool) {}
+}
+
+/// An [AggregatorClient] that records the duration and outcome of the requests of another
+/// client to a [MetricsRecorder].
+pub struct MeteredAggregatorClient {
+    aggregator_client: Arc<dyn AggregatorClient>,
+    metrics_recorder: Arc<dyn MetricsRecorder>,
+}
+
+impl MeteredAggregatorClient {
+    /// Constructs a new `MeteredAggregatorClient`.
+    pub fn new(
+        aggregator_client: Arc<dyn AggregatorClient>,
+        metrics_recorder: Arc<dyn MetricsRecorder>,
+    ) -> Self {
+        Self {
+            aggregator_client,
+            metrics_recorder,
+        }
+    }
+}
+
+#[cfg_attr(target_family = "wasm", async_trait(?Send))]
+#[cfg_attr(not(target_family = "wasm"), async_trait)]
+impl AggregatorClient for MeteredAggregatorClient {
+    async fn get_content(
+        &self,
+        request: AggregatorRequest,
+    ) -> Result<String, AggregatorClientError> {
+        let route = request.route();
+        let start = Instant::now();
+        let result = self.aggregator_client.get_content(request).await;
+
+        self.metrics_recorder
+            .record_aggregator_request(&route, start.elapsed(), result.is_ok());
+
+        result
+    }
+
+    async fn get_content_reader(
+        &self,
+        request: AggregatorRequest,
+    ) -> Result<Box<dyn Read + Send>, AggregatorClientError> {
+        let route = request.route();
+        let start = Instant::now();
+        let result = self.aggregator_client.get_content_reader(request).await;
+
+        self.metrics_recorder
+            .record_aggregator_request(&route, start.elapsed(), result.is_ok());
+
+        result
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test_utils {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    /// A [MetricsRecorder] that keeps the received measures, durations excluded.
+    #[derive(Default)]
+    pub struct StackMetricsRecorder {
+        pub aggregator_requests: Mutex<Vec<(String, bool)>>,
+        pub aggregator_response_sizes: Mutex<Vec<(String, u64, u64)>>,
+        pub downloaded_bytes: Mutex<u64>,
+        pub verified_certificates: Mutex<u64>,
+        pub digest_computations: Mutex<u64>,
+        pub cache_lookups: Mutex<Vec<(String, bool)>>,
+    }
+
+    impl MetricsRecorder for StackMetricsRecorder {
+        fn record_aggregator_request(&self, route: &str, _duration: Duration, success: bool) {
+            let mut requests = self.aggregator_requests.lock().unwrap();
+            requests.push((route.to_string(), success));
+        }
+
+        fn record_aggregator_response_size(
+            &self,
+            route: &str,
+            transferred_bytes: u64,
+            content_bytes: u64,
+        ) {
+            let mut sizes = self.aggregator_response_sizes.lock().unwrap();
+            sizes.push((route.to_string(), transferred_bytes, content_bytes));
+        }
+
+        fn record_downloaded_bytes(&self, bytes: u64) {
+            *self.downloaded_bytes.lock().unwrap() += bytes;
+        }
+
+        fn record_certificate_verified(&self) {
+            *self.verified_certificates.lock().unwrap() += 1;
+        }
+
+        fn record_digest_computation(&self, _duration: Duration) {
+            *self.digest_computations.lock().unwrap() += 1;
+        }
+
+        fn record_cache_lookup(&self, cache: &str, hit: bool) {
+            let mut lookups = self.cache_lookups.lock().unwrap();
+            lookups.push((cache.to_string(), hit));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::anyhow;
+
+    use crate::aggregator_client::MockAggregatorHTTPClient;
+
+    use super::test_utils::StackMetricsRecorder;
+    use super::*;
+
+    #[tokio::test]
+    async fn record_aggregator_requests_outcome() {
+        let mut aggregator_client = MockAggregatorHTTPClient::new();
+        aggregator_client
+            .expect_get_content()
+            .times(1)
+            .returning(|_| Ok("content".to_string()));
+        aggregator_client.expect_get_content().returning(|_| {
+            Err(AggregatorClientError::RemoteServerTechnical(anyhow!(
+                "error"
+            )))
+        });
+        let metrics_recorder = Arc::new(StackMetricsRecorder::default());
+        let client =
+            MeteredAggregatorClient::new(Arc::new(aggregator_client), metrics_recorder.clone());
+
+        client
+            .get_content(AggregatorRequest::ListCertificates)
+            .await
+            .unwrap();
+        client
+            .get_content(AggregatorRequest::ListSnapshots)
+            .await
+            .expect_err("get_content should fail");
+
+        assert_eq!(
+            vec![
+                (AggregatorRequest::ListCertificates.route(), true),
+                (AggregatorRequest::ListSnapshots.route(), false)
+            ],
+            *metrics_recorder.aggregator_requests.lock().unwrap()
+        );
+    }
+}
@@ -3,6 +3,10 @@
 //! In order to do so it defines a [MithrilStakeDistributionClient] which exposes the following features:
 //!  - [get][MithrilStakeDistributionClient::get]: get a Mithril stake distribution data from its hash
 //!  - [list][MithrilStakeDistributionClient::list]: get the list of available Mithril stake distribution
+//!  - [verify][MithrilStakeDistributionClient::verify]: check that a Mithril stake distribution is
+//!    the one signed by a certificate
+//!  - [cross_check_signers][MithrilStakeDistributionClient::cross_check_signers]: check that the
+//!    signers of a Mithril stake distribution are the parties listed in its certificate
 //!
 //! # Get a Mithril stake distribution
 //!
@@ -20,6 +24,29 @@
 //! # }
 //! ```
 //!
+//! # Verify a Mithril stake distribution
+//!
+//! To check that a Mithril stake distribution is the one signed by its certificate, once the
+//! certificate chain has been verified.
+//!
+//! ```no_run
+//! # async fn run() -> mithril_client::MithrilResult<()> {
+//! use mithril_client::ClientBuilder;
+//!
+//! let client = ClientBuilder::aggregator("YOUR_AGGREGATOR_ENDPOINT", "YOUR_GENESIS_VERIFICATION_KEY").build()?;
+//! let mithril_stake_distribution = client.mithril_stake_distribution().get("MITHRIL_STAKE_DISTRIBUTION_HASH").await?.unwrap();
+//! let certificate = client
+//!     .certificate()
+//!     .verify_chain(&mithril_stake_distribution.certificate_hash)
+//!     .await?;
+//!
+//! client
+//!     .mithril_stake_distribution()
+//!     .verify(&mithril_stake_distribution, &certificate)?;
+//! #    Ok(())
+//! # }
+//! ```
+//!
 //! # List available Mithril stake distributions
 //!
 //! To list available Mithril stake distributions using the [ClientBuilder][crate::client::ClientBuilder].
@@ -38,22 +65,110 @@
 //! # }
 //! ```
 
+use std::borrow::Cow;
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
-use crate::aggregator_client::{AggregatorClient, AggregatorClientError, AggregatorRequest};
-use anyhow::Context;
+use crate::aggregator_client::{
+    deserialize_content_reader, AggregatorClient, AggregatorClientError, AggregatorRequest,
+};
+use crate::common::entities::{Epoch, PartyId, ProtocolParameters, Stake};
+use crate::common::messages::SignerWithStakeMessagePartRef;
+use crate::common::protocol::AggregateVerificationKeyProgress;
+use crate::compute::ComputeExecutor;
+use anyhow::{anyhow, Context};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    MessageBuilder, MithrilCertificate, MithrilResult, MithrilStakeDistribution,
+    MithrilStakeDistributionListItem,
+};
+
+/// A difference between the signers of a Mithril stake distribution and the parties listed in
+/// the metadata of its certificate.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum SignersDiscrepancy {
+    /// A signer of the stake distribution is not listed in the certificate.
+    #[error("party '{party_id}' is a signer of the stake distribution but is not listed in the certificate")]
+    MissingFromCertificate {
+        /// Party id of the signer
+        party_id: PartyId,
+
+        /// Stake of the signer in the stake distribution
+        stake: Stake,
+    },
+
+    /// A party listed in the certificate is not a signer of the stake distribution.
+    #[error("party '{party_id}' is listed in the certificate but is not a signer of the stake distribution")]
+    MissingFromStakeDistribution {
+        /// Party id of the party
+        party_id: PartyId,
+
+        /// Stake of the party in the certificate
+        stake: Stake,
+    },
+
+    /// A party has different stakes in the stake distribution and in the certificate.
+    #[error("party '{party_id}' has a stake of {stake_distribution_stake} in the stake distribution but of {certificate_stake} in the certificate")]
+    StakeMismatch {
+        /// Party id of the party
+        party_id: PartyId,
+
+        /// Stake of the party in the stake distribution
+        stake_distribution_stake: Stake,
+
+        /// Stake of the party in the certificate
+        certificate_stake: Stake,
+    },
+}
+
+impl SignersDiscrepancy {
+    /// Party id of the party concerned by the discrepancy
+    pub fn party_id(&self) -> &str {
+        match self {
+            Self::MissingFromCertificate { party_id, .. }
+            | Self::MissingFromStakeDistribution { party_id, .. }
+            | Self::StakeMismatch { party_id, .. } => party_id,
+        }
+    }
+}
+
+/// Error raised when the signers of a Mithril stake distribution are not the parties listed in
+/// its certificate, see [MithrilStakeDistributionClient::cross_check_signers].
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("Mithril stake distribution '{hash}' signers do not match the parties listed in certificate '{certificate_hash}', {} discrepancies found", discrepancies.len())]
+pub struct SignersCrossCheckError {
+    /// Hash of the Mithril stake distribution
+    pub hash: String,
 
-use crate::{MithrilResult, MithrilStakeDistribution, MithrilStakeDistributionListItem};
+    /// Hash of the certificate
+    pub certificate_hash: String,
+
+    /// Discrepancies found, ordered by party id
+    pub discrepancies: Vec<SignersDiscrepancy>,
+}
 
 /// HTTP client for MithrilStakeDistribution API from the Aggregator
 pub struct MithrilStakeDistributionClient {
     aggregator_client: Arc<dyn AggregatorClient>,
+    compute_executor: ComputeExecutor,
 }
 
 impl MithrilStakeDistributionClient {
     /// Constructs a new `MithrilStakeDistributionClient`.
     pub fn new(aggregator_client: Arc<dyn AggregatorClient>) -> Self {
-        Self { aggregator_client }
+        Self {
+            aggregator_client,
+            compute_executor: ComputeExecutor::default(),
+        }
+    }
+
+    /// Set the [ComputeExecutor] on which [get_and_verify][Self::get_and_verify] computes the
+    /// aggregate verification keys of the stake distributions.
+    pub fn with_compute_executor(mut self, compute_executor: ComputeExecutor) -> Self {
+        self.compute_executor = compute_executor;
+        self
     }
 
     /// Fetch a list of signed MithrilStakeDistribution
@@ -70,17 +185,20 @@ impl MithrilStakeDistributionClient {
     }
 
     /// Get the given stake distribution data. If it cannot be found, a None is returned.
+    ///
+    /// The stake distribution is deserialized while its content is downloaded, see
+    /// [AggregatorClient::get_content_reader].
     pub async fn get(&self, hash: &str) -> MithrilResult<Option<MithrilStakeDistribution>> {
         match self
             .aggregator_client
-            .get_content(AggregatorRequest::GetMithrilStakeDistribution {
+            .get_content_reader(AggregatorRequest::GetMithrilStakeDistribution {
                 hash: hash.to_string(),
             })
             .await
         {
-            Ok(content) => {
+            Ok(reader) => {
                 let stake_distribution_entity: MithrilStakeDistribution =
-                    serde_json::from_str(&content).with_context(|| {
+                    deserialize_content_reader(reader).await.with_context(|| {
                         "MithrilStakeDistribution Client can not deserialize artifact"
                     })?;
 
@@ -90,4 +208,408 @@ impl MithrilStakeDistributionClient {
             Err(e) => Err(e.into()),
         }
     }
+
+    /// Get the given stake distribution and check that it is the one signed by the given
+    /// certificate, without retaining it.
+    ///
+    /// The stake distribution is deserialized while its content is downloaded, so its JSON
+    /// content is never held in memory as a whole.
+    pub async fn get_and_verify(
+        &self,
+        hash: &str,
+        certificate: &MithrilCertificate,
+    ) -> MithrilResult<()> {
+        let reader = self
+            .aggregator_client
+            .get_content_reader(AggregatorRequest::GetMithrilStakeDistribution {
+                hash: hash.to_string(),
+            })
+            .await
+            .with_context(|| {
+                format!("MithrilStakeDistribution Client can not get the artifact '{hash}'")
+            })?;
+        let mithril_stake_distribution: MithrilStakeDistribution =
+            deserialize_content_reader(reader).await.with_context(|| {
+                format!("MithrilStakeDistribution Client can not read the artifact '{hash}'")
+            })?;
+
+        let certificate = certificate.clone();
+        self.compute_executor
+            .run(move || {
+                Self::verify_stake_distribution(
+                    &mithril_stake_distribution,
+                    &certificate,
+                    MessageBuilder::new(),
+                )
+            })
+            .await?
+    }
+
+    /// Check that the given Mithril stake distribution is the one signed by the given
+    /// certificate: the aggregate verification key of its signers is recomputed and compared to
+    /// the one of the certificate protocol message.
+    ///
+    /// Warning: this doesn't verify the certificate itself, its chain must be verified using
+    /// [CertificateClient::verify_chain][crate::certificate_client::CertificateClient::verify_chain].
+    pub fn verify(
+        &self,
+        mithril_stake_distribution: &MithrilStakeDistribution,
+        certificate: &MithrilCertificate,
+    ) -> MithrilResult<()> {
+        self.verify_with_progress(mithril_stake_distribution, certificate, |_| {})
+    }
+
+    /// Same as [verify][MithrilStakeDistributionClient::verify], calling `on_progress` each
+    /// time a signer is registered in the computation of the aggregate verification key.
+    pub fn verify_with_progress<F>(
+        &self,
+        mithril_stake_distribution: &MithrilStakeDistribution,
+        certificate: &MithrilCertificate,
+        on_progress: F,
+    ) -> MithrilResult<()>
+    where
+        F: Fn(AggregateVerificationKeyProgress) + Send + Sync + 'static,
+    {
+        Self::verify_stake_distribution(
+            mithril_stake_distribution,
+            certificate,
+            MessageBuilder::new().with_aggregate_verification_key_progress(on_progress),
+        )
+    }
+
+    /// Check that the given JSON serialized Mithril stake distribution is the one signed by the
+    /// given certificate.
+    ///
+    /// The hex encoded keys of its signers are borrowed from `json` rather than copied, use this
+    /// when the stake distribution only needs to be verified, not retained.
+    pub fn verify_json(&self, json: &[u8], certificate: &MithrilCertificate) -> MithrilResult<()> {
+        let mithril_stake_distribution: MithrilStakeDistributionRef = serde_json::from_slice(json)
+            .with_context(|| "MithrilStakeDistribution Client can not deserialize artifact")?;
+
+        Self::verify_signers(
+            mithril_stake_distribution,
+            certificate,
+            MessageBuilder::new(),
+        )
+    }
+
+    /// Check that the signers of the given Mithril stake distribution are the parties listed in
+    /// the metadata of the given certificate, with the same stakes.
+    ///
+    /// This catches an aggregator serving a stake distribution inconsistent with the certificate
+    /// it claims, all the differences are listed in a [SignersCrossCheckError].
+    ///
+    /// Warning: like [verify][MithrilStakeDistributionClient::verify] this doesn't verify the
+    /// certificate itself.
+    pub fn cross_check_signers(
+        &self,
+        mithril_stake_distribution: &MithrilStakeDistribution,
+        certificate: &MithrilCertificate,
+    ) -> MithrilResult<()> {
+        let mut certificate_parties: BTreeMap<&str, Stake> = certificate
+            .metadata
+            .signers
+            .iter()
+            .map(|party| (party.party_id.as_str(), party.stake))
+            .collect();
+        let stake_distribution_signers: BTreeMap<&str, Stake> = mithril_stake_distribution
+            .signers_with_stake
+            .iter()
+            .map(|signer| (signer.party_id.as_str(), signer.stake))
+            .collect();
+
+        let mut discrepancies = vec![];
+        for (party_id, stake) in stake_distribution_signers {
+            match certificate_parties.remove(party_id) {
+                None => discrepancies.push(SignersDiscrepancy::MissingFromCertificate {
+                    party_id: party_id.to_string(),
+                    stake,
+                }),
+                Some(certificate_stake) if certificate_stake != stake => {
+                    discrepancies.push(SignersDiscrepancy::StakeMismatch {
+                        party_id: party_id.to_string(),
+                        stake_distribution_stake: stake,
+                        certificate_stake,
+                    })
+                }
+                Some(_) => {}
+            }
+        }
+        discrepancies.extend(certificate_parties.into_iter().map(|(party_id, stake)| {
+            SignersDiscrepancy::MissingFromStakeDistribution {
+                party_id: party_id.to_string(),
+                stake,
+            }
+        }));
+        discrepancies.sort_by(|a, b| a.party_id().cmp(b.party_id()));
+
+        if !discrepancies.is_empty() {
+            return Err(SignersCrossCheckError {
+                hash: mithril_stake_distribution.hash.clone(),
+                certificate_hash: certificate.hash.clone(),
+                discrepancies,
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+
+    fn verify_stake_distribution(
+        mithril_stake_distribution: &MithrilStakeDistribution,
+        certificate: &MithrilCertificate,
+        message_builder: MessageBuilder,
+    ) -> MithrilResult<()> {
+        let signers: Vec<SignerWithStakeMessagePartRef> = mithril_stake_distribution
+            .signers_with_stake
+            .iter()
+            .map(SignerWithStakeMessagePartRef::from)
+            .collect();
+
+        Self::verify_signers(
+            MithrilStakeDistributionRef {
+                epoch: mithril_stake_distribution.epoch,
+                signers_with_stake: signers,
+                hash: Cow::Borrowed(&mithril_stake_distribution.hash),
+                certificate_hash: Cow::Borrowed(&mithril_stake_distribution.certificate_hash),
+                protocol_parameters: mithril_stake_distribution.protocol_parameters.clone(),
+            },
+            certificate,
+            message_builder,
+        )
+    }
+
+    fn verify_signers(
+        mithril_stake_distribution: MithrilStakeDistributionRef,
+        certificate: &MithrilCertificate,
+        message_builder: MessageBuilder,
+    ) -> MithrilResult<()> {
+        if mithril_stake_distribution.certificate_hash != certificate.hash {
+            return Err(anyhow!(
+                "Mithril stake distribution '{}' is certified by certificate '{}', not by certificate '{}'",
+                mithril_stake_distribution.hash,
+                mithril_stake_distribution.certificate_hash,
+                certificate.hash
+            ));
+        }
+        if mithril_stake_distribution.epoch != certificate.beacon.epoch {
+            return Err(anyhow!(
+                "Mithril stake distribution '{}' is of epoch {}, but its certificate '{}' is of epoch {}",
+                mithril_stake_distribution.hash,
+                mithril_stake_distribution.epoch,
+                certificate.hash,
+                certificate.beacon.epoch
+            ));
+        }
+
+        let message = message_builder
+            .compute_mithril_stake_distribution_message_of_signers(
+                mithril_stake_distribution.epoch,
+                &mithril_stake_distribution.signers_with_stake,
+                &mithril_stake_distribution.protocol_parameters,
+            )
+            .with_context(|| {
+                format!(
+                    "Could not compute the message of Mithril stake distribution '{}'",
+                    mithril_stake_distribution.hash
+                )
+            })?;
+        if !certificate.match_message(&message) {
+            return Err(anyhow!(
+                "Mithril stake distribution '{}' does not match the message signed by its certificate '{}'",
+                mithril_stake_distribution.hash,
+                certificate.hash
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// The parts of a Mithril stake distribution needed to verify it, borrowing its signers.
+#[derive(Deserialize)]
+struct MithrilStakeDistributionRef<'a> {
+    epoch: Epoch,
+    #[serde(borrow, rename = "signers")]
+    signers_with_stake: Vec<SignerWithStakeMessagePartRef<'a>>,
+    #[serde(borrow)]
+    hash: Cow<'a, str>,
+    #[serde(borrow)]
+    certificate_hash: Cow<'a, str>,
+    protocol_parameters: ProtocolParameters,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::aggregator_client::MockAggregatorHTTPClient;
+    use crate::common::entities::StakeDistributionParty;
+    use crate::common::messages::SignerWithStakeMessagePart;
+
+    use super::*;
+
+    fn certificate_signing(
+        mithril_stake_distribution: &MithrilStakeDistribution,
+    ) -> MithrilCertificate {
+        let message = MessageBuilder::new()
+            .compute_mithril_stake_distribution_message(mithril_stake_distribution)
+            .unwrap();
+        let mut certificate = MithrilCertificate::dummy();
+        certificate.hash = mithril_stake_distribution.certificate_hash.clone();
+        certificate.beacon.epoch = mithril_stake_distribution.epoch;
+        certificate.signed_message = message.compute_hash();
+        certificate.protocol_message = message;
+
+        certificate
+    }
+
+    fn client() -> MithrilStakeDistributionClient {
+        MithrilStakeDistributionClient::new(Arc::new(MockAggregatorHTTPClient::new()))
+    }
+
+    #[test]
+    fn verify_a_mithril_stake_distribution_signed_by_its_certificate() {
+        let mithril_stake_distribution = MithrilStakeDistribution::dummy();
+        let certificate = certificate_signing(&mithril_stake_distribution);
+
+        client()
+            .verify(&mithril_stake_distribution, &certificate)
+            .unwrap();
+    }
+
+    #[test]
+    fn verify_a_mithril_stake_distribution_not_signed_by_the_certificate_fails() {
+        let mithril_stake_distribution = MithrilStakeDistribution::dummy();
+        let certificate = certificate_signing(&mithril_stake_distribution);
+
+        let mut other_certificate = certificate.clone();
+        other_certificate.hash = "other-certificate-hash".to_string();
+        client()
+            .verify(&mithril_stake_distribution, &other_certificate)
+            .expect_err("verify should fail with a certificate of another artifact");
+
+        let mut other_epoch = mithril_stake_distribution.clone();
+        other_epoch.epoch = mithril_stake_distribution.epoch + 1;
+        client()
+            .verify(&other_epoch, &certificate)
+            .expect_err("verify should fail with a certificate of another epoch");
+
+        let mut other_signers = mithril_stake_distribution.clone();
+        other_signers.signers_with_stake[0].stake += 1;
+        client()
+            .verify(&other_signers, &certificate)
+            .expect_err("verify should fail if the signers are not the certified ones");
+    }
+
+    #[test]
+    fn verify_a_json_mithril_stake_distribution_borrowing_its_signers() {
+        let mithril_stake_distribution = MithrilStakeDistribution::dummy();
+        let certificate = certificate_signing(&mithril_stake_distribution);
+        let json = serde_json::to_vec(&mithril_stake_distribution).unwrap();
+
+        client().verify_json(&json, &certificate).unwrap();
+
+        let mut other_signers = mithril_stake_distribution.clone();
+        other_signers.signers_with_stake[0].stake += 1;
+        client()
+            .verify_json(&serde_json::to_vec(&other_signers).unwrap(), &certificate)
+            .expect_err("verify should fail if the signers are not the certified ones");
+    }
+
+    fn certificate_listing(
+        mithril_stake_distribution: &MithrilStakeDistribution,
+    ) -> MithrilCertificate {
+        let mut certificate = MithrilCertificate::dummy();
+        certificate.hash = mithril_stake_distribution.certificate_hash.clone();
+        certificate.metadata.signers = mithril_stake_distribution
+            .signers_with_stake
+            .iter()
+            .map(|signer| StakeDistributionParty {
+                party_id: signer.party_id.clone(),
+                stake: signer.stake,
+            })
+            .collect();
+
+        certificate
+    }
+
+    #[test]
+    fn cross_check_signers_listed_in_the_certificate() {
+        let mithril_stake_distribution = MithrilStakeDistribution::dummy();
+        let certificate = certificate_listing(&mithril_stake_distribution);
+
+        client()
+            .cross_check_signers(&mithril_stake_distribution, &certificate)
+            .unwrap();
+    }
+
+    #[test]
+    fn cross_check_signers_reports_all_the_discrepancies() {
+        let mut mithril_stake_distribution = MithrilStakeDistribution::dummy();
+        mithril_stake_distribution
+            .signers_with_stake
+            .push(SignerWithStakeMessagePart {
+                party_id: "pool2".to_string(),
+                stake: 20,
+                ..SignerWithStakeMessagePart::dummy()
+            });
+        let mut certificate = certificate_listing(&mithril_stake_distribution);
+        let removed_party = certificate.metadata.signers.remove(0);
+        certificate.metadata.signers[0].stake = 21;
+        certificate.metadata.signers.push(StakeDistributionParty {
+            party_id: "pool3".to_string(),
+            stake: 10,
+        });
+
+        let error = client()
+            .cross_check_signers(&mithril_stake_distribution, &certificate)
+            .expect_err("cross check should fail");
+
+        assert_eq!(
+            Some(&SignersCrossCheckError {
+                hash: mithril_stake_distribution.hash.clone(),
+                certificate_hash: certificate.hash.clone(),
+                discrepancies: vec![
+                    SignersDiscrepancy::MissingFromCertificate {
+                        party_id: removed_party.party_id,
+                        stake: removed_party.stake,
+                    },
+                    SignersDiscrepancy::StakeMismatch {
+                        party_id: "pool2".to_string(),
+                        stake_distribution_stake: 20,
+                        certificate_stake: 21,
+                    },
+                    SignersDiscrepancy::MissingFromStakeDistribution {
+                        party_id: "pool3".to_string(),
+                        stake: 10,
+                    },
+                ],
+            }),
+            error.downcast_ref::<SignersCrossCheckError>()
+        );
+    }
+
+    #[tokio::test]
+    async fn get_deserializes_the_streamed_content() {
+        let mut aggregator_client = MockAggregatorHTTPClient::new();
+        aggregator_client
+            .expect_get_content_reader()
+            .returning(|_| {
+                let content = serde_json::to_vec(&MithrilStakeDistribution::dummy()).unwrap();
+                Ok(Box::new(std::io::Cursor::new(content)))
+            })
+            .times(2);
+        let client = MithrilStakeDistributionClient::new(Arc::new(aggregator_client));
+        let certificate = certificate_signing(&MithrilStakeDistribution::dummy());
+
+        let mithril_stake_distribution = client.get("hash-123").await.unwrap();
+        client
+            .get_and_verify("hash-123", &certificate)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            Some(MithrilStakeDistribution::dummy()),
+            mithril_stake_distribution
+        );
+    }
 }
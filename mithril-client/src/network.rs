@@ -0,0 +1,133 @@
+//! Presets of the official Mithril networks.
+//!
+//! A [Network] embeds the endpoint of the official aggregator of a Cardano network and the
+//! genesis verification key of its certificate chain, so a client can be built without copying
+//! them from the Mithril documentation using
+//! [ClientBuilder::for_network][crate::ClientBuilder::for_network].
+//!
+//! Both values can still be overridden, ie: to use another aggregator of the same network.
+//!
+//! **Note:** _Available using crate feature_ **network_presets**.
+//!
+//! # Verify the latest snapshot of the mainnet
+//!
+//! ```no_run
+//! # async fn run() -> mithril_client::MithrilResult<()> {
+//! use mithril_client::{network::Network, ClientBuilder};
+//!
+//! let client = ClientBuilder::for_network(Network::Mainnet).build()?;
+//!
+//! let snapshots = client.snapshot().list().await?;
+//! let certificate = client.certificate().verify_chain(&snapshots[0].certificate_hash).await?;
+//! #    Ok(())
+//! # }
+//! ```
+
+use anyhow::anyhow;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use crate::MithrilError;
+
+/// An official Mithril network.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Network {
+    /// Cardano mainnet, served by the `release-mainnet` aggregator.
+    Mainnet,
+
+    /// Cardano preprod testnet, served by the `release-preprod` aggregator.
+    Preprod,
+
+    /// Cardano preview testnet, served by the `pre-release-preview` aggregator.
+    Preview,
+}
+
+impl Network {
+    /// All the official networks.
+    pub const ALL: [Network; 3] = [Network::Mainnet, Network::Preprod, Network::Preview];
+
+    /// Name of the network, as used by Cardano.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Network::Mainnet => "mainnet",
+            Network::Preprod => "preprod",
+            Network::Preview => "preview",
+        }
+    }
+
+    /// Endpoint of the official aggregator of the network.
+    pub fn aggregator_endpoint(&self) -> &'static str {
+        match self {
+            Network::Mainnet => "https://aggregator.release-mainnet.api.mithril.network/aggregator",
+            Network::Preprod => "https://aggregator.release-preprod.api.mithril.network/aggregator",
+            Network::Preview => {
+                "https://aggregator.pre-release-preview.api.mithril.network/aggregator"
+            }
+        }
+    }
+
+    /// URL where the genesis verification key of the network is published, to
+    /// [fetch][crate::genesis::fetch_genesis_verification_key] it and check it against a pinned
+    /// fingerprint instead of trusting the embedded one.
+    pub fn genesis_verification_key_url(&self) -> &'static str {
+        match self {
+            Network::Mainnet => "https://raw.githubusercontent.com/input-output-hk/mithril/main/mithril-infra/configuration/release-mainnet/genesis.vkey",
+            Network::Preprod => "https://raw.githubusercontent.com/input-output-hk/mithril/main/mithril-infra/configuration/release-preprod/genesis.vkey",
+            Network::Preview => "https://raw.githubusercontent.com/input-output-hk/mithril/main/mithril-infra/configuration/pre-release-preview/genesis.vkey",
+        }
+    }
+
+    /// Genesis verification key of the certificate chain of the network.
+    pub fn genesis_verification_key(&self) -> &'static str {
+        match self {
+            Network::Mainnet => "5b3139312c36362c3134302c3138352c3133382c31312c3233372c3230372c3235302c3134342c32372c322c3138382c33302c31322c38312c3135352c3230342c31302c3137392c37352c32332c3133382c3139362c3231372c352c31342c32302c35372c37392c33392c3137365d",
+            Network::Preprod | Network::Preview => "5b3132372c37332c3132342c3136312c362c3133372c3133312c3231332c3230372c3131372c3139382c38352c3137362c3139392c3136322c3234312c36382c3132332c3131392c3134352c31332c3233322c3234332c34392c3232392c322c3234392c3230352c3230352c33392c3233352c34345d",
+        }
+    }
+}
+
+impl Display for Network {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Network {
+    type Err = MithrilError;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Network::ALL
+            .into_iter()
+            .find(|network| network.name() == name.to_lowercase())
+            .ok_or(anyhow!(
+                "Unknown network '{name}', expected one of: mainnet, preprod, preview"
+            ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::crypto_helper::ProtocolGenesisVerificationKey;
+
+    use super::*;
+
+    #[test]
+    fn parse_network_from_its_name() {
+        for network in Network::ALL {
+            assert_eq!(network, network.to_string().parse::<Network>().unwrap());
+        }
+        assert_eq!(Network::Mainnet, "MainNet".parse::<Network>().unwrap());
+
+        "sanchonet"
+            .parse::<Network>()
+            .expect_err("parsing an unknown network should fail");
+    }
+
+    #[test]
+    fn embedded_genesis_verification_keys_are_valid() {
+        for network in Network::ALL {
+            ProtocolGenesisVerificationKey::try_from(network.genesis_verification_key())
+                .unwrap_or_else(|_| panic!("invalid genesis verification key for {network}"));
+        }
+    }
+}
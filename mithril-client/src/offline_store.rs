@@ -0,0 +1,656 @@
+//! Keep a local copy of aggregator artifacts to use the client while offline.
+//!
+//! Ahead of a known offline window, [Client::prefetch][crate::Client::prefetch] downloads and
+//! verifies a declared set of artifacts described by a [PrefetchPlan], then keeps them in an
+//! [OfflineStore] (see [ClientBuilder::with_offline_store][crate::ClientBuilder::with_offline_store]).
+//! Only verified artifacts are stored, and they are served back when the aggregator can't be
+//! reached.
+//!
+//! The prefetched artifacts are pinned: they are served whatever their age until they are
+//! [unpinned][crate::Client::unpin]. The lists of artifacts are not pinned, they are only served
+//! while they are younger than the
+//! [maximum age][crate::ClientBuilder::with_offline_store_max_age] of the store contents.
+//!
+//! Two stores are available:
+//!  - [MemoryOfflineStore]: keeps the artifacts in memory, they are lost when the program stops.
+//!  - [FsOfflineStore]: keeps the artifacts as files in a directory (requires the **fs** feature).
+//!
+//! # Prefetch artifacts before going offline
+//!
+//! ```no_run
+//! # async fn run() -> mithril_client::MithrilResult<()> {
+//! use mithril_client::{offline_store::{FsOfflineStore, PrefetchPlan}, ClientBuilder};
+//! use std::sync::Arc;
+//!
+//! let store = Arc::new(FsOfflineStore::new("/home/user/mithril_store")?);
+//! let client = ClientBuilder::aggregator("YOUR_AGGREGATOR_ENDPOINT", "YOUR_GENESIS_VERIFICATION_KEY")
+//!     .with_offline_store(store)
+//!     .build()?;
+//!
+//! let plan = PrefetchPlan::new()
+//!     .with_latest_certificates(5)
+//!     .with_latest_mithril_stake_distributions(2)
+//!     .with_latest_snapshots(1);
+//! let report = client.prefetch(plan).await?;
+//!
+//! println!("Prefetched {} snapshots", report.snapshots.len());
+//!
+//! // Once back online, let the prefetched artifacts expire
+//! client.unpin(&report)?;
+//! #    Ok(())
+//! # }
+//! ```
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use slog::{warn, Logger};
+use std::collections::HashMap;
+use std::io::{Cursor, Read};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use crate::aggregator_client::{AggregatorClient, AggregatorClientError, AggregatorRequest};
+use crate::metrics::MetricsRecorder;
+use crate::MithrilResult;
+
+cfg_fs! {
+    use anyhow::Context;
+    use sha2::{Digest, Sha256};
+    use std::path::{Path, PathBuf};
+}
+
+/// Default maximum age of the unpinned contents served by an [OfflineAggregatorClient].
+pub const DEFAULT_OFFLINE_STORE_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Content stored for a route in an [OfflineStore].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StoredContent {
+    /// Content of the aggregator response
+    pub content: String,
+
+    /// Date and time at which the content was stored
+    pub stored_at: DateTime<Utc>,
+
+    /// Whether the content is served whatever its age
+    pub pinned: bool,
+}
+
+impl StoredContent {
+    /// Constructs a new `StoredContent` stored now.
+    pub fn new(content: String, pinned: bool) -> Self {
+        Self {
+            content,
+            stored_at: Utc::now(),
+            pinned,
+        }
+    }
+
+    /// Check if the content can still be served at the given date, given the maximum age of the
+    /// unpinned contents.
+    pub fn is_fresh(&self, max_age: Duration, now: DateTime<Utc>) -> bool {
+        self.pinned
+            || chrono::Duration::from_std(max_age)
+                .is_ok_and(|max_age| now.signed_duration_since(self.stored_at) <= max_age)
+    }
+}
+
+/// Storage of the aggregator responses, identified by the route of their request.
+pub trait OfflineStore: Sync + Send {
+    /// Get the content stored for the given route, if any.
+    fn get(&self, route: &str) -> MithrilResult<Option<StoredContent>>;
+
+    /// Store the content of the given route, replacing any previously stored content.
+    fn store(&self, route: &str, content: &StoredContent) -> MithrilResult<()>;
+
+    /// Remove the content stored for the given route, if any.
+    fn remove(&self, route: &str) -> MithrilResult<()>;
+}
+
+/// An [OfflineStore] that keeps the artifacts in memory.
+#[derive(Default)]
+pub struct MemoryOfflineStore {
+    contents: RwLock<HashMap<String, StoredContent>>,
+}
+
+impl MemoryOfflineStore {
+    /// Constructs a new empty `MemoryOfflineStore`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl OfflineStore for MemoryOfflineStore {
+    fn get(&self, route: &str) -> MithrilResult<Option<StoredContent>> {
+        let contents = self
+            .contents
+            .read()
+            .map_err(|e| anyhow!("Offline store lock is poisoned: {e}"))?;
+
+        Ok(contents.get(route).cloned())
+    }
+
+    fn store(&self, route: &str, content: &StoredContent) -> MithrilResult<()> {
+        let mut contents = self
+            .contents
+            .write()
+            .map_err(|e| anyhow!("Offline store lock is poisoned: {e}"))?;
+        contents.insert(route.to_string(), content.clone());
+
+        Ok(())
+    }
+
+    fn remove(&self, route: &str) -> MithrilResult<()> {
+        let mut contents = self
+            .contents
+            .write()
+            .map_err(|e| anyhow!("Offline store lock is poisoned: {e}"))?;
+        contents.remove(route);
+
+        Ok(())
+    }
+}
+
+cfg_fs! {
+/// An [OfflineStore] that keeps each artifact as a file in a directory.
+pub struct FsOfflineStore {
+    directory: PathBuf,
+}
+
+impl FsOfflineStore {
+    /// Constructs a new `FsOfflineStore` that use the given directory, creating it if needed.
+    pub fn new<P: AsRef<Path>>(directory: P) -> MithrilResult<Self> {
+        let directory = directory.as_ref().to_path_buf();
+        std::fs::create_dir_all(&directory).with_context(|| {
+            format!(
+                "Could not create offline store directory '{}'",
+                directory.display()
+            )
+        })?;
+
+        Ok(Self { directory })
+    }
+
+    fn file_path(&self, route: &str) -> PathBuf {
+        // Name the file after the hash of the route so a stored artifact can't escape the store
+        // directory and two routes never share a file.
+        let file_name = hex::encode(Sha256::digest(route.as_bytes()));
+        self.directory.join(format!("{file_name}.json"))
+    }
+}
+
+impl OfflineStore for FsOfflineStore {
+    fn get(&self, route: &str) -> MithrilResult<Option<StoredContent>> {
+        let path = self.file_path(route);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let file_content = std::fs::read_to_string(&path).with_context(|| {
+            format!("Could not read offline store file '{}'", path.display())
+        })?;
+        let content = serde_json::from_str(&file_content).with_context(|| {
+            format!("Could not deserialize offline store file '{}'", path.display())
+        })?;
+
+        Ok(Some(content))
+    }
+
+    fn store(&self, route: &str, content: &StoredContent) -> MithrilResult<()> {
+        let path = self.file_path(route);
+        let file_content = serde_json::to_string(content)
+            .with_context(|| format!("Could not serialize the stored content of '{route}'"))?;
+        std::fs::write(&path, file_content).with_context(|| {
+            format!("Could not write offline store file '{}'", path.display())
+        })
+    }
+
+    fn remove(&self, route: &str) -> MithrilResult<()> {
+        let path = self.file_path(route);
+        if !path.exists() {
+            return Ok(());
+        }
+
+        std::fs::remove_file(&path).with_context(|| {
+            format!("Could not remove offline store file '{}'", path.display())
+        })
+    }
+}
+}
+
+/// Store the given verified artifact as the content of the given request.
+pub(crate) fn store_verified<T: Serialize>(
+    store: &dyn OfflineStore,
+    request: &AggregatorRequest,
+    artifact: &T,
+    pinned: bool,
+) -> MithrilResult<()> {
+    let route = request.route();
+    let content = serde_json::to_string(artifact)
+        .map_err(|e| anyhow!("Could not serialize the artifact of '{route}': {e}"))?;
+
+    store.store(&route, &StoredContent::new(content, pinned))
+}
+
+/// Unpin the content stored for the given request, if any, so it expires once older than the
+/// maximum age of the store contents.
+pub(crate) fn unpin(store: &dyn OfflineStore, request: &AggregatorRequest) -> MithrilResult<()> {
+    let route = request.route();
+    match store.get(&route)? {
+        Some(content) if content.pinned => store.store(
+            &route,
+            &StoredContent {
+                pinned: false,
+                ..content
+            },
+        ),
+        _ => Ok(()),
+    }
+}
+
+/// Declares the artifacts to download and verify with [Client::prefetch][crate::Client::prefetch].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PrefetchPlan {
+    /// Number of the latest certificates to prefetch, with their chain.
+    pub latest_certificates: usize,
+
+    /// Number of the latest Mithril stake distributions to prefetch, with their certificate chain.
+    pub latest_mithril_stake_distributions: usize,
+
+    /// Number of the latest snapshots metadata to prefetch, with their certificate chain.
+    ///
+    /// Note: only the metadata are prefetched, not the snapshots archives.
+    pub latest_snapshots: usize,
+}
+
+impl PrefetchPlan {
+    /// Constructs a new empty `PrefetchPlan`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Prefetch the given number of the latest certificates.
+    pub fn with_latest_certificates(mut self, count: usize) -> Self {
+        self.latest_certificates = count;
+        self
+    }
+
+    /// Prefetch the given number of the latest Mithril stake distributions.
+    pub fn with_latest_mithril_stake_distributions(mut self, count: usize) -> Self {
+        self.latest_mithril_stake_distributions = count;
+        self
+    }
+
+    /// Prefetch the given number of the latest snapshots metadata.
+    pub fn with_latest_snapshots(mut self, count: usize) -> Self {
+        self.latest_snapshots = count;
+        self
+    }
+}
+
+/// Identifiers of the artifacts downloaded and verified by [Client::prefetch][crate::Client::prefetch].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PrefetchReport {
+    /// Hashes of the prefetched certificates, including the certificates of their chains
+    pub certificates: Vec<String>,
+
+    /// Hashes of the prefetched Mithril stake distributions
+    pub mithril_stake_distributions: Vec<String>,
+
+    /// Digests of the prefetched snapshots
+    pub snapshots: Vec<String>,
+}
+
+impl PrefetchReport {
+    /// Requests of the prefetched artifacts, which contents are pinned in the store.
+    pub(crate) fn pinned_requests(&self) -> Vec<AggregatorRequest> {
+        let certificates = self
+            .certificates
+            .iter()
+            .map(|hash| AggregatorRequest::GetCertificate { hash: hash.clone() });
+        let mithril_stake_distributions = self
+            .mithril_stake_distributions
+            .iter()
+            .map(|hash| AggregatorRequest::GetMithrilStakeDistribution { hash: hash.clone() });
+        let snapshots = self
+            .snapshots
+            .iter()
+            .map(|digest| AggregatorRequest::GetSnapshot {
+                digest: digest.clone(),
+            });
+
+        certificates
+            .chain(mithril_stake_distributions)
+            .chain(snapshots)
+            .collect()
+    }
+}
+
+/// An [AggregatorClient] that serves the contents of an [OfflineStore] when the aggregator can't
+/// be reached.
+///
+/// The unpinned contents are only served while they are younger than the
+/// [maximum age][Self::with_max_age], older ones are removed from the store.
+pub struct OfflineAggregatorClient {
+    aggregator_client: Arc<dyn AggregatorClient>,
+    store: Arc<dyn OfflineStore>,
+    max_age: Duration,
+    metrics_recorder: Option<Arc<dyn MetricsRecorder>>,
+    logger: Logger,
+}
+
+impl OfflineAggregatorClient {
+    /// Constructs a new `OfflineAggregatorClient`.
+    pub fn new(
+        aggregator_client: Arc<dyn AggregatorClient>,
+        store: Arc<dyn OfflineStore>,
+        logger: Logger,
+    ) -> Self {
+        Self {
+            aggregator_client,
+            store,
+            max_age: DEFAULT_OFFLINE_STORE_MAX_AGE,
+            metrics_recorder: None,
+            logger,
+        }
+    }
+
+    /// Set the maximum age of the unpinned contents served while the aggregator can't be
+    /// reached, default to [DEFAULT_OFFLINE_STORE_MAX_AGE].
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    /// Set the [MetricsRecorder] that will record the lookups in the store done while the
+    /// aggregator can't be reached.
+    pub fn with_metrics_recorder(mut self, metrics_recorder: Arc<dyn MetricsRecorder>) -> Self {
+        self.metrics_recorder = Some(metrics_recorder);
+        self
+    }
+
+    fn get_stored_content(
+        &self,
+        route: &str,
+        error: anyhow::Error,
+    ) -> Result<String, AggregatorClientError> {
+        let stored_content = self
+            .store
+            .get(route)
+            .map_err(AggregatorClientError::SubsystemError)?;
+        let fresh_content = match stored_content {
+            Some(content) if content.is_fresh(self.max_age, Utc::now()) => Some(content.content),
+            Some(_) => {
+                if let Err(remove_error) = self.store.remove(route) {
+                    warn!(
+                        self.logger, "Could not remove a stale content from the offline store";
+                        "route" => route, "error" => ?remove_error
+                    );
+                }
+                None
+            }
+            None => None,
+        };
+        if let Some(metrics_recorder) = &self.metrics_recorder {
+            metrics_recorder.record_cache_lookup("offline_store", fresh_content.is_some());
+        }
+
+        fresh_content.ok_or(AggregatorClientError::SubsystemError(error))
+    }
+}
+
+#[cfg_attr(target_family = "wasm", async_trait(?Send))]
+#[cfg_attr(not(target_family = "wasm"), async_trait)]
+impl AggregatorClient for OfflineAggregatorClient {
+    async fn get_content(
+        &self,
+        request: AggregatorRequest,
+    ) -> Result<String, AggregatorClientError> {
+        let route = request.route();
+
+        match self.aggregator_client.get_content(request).await {
+            Err(AggregatorClientError::SubsystemError(error)) => {
+                self.get_stored_content(&route, error)
+            }
+            result => result,
+        }
+    }
+
+    async fn get_content_reader(
+        &self,
+        request: AggregatorRequest,
+    ) -> Result<Box<dyn Read + Send>, AggregatorClientError> {
+        let route = request.route();
+
+        match self.aggregator_client.get_content_reader(request).await {
+            Err(AggregatorClientError::SubsystemError(error)) => {
+                let content = self.get_stored_content(&route, error)?;
+                Ok(Box::new(Cursor::new(content)))
+            }
+            result => result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::aggregator_client::MockAggregatorHTTPClient;
+    use crate::metrics::test_utils::StackMetricsRecorder;
+    use crate::test_utils;
+
+    use super::*;
+
+    fn offline_client(
+        result: fn() -> Result<String, AggregatorClientError>,
+        store: Arc<dyn OfflineStore>,
+    ) -> OfflineAggregatorClient {
+        let mut aggregator_client = MockAggregatorHTTPClient::new();
+        aggregator_client
+            .expect_get_content()
+            .returning(move |_| result());
+
+        OfflineAggregatorClient::new(
+            Arc::new(aggregator_client),
+            store,
+            test_utils::test_logger(),
+        )
+    }
+
+    fn unreachable_aggregator() -> Result<String, AggregatorClientError> {
+        Err(AggregatorClientError::SubsystemError(anyhow!("offline")))
+    }
+
+    fn store_with(route: &str, content: StoredContent) -> Arc<MemoryOfflineStore> {
+        let store = Arc::new(MemoryOfflineStore::new());
+        store.store(route, &content).unwrap();
+
+        store
+    }
+
+    fn stored_days_ago(days: i64, pinned: bool) -> StoredContent {
+        StoredContent {
+            content: "stored".to_string(),
+            stored_at: Utc::now() - chrono::Duration::days(days),
+            pinned,
+        }
+    }
+
+    #[tokio::test]
+    async fn do_not_record_unverified_aggregator_responses_in_the_store() {
+        let store = Arc::new(MemoryOfflineStore::new());
+        let client = offline_client(|| Ok("content".to_string()), store.clone());
+
+        let content = client
+            .get_content(AggregatorRequest::ListCertificates)
+            .await
+            .unwrap();
+
+        assert_eq!("content", content);
+        assert_eq!(
+            None,
+            store
+                .get(&AggregatorRequest::ListCertificates.route())
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn serve_stored_content_when_the_aggregator_is_unreachable() {
+        let route = AggregatorRequest::ListCertificates.route();
+        let store = store_with(&route, StoredContent::new("stored".to_string(), false));
+        let client = offline_client(unreachable_aggregator, store);
+
+        let content = client
+            .get_content(AggregatorRequest::ListCertificates)
+            .await
+            .unwrap();
+
+        assert_eq!("stored", content);
+    }
+
+    #[tokio::test]
+    async fn do_not_serve_and_remove_stale_unpinned_content() {
+        let route = AggregatorRequest::ListCertificates.route();
+        let store = store_with(&route, stored_days_ago(2, false));
+        let client = offline_client(unreachable_aggregator, store.clone());
+
+        client
+            .get_content(AggregatorRequest::ListCertificates)
+            .await
+            .expect_err("get_content should fail");
+
+        assert_eq!(None, store.get(&route).unwrap());
+    }
+
+    #[tokio::test]
+    async fn serve_pinned_content_whatever_its_age() {
+        let route = AggregatorRequest::ListCertificates.route();
+        let store = store_with(&route, stored_days_ago(2, true));
+        let client =
+            offline_client(unreachable_aggregator, store).with_max_age(Duration::from_secs(60));
+
+        let content = client
+            .get_content(AggregatorRequest::ListCertificates)
+            .await
+            .unwrap();
+
+        assert_eq!("stored", content);
+    }
+
+    #[test]
+    fn unpinned_content_expires_once_older_than_the_max_age() {
+        let request = AggregatorRequest::GetCertificate {
+            hash: "hash".to_string(),
+        };
+        let store = store_with(&request.route(), stored_days_ago(2, true));
+        assert!(store
+            .get(&request.route())
+            .unwrap()
+            .unwrap()
+            .is_fresh(Duration::from_secs(60), Utc::now()));
+
+        unpin(store.as_ref(), &request).unwrap();
+
+        let content = store.get(&request.route()).unwrap().unwrap();
+        assert!(!content.pinned);
+        assert!(!content.is_fresh(Duration::from_secs(60), Utc::now()));
+        assert!(content.is_fresh(Duration::from_secs(3 * 24 * 60 * 60), Utc::now()));
+    }
+
+    #[tokio::test]
+    async fn record_store_lookups_done_while_the_aggregator_is_unreachable() {
+        let store = store_with(
+            &AggregatorRequest::ListCertificates.route(),
+            StoredContent::new("stored".to_string(), true),
+        );
+        let metrics_recorder = Arc::new(StackMetricsRecorder::default());
+        let client = offline_client(unreachable_aggregator, store)
+            .with_metrics_recorder(metrics_recorder.clone());
+
+        client
+            .get_content(AggregatorRequest::ListCertificates)
+            .await
+            .unwrap();
+        client
+            .get_content(AggregatorRequest::ListSnapshots)
+            .await
+            .expect_err("get_content should fail");
+
+        assert_eq!(
+            vec![
+                ("offline_store".to_string(), true),
+                ("offline_store".to_string(), false)
+            ],
+            *metrics_recorder.cache_lookups.lock().unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn return_the_aggregator_error_if_nothing_is_stored() {
+        let client = offline_client(unreachable_aggregator, Arc::new(MemoryOfflineStore::new()));
+
+        let error = client
+            .get_content(AggregatorRequest::ListCertificates)
+            .await
+            .expect_err("get_content should fail");
+
+        assert!(
+            matches!(error, AggregatorClientError::SubsystemError(_)),
+            "Unexpected error: {error:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn do_not_serve_stored_content_for_logical_errors() {
+        let store = store_with(
+            &AggregatorRequest::ListCertificates.route(),
+            StoredContent::new("stored".to_string(), true),
+        );
+        let client = offline_client(
+            || {
+                Err(AggregatorClientError::RemoteServerLogical(anyhow!(
+                    "not found"
+                )))
+            },
+            store,
+        );
+
+        client
+            .get_content(AggregatorRequest::ListCertificates)
+            .await
+            .expect_err("get_content should fail");
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn fs_store_keep_contents_in_distinct_files_inside_its_directory() {
+        let directory = test_utils::get_test_directory(
+            "offline_store",
+            "fs_store_keep_contents_in_distinct_files_inside_its_directory",
+        );
+        let store = FsOfflineStore::new(&directory).unwrap();
+        let route = AggregatorRequest::GetCertificate {
+            hash: "../hash".to_string(),
+        }
+        .route();
+        let colliding_route = route.replace('/', "_");
+        let content = StoredContent::new("content".to_string(), true);
+
+        assert_eq!(None, store.get(&route).unwrap());
+        store.store(&route, &content).unwrap();
+        store
+            .store(
+                &colliding_route,
+                &StoredContent::new("other".to_string(), true),
+            )
+            .unwrap();
+
+        assert_eq!(Some(content), store.get(&route).unwrap());
+        assert_eq!(2, std::fs::read_dir(&directory).unwrap().count());
+
+        store.remove(&route).unwrap();
+        assert_eq!(None, store.get(&route).unwrap());
+        assert_eq!(1, std::fs::read_dir(&directory).unwrap().count());
+    }
+}
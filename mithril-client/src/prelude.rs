@@ -0,0 +1,28 @@
+//! Curated set of the types needed by most users of this crate.
+//!
+//! The types exported here are the stable import surface of the crate: they may only be removed
+//! or renamed with a major version bump, whereas the modules that define them can be
+//! reorganized between minor versions.
+//!
+//! ```no_run
+//! # async fn run() -> mithril_client::MithrilResult<()> {
+//! use mithril_client::prelude::*;
+//!
+//! let client: Client = ClientBuilder::aggregator("YOUR_AGGREGATOR_ENDPOINT", "YOUR_GENESIS_VERIFICATION_KEY")
+//!     .build()?;
+//!
+//! let snapshots: Vec<SnapshotListItem> = client.snapshot().list().await?;
+//! let certificate: MithrilCertificate = client
+//!     .certificate()
+//!     .verify_chain(&snapshots[0].certificate_hash)
+//!     .await?;
+//! #    Ok(())
+//! # }
+//! ```
+
+pub use crate::feedback::{FeedbackReceiver, MithrilEvent, SlogFeedbackReceiver};
+pub use crate::{
+    Client, ClientBuilder, MessageBuilder, MithrilCertificate, MithrilCertificateListItem,
+    MithrilError, MithrilResult, MithrilStakeDistribution, MithrilStakeDistributionListItem,
+    Snapshot, SnapshotListItem,
+};
@@ -0,0 +1,506 @@
+//! Self-contained proofs that a certificate chain is valid, verifiable without an aggregator.
+//!
+//! A [ProofBundle] holds a certificate, all its ancestors down to the genesis certificate and
+//! the genesis verification key the chain was verified with. It can be serialized as JSON or
+//! CBOR to be archived (ie: for audits) or moved to an air-gapped machine, where it's checked
+//! with [verify_bundle].
+//!
+//! Large bundles can also be written with [ProofBundle::write_stream] in a streaming format (a
+//! header followed by one certificate per line) that [verify_bundle_stream] verifies from any
+//! [AsyncRead] as the certificates are decoded, keeping at most two certificates in memory.
+//!
+//! **Warning:** the genesis verification key embedded in a bundle is informative only, a bundle
+//! must always be verified against a genesis verification key obtained from a trusted source.
+//!
+//! # Export a proof bundle
+//!
+//! ```no_run
+//! # async fn run() -> mithril_client::MithrilResult<()> {
+//! use mithril_client::ClientBuilder;
+//!
+//! let client = ClientBuilder::aggregator("YOUR_AGGREGATOR_ENDPOINT", "YOUR_GENESIS_VERIFICATION_KEY").build()?;
+//! let bundle = client.certificate().export_proof_bundle("CERTIFICATE_HASH").await?;
+//!
+//! std::fs::write("proof_bundle.json", bundle.to_json()?)?;
+//! #    Ok(())
+//! # }
+//! ```
+//!
+//! # Verify it offline
+//!
+//! ```no_run
+//! # async fn run() -> mithril_client::MithrilResult<()> {
+//! use mithril_client::proof_bundle::{verify_bundle, ProofBundle};
+//!
+//! let bundle = ProofBundle::from_json(&std::fs::read_to_string("proof_bundle.json")?)?;
+//! let certificate = verify_bundle(&bundle, "YOUR_GENESIS_VERIFICATION_KEY").await?;
+//!
+//! println!("Chain of Certificate (hash: {}) is valid", certificate.hash);
+//! #    Ok(())
+//! # }
+//! ```
+//!
+//! # Verify a streamed bundle from the standard input
+//!
+//! ```no_run
+//! # async fn run() -> mithril_client::MithrilResult<()> {
+//! use mithril_client::proof_bundle::verify_bundle_stream;
+//!
+//! let certificate = verify_bundle_stream(tokio::io::stdin(), "YOUR_GENESIS_VERIFICATION_KEY").await?;
+//!
+//! println!("Chain of Certificate (hash: {}) is valid", certificate.hash);
+//! #    Ok(())
+//! # }
+//! ```
+
+use anyhow::{anyhow, Context};
+use async_trait::async_trait;
+use serde::{Deserialize, Deserializer, Serialize};
+use slog::{o, Logger};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+
+use crate::common::certificate_chain::{
+    CertificateRetriever, CertificateRetrieverError, CertificateVerifier,
+    MithrilCertificateVerifier,
+};
+use crate::common::crypto_helper::ProtocolGenesisVerificationKey;
+use crate::common::entities::Certificate;
+use crate::{MithrilCertificate, MithrilResult};
+
+/// Current version of the serialization schema of [ProofBundle].
+pub const PROOF_BUNDLE_SCHEMA_VERSION: u32 = 1;
+
+/// A certificate with its whole chain of ancestors, see [proof bundle][crate::proof_bundle].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProofBundle {
+    /// Version of the serialization schema of the bundle
+    #[serde(deserialize_with = "deserialize_schema_version")]
+    pub schema_version: u32,
+
+    /// Genesis verification key the chain was verified with when the bundle was exported
+    pub genesis_verification_key: String,
+
+    /// Certificates of the chain, from the proven certificate (included) to the genesis
+    /// certificate
+    pub certificate_chain: Vec<MithrilCertificate>,
+}
+
+fn deserialize_schema_version<'de, D>(deserializer: D) -> Result<u32, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let schema_version = u32::deserialize(deserializer)?;
+    if schema_version > PROOF_BUNDLE_SCHEMA_VERSION {
+        return Err(serde::de::Error::custom(format!(
+            "unsupported proof bundle schema version: {schema_version}, \
+            latest supported: {PROOF_BUNDLE_SCHEMA_VERSION}"
+        )));
+    }
+
+    Ok(schema_version)
+}
+
+impl ProofBundle {
+    /// Constructs a new `ProofBundle`, the chain must start with the proven certificate.
+    pub fn new(genesis_verification_key: &str, certificate_chain: Vec<MithrilCertificate>) -> Self {
+        Self {
+            schema_version: PROOF_BUNDLE_SCHEMA_VERSION,
+            genesis_verification_key: genesis_verification_key.to_string(),
+            certificate_chain,
+        }
+    }
+
+    /// The proven certificate, `None` if the chain is empty.
+    pub fn certificate(&self) -> Option<&MithrilCertificate> {
+        self.certificate_chain.first()
+    }
+
+    /// Serialize the bundle as JSON.
+    pub fn to_json(&self) -> MithrilResult<String> {
+        serde_json::to_string(self).with_context(|| "Could not serialize proof bundle to JSON")
+    }
+
+    /// Deserialize a bundle from JSON.
+    pub fn from_json(json: &str) -> MithrilResult<Self> {
+        serde_json::from_str(json).with_context(|| "Could not deserialize proof bundle from JSON")
+    }
+
+    /// Serialize the bundle as CBOR.
+    pub fn to_cbor(&self) -> MithrilResult<Vec<u8>> {
+        serde_cbor::to_vec(self).with_context(|| "Could not serialize proof bundle to CBOR")
+    }
+
+    /// Deserialize a bundle from CBOR.
+    pub fn from_cbor(bytes: &[u8]) -> MithrilResult<Self> {
+        serde_cbor::from_slice(bytes)
+            .with_context(|| "Could not deserialize proof bundle from CBOR")
+    }
+
+    /// Write the bundle in the streaming format verified by [verify_bundle_stream]: a JSON
+    /// [ProofBundleHeader] line followed by one JSON certificate per line, in chain order.
+    pub async fn write_stream<W: AsyncWrite + Unpin>(&self, writer: &mut W) -> MithrilResult<()> {
+        let header = ProofBundleHeader {
+            schema_version: self.schema_version,
+            genesis_verification_key: self.genesis_verification_key.clone(),
+        };
+        write_json_line(writer, &header).await?;
+        for certificate in &self.certificate_chain {
+            write_json_line(writer, certificate).await?;
+        }
+        writer
+            .flush()
+            .await
+            .with_context(|| "Could not write proof bundle stream")
+    }
+}
+
+/// First line of a streamed [ProofBundle], see [ProofBundle::write_stream].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofBundleHeader {
+    /// Version of the serialization schema of the bundle
+    #[serde(deserialize_with = "deserialize_schema_version")]
+    pub schema_version: u32,
+
+    /// Genesis verification key the chain was verified with when the bundle was exported
+    pub genesis_verification_key: String,
+}
+
+async fn write_json_line<W: AsyncWrite + Unpin, T: Serialize>(
+    writer: &mut W,
+    value: &T,
+) -> MithrilResult<()> {
+    let mut line =
+        serde_json::to_vec(value).with_context(|| "Could not serialize proof bundle line")?;
+    line.push(b'\n');
+
+    writer
+        .write_all(&line)
+        .await
+        .with_context(|| "Could not write proof bundle stream")
+}
+
+/// Verify the certificate chain of the given bundle without an aggregator, using the given trusted
+/// genesis verification key, returns the proven certificate if the chain is valid.
+///
+/// Fails if the genesis verification key embedded in the bundle is not the trusted one.
+pub async fn verify_bundle(
+    bundle: &ProofBundle,
+    genesis_verification_key: &str,
+) -> MithrilResult<MithrilCertificate> {
+    if bundle.genesis_verification_key != genesis_verification_key {
+        return Err(anyhow!(
+            "Proof bundle was exported with another genesis verification key than the trusted one"
+        ));
+    }
+    let certificate = bundle
+        .certificate()
+        .ok_or(anyhow!("Proof bundle does not contain any certificate"))?;
+    let genesis_verification_key =
+        ProtocolGenesisVerificationKey::try_from(genesis_verification_key)
+            .with_context(|| "Invalid genesis verification key")?;
+
+    let retriever = Arc::new(BundleCertificateRetriever::new(bundle));
+    let verifier =
+        MithrilCertificateVerifier::new(Logger::root(slog::Discard, o!()), retriever.clone());
+    let first_certificate = retriever.get_certificate_details(&certificate.hash).await?;
+    verifier
+        .verify_certificate_chain(first_certificate, &genesis_verification_key)
+        .await
+        .with_context(|| {
+            format!(
+                "Certificate chain of proof bundle certificate '{}' is invalid",
+                certificate.hash
+            )
+        })?;
+
+    Ok(certificate.clone())
+}
+
+/// Verify a bundle written with [ProofBundle::write_stream] as it's read from the given `reader`,
+/// using the given trusted genesis verification key, returns the proven certificate if the chain
+/// is valid.
+///
+/// Each certificate is verified as soon as its previous certificate is decoded, so an invalid
+/// chain is rejected without reading the rest of the stream.
+pub async fn verify_bundle_stream<R: AsyncRead + Unpin>(
+    reader: R,
+    genesis_verification_key: &str,
+) -> MithrilResult<MithrilCertificate> {
+    let mut lines = BufReader::new(reader).lines();
+    let header_line = next_stream_line(&mut lines)
+        .await?
+        .ok_or(anyhow!("Proof bundle stream is empty"))?;
+    let header: ProofBundleHeader = serde_json::from_str(&header_line)
+        .with_context(|| "Could not deserialize proof bundle stream header")?;
+    if header.genesis_verification_key != genesis_verification_key {
+        return Err(anyhow!(
+            "Proof bundle was exported with another genesis verification key than the trusted one"
+        ));
+    }
+    let genesis_verification_key =
+        ProtocolGenesisVerificationKey::try_from(genesis_verification_key)
+            .with_context(|| "Invalid genesis verification key")?;
+    let logger = Logger::root(slog::Discard, o!());
+
+    let mut proven_certificate: Option<MithrilCertificate> = None;
+    let mut pending_certificate: Option<Certificate> = None;
+    while let Some(line) = next_stream_line(&mut lines).await? {
+        let message: MithrilCertificate = serde_json::from_str(&line)
+            .with_context(|| "Could not deserialize proof bundle stream certificate")?;
+        let certificate: Certificate = message.clone().try_into()?;
+        if proven_certificate.is_none() {
+            proven_certificate = Some(message);
+        }
+
+        if let Some(pending) = pending_certificate.take() {
+            let verifier = MithrilCertificateVerifier::new(
+                logger.clone(),
+                Arc::new(StreamedCertificateRetriever(Some(certificate.clone()))),
+            );
+            let previous = verifier
+                .verify_certificate(&pending, &genesis_verification_key)
+                .await
+                .with_context(|| format!("Certificate '{}' is invalid", pending.hash))?;
+            if previous.is_none() {
+                return Err(anyhow!(
+                    "Proof bundle stream continues after the end of the chain of certificate '{}'",
+                    pending.hash
+                ));
+            }
+        }
+        pending_certificate = Some(certificate);
+    }
+
+    let last_certificate =
+        pending_certificate.ok_or(anyhow!("Proof bundle does not contain any certificate"))?;
+    let verifier =
+        MithrilCertificateVerifier::new(logger, Arc::new(StreamedCertificateRetriever(None)));
+    verifier
+        .verify_certificate(&last_certificate, &genesis_verification_key)
+        .await
+        .with_context(|| format!("Certificate '{}' is invalid", last_certificate.hash))?;
+
+    Ok(proven_certificate.expect("a proven certificate was decoded with the last certificate"))
+}
+
+async fn next_stream_line<R: AsyncRead + Unpin>(
+    lines: &mut tokio::io::Lines<BufReader<R>>,
+) -> MithrilResult<Option<String>> {
+    loop {
+        match lines
+            .next_line()
+            .await
+            .with_context(|| "Could not read proof bundle stream")?
+        {
+            Some(line) if line.trim().is_empty() => continue,
+            line => return Ok(line),
+        }
+    }
+}
+
+/// A [CertificateRetriever] that serves only the certificate that follows the one being verified
+/// in a proof bundle stream.
+struct StreamedCertificateRetriever(Option<Certificate>);
+
+#[cfg_attr(target_family = "wasm", async_trait(?Send))]
+#[cfg_attr(not(target_family = "wasm"), async_trait)]
+impl CertificateRetriever for StreamedCertificateRetriever {
+    async fn get_certificate_details(
+        &self,
+        certificate_hash: &str,
+    ) -> Result<Certificate, CertificateRetrieverError> {
+        match &self.0 {
+            Some(certificate) if certificate.hash == certificate_hash => Ok(certificate.clone()),
+            Some(certificate) => Err(CertificateRetrieverError(anyhow!(
+                "Certificate '{certificate_hash}' is expected next in the proof bundle stream, found '{}'",
+                certificate.hash
+            ))),
+            None => Err(CertificateRetrieverError(anyhow!(
+                "Certificate '{certificate_hash}' is missing from the proof bundle"
+            ))),
+        }
+    }
+}
+
+/// A [CertificateRetriever] that serves the certificates of a [ProofBundle].
+struct BundleCertificateRetriever {
+    certificates: HashMap<String, MithrilCertificate>,
+}
+
+impl BundleCertificateRetriever {
+    fn new(bundle: &ProofBundle) -> Self {
+        Self {
+            certificates: bundle
+                .certificate_chain
+                .iter()
+                .map(|certificate| (certificate.hash.clone(), certificate.clone()))
+                .collect(),
+        }
+    }
+}
+
+#[cfg_attr(target_family = "wasm", async_trait(?Send))]
+#[cfg_attr(not(target_family = "wasm"), async_trait)]
+impl CertificateRetriever for BundleCertificateRetriever {
+    async fn get_certificate_details(
+        &self,
+        certificate_hash: &str,
+    ) -> Result<Certificate, CertificateRetrieverError> {
+        let message = self.certificates.get(certificate_hash).ok_or_else(|| {
+            CertificateRetrieverError(anyhow!(
+                "Certificate '{certificate_hash}' is missing from the proof bundle"
+            ))
+        })?;
+
+        message
+            .clone()
+            .try_into()
+            .map_err(CertificateRetrieverError)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mithril_common::crypto_helper::tests_setup::setup_certificate_chain;
+    use mithril_common::messages::CertificateMessage as CommonCertificateMessage;
+
+    use crate::test_tools::fake_keys;
+
+    use super::*;
+
+    fn valid_bundle(total_certificates: u64) -> ProofBundle {
+        let (certificates, genesis_verifier) = setup_certificate_chain(total_certificates, 1);
+        let certificate_chain = certificates
+            .into_iter()
+            .map(|certificate| {
+                let message = CommonCertificateMessage::try_from(certificate).unwrap();
+                serde_json::from_str(&serde_json::to_string(&message).unwrap()).unwrap()
+            })
+            .collect();
+
+        ProofBundle::new(
+            &genesis_verifier
+                .to_verification_key()
+                .to_json_hex()
+                .unwrap(),
+            certificate_chain,
+        )
+    }
+
+    #[tokio::test]
+    async fn verify_a_bundle_serialized_as_json_or_cbor() {
+        let bundle = valid_bundle(4);
+        let genesis_verification_key = bundle.genesis_verification_key.clone();
+
+        for deserialized_bundle in [
+            ProofBundle::from_json(&bundle.to_json().unwrap()).unwrap(),
+            ProofBundle::from_cbor(&bundle.to_cbor().unwrap()).unwrap(),
+        ] {
+            assert_eq!(bundle, deserialized_bundle);
+            let certificate = verify_bundle(&deserialized_bundle, &genesis_verification_key)
+                .await
+                .unwrap();
+            assert_eq!(bundle.certificate_chain[0], certificate);
+        }
+    }
+
+    #[tokio::test]
+    async fn verify_a_bundle_with_another_genesis_verification_key_fails() {
+        let mut bundle = valid_bundle(2);
+        let other_genesis_verification_key = fake_keys::genesis_verification_key()
+            .into_iter()
+            .find(|key| *key != bundle.genesis_verification_key)
+            .unwrap();
+
+        verify_bundle(&bundle, other_genesis_verification_key)
+            .await
+            .expect_err("verification with an untrusted genesis key should fail");
+
+        bundle.genesis_verification_key = other_genesis_verification_key.to_string();
+        verify_bundle(&bundle, other_genesis_verification_key)
+            .await
+            .expect_err("verification of a chain signed by another genesis key should fail");
+    }
+
+    #[tokio::test]
+    async fn verify_a_bundle_with_an_incomplete_chain_fails() {
+        let mut bundle = valid_bundle(3);
+        let genesis_verification_key = bundle.genesis_verification_key.clone();
+        bundle.certificate_chain.pop();
+
+        let error = verify_bundle(&bundle, &genesis_verification_key)
+            .await
+            .expect_err("verification of an incomplete chain should fail");
+
+        assert!(
+            format!("{error:?}").contains("missing from the proof bundle"),
+            "Unexpected error: {error:?}"
+        );
+    }
+
+    async fn stream_of(bundle: &ProofBundle) -> Vec<u8> {
+        let mut stream = vec![];
+        bundle.write_stream(&mut stream).await.unwrap();
+
+        stream
+    }
+
+    #[tokio::test]
+    async fn verify_a_streamed_bundle() {
+        let bundle = valid_bundle(5);
+        let stream = stream_of(&bundle).await;
+
+        let certificate = verify_bundle_stream(stream.as_slice(), &bundle.genesis_verification_key)
+            .await
+            .unwrap();
+
+        assert_eq!(bundle.certificate_chain[0], certificate);
+        assert_eq!(
+            bundle.certificate_chain.len() + 1,
+            String::from_utf8(stream).unwrap().lines().count()
+        );
+    }
+
+    #[tokio::test]
+    async fn verify_a_streamed_bundle_with_an_invalid_chain_fails() {
+        let bundle = valid_bundle(4);
+        let genesis_verification_key = bundle.genesis_verification_key.clone();
+
+        let mut incomplete_bundle = bundle.clone();
+        incomplete_bundle.certificate_chain.pop();
+        let error = verify_bundle_stream(
+            stream_of(&incomplete_bundle).await.as_slice(),
+            &genesis_verification_key,
+        )
+        .await
+        .expect_err("verification of an incomplete chain should fail");
+        assert!(
+            format!("{error:?}").contains("missing from the proof bundle"),
+            "Unexpected error: {error:?}"
+        );
+
+        let mut unordered_bundle = bundle.clone();
+        unordered_bundle.certificate_chain.swap(1, 2);
+        let error = verify_bundle_stream(
+            stream_of(&unordered_bundle).await.as_slice(),
+            &genesis_verification_key,
+        )
+        .await
+        .expect_err("verification of an unordered chain should fail");
+        assert!(
+            format!("{error:?}").contains("expected next in the proof bundle stream"),
+            "Unexpected error: {error:?}"
+        );
+    }
+
+    #[test]
+    fn deserialize_bundle_with_unsupported_schema_version_fails() {
+        let mut bundle = ProofBundle::new("genesis_verification_key", vec![]);
+        bundle.schema_version = PROOF_BUNDLE_SCHEMA_VERSION + 1;
+
+        ProofBundle::from_json(&bundle.to_json().unwrap())
+            .expect_err("unsupported schema version should fail");
+    }
+}
@@ -0,0 +1,362 @@
+//! Match the artifacts retrieved from an Aggregator with the protocol message signed by their
+//! certificate.
+//!
+//! A [ProtocolMessageMatcher] describes what a certificate must have signed for an artifact, per
+//! signed entity type: the digest of a snapshot, the next aggregate verification key of a
+//! Mithril stake distribution or a whole protocol message computed locally.
+//!
+//! Parts of a protocol message are only trusted if the message is the one signed by the
+//! certificate, a mismatch is reported with a [ProtocolMessageMismatch].
+//!
+//! Parts computed out-of-band, ie: a digest computed by another tool, can be checked against a
+//! certificate with [verify_matches][MithrilCertificate::verify_matches]: the mismatching part is
+//! reported.
+//!
+//! ```no_run
+//! # async fn run() -> mithril_client::MithrilResult<()> {
+//! use mithril_client::protocol_message_matcher::ProtocolMessagePartKey;
+//! use mithril_client::ClientBuilder;
+//!
+//! let client = ClientBuilder::aggregator("YOUR_AGGREGATOR_ENDPOINT", "YOUR_GENESIS_VERIFICATION_KEY").build()?;
+//! let certificate = client.certificate().verify_chain("CERTIFICATE_HASH").await?;
+//!
+//! certificate.verify_matches(&[
+//!     (ProtocolMessagePartKey::SnapshotDigest, "SNAPSHOT_DIGEST".to_string()),
+//!     (ProtocolMessagePartKey::NextAggregateVerificationKey, "NEXT_AVK".to_string()),
+//! ])?;
+//! #    Ok(())
+//! # }
+//! ```
+//!
+//! ```no_run
+//! # async fn run() -> mithril_client::MithrilResult<()> {
+//! use mithril_client::protocol_message_matcher::ProtocolMessageMatcher;
+//! use mithril_client::ClientBuilder;
+//!
+//! let client = ClientBuilder::aggregator("YOUR_AGGREGATOR_ENDPOINT", "YOUR_GENESIS_VERIFICATION_KEY").build()?;
+//! let snapshot = client.snapshot().get("SNAPSHOT_DIGEST").await?.unwrap();
+//!
+//! let certificate = client
+//!     .certificate()
+//!     .verify_chain_matching(
+//!         &snapshot.certificate_hash,
+//!         &ProtocolMessageMatcher::snapshot(&snapshot),
+//!     )
+//!     .await?;
+//! #    Ok(())
+//! # }
+//! ```
+
+use anyhow::Context;
+use thiserror::Error;
+
+pub use crate::common::entities::ProtocolMessagePartKey;
+
+use crate::common::entities::ProtocolMessage;
+use crate::{
+    MessageBuilder, MithrilCertificate, MithrilResult, MithrilStakeDistribution, Snapshot,
+};
+
+/// What a certificate must have signed for an artifact.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProtocolMessageMatcher {
+    /// The certificate signs the snapshot with the given digest
+    SnapshotDigest(String),
+
+    /// The certificate signs the Mithril stake distribution which signers have the given next
+    /// aggregate verification key
+    NextAggregateVerificationKey(String),
+
+    /// The certificate signs exactly the given protocol message
+    ProtocolMessage(ProtocolMessage),
+
+    /// The certificate signs exactly the protocol message made of the given parts, if a key is
+    /// given more than once its last value is used
+    MessageParts(Vec<(ProtocolMessagePartKey, String)>),
+}
+
+impl ProtocolMessageMatcher {
+    /// Match the certificate of the given snapshot.
+    pub fn snapshot(snapshot: &Snapshot) -> Self {
+        Self::SnapshotDigest(snapshot.digest.clone())
+    }
+
+    /// Match the certificate of the given Mithril stake distribution, the aggregate verification
+    /// key of its signers is computed.
+    pub fn mithril_stake_distribution(
+        mithril_stake_distribution: &MithrilStakeDistribution,
+    ) -> MithrilResult<Self> {
+        let next_aggregate_verification_key = MessageBuilder::new()
+            .compute_next_aggregate_verification_key(mithril_stake_distribution)
+            .with_context(|| {
+                format!(
+                    "Could not compute the aggregate verification key of Mithril stake distribution '{}'",
+                    mithril_stake_distribution.hash
+                )
+            })?;
+
+        Ok(Self::NextAggregateVerificationKey(
+            next_aggregate_verification_key,
+        ))
+    }
+
+    /// Check that the given certificate signed the expected protocol message.
+    pub fn matches(&self, certificate: &MithrilCertificate) -> Result<(), ProtocolMessageMismatch> {
+        match self {
+            Self::SnapshotDigest(digest) => {
+                Self::match_part(certificate, ProtocolMessagePartKey::SnapshotDigest, digest)
+            }
+            Self::NextAggregateVerificationKey(key) => Self::match_part(
+                certificate,
+                ProtocolMessagePartKey::NextAggregateVerificationKey,
+                key,
+            ),
+            Self::ProtocolMessage(message) if certificate.match_message(message) => Ok(()),
+            Self::ProtocolMessage(_) => Err(ProtocolMessageMismatch::MessageMismatch {
+                certificate_hash: certificate.hash.clone(),
+            }),
+            Self::MessageParts(parts) => Self::match_parts(certificate, parts),
+        }
+    }
+
+    fn match_parts(
+        certificate: &MithrilCertificate,
+        parts: &[(ProtocolMessagePartKey, String)],
+    ) -> Result<(), ProtocolMessageMismatch> {
+        let mut message = ProtocolMessage::new();
+        for (key, value) in parts {
+            message.set_message_part(*key, value.clone());
+        }
+        if certificate.match_message(&message) {
+            return Ok(());
+        }
+
+        for (part, expected) in &message.message_parts {
+            Self::match_part(certificate, *part, expected)?;
+        }
+        match certificate
+            .protocol_message
+            .message_parts
+            .keys()
+            .find(|part| !message.message_parts.contains_key(part))
+        {
+            Some(part) => Err(ProtocolMessageMismatch::UnexpectedPart {
+                certificate_hash: certificate.hash.clone(),
+                part: *part,
+            }),
+            None => Err(ProtocolMessageMismatch::MessageMismatch {
+                certificate_hash: certificate.hash.clone(),
+            }),
+        }
+    }
+
+    fn match_part(
+        certificate: &MithrilCertificate,
+        part: ProtocolMessagePartKey,
+        expected: &str,
+    ) -> Result<(), ProtocolMessageMismatch> {
+        if !certificate.match_message(&certificate.protocol_message) {
+            return Err(ProtocolMessageMismatch::UnsignedProtocolMessage {
+                certificate_hash: certificate.hash.clone(),
+            });
+        }
+
+        match certificate.protocol_message.get_message_part(&part) {
+            Some(actual) if actual == expected => Ok(()),
+            actual => Err(ProtocolMessageMismatch::PartMismatch {
+                certificate_hash: certificate.hash.clone(),
+                part,
+                expected: expected.to_string(),
+                actual: actual.cloned(),
+            }),
+        }
+    }
+}
+
+impl MithrilCertificate {
+    /// Check that the certificate signed the protocol message made of the given parts,
+    /// reporting which part mismatched if it didn't.
+    ///
+    /// The certificate itself is not verified, its chain should be verified first with
+    /// [CertificateClient::verify_chain][crate::certificate_client::CertificateClient::verify_chain].
+    pub fn verify_matches(
+        &self,
+        parts: &[(ProtocolMessagePartKey, String)],
+    ) -> Result<(), ProtocolMessageMismatch> {
+        ProtocolMessageMatcher::MessageParts(parts.to_vec()).matches(self)
+    }
+}
+
+/// Error raised when a certificate did not sign the protocol message expected by a
+/// [ProtocolMessageMatcher]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ProtocolMessageMismatch {
+    /// The protocol message of the certificate is not the one it signed
+    #[error("The protocol message of certificate '{certificate_hash}' is not the one it signed")]
+    UnsignedProtocolMessage {
+        /// hash of the certificate
+        certificate_hash: String,
+    },
+
+    /// A part of the signed protocol message is missing or differs from the expected one
+    #[error("Certificate '{certificate_hash}' signed {part} {actual:?}, expected '{expected}'")]
+    PartMismatch {
+        /// hash of the certificate
+        certificate_hash: String,
+
+        /// the mismatching part
+        part: ProtocolMessagePartKey,
+
+        /// the expected value of the part
+        expected: String,
+
+        /// the value of the part signed by the certificate, if any
+        actual: Option<String>,
+    },
+
+    /// The signed protocol message has a part that was not expected
+    #[error("Certificate '{certificate_hash}' signed an unexpected {part}")]
+    UnexpectedPart {
+        /// hash of the certificate
+        certificate_hash: String,
+
+        /// the unexpected part
+        part: ProtocolMessagePartKey,
+    },
+
+    /// The certificate did not sign the expected protocol message
+    #[error("Certificate '{certificate_hash}' did not sign the expected protocol message")]
+    MessageMismatch {
+        /// hash of the certificate
+        certificate_hash: String,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn certificate_signing(protocol_message: ProtocolMessage) -> MithrilCertificate {
+        MithrilCertificate {
+            signed_message: protocol_message.compute_hash(),
+            protocol_message,
+            ..MithrilCertificate::dummy()
+        }
+    }
+
+    fn snapshot_message(digest: &str) -> ProtocolMessage {
+        let mut message = ProtocolMessage::new();
+        message.set_message_part(ProtocolMessagePartKey::SnapshotDigest, digest.to_string());
+        message
+    }
+
+    #[test]
+    fn match_the_digest_of_a_snapshot() {
+        let certificate = certificate_signing(snapshot_message("digest"));
+
+        ProtocolMessageMatcher::SnapshotDigest("digest".to_string())
+            .matches(&certificate)
+            .unwrap();
+        assert_eq!(
+            Err(ProtocolMessageMismatch::PartMismatch {
+                certificate_hash: certificate.hash.clone(),
+                part: ProtocolMessagePartKey::SnapshotDigest,
+                expected: "other".to_string(),
+                actual: Some("digest".to_string()),
+            }),
+            ProtocolMessageMatcher::SnapshotDigest("other".to_string()).matches(&certificate)
+        );
+        assert_eq!(
+            Err(ProtocolMessageMismatch::PartMismatch {
+                certificate_hash: certificate.hash.clone(),
+                part: ProtocolMessagePartKey::NextAggregateVerificationKey,
+                expected: "avk".to_string(),
+                actual: None,
+            }),
+            ProtocolMessageMatcher::NextAggregateVerificationKey("avk".to_string())
+                .matches(&certificate)
+        );
+    }
+
+    #[test]
+    fn parts_of_an_unsigned_protocol_message_are_not_trusted() {
+        let mut certificate = certificate_signing(snapshot_message("digest"));
+        certificate.signed_message = "tampered".to_string();
+
+        assert_eq!(
+            Err(ProtocolMessageMismatch::UnsignedProtocolMessage {
+                certificate_hash: certificate.hash.clone(),
+            }),
+            ProtocolMessageMatcher::SnapshotDigest("digest".to_string()).matches(&certificate)
+        );
+    }
+
+    #[test]
+    fn match_a_whole_protocol_message() {
+        let certificate = certificate_signing(snapshot_message("digest"));
+
+        ProtocolMessageMatcher::ProtocolMessage(snapshot_message("digest"))
+            .matches(&certificate)
+            .unwrap();
+        ProtocolMessageMatcher::ProtocolMessage(snapshot_message("other"))
+            .matches(&certificate)
+            .expect_err("a different protocol message should not match");
+    }
+
+    #[test]
+    fn match_the_aggregate_verification_key_of_a_mithril_stake_distribution() {
+        let mithril_stake_distribution = MithrilStakeDistribution::dummy();
+        let message = MessageBuilder::new()
+            .compute_mithril_stake_distribution_message(&mithril_stake_distribution)
+            .unwrap();
+        let certificate = certificate_signing(message);
+
+        ProtocolMessageMatcher::mithril_stake_distribution(&mithril_stake_distribution)
+            .unwrap()
+            .matches(&certificate)
+            .unwrap();
+    }
+
+    #[test]
+    fn verify_a_certificate_against_protocol_message_parts() {
+        let mut message = snapshot_message("digest");
+        message.set_message_part(
+            ProtocolMessagePartKey::NextAggregateVerificationKey,
+            "avk".to_string(),
+        );
+        let certificate = certificate_signing(message);
+
+        certificate
+            .verify_matches(&[
+                (
+                    ProtocolMessagePartKey::NextAggregateVerificationKey,
+                    "avk".to_string(),
+                ),
+                (ProtocolMessagePartKey::SnapshotDigest, "digest".to_string()),
+            ])
+            .unwrap();
+        assert_eq!(
+            Err(ProtocolMessageMismatch::PartMismatch {
+                certificate_hash: certificate.hash.clone(),
+                part: ProtocolMessagePartKey::NextAggregateVerificationKey,
+                expected: "other-avk".to_string(),
+                actual: Some("avk".to_string()),
+            }),
+            certificate.verify_matches(&[
+                (ProtocolMessagePartKey::SnapshotDigest, "digest".to_string()),
+                (
+                    ProtocolMessagePartKey::NextAggregateVerificationKey,
+                    "other-avk".to_string()
+                ),
+            ])
+        );
+        assert_eq!(
+            Err(ProtocolMessageMismatch::UnexpectedPart {
+                certificate_hash: certificate.hash.clone(),
+                part: ProtocolMessagePartKey::NextAggregateVerificationKey,
+            }),
+            certificate
+                .verify_matches(&[(ProtocolMessagePartKey::SnapshotDigest, "digest".to_string())])
+        );
+    }
+}
@@ -0,0 +1,149 @@
+//! Egress of the client through an HTTP(S) or SOCKS5 proxy.
+//!
+//! A [ProxyConfig] given to the client using
+//! [ClientBuilder::with_proxy][crate::ClientBuilder::with_proxy] is used by both the requests
+//! to the aggregator and the snapshot downloads, except for the hosts of its no-proxy list.
+//!
+//! Supported proxy schemes are `http`, `https`, `socks5` and `socks5h` (the proxy resolves the
+//! host names).
+//!
+//! **Note:** proxies are not available on wasm targets, the browser settings apply there.
+//!
+//! # Use an authenticated corporate proxy
+//!
+//! ```no_run
+//! # async fn run() -> mithril_client::MithrilResult<()> {
+//! use mithril_client::{proxy::ProxyConfig, ClientBuilder};
+//!
+//! let client = ClientBuilder::aggregator("YOUR_AGGREGATOR_ENDPOINT", "YOUR_GENESIS_VERIFICATION_KEY")
+//!     .with_proxy(
+//!         ProxyConfig::new("http://proxy.corp.example:3128")
+//!             .with_basic_auth("user", "password")
+//!             .with_no_proxy(["localhost", ".corp.example"]),
+//!     )
+//!     .build()?;
+//!
+//! let snapshots = client.snapshot().list().await?;
+//! #    Ok(())
+//! # }
+//! ```
+
+use anyhow::{anyhow, Context};
+use reqwest::{NoProxy, Proxy, Url};
+
+use crate::MithrilResult;
+
+const SUPPORTED_SCHEMES: [&str; 4] = ["http", "https", "socks5", "socks5h"];
+
+/// Proxy through which the client reaches the network.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ProxyConfig {
+    url: String,
+    credentials: Option<(String, String)>,
+    no_proxy: Vec<String>,
+}
+
+impl ProxyConfig {
+    /// Constructs a new `ProxyConfig` for the proxy at the given url.
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            credentials: None,
+            no_proxy: vec![],
+        }
+    }
+
+    /// Authenticate to the proxy with the given credentials.
+    pub fn with_basic_auth(mut self, username: &str, password: &str) -> Self {
+        self.credentials = Some((username.to_string(), password.to_string()));
+        self
+    }
+
+    /// Reach the given hosts, domains (ie: `.example.com`), IP addresses or networks (ie:
+    /// `10.0.0.0/8`) directly instead of through the proxy.
+    pub fn with_no_proxy<I, S>(mut self, hosts: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.no_proxy.extend(hosts.into_iter().map(Into::into));
+        self
+    }
+
+    /// Url of the proxy.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Build the proxy to give to a [reqwest::ClientBuilder].
+    pub(crate) fn to_reqwest_proxy(&self) -> MithrilResult<Proxy> {
+        let url =
+            Url::parse(&self.url).with_context(|| format!("Invalid proxy url: '{}'", self.url))?;
+        if !SUPPORTED_SCHEMES.contains(&url.scheme()) {
+            return Err(anyhow!(
+                "Unsupported proxy scheme '{}', expected one of: {}",
+                url.scheme(),
+                SUPPORTED_SCHEMES.join(", ")
+            ));
+        }
+
+        let proxy = Proxy::all(url).with_context(|| format!("Invalid proxy: '{}'", self.url))?;
+        let proxy = match &self.credentials {
+            Some((username, password)) => proxy.basic_auth(username, password),
+            None => proxy,
+        };
+
+        Ok(proxy.no_proxy(NoProxy::from_string(&self.no_proxy.join(","))))
+    }
+}
+
+impl std::fmt::Debug for ProxyConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProxyConfig")
+            .field("url", &self.url)
+            .field(
+                "credentials",
+                &self.credentials.as_ref().map(|(username, _)| username),
+            )
+            .field("no_proxy", &self.no_proxy)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_proxies_of_supported_schemes() {
+        for url in [
+            "http://proxy:3128",
+            "https://proxy:3129",
+            "socks5://127.0.0.1:1080",
+            "socks5h://127.0.0.1:1080",
+        ] {
+            ProxyConfig::new(url)
+                .with_basic_auth("user", "password")
+                .with_no_proxy(["localhost"])
+                .to_reqwest_proxy()
+                .unwrap_or_else(|e| panic!("building proxy '{url}' should not fail: {e:?}"));
+        }
+    }
+
+    #[test]
+    fn build_proxy_with_unsupported_scheme_fails() {
+        ProxyConfig::new("ftp://proxy:21")
+            .to_reqwest_proxy()
+            .expect_err("an ftp proxy should be rejected");
+        ProxyConfig::new("not an url")
+            .to_reqwest_proxy()
+            .expect_err("an invalid url should be rejected");
+    }
+
+    #[test]
+    fn debug_output_does_not_leak_the_password() {
+        let proxy = ProxyConfig::new("http://proxy:3128").with_basic_auth("user", "s3cr3t");
+
+        assert!(!format!("{proxy:?}").contains("s3cr3t"));
+    }
+}
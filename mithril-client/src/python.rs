@@ -0,0 +1,200 @@
+//! Python bindings of the client, built with [pyo3](https://pyo3.rs).
+//!
+//! The bindings expose a `Client` class, backed by the [blocking client][crate::blocking], which
+//! returns the fetched artifacts as Python dictionaries with the same fields as their JSON
+//! representation.
+//!
+//! **Note:** _Available using crate feature_ **python**. The Python module can be built with
+//! [maturin](https://www.maturin.rs), enabling the `pyo3/extension-module` feature:
+//! `maturin build --features python,pyo3/extension-module`.
+//!
+//! ```python
+//! import mithril_client
+//!
+//! client = mithril_client.Client("YOUR_AGGREGATOR_ENDPOINT", "YOUR_GENESIS_VERIFICATION_KEY")
+//! snapshot = client.list_snapshots()[0]
+//! certificate = client.download_snapshot(snapshot["digest"], "/tmp/db")
+//!
+//! print(f"Snapshot {snapshot['digest']} is certified by {certificate['hash']}")
+//! ```
+
+use anyhow::{anyhow, Context};
+use pyo3::exceptions::PyRuntimeError;
+use pyo3::prelude::*;
+use serde::Serialize;
+use std::path::Path;
+
+use crate::snapshot_client::SnapshotClientError;
+use crate::{blocking, ClientBuilder, MithrilError, MithrilResult};
+
+fn to_py_err(error: MithrilError) -> PyErr {
+    PyRuntimeError::new_err(format!("{error:?}"))
+}
+
+/// Convert the given value to its Python representation, through its JSON serialization.
+fn to_python<T: Serialize>(py: Python<'_>, value: &T) -> PyResult<PyObject> {
+    let json = serde_json::to_string(value).map_err(|e| to_py_err(e.into()))?;
+    let object = py.import("json")?.call_method1("loads", (json,))?;
+
+    Ok(object.into())
+}
+
+fn result_to_python<T: Serialize>(py: Python<'_>, result: MithrilResult<T>) -> PyResult<PyObject> {
+    to_python(py, &result.map_err(to_py_err)?)
+}
+
+/// Python client that fetches and verifies the artifacts certified by a Mithril aggregator.
+#[pyclass(name = "Client", module = "mithril_client")]
+pub struct PyClient {
+    client: blocking::Client,
+}
+
+#[pymethods]
+impl PyClient {
+    /// Constructs a new client that fetches data from the aggregator at the given endpoint and
+    /// verifies them with the given genesis verification key.
+    #[new]
+    fn new(aggregator_endpoint: &str, genesis_verification_key: &str) -> PyResult<Self> {
+        let client = ClientBuilder::aggregator(aggregator_endpoint, genesis_verification_key)
+            .build()
+            .and_then(blocking::Client::new)
+            .map_err(to_py_err)?;
+
+        Ok(Self { client })
+    }
+
+    /// List the latest certificates.
+    fn list_certificates(&self, py: Python<'_>) -> PyResult<PyObject> {
+        result_to_python(py, self.client.certificate().list())
+    }
+
+    /// Get the certificate with the given hash, `None` if it doesn't exist.
+    fn get_certificate(&self, py: Python<'_>, certificate_hash: &str) -> PyResult<PyObject> {
+        result_to_python(py, self.client.certificate().get(certificate_hash))
+    }
+
+    /// Verify the chain of the certificate with the given hash, returns the certificate.
+    fn verify_certificate_chain(
+        &self,
+        py: Python<'_>,
+        certificate_hash: &str,
+    ) -> PyResult<PyObject> {
+        result_to_python(py, self.client.certificate().verify_chain(certificate_hash))
+    }
+
+    /// List the latest Mithril stake distributions.
+    fn list_mithril_stake_distributions(&self, py: Python<'_>) -> PyResult<PyObject> {
+        result_to_python(py, self.client.mithril_stake_distribution().list())
+    }
+
+    /// Get the Mithril stake distribution with the given hash, `None` if it doesn't exist.
+    fn get_mithril_stake_distribution(&self, py: Python<'_>, hash: &str) -> PyResult<PyObject> {
+        result_to_python(py, self.client.mithril_stake_distribution().get(hash))
+    }
+
+    /// List the latest snapshots.
+    fn list_snapshots(&self, py: Python<'_>) -> PyResult<PyObject> {
+        result_to_python(py, self.client.snapshot().list())
+    }
+
+    /// Get the snapshot with the given digest, `None` if it doesn't exist.
+    fn get_snapshot(&self, py: Python<'_>, digest: &str) -> PyResult<PyObject> {
+        result_to_python(py, self.client.snapshot().get(digest))
+    }
+
+    /// Verify the certificate chain of the snapshot with the given digest, then download it in a
+    /// staging directory and promote it to the given directory, which must not exist or be empty,
+    /// once its digest is verified against its certificate.
+    ///
+    /// The snapshot is removed if its digest doesn't match its certificate, see
+    /// [download_unpack_staged][crate::snapshot_client::SnapshotClient::download_unpack_staged].
+    ///
+    /// Returns the certificate of the snapshot.
+    fn download_snapshot(
+        &self,
+        py: Python<'_>,
+        digest: &str,
+        target_directory: &str,
+    ) -> PyResult<PyObject> {
+        let target_directory = Path::new(target_directory);
+        let result = py.allow_threads(|| {
+            let snapshot = self
+                .client
+                .snapshot()
+                .get(digest)?
+                .ok_or_else(|| anyhow!("No snapshot exist for digest '{digest}'"))?;
+            let certificate = self
+                .client
+                .certificate()
+                .verify_chain(&snapshot.certificate_hash)?;
+            if let Err(error) = self.client.snapshot().download_unpack_staged(
+                &snapshot,
+                &certificate,
+                &self.client.message_builder(),
+                target_directory,
+            ) {
+                if let Some(SnapshotClientError::Quarantined { quarantine_dir, .. }) =
+                    error.downcast_ref::<SnapshotClientError>()
+                {
+                    std::fs::remove_dir_all(quarantine_dir).with_context(|| {
+                        format!("Could not remove directory '{}'", quarantine_dir.display())
+                    })?;
+                }
+                return Err(error);
+            }
+
+            Ok(certificate)
+        });
+
+        result_to_python(py, result)
+    }
+}
+
+/// The `mithril_client` Python module.
+#[pymodule]
+fn mithril_client(_py: Python<'_>, module: &PyModule) -> PyResult<()> {
+    module.add_class::<PyClient>()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pyo3::types::PyDict;
+
+    use crate::Snapshot;
+
+    use super::*;
+
+    #[test]
+    fn artifacts_are_converted_to_python_dictionaries() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let snapshot = to_python(py, &Snapshot::dummy()).unwrap();
+            let snapshot: &PyDict = snapshot.downcast(py).unwrap();
+
+            let digest: String = snapshot
+                .get_item("digest")
+                .unwrap()
+                .unwrap()
+                .extract()
+                .unwrap();
+            assert_eq!(Snapshot::dummy().digest, digest);
+
+            let none = to_python(py, &None::<Snapshot>).unwrap();
+            assert!(none.is_none(py));
+        });
+    }
+
+    #[test]
+    fn errors_are_raised_as_runtime_errors() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let error = result_to_python(py, Err::<(), _>(anyhow!("failure"))).unwrap_err();
+
+            assert!(error.is_instance_of::<PyRuntimeError>(py));
+        });
+    }
+}
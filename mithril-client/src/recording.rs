@@ -0,0 +1,555 @@
+//! Record the interactions with an aggregator and replay them later.
+//!
+//! A [RecordingAggregatorClient] writes every request made to an aggregator, with its response or
+//! its error, as a JSON file in a directory (see
+//! [ClientBuilder::with_interactions_recording][crate::ClientBuilder::with_interactions_recording]).
+//!
+//! A [ReplayingAggregatorClient] serves back the interactions recorded in such a directory, in
+//! the order they were recorded, so a failure seen by a user can be reproduced deterministically
+//! and without access to their aggregator.
+//!
+//! **Note:** _Available using crate feature_ **fs**.
+//!
+//! # Record the interactions of a user
+//!
+//! ```no_run
+//! # async fn run() -> mithril_client::MithrilResult<()> {
+//! use mithril_client::ClientBuilder;
+//!
+//! let client = ClientBuilder::aggregator("YOUR_AGGREGATOR_ENDPOINT", "YOUR_GENESIS_VERIFICATION_KEY")
+//!     .with_interactions_recording("/home/user/mithril_recording")
+//!     .build()?;
+//!
+//! let snapshots = client.snapshot().list().await?;
+//! client.certificate().verify_chain(&snapshots[0].certificate_hash).await?;
+//! #    Ok(())
+//! # }
+//! ```
+//!
+//! # Replay them
+//!
+//! ```no_run
+//! # async fn run() -> mithril_client::MithrilResult<()> {
+//! use mithril_client::{recording::ReplayingAggregatorClient, ClientBuilder};
+//! use std::sync::Arc;
+//!
+//! let aggregator_client = ReplayingAggregatorClient::new("/home/user/mithril_recording")?;
+//! let client = ClientBuilder::new("YOUR_GENESIS_VERIFICATION_KEY")
+//!     .with_aggregator_client(Arc::new(aggregator_client))
+//!     .build()?;
+//!
+//! let snapshots = client.snapshot().list().await?;
+//! client.certificate().verify_chain(&snapshots[0].certificate_hash).await?;
+//! #    Ok(())
+//! # }
+//! ```
+
+use anyhow::{anyhow, Context};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::aggregator_client::{
+    AggregatorClient, AggregatorClientError, AggregatorRequest, ContentInspectingReader,
+};
+use crate::MithrilResult;
+
+/// Kind of the [AggregatorClientError] of a recorded interaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordedErrorKind {
+    /// See [AggregatorClientError::RemoteServerTechnical]
+    RemoteServerTechnical,
+
+    /// See [AggregatorClientError::RemoteServerLogical]
+    RemoteServerLogical,
+
+    /// See [AggregatorClientError::ApiVersionMismatch]
+    ApiVersionMismatch,
+
+    /// See [AggregatorClientError::SubsystemError]
+    SubsystemError,
+
+    /// See [AggregatorClientError::ResponseSchemaMismatch]
+    ResponseSchemaMismatch,
+
+    /// See [AggregatorClientError::TlsPinMismatch]
+    TlsPinMismatch,
+
+    /// See [AggregatorClientError::RateLimited], its `Retry-After` delay is not recorded
+    RateLimited,
+
+    /// See [AggregatorClientError::NonJsonResponse], its message is the snippet of the response
+    NonJsonResponse,
+}
+
+/// Outcome of a recorded interaction.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RecordedOutcome {
+    /// The aggregator answered with the given content.
+    Response {
+        /// Content of the response
+        content: String,
+    },
+
+    /// The request failed.
+    Error {
+        /// Kind of the error
+        kind: RecordedErrorKind,
+
+        /// Message of the error, including its causes
+        message: String,
+
+        /// Content type of the response, only recorded for non JSON responses
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        content_type: Option<String>,
+    },
+}
+
+impl RecordedOutcome {
+    fn from_result(result: &Result<String, AggregatorClientError>) -> Self {
+        match result {
+            Ok(content) => Self::Response {
+                content: content.clone(),
+            },
+            Err(error) => Self::from_error(error),
+        }
+    }
+
+    fn from_error(error: &AggregatorClientError) -> Self {
+        let (kind, error) = match error {
+            AggregatorClientError::RemoteServerTechnical(error) => {
+                (RecordedErrorKind::RemoteServerTechnical, error)
+            }
+            AggregatorClientError::RemoteServerLogical(error) => {
+                (RecordedErrorKind::RemoteServerLogical, error)
+            }
+            AggregatorClientError::ApiVersionMismatch(error) => {
+                (RecordedErrorKind::ApiVersionMismatch, error)
+            }
+            AggregatorClientError::SubsystemError(error) => {
+                (RecordedErrorKind::SubsystemError, error)
+            }
+            AggregatorClientError::ResponseSchemaMismatch(error) => {
+                (RecordedErrorKind::ResponseSchemaMismatch, error)
+            }
+            AggregatorClientError::TlsPinMismatch(error) => {
+                (RecordedErrorKind::TlsPinMismatch, error)
+            }
+            error @ AggregatorClientError::RateLimited { .. } => {
+                return Self::Error {
+                    kind: RecordedErrorKind::RateLimited,
+                    message: error.to_string(),
+                    content_type: None,
+                }
+            }
+            AggregatorClientError::NonJsonResponse {
+                content_type,
+                snippet,
+            } => {
+                return Self::Error {
+                    kind: RecordedErrorKind::NonJsonResponse,
+                    message: snippet.clone(),
+                    content_type: Some(content_type.clone()),
+                }
+            }
+        };
+
+        Self::Error {
+            kind,
+            message: format!("{error:?}"),
+            content_type: None,
+        }
+    }
+
+    fn into_result(self) -> Result<String, AggregatorClientError> {
+        match self {
+            Self::Response { content } => Ok(content),
+            Self::Error {
+                kind,
+                message,
+                content_type,
+            } => {
+                if kind == RecordedErrorKind::NonJsonResponse {
+                    return Err(AggregatorClientError::NonJsonResponse {
+                        content_type: content_type.unwrap_or_default(),
+                        snippet: message,
+                    });
+                }
+                let error = anyhow!(message);
+                Err(match kind {
+                    RecordedErrorKind::RemoteServerTechnical => {
+                        AggregatorClientError::RemoteServerTechnical(error)
+                    }
+                    RecordedErrorKind::RemoteServerLogical => {
+                        AggregatorClientError::RemoteServerLogical(error)
+                    }
+                    RecordedErrorKind::ApiVersionMismatch => {
+                        AggregatorClientError::ApiVersionMismatch(error)
+                    }
+                    RecordedErrorKind::SubsystemError => {
+                        AggregatorClientError::SubsystemError(error)
+                    }
+                    RecordedErrorKind::ResponseSchemaMismatch => {
+                        AggregatorClientError::ResponseSchemaMismatch(error)
+                    }
+                    RecordedErrorKind::TlsPinMismatch => {
+                        AggregatorClientError::TlsPinMismatch(error)
+                    }
+                    RecordedErrorKind::RateLimited => {
+                        AggregatorClientError::RateLimited { retry_after: None }
+                    }
+                    RecordedErrorKind::NonJsonResponse => {
+                        unreachable!("non JSON responses are replayed above")
+                    }
+                })
+            }
+        }
+    }
+}
+
+/// A request made to an aggregator and its outcome, as written in a recording directory.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedInteraction {
+    /// Route of the request, see [AggregatorRequest::route]
+    pub route: String,
+
+    /// Outcome of the request
+    pub outcome: RecordedOutcome,
+}
+
+fn list_recording_files(directory: &Path) -> MithrilResult<Vec<PathBuf>> {
+    let mut files = vec![];
+    for entry in std::fs::read_dir(directory).with_context(|| {
+        format!(
+            "Could not read recording directory '{}'",
+            directory.display()
+        )
+    })? {
+        let path = entry?.path();
+        if path
+            .extension()
+            .is_some_and(|extension| extension == "json")
+        {
+            files.push(path);
+        }
+    }
+    // The files names start with their zero padded sequence number
+    files.sort();
+
+    Ok(files)
+}
+
+/// An [AggregatorClient] that writes the interactions of another client in a directory.
+///
+/// New interactions are appended after the ones already recorded in the directory.
+pub struct RecordingAggregatorClient {
+    aggregator_client: Arc<dyn AggregatorClient>,
+    recorder: InteractionRecorder,
+}
+
+/// Writes the interactions in the recording directory, shared with the readers of the
+/// streamed responses which are only recorded once fully read.
+#[derive(Clone)]
+struct InteractionRecorder {
+    directory: PathBuf,
+    next_sequence: Arc<AtomicUsize>,
+}
+
+impl RecordingAggregatorClient {
+    /// Constructs a new `RecordingAggregatorClient` that records in the given directory,
+    /// creating it if needed.
+    pub fn new<P: AsRef<Path>>(
+        aggregator_client: Arc<dyn AggregatorClient>,
+        directory: P,
+    ) -> MithrilResult<Self> {
+        let directory = directory.as_ref().to_path_buf();
+        std::fs::create_dir_all(&directory).with_context(|| {
+            format!(
+                "Could not create recording directory '{}'",
+                directory.display()
+            )
+        })?;
+        let next_sequence = list_recording_files(&directory)?.len();
+
+        Ok(Self {
+            aggregator_client,
+            recorder: InteractionRecorder {
+                directory,
+                next_sequence: Arc::new(AtomicUsize::new(next_sequence)),
+            },
+        })
+    }
+}
+
+impl InteractionRecorder {
+    fn record(&self, interaction: &RecordedInteraction) -> MithrilResult<()> {
+        let sequence = self.next_sequence.fetch_add(1, Ordering::SeqCst);
+        // Flatten the route so a recording can't escape the recording directory.
+        let file_name = interaction.route.replace(['/', '\\'], "_");
+        let path = self
+            .directory
+            .join(format!("{sequence:06}_{file_name}.json"));
+        let content = serde_json::to_string_pretty(interaction)
+            .with_context(|| "Could not serialize recorded interaction")?;
+
+        std::fs::write(&path, content)
+            .with_context(|| format!("Could not write recording file '{}'", path.display()))
+    }
+}
+
+#[cfg_attr(target_family = "wasm", async_trait(?Send))]
+#[cfg_attr(not(target_family = "wasm"), async_trait)]
+impl AggregatorClient for RecordingAggregatorClient {
+    async fn get_content(
+        &self,
+        request: AggregatorRequest,
+    ) -> Result<String, AggregatorClientError> {
+        let route = request.route();
+        let result = self.aggregator_client.get_content(request).await;
+
+        self.recorder
+            .record(&RecordedInteraction {
+                route,
+                outcome: RecordedOutcome::from_result(&result),
+            })
+            .map_err(AggregatorClientError::SubsystemError)?;
+
+        result
+    }
+
+    async fn get_content_reader(
+        &self,
+        request: AggregatorRequest,
+    ) -> Result<Box<dyn Read + Send>, AggregatorClientError> {
+        let route = request.route();
+        match self.aggregator_client.get_content_reader(request).await {
+            Ok(reader) => {
+                let recorder = self.recorder.clone();
+                let reader = ContentInspectingReader::new(reader, move |content| {
+                    recorder.record(&RecordedInteraction {
+                        route,
+                        outcome: RecordedOutcome::Response {
+                            content: String::from_utf8_lossy(content).into_owned(),
+                        },
+                    })
+                });
+
+                Ok(Box::new(reader))
+            }
+            Err(error) => {
+                self.recorder
+                    .record(&RecordedInteraction {
+                        route,
+                        outcome: RecordedOutcome::from_error(&error),
+                    })
+                    .map_err(AggregatorClientError::SubsystemError)?;
+
+                Err(error)
+            }
+        }
+    }
+}
+
+/// An [AggregatorClient] that serves the interactions recorded in a directory by a
+/// [RecordingAggregatorClient].
+///
+/// The interactions of a route are served in the order they were recorded, once they are all
+/// served the last one is served again.
+pub struct ReplayingAggregatorClient {
+    outcomes: Mutex<HashMap<String, VecDeque<RecordedOutcome>>>,
+}
+
+impl ReplayingAggregatorClient {
+    /// Constructs a new `ReplayingAggregatorClient` that serves the interactions recorded in
+    /// the given directory.
+    pub fn new<P: AsRef<Path>>(directory: P) -> MithrilResult<Self> {
+        let mut outcomes: HashMap<String, VecDeque<RecordedOutcome>> = HashMap::new();
+        for path in list_recording_files(directory.as_ref())? {
+            let content = std::fs::read_to_string(&path)
+                .with_context(|| format!("Could not read recording file '{}'", path.display()))?;
+            let interaction: RecordedInteraction = serde_json::from_str(&content)
+                .with_context(|| format!("Invalid recording file '{}'", path.display()))?;
+            outcomes
+                .entry(interaction.route)
+                .or_default()
+                .push_back(interaction.outcome);
+        }
+
+        Ok(Self {
+            outcomes: Mutex::new(outcomes),
+        })
+    }
+
+    fn next_outcome(&self, route: &str) -> MithrilResult<Option<RecordedOutcome>> {
+        let mut outcomes = self
+            .outcomes
+            .lock()
+            .map_err(|e| anyhow!("Replaying client lock is poisoned: {e}"))?;
+
+        Ok(outcomes.get_mut(route).and_then(|route_outcomes| {
+            if route_outcomes.len() > 1 {
+                route_outcomes.pop_front()
+            } else {
+                route_outcomes.front().cloned()
+            }
+        }))
+    }
+}
+
+#[cfg_attr(target_family = "wasm", async_trait(?Send))]
+#[cfg_attr(not(target_family = "wasm"), async_trait)]
+impl AggregatorClient for ReplayingAggregatorClient {
+    async fn get_content(
+        &self,
+        request: AggregatorRequest,
+    ) -> Result<String, AggregatorClientError> {
+        let route = request.route();
+
+        match self
+            .next_outcome(&route)
+            .map_err(AggregatorClientError::SubsystemError)?
+        {
+            Some(outcome) => outcome.into_result(),
+            None => Err(AggregatorClientError::SubsystemError(anyhow!(
+                "No interaction recorded for route '{route}'"
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::aggregator_client::MockAggregatorHTTPClient;
+    use crate::test_utils;
+
+    use super::*;
+
+    fn snapshot_request(digest: &str) -> AggregatorRequest {
+        AggregatorRequest::GetSnapshot {
+            digest: digest.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn replay_the_recorded_responses_and_errors() {
+        let directory =
+            test_utils::get_test_directory("recording", "replay_the_recorded_responses_and_errors");
+        let mut http_client = MockAggregatorHTTPClient::new();
+        http_client
+            .expect_get_content()
+            .returning(|request| match request {
+                AggregatorRequest::ListSnapshots => Ok("[]".to_string()),
+                _ => Err(AggregatorClientError::RemoteServerLogical(anyhow!(
+                    "not found"
+                ))),
+            });
+        let recording_client =
+            RecordingAggregatorClient::new(Arc::new(http_client), &directory).unwrap();
+        recording_client
+            .get_content(AggregatorRequest::ListSnapshots)
+            .await
+            .unwrap();
+        recording_client
+            .get_content(snapshot_request("digest"))
+            .await
+            .unwrap_err();
+
+        let replaying_client = ReplayingAggregatorClient::new(&directory).unwrap();
+
+        assert_eq!(
+            "[]",
+            replaying_client
+                .get_content(AggregatorRequest::ListSnapshots)
+                .await
+                .unwrap()
+        );
+        let error = replaying_client
+            .get_content(snapshot_request("digest"))
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(error, AggregatorClientError::RemoteServerLogical(_)),
+            "Unexpected error: {error:?}"
+        );
+        let error = replaying_client
+            .get_content(snapshot_request("other"))
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(error, AggregatorClientError::SubsystemError(_)),
+            "Unexpected error: {error:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn replay_the_responses_of_a_route_in_recording_order() {
+        let directory = test_utils::get_test_directory(
+            "recording",
+            "replay_the_responses_of_a_route_in_recording_order",
+        );
+        let mut http_client = MockAggregatorHTTPClient::new();
+        let mut sequence = 0;
+        http_client.expect_get_content().returning(move |_| {
+            sequence += 1;
+            Ok(format!("response-{sequence}"))
+        });
+        let http_client: Arc<dyn AggregatorClient> = Arc::new(http_client);
+        for _ in 0..2 {
+            // A new recording client appends its interactions to the existing ones
+            RecordingAggregatorClient::new(http_client.clone(), &directory)
+                .unwrap()
+                .get_content(AggregatorRequest::ListCertificates)
+                .await
+                .unwrap();
+        }
+
+        let replaying_client = ReplayingAggregatorClient::new(&directory).unwrap();
+        let mut responses = vec![];
+        for _ in 0..3 {
+            responses.push(
+                replaying_client
+                    .get_content(AggregatorRequest::ListCertificates)
+                    .await
+                    .unwrap(),
+            );
+        }
+
+        assert_eq!(vec!["response-1", "response-2", "response-2"], responses);
+    }
+
+    #[tokio::test]
+    async fn record_streamed_responses_once_fully_read() {
+        let directory = test_utils::get_test_directory(
+            "recording",
+            "record_streamed_responses_once_fully_read",
+        );
+        let mut http_client = MockAggregatorHTTPClient::new();
+        http_client
+            .expect_get_content_reader()
+            .returning(|_| Ok(Box::new(std::io::Cursor::new("[]"))));
+        let recording_client =
+            RecordingAggregatorClient::new(Arc::new(http_client), &directory).unwrap();
+
+        let mut reader = recording_client
+            .get_content_reader(AggregatorRequest::ListSnapshots)
+            .await
+            .unwrap();
+        assert!(list_recording_files(&directory).unwrap().is_empty());
+        std::io::copy(&mut reader, &mut std::io::sink()).unwrap();
+
+        let replaying_client = ReplayingAggregatorClient::new(&directory).unwrap();
+        assert_eq!(
+            "[]",
+            replaying_client
+                .get_content(AggregatorRequest::ListSnapshots)
+                .await
+                .unwrap()
+        );
+    }
+}
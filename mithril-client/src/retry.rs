@@ -0,0 +1,380 @@
+//! Retry of the requests to the aggregator that failed because of a transient error.
+//!
+//! When a [RetryPolicy] is given to the client using
+//! [ClientBuilder::with_retry_policy][crate::ClientBuilder::with_retry_policy], each request
+//! that fails because the aggregator couldn't be reached or answered with a technical error is
+//! sent again, after a delay that doubles between each attempt.
+//!
+//! Requests that fail with a logical error (ie: the artifact doesn't exist) or an API version
+//! mismatch are never retried.
+//!
+//! Requests rate limited by the aggregator (429 status) are sent again after the delay given
+//! by its `Retry-After` header, even without retry policy, as long as the total time spent
+//! waiting for the rate limits stays under
+//! [max_rate_limit_wait][RetryPolicy::max_rate_limit_wait]. Each wait is reported with a
+//! [MithrilEvent::AggregatorRateLimited] [feedback][crate::feedback] event.
+//!
+//! # Retry each request up to 5 times
+//!
+//! ```no_run
+//! # async fn run() -> mithril_client::MithrilResult<()> {
+//! use mithril_client::{retry::RetryPolicy, ClientBuilder};
+//! use std::time::Duration;
+//!
+//! let client = ClientBuilder::aggregator("YOUR_AGGREGATOR_ENDPOINT", "YOUR_GENESIS_VERIFICATION_KEY")
+//!     .with_retry_policy(RetryPolicy::new(5, Duration::from_millis(200)))
+//!     .build()?;
+//!
+//! let snapshots = client.snapshot().list().await?;
+//! #    Ok(())
+//! # }
+//! ```
+
+use async_trait::async_trait;
+use slog::{warn, Logger};
+use std::io::Read;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::aggregator_client::{AggregatorClient, AggregatorClientError, AggregatorRequest};
+use crate::utils::time;
+use crate::feedback::{FeedbackSender, MithrilEvent};
+
+/// How many times and how often a failed request is sent again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Maximum number of attempts of a request, including the first one
+    pub max_attempts: u32,
+
+    /// Delay before the first retry, doubled before each following retry
+    pub initial_delay: Duration,
+
+    /// Maximum total time spent waiting for the rate limits of the aggregator for a request
+    pub max_rate_limit_wait: Duration,
+}
+
+impl RetryPolicy {
+    /// Default value of [max_rate_limit_wait][Self::max_rate_limit_wait]
+    pub const DEFAULT_MAX_RATE_LIMIT_WAIT: Duration = Duration::from_secs(300);
+
+    /// Constructs a new `RetryPolicy`.
+    pub fn new(max_attempts: u32, initial_delay: Duration) -> Self {
+        Self {
+            max_attempts,
+            initial_delay,
+            max_rate_limit_wait: Self::DEFAULT_MAX_RATE_LIMIT_WAIT,
+        }
+    }
+
+    /// Policy that never retries a failed request but waits for the rate limits of the
+    /// aggregator, used when no policy is given to the client.
+    pub fn rate_limit_only() -> Self {
+        Self::new(1, Duration::from_secs(1))
+    }
+
+    /// Set the maximum total time spent waiting for the rate limits of the aggregator for a
+    /// request.
+    pub fn with_max_rate_limit_wait(mut self, max_rate_limit_wait: Duration) -> Self {
+        self.max_rate_limit_wait = max_rate_limit_wait;
+        self
+    }
+
+    /// Delay to wait before the given retry, starting at 1.
+    pub fn delay_before_retry(&self, retry: u32) -> Duration {
+        self.initial_delay
+            .saturating_mul(2u32.saturating_pow(retry.saturating_sub(1)))
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new(3, Duration::from_millis(500))
+    }
+}
+
+/// An [AggregatorClient] that sends again the requests that failed because of a transient error
+/// or a rate limit, according to a [RetryPolicy].
+pub struct RetryingAggregatorClient {
+    aggregator_client: Arc<dyn AggregatorClient>,
+    retry_policy: RetryPolicy,
+    feedback_sender: FeedbackSender,
+    logger: Logger,
+}
+
+impl RetryingAggregatorClient {
+    /// Constructs a new `RetryingAggregatorClient`.
+    pub fn new(
+        aggregator_client: Arc<dyn AggregatorClient>,
+        retry_policy: RetryPolicy,
+        logger: Logger,
+    ) -> Self {
+        Self {
+            aggregator_client,
+            retry_policy,
+            feedback_sender: FeedbackSender::new(&[]),
+            logger,
+        }
+    }
+
+    /// Set the [FeedbackSender] used to report the rate limited requests.
+    pub fn with_feedback_sender(mut self, feedback_sender: FeedbackSender) -> Self {
+        self.feedback_sender = feedback_sender;
+        self
+    }
+
+    fn is_transient(error: &AggregatorClientError) -> bool {
+        matches!(
+            error,
+            AggregatorClientError::RemoteServerTechnical(_)
+                | AggregatorClientError::SubsystemError(_)
+        )
+    }
+
+    async fn send<T, F, Fut>(
+        &self,
+        request: &AggregatorRequest,
+        send_request: F,
+    ) -> Result<T, AggregatorClientError>
+    where
+        F: Fn(Arc<dyn AggregatorClient>) -> Fut,
+        Fut: std::future::Future<Output = Result<T, AggregatorClientError>>,
+    {
+        let mut attempt = 1;
+        let mut rate_limited = 0;
+        let mut rate_limit_wait = Duration::ZERO;
+        loop {
+            match send_request(self.aggregator_client.clone()).await {
+                Err(AggregatorClientError::RateLimited { retry_after }) => {
+                    rate_limited += 1;
+                    let delay = retry_after
+                        .unwrap_or_else(|| self.retry_policy.delay_before_retry(rate_limited));
+                    if rate_limit_wait + delay > self.retry_policy.max_rate_limit_wait {
+                        return Err(AggregatorClientError::RateLimited { retry_after });
+                    }
+                    warn!(
+                        self.logger,
+                        "Request rate limited by the aggregator, retrying in {delay:?}";
+                        "route" => request.route(), "rate_limited" => rate_limited
+                    );
+                    self.feedback_sender
+                        .send_event(MithrilEvent::AggregatorRateLimited {
+                            route: request.route(),
+                            retry_after: delay,
+                        })
+                        .await;
+                    time::sleep(delay).await;
+                    rate_limit_wait += delay;
+                }
+                Err(error)
+                    if attempt < self.retry_policy.max_attempts && Self::is_transient(&error) =>
+                {
+                    let delay = self.retry_policy.delay_before_retry(attempt);
+                    warn!(
+                        self.logger,
+                        "Request to the aggregator failed, retrying in {delay:?}";
+                        "route" => request.route(), "attempt" => attempt, "error" => ?error
+                    );
+                    time::sleep(delay).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+#[cfg_attr(target_family = "wasm", async_trait(?Send))]
+#[cfg_attr(not(target_family = "wasm"), async_trait)]
+impl AggregatorClient for RetryingAggregatorClient {
+    async fn get_content(
+        &self,
+        request: AggregatorRequest,
+    ) -> Result<String, AggregatorClientError> {
+        self.send(&request, |client| {
+            let request = request.clone();
+            async move { client.get_content(request).await }
+        })
+        .await
+    }
+
+    async fn get_content_reader(
+        &self,
+        request: AggregatorRequest,
+    ) -> Result<Box<dyn Read + Send>, AggregatorClientError> {
+        self.send(&request, |client| {
+            let request = request.clone();
+            async move { client.get_content_reader(request).await }
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::anyhow;
+    use mockall::Sequence;
+
+    use crate::aggregator_client::MockAggregatorHTTPClient;
+    use crate::feedback::StackFeedbackReceiver;
+    use crate::test_utils;
+
+    use super::*;
+
+    fn retrying_client(
+        aggregator_client: MockAggregatorHTTPClient,
+        max_attempts: u32,
+    ) -> RetryingAggregatorClient {
+        RetryingAggregatorClient::new(
+            Arc::new(aggregator_client),
+            RetryPolicy::new(max_attempts, Duration::ZERO),
+            test_utils::test_logger(),
+        )
+    }
+
+    #[test]
+    fn delay_doubles_between_each_retry() {
+        let policy = RetryPolicy::new(4, Duration::from_millis(100));
+
+        assert_eq!(
+            vec![
+                Duration::from_millis(100),
+                Duration::from_millis(200),
+                Duration::from_millis(400)
+            ],
+            (1..4)
+                .map(|retry| policy.delay_before_retry(retry))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[tokio::test]
+    async fn retry_until_the_request_succeeds() {
+        let mut sequence = Sequence::new();
+        let mut aggregator_client = MockAggregatorHTTPClient::new();
+        aggregator_client
+            .expect_get_content()
+            .times(2)
+            .in_sequence(&mut sequence)
+            .returning(|_| {
+                Err(AggregatorClientError::RemoteServerTechnical(anyhow!(
+                    "unavailable"
+                )))
+            });
+        aggregator_client
+            .expect_get_content()
+            .times(1)
+            .in_sequence(&mut sequence)
+            .returning(|_| Ok("content".to_string()));
+        let client = retrying_client(aggregator_client, 3);
+
+        let content = client
+            .get_content(AggregatorRequest::ListSnapshots)
+            .await
+            .unwrap();
+
+        assert_eq!("content", content);
+    }
+
+    #[tokio::test]
+    async fn stop_retrying_after_the_max_attempts() {
+        let mut aggregator_client = MockAggregatorHTTPClient::new();
+        aggregator_client
+            .expect_get_content()
+            .times(2)
+            .returning(|_| Err(AggregatorClientError::SubsystemError(anyhow!("timeout"))));
+        let client = retrying_client(aggregator_client, 2);
+
+        client
+            .get_content(AggregatorRequest::ListSnapshots)
+            .await
+            .expect_err("get_content should fail after the max attempts");
+    }
+
+    #[tokio::test]
+    async fn never_retry_a_logical_error() {
+        let mut aggregator_client = MockAggregatorHTTPClient::new();
+        aggregator_client
+            .expect_get_content()
+            .times(1)
+            .returning(|_| {
+                Err(AggregatorClientError::RemoteServerLogical(anyhow!(
+                    "not found"
+                )))
+            });
+        let client = retrying_client(aggregator_client, 3);
+
+        client
+            .get_content(AggregatorRequest::ListSnapshots)
+            .await
+            .expect_err("get_content should fail");
+    }
+
+    #[tokio::test]
+    async fn wait_for_the_rate_limit_before_sending_the_request_again() {
+        let mut sequence = Sequence::new();
+        let mut aggregator_client = MockAggregatorHTTPClient::new();
+        aggregator_client
+            .expect_get_content()
+            .times(2)
+            .in_sequence(&mut sequence)
+            .returning(|_| {
+                Err(AggregatorClientError::RateLimited {
+                    retry_after: Some(Duration::ZERO),
+                })
+            });
+        aggregator_client
+            .expect_get_content()
+            .times(1)
+            .in_sequence(&mut sequence)
+            .returning(|_| Ok("content".to_string()));
+        let feedback_receiver = Arc::new(StackFeedbackReceiver::new());
+        // Rate limits don't count as attempts
+        let client = retrying_client(aggregator_client, 1)
+            .with_feedback_sender(FeedbackSender::new(&[feedback_receiver.clone()]));
+
+        let content = client
+            .get_content(AggregatorRequest::ListSnapshots)
+            .await
+            .unwrap();
+
+        assert_eq!("content", content);
+        assert_eq!(
+            vec![
+                MithrilEvent::AggregatorRateLimited {
+                    route: "artifact/snapshots".to_string(),
+                    retry_after: Duration::ZERO,
+                };
+                2
+            ],
+            feedback_receiver.stacked_events()
+        );
+    }
+
+    #[tokio::test]
+    async fn fail_when_the_rate_limit_exceeds_the_max_wait() {
+        let mut aggregator_client = MockAggregatorHTTPClient::new();
+        aggregator_client
+            .expect_get_content()
+            .times(1)
+            .returning(|_| {
+                Err(AggregatorClientError::RateLimited {
+                    retry_after: Some(Duration::from_secs(60)),
+                })
+            });
+        let client = RetryingAggregatorClient::new(
+            Arc::new(aggregator_client),
+            RetryPolicy::new(3, Duration::ZERO).with_max_rate_limit_wait(Duration::from_secs(30)),
+            test_utils::test_logger(),
+        );
+
+        let error = client
+            .get_content(AggregatorRequest::ListSnapshots)
+            .await
+            .expect_err("get_content should fail");
+
+        assert!(
+            matches!(error, AggregatorClientError::RateLimited { .. }),
+            "Unexpected error: {error:?}"
+        );
+    }
+}
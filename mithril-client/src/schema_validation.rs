@@ -0,0 +1,567 @@
+//! Validation of the aggregator responses against the schemas of its Open API specification.
+//!
+//! When the client is built with the strict schema validation mode (see
+//! [ClientBuilder::with_strict_schema_validation][crate::ClientBuilder::with_strict_schema_validation]),
+//! each response of the aggregator is checked against the schema declared in the Open API
+//! specification embedded in the library before being deserialized.
+//!
+//! A response that diverges from its schema is rejected with an
+//! [AggregatorClientError::ResponseSchemaMismatch] error which source is a
+//! [SchemaValidationReport] listing every divergent field, this helps to diagnose a version drift
+//! between the client and the aggregator.
+//!
+//! The validation is disabled if the library was built without the Open API specification, see
+//! [OpenApiSchemaValidator::has_embedded_specification].
+//!
+//! # Diagnose a version drift
+//!
+//! ```no_run
+//! # async fn run() -> mithril_client::MithrilResult<()> {
+//! use mithril_client::{schema_validation::SchemaValidationReport, ClientBuilder};
+//!
+//! let client = ClientBuilder::aggregator("YOUR_AGGREGATOR_ENDPOINT", "YOUR_GENESIS_VERIFICATION_KEY")
+//!     .with_strict_schema_validation(true)
+//!     .build()?;
+//!
+//! if let Err(error) = client.snapshot().list().await {
+//!     match error.chain().find_map(|e| e.downcast_ref::<SchemaValidationReport>()) {
+//!         Some(report) => println!("The aggregator response diverged:\n{report}"),
+//!         None => println!("Listing failed: {error:?}"),
+//!     }
+//! }
+//! #    Ok(())
+//! # }
+//! ```
+
+use anyhow::{anyhow, Context};
+use async_trait::async_trait;
+use serde_json::{Map, Value};
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+use std::io::Read;
+use std::sync::Arc;
+use thiserror::Error;
+
+use crate::aggregator_client::{
+    AggregatorClient, AggregatorClientError, AggregatorRequest, ContentInspectingReader,
+};
+use crate::common::api_version::OPEN_API_SCHEMAS_JSON;
+use crate::MithrilResult;
+
+/// A field of a response that diverged from its schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaViolation {
+    /// JSON pointer of the divergent field, ie: `/0/beacon/epoch`
+    pub path: String,
+
+    /// Description of the divergence
+    pub message: String,
+}
+
+impl Display for SchemaViolation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let path = if self.path.is_empty() {
+            "/"
+        } else {
+            &self.path
+        };
+        write!(f, "'{path}': {}", self.message)
+    }
+}
+
+/// Result of the validation of an aggregator response against its schema.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub struct SchemaValidationReport {
+    /// Route of the validated response
+    pub route: String,
+
+    /// Fields of the response that diverged from the schema
+    pub violations: Vec<SchemaViolation>,
+}
+
+impl SchemaValidationReport {
+    /// Returns `true` if the response matches its schema.
+    pub fn is_valid(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+impl Display for SchemaValidationReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Response of route '{}' diverged from its schema in {} field(s):",
+            self.route,
+            self.violations.len()
+        )?;
+        for violation in &self.violations {
+            write!(f, "\n - {violation}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Validate JSON documents against the schemas of an Open API specification.
+pub struct OpenApiSchemaValidator {
+    paths: Map<String, Value>,
+    schemas: Map<String, Value>,
+}
+
+impl OpenApiSchemaValidator {
+    /// Returns `true` if an Open API specification was embedded in the library when it was built,
+    /// it is not if the library is built without the specification (ie: from its published
+    /// package).
+    pub fn has_embedded_specification() -> bool {
+        Self::from_embedded_specification().is_ok_and(|validator| !validator.paths.is_empty())
+    }
+
+    /// Constructs a validator that uses the specification embedded in the library.
+    pub fn from_embedded_specification() -> MithrilResult<Self> {
+        Self::from_json(OPEN_API_SCHEMAS_JSON)
+    }
+
+    /// Constructs a validator from a JSON document with the `paths` and `schemas` (the
+    /// `components.schemas` section) of an Open API specification.
+    pub fn from_json(specification: &str) -> MithrilResult<Self> {
+        let mut specification: Value = serde_json::from_str(specification)
+            .with_context(|| "Could not parse the Open API specification")?;
+        let mut take_object = |key: &str| match specification[key].take() {
+            Value::Object(object) => Ok(object),
+            _ => Err(anyhow!("Open API specification has no '{key}' object")),
+        };
+
+        Ok(Self {
+            paths: take_object("paths")?,
+            schemas: take_object("schemas")?,
+        })
+    }
+
+    /// Validate the response of the given route, ie: `certificate/123`, against the schema of the
+    /// successful response of the matching path of the specification.
+    pub fn validate_response(
+        &self,
+        route: &str,
+        content: &str,
+    ) -> MithrilResult<SchemaValidationReport> {
+        let schema = self
+            .response_schema(route)
+            .ok_or_else(|| anyhow!("No response schema found for route '{route}'"))?;
+        let document: Value = serde_json::from_str(content)
+            .with_context(|| format!("Response of route '{route}' is not valid JSON"))?;
+        let mut violations = vec![];
+        self.validate(&document, schema, "", &mut violations);
+
+        Ok(SchemaValidationReport {
+            route: route.to_string(),
+            violations,
+        })
+    }
+
+    /// Find the schema of the route, when several templates match it the most specific one is
+    /// used (literal segments take precedence over parameters, from left to right).
+    fn response_schema(&self, route: &str) -> Option<&Value> {
+        let route_segments: Vec<&str> = route.trim_matches('/').split('/').collect();
+        let (_, path) = self
+            .paths
+            .iter()
+            .filter_map(|(template, path)| {
+                let template_segments: Vec<&str> = template.trim_matches('/').split('/').collect();
+                if template_segments.len() != route_segments.len() {
+                    return None;
+                }
+                template_segments
+                    .iter()
+                    .zip(&route_segments)
+                    .map(|(template, segment)| {
+                        if template.starts_with('{') && template.ends_with('}') {
+                            Some(false)
+                        } else {
+                            (template == segment).then_some(true)
+                        }
+                    })
+                    .collect::<Option<Vec<bool>>>()
+                    .map(|literal_segments| (literal_segments, path))
+            })
+            .max_by(|(left, _), (right, _)| left.cmp(right))?;
+
+        path.pointer("/get/responses/200/content/application~1json/schema")
+    }
+
+    /// Follow the `$ref` of the schema, yields `Value::Null` if a reference can not be resolved
+    /// or if the references form a cycle.
+    fn resolve<'a>(&'a self, mut schema: &'a Value) -> &'a Value {
+        let mut visited_references = HashSet::new();
+        while let Some(reference) = schema["$ref"].as_str() {
+            if !visited_references.insert(reference) {
+                return &Value::Null;
+            }
+            schema = match reference
+                .strip_prefix("#/components/schemas/")
+                .and_then(|name| self.schemas.get(name))
+            {
+                Some(schema) => schema,
+                None => return &Value::Null,
+            };
+        }
+
+        schema
+    }
+
+    fn validate(
+        &self,
+        document: &Value,
+        schema: &Value,
+        path: &str,
+        violations: &mut Vec<SchemaViolation>,
+    ) {
+        let schema = self.resolve(schema);
+        let mut violation = |message: String| {
+            violations.push(SchemaViolation {
+                path: path.to_string(),
+                message,
+            })
+        };
+        if schema.is_null() {
+            violation("schema reference could not be resolved".to_string());
+            return;
+        }
+        if document.is_null() && schema["nullable"].as_bool().unwrap_or(false) {
+            return;
+        }
+
+        if let Some(expected_type) = schema["type"].as_str() {
+            if !matches_type(document, expected_type) {
+                violation(format!(
+                    "expected type '{expected_type}', found '{}'",
+                    type_name(document)
+                ));
+                return;
+            }
+        }
+        if let Some(values) = schema["enum"].as_array() {
+            if !values.contains(document) {
+                violation(format!("value {document} is not one of {values:?}"));
+            }
+        }
+
+        if let Some(schemas) = schema["allOf"].as_array() {
+            for schema in schemas {
+                self.validate(document, schema, path, violations);
+            }
+        }
+        for keyword in ["oneOf", "anyOf"] {
+            if let Some(schemas) = schema[keyword].as_array() {
+                let is_valid = schemas.iter().any(|schema| {
+                    let mut alternative_violations = vec![];
+                    self.validate(document, schema, path, &mut alternative_violations);
+                    alternative_violations.is_empty()
+                });
+                if !is_valid {
+                    violations.push(SchemaViolation {
+                        path: path.to_string(),
+                        message: format!("value does not match any schema of '{keyword}'"),
+                    });
+                }
+            }
+        }
+
+        match document {
+            Value::Object(object) => self.validate_object(object, schema, path, violations),
+            Value::Array(items) => {
+                if let Some(items_schema) = schema.get("items") {
+                    for (index, item) in items.iter().enumerate() {
+                        self.validate(item, items_schema, &format!("{path}/{index}"), violations);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn validate_object(
+        &self,
+        object: &Map<String, Value>,
+        schema: &Value,
+        path: &str,
+        violations: &mut Vec<SchemaViolation>,
+    ) {
+        for required in schema["required"].as_array().into_iter().flatten() {
+            if let Some(field) = required.as_str().filter(|f| !object.contains_key(*f)) {
+                violations.push(SchemaViolation {
+                    path: format!("{path}/{field}"),
+                    message: "required field is missing".to_string(),
+                });
+            }
+        }
+
+        let properties = schema["properties"].as_object();
+        for (field, value) in object {
+            let field_path = format!("{path}/{field}");
+            match (
+                properties.and_then(|p| p.get(field)),
+                &schema["additionalProperties"],
+            ) {
+                (Some(field_schema), _) => {
+                    self.validate(value, field_schema, &field_path, violations)
+                }
+                (None, Value::Bool(false)) if schema.get("allOf").is_none() => {
+                    violations.push(SchemaViolation {
+                        path: field_path,
+                        message: "field is not declared in the schema".to_string(),
+                    })
+                }
+                (None, additional_schema @ Value::Object(_)) => {
+                    self.validate(value, additional_schema, &field_path, violations)
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+fn matches_type(document: &Value, expected_type: &str) -> bool {
+    match expected_type {
+        "object" => document.is_object(),
+        "array" => document.is_array(),
+        "string" => document.is_string(),
+        "integer" => document.is_i64() || document.is_u64(),
+        "number" => document.is_number(),
+        "boolean" => document.is_boolean(),
+        _ => true,
+    }
+}
+
+fn type_name(document: &Value) -> &'static str {
+    match document {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(number) if number.is_f64() => "number",
+        Value::Number(_) => "integer",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// An [AggregatorClient] that validates the responses of another client against their Open API
+/// schema, and rejects the divergent ones.
+pub struct SchemaValidatingAggregatorClient {
+    aggregator_client: Arc<dyn AggregatorClient>,
+    validator: Arc<OpenApiSchemaValidator>,
+}
+
+impl SchemaValidatingAggregatorClient {
+    /// Constructs a new `SchemaValidatingAggregatorClient` that validates the responses against
+    /// the specification embedded in the library.
+    pub fn new(aggregator_client: Arc<dyn AggregatorClient>) -> MithrilResult<Self> {
+        Ok(Self {
+            aggregator_client,
+            validator: Arc::new(OpenApiSchemaValidator::from_embedded_specification()?),
+        })
+    }
+}
+
+#[cfg_attr(target_family = "wasm", async_trait(?Send))]
+#[cfg_attr(not(target_family = "wasm"), async_trait)]
+impl AggregatorClient for SchemaValidatingAggregatorClient {
+    async fn get_content(
+        &self,
+        request: AggregatorRequest,
+    ) -> Result<String, AggregatorClientError> {
+        let route = request.route();
+        let content = self.aggregator_client.get_content(request).await?;
+
+        let report = self
+            .validator
+            .validate_response(&route, &content)
+            .map_err(AggregatorClientError::ResponseSchemaMismatch)?;
+        if !report.is_valid() {
+            return Err(AggregatorClientError::ResponseSchemaMismatch(report.into()));
+        }
+
+        Ok(content)
+    }
+
+    async fn get_content_reader(
+        &self,
+        request: AggregatorRequest,
+    ) -> Result<Box<dyn Read + Send>, AggregatorClientError> {
+        let route = request.route();
+        let reader = self.aggregator_client.get_content_reader(request).await?;
+        let validator = self.validator.clone();
+
+        Ok(Box::new(ContentInspectingReader::new(
+            reader,
+            move |content| {
+                let content = std::str::from_utf8(content)
+                    .with_context(|| format!("Response of route '{route}' is not UTF-8"))?;
+                let report = validator.validate_response(&route, content)?;
+                if !report.is_valid() {
+                    return Err(report.into());
+                }
+
+                Ok(())
+            },
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::aggregator_client::MockAggregatorHTTPClient;
+    use crate::common::messages::AggregatorFeaturesMessage;
+    use crate::{MithrilCertificateListItem, Snapshot, SnapshotListItem};
+
+    use super::*;
+
+    fn validator() -> OpenApiSchemaValidator {
+        OpenApiSchemaValidator::from_embedded_specification().unwrap()
+    }
+
+    #[test]
+    fn client_messages_match_the_embedded_schemas() {
+        let responses = [
+            (
+                AggregatorRequest::ListCertificates,
+                serde_json::to_string(&vec![MithrilCertificateListItem::dummy()]).unwrap(),
+            ),
+            (
+                AggregatorRequest::ListSnapshots,
+                serde_json::to_string(&vec![SnapshotListItem::dummy()]).unwrap(),
+            ),
+            (
+                AggregatorRequest::GetSnapshot {
+                    digest: "digest".to_string(),
+                },
+                serde_json::to_string(&Snapshot::dummy()).unwrap(),
+            ),
+            (
+                AggregatorRequest::GetAggregatorFeatures,
+                serde_json::to_string(&AggregatorFeaturesMessage::dummy()).unwrap(),
+            ),
+        ];
+
+        for (request, content) in responses {
+            let report = validator()
+                .validate_response(&request.route(), &content)
+                .unwrap();
+
+            assert!(report.is_valid(), "{report}");
+        }
+    }
+
+    #[test]
+    fn the_specification_is_embedded_when_built_from_the_repository() {
+        assert!(OpenApiSchemaValidator::has_embedded_specification());
+    }
+
+    #[test]
+    fn report_every_divergent_field() {
+        let mut snapshot = serde_json::to_value(Snapshot::dummy()).unwrap();
+        snapshot["size"] = Value::from("big");
+        snapshot.as_object_mut().unwrap().remove("digest");
+        snapshot["unexpected"] = Value::from(true);
+
+        let report = validator()
+            .validate_response("artifact/snapshot/digest", &snapshot.to_string())
+            .unwrap();
+
+        assert_eq!(
+            vec![
+                "/digest".to_string(),
+                "/size".to_string(),
+                "/unexpected".to_string()
+            ],
+            report
+                .violations
+                .iter()
+                .map(|violation| violation.path.clone())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn validate_response_fails_for_unknown_route() {
+        validator()
+            .validate_response("unknown/route", "{}")
+            .expect_err("validate_response should fail for an unknown route");
+    }
+
+    #[test]
+    fn validate_response_reports_cyclic_references() {
+        let validator = OpenApiSchemaValidator::from_json(
+            r##"{
+                "paths": {"/cycle": {"get": {"responses": {"200": {"content": {"application/json": {
+                    "schema": {"$ref": "#/components/schemas/A"}
+                }}}}}}},
+                "schemas": {
+                    "A": {"$ref": "#/components/schemas/B"},
+                    "B": {"$ref": "#/components/schemas/A"}
+                }
+            }"##,
+        )
+        .unwrap();
+
+        let report = validator.validate_response("cycle", "{}").unwrap();
+
+        assert_eq!(
+            vec![SchemaViolation {
+                path: String::new(),
+                message: "schema reference could not be resolved".to_string(),
+            }],
+            report.violations
+        );
+    }
+
+    #[test]
+    fn validate_response_uses_the_most_specific_route() {
+        let validator = OpenApiSchemaValidator::from_json(
+            r##"{
+                "paths": {
+                    "/artifact/{type}/{id}": {"get": {"responses": {"200": {"content": {
+                        "application/json": {"schema": {"type": "string"}}
+                    }}}}},
+                    "/artifact/snapshot/{digest}": {"get": {"responses": {"200": {"content": {
+                        "application/json": {"schema": {"type": "object"}}
+                    }}}}}
+                },
+                "schemas": {}
+            }"##,
+        )
+        .unwrap();
+
+        let snapshot_report = validator
+            .validate_response("artifact/snapshot/digest", "{}")
+            .unwrap();
+        let other_report = validator
+            .validate_response("artifact/other/id", r#""value""#)
+            .unwrap();
+
+        assert!(snapshot_report.is_valid(), "{snapshot_report}");
+        assert!(other_report.is_valid(), "{other_report}");
+    }
+
+    #[tokio::test]
+    async fn client_rejects_divergent_responses() {
+        let mut aggregator_client = MockAggregatorHTTPClient::new();
+        aggregator_client
+            .expect_get_content()
+            .returning(|_| Ok(r#"[{"digest": 12}]"#.to_string()));
+        let client = SchemaValidatingAggregatorClient::new(Arc::new(aggregator_client)).unwrap();
+
+        let error = client
+            .get_content(AggregatorRequest::ListSnapshots)
+            .await
+            .expect_err("get_content should fail");
+
+        let AggregatorClientError::ResponseSchemaMismatch(source) = error else {
+            panic!("Unexpected error: {error:?}");
+        };
+        let report = source.downcast_ref::<SchemaValidationReport>().unwrap();
+        assert!(report.violations.contains(&SchemaViolation {
+            path: "/0/digest".to_string(),
+            message: "expected type 'string', found 'integer'".to_string(),
+        }));
+    }
+}
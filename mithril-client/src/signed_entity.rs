@@ -0,0 +1,370 @@
+//! Describe the types of data signed by the Mithril protocol in a single place.
+//!
+//! Each type of signed data, or signed entity, is described by a [SignedEntity]: its name, the
+//! routes of its artifacts on an aggregator and the parts of the protocol message signed for it.
+//! The signed entities known by a client are kept in a [SignedEntityRegistry], that already
+//! contains the signed entities of this version of the library.
+//!
+//! Adding a signed entity to the registry is enough for the [MessageBuilder][crate::MessageBuilder]
+//! to assemble its messages and for the [Client][crate::Client] to fetch its artifacts.
+//!
+//! # Fetch the artifacts of a new signed entity
+//!
+//! ```no_run
+//! # async fn run() -> mithril_client::MithrilResult<()> {
+//! use mithril_client::aggregator_client::AggregatorRoute;
+//! use mithril_client::common::entities::ProtocolMessagePartKey;
+//! use mithril_client::signed_entity::{SignedEntity, SignedEntityRegistry};
+//! use mithril_client::ClientBuilder;
+//! use std::sync::Arc;
+//!
+//! struct CardanoTransactions;
+//!
+//! impl SignedEntity for CardanoTransactions {
+//!     fn name(&self) -> &'static str {
+//!         "CardanoTransactions"
+//!     }
+//!
+//!     fn artifacts_route(&self) -> Option<AggregatorRoute> {
+//!         Some(AggregatorRoute::new("artifact/cardano-transactions"))
+//!     }
+//!
+//!     fn artifact_route(&self, artifact_id: &str) -> Option<AggregatorRoute> {
+//!         Some(AggregatorRoute::new("artifact/cardano-transaction").with_parameter(artifact_id))
+//!     }
+//!
+//!     fn required_message_parts(&self) -> &'static [ProtocolMessagePartKey] {
+//!         &[ProtocolMessagePartKey::SnapshotDigest]
+//!     }
+//! }
+//!
+//! let client = ClientBuilder::aggregator("YOUR_AGGREGATOR_ENDPOINT", "YOUR_GENESIS_VERIFICATION_KEY")
+//!     .with_signed_entity_registry(
+//!         SignedEntityRegistry::default().with_signed_entity(Arc::new(CardanoTransactions)),
+//!     )
+//!     .build()?;
+//!
+//! let transactions_sets: Vec<serde_json::Value> =
+//!     client.list_signed_entity_artifacts("CardanoTransactions").await?;
+//! #    Ok(())
+//! # }
+//! ```
+
+use anyhow::anyhow;
+use std::collections::BTreeMap;
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+
+use crate::aggregator_client::{AggregatorRequest, AggregatorRoute};
+use crate::common::entities::{ProtocolMessage, ProtocolMessagePartKey, SignedEntityType};
+use crate::MithrilResult;
+
+/// Description of a type of data signed by the Mithril protocol.
+pub trait SignedEntity: Send + Sync {
+    /// Name of the signed entity, as displayed by a [SignedEntityType] (ie:
+    /// `MithrilStakeDistribution`).
+    fn name(&self) -> &'static str;
+
+    /// Route of the list of the artifacts of the signed entity, `None` if the aggregator doesn't
+    /// publish them.
+    fn artifacts_route(&self) -> Option<AggregatorRoute>;
+
+    /// Route of the artifact of the signed entity with the given identifier, `None` if the
+    /// aggregator doesn't publish them.
+    fn artifact_route(&self, artifact_id: &str) -> Option<AggregatorRoute>;
+
+    /// Parts of the protocol message that must be signed for the signed entity, besides the
+    /// aggregate verification key of the next signers that is always signed.
+    fn required_message_parts(&self) -> &'static [ProtocolMessagePartKey];
+
+    /// Parts of the protocol message that may be signed for the signed entity.
+    fn optional_message_parts(&self) -> &'static [ProtocolMessagePartKey] {
+        &[]
+    }
+
+    /// Assemble the protocol message signed for the signed entity from the aggregate verification
+    /// key of the next signers and the other parts of the message.
+    ///
+    /// Fails if a required part is missing or if a part that the signed entity doesn't sign is
+    /// given.
+    fn assemble_protocol_message(
+        &self,
+        next_aggregate_verification_key: String,
+        parts: BTreeMap<ProtocolMessagePartKey, String>,
+    ) -> MithrilResult<ProtocolMessage> {
+        let name = self.name();
+        let mut message = ProtocolMessage::new();
+        message.set_message_part(
+            ProtocolMessagePartKey::NextAggregateVerificationKey,
+            next_aggregate_verification_key,
+        );
+        if let Some(missing_part) = self
+            .required_message_parts()
+            .iter()
+            .find(|key| !parts.contains_key(key))
+        {
+            return Err(anyhow!(
+                "Could not assemble message of '{name}': missing {missing_part} part"
+            ));
+        }
+        for (key, value) in parts {
+            if !self.required_message_parts().contains(&key)
+                && !self.optional_message_parts().contains(&key)
+            {
+                return Err(anyhow!(
+                    "Could not assemble message of '{name}': {key} part is not signed for this \
+                    signed entity"
+                ));
+            }
+            message.set_message_part(key, value);
+        }
+
+        Ok(message)
+    }
+}
+
+/// The [SignedEntity] of the Mithril stake distributions.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MithrilStakeDistributionSignedEntity;
+
+impl SignedEntity for MithrilStakeDistributionSignedEntity {
+    fn name(&self) -> &'static str {
+        "MithrilStakeDistribution"
+    }
+
+    fn artifacts_route(&self) -> Option<AggregatorRoute> {
+        Some(AggregatorRequest::ListMithrilStakeDistributions.aggregator_route())
+    }
+
+    fn artifact_route(&self, artifact_id: &str) -> Option<AggregatorRoute> {
+        Some(
+            AggregatorRequest::GetMithrilStakeDistribution {
+                hash: artifact_id.to_string(),
+            }
+            .aggregator_route(),
+        )
+    }
+
+    fn required_message_parts(&self) -> &'static [ProtocolMessagePartKey] {
+        &[]
+    }
+}
+
+/// The [SignedEntity] of the Cardano stake distributions, their artifacts are not published.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CardanoStakeDistributionSignedEntity;
+
+impl SignedEntity for CardanoStakeDistributionSignedEntity {
+    fn name(&self) -> &'static str {
+        "CardanoStakeDistribution"
+    }
+
+    fn artifacts_route(&self) -> Option<AggregatorRoute> {
+        None
+    }
+
+    fn artifact_route(&self, _artifact_id: &str) -> Option<AggregatorRoute> {
+        None
+    }
+
+    fn required_message_parts(&self) -> &'static [ProtocolMessagePartKey] {
+        &[]
+    }
+}
+
+/// The [SignedEntity] of the full Cardano immutable files, published as snapshots.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CardanoImmutableFilesFullSignedEntity;
+
+impl SignedEntity for CardanoImmutableFilesFullSignedEntity {
+    fn name(&self) -> &'static str {
+        "CardanoImmutableFilesFull"
+    }
+
+    fn artifacts_route(&self) -> Option<AggregatorRoute> {
+        Some(AggregatorRequest::ListSnapshots.aggregator_route())
+    }
+
+    fn artifact_route(&self, artifact_id: &str) -> Option<AggregatorRoute> {
+        Some(
+            AggregatorRequest::GetSnapshot {
+                digest: artifact_id.to_string(),
+            }
+            .aggregator_route(),
+        )
+    }
+
+    fn required_message_parts(&self) -> &'static [ProtocolMessagePartKey] {
+        &[ProtocolMessagePartKey::SnapshotDigest]
+    }
+
+    fn optional_message_parts(&self) -> &'static [ProtocolMessagePartKey] {
+        &[ProtocolMessagePartKey::AncillaryDigest]
+    }
+}
+
+/// The [signed entities][SignedEntity] known by a client, by name.
+///
+/// The default registry contains the signed entities of this version of the library, more can
+/// be added with [with_signed_entity][SignedEntityRegistry::with_signed_entity].
+#[derive(Clone)]
+pub struct SignedEntityRegistry {
+    signed_entities: BTreeMap<&'static str, Arc<dyn SignedEntity>>,
+}
+
+impl SignedEntityRegistry {
+    /// Constructs a new `SignedEntityRegistry` without any signed entity.
+    pub fn empty() -> Self {
+        Self {
+            signed_entities: BTreeMap::new(),
+        }
+    }
+
+    /// Add the given signed entity, replacing any signed entity with the same name.
+    pub fn with_signed_entity(mut self, signed_entity: Arc<dyn SignedEntity>) -> Self {
+        self.signed_entities
+            .insert(signed_entity.name(), signed_entity);
+        self
+    }
+
+    /// Get the signed entity with the given name.
+    pub fn get(&self, name: &str) -> Option<Arc<dyn SignedEntity>> {
+        self.signed_entities.get(name).cloned()
+    }
+
+    /// Get the signed entity with the given name, fails if it's not registered.
+    pub fn get_or_fail(&self, name: &str) -> MithrilResult<Arc<dyn SignedEntity>> {
+        self.get(name)
+            .ok_or_else(|| anyhow!("Unknown signed entity '{name}'"))
+    }
+
+    /// Get the signed entity of the given [SignedEntityType].
+    pub fn get_for_type(
+        &self,
+        signed_entity_type: &SignedEntityType,
+    ) -> MithrilResult<Arc<dyn SignedEntity>> {
+        self.get_or_fail(&signed_entity_type.to_string())
+    }
+
+    /// Names of the registered signed entities, in alphabetical order.
+    pub fn names(&self) -> Vec<&'static str> {
+        self.signed_entities.keys().copied().collect()
+    }
+}
+
+impl Default for SignedEntityRegistry {
+    fn default() -> Self {
+        Self::empty()
+            .with_signed_entity(Arc::new(MithrilStakeDistributionSignedEntity))
+            .with_signed_entity(Arc::new(CardanoStakeDistributionSignedEntity))
+            .with_signed_entity(Arc::new(CardanoImmutableFilesFullSignedEntity))
+    }
+}
+
+impl Debug for SignedEntityRegistry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SignedEntityRegistry")
+            .field("signed_entities", &self.names())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::entities::{Beacon, Epoch};
+
+    use super::*;
+
+    struct FakeSignedEntity;
+
+    impl SignedEntity for FakeSignedEntity {
+        fn name(&self) -> &'static str {
+            "Fake"
+        }
+
+        fn artifacts_route(&self) -> Option<AggregatorRoute> {
+            Some(AggregatorRoute::new("artifact/fakes"))
+        }
+
+        fn artifact_route(&self, artifact_id: &str) -> Option<AggregatorRoute> {
+            Some(AggregatorRoute::new("artifact/fake").with_parameter(artifact_id))
+        }
+
+        fn required_message_parts(&self) -> &'static [ProtocolMessagePartKey] {
+            &[ProtocolMessagePartKey::AncillaryDigest]
+        }
+    }
+
+    #[test]
+    fn default_registry_knows_every_signed_entity_type() {
+        let registry = SignedEntityRegistry::default();
+
+        for signed_entity_type in [
+            SignedEntityType::MithrilStakeDistribution(Epoch(1)),
+            SignedEntityType::CardanoStakeDistribution(Epoch(1)),
+            SignedEntityType::CardanoImmutableFilesFull(Beacon::default()),
+        ] {
+            assert_eq!(
+                signed_entity_type.to_string(),
+                registry.get_for_type(&signed_entity_type).unwrap().name()
+            );
+        }
+        assert!(
+            registry.get_or_fail("Fake").is_err(),
+            "Fake should not be registered by default"
+        );
+    }
+
+    #[test]
+    fn register_a_new_signed_entity() {
+        let registry =
+            SignedEntityRegistry::default().with_signed_entity(Arc::new(FakeSignedEntity));
+
+        let signed_entity = registry.get("Fake").unwrap();
+
+        assert_eq!(
+            Some("artifact/fake/id".to_string()),
+            signed_entity.artifact_route("id").map(|r| r.to_string())
+        );
+        assert_eq!(4, registry.names().len());
+    }
+
+    #[test]
+    fn assemble_message_with_the_parts_signed_for_the_signed_entity() {
+        let parts = |keys: &[ProtocolMessagePartKey]| {
+            keys.iter()
+                .map(|key| (*key, format!("{key}-value")))
+                .collect::<BTreeMap<_, _>>()
+        };
+
+        let message = FakeSignedEntity
+            .assemble_protocol_message(
+                "next-avk".to_string(),
+                parts(&[ProtocolMessagePartKey::AncillaryDigest]),
+            )
+            .unwrap();
+
+        let mut expected_message = ProtocolMessage::new();
+        expected_message.set_message_part(
+            ProtocolMessagePartKey::NextAggregateVerificationKey,
+            "next-avk".to_string(),
+        );
+        expected_message.set_message_part(
+            ProtocolMessagePartKey::AncillaryDigest,
+            "ancillary_digest-value".to_string(),
+        );
+        assert_eq!(expected_message, message);
+        FakeSignedEntity
+            .assemble_protocol_message("next-avk".to_string(), parts(&[]))
+            .expect_err("a required part is missing");
+        FakeSignedEntity
+            .assemble_protocol_message(
+                "next-avk".to_string(),
+                parts(&[
+                    ProtocolMessagePartKey::AncillaryDigest,
+                    ProtocolMessagePartKey::SnapshotDigest,
+                ]),
+            )
+            .expect_err("a part not signed for the signed entity is given");
+    }
+}
@@ -3,7 +3,16 @@
 //! In order to do so it defines a [SnapshotClient] which exposes the following features:
 //!  - [get][SnapshotClient::get]: get a single snapshot data from its digest
 //!  - [list][SnapshotClient::list]: get the list of available snapshots
+//!  - [watch][SnapshotClient::watch]: stream the newly published snapshots
+//!  - [select_for_target][SnapshotClient::select_for_target]: select the snapshot closest to a
+//!    target epoch or immutable file number
+//!  - [validate][SnapshotClient::validate]: validate the locations and size of a snapshot before downloading it
 //!  - [download_unpack][SnapshotClient::download_unpack]: download and unpack the tarball of a snapshot to a directory
+//!  - [download_to_writer][SnapshotClient::download_to_writer]: download the tarball of a snapshot, without unpacking it, into any writer
+//!  - [download_unpack_staged][SnapshotClient::download_unpack_staged]: download and unpack a snapshot in a staging directory, promoted to the target directory once verified
+//!  - [check_disk_space][SnapshotClient::check_disk_space]: check that there is enough disk space to download a snapshot
+//!  - [probe_locations][SnapshotClient::probe_locations]: probe the locations of a snapshot and rank them to pick the best mirror
+//!  - [download_unpack_ancillary][SnapshotClient::download_unpack_ancillary]: download, verify and unpack the ancillary files of a snapshot
 //!
 //! # Get a single snapshot
 //!
@@ -39,6 +48,74 @@
 //! # }
 //! ```
 //!
+//! # Watch newly published snapshots
+//!
+//! To be notified of the snapshots published by the aggregator using the [ClientBuilder][crate::client::ClientBuilder].
+//!
+//! ```no_run
+//! # async fn run() -> mithril_client::MithrilResult<()> {
+//! use futures::StreamExt;
+//! use mithril_client::ClientBuilder;
+//! use std::time::Duration;
+//!
+//! let client = ClientBuilder::aggregator("YOUR_AGGREGATOR_ENDPOINT", "YOUR_GENESIS_VERIFICATION_KEY").build()?;
+//! let snapshot_client = client.snapshot();
+//! let mut snapshots = Box::pin(snapshot_client.watch(Duration::from_secs(60)));
+//!
+//! while let Some(snapshot) = snapshots.next().await {
+//!     let snapshot = snapshot?;
+//!     println!("New snapshot digest={}, size={}", snapshot.digest, snapshot.size);
+//! }
+//! #    Ok(())
+//! # }
+//! ```
+//!
+//! # Select the snapshot of a target epoch
+//!
+//! To select the latest snapshot of an epoch at most the target one, with the hash of the
+//! certificate to verify it against.
+//!
+//! ```no_run
+//! # async fn run() -> mithril_client::MithrilResult<()> {
+//! use mithril_client::common::entities::Epoch;
+//! use mithril_client::snapshot_client::SnapshotTarget;
+//! use mithril_client::ClientBuilder;
+//!
+//! let client = ClientBuilder::aggregator("YOUR_AGGREGATOR_ENDPOINT", "YOUR_GENESIS_VERIFICATION_KEY").build()?;
+//! let selected = client
+//!     .snapshot()
+//!     .select_for_target(SnapshotTarget::Epoch(Epoch(500)))
+//!     .await?
+//!     .expect("no snapshot at or before the target epoch");
+//! let certificate = client.certificate().verify_chain(&selected.certificate_hash).await?;
+//!
+//! println!("Selected snapshot digest={}, beacon={}", selected.snapshot.digest, selected.snapshot.beacon);
+//! #    Ok(())
+//! # }
+//! ```
+//!
+//! # Estimate the footprint of a snapshot
+//!
+//! To know how many immutable files a snapshot holds and how much disk space it needs once
+//! downloaded and unpacked, ie: to provision the disk of an instance before downloading it.
+//!
+//! ```no_run
+//! # async fn run() -> mithril_client::MithrilResult<()> {
+//! use mithril_client::snapshot_client::SnapshotFootprint;
+//! use mithril_client::ClientBuilder;
+//!
+//! let client = ClientBuilder::aggregator("YOUR_AGGREGATOR_ENDPOINT", "YOUR_GENESIS_VERIFICATION_KEY").build()?;
+//! let snapshot = client.snapshot().get("SNAPSHOT_DIGEST").await?.unwrap();
+//! let footprint = SnapshotFootprint::from(&snapshot);
+//!
+//! println!(
+//!     "Snapshot digest={}, immutable files={}, required disk space={} bytes",
+//!     snapshot.digest, footprint.immutable_files_count, footprint.required_disk_space
+//! );
+//! #    Ok(())
+//! # }
+//! ```
+//!
 //! # Download a snapshot
 //! **Note:** _Available on crate feature_ **fs** _only._
 //!
@@ -54,6 +131,7 @@
 //!
 //! // Note: the directory must already exist, and the user running the binary must have read/write access to it.
 //! let target_directory = Path::new("/home/user/download/");
+//! client.snapshot().check_disk_space(&snapshot, target_directory)?;
 //! client
 //!    .snapshot()
 //!    .download_unpack(&snapshot, target_directory)
@@ -62,20 +140,248 @@
 //! #    Ok(())
 //! # }
 //! ```
+//!
+//! # Extract a range of immutable files of a snapshot
+//! **Note:** _Available on crate feature_ **fs** _only._
+//!
+//! When only some immutable files are needed (ie: for analytics) the unpacking can be restricted
+//! to a range of them, each unpacked file can then be verified against reference digests.
+//!
+//! ```no_run
+//! # async fn run() -> mithril_client::MithrilResult<()> {
+//! use mithril_client::common::digesters::verify_immutable_range;
+//! use mithril_client::snapshot_downloader::UnpackOptions;
+//! use mithril_client::ClientBuilder;
+//! use std::collections::BTreeMap;
+//! use std::path::Path;
+//!
+//! let client = ClientBuilder::aggregator("YOUR_AGGREGATOR_ENDPOINT", "YOUR_GENESIS_VERIFICATION_KEY")
+//!     .with_unpack_options(UnpackOptions {
+//!         immutable_files: Some(0..=1000),
+//!         ..UnpackOptions::default()
+//!     })
+//!     .build()?;
+//! let snapshot = client.snapshot().get("SNAPSHOT_DIGEST").await?.unwrap();
+//!
+//! let target_directory = Path::new("/home/user/download/");
+//! client.snapshot().download_unpack(&snapshot, target_directory).await?;
+//! let reference_digests = BTreeMap::new();
+//! let logger = slog::Logger::root(slog::Discard, slog::o!());
+//! let files = verify_immutable_range(target_directory, 0..=1000, &reference_digests, &logger).await?;
+//! #
+//! #    Ok(())
+//! # }
+//! ```
+//!
+//! # Download a snapshot archive into a writer
+//! **Note:** _Available on crate feature_ **fs** _only._
+//!
+//! To download the tarball of a snapshot without unpacking it, into any [AsyncWrite][tokio::io::AsyncWrite]
+//! (ie: to upload it again or transform it in flight).
+//!
+//! ```no_run
+//! # async fn run() -> mithril_client::MithrilResult<()> {
+//! use mithril_client::ClientBuilder;
+//!
+//! let client = ClientBuilder::aggregator("YOUR_AGGREGATOR_ENDPOINT", "YOUR_GENESIS_VERIFICATION_KEY").build()?;
+//! let snapshot = client.snapshot().get("SNAPSHOT_DIGEST").await?.unwrap();
+//!
+//! let mut archive = tokio::fs::File::create("/home/user/download/snapshot.tar.zst").await?;
+//! let archive_digest = client
+//!    .snapshot()
+//!    .download_to_writer(&snapshot, &mut archive)
+//!    .await?;
+//!
+//! println!("Downloaded archive sha256={archive_digest}");
+//! #
+//! #    Ok(())
+//! # }
+//! ```
+//!
+//! # Download and verify a snapshot before promoting it
+//! **Note:** _Available on crate feature_ **fs** _only._
+//!
+//! To never leave a corrupt database in the target directory, the snapshot can be unpacked in a
+//! staging directory and promoted to the target directory only once its digest is verified.
+//!
+//! ```no_run
+//! # async fn run() -> mithril_client::MithrilResult<()> {
+//! use mithril_client::{ClientBuilder, MessageBuilder};
+//! use std::path::Path;
+//!
+//! let client = ClientBuilder::aggregator("YOUR_AGGREGATOR_ENDPOINT", "YOUR_GENESIS_VERIFICATION_KEY").build()?;
+//! let snapshot = client.snapshot().get("SNAPSHOT_DIGEST").await?.unwrap();
+//! let certificate = client
+//!     .certificate()
+//!     .verify_chain(&snapshot.certificate_hash)
+//!     .await?;
+//!
+//! client
+//!    .snapshot()
+//!    .download_unpack_staged(&snapshot, &certificate, &MessageBuilder::new(), Path::new("/home/user/db"))
+//!    .await?;
+//! #
+//! #    Ok(())
+//! # }
+//! ```
+//!
+//! # Download the ancillary files of a snapshot
+//! **Note:** _Available on crate feature_ **fs** _only._
+//!
+//! Some snapshots also provide an archive of ancillary files (ie: the ledger state) that allows a
+//! faster restart of the Cardano node. Their download is opt-in, and the digest of their archive
+//! is checked against the one signed in the snapshot certificate.
+//!
+//! ```no_run
+//! # async fn run() -> mithril_client::MithrilResult<()> {
+//! use mithril_client::ClientBuilder;
+//! use std::path::Path;
+//!
+//! let client = ClientBuilder::aggregator("YOUR_AGGREGATOR_ENDPOINT", "YOUR_GENESIS_VERIFICATION_KEY").build()?;
+//! let snapshot = client.snapshot().get("SNAPSHOT_DIGEST").await?.unwrap();
+//! let certificate = client
+//!     .certificate()
+//!     .verify_chain(&snapshot.certificate_hash)
+//!     .await?;
+//!
+//! let target_directory = Path::new("/home/user/download/");
+//! client
+//!    .snapshot()
+//!    .download_unpack_ancillary(&snapshot, &certificate, target_directory)
+//!    .await?;
+//! #
+//! #    Ok(())
+//! # }
+//! ```
 
 use anyhow::Context;
+use futures::Stream;
 #[cfg(feature = "fs")]
 use slog::Logger;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 use thiserror::Error;
 
 use crate::aggregator_client::{AggregatorClient, AggregatorClientError, AggregatorRequest};
 #[cfg(feature = "fs")]
+use crate::common::digesters::cache::ImmutableFileDigestCacheProvider;
+use crate::common::entities::{Beacon, CompressionAlgorithm, Epoch, ImmutableFileNumber};
+#[cfg(feature = "fs")]
+use crate::download_layout::DownloadLayout;
+#[cfg(feature = "fs")]
 use crate::feedback::FeedbackSender;
 #[cfg(feature = "fs")]
-use crate::snapshot_downloader::SnapshotDownloader;
+use crate::snapshot_downloader::{DownloadReport, SnapshotAvailabilityReport, SnapshotDownloader};
+use crate::snapshot_validation::{SnapshotValidationError, SnapshotValidationPolicy};
+use crate::utils::watch_list;
 use crate::{MithrilResult, Snapshot, SnapshotListItem};
 
+/// The beacon that a snapshot is selected for, see [SnapshotClient::select_for_target].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotTarget {
+    /// Select the latest snapshot of an epoch at most the given one
+    Epoch(Epoch),
+
+    /// Select the latest snapshot of an immutable file number at most the given one
+    ImmutableFileNumber(ImmutableFileNumber),
+}
+
+impl SnapshotTarget {
+    /// Select, among the given snapshots, the one which beacon is the closest to the target
+    /// without exceeding it.
+    pub fn select<'a>(&self, snapshots: &'a [SnapshotListItem]) -> Option<&'a SnapshotListItem> {
+        snapshots
+            .iter()
+            .filter(|snapshot| match self {
+                Self::Epoch(epoch) => snapshot.beacon.epoch <= *epoch,
+                Self::ImmutableFileNumber(immutable_file_number) => {
+                    snapshot.beacon.immutable_file_number <= *immutable_file_number
+                }
+            })
+            .max_by_key(|snapshot| (snapshot.beacon.epoch, snapshot.beacon.immutable_file_number))
+    }
+}
+
+/// A snapshot selected for a [SnapshotTarget], with the hash of the certificate to verify it
+/// against.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelectedSnapshot {
+    /// The selected snapshot
+    pub snapshot: SnapshotListItem,
+
+    /// Hash of the certificate of the snapshot
+    pub certificate_hash: String,
+}
+
+/// Number of files, chunk, primary and secondary, of each immutable file number of the Cardano
+/// database.
+const FILES_PER_IMMUTABLE_FILE_NUMBER: u64 = 3;
+
+/// Estimated footprint of a snapshot once downloaded and unpacked, derived from its beacon and
+/// archive metadata only, so it can be computed before downloading anything.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SnapshotFootprint {
+    /// Number of immutable files of the snapshot, ie: the chunk, primary and secondary files of
+    /// each immutable file number up to the one of the beacon
+    pub immutable_files_count: u64,
+
+    /// Size of the snapshot archive, in bytes
+    pub archive_size: u64,
+
+    /// Estimated size of the unpacked files, in bytes
+    pub unpacked_size: u64,
+
+    /// Estimated space required to download and unpack the snapshot, in bytes, ie: the space
+    /// checked by [SnapshotClient::check_disk_space]
+    pub required_disk_space: u64,
+}
+
+impl SnapshotFootprint {
+    /// Estimate the footprint of a snapshot from its beacon, archive size and compression
+    /// algorithm.
+    ///
+    /// The sizes are estimated with the
+    /// [ratio][crate::common::entities::CompressionAlgorithm::free_space_snapshot_ratio] of the
+    /// compression algorithm, which accounts for the archive plus the unpacked files.
+    pub fn estimate(
+        beacon: &Beacon,
+        archive_size: u64,
+        compression_algorithm: CompressionAlgorithm,
+    ) -> Self {
+        let required_disk_space =
+            (archive_size as f64 * compression_algorithm.free_space_snapshot_ratio()).ceil() as u64;
+
+        Self {
+            immutable_files_count: (beacon.immutable_file_number + 1)
+                * FILES_PER_IMMUTABLE_FILE_NUMBER,
+            archive_size,
+            unpacked_size: required_disk_space.saturating_sub(archive_size),
+            required_disk_space,
+        }
+    }
+}
+
+impl From<&Snapshot> for SnapshotFootprint {
+    fn from(snapshot: &Snapshot) -> Self {
+        Self::estimate(
+            &snapshot.beacon,
+            snapshot.size,
+            snapshot.compression_algorithm.unwrap_or_default(),
+        )
+    }
+}
+
+impl From<&SnapshotListItem> for SnapshotFootprint {
+    fn from(snapshot: &SnapshotListItem) -> Self {
+        Self::estimate(
+            &snapshot.beacon,
+            snapshot.size,
+            snapshot.compression_algorithm.unwrap_or_default(),
+        )
+    }
+}
+
 /// Error for the Snapshot client
 #[derive(Error, Debug)]
 pub enum SnapshotClientError {
@@ -88,16 +394,79 @@ pub enum SnapshotClientError {
         /// list of locations tried
         locations: String,
     },
+
+    /// Not enough space on the disk to download and unpack the snapshot.
+    #[error("There is only {available_space} bytes available in directory '{}' to download and unpack the snapshot digest '{digest}' that requires {required_space} bytes.", target_dir.display())]
+    NotEnoughSpace {
+        /// given digest
+        digest: String,
+
+        /// directory where the snapshot would be unpacked
+        target_dir: PathBuf,
+
+        /// space available on the filesystem of the directory, in bytes
+        available_space: u64,
+
+        /// estimated space required to download and unpack the snapshot, in bytes
+        required_space: u64,
+    },
+
+    /// The snapshot does not provide ancillary files.
+    #[error("The snapshot digest '{digest}' does not provide ancillary files.")]
+    NoAncillaryLocations {
+        /// given digest
+        digest: String,
+    },
+
+    /// The certificate does not certify the digest of the ancillary files.
+    #[error("The certificate '{certificate_hash}' does not certify the ancillary files of the snapshot digest '{digest}'.")]
+    AncillaryNotCertified {
+        /// given digest
+        digest: String,
+
+        /// hash of the given certificate
+        certificate_hash: String,
+    },
+
+    /// The unpacked snapshot does not match its certificate, it was moved to a quarantine
+    /// directory.
+    #[error("The snapshot digest '{digest}' does not match its certificate '{certificate_hash}', it was moved to quarantine directory '{}'.", quarantine_dir.display())]
+    Quarantined {
+        /// given digest
+        digest: String,
+
+        /// hash of the certificate of the snapshot
+        certificate_hash: String,
+
+        /// directory where the unpacked snapshot was moved
+        quarantine_dir: PathBuf,
+    },
+
+    /// The digest of the downloaded ancillary files archive does not match the certified one.
+    #[error("The digest of the ancillary files archive of the snapshot digest '{digest}' is invalid: expected '{expected_digest}', computed '{computed_digest}'.")]
+    AncillaryDigestMismatch {
+        /// given digest
+        digest: String,
+
+        /// digest of the archive signed in the certificate
+        expected_digest: String,
+
+        /// digest of the downloaded archive
+        computed_digest: String,
+    },
 }
 
 /// Aggregator client for the snapshot artifact
 pub struct SnapshotClient {
     aggregator_client: Arc<dyn AggregatorClient>,
+    validation_policy: SnapshotValidationPolicy,
     #[cfg(feature = "fs")]
     snapshot_downloader: Arc<dyn SnapshotDownloader>,
     #[cfg(feature = "fs")]
     feedback_sender: FeedbackSender,
     #[cfg(feature = "fs")]
+    immutable_digest_cache: Option<Arc<dyn ImmutableFileDigestCacheProvider>>,
+    #[cfg(feature = "fs")]
     logger: Logger,
 }
 
@@ -111,15 +480,37 @@ impl SnapshotClient {
     ) -> Self {
         Self {
             aggregator_client,
+            validation_policy: SnapshotValidationPolicy::default(),
             #[cfg(feature = "fs")]
             snapshot_downloader,
             #[cfg(feature = "fs")]
             feedback_sender,
             #[cfg(feature = "fs")]
+            immutable_digest_cache: None,
+            #[cfg(feature = "fs")]
             logger,
         }
     }
 
+    /// Set the [SnapshotValidationPolicy] that the snapshots must comply with to be downloaded.
+    pub fn with_validation_policy(mut self, validation_policy: SnapshotValidationPolicy) -> Self {
+        self.validation_policy = validation_policy;
+        self
+    }
+
+    cfg_fs! {
+    /// Store the [digests of the immutable files][DownloadReport::immutable_digests] computed
+    /// while unpacking the snapshots in the given cache, once the snapshots are verified by
+    /// [download_unpack_staged][Self::download_unpack_staged].
+    pub fn with_immutable_digest_cache(
+        mut self,
+        immutable_digest_cache: Arc<dyn ImmutableFileDigestCacheProvider>,
+    ) -> Self {
+        self.immutable_digest_cache = Some(immutable_digest_cache);
+        self
+    }
+    }
+
     /// Return a list of available snapshots
     pub async fn list(&self) -> MithrilResult<Vec<SnapshotListItem>> {
         let response = self
@@ -133,6 +524,37 @@ impl SnapshotClient {
         Ok(items)
     }
 
+    /// Select the snapshot closest to the given `target` without exceeding it among the
+    /// available snapshots, see [SnapshotTarget::select]. `None` is returned if all the snapshots
+    /// exceed the target.
+    pub async fn select_for_target(
+        &self,
+        target: SnapshotTarget,
+    ) -> MithrilResult<Option<SelectedSnapshot>> {
+        let snapshots = self.list().await?;
+
+        Ok(target.select(&snapshots).map(|snapshot| SelectedSnapshot {
+            certificate_hash: snapshot.certificate_hash.clone(),
+            snapshot: snapshot.clone(),
+        }))
+    }
+
+    /// Poll the aggregator every `interval` and stream the snapshots published since the
+    /// previous poll, oldest first.
+    ///
+    /// The snapshots already available when the first poll is done are not streamed. Listing
+    /// errors are streamed and the polling goes on.
+    pub fn watch(
+        &self,
+        interval: Duration,
+    ) -> impl Stream<Item = MithrilResult<SnapshotListItem>> + '_ {
+        watch_list(
+            interval,
+            move || self.list(),
+            |snapshot| snapshot.digest.clone(),
+        )
+    }
+
     /// Get the given snapshot data. If it cannot be found, a None is returned.
     pub async fn get(&self, digest: &str) -> MithrilResult<Option<Snapshot>> {
         match self
@@ -153,66 +575,599 @@ impl SnapshotClient {
         }
     }
 
+    /// Check that the given snapshot complies with the [SnapshotValidationPolicy] of this client.
+    ///
+    /// The snapshots are validated before being downloaded, a [SnapshotValidationError] lists all
+    /// the problems found.
+    pub fn validate(&self, snapshot: &Snapshot) -> Result<(), SnapshotValidationError> {
+        self.validation_policy.validate(snapshot)
+    }
+
     cfg_fs! {
+    /// Check that the filesystem of the given directory has enough space available to download
+    /// and unpack the given snapshot, return the estimated required space in bytes.
+    ///
+    /// The required space is the [estimated footprint][SnapshotFootprint] of the snapshot.
+    ///
+    /// If there isn't enough space a [SnapshotClientError::NotEnoughSpace] error is returned.
+    ///
+    /// **NOTE**: The directory may not exist yet, in that case the space available on its closest
+    /// existing ancestor is checked.
+    pub fn check_disk_space(
+        &self,
+        snapshot: &Snapshot,
+        target_dir: &std::path::Path,
+    ) -> MithrilResult<u64> {
+        let required_space = SnapshotFootprint::from(snapshot).required_disk_space;
+        let existing_dir = target_dir
+            .ancestors()
+            .find(|dir| dir.is_dir())
+            .with_context(|| {
+                format!(
+                    "Could not find an existing directory in the path '{}'",
+                    target_dir.display()
+                )
+            })?;
+        let available_space = fs2::available_space(existing_dir).with_context(|| {
+            format!(
+                "Could not get the available space of directory '{}'",
+                existing_dir.display()
+            )
+        })?;
+
+        if available_space < required_space {
+            return Err(SnapshotClientError::NotEnoughSpace {
+                digest: snapshot.digest.clone(),
+                target_dir: target_dir.to_path_buf(),
+                available_space,
+                required_space,
+            }
+            .into());
+        }
+
+        Ok(required_space)
+    }
+
+    /// Probe all the locations of the given snapshot with HEAD requests and rank them by
+    /// availability, announced size, range requests support and latency.
+    ///
+    /// The snapshot is downloaded from the [best][SnapshotAvailabilityReport::best] location of
+    /// this report.
+    pub async fn probe_locations(&self, snapshot: &Snapshot) -> SnapshotAvailabilityReport {
+        self.rank_locations(&snapshot.locations, snapshot.size).await
+    }
+
+    async fn rank_locations(
+        &self,
+        locations: &[String],
+        expected_size: u64,
+    ) -> SnapshotAvailabilityReport {
+        let probes = locations
+            .iter()
+            .map(|location| self.snapshot_downloader.probe_location(location));
+
+        SnapshotAvailabilityReport::new(futures::future::join_all(probes).await, expected_size)
+    }
+
     /// Download and unpack the given snapshot to the given directory
     ///
+    /// The snapshot is downloaded from its best [available location][Self::probe_locations], if
+    /// that location fails during the download the rest of the archive is downloaded from the
+    /// other available locations.
+    ///
     /// **NOTE**: The directory should already exist, and the user running the binary
-    /// must have read/write access to it.
+    /// must have read/write access to it. Use [check_disk_space][SnapshotClient::check_disk_space]
+    /// beforehand to make sure that the snapshot fits on the disk.
     pub async fn download_unpack(
         &self,
         snapshot: &Snapshot,
         target_dir: &std::path::Path,
     ) -> MithrilResult<()> {
+        self.download_unpack_with_report(snapshot, target_dir)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Download and unpack the given snapshot to the given directory, like
+    /// [download_unpack][Self::download_unpack], and return a [DownloadReport] with the digest of
+    /// the archive and the time spent in each stage of the download.
+    ///
+    /// The archive is hashed while it's streamed to the unpacker through bounded buffers: the
+    /// memory used doesn't depend on the size of the snapshot.
+    pub async fn download_unpack_with_report(
+        &self,
+        snapshot: &Snapshot,
+        target_dir: &std::path::Path,
+    ) -> MithrilResult<DownloadReport> {
         use crate::feedback::MithrilEvent;
 
-        for location in snapshot.locations.as_slice() {
-            if self.snapshot_downloader.probe(location).await.is_ok() {
-                let download_id = MithrilEvent::new_snapshot_download_id();
-                self.feedback_sender
-                    .send_event(MithrilEvent::SnapshotDownloadStarted {
-                        digest: snapshot.digest.clone(),
-                        download_id: download_id.clone(),
-                        size: snapshot.size,
-                    })
-                    .await;
-                return match self
-                    .snapshot_downloader
-                    .download_unpack(
-                        location,
-                        target_dir,
-                        snapshot.compression_algorithm.unwrap_or_default(),
-                        &download_id,
-                        snapshot.size,
-                    )
-                    .await
-                {
-                    Ok(()) => {
-                        // todo: add snapshot statistics to cli (it was previously done here)
-                        // note: the snapshot download does not fail if the statistic call fails.
-                        self.feedback_sender
-                            .send_event(MithrilEvent::SnapshotDownloadCompleted { download_id })
-                            .await;
-                        Ok(())
-                    }
-                    Err(e) => {
+        self.validate(snapshot)?;
+        let report = self.probe_locations(snapshot).await;
+        let locations = report.available_locations();
+        if !locations.is_empty() {
+            let download_id = MithrilEvent::new_snapshot_download_id();
+            self.feedback_sender
+                .send_event(MithrilEvent::SnapshotDownloadStarted {
+                    digest: snapshot.digest.clone(),
+                    download_id: download_id.clone(),
+                    size: snapshot.size,
+                })
+                .await;
+            return match self
+                .snapshot_downloader
+                .download_unpack_with_report(
+                    &locations,
+                    target_dir,
+                    snapshot.compression_algorithm.unwrap_or_default(),
+                    &download_id,
+                    snapshot.size,
+                )
+                .await
+            {
+                Ok(download_report) => {
+                    // todo: add snapshot statistics to cli (it was previously done here)
+                    // note: the snapshot download does not fail if the statistic call fails.
+                    self.feedback_sender
+                        .send_event(MithrilEvent::SnapshotDownloadCompleted { download_id })
+                        .await;
+                    Ok(download_report)
+                }
+                Err(e) => {
+                    slog::warn!(
+                        self.logger,
+                        "Failed downloading snapshot from '{}' Error: {e}.",
+                        locations.join(", ")
+                    );
+                    Err(e)
+                }
+            };
+        }
+
+        let locations = snapshot.locations.join(", ");
+
+        Err(SnapshotClientError::NoWorkingLocation {
+            digest: snapshot.digest.clone(),
+            locations,
+        }
+        .into())
+    }
+
+    /// Download the archive of the given snapshot, without unpacking it, into the given writer
+    /// (ie: a pipe, an object storage uploader or a hashing sink) and return the hex encoded
+    /// SHA256 digest of the archive.
+    ///
+    /// The writer is flushed once the archive is downloaded but it's not shut down.
+    pub async fn download_to_writer<W: tokio::io::AsyncWrite + Send + Unpin + 'static>(
+        &self,
+        snapshot: &Snapshot,
+        writer: &mut W,
+    ) -> MithrilResult<String> {
+        use crate::feedback::MithrilEvent;
+
+        self.validate(snapshot)?;
+        let report = self.probe_locations(snapshot).await;
+        let location = report.best().map(|probe| &probe.location).ok_or_else(|| {
+            SnapshotClientError::NoWorkingLocation {
+                digest: snapshot.digest.clone(),
+                locations: snapshot.locations.join(", "),
+            }
+        })?;
+        let download_id = MithrilEvent::new_snapshot_download_id();
+        self.feedback_sender
+            .send_event(MithrilEvent::SnapshotDownloadStarted {
+                digest: snapshot.digest.clone(),
+                download_id: download_id.clone(),
+                size: snapshot.size,
+            })
+            .await;
+        let archive_digest = self
+            .snapshot_downloader
+            .download_to_writer(location, writer, &download_id, snapshot.size)
+            .await
+            .map_err(|e| {
+                slog::warn!(
+                    self.logger,
+                    "Failed downloading snapshot from '{location}' Error: {e}."
+                );
+                e
+            })?;
+        self.feedback_sender
+            .send_event(MithrilEvent::SnapshotDownloadCompleted { download_id })
+            .await;
+
+        Ok(archive_digest)
+    }
+
+    /// Download and unpack the given snapshot in a staging directory, then atomically promote it
+    /// to the given directory only once its digest has been verified against the given
+    /// certificate, that should have been
+    /// [verified][crate::certificate_client::CertificateClient::verify_chain] beforehand.
+    ///
+    /// The staging directory is created next to `target_dir`, so they usually share the same
+    /// filesystem, and `target_dir` must either not exist or be empty. Staging directories left by
+    /// interrupted attempts are removed first. If `target_dir` is on another filesystem (ie: a
+    /// mount point) the snapshot is [moved][crate::download_layout::DownloadLayout::move_path] by
+    /// a synced copy.
+    ///
+    /// The verified snapshot must match the [Cardano db layout][crate::download_layout::DownloadLayout::cardano_db]
+    /// to be promoted.
+    ///
+    /// If the verification fails the unpacked snapshot is moved to a `.<target_dir name>.quarantine`
+    /// directory next to `target_dir`, replacing the one of a previous failure, and a
+    /// [SnapshotClientError::Quarantined] error is returned. The quarantine directory is removed
+    /// once a snapshot is promoted.
+    ///
+    /// The [digests of the immutable files][DownloadReport::immutable_digests] computed while
+    /// unpacking are only stored in the [immutable digest cache][Self::with_immutable_digest_cache]
+    /// once the snapshot is promoted.
+    pub async fn download_unpack_staged(
+        &self,
+        snapshot: &Snapshot,
+        certificate: &crate::MithrilCertificate,
+        message_builder: &crate::MessageBuilder,
+        target_dir: &std::path::Path,
+    ) -> MithrilResult<()> {
+        self.validate(snapshot)?;
+        if certificate.hash != snapshot.certificate_hash {
+            return Err(anyhow::anyhow!(
+                "The certificate '{}' is not the certificate '{}' of the snapshot digest '{}'",
+                certificate.hash,
+                snapshot.certificate_hash,
+                snapshot.digest
+            ));
+        }
+        if target_dir.exists()
+            && std::fs::read_dir(target_dir)
+                .with_context(|| format!("Could not read directory '{}'", target_dir.display()))?
+                .next()
+                .is_some()
+        {
+            return Err(anyhow::anyhow!(
+                "The directory '{}' must not exist or be empty to promote a staged snapshot",
+                target_dir.display()
+            ));
+        }
+        let target_name = target_dir
+            .file_name()
+            .with_context(|| format!("Invalid target directory '{}'", target_dir.display()))?
+            .to_string_lossy();
+        let parent_dir = match target_dir.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => std::path::Path::new("."),
+        };
+        let staging_prefix = format!(".{target_name}.staging-");
+        let quarantine_dir = parent_dir.join(format!(".{target_name}.quarantine"));
+
+        std::fs::create_dir_all(parent_dir)
+            .with_context(|| format!("Could not create directory '{}'", parent_dir.display()))?;
+        for entry in std::fs::read_dir(parent_dir)
+            .with_context(|| format!("Could not read directory '{}'", parent_dir.display()))?
+        {
+            let entry = entry?;
+            if entry.file_name().to_string_lossy().starts_with(&staging_prefix) {
+                std::fs::remove_dir_all(entry.path()).with_context(|| {
+                    format!("Could not remove directory '{}'", entry.path().display())
+                })?;
+            }
+        }
+        let staging_dir = parent_dir.join(format!("{staging_prefix}{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir(&staging_dir)
+            .with_context(|| format!("Could not create directory '{}'", staging_dir.display()))?;
+
+        let verification = async {
+            let download_report = self
+                .download_unpack_with_report(snapshot, &staging_dir)
+                .await?;
+            let message = message_builder
+                .compute_snapshot_message(certificate, &staging_dir)
+                .await?;
+            Ok::<_, anyhow::Error>((
+                certificate.match_message(&message),
+                download_report.immutable_digests,
+            ))
+        }
+        .await;
+
+        match verification {
+            Ok((true, immutable_digests)) => {
+                if let Err(e) = DownloadLayout::cardano_db().verify(&staging_dir) {
+                    let _ = std::fs::remove_dir_all(&staging_dir);
+                    return Err(e.into());
+                }
+                if target_dir.exists() {
+                    std::fs::remove_dir(target_dir).with_context(|| {
+                        format!("Could not remove directory '{}'", target_dir.display())
+                    })?;
+                }
+                DownloadLayout::move_path(&staging_dir, target_dir)?;
+                if quarantine_dir.exists() {
+                    let _ = std::fs::remove_dir_all(&quarantine_dir);
+                }
+                if let Some(immutable_digest_cache) = &self.immutable_digest_cache {
+                    if let Err(error) = immutable_digest_cache.store(immutable_digests).await {
                         slog::warn!(
                             self.logger,
-                            "Failed downloading snapshot from '{location}' Error: {e}."
+                            "Error while storing the unpacked immutable files digests to cache: {error}"
                         );
-                        Err(e)
                     }
-                };
+                }
+                Ok(())
+            }
+            Ok((false, _)) => {
+                slog::warn!(
+                    self.logger,
+                    "Snapshot digest '{}' does not match its certificate, moving it to quarantine directory '{}'",
+                    snapshot.digest,
+                    quarantine_dir.display()
+                );
+                if quarantine_dir.exists() {
+                    std::fs::remove_dir_all(&quarantine_dir).with_context(|| {
+                        format!("Could not remove directory '{}'", quarantine_dir.display())
+                    })?;
+                }
+                DownloadLayout::move_path(&staging_dir, &quarantine_dir)?;
+                Err(SnapshotClientError::Quarantined {
+                    digest: snapshot.digest.clone(),
+                    certificate_hash: certificate.hash.clone(),
+                    quarantine_dir,
+                }
+                .into())
+            }
+            Err(e) => {
+                let _ = std::fs::remove_dir_all(&staging_dir);
+                Err(e)
             }
         }
+    }
 
-        let locations = snapshot.locations.join(", ");
+    /// Download, verify and unpack the ancillary files (ie: the ledger state) of the given
+    /// snapshot to the given directory.
+    ///
+    /// The SHA256 digest of the downloaded archive must match the
+    /// [ancillary digest][crate::common::entities::ProtocolMessagePartKey::AncillaryDigest]
+    /// signed in the given certificate, that must be the certificate of the snapshot and should
+    /// have been [verified][crate::certificate_client::CertificateClient::verify_chain] beforehand.
+    ///
+    /// The archive is downloaded in a temporary directory inside `target_dir` and only unpacked
+    /// once its digest has been verified, using the
+    /// [ancillary compression algorithm][Snapshot::ancillary_compression_algorithm]. The unpacked
+    /// content is then moved to `target_dir`, replacing existing entries with the same names.
+    pub async fn download_unpack_ancillary(
+        &self,
+        snapshot: &Snapshot,
+        certificate: &crate::MithrilCertificate,
+        target_dir: &std::path::Path,
+    ) -> MithrilResult<()> {
+        use crate::common::entities::ProtocolMessagePartKey;
+        use crate::feedback::MithrilEvent;
+        use crate::snapshot_downloader::{unpack_archive_file, UnpackOptions};
+
+        let ancillary_locations = snapshot
+            .ancillary_locations
+            .as_ref()
+            .filter(|locations| !locations.is_empty())
+            .ok_or_else(|| SnapshotClientError::NoAncillaryLocations {
+                digest: snapshot.digest.clone(),
+            })?;
+        self.validate(snapshot)?;
+        if certificate.hash != snapshot.certificate_hash {
+            return Err(anyhow::anyhow!(
+                "The certificate '{}' is not the certificate '{}' of the snapshot digest '{}'",
+                certificate.hash,
+                snapshot.certificate_hash,
+                snapshot.digest
+            ));
+        }
+        let expected_digest = certificate
+            .protocol_message
+            .get_message_part(&ProtocolMessagePartKey::AncillaryDigest)
+            .ok_or_else(|| SnapshotClientError::AncillaryNotCertified {
+                digest: snapshot.digest.clone(),
+                certificate_hash: certificate.hash.clone(),
+            })?;
+
+        let report = self
+            .rank_locations(ancillary_locations, snapshot.ancillary_size.unwrap_or_default())
+            .await;
+        if let Some(location) = report.best().map(|probe| &probe.location) {
+            let staging_dir = target_dir.join(".ancillary_unpack");
+            if staging_dir.exists() {
+                std::fs::remove_dir_all(&staging_dir).with_context(|| {
+                    format!("Could not remove directory '{}'", staging_dir.display())
+                })?;
+            }
+            let unpack_dir = staging_dir.join("content");
+            std::fs::create_dir_all(&unpack_dir).with_context(|| {
+                format!("Could not create directory '{}'", unpack_dir.display())
+            })?;
+
+            let download_id = MithrilEvent::new_snapshot_download_id();
+            let size = snapshot.ancillary_size.unwrap_or_default();
+            self.feedback_sender
+                .send_event(MithrilEvent::SnapshotDownloadStarted {
+                    digest: snapshot.digest.clone(),
+                    download_id: download_id.clone(),
+                    size,
+                })
+                .await;
+            // The archive is only unpacked once its digest is verified.
+            let archive_path = staging_dir.join("ancillary.archive");
+            let result: MithrilResult<()> = async {
+                let mut archive_file = tokio::fs::File::create(&archive_path)
+                    .await
+                    .with_context(|| {
+                        format!("Could not create file '{}'", archive_path.display())
+                    })?;
+                let computed_digest = self
+                    .snapshot_downloader
+                    .download_to_writer(location, &mut archive_file, &download_id, size)
+                    .await?;
+                drop(archive_file);
+                if &computed_digest != expected_digest {
+                    return Err(SnapshotClientError::AncillaryDigestMismatch {
+                        digest: snapshot.digest.clone(),
+                        expected_digest: expected_digest.clone(),
+                        computed_digest,
+                    }
+                    .into());
+                }
+                unpack_archive_file(
+                    &archive_path,
+                    snapshot.ancillary_compression_algorithm.unwrap_or_default(),
+                    &unpack_dir,
+                    UnpackOptions::default(),
+                )
+                .await?;
+
+                Self::move_dir_content(&unpack_dir, target_dir)
+            }
+            .await;
+            let _ = std::fs::remove_dir_all(&staging_dir);
+
+            return match result {
+                Ok(()) => {
+                    self.feedback_sender
+                        .send_event(MithrilEvent::SnapshotDownloadCompleted { download_id })
+                        .await;
+                    Ok(())
+                }
+                Err(e) => {
+                    slog::warn!(
+                        self.logger,
+                        "Failed downloading ancillary files from '{location}' Error: {e}."
+                    );
+                    Err(e)
+                }
+            };
+        }
 
         Err(SnapshotClientError::NoWorkingLocation {
             digest: snapshot.digest.clone(),
-            locations,
+            locations: ancillary_locations.join(", "),
         }
         .into())
     }
+
+    fn move_dir_content(
+        source_dir: &std::path::Path,
+        target_dir: &std::path::Path,
+    ) -> MithrilResult<()> {
+        let entries = std::fs::read_dir(source_dir)
+            .with_context(|| format!("Could not read directory '{}'", source_dir.display()))?;
+        for entry in entries {
+            let entry = entry?;
+            let target = target_dir.join(entry.file_name());
+            if target.is_dir() {
+                std::fs::remove_dir_all(&target)
+            } else if target.exists() {
+                std::fs::remove_file(&target)
+            } else {
+                Ok(())
+            }
+            .with_context(|| format!("Could not replace '{}'", target.display()))?;
+            DownloadLayout::move_path(&entry.path(), &target)?;
+        }
+
+        Ok(())
+    }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::aggregator_client::MockAggregatorHTTPClient;
+
+    use super::*;
+
+    fn snapshot(epoch: u64, immutable_file_number: u64) -> SnapshotListItem {
+        SnapshotListItem {
+            digest: format!("digest-{epoch}-{immutable_file_number}"),
+            certificate_hash: format!("certificate-{epoch}-{immutable_file_number}"),
+            beacon: Beacon::new("testnet".to_string(), epoch, immutable_file_number),
+            ..SnapshotListItem::dummy()
+        }
+    }
+
+    #[test]
+    fn select_the_closest_snapshot_not_exceeding_the_target() {
+        let snapshots = vec![
+            snapshot(12, 130),
+            snapshot(11, 120),
+            snapshot(11, 115),
+            snapshot(10, 100),
+        ];
+
+        assert_eq!(
+            Some(&snapshots[1]),
+            SnapshotTarget::Epoch(Epoch(11)).select(&snapshots)
+        );
+        assert_eq!(
+            Some(&snapshots[2]),
+            SnapshotTarget::ImmutableFileNumber(119).select(&snapshots)
+        );
+        assert_eq!(
+            Some(&snapshots[0]),
+            SnapshotTarget::Epoch(Epoch(20)).select(&snapshots)
+        );
+        assert_eq!(
+            None,
+            SnapshotTarget::ImmutableFileNumber(99).select(&snapshots)
+        );
+    }
+
+    #[test]
+    fn estimate_the_footprint_of_a_snapshot_from_its_metadata() {
+        let snapshot = SnapshotListItem {
+            size: 100,
+            compression_algorithm: Some(CompressionAlgorithm::Zstandard),
+            ..snapshot(10, 99)
+        };
+
+        assert_eq!(
+            SnapshotFootprint {
+                immutable_files_count: 300,
+                archive_size: 100,
+                unpacked_size: 300,
+                required_disk_space: 400,
+            },
+            SnapshotFootprint::from(&snapshot)
+        );
+        assert_eq!(
+            250,
+            SnapshotFootprint::estimate(&snapshot.beacon, 100, CompressionAlgorithm::Gzip)
+                .required_disk_space
+        );
+    }
+
+    #[tokio::test]
+    async fn select_for_target_returns_the_certificate_hash_of_the_snapshot() {
+        let mut aggregator_client = MockAggregatorHTTPClient::new();
+        aggregator_client.expect_get_content().returning(|_| {
+            Ok(serde_json::to_string(&vec![snapshot(11, 120), snapshot(10, 100)]).unwrap())
+        });
+        let client = SnapshotClient::new(
+            Arc::new(aggregator_client),
+            #[cfg(feature = "fs")]
+            Arc::new(crate::snapshot_downloader::MockHttpSnapshotDownloader::new()),
+            #[cfg(feature = "fs")]
+            FeedbackSender::new(&[]),
+            #[cfg(feature = "fs")]
+            crate::test_utils::test_logger(),
+        );
+
+        let selected = client
+            .select_for_target(SnapshotTarget::Epoch(Epoch(10)))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            Some(SelectedSnapshot {
+                snapshot: snapshot(10, 100),
+                certificate_hash: "certificate-10-100".to_string(),
+            }),
+            selected
+        );
     }
 }
 
@@ -220,21 +1175,49 @@ impl SnapshotClient {
 mod tests_download {
     use crate::{
         aggregator_client::MockAggregatorHTTPClient,
+        common::{
+            digesters::{cache::MemoryImmutableFileDigestCacheProvider, ImmutableFile},
+            entities::{Beacon, CompressionAlgorithm, ProtocolMessage, ProtocolMessagePartKey},
+            messages::CertificateMetadataMessagePart,
+        },
         feedback::{MithrilEvent, StackFeedbackReceiver},
-        snapshot_downloader::MockHttpSnapshotDownloader,
-        test_utils,
+        snapshot_downloader::{MockHttpSnapshotDownloader, SnapshotLocationProbe},
+        test_utils, MithrilCertificate,
     };
+    use sha2::Digest;
+    use std::fs;
     use std::path::Path;
 
     use super::*;
 
+    fn available_location(location: &str) -> SnapshotLocationProbe {
+        SnapshotLocationProbe {
+            location: location.to_string(),
+            error: None,
+            size: None,
+            supports_range: false,
+            latency: Some(std::time::Duration::from_millis(1)),
+        }
+    }
+
+    fn download_report() -> DownloadReport {
+        DownloadReport {
+            archive_digest: "archive_digest".to_string(),
+            archive_size: 0,
+            timings: Default::default(),
+            immutable_digests: vec![],
+        }
+    }
+
     #[tokio::test]
     async fn download_unpack_send_feedbacks() {
         let mut snapshot_downloader = MockHttpSnapshotDownloader::new();
-        snapshot_downloader.expect_probe().returning(|_| Ok(()));
         snapshot_downloader
-            .expect_download_unpack()
-            .returning(|_, _, _, _, _| Ok(()));
+            .expect_probe_location()
+            .returning(available_location);
+        snapshot_downloader
+            .expect_download_unpack_with_report()
+            .returning(|_, _, _, _, _| Ok(download_report()));
         let feedback_receiver = Arc::new(StackFeedbackReceiver::new());
         let client = SnapshotClient::new(
             Arc::new(MockAggregatorHTTPClient::new()),
@@ -264,4 +1247,513 @@ mod tests_download {
 
         assert_eq!(actual, expected);
     }
+
+    #[tokio::test]
+    async fn download_unpack_from_the_best_location() {
+        let mut snapshot_downloader = MockHttpSnapshotDownloader::new();
+        snapshot_downloader
+            .expect_probe_location()
+            .returning(|location| match location {
+                "unavailable" => SnapshotLocationProbe::failure(location, anyhow::anyhow!("404")),
+                "slow" => SnapshotLocationProbe {
+                    latency: Some(std::time::Duration::from_secs(2)),
+                    ..available_location(location)
+                },
+                _ => available_location(location),
+            });
+        snapshot_downloader
+            .expect_download_unpack_with_report()
+            .withf(|locations, _, _, _, _| locations.iter().eq(["fast", "slow"]))
+            .returning(|_, _, _, _, _| Ok(download_report()))
+            .once();
+        let client = SnapshotClient::new(
+            Arc::new(MockAggregatorHTTPClient::new()),
+            Arc::new(snapshot_downloader),
+            FeedbackSender::new(&[]),
+            test_utils::test_logger(),
+        );
+        let snapshot = Snapshot {
+            locations: vec![
+                "unavailable".to_string(),
+                "slow".to_string(),
+                "fast".to_string(),
+            ],
+            ..Snapshot::dummy()
+        };
+
+        let report = client.probe_locations(&snapshot).await;
+        assert_eq!(
+            vec!["fast", "slow", "unavailable"],
+            report
+                .probes
+                .iter()
+                .map(|probe| probe.location.as_str())
+                .collect::<Vec<_>>()
+        );
+
+        client
+            .download_unpack(&snapshot, Path::new(""))
+            .await
+            .expect("download should succeed");
+    }
+
+    #[tokio::test]
+    async fn download_unpack_fails_if_no_location_is_available() {
+        let mut snapshot_downloader = MockHttpSnapshotDownloader::new();
+        snapshot_downloader
+            .expect_probe_location()
+            .returning(|location| SnapshotLocationProbe::failure(location, anyhow::anyhow!("404")));
+        let client = SnapshotClient::new(
+            Arc::new(MockAggregatorHTTPClient::new()),
+            Arc::new(snapshot_downloader),
+            FeedbackSender::new(&[]),
+            test_utils::test_logger(),
+        );
+
+        let error = client
+            .download_unpack(&Snapshot::dummy(), Path::new(""))
+            .await
+            .expect_err("download should fail");
+
+        assert!(
+            matches!(
+                error.downcast_ref::<SnapshotClientError>(),
+                Some(SnapshotClientError::NoWorkingLocation { .. })
+            ),
+            "Unexpected error: {error:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn download_unpack_fails_without_probing_locations_if_snapshot_is_invalid() {
+        let client = snapshot_client()
+            .with_validation_policy(SnapshotValidationPolicy::default().with_https_only(true));
+        let snapshot = Snapshot {
+            locations: vec!["http://host/snapshot.tar.gz".to_string()],
+            ..Snapshot::dummy()
+        };
+
+        let error = client
+            .download_unpack(&snapshot, Path::new(""))
+            .await
+            .expect_err("download should fail");
+
+        assert!(
+            error.downcast_ref::<SnapshotValidationError>().is_some(),
+            "Unexpected error: {error:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn download_to_writer_from_the_best_location_send_feedbacks() {
+        let mut snapshot_downloader = MockHttpSnapshotDownloader::new();
+        snapshot_downloader
+            .expect_probe_location()
+            .returning(|location| match location {
+                "unavailable" => SnapshotLocationProbe::failure(location, anyhow::anyhow!("404")),
+                _ => available_location(location),
+            });
+        snapshot_downloader
+            .expect_download_to_writer()
+            .withf(|location, _, _, _| location == "available")
+            .returning(|_, _, _, _| Ok("archive_digest".to_string()))
+            .once();
+        let feedback_receiver = Arc::new(StackFeedbackReceiver::new());
+        let client = SnapshotClient::new(
+            Arc::new(MockAggregatorHTTPClient::new()),
+            Arc::new(snapshot_downloader),
+            FeedbackSender::new(&[feedback_receiver.clone()]),
+            test_utils::test_logger(),
+        );
+        let snapshot = Snapshot {
+            locations: vec!["unavailable".to_string(), "available".to_string()],
+            ..Snapshot::dummy()
+        };
+
+        let archive_digest = client
+            .download_to_writer(&snapshot, &mut Vec::new())
+            .await
+            .expect("download should succeed");
+
+        assert_eq!("archive_digest", archive_digest);
+        let actual = feedback_receiver.stacked_events();
+        let id = actual[0].event_id();
+        assert_eq!(
+            vec![
+                MithrilEvent::SnapshotDownloadStarted {
+                    digest: snapshot.digest,
+                    download_id: id.to_string(),
+                    size: snapshot.size,
+                },
+                MithrilEvent::SnapshotDownloadCompleted {
+                    download_id: id.to_string(),
+                },
+            ],
+            actual
+        );
+    }
+
+    fn snapshot_client() -> SnapshotClient {
+        SnapshotClient::new(
+            Arc::new(MockAggregatorHTTPClient::new()),
+            Arc::new(MockHttpSnapshotDownloader::new()),
+            FeedbackSender::new(&[]),
+            test_utils::test_logger(),
+        )
+    }
+
+    #[test]
+    fn check_disk_space_return_required_space_if_enough_space_is_available() {
+        let snapshot = Snapshot {
+            size: 10,
+            compression_algorithm: Some(CompressionAlgorithm::Zstandard),
+            ..Snapshot::dummy()
+        };
+
+        let required_space = snapshot_client()
+            .check_disk_space(&snapshot, &std::env::temp_dir().join("not_yet_created"))
+            .expect("check_disk_space should not fail");
+
+        assert_eq!(40, required_space);
+    }
+
+    #[test]
+    fn check_disk_space_fails_if_not_enough_space_is_available() {
+        let snapshot = Snapshot {
+            size: u64::MAX / 4,
+            compression_algorithm: Some(CompressionAlgorithm::Gzip),
+            ..Snapshot::dummy()
+        };
+
+        let error = snapshot_client()
+            .check_disk_space(&snapshot, &std::env::temp_dir())
+            .expect_err("check_disk_space should fail");
+
+        assert!(
+            matches!(
+                error.downcast_ref::<SnapshotClientError>(),
+                Some(SnapshotClientError::NotEnoughSpace { .. })
+            ),
+            "Unexpected error: {error:?}"
+        );
+    }
+
+    fn snapshot_certificate(
+        snapshot: &Snapshot,
+        ancillary_digest: Option<&str>,
+    ) -> MithrilCertificate {
+        let mut protocol_message = ProtocolMessage::new();
+        protocol_message.set_message_part(
+            ProtocolMessagePartKey::SnapshotDigest,
+            snapshot.digest.clone(),
+        );
+        if let Some(digest) = ancillary_digest {
+            protocol_message
+                .set_message_part(ProtocolMessagePartKey::AncillaryDigest, digest.to_string());
+        }
+
+        MithrilCertificate {
+            hash: snapshot.certificate_hash.clone(),
+            previous_hash: "previous_hash".to_string(),
+            beacon: Beacon::default(),
+            metadata: CertificateMetadataMessagePart::dummy(),
+            protocol_message,
+            signed_message: "signed_message".to_string(),
+            aggregate_verification_key: "avk".to_string(),
+            multi_signature: "multi_signature".to_string(),
+            genesis_signature: String::new(),
+        }
+    }
+
+    /// Snapshot client which downloader serves an ancillary archive of a `ledger` file.
+    fn snapshot_client_with_ancillary_archive() -> (SnapshotClient, String) {
+        let archive = test_utils::gzip_archive(&[("ledger", "new ledger")]);
+        let archive_digest = hex::encode(sha2::Sha256::digest(&archive));
+        let snapshot_client = SnapshotClient::new(
+            Arc::new(MockAggregatorHTTPClient::new()),
+            Arc::new(test_utils::ArchiveSnapshotDownloader {
+                archive,
+                failing_locations: vec![],
+            }),
+            FeedbackSender::new(&[]),
+            test_utils::test_logger(),
+        );
+
+        (snapshot_client, archive_digest)
+    }
+
+    fn snapshot_with_gzip_ancillary() -> Snapshot {
+        Snapshot {
+            ancillary_compression_algorithm: Some(CompressionAlgorithm::Gzip),
+            ..Snapshot::dummy()
+        }
+    }
+
+    #[tokio::test]
+    async fn download_unpack_ancillary_fails_without_ancillary_locations() {
+        let snapshot = Snapshot {
+            ancillary_locations: None,
+            ..Snapshot::dummy()
+        };
+        let certificate = snapshot_certificate(&snapshot, Some("digest"));
+
+        let error = snapshot_client()
+            .download_unpack_ancillary(&snapshot, &certificate, Path::new(""))
+            .await
+            .expect_err("download_unpack_ancillary should fail");
+
+        assert!(
+            matches!(
+                error.downcast_ref::<SnapshotClientError>(),
+                Some(SnapshotClientError::NoAncillaryLocations { .. })
+            ),
+            "Unexpected error: {error:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn download_unpack_ancillary_fails_if_the_certificate_does_not_certify_it() {
+        let snapshot = Snapshot::dummy();
+        let certificate = snapshot_certificate(&snapshot, None);
+
+        let error = snapshot_client()
+            .download_unpack_ancillary(&snapshot, &certificate, Path::new(""))
+            .await
+            .expect_err("download_unpack_ancillary should fail");
+
+        assert!(
+            matches!(
+                error.downcast_ref::<SnapshotClientError>(),
+                Some(SnapshotClientError::AncillaryNotCertified { .. })
+            ),
+            "Unexpected error: {error:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn download_unpack_ancillary_fails_and_keep_target_dir_untouched_if_digest_mismatch() {
+        let target_dir =
+            test_utils::get_test_directory("snapshot_client", "ancillary_digest_mismatch");
+        fs::write(target_dir.join("ledger"), "old ledger").unwrap();
+        let snapshot = snapshot_with_gzip_ancillary();
+        let certificate = snapshot_certificate(&snapshot, Some("certified_digest"));
+        let (snapshot_client, _) = snapshot_client_with_ancillary_archive();
+
+        let error = snapshot_client
+            .download_unpack_ancillary(&snapshot, &certificate, &target_dir)
+            .await
+            .expect_err("download_unpack_ancillary should fail");
+
+        assert!(
+            matches!(
+                error.downcast_ref::<SnapshotClientError>(),
+                Some(SnapshotClientError::AncillaryDigestMismatch { .. })
+            ),
+            "Unexpected error: {error:?}"
+        );
+        assert_eq!(
+            "old ledger",
+            fs::read_to_string(target_dir.join("ledger")).unwrap()
+        );
+        assert!(!target_dir.join(".ancillary_unpack").exists());
+    }
+
+    #[tokio::test]
+    async fn download_unpack_ancillary_move_verified_files_to_target_dir() {
+        let target_dir = test_utils::get_test_directory("snapshot_client", "ancillary_verified");
+        fs::write(target_dir.join("ledger"), "old ledger").unwrap();
+        let snapshot = snapshot_with_gzip_ancillary();
+        let (snapshot_client, archive_digest) = snapshot_client_with_ancillary_archive();
+        let certificate = snapshot_certificate(&snapshot, Some(&archive_digest));
+
+        snapshot_client
+            .download_unpack_ancillary(&snapshot, &certificate, &target_dir)
+            .await
+            .expect("download_unpack_ancillary should not fail");
+
+        assert_eq!(
+            "new ledger",
+            fs::read_to_string(target_dir.join("ledger")).unwrap()
+        );
+        assert!(!target_dir.join(".ancillary_unpack").exists());
+    }
+
+    /// Snapshot client which downloader "unpacks" an `immutable` file.
+    fn snapshot_client_with_snapshot_downloader() -> SnapshotClient {
+        let mut snapshot_downloader = MockHttpSnapshotDownloader::new();
+        snapshot_downloader
+            .expect_probe_location()
+            .returning(available_location);
+        snapshot_downloader
+            .expect_download_unpack_with_report()
+            .returning(|_, dir, _, _, _| {
+                fs::create_dir_all(dir.join("immutable")).unwrap();
+                fs::write(dir.join("immutable").join("00000.chunk"), "chunk").unwrap();
+                Ok(DownloadReport {
+                    immutable_digests: vec![(
+                        "00000.chunk".to_string(),
+                        "chunk_digest".to_string(),
+                    )],
+                    ..download_report()
+                })
+            });
+
+        SnapshotClient::new(
+            Arc::new(MockAggregatorHTTPClient::new()),
+            Arc::new(snapshot_downloader),
+            FeedbackSender::new(&[]),
+            test_utils::test_logger(),
+        )
+    }
+
+    fn signed_snapshot_certificate(snapshot: &Snapshot) -> MithrilCertificate {
+        let mut certificate = snapshot_certificate(snapshot, None);
+        certificate.signed_message = certificate.protocol_message.compute_hash();
+        certificate
+    }
+
+    async fn cached_chunk_digest(cache: &MemoryImmutableFileDigestCacheProvider) -> Option<String> {
+        let chunk =
+            ImmutableFile::dummy(PathBuf::from("00000.chunk"), 0, "00000.chunk".to_string());
+        cache.get(vec![chunk.clone()]).await.unwrap()[&chunk].clone()
+    }
+
+    fn message_builder_computing(digest: &str) -> crate::MessageBuilder {
+        crate::MessageBuilder::new().with_immutable_digester(Arc::new(
+            crate::common::digesters::DumbImmutableDigester::new(digest, true),
+        ))
+    }
+
+    #[tokio::test]
+    async fn download_unpack_staged_promote_verified_snapshot() {
+        let parent_dir = test_utils::get_test_directory("snapshot_client", "staged_verified");
+        let target_dir = parent_dir.join("db");
+        fs::create_dir(parent_dir.join(".db.staging-interrupted")).unwrap();
+        fs::create_dir(parent_dir.join(".db.quarantine")).unwrap();
+        let snapshot = Snapshot::dummy();
+        let certificate = signed_snapshot_certificate(&snapshot);
+        let cache = Arc::new(MemoryImmutableFileDigestCacheProvider::default());
+
+        snapshot_client_with_snapshot_downloader()
+            .with_immutable_digest_cache(cache.clone())
+            .download_unpack_staged(
+                &snapshot,
+                &certificate,
+                &message_builder_computing(&snapshot.digest),
+                &target_dir,
+            )
+            .await
+            .expect("download_unpack_staged should not fail");
+
+        assert!(target_dir.join("immutable").join("00000.chunk").exists());
+        assert_eq!(
+            vec!["db".to_string()],
+            fs::read_dir(&parent_dir)
+                .unwrap()
+                .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+                .collect::<Vec<_>>(),
+            "staging and quarantine directories should have been removed"
+        );
+        assert_eq!(
+            Some("chunk_digest".to_string()),
+            cached_chunk_digest(&cache).await
+        );
+    }
+
+    #[tokio::test]
+    async fn download_unpack_staged_quarantine_snapshot_that_does_not_match_its_certificate() {
+        let parent_dir = test_utils::get_test_directory("snapshot_client", "staged_quarantined");
+        let target_dir = parent_dir.join("db");
+        let snapshot = Snapshot::dummy();
+        let certificate = signed_snapshot_certificate(&snapshot);
+        let cache = Arc::new(MemoryImmutableFileDigestCacheProvider::default());
+
+        let error = snapshot_client_with_snapshot_downloader()
+            .with_immutable_digest_cache(cache.clone())
+            .download_unpack_staged(
+                &snapshot,
+                &certificate,
+                &message_builder_computing("other_digest"),
+                &target_dir,
+            )
+            .await
+            .expect_err("download_unpack_staged should fail");
+
+        match error.downcast_ref::<SnapshotClientError>() {
+            Some(SnapshotClientError::Quarantined { quarantine_dir, .. }) => {
+                assert_eq!(&parent_dir.join(".db.quarantine"), quarantine_dir);
+                assert!(quarantine_dir
+                    .join("immutable")
+                    .join("00000.chunk")
+                    .exists());
+            }
+            _ => panic!("Unexpected error: {error:?}"),
+        }
+        assert!(!target_dir.exists());
+        assert_eq!(None, cached_chunk_digest(&cache).await);
+    }
+
+    #[tokio::test]
+    async fn download_unpack_staged_fails_if_target_dir_is_not_empty() {
+        let target_dir = test_utils::get_test_directory("snapshot_client", "staged_not_empty");
+        fs::write(target_dir.join("file"), "content").unwrap();
+        let snapshot = Snapshot::dummy();
+
+        snapshot_client()
+            .download_unpack_staged(
+                &snapshot,
+                &signed_snapshot_certificate(&snapshot),
+                &message_builder_computing(&snapshot.digest),
+                &target_dir,
+            )
+            .await
+            .expect_err("download_unpack_staged should fail");
+
+        assert_eq!(
+            "content",
+            fs::read_to_string(target_dir.join("file")).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn download_unpack_staged_does_not_promote_snapshot_without_cardano_db_layout() {
+        let parent_dir =
+            test_utils::get_test_directory("snapshot_client", "staged_without_cardano_db_layout");
+        let target_dir = parent_dir.join("db");
+        let mut snapshot_downloader = MockHttpSnapshotDownloader::new();
+        snapshot_downloader
+            .expect_probe_location()
+            .returning(available_location);
+        snapshot_downloader
+            .expect_download_unpack_with_report()
+            .returning(|_, dir, _, _, _| {
+                fs::write(dir.join("00000.chunk"), "chunk").unwrap();
+                Ok(download_report())
+            });
+        let client = SnapshotClient::new(
+            Arc::new(MockAggregatorHTTPClient::new()),
+            Arc::new(snapshot_downloader),
+            FeedbackSender::new(&[]),
+            test_utils::test_logger(),
+        );
+        let snapshot = Snapshot::dummy();
+
+        let error = client
+            .download_unpack_staged(
+                &snapshot,
+                &signed_snapshot_certificate(&snapshot),
+                &message_builder_computing(&snapshot.digest),
+                &target_dir,
+            )
+            .await
+            .expect_err("download_unpack_staged should fail");
+
+        assert!(
+            error
+                .downcast_ref::<crate::download_layout::DownloadLayoutError>()
+                .is_some(),
+            "Unexpected error: {error:?}"
+        );
+        assert_eq!(0, fs::read_dir(&parent_dir).unwrap().count());
+    }
 }
@@ -10,18 +10,138 @@
 use anyhow::{anyhow, Context};
 use async_trait::async_trait;
 use futures::StreamExt;
+use reqwest::header::{ACCEPT_RANGES, CONTENT_LENGTH, CONTENT_RANGE, ETAG, IF_RANGE, RANGE};
 use reqwest::{Response, StatusCode};
-use slog::{debug, Logger};
+use sha2::{Digest, Sha256};
+use slog::{debug, warn, Logger};
+use std::io::SeekFrom;
 use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWrite, AsyncWriteExt};
 
 #[cfg(test)]
 use mockall::automock;
 
 use crate::common::entities::CompressionAlgorithm;
+use crate::connection_pool::ConnectionPoolConfig;
+use crate::dns::DnsConfig;
 use crate::feedback::{FeedbackSender, MithrilEvent};
-use crate::utils::SnapshotUnpacker;
+use crate::metrics::MetricsRecorder;
+use crate::proxy::ProxyConfig;
+use crate::tls::TlsConfig;
+use crate::utils::{
+    DownloadCheckpoint, HashingTee, HttpClientSettings, SnapshotUnpacker, PIPELINE_CHUNK_SIZE,
+};
 use crate::MithrilResult;
 
+pub use crate::utils::{
+    OverwritePolicy, PipelineStageTimings, UnpackOptions, UnpackedImmutableDigests,
+};
+
+/// Number of downloaded bytes after which the progress of a resumable download is persisted.
+const CHECKPOINT_INTERVAL_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Result of the [probe][SnapshotDownloader::probe_location] of a snapshot location.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotLocationProbe {
+    /// The probed location
+    pub location: String,
+
+    /// Reason why the location is not available, `None` if it is
+    pub error: Option<String>,
+
+    /// Size of the archive announced by the location
+    pub size: Option<u64>,
+
+    /// Whether the location supports range requests, needed to resume interrupted downloads
+    pub supports_range: bool,
+
+    /// Time taken by the location to answer
+    pub latency: Option<Duration>,
+}
+
+impl SnapshotLocationProbe {
+    /// Constructs the probe of a location that is not available.
+    pub fn failure(location: &str, error: anyhow::Error) -> Self {
+        Self {
+            location: location.to_string(),
+            error: Some(format!("{error:?}")),
+            size: None,
+            supports_range: false,
+            latency: None,
+        }
+    }
+
+    /// Returns `true` if the location is available.
+    pub fn is_available(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Ranked results of the probe of the locations of a snapshot, see
+/// [SnapshotClient::probe_locations][crate::snapshot_client::SnapshotClient::probe_locations].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotAvailabilityReport {
+    /// Probes ranked from the best location to the worst: available locations first, then the
+    /// ones announcing the expected size, then the ones supporting range requests, then the ones
+    /// with the lowest latency.
+    pub probes: Vec<SnapshotLocationProbe>,
+}
+
+impl SnapshotAvailabilityReport {
+    /// Constructs a new `SnapshotAvailabilityReport` ranking the given probes of the locations of
+    /// an archive of the given size.
+    pub fn new(probes: Vec<SnapshotLocationProbe>, expected_size: u64) -> Self {
+        let mut probes = probes;
+        probes.sort_by_key(|probe| {
+            (
+                !probe.is_available(),
+                probe.size.is_some_and(|size| size != expected_size),
+                !probe.supports_range,
+                probe.latency,
+            )
+        });
+
+        Self { probes }
+    }
+
+    /// Get the best available location, if any.
+    pub fn best(&self) -> Option<&SnapshotLocationProbe> {
+        self.probes.first().filter(|probe| probe.is_available())
+    }
+
+    /// Get all the available locations, from the best to the worst.
+    pub fn available_locations(&self) -> Vec<String> {
+        self.probes
+            .iter()
+            .filter(|probe| probe.is_available())
+            .map(|probe| probe.location.clone())
+            .collect()
+    }
+}
+
+/// Report of the download and unpacking of an archive, see
+/// [SnapshotDownloader::download_unpack_with_report].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DownloadReport {
+    /// Hex encoded SHA256 digest of the downloaded archive
+    pub archive_digest: String,
+
+    /// Size of the downloaded archive
+    pub archive_size: u64,
+
+    /// Time spent in each stage of the download pipeline
+    pub timings: PipelineStageTimings,
+
+    /// Digests of the immutable files computed while unpacking them, see
+    /// [HttpSnapshotDownloader::with_immutable_files_digests]
+    ///
+    /// They are computed from an archive that is not verified yet, they must only be stored in
+    /// an immutable digest cache once the snapshot is verified.
+    pub immutable_digests: UnpackedImmutableDigests,
+}
+
 /// API that defines a snapshot downloader
 #[async_trait]
 pub trait SnapshotDownloader: Sync + Send {
@@ -40,14 +160,177 @@ pub trait SnapshotDownloader: Sync + Send {
         snapshot_size: u64,
     ) -> MithrilResult<()>;
 
+    /// Download and unpack a snapshot archive on the disk, like
+    /// [download_unpack][Self::download_unpack], from the first of the given mirror locations.
+    ///
+    /// If a location fails in the middle of the download, the download goes on from the next
+    /// locations and only fails once all of them failed.
+    ///
+    /// The default implementation downloads the whole archive again from the next location
+    /// with [download_unpack][Self::download_unpack] when a location fails.
+    async fn download_unpack_from_mirrors(
+        &self,
+        locations: &[String],
+        target_dir: &Path,
+        compression_algorithm: CompressionAlgorithm,
+        download_id: &str,
+        snapshot_size: u64,
+    ) -> MithrilResult<()> {
+        let mut last_error = None;
+        for location in locations {
+            match self
+                .download_unpack(
+                    location,
+                    target_dir,
+                    compression_algorithm,
+                    download_id,
+                    snapshot_size,
+                )
+                .await
+            {
+                Ok(()) => return Ok(()),
+                Err(error) => last_error = Some(error),
+            }
+        }
+
+        Err(locations_exhausted(locations, last_error))
+    }
+
+    /// Download and unpack a snapshot archive on the disk from the given mirror locations, like
+    /// [download_unpack_from_mirrors][Self::download_unpack_from_mirrors], and return a
+    /// [DownloadReport] with the digest of the archive and the timings of the download stages.
+    ///
+    /// The default implementation downloads the whole archive again from the next location
+    /// with [download_unpack_with_digest][Self::download_unpack_with_digest] when a location
+    /// fails, and only measures the total duration.
+    async fn download_unpack_with_report(
+        &self,
+        locations: &[String],
+        target_dir: &Path,
+        compression_algorithm: CompressionAlgorithm,
+        download_id: &str,
+        archive_size: u64,
+    ) -> MithrilResult<DownloadReport> {
+        let start = Instant::now();
+        let mut last_error = None;
+        for location in locations {
+            match self
+                .download_unpack_with_digest(
+                    location,
+                    target_dir,
+                    compression_algorithm,
+                    download_id,
+                    archive_size,
+                )
+                .await
+            {
+                Ok(archive_digest) => {
+                    return Ok(DownloadReport {
+                        archive_digest,
+                        archive_size,
+                        timings: PipelineStageTimings {
+                            total: start.elapsed(),
+                            ..PipelineStageTimings::default()
+                        },
+                        immutable_digests: vec![],
+                    })
+                }
+                Err(error) => last_error = Some(error),
+            }
+        }
+
+        Err(locations_exhausted(locations, last_error))
+    }
+
+    /// Download and unpack an archive on the disk, like [download_unpack][Self::download_unpack],
+    /// and return the hex encoded SHA256 digest of the downloaded archive.
+    ///
+    /// The default implementation downloads the archive to a file inside `target_dir` with
+    /// [download_to_writer][Self::download_to_writer], unpacks it with the default
+    /// [UnpackOptions] and removes it.
+    async fn download_unpack_with_digest(
+        &self,
+        location: &str,
+        target_dir: &Path,
+        compression_algorithm: CompressionAlgorithm,
+        download_id: &str,
+        archive_size: u64,
+    ) -> MithrilResult<String> {
+        let archive_path = target_dir.join(format!(".{download_id}.archive"));
+        let result: MithrilResult<String> = async {
+            let mut archive_file =
+                tokio::fs::File::create(&archive_path)
+                    .await
+                    .with_context(|| {
+                        format!(
+                            "Download: could not create file '{}'",
+                            archive_path.display()
+                        )
+                    })?;
+            let archive_digest = self
+                .download_to_writer(location, &mut archive_file, download_id, archive_size)
+                .await?;
+            drop(archive_file);
+            unpack_archive_file(
+                &archive_path,
+                compression_algorithm,
+                target_dir,
+                UnpackOptions::default(),
+            )
+            .await?;
+
+            Ok(archive_digest)
+        }
+        .await;
+        let _ = tokio::fs::remove_file(&archive_path).await;
+
+        result
+    }
+
+    /// Download an archive, without unpacking it, into the given writer and return the hex
+    /// encoded SHA256 digest of the downloaded archive.
+    ///
+    /// The writer is flushed once the archive is downloaded but it's not shut down.
+    async fn download_to_writer(
+        &self,
+        location: &str,
+        writer: &mut (dyn AsyncWrite + Send + Unpin + 'static),
+        download_id: &str,
+        archive_size: u64,
+    ) -> MithrilResult<String>;
+
     /// Test if the given snapshot location exists.
     async fn probe(&self, location: &str) -> MithrilResult<()>;
+
+    /// Measure the availability, announced size, range support and latency of the given
+    /// snapshot location.
+    ///
+    /// The default implementation only measures the availability and latency using
+    /// [probe][Self::probe].
+    async fn probe_location(&self, location: &str) -> SnapshotLocationProbe {
+        let start = Instant::now();
+        match self.probe(location).await {
+            Ok(()) => SnapshotLocationProbe {
+                location: location.to_string(),
+                error: None,
+                size: None,
+                supports_range: false,
+                latency: Some(start.elapsed()),
+            },
+            Err(error) => SnapshotLocationProbe::failure(location, error),
+        }
+    }
 }
 
 /// A snapshot downloader that only handles download through HTTP.
 pub struct HttpSnapshotDownloader {
     http_client: reqwest::Client,
+    http_client_settings: HttpClientSettings,
     feedback_sender: FeedbackSender,
+    unpack_options: UnpackOptions,
+    resumable_downloads: bool,
+    digest_immutable_files: bool,
+    metrics_recorder: Option<Arc<dyn MetricsRecorder>>,
     logger: Logger,
 }
 
@@ -60,11 +343,88 @@ impl HttpSnapshotDownloader {
 
         Ok(Self {
             http_client,
+            http_client_settings: HttpClientSettings::default(),
             feedback_sender,
+            unpack_options: UnpackOptions::default(),
+            resumable_downloads: false,
+            digest_immutable_files: false,
+            metrics_recorder: None,
             logger,
         })
     }
 
+    /// Download the snapshots through the given proxy, see [proxy][crate::proxy].
+    pub fn with_proxy(mut self, proxy: &ProxyConfig) -> MithrilResult<Self> {
+        self.http_client_settings.proxy = Some(proxy.clone());
+        self.rebuild_http_client()
+    }
+
+    /// Download the snapshots using the given TLS settings, see [tls][crate::tls].
+    pub fn with_tls(mut self, tls: &TlsConfig) -> MithrilResult<Self> {
+        self.http_client_settings.tls = Some(tls.clone());
+        self.rebuild_http_client()
+    }
+
+    /// Tune the pool of the connections to the snapshot locations, see
+    /// [connection_pool][crate::connection_pool].
+    pub fn with_connection_pool(
+        mut self,
+        connection_pool: &ConnectionPoolConfig,
+    ) -> MithrilResult<Self> {
+        self.http_client_settings.connection_pool = Some(connection_pool.clone());
+        self.rebuild_http_client()
+    }
+
+    /// Resolve the names of the snapshot locations using the given settings, see
+    /// [dns][crate::dns].
+    pub fn with_dns(mut self, dns: &DnsConfig) -> MithrilResult<Self> {
+        self.http_client_settings.dns = Some(dns.clone());
+        self.rebuild_http_client()
+    }
+
+    fn rebuild_http_client(mut self) -> MithrilResult<Self> {
+        self.http_client = self
+            .http_client_settings
+            .build()
+            .with_context(|| "Building http client for HttpSnapshotDownloader failed")?;
+        Ok(self)
+    }
+
+    /// Persist the downloaded archives and the progress of their download in the target
+    /// directory, so an interrupted download can be resumed, even by another process.
+    ///
+    /// The archive is unpacked once fully downloaded, then removed: the target directory must
+    /// have room for both the archive and its unpacked content.
+    pub fn with_resumable_downloads(mut self, resumable_downloads: bool) -> Self {
+        self.resumable_downloads = resumable_downloads;
+        self
+    }
+
+    /// Set the [UnpackOptions] honored when unpacking the downloaded snapshots.
+    pub fn with_unpack_options(mut self, unpack_options: UnpackOptions) -> Self {
+        self.unpack_options = unpack_options;
+        self
+    }
+
+    /// Digest the immutable files while unpacking them and return their digests in the
+    /// [DownloadReport], so they can be stored in an immutable digest cache once the snapshot is
+    /// verified, see
+    /// [SnapshotClient::download_unpack_staged][crate::snapshot_client::SnapshotClient::download_unpack_staged].
+    pub fn with_immutable_files_digests(mut self, digest_immutable_files: bool) -> Self {
+        self.digest_immutable_files = digest_immutable_files;
+        self
+    }
+
+    /// Set the [MetricsRecorder] that will count the downloaded bytes.
+    pub fn with_metrics_recorder(mut self, metrics_recorder: Arc<dyn MetricsRecorder>) -> Self {
+        self.metrics_recorder = Some(metrics_recorder);
+        self
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "snapshot_request", skip(self))
+    )]
     async fn get(&self, location: &str) -> MithrilResult<Response> {
         debug!(self.logger, "GET Snapshot location='{location}'.");
         let request_builder = self.http_client.get(location);
@@ -78,68 +438,579 @@ impl HttpSnapshotDownloader {
             status_code => Err(anyhow!("Unhandled error {status_code}")),
         }
     }
-}
 
-#[cfg_attr(test, automock)]
-#[async_trait]
-impl SnapshotDownloader for HttpSnapshotDownloader {
-    async fn download_unpack(
+    /// Request the bytes of the archive at the given location starting at the given offset.
+    ///
+    /// The `if_range` entity tag ensures that the server sends the rest of the same archive,
+    /// or the whole archive if it changed. A partial response must still be checked with
+    /// [is_continuation] before appending it to the bytes already downloaded.
+    async fn get_range(
+        &self,
+        location: &str,
+        from: u64,
+        if_range: Option<&str>,
+    ) -> MithrilResult<Response> {
+        debug!(
+            self.logger, "GET Snapshot location='{location}'.";
+            "from" => from
+        );
+        let mut request_builder = self.http_client.get(location);
+        if from > 0 {
+            request_builder = request_builder.header(RANGE, format!("bytes={from}-"));
+            if let Some(etag) = if_range {
+                request_builder = request_builder.header(IF_RANGE, etag);
+            }
+        }
+        let response = request_builder.send().await.with_context(|| {
+            format!("Cannot perform a GET for the snapshot (location='{location}')")
+        })?;
+
+        match response.status() {
+            StatusCode::OK | StatusCode::PARTIAL_CONTENT | StatusCode::RANGE_NOT_SATISFIABLE => {
+                Ok(response)
+            }
+            StatusCode::NOT_FOUND => Err(anyhow!("Location='{location} not found")),
+            status_code => Err(anyhow!("Unhandled error {status_code}")),
+        }
+    }
+
+    /// Request the bytes of the archive that are missing from the given checkpoint to the given
+    /// location.
+    ///
+    /// The entity tag of the checkpoint is only sent to the location it was received from.
+    async fn get_from(
         &self,
         location: &str,
+        checkpoint: &DownloadCheckpoint,
+    ) -> MithrilResult<Response> {
+        let if_range = checkpoint
+            .etag
+            .as_deref()
+            .filter(|_| location == checkpoint.location);
+
+        self.get_range(location, checkpoint.downloaded_bytes, if_range)
+            .await
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "snapshot_download",
+            skip(self, target_dir, compression_algorithm)
+        )
+    )]
+    async fn download_and_unpack(
+        &self,
+        locations: &[String],
         target_dir: &Path,
         compression_algorithm: CompressionAlgorithm,
         download_id: &str,
-        snapshot_size: u64,
-    ) -> MithrilResult<()> {
+        archive_size: u64,
+    ) -> MithrilResult<DownloadReport> {
         if !target_dir.is_dir() {
             Err(
                 anyhow!("target path is not a directory or does not exist: `{target_dir:?}`")
                     .context("Download-Unpack: prerequisite error"),
             )?;
         }
-        let mut downloaded_bytes: u64 = 0;
-        let mut remote_stream = self.get(location).await?.bytes_stream();
-        let (sender, receiver) = flume::bounded(5);
+        let start = Instant::now();
+        let (mut tee, receiver) = HashingTee::channel();
 
         let dest_dir = target_dir.to_path_buf();
-        let unpack_thread = tokio::task::spawn_blocking(move || -> MithrilResult<()> {
-            let unpacker = SnapshotUnpacker;
-            unpacker.unpack_snapshot(receiver, compression_algorithm, &dest_dir)
+        let unpack_options = self.unpack_options.clone();
+        let digest_immutable_files = self.digest_immutable_files;
+        #[cfg(feature = "tracing")]
+        let download_span = tracing::Span::current();
+        let unpack_thread = tokio::task::spawn_blocking(move || {
+            #[cfg(feature = "tracing")]
+            let _unpack_span =
+                tracing::info_span!(parent: &download_span, "snapshot_unpack").entered();
+            let unpacker = SnapshotUnpacker::new(unpack_options)
+                .with_immutable_files_digests(digest_immutable_files);
+            unpacker.unpack_snapshot_with_report(receiver, compression_algorithm, &dest_dir)
         });
 
-        while let Some(item) = remote_stream.next().await {
-            let chunk = item.with_context(|| "Download: Could not read from byte stream")?;
-
-            sender.send_async(chunk.to_vec()).await.with_context(|| {
-                format!("Download: could not write {} bytes to stream.", chunk.len())
-            })?;
+        let pipeline = async {
+            if self.resumable_downloads {
+                self.download_resumable(locations, target_dir, download_id, archive_size, &mut tee)
+                    .await?;
+            } else {
+                self.download_streamed(locations, download_id, archive_size, &mut tee)
+                    .await?;
+            }
 
-            downloaded_bytes += chunk.len() as u64;
-            self.feedback_sender
-                .send_event(MithrilEvent::SnapshotDownloadProgress {
-                    download_id: download_id.to_owned(),
-                    downloaded_bytes,
-                    size: snapshot_size,
-                })
+            let downloaded_bytes = tee.sent_bytes();
+            let (archive_digest, timings) = tee.finish(); // Signal EOF
+            let unpack_report = unpack_thread
                 .await
+                .with_context(|| {
+                    format!(
+                        "Unpack: panic while unpacking to dir '{}'",
+                        target_dir.display()
+                    )
+                })?
+                .with_context(|| {
+                    format!("Unpack: could not unpack to dir '{}'", target_dir.display())
+                })?;
+
+            Ok::<_, anyhow::Error>((downloaded_bytes, archive_digest, timings, unpack_report))
+        }
+        .await;
+        let (downloaded_bytes, archive_digest, timings, unpack_report) = match pipeline {
+            Ok(result) => result,
+            Err(error) => {
+                if let (true, Some(location)) = (self.resumable_downloads, locations.first()) {
+                    self.remove_unpackable_archive(target_dir, location, archive_size);
+                }
+                return Err(error);
+            }
+        };
+        if let (true, Some(location)) = (self.resumable_downloads, locations.first()) {
+            DownloadCheckpoint::new(location, archive_size).remove(target_dir)?;
+        }
+
+        let timings = PipelineStageTimings {
+            decompression: unpack_report.timings.decompression,
+            unpacking: unpack_report.timings.unpacking,
+            total: start.elapsed(),
+            ..timings
+        };
+        debug!(
+            self.logger, "Snapshot download pipeline timings";
+            "download_id" => download_id, "downloaded_bytes" => downloaded_bytes,
+            "timings" => ?timings
+        );
+
+        Ok(DownloadReport {
+            archive_digest,
+            archive_size: downloaded_bytes,
+            timings,
+            immutable_digests: unpack_report.immutable_digests,
+        })
+    }
+
+    /// Download the archive and stream it to the unpacker while it's downloaded.
+    ///
+    /// If a location fails, the rest of the archive is requested to the next location with a
+    /// range request.
+    async fn download_streamed(
+        &self,
+        locations: &[String],
+        download_id: &str,
+        archive_size: u64,
+        tee: &mut HashingTee,
+    ) -> MithrilResult<()> {
+        let mut last_error = None;
+
+        for location in locations {
+            let downloaded_bytes = tee.sent_bytes();
+            let response = if downloaded_bytes == 0 {
+                self.get(location).await
+            } else {
+                self.get_range(location, downloaded_bytes, None)
+                    .await
+                    .and_then(|response| match response.status() {
+                        StatusCode::PARTIAL_CONTENT
+                            if is_continuation(&response, downloaded_bytes, archive_size) =>
+                        {
+                            Ok(response)
+                        }
+                        StatusCode::PARTIAL_CONTENT => Err(anyhow!(
+                            "Location='{location}' did not send the continuation of the archive \
+                            of {archive_size} bytes from byte {downloaded_bytes}"
+                        )),
+                        status_code => Err(anyhow!(
+                            "Location='{location}' answered {status_code} to a range request"
+                        )),
+                    })
+            };
+            let mut remote_stream = match response {
+                Ok(response) => response.bytes_stream(),
+                Err(error) => {
+                    self.warn_failover(location, downloaded_bytes, &error);
+                    last_error = Some(error);
+                    continue;
+                }
+            };
+
+            let mut stream_error = None;
+            while let Some(item) = tee.pull(&mut remote_stream).await {
+                let chunk = match item {
+                    Ok(chunk) => chunk,
+                    Err(error) => {
+                        stream_error = Some(
+                            anyhow!(error).context("Download: Could not read from byte stream"),
+                        );
+                        break;
+                    }
+                };
+                tee.send(&chunk).await?;
+
+                self.report_progress(download_id, chunk.len(), tee.sent_bytes(), archive_size)
+                    .await;
+            }
+
+            match stream_error {
+                None => return Ok(()),
+                Some(error) => {
+                    self.warn_failover(location, tee.sent_bytes(), &error);
+                    last_error = Some(error);
+                }
+            }
+        }
+
+        Err(locations_exhausted(locations, last_error))
+    }
+
+    /// Remove the archive of a resumable download if it was fully downloaded but could not be
+    /// unpacked: it's corrupt and every retry would reuse it instead of downloading it again.
+    fn remove_unpackable_archive(&self, target_dir: &Path, location: &str, archive_size: u64) {
+        let checkpoint = DownloadCheckpoint::load(target_dir, location, archive_size);
+        if checkpoint.is_complete() {
+            if let Err(error) = checkpoint.remove(target_dir) {
+                warn!(
+                    self.logger,
+                    "Error while removing the archive that could not be unpacked: {error}"
+                );
+            }
+        }
+    }
+
+    /// Download the archive to the target directory, resuming a previous download of the same
+    /// archive if any, then stream it to the unpacker.
+    ///
+    /// The progress of the download is persisted under the first location, if another location
+    /// has to be used the download goes on from the persisted progress with a range request.
+    async fn download_resumable(
+        &self,
+        locations: &[String],
+        target_dir: &Path,
+        download_id: &str,
+        archive_size: u64,
+        tee: &mut HashingTee,
+    ) -> MithrilResult<()> {
+        let primary_location = locations
+            .first()
+            .ok_or_else(|| anyhow!("Download: no location to download the snapshot from"))?;
+        let mut checkpoint = DownloadCheckpoint::load(target_dir, primary_location, archive_size);
+        let archive_path = DownloadCheckpoint::archive_path(target_dir, primary_location);
+        if !checkpoint.is_complete() {
+            let download_start = Instant::now();
+            let mut last_error = None;
+            for location in locations {
+                match self
+                    .download_to_file(
+                        location,
+                        &mut checkpoint,
+                        target_dir,
+                        &archive_path,
+                        download_id,
+                    )
+                    .await
+                {
+                    Ok(()) => {
+                        last_error = None;
+                        break;
+                    }
+                    Err(error) => {
+                        self.warn_failover(location, checkpoint.downloaded_bytes, &error);
+                        last_error = Some(error);
+                    }
+                }
+            }
+            if last_error.is_some() {
+                return Err(locations_exhausted(locations, last_error));
+            }
+            tee.add_network_time(download_start.elapsed());
         }
 
-        drop(sender); // Signal EOF
-        unpack_thread
+        let archive = tokio::fs::File::open(&archive_path)
             .await
             .with_context(|| {
                 format!(
-                    "Unpack: panic while unpacking to dir '{}'",
-                    target_dir.display()
+                    "Download: could not open downloaded archive '{}'",
+                    archive_path.display()
+                )
+            })?;
+        let mut archive = archive.take(checkpoint.downloaded_bytes);
+        let mut buffer = vec![0; PIPELINE_CHUNK_SIZE];
+        loop {
+            let read_start = Instant::now();
+            let read_bytes = archive.read(&mut buffer).await.with_context(|| {
+                format!(
+                    "Download: could not read downloaded archive '{}'",
+                    archive_path.display()
                 )
-            })?
+            })?;
+            tee.add_network_time(read_start.elapsed());
+            if read_bytes == 0 {
+                break;
+            }
+
+            tee.send(&buffer[..read_bytes]).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn download_to_file(
+        &self,
+        location: &str,
+        checkpoint: &mut DownloadCheckpoint,
+        target_dir: &Path,
+        archive_path: &Path,
+        download_id: &str,
+    ) -> MithrilResult<()> {
+        let mut archive = tokio::fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(false)
+            .open(archive_path)
+            .await
             .with_context(|| {
-                format!("Unpack: could not unpack to dir '{}'", target_dir.display())
+                format!(
+                    "Download: could not open archive file '{}'",
+                    archive_path.display()
+                )
+            })?;
+
+        let mut response = self.get_from(location, checkpoint).await?;
+        if response.status() == StatusCode::RANGE_NOT_SATISFIABLE {
+            debug!(
+                self.logger, "Download checkpoint rejected by the server, restarting download";
+                "location" => location
+            );
+            *checkpoint = DownloadCheckpoint::new(&checkpoint.location, checkpoint.archive_size);
+            response = self.get_from(location, checkpoint).await?;
+        }
+        if response.status() == StatusCode::PARTIAL_CONTENT
+            && !is_continuation(
+                &response,
+                checkpoint.downloaded_bytes,
+                checkpoint.archive_size,
+            )
+        {
+            debug!(
+                self.logger, "Server did not send the continuation of the archive, restarting download";
+                "location" => location
+            );
+            *checkpoint = DownloadCheckpoint::new(&checkpoint.location, checkpoint.archive_size);
+            response = self.get_from(location, checkpoint).await?;
+        }
+        if response.status() == StatusCode::OK {
+            if checkpoint.downloaded_bytes > 0 {
+                debug!(
+                    self.logger, "Server sent the whole archive, restarting download";
+                    "location" => location
+                );
+            }
+            checkpoint.downloaded_bytes = 0;
+            // The entity tag is only relevant to the location the checkpoint is persisted under
+            checkpoint.etag = response
+                .headers()
+                .get(ETAG)
+                .and_then(|etag| etag.to_str().ok())
+                .filter(|_| location == checkpoint.location)
+                .map(str::to_string);
+        } else {
+            debug!(
+                self.logger, "Resuming download";
+                "location" => location, "downloaded_bytes" => checkpoint.downloaded_bytes
+            );
+        }
+
+        archive.set_len(checkpoint.downloaded_bytes).await?;
+        archive.seek(SeekFrom::End(0)).await?;
+
+        let mut remote_stream = response.bytes_stream();
+        let mut unsaved_bytes: u64 = 0;
+        while let Some(item) = remote_stream.next().await {
+            let chunk = match item {
+                Ok(chunk) => chunk,
+                Err(error) => {
+                    // Keep the progress made so far so the download can go on from it
+                    Self::save_checkpoint(&mut archive, checkpoint, target_dir).await?;
+                    return Err(anyhow!(error).context("Download: Could not read from byte stream"));
+                }
+            };
+            archive.write_all(&chunk).await.with_context(|| {
+                format!(
+                    "Download: could not write {} bytes to archive.",
+                    chunk.len()
+                )
             })?;
 
+            checkpoint.downloaded_bytes += chunk.len() as u64;
+            unsaved_bytes += chunk.len() as u64;
+            self.report_progress(
+                download_id,
+                chunk.len(),
+                checkpoint.downloaded_bytes,
+                checkpoint.archive_size,
+            )
+            .await;
+
+            if unsaved_bytes >= CHECKPOINT_INTERVAL_BYTES {
+                Self::save_checkpoint(&mut archive, checkpoint, target_dir).await?;
+                unsaved_bytes = 0;
+            }
+        }
+
+        Self::save_checkpoint(&mut archive, checkpoint, target_dir).await
+    }
+
+    fn warn_failover(&self, location: &str, downloaded_bytes: u64, error: &anyhow::Error) {
+        warn!(
+            self.logger, "Download from a location failed, failing over to the next one";
+            "location" => location, "downloaded_bytes" => downloaded_bytes, "error" => ?error
+        );
+    }
+
+    async fn save_checkpoint(
+        archive: &mut tokio::fs::File,
+        checkpoint: &DownloadCheckpoint,
+        target_dir: &Path,
+    ) -> MithrilResult<()> {
+        archive.flush().await?;
+        archive
+            .sync_data()
+            .await
+            .with_context(|| "Download: could not sync archive file to the disk")?;
+        checkpoint.save(target_dir)
+    }
+
+    async fn report_progress(
+        &self,
+        download_id: &str,
+        chunk_size: usize,
+        downloaded_bytes: u64,
+        size: u64,
+    ) {
+        if let Some(metrics_recorder) = &self.metrics_recorder {
+            metrics_recorder.record_downloaded_bytes(chunk_size as u64);
+        }
+        self.feedback_sender
+            .send_event(MithrilEvent::SnapshotDownloadProgress {
+                download_id: download_id.to_owned(),
+                downloaded_bytes,
+                size,
+            })
+            .await
+    }
+}
+
+#[cfg_attr(test, automock)]
+#[async_trait]
+impl SnapshotDownloader for HttpSnapshotDownloader {
+    async fn download_unpack(
+        &self,
+        location: &str,
+        target_dir: &Path,
+        compression_algorithm: CompressionAlgorithm,
+        download_id: &str,
+        snapshot_size: u64,
+    ) -> MithrilResult<()> {
+        self.download_unpack_with_digest(
+            location,
+            target_dir,
+            compression_algorithm,
+            download_id,
+            snapshot_size,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn download_unpack_with_digest(
+        &self,
+        location: &str,
+        target_dir: &Path,
+        compression_algorithm: CompressionAlgorithm,
+        download_id: &str,
+        archive_size: u64,
+    ) -> MithrilResult<String> {
+        let report = self
+            .download_and_unpack(
+                &[location.to_string()],
+                target_dir,
+                compression_algorithm,
+                download_id,
+                archive_size,
+            )
+            .await?;
+
+        Ok(report.archive_digest)
+    }
+
+    async fn download_unpack_with_report(
+        &self,
+        locations: &[String],
+        target_dir: &Path,
+        compression_algorithm: CompressionAlgorithm,
+        download_id: &str,
+        archive_size: u64,
+    ) -> MithrilResult<DownloadReport> {
+        self.download_and_unpack(
+            locations,
+            target_dir,
+            compression_algorithm,
+            download_id,
+            archive_size,
+        )
+        .await
+    }
+
+    async fn download_unpack_from_mirrors(
+        &self,
+        locations: &[String],
+        target_dir: &Path,
+        compression_algorithm: CompressionAlgorithm,
+        download_id: &str,
+        snapshot_size: u64,
+    ) -> MithrilResult<()> {
+        self.download_and_unpack(
+            locations,
+            target_dir,
+            compression_algorithm,
+            download_id,
+            snapshot_size,
+        )
+        .await?;
+
         Ok(())
     }
 
+    async fn download_to_writer(
+        &self,
+        location: &str,
+        writer: &mut (dyn AsyncWrite + Send + Unpin + 'static),
+        download_id: &str,
+        archive_size: u64,
+    ) -> MithrilResult<String> {
+        let mut downloaded_bytes: u64 = 0;
+        let mut hasher = Sha256::new();
+        let mut remote_stream = self.get(location).await?.bytes_stream();
+
+        while let Some(item) = remote_stream.next().await {
+            let chunk = item.with_context(|| "Download: Could not read from byte stream")?;
+            hasher.update(&chunk);
+            writer.write_all(&chunk).await.with_context(|| {
+                format!("Download: could not write {} bytes to writer.", chunk.len())
+            })?;
+
+            downloaded_bytes += chunk.len() as u64;
+            self.report_progress(download_id, chunk.len(), downloaded_bytes, archive_size)
+                .await;
+        }
+        writer
+            .flush()
+            .await
+            .with_context(|| "Download: could not flush writer")?;
+
+        Ok(hex::encode(hasher.finalize()))
+    }
+
     async fn probe(&self, location: &str) -> MithrilResult<()> {
         debug!(self.logger, "HEAD Snapshot location='{location}'.");
 
@@ -154,4 +1025,727 @@ impl SnapshotDownloader for HttpSnapshotDownloader {
             status_code => Err(anyhow!("Unhandled error {status_code}")),
         }
     }
+
+    async fn probe_location(&self, location: &str) -> SnapshotLocationProbe {
+        debug!(self.logger, "HEAD Snapshot location='{location}'.");
+
+        let start = Instant::now();
+        let response = match self.http_client.head(location).send().await {
+            Ok(response) => response,
+            Err(error) => {
+                return SnapshotLocationProbe::failure(
+                    location,
+                    anyhow!(error).context(format!(
+                        "Cannot perform a HEAD for snapshot at location='{location}'"
+                    )),
+                )
+            }
+        };
+        let latency = start.elapsed();
+
+        match response.status() {
+            StatusCode::OK => {
+                let header = |name| {
+                    response
+                        .headers()
+                        .get(name)
+                        .and_then(|value| value.to_str().ok())
+                };
+                SnapshotLocationProbe {
+                    location: location.to_string(),
+                    error: None,
+                    size: header(CONTENT_LENGTH).and_then(|length| length.parse().ok()),
+                    supports_range: header(ACCEPT_RANGES)
+                        .is_some_and(|ranges| ranges.split(',').any(|unit| unit.trim() == "bytes")),
+                    latency: Some(latency),
+                }
+            }
+            StatusCode::NOT_FOUND => SnapshotLocationProbe::failure(
+                location,
+                anyhow!("Snapshot location='{location} not found"),
+            ),
+            status_code => {
+                SnapshotLocationProbe::failure(location, anyhow!("Unhandled error {status_code}"))
+            }
+        }
+    }
+}
+
+fn locations_exhausted(locations: &[String], last_error: Option<anyhow::Error>) -> anyhow::Error {
+    let context = format!(
+        "Download: all the locations failed, tried: '{}'",
+        locations.join(", ")
+    );
+
+    match last_error {
+        Some(error) => error.context(context),
+        None => anyhow!(context),
+    }
+}
+
+/// Unpack the archive file at the given path into the given directory.
+pub(crate) async fn unpack_archive_file(
+    archive_path: &Path,
+    compression_algorithm: CompressionAlgorithm,
+    target_dir: &Path,
+    unpack_options: UnpackOptions,
+) -> MithrilResult<()> {
+    let archive_path = archive_path.to_path_buf();
+    let unpack_dir = target_dir.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        let archive_file = std::fs::File::open(&archive_path).with_context(|| {
+            format!(
+                "Unpack: could not open archive '{}'",
+                archive_path.display()
+            )
+        })?;
+        SnapshotUnpacker::new(unpack_options).unpack_reader_with_report(
+            std::io::BufReader::new(archive_file),
+            compression_algorithm,
+            &unpack_dir,
+        )
+    })
+    .await
+    .with_context(|| {
+        format!(
+            "Unpack: panic while unpacking to dir '{}'",
+            target_dir.display()
+        )
+    })?
+    .with_context(|| format!("Unpack: could not unpack to dir '{}'", target_dir.display()))?;
+
+    Ok(())
+}
+
+/// Check that the `Content-Range` header of a partial response starts at the requested offset
+/// and announces the expected size of the archive, so that the received bytes are the
+/// continuation of the same archive and can be appended to the bytes already downloaded.
+fn is_continuation(response: &Response, from: u64, archive_size: u64) -> bool {
+    let Some(range) = response
+        .headers()
+        .get(CONTENT_RANGE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|content_range| content_range.strip_prefix("bytes "))
+    else {
+        return false;
+    };
+    let Some((bytes, total)) = range.split_once('/') else {
+        return false;
+    };
+    let start = bytes
+        .split('-')
+        .next()
+        .and_then(|start| start.trim().parse::<u64>().ok());
+
+    start == Some(from) && total.trim().parse::<u64>().ok() == Some(archive_size)
+}
+
+#[cfg(test)]
+mod tests {
+    use httpmock::MockServer;
+    use std::fs;
+
+    use crate::test_utils;
+
+    use super::*;
+
+    fn resumable_downloader() -> HttpSnapshotDownloader {
+        HttpSnapshotDownloader::new(FeedbackSender::new(&[]), test_utils::test_logger())
+            .unwrap()
+            .with_resumable_downloads(true)
+    }
+
+    /// Persist the first `downloaded_bytes` of the archive and their checkpoint, as an interrupted
+    /// download would have.
+    fn interrupted_download(
+        target_dir: &Path,
+        location: &str,
+        archive: &[u8],
+        downloaded_bytes: usize,
+    ) {
+        fs::write(
+            DownloadCheckpoint::archive_path(target_dir, location),
+            &archive[..downloaded_bytes],
+        )
+        .unwrap();
+        DownloadCheckpoint {
+            etag: Some("\"v1\"".to_string()),
+            downloaded_bytes: downloaded_bytes as u64,
+            ..DownloadCheckpoint::new(location, archive.len() as u64)
+        }
+        .save(target_dir)
+        .unwrap();
+    }
+
+    async fn download(
+        downloader: &HttpSnapshotDownloader,
+        location: &str,
+        target_dir: &Path,
+        archive_size: usize,
+    ) -> MithrilResult<String> {
+        downloader
+            .download_unpack_with_digest(
+                location,
+                target_dir,
+                CompressionAlgorithm::Gzip,
+                "download_id",
+                archive_size as u64,
+            )
+            .await
+    }
+
+    fn probe(
+        location: &str,
+        size: Option<u64>,
+        supports_range: bool,
+        latency_ms: u64,
+    ) -> SnapshotLocationProbe {
+        SnapshotLocationProbe {
+            location: location.to_string(),
+            error: None,
+            size,
+            supports_range,
+            latency: Some(Duration::from_millis(latency_ms)),
+        }
+    }
+
+    #[test]
+    fn availability_report_rank_available_then_sized_then_resumable_then_fastest_locations() {
+        let report = SnapshotAvailabilityReport::new(
+            vec![
+                SnapshotLocationProbe::failure("unavailable", anyhow!("error")),
+                probe("wrong_size", Some(12), true, 1),
+                probe("slow", Some(100), true, 50),
+                probe("not_resumable", Some(100), false, 1),
+                probe("unknown_size", None, true, 10),
+                probe("fast", Some(100), true, 5),
+            ],
+            100,
+        );
+
+        assert_eq!(
+            vec![
+                "fast",
+                "unknown_size",
+                "slow",
+                "not_resumable",
+                "wrong_size",
+                "unavailable"
+            ],
+            report
+                .probes
+                .iter()
+                .map(|probe| probe.location.as_str())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!("fast", report.best().unwrap().location);
+    }
+
+    #[test]
+    fn availability_report_has_no_best_location_if_none_is_available() {
+        let report = SnapshotAvailabilityReport::new(
+            vec![SnapshotLocationProbe::failure(
+                "unavailable",
+                anyhow!("error"),
+            )],
+            100,
+        );
+
+        assert_eq!(None, report.best());
+    }
+
+    #[tokio::test]
+    async fn probe_location_read_size_and_range_support() {
+        let server = MockServer::start_async().await;
+        server
+            .mock_async(|when, then| {
+                when.method(httpmock::Method::HEAD).path("/snapshot.tar.gz");
+                then.status(200)
+                    .header("content-length", "1234")
+                    .header("accept-ranges", "bytes");
+            })
+            .await;
+        let downloader =
+            HttpSnapshotDownloader::new(FeedbackSender::new(&[]), test_utils::test_logger())
+                .unwrap();
+
+        let probe = downloader
+            .probe_location(&server.url("/snapshot.tar.gz"))
+            .await;
+
+        assert!(probe.is_available(), "Unexpected probe: {probe:?}");
+        assert_eq!(Some(1234), probe.size);
+        assert!(probe.supports_range);
+        assert!(probe.latency.is_some());
+    }
+
+    #[tokio::test]
+    async fn probe_missing_location() {
+        let server = MockServer::start_async().await;
+        server
+            .mock_async(|when, then| {
+                when.method(httpmock::Method::HEAD);
+                then.status(404);
+            })
+            .await;
+        let downloader =
+            HttpSnapshotDownloader::new(FeedbackSender::new(&[]), test_utils::test_logger())
+                .unwrap();
+
+        let probe = downloader
+            .probe_location(&server.url("/snapshot.tar.gz"))
+            .await;
+
+        assert!(!probe.is_available());
+    }
+
+    #[tokio::test]
+    async fn resume_an_interrupted_download_from_its_checkpoint() {
+        let target_dir = test_utils::get_test_directory(
+            "snapshot_downloader",
+            "resume_an_interrupted_download_from_its_checkpoint",
+        );
+        let archive = test_utils::gzip_archive(&[("immutable/00001.chunk", "chunk content")]);
+        let half = archive.len() / 2;
+        let server = MockServer::start();
+        let location = server.url("/snapshot.tar.gz");
+        let mock = server.mock(|when, then| {
+            when.path("/snapshot.tar.gz")
+                .header("Range", format!("bytes={half}-"))
+                .header("If-Range", "\"v1\"");
+            then.status(206)
+                .header(
+                    "Content-Range",
+                    format!("bytes {half}-{}/{}", archive.len() - 1, archive.len()),
+                )
+                .body(&archive[half..]);
+        });
+        interrupted_download(&target_dir, &location, &archive, half);
+
+        let digest = download(
+            &resumable_downloader(),
+            &location,
+            &target_dir,
+            archive.len(),
+        )
+        .await
+        .unwrap();
+
+        mock.assert();
+        assert_eq!(hex::encode(Sha256::digest(&archive)), digest);
+        assert_eq!(
+            "chunk content",
+            fs::read_to_string(target_dir.join("immutable/00001.chunk")).unwrap()
+        );
+        assert!(
+            !DownloadCheckpoint::archive_path(&target_dir, &location).exists(),
+            "the downloaded archive should be removed once unpacked"
+        );
+    }
+
+    #[tokio::test]
+    async fn download_again_a_complete_archive_that_could_not_be_unpacked() {
+        let target_dir = test_utils::get_test_directory(
+            "snapshot_downloader",
+            "download_again_a_complete_archive_that_could_not_be_unpacked",
+        );
+        let archive = test_utils::gzip_archive(&[("immutable/00001.chunk", "chunk content")]);
+        let server = MockServer::start();
+        let location = server.url("/snapshot.tar.gz");
+        let mock = server.mock(|when, then| {
+            when.path("/snapshot.tar.gz");
+            then.status(200).body(&archive);
+        });
+        let corrupt_archive = vec![0; archive.len()];
+        interrupted_download(&target_dir, &location, &corrupt_archive, archive.len());
+
+        download(
+            &resumable_downloader(),
+            &location,
+            &target_dir,
+            archive.len(),
+        )
+        .await
+        .expect_err("download should fail to unpack the corrupt archive");
+        mock.assert_hits(0);
+        assert!(!DownloadCheckpoint::archive_path(&target_dir, &location).exists());
+
+        download(
+            &resumable_downloader(),
+            &location,
+            &target_dir,
+            archive.len(),
+        )
+        .await
+        .unwrap();
+        mock.assert();
+        assert_eq!(
+            "chunk content",
+            fs::read_to_string(target_dir.join("immutable/00001.chunk")).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn resume_an_interrupted_download_from_a_mirror_if_the_first_location_fails() {
+        let target_dir = test_utils::get_test_directory(
+            "snapshot_downloader",
+            "resume_an_interrupted_download_from_a_mirror_if_the_first_location_fails",
+        );
+        let archive = test_utils::gzip_archive(&[("immutable/00001.chunk", "chunk content")]);
+        let half = archive.len() / 2;
+        let server = MockServer::start();
+        let location = server.url("/snapshot.tar.gz");
+        server.mock(|when, then| {
+            when.path("/snapshot.tar.gz");
+            then.status(503);
+        });
+        let mirror_server = MockServer::start();
+        let mirror_location = mirror_server.url("/snapshot.tar.gz");
+        let mirror_mock = mirror_server.mock(|when, then| {
+            when.path("/snapshot.tar.gz")
+                .header("Range", format!("bytes={half}-"));
+            then.status(206)
+                .header(
+                    "Content-Range",
+                    format!("bytes {half}-{}/{}", archive.len() - 1, archive.len()),
+                )
+                .body(&archive[half..]);
+        });
+        interrupted_download(&target_dir, &location, &archive, half);
+
+        resumable_downloader()
+            .download_unpack_from_mirrors(
+                &[location.clone(), mirror_location],
+                &target_dir,
+                CompressionAlgorithm::Gzip,
+                "download_id",
+                archive.len() as u64,
+            )
+            .await
+            .unwrap();
+
+        mirror_mock.assert();
+        assert_eq!(
+            "chunk content",
+            fs::read_to_string(target_dir.join("immutable/00001.chunk")).unwrap()
+        );
+        assert!(!DownloadCheckpoint::archive_path(&target_dir, &location).exists());
+    }
+
+    #[tokio::test]
+    async fn restart_an_interrupted_download_if_a_mirror_sends_a_range_of_another_archive() {
+        let target_dir = test_utils::get_test_directory(
+            "snapshot_downloader",
+            "restart_an_interrupted_download_if_a_mirror_sends_a_range_of_another_archive",
+        );
+        let archive = test_utils::gzip_archive(&[("immutable/00001.chunk", "chunk content")]);
+        let half = archive.len() / 2;
+        let server = MockServer::start();
+        let location = server.url("/snapshot.tar.gz");
+        server.mock(|when, then| {
+            when.path("/snapshot.tar.gz");
+            then.status(503);
+        });
+        let mirror_server = MockServer::start();
+        let mirror_location = mirror_server.url("/snapshot.tar.gz");
+        let range_mock = mirror_server.mock(|when, then| {
+            when.path("/snapshot.tar.gz")
+                .header("Range", format!("bytes={half}-"));
+            then.status(206)
+                .header(
+                    "Content-Range",
+                    format!("bytes {half}-{}/{}", archive.len(), archive.len() + 1),
+                )
+                .body(&archive[half..]);
+        });
+        let full_mock = mirror_server.mock(|when, then| {
+            when.path("/snapshot.tar.gz").matches(|request| {
+                !request
+                    .headers
+                    .iter()
+                    .flatten()
+                    .any(|(name, _)| name.eq_ignore_ascii_case("range"))
+            });
+            then.status(200).body(&archive);
+        });
+        interrupted_download(&target_dir, &location, &archive, half);
+
+        resumable_downloader()
+            .download_unpack_from_mirrors(
+                &[location.clone(), mirror_location],
+                &target_dir,
+                CompressionAlgorithm::Gzip,
+                "download_id",
+                archive.len() as u64,
+            )
+            .await
+            .unwrap();
+
+        range_mock.assert();
+        full_mock.assert();
+        assert_eq!(
+            "chunk content",
+            fs::read_to_string(target_dir.join("immutable/00001.chunk")).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn a_partial_response_is_a_continuation_if_its_range_starts_at_the_offset_of_the_same_archive(
+    ) {
+        let server = MockServer::start();
+        for (path, content_range) in [
+            ("/continuation", "bytes 50-99/100"),
+            ("/other_offset", "bytes 40-99/100"),
+            ("/other_size", "bytes 50-99/120"),
+            ("/unknown_size", "bytes 50-99/*"),
+        ] {
+            server.mock(|when, then| {
+                when.path(path);
+                then.status(206).header("Content-Range", content_range);
+            });
+        }
+        server.mock(|when, then| {
+            when.path("/no_range");
+            then.status(206);
+        });
+        let is_continuation_at = |path: &'static str| {
+            let url = server.url(path);
+            async move {
+                let response = reqwest::get(url).await.unwrap();
+                is_continuation(&response, 50, 100)
+            }
+        };
+
+        assert!(is_continuation_at("/continuation").await);
+        assert!(!is_continuation_at("/other_offset").await);
+        assert!(!is_continuation_at("/other_size").await);
+        assert!(!is_continuation_at("/unknown_size").await);
+        assert!(!is_continuation_at("/no_range").await);
+    }
+
+    #[tokio::test]
+    async fn default_download_from_mirrors_goes_on_with_the_next_locations() {
+        let target_dir = test_utils::get_test_directory(
+            "snapshot_downloader",
+            "default_download_from_mirrors_goes_on_with_the_next_locations",
+        );
+        let downloader = test_utils::ArchiveSnapshotDownloader {
+            archive: test_utils::gzip_archive(&[("immutable/00001.chunk", "chunk content")]),
+            failing_locations: vec!["failing".to_string()],
+        };
+
+        downloader
+            .download_unpack_from_mirrors(
+                &["failing".to_string(), "mirror".to_string()],
+                &target_dir,
+                CompressionAlgorithm::Gzip,
+                "download_id",
+                100,
+            )
+            .await
+            .unwrap();
+        assert!(target_dir.join("immutable/00001.chunk").exists());
+
+        downloader
+            .download_unpack_with_report(
+                &["failing".to_string()],
+                &target_dir,
+                CompressionAlgorithm::Gzip,
+                "download_id",
+                100,
+            )
+            .await
+            .expect_err("download should fail if all the locations fail");
+    }
+
+    #[tokio::test]
+    async fn download_from_mirrors_fails_once_all_the_locations_failed() {
+        let target_dir = test_utils::get_test_directory(
+            "snapshot_downloader",
+            "download_from_mirrors_fails_once_all_the_locations_failed",
+        );
+        let servers = [MockServer::start(), MockServer::start()];
+        let mocks = servers.each_ref().map(|server| {
+            server.mock(|when, then| {
+                when.path("/snapshot.tar.gz");
+                then.status(503);
+            })
+        });
+        let downloader =
+            HttpSnapshotDownloader::new(FeedbackSender::new(&[]), test_utils::test_logger())
+                .unwrap();
+
+        downloader
+            .download_unpack_from_mirrors(
+                &servers
+                    .each_ref()
+                    .map(|server| server.url("/snapshot.tar.gz")),
+                &target_dir,
+                CompressionAlgorithm::Gzip,
+                "download_id",
+                100,
+            )
+            .await
+            .expect_err("download should fail if all the locations fail");
+
+        for mock in mocks {
+            mock.assert();
+        }
+    }
+
+    #[tokio::test]
+    async fn restart_an_interrupted_download_if_the_server_sends_the_whole_archive() {
+        let target_dir = test_utils::get_test_directory(
+            "snapshot_downloader",
+            "restart_an_interrupted_download_if_the_server_sends_the_whole_archive",
+        );
+        let archive = test_utils::gzip_archive(&[("immutable/00001.chunk", "chunk content")]);
+        let server = MockServer::start();
+        let location = server.url("/snapshot.tar.gz");
+        server.mock(|when, then| {
+            when.path("/snapshot.tar.gz");
+            then.status(200).header("ETag", "\"v2\"").body(&archive);
+        });
+        interrupted_download(&target_dir, &location, &archive, archive.len() / 2);
+
+        let digest = download(
+            &resumable_downloader(),
+            &location,
+            &target_dir,
+            archive.len(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(hex::encode(Sha256::digest(&archive)), digest);
+        assert!(target_dir.join("immutable/00001.chunk").exists());
+    }
+
+    #[tokio::test]
+    async fn unpack_a_completed_download_without_requesting_it_again() {
+        let target_dir = test_utils::get_test_directory(
+            "snapshot_downloader",
+            "unpack_a_completed_download_without_requesting_it_again",
+        );
+        let archive = test_utils::gzip_archive(&[("immutable/00001.chunk", "chunk content")]);
+        let server = MockServer::start();
+        let location = server.url("/snapshot.tar.gz");
+        let mock = server.mock(|when, then| {
+            when.path("/snapshot.tar.gz");
+            then.status(200).body(&archive);
+        });
+        interrupted_download(&target_dir, &location, &archive, archive.len());
+
+        download(
+            &resumable_downloader(),
+            &location,
+            &target_dir,
+            archive.len(),
+        )
+        .await
+        .unwrap();
+
+        mock.assert_hits(0);
+        assert!(target_dir.join("immutable/00001.chunk").exists());
+    }
+
+    #[tokio::test]
+    async fn download_archive_to_a_writer_without_unpacking_it() {
+        let archive = test_utils::gzip_archive(&[("immutable/00001.chunk", "chunk content")]);
+        let server = MockServer::start();
+        let location = server.url("/snapshot.tar.gz");
+        server.mock(|when, then| {
+            when.path("/snapshot.tar.gz");
+            then.status(200).body(&archive);
+        });
+        let feedback_receiver = Arc::new(crate::feedback::StackFeedbackReceiver::new());
+        let downloader = HttpSnapshotDownloader::new(
+            FeedbackSender::new(&[feedback_receiver.clone()]),
+            test_utils::test_logger(),
+        )
+        .unwrap();
+        let mut writer = Vec::new();
+
+        let digest = downloader
+            .download_to_writer(&location, &mut writer, "download_id", archive.len() as u64)
+            .await
+            .unwrap();
+
+        assert_eq!(archive, writer);
+        assert_eq!(hex::encode(Sha256::digest(&archive)), digest);
+        assert_eq!(
+            Some(&MithrilEvent::SnapshotDownloadProgress {
+                download_id: "download_id".to_string(),
+                downloaded_bytes: archive.len() as u64,
+                size: archive.len() as u64,
+            }),
+            feedback_receiver.stacked_events().last()
+        );
+    }
+
+    #[tokio::test]
+    async fn report_the_digest_and_the_stage_timings_of_a_streamed_download() {
+        let target_dir = test_utils::get_test_directory(
+            "snapshot_downloader",
+            "report_the_stage_timings_of_a_streamed_download",
+        );
+        let archive = test_utils::gzip_archive(&[("immutable/00001.chunk", "chunk content")]);
+        let server = MockServer::start();
+        let location = server.url("/snapshot.tar.gz");
+        server.mock(|when, then| {
+            when.path("/snapshot.tar.gz");
+            then.status(200).body(&archive);
+        });
+        let downloader =
+            HttpSnapshotDownloader::new(FeedbackSender::new(&[]), test_utils::test_logger())
+                .unwrap();
+
+        let report = downloader
+            .download_unpack_with_report(
+                &[location],
+                &target_dir,
+                CompressionAlgorithm::Gzip,
+                "download_id",
+                archive.len() as u64,
+            )
+            .await
+            .unwrap();
+
+        assert!(target_dir.join("immutable/00001.chunk").exists());
+        assert_eq!(hex::encode(Sha256::digest(&archive)), report.archive_digest);
+        assert_eq!(archive.len() as u64, report.archive_size);
+        assert!(report.timings.total >= report.timings.network);
+        assert!(report.timings.total >= report.timings.unpacking);
+    }
+
+    #[tokio::test]
+    async fn default_download_unpack_with_digest_unpack_the_downloaded_archive_and_remove_it() {
+        let target_dir = test_utils::get_test_directory(
+            "snapshot_downloader",
+            "default_download_unpack_with_digest",
+        );
+        let archive = test_utils::gzip_archive(&[("immutable/00001.chunk", "chunk content")]);
+        let downloader = test_utils::ArchiveSnapshotDownloader {
+            archive: archive.clone(),
+            failing_locations: vec![],
+        };
+
+        let archive_digest = downloader
+            .download_unpack_with_digest(
+                "location",
+                &target_dir,
+                CompressionAlgorithm::Gzip,
+                "download_id",
+                archive.len() as u64,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(hex::encode(Sha256::digest(&archive)), archive_digest);
+        assert_eq!(
+            "chunk content",
+            fs::read_to_string(target_dir.join("immutable/00001.chunk")).unwrap()
+        );
+        assert!(!target_dir.join(".download_id.archive").exists());
+    }
 }
@@ -0,0 +1,330 @@
+//! Validation of the snapshots returned by an Aggregator before they are downloaded.
+//!
+//! A [SnapshotValidationPolicy] checks that a snapshot has download locations, that they comply
+//! with the policy (ie: only `https` locations), that its size is within sane bounds and that
+//! its locations match its compression algorithm.
+//!
+//! All the problems found are reported at once in a [SnapshotValidationError].
+//!
+//! The [SnapshotClient][crate::snapshot_client::SnapshotClient] validates the snapshots with its
+//! policy before downloading them, the policy can be set with
+//! [ClientBuilder::with_snapshot_validation_policy][crate::ClientBuilder::with_snapshot_validation_policy].
+//!
+//! ```no_run
+//! # async fn run() -> mithril_client::MithrilResult<()> {
+//! use mithril_client::snapshot_validation::SnapshotValidationPolicy;
+//! use mithril_client::ClientBuilder;
+//!
+//! let client = ClientBuilder::aggregator("YOUR_AGGREGATOR_ENDPOINT", "YOUR_GENESIS_VERIFICATION_KEY")
+//!     .with_snapshot_validation_policy(SnapshotValidationPolicy::default().with_https_only(true))
+//!     .build()?;
+//! let snapshot = client.snapshot().get("SNAPSHOT_DIGEST").await?.unwrap();
+//!
+//! client.snapshot().validate(&snapshot)?;
+//! #    Ok(())
+//! # }
+//! ```
+
+use reqwest::Url;
+use std::fmt::{Display, Formatter};
+use thiserror::Error;
+
+use crate::common::entities::CompressionAlgorithm;
+use crate::Snapshot;
+
+/// A problem found while validating a snapshot
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SnapshotValidationProblem {
+    /// The snapshot has no download location
+    NoLocations,
+
+    /// A location is not a valid url
+    InvalidLocation {
+        /// the invalid location
+        location: String,
+
+        /// why the location is invalid
+        reason: String,
+    },
+
+    /// A location does not use the `https` scheme while the policy requires it
+    InsecureLocation {
+        /// the insecure location
+        location: String,
+    },
+
+    /// The size of the snapshot is outside the bounds of the policy
+    SizeOutOfBounds {
+        /// size of the snapshot in bytes
+        size: u64,
+
+        /// minimum size allowed in bytes
+        min_size: u64,
+
+        /// maximum size allowed in bytes
+        max_size: u64,
+    },
+
+    /// A location points to an archive compressed with another algorithm than the one of the
+    /// snapshot
+    CompressionAlgorithmMismatch {
+        /// the location of the archive
+        location: String,
+
+        /// compression algorithm of the snapshot
+        compression_algorithm: CompressionAlgorithm,
+    },
+}
+
+impl Display for SnapshotValidationProblem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoLocations => write!(f, "no download location"),
+            Self::InvalidLocation { location, reason } => {
+                write!(f, "invalid location '{location}': {reason}")
+            }
+            Self::InsecureLocation { location } => {
+                write!(f, "location '{location}' does not use https")
+            }
+            Self::SizeOutOfBounds {
+                size,
+                min_size,
+                max_size,
+            } => write!(
+                f,
+                "size of {size} bytes is not between {min_size} and {max_size} bytes"
+            ),
+            Self::CompressionAlgorithmMismatch {
+                location,
+                compression_algorithm,
+            } => write!(
+                f,
+                "location '{location}' is not a '{}' archive as expected by the '{compression_algorithm}' compression algorithm",
+                compression_algorithm.tar_file_extension()
+            ),
+        }
+    }
+}
+
+/// Error raised when a snapshot does not comply with a [SnapshotValidationPolicy]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("The snapshot digest '{digest}' is invalid: {}.", problems.iter().map(|p| p.to_string()).collect::<Vec<_>>().join("; "))]
+pub struct SnapshotValidationError {
+    /// digest of the invalid snapshot
+    pub digest: String,
+
+    /// all the problems found
+    pub problems: Vec<SnapshotValidationProblem>,
+}
+
+/// Rules that a snapshot must comply with to be downloaded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotValidationPolicy {
+    https_only: bool,
+    min_size: u64,
+    max_size: u64,
+}
+
+impl Default for SnapshotValidationPolicy {
+    /// Default policy: any location scheme and a size between 1 byte and 16 TiB.
+    fn default() -> Self {
+        Self {
+            https_only: false,
+            min_size: 1,
+            max_size: 16 * 1024_u64.pow(4),
+        }
+    }
+}
+
+impl SnapshotValidationPolicy {
+    /// Only accept locations that are `https` urls.
+    ///
+    /// Locations aren't required to be urls otherwise, so custom
+    /// [SnapshotDownloader][crate::snapshot_downloader::SnapshotDownloader] can resolve them
+    /// their own way.
+    pub fn with_https_only(mut self, https_only: bool) -> Self {
+        self.https_only = https_only;
+        self
+    }
+
+    /// Set the bounds, inclusive and in bytes, of the size of the snapshots.
+    pub fn with_size_bounds(mut self, min_size: u64, max_size: u64) -> Self {
+        self.min_size = min_size;
+        self.max_size = max_size;
+        self
+    }
+
+    /// Validate the given snapshot, including its ancillary files locations if any, returning
+    /// all the problems found.
+    pub fn validate(&self, snapshot: &Snapshot) -> Result<(), SnapshotValidationError> {
+        let mut problems = vec![];
+
+        if snapshot.locations.is_empty() {
+            problems.push(SnapshotValidationProblem::NoLocations);
+        }
+        let ancillary_locations = snapshot.ancillary_locations.iter().flatten();
+        for location in snapshot.locations.iter().chain(ancillary_locations) {
+            problems.extend(self.validate_location(location));
+        }
+
+        if !(self.min_size..=self.max_size).contains(&snapshot.size) {
+            problems.push(SnapshotValidationProblem::SizeOutOfBounds {
+                size: snapshot.size,
+                min_size: self.min_size,
+                max_size: self.max_size,
+            });
+        }
+
+        let compression_algorithm = snapshot.compression_algorithm.unwrap_or_default();
+        for location in &snapshot.locations {
+            if Self::is_archive_of_other_algorithm(location, compression_algorithm) {
+                problems.push(SnapshotValidationProblem::CompressionAlgorithmMismatch {
+                    location: location.clone(),
+                    compression_algorithm,
+                });
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(SnapshotValidationError {
+                digest: snapshot.digest.clone(),
+                problems,
+            })
+        }
+    }
+
+    fn validate_location(&self, location: &str) -> Option<SnapshotValidationProblem> {
+        if !self.https_only {
+            return None;
+        }
+
+        match Url::parse(location) {
+            Ok(url) if url.scheme() == "https" => None,
+            Ok(_) => Some(SnapshotValidationProblem::InsecureLocation {
+                location: location.to_string(),
+            }),
+            Err(e) => Some(SnapshotValidationProblem::InvalidLocation {
+                location: location.to_string(),
+                reason: e.to_string(),
+            }),
+        }
+    }
+
+    fn is_archive_of_other_algorithm(
+        location: &str,
+        compression_algorithm: CompressionAlgorithm,
+    ) -> bool {
+        // Strip the query and fragment of urls, the archive extension ends the path
+        let path = location.split(['?', '#']).next().unwrap_or_default();
+
+        CompressionAlgorithm::list()
+            .into_iter()
+            .filter(|algorithm| *algorithm != compression_algorithm)
+            .any(|algorithm| path.ends_with(&format!(".{}", algorithm.tar_file_extension())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dummy_snapshot_is_valid() {
+        SnapshotValidationPolicy::default()
+            .with_https_only(true)
+            .validate(&Snapshot::dummy())
+            .expect("dummy snapshot should be valid");
+    }
+
+    #[test]
+    fn report_all_the_problems_of_a_snapshot() {
+        let snapshot = Snapshot {
+            size: 0,
+            locations: vec![],
+            ancillary_locations: Some(vec![
+                "http://host/ancillary.tar.gz".to_string(),
+                "not an url".to_string(),
+            ]),
+            ..Snapshot::dummy()
+        };
+
+        let error = SnapshotValidationPolicy::default()
+            .with_https_only(true)
+            .validate(&snapshot)
+            .expect_err("validation should fail");
+
+        assert_eq!(
+            vec![
+                SnapshotValidationProblem::NoLocations,
+                SnapshotValidationProblem::InsecureLocation {
+                    location: "http://host/ancillary.tar.gz".to_string()
+                },
+                SnapshotValidationProblem::InvalidLocation {
+                    location: "not an url".to_string(),
+                    reason: "relative URL without a base".to_string()
+                },
+                SnapshotValidationProblem::SizeOutOfBounds {
+                    size: 0,
+                    min_size: 1,
+                    max_size: 16 * 1024_u64.pow(4)
+                },
+            ],
+            error.problems
+        );
+    }
+
+    #[test]
+    fn locations_are_not_required_to_be_urls_without_https_only_policy() {
+        let snapshot = Snapshot {
+            locations: vec!["http://host/snapshot".to_string(), "mirror".to_string()],
+            ..Snapshot::dummy()
+        };
+
+        SnapshotValidationPolicy::default()
+            .validate(&snapshot)
+            .expect("validation should succeed");
+    }
+
+    #[test]
+    fn report_locations_of_archives_compressed_with_another_algorithm() {
+        let snapshot = Snapshot {
+            locations: vec![
+                "https://host/snapshot.tar.zst?token=1".to_string(),
+                "https://host/snapshot.tar.gz".to_string(),
+            ],
+            compression_algorithm: None,
+            ..Snapshot::dummy()
+        };
+
+        let error = SnapshotValidationPolicy::default()
+            .validate(&snapshot)
+            .expect_err("validation should fail");
+
+        assert_eq!(
+            vec![SnapshotValidationProblem::CompressionAlgorithmMismatch {
+                location: "https://host/snapshot.tar.zst?token=1".to_string(),
+                compression_algorithm: CompressionAlgorithm::Gzip,
+            }],
+            error.problems
+        );
+    }
+
+    #[test]
+    fn size_bounds_are_inclusive() {
+        let snapshot = Snapshot {
+            size: 10,
+            ..Snapshot::dummy()
+        };
+
+        SnapshotValidationPolicy::default()
+            .with_size_bounds(10, 10)
+            .validate(&snapshot)
+            .expect("validation should succeed");
+        SnapshotValidationPolicy::default()
+            .with_size_bounds(11, 20)
+            .validate(&snapshot)
+            .expect_err("validation should fail");
+    }
+}
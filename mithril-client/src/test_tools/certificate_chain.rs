@@ -0,0 +1,586 @@
+//! Generate synthetic certificate chains, valid or tampered, to test the certificate chain
+//! verification of an application.
+//!
+//! The chains are built with the same tooling that an aggregator uses: the certificates are
+//! signed by deterministic signers built with a [SignerBuilder] and the chain is rooted on a
+//! genesis certificate signed by a deterministic [ProtocolGenesisSigner].
+//!
+//! # Verify a tampered chain
+//!
+//! ```no_run
+//! # async fn run() -> mithril_client::MithrilResult<()> {
+//! use mithril_client::test_tools::certificate_chain::{
+//!     CertificateChainBuilder, CertificateChainTampering,
+//! };
+//! use mithril_client::test_tools::FakeAggregatorClient;
+//! use mithril_client::ClientBuilder;
+//! use std::sync::Arc;
+//!
+//! let chain = CertificateChainBuilder::new()
+//!     .with_total_certificates(4)
+//!     .with_tampering(CertificateChainTampering::MultiSignature { position: 1 })
+//!     .build()?;
+//! let aggregator_client =
+//!     Arc::new(FakeAggregatorClient::new().with_certificate_chain(&chain.certificates));
+//! let client = ClientBuilder::new(&chain.genesis_verification_key)
+//!     .with_aggregator_client(aggregator_client)
+//!     .build()?;
+//!
+//! let result = client
+//!     .certificate()
+//!     .verify_chain(&chain.latest_certificate().hash)
+//!     .await;
+//!
+//! assert!(result.is_err());
+//! #    Ok(())
+//! # }
+//! ```
+
+use anyhow::{anyhow, Context};
+use chrono::{DateTime, Duration, Utc};
+use ed25519_dalek::SigningKey;
+use kes_summed_ed25519::kes::{Sum6Kes, Sum6KesSig};
+use kes_summed_ed25519::traits::KesSk;
+use kes_summed_ed25519::PublicKey as KesPublicKey;
+use rand_chacha::ChaCha20Rng;
+use rand_core::SeedableRng;
+use sha2::{Digest, Sha256};
+
+use crate::common::certificate_chain::CertificateGenesisProducer;
+use crate::common::crypto_helper::{
+    KESPeriod, KesSigner, OpCert, ProtocolAggregateVerificationKey, ProtocolGenesisSigner,
+    ProtocolInitializer, ProtocolMultiSignature, StmInitializerSetupOptions, PROTOCOL_VERSION,
+};
+use crate::common::entities::{
+    Beacon, Certificate, CertificateMetadata, CertificateSignature, Epoch, ProtocolMessage,
+    ProtocolMessagePartKey, ProtocolParameters, SignerWithStake,
+};
+use crate::common::protocol::SignerBuilder;
+use crate::common::StdResult;
+use crate::{MithrilCertificate, MithrilResult};
+
+/// A defect introduced in a certificate of a synthetic chain.
+///
+/// The position of the tampered certificate is counted from the latest certificate of the chain
+/// (`0`) to its genesis certificate (`total_certificates - 1`). The certificates that come after
+/// a tampered certificate are chained to it, so the defect is the only reason for the chain to be
+/// invalid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CertificateChainTampering {
+    /// The hash of the certificate doesn't match its content.
+    Hash {
+        /// Position of the tampered certificate
+        position: usize,
+    },
+
+    /// The aggregate verification key of a standard certificate is not the one announced by its
+    /// previous certificate.
+    AggregateVerificationKey {
+        /// Position of the tampered certificate
+        position: usize,
+    },
+
+    /// The multi-signature of a standard certificate is valid but signs another message.
+    MultiSignature {
+        /// Position of the tampered certificate
+        position: usize,
+    },
+
+    /// The genesis signature of the genesis certificate is valid but signs another message.
+    GenesisSignature,
+}
+
+impl CertificateChainTampering {
+    fn position(&self, total_certificates: usize) -> usize {
+        match self {
+            Self::Hash { position }
+            | Self::AggregateVerificationKey { position }
+            | Self::MultiSignature { position } => *position,
+            Self::GenesisSignature => total_certificates - 1,
+        }
+    }
+}
+
+/// A synthetic certificate chain built by a [CertificateChainBuilder].
+#[derive(Debug, Clone)]
+pub struct CertificateChainFixture {
+    /// The certificates of the chain, from the latest to the genesis certificate
+    pub certificates: Vec<MithrilCertificate>,
+
+    /// The genesis verification key that verifies the genesis certificate of the chain
+    pub genesis_verification_key: String,
+}
+
+impl CertificateChainFixture {
+    /// The latest certificate of the chain, where a chain verification starts.
+    pub fn latest_certificate(&self) -> &MithrilCertificate {
+        &self.certificates[0]
+    }
+
+    /// The genesis certificate of the chain.
+    pub fn genesis_certificate(&self) -> &MithrilCertificate {
+        &self.certificates[self.certificates.len() - 1]
+    }
+}
+
+/// Build a synthetic [CertificateChainFixture].
+///
+/// The chain starts with a genesis certificate alone in its epoch, followed by standard
+/// certificates grouped by epochs of `certificates_per_epoch` certificates, each epoch being
+/// signed by its own set of signers.
+#[derive(Debug, Clone)]
+pub struct CertificateChainBuilder {
+    total_certificates: usize,
+    certificates_per_epoch: usize,
+    signers_per_epoch: usize,
+    protocol_parameters: ProtocolParameters,
+    tamperings: Vec<CertificateChainTampering>,
+    genesis_sealed_at: Option<DateTime<Utc>>,
+}
+
+impl Default for CertificateChainBuilder {
+    fn default() -> Self {
+        Self {
+            total_certificates: 5,
+            certificates_per_epoch: 1,
+            signers_per_epoch: 3,
+            protocol_parameters: ProtocolParameters::new(5, 100, 0.65),
+            tamperings: vec![],
+            genesis_sealed_at: None,
+        }
+    }
+}
+
+impl CertificateChainBuilder {
+    /// Constructs a new `CertificateChainBuilder` of a valid chain of 5 certificates.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the number of certificates of the chain, genesis certificate included.
+    pub fn with_total_certificates(mut self, total_certificates: usize) -> Self {
+        self.total_certificates = total_certificates;
+        self
+    }
+
+    /// Set the number of standard certificates of each epoch.
+    pub fn with_certificates_per_epoch(mut self, certificates_per_epoch: usize) -> Self {
+        self.certificates_per_epoch = certificates_per_epoch;
+        self
+    }
+
+    /// Set the number of signers of each epoch.
+    pub fn with_signers_per_epoch(mut self, signers_per_epoch: usize) -> Self {
+        self.signers_per_epoch = signers_per_epoch;
+        self
+    }
+
+    /// Set the protocol parameters used to sign the certificates.
+    pub fn with_protocol_parameters(mut self, protocol_parameters: ProtocolParameters) -> Self {
+        self.protocol_parameters = protocol_parameters;
+        self
+    }
+
+    /// Add a defect to the chain, can be called several times to add several defects.
+    pub fn with_tampering(mut self, tampering: CertificateChainTampering) -> Self {
+        self.tamperings.push(tampering);
+        self
+    }
+
+    /// Date the certificates from the given date of their genesis certificate instead of the
+    /// current time, each following certificate being sealed ten minutes after its previous
+    /// certificate, so the same builder always builds the same chain.
+    pub fn with_genesis_sealed_at(mut self, genesis_sealed_at: DateTime<Utc>) -> Self {
+        self.genesis_sealed_at = Some(genesis_sealed_at);
+        self
+    }
+
+    /// Build the certificate chain.
+    pub fn build(&self) -> MithrilResult<CertificateChainFixture> {
+        self.check()?;
+        let genesis_signer = ProtocolGenesisSigner::create_deterministic_genesis_signer();
+        let last_epoch = self.epoch_of(self.total_certificates - 1);
+        let epoch_signers = (1..=*last_epoch + 1)
+            .map(|epoch| self.build_epoch_signers(Epoch(epoch)))
+            .collect::<MithrilResult<Vec<_>>>()?;
+        let signers_of = |epoch: Epoch| &epoch_signers[*epoch as usize - 1];
+
+        let mut certificates: Vec<Certificate> = Vec::with_capacity(self.total_certificates);
+        for index in 0..self.total_certificates {
+            let epoch = self.epoch_of(index);
+            let next_avk = signers_of(epoch + 1).compute_aggregate_verification_key();
+            let beacon = Beacon::new("devnet".to_string(), *epoch, index as u64 * 10);
+            let tampering = self.tampering_of(index);
+
+            let mut certificate = match certificates.last() {
+                None => {
+                    let mut genesis_message =
+                        CertificateGenesisProducer::create_genesis_protocol_message(&next_avk)?;
+                    if tampering == Some(CertificateChainTampering::GenesisSignature) {
+                        genesis_message.set_message_part(
+                            ProtocolMessagePartKey::SnapshotDigest,
+                            "tampered".to_string(),
+                        );
+                    }
+                    let genesis_signature =
+                        genesis_signer.sign(genesis_message.compute_hash().as_bytes());
+
+                    let mut certificate = CertificateGenesisProducer::create_genesis_certificate(
+                        self.protocol_parameters.clone(),
+                        beacon,
+                        next_avk,
+                        genesis_signature,
+                    )?;
+                    if let Some(sealed_at) = self.sealed_at_of(index) {
+                        certificate.metadata.initiated_at = sealed_at;
+                        certificate.metadata.sealed_at = sealed_at;
+                        certificate.hash = certificate.compute_hash();
+                    }
+                    certificate
+                }
+                Some(previous_certificate) => {
+                    self.build_standard_certificate(StandardCertificateParameters {
+                        previous_hash: previous_certificate.hash.clone(),
+                        beacon,
+                        sealed_at: self.sealed_at_of(index).unwrap_or_else(Utc::now),
+                        signers: signers_of(epoch),
+                        next_avk,
+                        tampering,
+                        other_signers: signers_of(epoch + 1),
+                    })?
+                }
+            };
+            if let Some(CertificateChainTampering::Hash { .. }) = tampering {
+                certificate.hash = hex::encode(Sha256::digest(certificate.hash.as_bytes()));
+            }
+            certificates.push(certificate);
+        }
+
+        let certificates = certificates
+            .into_iter()
+            .rev()
+            .map(MithrilCertificate::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+        let genesis_verification_key = genesis_signer
+            .create_genesis_verifier()
+            .to_verification_key()
+            .to_json_hex()
+            .with_context(|| "Could not encode the genesis verification key")?;
+
+        Ok(CertificateChainFixture {
+            certificates,
+            genesis_verification_key,
+        })
+    }
+
+    fn check(&self) -> MithrilResult<()> {
+        if self.total_certificates == 0 {
+            return Err(anyhow!(
+                "A certificate chain needs at least a genesis certificate"
+            ));
+        }
+        if self.certificates_per_epoch == 0 || self.signers_per_epoch == 0 {
+            return Err(anyhow!(
+                "A certificate chain needs at least one certificate and one signer per epoch"
+            ));
+        }
+        for tampering in &self.tamperings {
+            let position = tampering.position(self.total_certificates);
+            let is_genesis = position == self.total_certificates - 1;
+            let is_applicable = match tampering {
+                CertificateChainTampering::Hash { .. } => position < self.total_certificates,
+                CertificateChainTampering::AggregateVerificationKey { .. }
+                | CertificateChainTampering::MultiSignature { .. } => {
+                    position < self.total_certificates && !is_genesis
+                }
+                CertificateChainTampering::GenesisSignature => true,
+            };
+            if !is_applicable {
+                return Err(anyhow!(
+                    "Tampering {tampering:?} can not be applied to a chain of {} certificates",
+                    self.total_certificates
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Epoch of the certificate at the given index, counted from the genesis certificate.
+    fn epoch_of(&self, index: usize) -> Epoch {
+        match index {
+            0 => Epoch(1),
+            _ => Epoch(2 + ((index - 1) / self.certificates_per_epoch) as u64),
+        }
+    }
+
+    fn sealed_at_of(&self, index: usize) -> Option<DateTime<Utc>> {
+        self.genesis_sealed_at
+            .map(|genesis_sealed_at| genesis_sealed_at + Duration::minutes(10 * index as i64))
+    }
+
+    fn tampering_of(&self, index: usize) -> Option<CertificateChainTampering> {
+        let position = self.total_certificates - 1 - index;
+        self.tamperings
+            .iter()
+            .find(|tampering| tampering.position(self.total_certificates) == position)
+            .copied()
+    }
+
+    fn build_epoch_signers(&self, epoch: Epoch) -> MithrilResult<EpochSigners> {
+        let mut signers = vec![];
+        let mut protocol_initializers = vec![];
+        for index in 0..self.signers_per_epoch {
+            let mut seed = [0u8; 32];
+            seed[..8].copy_from_slice(&epoch.to_le_bytes());
+            seed[8..16].copy_from_slice(&(index as u64).to_le_bytes());
+            let kes_signer = SyntheticKesSigner { seed };
+            let cold_secret_key = SigningKey::generate(&mut ChaCha20Rng::from_seed(seed));
+            let operational_certificate =
+                OpCert::new(kes_signer.verification_key(), 0, 0, cold_secret_key);
+            let party_id = operational_certificate
+                .compute_protocol_party_id()
+                .with_context(|| "Could not compute the party id of a synthetic signer")?;
+            let stake = 10 + index as u64;
+            let protocol_initializer = ProtocolInitializer::setup_with_kes_signer(
+                self.protocol_parameters.clone().into(),
+                Some(&kes_signer),
+                Some(0),
+                stake,
+                &mut ChaCha20Rng::from_seed(seed),
+                &StmInitializerSetupOptions::default(),
+            )?;
+
+            signers.push(SignerWithStake::new(
+                party_id,
+                protocol_initializer.verification_key().into(),
+                protocol_initializer.verification_key_signature(),
+                Some(operational_certificate.into()),
+                Some(0),
+                stake,
+            ));
+            protocol_initializers.push(protocol_initializer);
+        }
+        let signer_builder = SignerBuilder::new(&signers, &self.protocol_parameters)
+            .with_context(|| format!("Could not register the signers of epoch {epoch}"))?;
+
+        Ok(EpochSigners {
+            signers,
+            protocol_initializers,
+            signer_builder,
+        })
+    }
+
+    fn build_standard_certificate(
+        &self,
+        parameters: StandardCertificateParameters,
+    ) -> MithrilResult<Certificate> {
+        let StandardCertificateParameters {
+            previous_hash,
+            beacon,
+            sealed_at,
+            signers,
+            next_avk,
+            tampering,
+            other_signers,
+        } = parameters;
+        let mut protocol_message = ProtocolMessage::new();
+        protocol_message.set_message_part(
+            ProtocolMessagePartKey::SnapshotDigest,
+            format!("digest-{}", beacon.immutable_file_number),
+        );
+        protocol_message.set_message_part(
+            ProtocolMessagePartKey::NextAggregateVerificationKey,
+            next_avk.to_json_hex()?,
+        );
+        let mut signed_message = protocol_message.clone();
+        if let Some(CertificateChainTampering::MultiSignature { .. }) = tampering {
+            signed_message.set_message_part(
+                ProtocolMessagePartKey::SnapshotDigest,
+                "tampered".to_string(),
+            );
+        }
+        let multi_signature = signers.sign(&signed_message)?;
+        let aggregate_verification_key = match tampering {
+            Some(CertificateChainTampering::AggregateVerificationKey { .. }) => {
+                other_signers.compute_aggregate_verification_key()
+            }
+            _ => signers.compute_aggregate_verification_key(),
+        };
+        let metadata = CertificateMetadata::new(
+            PROTOCOL_VERSION.to_string(),
+            self.protocol_parameters.clone(),
+            sealed_at,
+            sealed_at,
+            signers.signers.iter().cloned().map(Into::into).collect(),
+        );
+
+        Ok(Certificate::new(
+            previous_hash,
+            beacon,
+            metadata,
+            protocol_message,
+            aggregate_verification_key,
+            CertificateSignature::MultiSignature(multi_signature),
+        ))
+    }
+}
+
+/// Parameters of a standard certificate built by a [CertificateChainBuilder].
+struct StandardCertificateParameters<'a> {
+    previous_hash: String,
+    beacon: Beacon,
+    sealed_at: DateTime<Utc>,
+
+    /// Signers of the certificate epoch
+    signers: &'a EpochSigners,
+
+    /// Aggregate verification key of the next epoch signers
+    next_avk: ProtocolAggregateVerificationKey,
+    tampering: Option<CertificateChainTampering>,
+
+    /// Signers of another epoch, used to tamper the aggregate verification key
+    other_signers: &'a EpochSigners,
+}
+
+/// A deterministic KES secret key kept in memory, the synthetic signers have no key files.
+struct SyntheticKesSigner {
+    seed: [u8; 32],
+}
+
+impl SyntheticKesSigner {
+    fn verification_key(&self) -> KesPublicKey {
+        let mut seed = self.seed;
+        let (_, kes_vk) = Sum6Kes::keygen(&mut [0u8; Sum6Kes::SIZE + 4], &mut seed);
+        kes_vk
+    }
+}
+
+impl KesSigner for SyntheticKesSigner {
+    fn sign(&self, message: &[u8], kes_period: KESPeriod) -> StdResult<Sum6KesSig> {
+        let mut seed = self.seed;
+        let mut key_buffer = [0u8; Sum6Kes::SIZE + 4];
+        let (mut kes_sk, _) = Sum6Kes::keygen(&mut key_buffer, &mut seed);
+        for period in 0..kes_period {
+            kes_sk.update().map_err(|_| {
+                anyhow!("Could not evolve the synthetic KES key to period {period}")
+            })?;
+        }
+
+        Ok(kes_sk.sign(message))
+    }
+}
+
+struct EpochSigners {
+    signers: Vec<SignerWithStake>,
+    protocol_initializers: Vec<ProtocolInitializer>,
+    signer_builder: SignerBuilder,
+}
+
+impl EpochSigners {
+    fn compute_aggregate_verification_key(&self) -> ProtocolAggregateVerificationKey {
+        self.signer_builder.compute_aggregate_verification_key()
+    }
+
+    fn sign(&self, message: &ProtocolMessage) -> MithrilResult<ProtocolMultiSignature> {
+        let mut single_signatures = vec![];
+        for (signer, protocol_initializer) in self.signers.iter().zip(&self.protocol_initializers) {
+            let single_signer = self.signer_builder.restore_signer_from_initializer(
+                signer.party_id.clone(),
+                protocol_initializer.duplicate_secret(),
+            )?;
+            single_signatures.extend(single_signer.sign(message)?);
+        }
+
+        self.signer_builder
+            .build_multi_signer()
+            .aggregate_single_signatures(&single_signatures, message)
+            .map_err(|e| anyhow!(e))
+            .with_context(|| "Could not aggregate the single signatures of the synthetic signers")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::test_tools::FakeAggregatorClient;
+    use crate::ClientBuilder;
+
+    use super::*;
+
+    async fn verify_chain(chain: &CertificateChainFixture) -> MithrilResult<MithrilCertificate> {
+        let client = ClientBuilder::new(&chain.genesis_verification_key)
+            .with_aggregator_client(Arc::new(
+                FakeAggregatorClient::new().with_certificate_chain(&chain.certificates),
+            ))
+            .build()?;
+
+        client
+            .certificate()
+            .verify_chain(&chain.latest_certificate().hash)
+            .await
+    }
+
+    #[tokio::test]
+    async fn build_a_valid_chain() {
+        for certificates_per_epoch in [1, 3] {
+            let chain = CertificateChainBuilder::new()
+                .with_total_certificates(6)
+                .with_certificates_per_epoch(certificates_per_epoch)
+                .build()
+                .unwrap();
+
+            assert_eq!(6, chain.certificates.len());
+            assert_eq!("", chain.genesis_certificate().previous_hash);
+            assert_eq!(
+                chain.latest_certificate().hash,
+                verify_chain(&chain).await.unwrap().hash
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn every_tampering_makes_the_chain_invalid() {
+        let total_certificates = 4;
+        let mut tamperings = vec![CertificateChainTampering::GenesisSignature];
+        for position in 0..total_certificates - 1 {
+            tamperings.extend([
+                CertificateChainTampering::Hash { position },
+                CertificateChainTampering::AggregateVerificationKey { position },
+                CertificateChainTampering::MultiSignature { position },
+            ]);
+        }
+        tamperings.push(CertificateChainTampering::Hash {
+            position: total_certificates - 1,
+        });
+
+        for tampering in tamperings {
+            let chain = CertificateChainBuilder::new()
+                .with_total_certificates(total_certificates)
+                .with_certificates_per_epoch(2)
+                .with_tampering(tampering)
+                .build()
+                .unwrap();
+
+            verify_chain(&chain)
+                .await
+                .expect_err(&format!("chain with {tampering:?} should be invalid"));
+        }
+    }
+
+    #[test]
+    fn reject_tampering_not_applicable_to_the_chain() {
+        for tampering in [
+            CertificateChainTampering::Hash { position: 3 },
+            CertificateChainTampering::MultiSignature { position: 2 },
+            CertificateChainTampering::AggregateVerificationKey { position: 2 },
+        ] {
+            CertificateChainBuilder::new()
+                .with_total_certificates(3)
+                .with_tampering(tampering)
+                .build()
+                .expect_err(&format!("{tampering:?} should not be applicable"));
+        }
+    }
+}
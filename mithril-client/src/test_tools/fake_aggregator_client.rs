@@ -0,0 +1,292 @@
+use anyhow::{anyhow, Context};
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+
+use crate::aggregator_client::{AggregatorClient, AggregatorClientError, AggregatorRequest};
+use crate::test_tools::fake_responses;
+use crate::{MithrilCertificate, MithrilResult, MithrilStakeDistribution, Snapshot};
+
+/// An [AggregatorClient] that serves predefined responses, a request without response fails
+/// the same way as a request to a real aggregator that answers with a `404`.
+///
+/// The requests received by the client are kept so tests can assert on them.
+#[derive(Debug, Default)]
+pub struct FakeAggregatorClient {
+    responses: HashMap<String, String>,
+    received_requests: Mutex<Vec<AggregatorRequest>>,
+}
+
+impl FakeAggregatorClient {
+    /// Constructs a new `FakeAggregatorClient` without any response.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Constructs a new `FakeAggregatorClient` that serves the [fake responses][fake_responses].
+    pub fn with_fake_responses() -> Self {
+        let certificate = MithrilCertificate::dummy();
+        let mithril_stake_distribution = MithrilStakeDistribution::dummy();
+        let snapshot = Snapshot::dummy();
+
+        Self::new()
+            .with_response(
+                AggregatorRequest::ListCertificates,
+                fake_responses::certificate_list(),
+            )
+            .with_response(
+                AggregatorRequest::GetCertificate {
+                    hash: certificate.hash,
+                },
+                fake_responses::certificate(),
+            )
+            .with_response(
+                AggregatorRequest::ListMithrilStakeDistributions,
+                fake_responses::mithril_stake_distribution_list(),
+            )
+            .with_response(
+                AggregatorRequest::GetMithrilStakeDistribution {
+                    hash: mithril_stake_distribution.hash,
+                },
+                fake_responses::mithril_stake_distribution(),
+            )
+            .with_response(
+                AggregatorRequest::ListSnapshots,
+                fake_responses::snapshot_list(),
+            )
+            .with_response(
+                AggregatorRequest::GetSnapshot {
+                    digest: snapshot.digest,
+                },
+                fake_responses::snapshot(),
+            )
+    }
+
+    /// Constructs a new `FakeAggregatorClient` that serves the JSON fixtures of the given
+    /// directory.
+    ///
+    /// Each `.json` file is served for the route of its path relative to the directory, without
+    /// its extension, ie: `certificate/<hash>.json` is served for
+    /// [GetCertificate][AggregatorRequest::GetCertificate] requests of the certificate `<hash>`.
+    pub fn with_fixtures_dir(dir: &Path) -> MithrilResult<Self> {
+        let mut client = Self::new();
+
+        for entry in walkdir::WalkDir::new(dir) {
+            let entry =
+                entry.with_context(|| format!("Could not list fixtures in '{}'", dir.display()))?;
+            let path = entry.path();
+            if !entry.file_type().is_file()
+                || path.extension().and_then(|e| e.to_str()) != Some("json")
+            {
+                continue;
+            }
+            let route = path
+                .strip_prefix(dir)?
+                .with_extension("")
+                .iter()
+                .map(|segment| segment.to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            let content = std::fs::read_to_string(path)
+                .with_context(|| format!("Could not read fixture '{}'", path.display()))?;
+            client.responses.insert(route, content);
+        }
+
+        Ok(client)
+    }
+
+    /// Serve the given content for the given request, replacing any previous response.
+    pub fn with_response<T: Into<String>>(
+        mut self,
+        request: AggregatorRequest,
+        content: T,
+    ) -> Self {
+        self.responses.insert(request.route(), content.into());
+        self
+    }
+
+    /// Serve the given certificate when it's requested by hash.
+    pub fn with_certificate(self, certificate: &MithrilCertificate) -> Self {
+        self.with_response(
+            AggregatorRequest::GetCertificate {
+                hash: certificate.hash.clone(),
+            },
+            to_json(certificate),
+        )
+    }
+
+    /// Serve each certificate of the given chain when it's requested by hash.
+    pub fn with_certificate_chain(self, certificates: &[MithrilCertificate]) -> Self {
+        certificates.iter().fold(self, |client, certificate| {
+            client.with_certificate(certificate)
+        })
+    }
+
+    /// Serve the given chain, from its latest to its genesis certificate, as a single bundle
+    /// when it's requested from its latest certificate.
+    pub fn with_certificate_chain_bundle(self, certificates: &[MithrilCertificate]) -> Self {
+        match certificates.first() {
+            Some(latest_certificate) => self.with_response(
+                AggregatorRequest::GetCertificateChain {
+                    from_hash: latest_certificate.hash.clone(),
+                },
+                to_json(&certificates),
+            ),
+            None => self,
+        }
+    }
+
+    /// Serve the given Mithril stake distribution when it's requested by hash.
+    pub fn with_mithril_stake_distribution(
+        self,
+        mithril_stake_distribution: &MithrilStakeDistribution,
+    ) -> Self {
+        self.with_response(
+            AggregatorRequest::GetMithrilStakeDistribution {
+                hash: mithril_stake_distribution.hash.clone(),
+            },
+            to_json(mithril_stake_distribution),
+        )
+    }
+
+    /// Serve the given snapshot when it's requested by digest.
+    pub fn with_snapshot(self, snapshot: &Snapshot) -> Self {
+        self.with_response(
+            AggregatorRequest::GetSnapshot {
+                digest: snapshot.digest.clone(),
+            },
+            to_json(snapshot),
+        )
+    }
+
+    /// Requests received by this client, in the order they were received.
+    pub fn received_requests(&self) -> Vec<AggregatorRequest> {
+        self.received_requests.lock().unwrap().clone()
+    }
+
+    /// Responses served by this client, indexed by route.
+    #[cfg(any(test, feature = "fake_aggregator_server"))]
+    pub(crate) fn responses(&self) -> &HashMap<String, String> {
+        &self.responses
+    }
+}
+
+fn to_json<T: serde::Serialize>(value: &T) -> String {
+    serde_json::to_string(value).expect("fake responses should be serializable")
+}
+
+#[cfg_attr(target_family = "wasm", async_trait(?Send))]
+#[cfg_attr(not(target_family = "wasm"), async_trait)]
+impl AggregatorClient for FakeAggregatorClient {
+    async fn get_content(
+        &self,
+        request: AggregatorRequest,
+    ) -> Result<String, AggregatorClientError> {
+        let route = request.route();
+        self.received_requests.lock().unwrap().push(request);
+
+        self.responses.get(&route).cloned().ok_or_else(|| {
+            AggregatorClientError::RemoteServerLogical(anyhow!(
+                "No fake response for route '{route}'"
+            ))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::certificate_client::MockCertificateVerifier;
+    use crate::{test_utils, ClientBuilder};
+
+    use super::*;
+
+    fn build_client(aggregator_client: Arc<FakeAggregatorClient>) -> crate::Client {
+        ClientBuilder::new("genesis_verification_key")
+            .with_aggregator_client(aggregator_client)
+            .with_certificate_verifier(Arc::new(MockCertificateVerifier::new()))
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn listed_artifacts_can_be_fetched_from_the_fake_responses() {
+        let aggregator_client = Arc::new(FakeAggregatorClient::with_fake_responses());
+        let client = build_client(aggregator_client.clone());
+
+        let certificates = client.certificate().list().await.unwrap();
+        let certificate = client
+            .certificate()
+            .get(&certificates[0].hash)
+            .await
+            .unwrap();
+        let distributions = client.mithril_stake_distribution().list().await.unwrap();
+        let distribution = client
+            .mithril_stake_distribution()
+            .get(&distributions[0].hash)
+            .await
+            .unwrap();
+        let snapshots = client.snapshot().list().await.unwrap();
+        let snapshot = client.snapshot().get(&snapshots[0].digest).await.unwrap();
+
+        assert_eq!(Some(MithrilCertificate::dummy()), certificate);
+        assert_eq!(Some(MithrilStakeDistribution::dummy()), distribution);
+        assert_eq!(Some(Snapshot::dummy()), snapshot);
+        assert_eq!(6, aggregator_client.received_requests().len());
+    }
+
+    #[tokio::test]
+    async fn request_without_response_fails_as_not_found() {
+        let snapshot = Snapshot {
+            digest: "custom-digest".to_string(),
+            ..Snapshot::dummy()
+        };
+        let aggregator_client = Arc::new(FakeAggregatorClient::new().with_snapshot(&snapshot));
+        let client = build_client(aggregator_client.clone());
+
+        assert_eq!(
+            Some(snapshot),
+            client.snapshot().get("custom-digest").await.unwrap()
+        );
+        assert_eq!(None, client.snapshot().get("unknown").await.unwrap());
+        assert_eq!(
+            vec![
+                AggregatorRequest::GetSnapshot {
+                    digest: "custom-digest".to_string()
+                },
+                AggregatorRequest::GetSnapshot {
+                    digest: "unknown".to_string()
+                },
+            ],
+            aggregator_client.received_requests()
+        );
+    }
+
+    #[tokio::test]
+    async fn serve_the_fixtures_of_a_directory() {
+        let dir = test_utils::get_test_directory(
+            "fake_aggregator_client",
+            "serve_the_fixtures_of_a_directory",
+        );
+        let snapshot = Snapshot::dummy();
+        std::fs::create_dir_all(dir.join("artifact").join("snapshot")).unwrap();
+        std::fs::write(
+            dir.join("artifact")
+                .join("snapshot")
+                .join(format!("{}.json", snapshot.digest)),
+            to_json(&snapshot),
+        )
+        .unwrap();
+        std::fs::write(dir.join("README.md"), "not a fixture").unwrap();
+        let aggregator_client = Arc::new(FakeAggregatorClient::with_fixtures_dir(&dir).unwrap());
+        let client = build_client(aggregator_client.clone());
+
+        assert_eq!(
+            Some(snapshot.clone()),
+            client.snapshot().get(&snapshot.digest).await.unwrap()
+        );
+        assert_eq!(1, aggregator_client.responses().len());
+    }
+}
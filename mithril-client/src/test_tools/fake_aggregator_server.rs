@@ -0,0 +1,125 @@
+use anyhow::{anyhow, Context};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use tokio::sync::oneshot;
+use warp::http::StatusCode;
+use warp::path::FullPath;
+use warp::Filter;
+
+use crate::test_tools::FakeAggregatorClient;
+use crate::MithrilResult;
+
+/// An in-process HTTP server that serves the responses of a [FakeAggregatorClient], so the HTTP
+/// path of a [Client][crate::Client] can be tested without a real aggregator.
+///
+/// A route without response is answered with a `404`. The server listens on a random local port
+/// and is stopped when dropped.
+///
+/// **Note:** _Available using crate feature_ **fake_aggregator_server**.
+pub struct FakeAggregatorServer {
+    address: SocketAddr,
+    received_routes: Arc<Mutex<Vec<String>>>,
+    shutdown_sender: Option<oneshot::Sender<()>>,
+}
+
+impl FakeAggregatorServer {
+    /// Start a server that serves the responses of the given client.
+    ///
+    /// Must be called within a tokio runtime.
+    pub async fn start(responses: FakeAggregatorClient) -> MithrilResult<Self> {
+        let responses: Arc<HashMap<String, String>> = Arc::new(responses.responses().clone());
+        let received_routes = Arc::new(Mutex::new(vec![]));
+        let routes = {
+            let received_routes = received_routes.clone();
+            warp::get()
+                .and(warp::path::full())
+                .and(warp::query::raw().or(warp::any().map(String::new)).unify())
+                .map(move |path: FullPath, query: String| {
+                    let route = match query.as_str() {
+                        "" => path.as_str().trim_start_matches('/').to_string(),
+                        query => format!("{}?{query}", path.as_str().trim_start_matches('/')),
+                    };
+                    received_routes.lock().unwrap().push(route.clone());
+
+                    match responses.get(&route) {
+                        Some(content) => warp::http::Response::builder()
+                            .status(StatusCode::OK)
+                            .header("content-type", "application/json")
+                            .body(content.clone()),
+                        None => warp::http::Response::builder()
+                            .status(StatusCode::NOT_FOUND)
+                            .body(String::new()),
+                    }
+                })
+        };
+        let (shutdown_sender, shutdown_receiver) = oneshot::channel::<()>();
+        let (address, server) = warp::serve(routes)
+            .try_bind_with_graceful_shutdown(([127, 0, 0, 1], 0), async {
+                shutdown_receiver.await.ok();
+            })
+            .map_err(|e| anyhow!(e))
+            .with_context(|| "Could not bind the fake aggregator server")?;
+        tokio::spawn(server);
+
+        Ok(Self {
+            address,
+            received_routes,
+            shutdown_sender: Some(shutdown_sender),
+        })
+    }
+
+    /// Url of the server, to use as the aggregator endpoint of a [ClientBuilder][crate::ClientBuilder].
+    pub fn url(&self) -> String {
+        format!("http://{}/", self.address)
+    }
+
+    /// Routes requested to the server, in the order they were received.
+    pub fn received_routes(&self) -> Vec<String> {
+        self.received_routes.lock().unwrap().clone()
+    }
+}
+
+impl Drop for FakeAggregatorServer {
+    fn drop(&mut self) {
+        if let Some(shutdown_sender) = self.shutdown_sender.take() {
+            let _ = shutdown_sender.send(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::certificate_client::MockCertificateVerifier;
+    use crate::{ClientBuilder, Snapshot};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn serve_the_fake_responses_over_http() {
+        let server = FakeAggregatorServer::start(FakeAggregatorClient::with_fake_responses())
+            .await
+            .unwrap();
+        let client = ClientBuilder::aggregator(&server.url(), "genesis_verification_key")
+            .with_certificate_verifier(Arc::new(MockCertificateVerifier::new()))
+            .build()
+            .unwrap();
+
+        let snapshots = client.snapshot().list().await.unwrap();
+        let snapshot = client.snapshot().get(&snapshots[0].digest).await.unwrap();
+        let unknown_snapshot = client.snapshot().get("unknown").await.unwrap();
+
+        assert_eq!(Some(Snapshot::dummy()), snapshot);
+        assert_eq!(None, unknown_snapshot);
+        assert_eq!(
+            vec![
+                "artifact/snapshots".to_string(),
+                format!("artifact/snapshot/{}", Snapshot::dummy().digest),
+                "artifact/snapshot/unknown".to_string(),
+            ],
+            server.received_routes()
+        );
+    }
+}
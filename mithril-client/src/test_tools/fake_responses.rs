@@ -0,0 +1,75 @@
+//! Pre serialized responses of an aggregator.
+//!
+//! The responses are consistent with each other: the items of the lists can be fetched using
+//! their hash or digest.
+
+use crate::{
+    MithrilCertificate, MithrilCertificateListItem, MithrilStakeDistribution,
+    MithrilStakeDistributionListItem, Snapshot, SnapshotListItem,
+};
+
+fn to_json<T: serde::Serialize>(value: &T) -> String {
+    serde_json::to_string(value).expect("fake responses should be serializable")
+}
+
+/// Response of the `certificates` route
+pub fn certificate_list() -> String {
+    to_json(&vec![MithrilCertificateListItem::dummy()])
+}
+
+/// Response of the `certificate/{hash}` route for the certificate listed in [certificate_list]
+pub fn certificate() -> String {
+    to_json(&MithrilCertificate::dummy())
+}
+
+/// Response of the `artifact/mithril-stake-distributions` route
+pub fn mithril_stake_distribution_list() -> String {
+    to_json(&vec![MithrilStakeDistributionListItem::dummy()])
+}
+
+/// Response of the `artifact/mithril-stake-distribution/{hash}` route for the Mithril stake
+/// distribution listed in [mithril_stake_distribution_list]
+pub fn mithril_stake_distribution() -> String {
+    to_json(&MithrilStakeDistribution::dummy())
+}
+
+/// Response of the `artifact/snapshots` route
+pub fn snapshot_list() -> String {
+    to_json(&vec![SnapshotListItem::dummy()])
+}
+
+/// Response of the `artifact/snapshot/{digest}` route for the snapshot listed in [snapshot_list]
+pub fn snapshot() -> String {
+    to_json(&Snapshot::dummy())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::schema_validation::OpenApiSchemaValidator;
+
+    use super::*;
+
+    #[test]
+    fn fake_responses_match_the_open_api_specification() {
+        let validator = OpenApiSchemaValidator::from_embedded_specification().unwrap();
+        let responses = [
+            ("certificates", certificate_list()),
+            ("certificate/hash", certificate()),
+            (
+                "artifact/mithril-stake-distributions",
+                mithril_stake_distribution_list(),
+            ),
+            (
+                "artifact/mithril-stake-distribution/hash-123",
+                mithril_stake_distribution(),
+            ),
+            ("artifact/snapshots", snapshot_list()),
+            ("artifact/snapshot/digest", snapshot()),
+        ];
+
+        for (route, content) in responses {
+            let report = validator.validate_response(route, &content).unwrap();
+            assert!(report.is_valid(), "{report}");
+        }
+    }
+}
@@ -0,0 +1,48 @@
+//! Tools to test applications built on the client without a real aggregator.
+//!
+//! They contain:
+//! * Some precomputed [fake keys][fake_keys]
+//! * [Fake responses][fake_responses] of an aggregator, built from the same data as the golden
+//!   messages of the client
+//! * A [FakeAggregatorClient] that serves those responses to a [Client][crate::Client]
+//! * A generator of synthetic [certificate chains][certificate_chain], valid or tampered
+//! * A generator of deterministic [test vectors][test_vectors] of the certificate chain
+//!   verification, written as JSON files to cross-check other implementations
+//! * A `FakeAggregatorServer` that serves the responses of a [FakeAggregatorClient] over HTTP,
+//!   available using crate feature **fake_aggregator_server**
+//!
+//! **Note:** _Available using crate feature_ **test_tools**.
+//!
+//! # Test an application against the fake responses
+//!
+//! ```no_run
+//! # async fn run() -> mithril_client::MithrilResult<()> {
+//! use mithril_client::test_tools::FakeAggregatorClient;
+//! use mithril_client::ClientBuilder;
+//! use std::sync::Arc;
+//!
+//! let aggregator_client = Arc::new(FakeAggregatorClient::with_fake_responses());
+//! let client = ClientBuilder::new("YOUR_GENESIS_VERIFICATION_KEY")
+//!     .with_aggregator_client(aggregator_client.clone())
+//!     .build()?;
+//!
+//! let snapshots = client.snapshot().list().await?;
+//!
+//! assert_eq!(1, snapshots.len());
+//! assert_eq!(1, aggregator_client.received_requests().len());
+//! #    Ok(())
+//! # }
+//! ```
+
+pub mod certificate_chain;
+mod fake_aggregator_client;
+#[cfg(any(test, feature = "fake_aggregator_server"))]
+mod fake_aggregator_server;
+pub mod fake_keys;
+pub mod fake_responses;
+pub mod test_vectors;
+
+pub use fake_aggregator_client::FakeAggregatorClient;
+#[cfg(any(test, feature = "fake_aggregator_server"))]
+#[cfg_attr(docsrs, doc(cfg(feature = "fake_aggregator_server")))]
+pub use fake_aggregator_server::FakeAggregatorServer;
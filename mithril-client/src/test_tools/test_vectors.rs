@@ -0,0 +1,380 @@
+//! Generate deterministic test vectors of the certificate chain verification, so other
+//! implementations of the client (ie: in JavaScript or Go) can be cross-checked against this
+//! crate.
+//!
+//! A [TestVectorsGenerator] builds a valid [certificate chain][super::certificate_chain] and a
+//! tampered copy of it for each [defect][CertificateChainTampering], then writes them to a
+//! directory along with the snapshots certified by the valid chain:
+//!
+//! ```text
+//! <directory>/
+//! ├── manifest.json                  the genesis verification key and the expected results
+//! ├── valid/
+//! │   ├── certificates.json          the certificates, from the latest to the genesis one
+//! │   └── snapshots.json             the snapshots certified by the standard certificates
+//! └── <tampering>/
+//!     └── certificates.json
+//! ```
+//!
+//! The vectors only depend on the parameters of the generator: generating them twice gives the
+//! same files.
+//!
+//! # Generate vectors for 3 epochs
+//!
+//! ```no_run
+//! # fn run() -> mithril_client::MithrilResult<()> {
+//! use mithril_client::test_tools::test_vectors::TestVectorsGenerator;
+//! use std::path::Path;
+//!
+//! let manifest = TestVectorsGenerator::new()
+//!     .with_epochs(3)
+//!     .with_signers_per_epoch(5)
+//!     .generate(Path::new("test_vectors"))?;
+//!
+//! println!("{} vectors generated", manifest.vectors.len());
+//! #    Ok(())
+//! # }
+//! ```
+
+use anyhow::{anyhow, Context};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::common::entities::{ProtocolMessagePartKey, ProtocolParameters};
+use crate::test_tools::certificate_chain::{
+    CertificateChainBuilder, CertificateChainFixture, CertificateChainTampering,
+};
+use crate::{MithrilResult, Snapshot};
+
+const MANIFEST_FILE: &str = "manifest.json";
+const CERTIFICATES_FILE: &str = "certificates.json";
+const SNAPSHOTS_FILE: &str = "snapshots.json";
+const VALID_VECTOR_NAME: &str = "valid";
+
+/// Parameters the test vectors were generated with.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TestVectorsParameters {
+    /// Number of epochs following the epoch of the genesis certificate
+    pub epochs: usize,
+
+    /// Number of standard certificates of each epoch
+    pub certificates_per_epoch: usize,
+
+    /// Number of signers of each epoch
+    pub signers_per_epoch: usize,
+
+    /// Protocol parameters used to sign the certificates
+    pub protocol_parameters: ProtocolParameters,
+
+    /// Date at which the genesis certificate was sealed
+    pub genesis_sealed_at: DateTime<Utc>,
+}
+
+/// A certificate chain of the test vectors and the expected result of its verification.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TestVector {
+    /// Name of the vector, also the name of its directory
+    pub name: String,
+
+    /// What the vector checks
+    pub description: String,
+
+    /// Hash of the certificate the verification of the chain starts from
+    pub latest_certificate_hash: String,
+
+    /// `true` if the verification of the chain must succeed
+    pub expected_valid: bool,
+}
+
+/// Content of the `manifest.json` file of the test vectors.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TestVectorsManifest {
+    /// Parameters the vectors were generated with
+    pub parameters: TestVectorsParameters,
+
+    /// Genesis verification key that verifies the genesis certificates of all the vectors
+    pub genesis_verification_key: String,
+
+    /// The generated vectors
+    pub vectors: Vec<TestVector>,
+}
+
+/// Generate the test vectors of the certificate chain verification, see
+/// [module documentation][self].
+#[derive(Debug, Clone)]
+pub struct TestVectorsGenerator {
+    parameters: TestVectorsParameters,
+}
+
+impl Default for TestVectorsGenerator {
+    fn default() -> Self {
+        Self {
+            parameters: TestVectorsParameters {
+                epochs: 2,
+                certificates_per_epoch: 2,
+                signers_per_epoch: 3,
+                protocol_parameters: ProtocolParameters::new(5, 100, 0.65),
+                genesis_sealed_at: DateTime::parse_from_rfc3339("2024-01-01T00:00:00Z")
+                    .unwrap()
+                    .with_timezone(&Utc),
+            },
+        }
+    }
+}
+
+impl TestVectorsGenerator {
+    /// Constructs a new `TestVectorsGenerator` of chains of 2 epochs of 2 certificates signed
+    /// by 3 signers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the number of epochs following the epoch of the genesis certificate.
+    pub fn with_epochs(mut self, epochs: usize) -> Self {
+        self.parameters.epochs = epochs;
+        self
+    }
+
+    /// Set the number of standard certificates of each epoch.
+    pub fn with_certificates_per_epoch(mut self, certificates_per_epoch: usize) -> Self {
+        self.parameters.certificates_per_epoch = certificates_per_epoch;
+        self
+    }
+
+    /// Set the number of signers of each epoch.
+    pub fn with_signers_per_epoch(mut self, signers_per_epoch: usize) -> Self {
+        self.parameters.signers_per_epoch = signers_per_epoch;
+        self
+    }
+
+    /// Set the protocol parameters used to sign the certificates.
+    pub fn with_protocol_parameters(mut self, protocol_parameters: ProtocolParameters) -> Self {
+        self.parameters.protocol_parameters = protocol_parameters;
+        self
+    }
+
+    /// Set the date at which the genesis certificate is sealed, `2024-01-01T00:00:00Z` by
+    /// default.
+    pub fn with_genesis_sealed_at(mut self, genesis_sealed_at: DateTime<Utc>) -> Self {
+        self.parameters.genesis_sealed_at = genesis_sealed_at;
+        self
+    }
+
+    /// Generate the test vectors in the given directory, created if it doesn't exist, and
+    /// return their manifest.
+    pub fn generate(&self, directory: &Path) -> MithrilResult<TestVectorsManifest> {
+        let total_certificates =
+            1 + self.parameters.epochs * self.parameters.certificates_per_epoch;
+        if total_certificates < 3 {
+            return Err(anyhow!(
+                "Test vectors need at least two standard certificates to tamper a certificate that is neither the genesis nor the latest one"
+            ));
+        }
+
+        let valid_chain = self.chain_builder(total_certificates).build()?;
+        write_json(
+            &directory.join(VALID_VECTOR_NAME).join(CERTIFICATES_FILE),
+            &valid_chain.certificates,
+        )?;
+        write_json(
+            &directory.join(VALID_VECTOR_NAME).join(SNAPSHOTS_FILE),
+            &certified_snapshots(&valid_chain)?,
+        )?;
+        let mut vectors = vec![TestVector {
+            name: VALID_VECTOR_NAME.to_string(),
+            description: "A valid certificate chain".to_string(),
+            latest_certificate_hash: valid_chain.latest_certificate().hash.clone(),
+            expected_valid: true,
+        }];
+
+        for (name, description, tampering) in tamperings() {
+            let chain = self
+                .chain_builder(total_certificates)
+                .with_tampering(tampering)
+                .build()?;
+            write_json(
+                &directory.join(name).join(CERTIFICATES_FILE),
+                &chain.certificates,
+            )?;
+            vectors.push(TestVector {
+                name: name.to_string(),
+                description: description.to_string(),
+                latest_certificate_hash: chain.latest_certificate().hash.clone(),
+                expected_valid: false,
+            });
+        }
+
+        let manifest = TestVectorsManifest {
+            parameters: self.parameters.clone(),
+            genesis_verification_key: valid_chain.genesis_verification_key,
+            vectors,
+        };
+        write_json(&directory.join(MANIFEST_FILE), &manifest)?;
+
+        Ok(manifest)
+    }
+
+    fn chain_builder(&self, total_certificates: usize) -> CertificateChainBuilder {
+        CertificateChainBuilder::new()
+            .with_total_certificates(total_certificates)
+            .with_certificates_per_epoch(self.parameters.certificates_per_epoch)
+            .with_signers_per_epoch(self.parameters.signers_per_epoch)
+            .with_protocol_parameters(self.parameters.protocol_parameters.clone())
+            .with_genesis_sealed_at(self.parameters.genesis_sealed_at)
+    }
+}
+
+/// Name, description and defect of the tampered vectors, the tampered certificate being the
+/// one before the latest certificate so the defect is only found by following the chain.
+fn tamperings() -> [(&'static str, &'static str, CertificateChainTampering); 4] {
+    [
+        (
+            "tampered_hash",
+            "The hash of a certificate doesn't match its content",
+            CertificateChainTampering::Hash { position: 1 },
+        ),
+        (
+            "tampered_aggregate_verification_key",
+            "The aggregate verification key of a certificate is not the one announced by its previous certificate",
+            CertificateChainTampering::AggregateVerificationKey { position: 1 },
+        ),
+        (
+            "tampered_multi_signature",
+            "The multi-signature of a certificate signs another message",
+            CertificateChainTampering::MultiSignature { position: 1 },
+        ),
+        (
+            "tampered_genesis_signature",
+            "The genesis signature of the genesis certificate signs another message",
+            CertificateChainTampering::GenesisSignature,
+        ),
+    ]
+}
+
+/// A snapshot for each standard certificate of the chain, its digest being the one signed by
+/// the certificate.
+fn certified_snapshots(chain: &CertificateChainFixture) -> MithrilResult<Vec<Snapshot>> {
+    chain
+        .certificates
+        .iter()
+        .filter(|certificate| certificate.genesis_signature.is_empty())
+        .map(|certificate| {
+            let digest = certificate
+                .protocol_message
+                .get_message_part(&ProtocolMessagePartKey::SnapshotDigest)
+                .with_context(|| {
+                    format!(
+                        "Certificate '{}' signs no snapshot digest",
+                        certificate.hash
+                    )
+                })?;
+
+            Ok(Snapshot {
+                digest: digest.clone(),
+                beacon: certificate.beacon.clone(),
+                certificate_hash: certificate.hash.clone(),
+                created_at: certificate.metadata.sealed_at,
+                ..Snapshot::default()
+            })
+        })
+        .collect()
+}
+
+fn write_json<T: Serialize>(path: &Path, value: &T) -> MithrilResult<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Could not create directory '{}'", parent.display()))?;
+    }
+    let content = serde_json::to_string_pretty(value)
+        .with_context(|| format!("Could not serialize '{}'", path.display()))?;
+
+    std::fs::write(path, content).with_context(|| format!("Could not write '{}'", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::test_tools::FakeAggregatorClient;
+    use crate::{test_utils, ClientBuilder, MithrilCertificate};
+
+    use super::*;
+
+    fn read_certificates(directory: &Path, vector: &TestVector) -> Vec<MithrilCertificate> {
+        let content = std::fs::read(directory.join(&vector.name).join(CERTIFICATES_FILE)).unwrap();
+        serde_json::from_slice(&content).unwrap()
+    }
+
+    #[tokio::test]
+    async fn generated_vectors_are_verified_as_expected() {
+        let directory = test_utils::get_test_directory(
+            "test_vectors",
+            "generated_vectors_are_verified_as_expected",
+        );
+
+        let manifest = TestVectorsGenerator::new().generate(&directory).unwrap();
+
+        assert_eq!(5, manifest.vectors.len());
+        for vector in &manifest.vectors {
+            let certificates = read_certificates(&directory, vector);
+            let client = ClientBuilder::new(&manifest.genesis_verification_key)
+                .with_aggregator_client(Arc::new(
+                    FakeAggregatorClient::new().with_certificate_chain(&certificates),
+                ))
+                .build()
+                .unwrap();
+
+            let result = client
+                .certificate()
+                .verify_chain(&vector.latest_certificate_hash)
+                .await;
+
+            assert_eq!(
+                vector.expected_valid,
+                result.is_ok(),
+                "unexpected verification result for vector '{}': {result:?}",
+                vector.name
+            );
+        }
+        let snapshots: Vec<Snapshot> = serde_json::from_slice(
+            &std::fs::read(directory.join(VALID_VECTOR_NAME).join(SNAPSHOTS_FILE)).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(4, snapshots.len());
+    }
+
+    #[test]
+    fn generating_twice_gives_the_same_vectors() {
+        let directory = test_utils::get_test_directory(
+            "test_vectors",
+            "generating_twice_gives_the_same_vectors",
+        );
+        let generator = TestVectorsGenerator::new().with_epochs(1);
+
+        let first = generator.generate(&directory.join("first")).unwrap();
+        let second = generator.generate(&directory.join("second")).unwrap();
+
+        assert_eq!(first, second);
+        for vector in &first.vectors {
+            assert_eq!(
+                read_certificates(&directory.join("first"), vector),
+                read_certificates(&directory.join("second"), vector),
+            );
+        }
+    }
+
+    #[test]
+    fn reject_chains_too_short_to_be_tampered() {
+        let directory = test_utils::get_test_directory(
+            "test_vectors",
+            "reject_chains_too_short_to_be_tampered",
+        );
+
+        TestVectorsGenerator::new()
+            .with_epochs(1)
+            .with_certificates_per_epoch(1)
+            .generate(&directory)
+            .expect_err("A chain of 2 certificates should be rejected");
+    }
+}
@@ -0,0 +1,226 @@
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+use crate::MithrilResult;
+
+const DOWNLOAD_FILE_STEM_PREFIX: &str = "mithril_download_";
+const ARCHIVE_SUFFIX: &str = ".part";
+const CHECKPOINT_SUFFIX: &str = ".checkpoint.json";
+const CHECKPOINT_TEMP_SUFFIX: &str = ".checkpoint.json.tmp";
+
+/// Progress of an archive download, persisted next to the partially downloaded archive so the
+/// download can be resumed by another process.
+///
+/// The archive is downloaded sequentially: its first `downloaded_bytes` are on the disk.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DownloadCheckpoint {
+    /// Location of the downloaded archive
+    pub location: String,
+
+    /// Entity tag of the archive sent by the server, used to ensure that a resumed download
+    /// targets the same archive
+    pub etag: Option<String>,
+
+    /// Expected size of the archive
+    pub archive_size: u64,
+
+    /// Number of bytes of the archive persisted on the disk
+    pub downloaded_bytes: u64,
+}
+
+impl DownloadCheckpoint {
+    /// Constructs a new `DownloadCheckpoint` of a download that didn't start yet.
+    pub fn new(location: &str, archive_size: u64) -> Self {
+        Self {
+            location: location.to_string(),
+            etag: None,
+            archive_size,
+            downloaded_bytes: 0,
+        }
+    }
+
+    /// Load the checkpoint of the download of the given location from the download directory.
+    ///
+    /// A new checkpoint is returned if none exists, if it can't be read, if it doesn't match the
+    /// expected archive size or if the partially downloaded archive is missing bytes.
+    pub fn load(download_dir: &Path, location: &str, archive_size: u64) -> Self {
+        let checkpoint = std::fs::read(Self::checkpoint_path(download_dir, location))
+            .ok()
+            .and_then(|content| serde_json::from_slice::<Self>(&content).ok())
+            .filter(|checkpoint| {
+                checkpoint.location == location && checkpoint.archive_size == archive_size
+            });
+        let archive_len = std::fs::metadata(Self::archive_path(download_dir, location))
+            .map(|metadata| metadata.len())
+            .unwrap_or_default();
+
+        match checkpoint {
+            Some(checkpoint) if checkpoint.downloaded_bytes <= archive_len => checkpoint,
+            _ => Self::new(location, archive_size),
+        }
+    }
+
+    /// Persist the checkpoint in the download directory.
+    pub fn save(&self, download_dir: &Path) -> MithrilResult<()> {
+        let path = Self::checkpoint_path(download_dir, &self.location);
+        let temp_path = path.with_extension("json.tmp");
+        std::fs::write(&temp_path, serde_json::to_vec(self)?).with_context(|| {
+            format!(
+                "Could not write download checkpoint to '{}'",
+                temp_path.display()
+            )
+        })?;
+        std::fs::rename(&temp_path, &path).with_context(|| {
+            format!(
+                "Could not write download checkpoint to '{}'",
+                path.display()
+            )
+        })
+    }
+
+    /// Returns `true` if the whole archive has been downloaded.
+    pub fn is_complete(&self) -> bool {
+        self.archive_size > 0 && self.downloaded_bytes >= self.archive_size
+    }
+
+    /// Remove the checkpoint and the downloaded archive from the download directory.
+    pub fn remove(&self, download_dir: &Path) -> MithrilResult<()> {
+        for path in [
+            Self::checkpoint_path(download_dir, &self.location),
+            Self::archive_path(download_dir, &self.location),
+        ] {
+            if path.exists() {
+                std::fs::remove_file(&path).with_context(|| {
+                    format!("Could not remove download file '{}'", path.display())
+                })?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Path of the partially downloaded archive of the given location.
+    pub fn archive_path(download_dir: &Path, location: &str) -> PathBuf {
+        download_dir.join(format!(".{}{ARCHIVE_SUFFIX}", Self::file_stem(location)))
+    }
+
+    /// Stem shared by the files of a download, if the given file name is the one of a
+    /// partially downloaded archive or of a checkpoint.
+    pub fn download_file_stem(file_name: &str) -> Option<&str> {
+        let name = file_name.strip_prefix('.')?;
+        let stem = [CHECKPOINT_TEMP_SUFFIX, CHECKPOINT_SUFFIX, ARCHIVE_SUFFIX]
+            .iter()
+            .find_map(|suffix| name.strip_suffix(suffix))?;
+
+        stem.starts_with(DOWNLOAD_FILE_STEM_PREFIX).then_some(stem)
+    }
+
+    /// Paths of all the files that a download with the given stem can leave in the download
+    /// directory: its partially downloaded archive, its checkpoint and its temporary checkpoint.
+    pub fn download_file_paths(download_dir: &Path, stem: &str) -> [PathBuf; 3] {
+        [ARCHIVE_SUFFIX, CHECKPOINT_SUFFIX, CHECKPOINT_TEMP_SUFFIX]
+            .map(|suffix| download_dir.join(format!(".{stem}{suffix}")))
+    }
+
+    /// Read the checkpoint of the download with the given stem, `None` if it's missing or
+    /// can't be read.
+    pub fn read_from_stem(download_dir: &Path, stem: &str) -> Option<Self> {
+        std::fs::read(download_dir.join(format!(".{stem}{CHECKPOINT_SUFFIX}")))
+            .ok()
+            .and_then(|content| serde_json::from_slice(&content).ok())
+    }
+
+    fn checkpoint_path(download_dir: &Path, location: &str) -> PathBuf {
+        download_dir.join(format!(".{}{CHECKPOINT_SUFFIX}", Self::file_stem(location)))
+    }
+
+    fn file_stem(location: &str) -> String {
+        let location_hash = hex::encode(Sha256::digest(location.as_bytes()));
+        format!("{DOWNLOAD_FILE_STEM_PREFIX}{}", &location_hash[..16])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::test_utils;
+
+    use super::*;
+
+    fn checkpoint(downloaded_bytes: u64) -> DownloadCheckpoint {
+        DownloadCheckpoint {
+            etag: Some("\"etag\"".to_string()),
+            downloaded_bytes,
+            ..DownloadCheckpoint::new("http://host/snapshot.tar.gz", 10)
+        }
+    }
+
+    #[test]
+    fn load_a_saved_checkpoint() {
+        let dir = test_utils::get_test_directory("download_checkpoint", "load_a_saved_checkpoint");
+        let checkpoint = checkpoint(4);
+        fs::write(
+            DownloadCheckpoint::archive_path(&dir, &checkpoint.location),
+            b"0123",
+        )
+        .unwrap();
+        checkpoint.save(&dir).unwrap();
+
+        let loaded = DownloadCheckpoint::load(&dir, &checkpoint.location, 10);
+
+        assert_eq!(checkpoint, loaded);
+    }
+
+    #[test]
+    fn load_a_new_checkpoint_if_the_saved_one_does_not_match() {
+        let dir = test_utils::get_test_directory(
+            "download_checkpoint",
+            "load_a_new_checkpoint_if_the_saved_one_does_not_match",
+        );
+        let checkpoint = checkpoint(4);
+        fs::write(
+            DownloadCheckpoint::archive_path(&dir, &checkpoint.location),
+            b"01",
+        )
+        .unwrap();
+        checkpoint.save(&dir).unwrap();
+
+        assert_eq!(
+            DownloadCheckpoint::new(&checkpoint.location, 10),
+            DownloadCheckpoint::load(&dir, &checkpoint.location, 10),
+            "the archive on the disk is missing bytes"
+        );
+        assert_eq!(
+            DownloadCheckpoint::new(&checkpoint.location, 20),
+            DownloadCheckpoint::load(&dir, &checkpoint.location, 20),
+            "the archive size changed"
+        );
+        assert_eq!(
+            DownloadCheckpoint::new("http://host/other.tar.gz", 10),
+            DownloadCheckpoint::load(&dir, "http://host/other.tar.gz", 10),
+            "no checkpoint for this location"
+        );
+    }
+
+    #[test]
+    fn remove_the_checkpoint_and_the_archive() {
+        let dir = test_utils::get_test_directory(
+            "download_checkpoint",
+            "remove_the_checkpoint_and_the_archive",
+        );
+        let checkpoint = checkpoint(4);
+        fs::write(
+            DownloadCheckpoint::archive_path(&dir, &checkpoint.location),
+            b"0123",
+        )
+        .unwrap();
+        checkpoint.save(&dir).unwrap();
+
+        checkpoint.remove(&dir).unwrap();
+
+        assert_eq!(0, fs::read_dir(&dir).unwrap().count());
+    }
+}
@@ -0,0 +1,184 @@
+//! Pipeline streaming a downloaded archive to its unpacker:
+//! network stream → [HashingTee] → bounded channel → decompressor → tar unpacker.
+//!
+//! The archive is hashed while it's streamed and at most [PIPELINE_BUFFER_CHUNKS] chunks of at
+//! most [PIPELINE_CHUNK_SIZE] bytes are buffered between the download and the unpacking: the
+//! memory used doesn't depend on the size of the archive.
+
+use anyhow::Context;
+use flume::{Receiver, Sender};
+use futures::{Stream, StreamExt};
+use sha2::{Digest, Sha256};
+use std::io::{self, Read};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::MithrilResult;
+
+/// Maximum number of chunks buffered between the download and the unpacking of an archive.
+pub const PIPELINE_BUFFER_CHUNKS: usize = 5;
+
+/// Maximum size of the chunks sent to the unpacker, bigger chunks are split.
+pub const PIPELINE_CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Time spent in each stage of the pipeline downloading and unpacking an archive.
+///
+/// The stages run concurrently: their timings overlap and don't add up to the total.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PipelineStageTimings {
+    /// Time spent waiting for the archive bytes: from the network, or from the disk for a
+    /// resumed download
+    pub network: Duration,
+
+    /// Time spent hashing the archive
+    pub hashing: Duration,
+
+    /// Time the download waited for the unpacker to catch up, the buffers being full
+    pub backpressure: Duration,
+
+    /// Time spent decompressing the archive
+    pub decompression: Duration,
+
+    /// Time spent reading the tar entries and writing the unpacked files to the disk
+    pub unpacking: Duration,
+
+    /// Total duration of the download and unpacking
+    pub total: Duration,
+}
+
+/// Tee hashing an archive while sending it, through a bounded channel, to its unpacker.
+pub struct HashingTee {
+    sender: Sender<Vec<u8>>,
+    hasher: Sha256,
+    sent_bytes: u64,
+    timings: PipelineStageTimings,
+}
+
+impl HashingTee {
+    /// Create a tee and the receiver to give to the unpacker.
+    pub fn channel() -> (Self, Receiver<Vec<u8>>) {
+        let (sender, receiver) = flume::bounded(PIPELINE_BUFFER_CHUNKS);
+        let tee = Self {
+            sender,
+            hasher: Sha256::new(),
+            sent_bytes: 0,
+            timings: PipelineStageTimings::default(),
+        };
+
+        (tee, receiver)
+    }
+
+    /// Wait for the next item of the given stream, the time waited is accounted as
+    /// [network][PipelineStageTimings::network] time.
+    pub async fn pull<S: Stream + Unpin>(&mut self, stream: &mut S) -> Option<S::Item> {
+        let start = Instant::now();
+        let item = stream.next().await;
+        self.timings.network += start.elapsed();
+
+        item
+    }
+
+    /// Account the given duration as [network][PipelineStageTimings::network] time.
+    pub fn add_network_time(&mut self, duration: Duration) {
+        self.timings.network += duration;
+    }
+
+    /// Hash the given chunk then send it to the unpacker, waiting while the buffers are full.
+    pub async fn send(&mut self, chunk: &[u8]) -> MithrilResult<()> {
+        let start = Instant::now();
+        self.hasher.update(chunk);
+        self.timings.hashing += start.elapsed();
+
+        for part in chunk.chunks(PIPELINE_CHUNK_SIZE) {
+            let start = Instant::now();
+            self.sender
+                .send_async(part.to_vec())
+                .await
+                .with_context(|| {
+                    format!("Download: could not write {} bytes to stream.", part.len())
+                })?;
+            self.timings.backpressure += start.elapsed();
+        }
+        self.sent_bytes += chunk.len() as u64;
+
+        Ok(())
+    }
+
+    /// Number of bytes sent so far.
+    pub fn sent_bytes(&self) -> u64 {
+        self.sent_bytes
+    }
+
+    /// Close the channel, signaling the end of the archive to the unpacker, and return the hex
+    /// encoded SHA256 digest of the archive with the timings of the download side stages.
+    pub fn finish(self) -> (String, PipelineStageTimings) {
+        (hex::encode(self.hasher.finalize()), self.timings)
+    }
+}
+
+/// Accumulator of the time spent in a stage, shareable between threads.
+#[derive(Debug, Clone, Default)]
+pub struct StageClock(Arc<AtomicU64>);
+
+impl StageClock {
+    /// Time accumulated so far.
+    pub fn elapsed(&self) -> Duration {
+        Duration::from_nanos(self.0.load(Ordering::Relaxed))
+    }
+
+    fn add(&self, duration: Duration) {
+        self.0
+            .fetch_add(duration.as_nanos() as u64, Ordering::Relaxed);
+    }
+}
+
+/// Reader accumulating the time spent reading its inner reader in a [StageClock].
+pub struct TimedReader<R> {
+    inner: R,
+    clock: StageClock,
+}
+
+impl<R: Read> TimedReader<R> {
+    /// Constructs a new `TimedReader`.
+    pub fn new(inner: R, clock: StageClock) -> Self {
+        Self { inner, clock }
+    }
+}
+
+impl<R: Read> Read for TimedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let start = Instant::now();
+        let result = self.inner.read(buf);
+        self.clock.add(start.elapsed());
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn hash_the_archive_while_sending_it_in_bounded_chunks() {
+        let (mut tee, receiver) = HashingTee::channel();
+        let archive = vec![7u8; PIPELINE_CHUNK_SIZE * 2 + 10];
+        let stream_archive = archive.clone();
+
+        let unpacker = std::thread::spawn(move || {
+            let chunks = receiver.iter().collect::<Vec<_>>();
+            assert!(chunks
+                .iter()
+                .all(|chunk| chunk.len() <= PIPELINE_CHUNK_SIZE));
+            chunks.concat()
+        });
+        tee.send(&stream_archive).await.unwrap();
+        let sent_bytes = tee.sent_bytes();
+        let (digest, _timings) = tee.finish();
+
+        assert_eq!(archive, unpacker.join().unwrap());
+        assert_eq!(archive.len() as u64, sent_bytes);
+        assert_eq!(hex::encode(Sha256::digest(&archive)), digest);
+    }
+}
@@ -0,0 +1,47 @@
+use anyhow::Context;
+
+use crate::connection_pool::ConnectionPoolConfig;
+use crate::dns::DnsConfig;
+use crate::proxy::ProxyConfig;
+use crate::tls::{SpkiPins, TlsConfig};
+use crate::MithrilResult;
+
+/// Transport settings shared by the http clients of the crate, kept so a client can be rebuilt
+/// each time one of them changes.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct HttpClientSettings {
+    pub proxy: Option<ProxyConfig>,
+    pub tls: Option<TlsConfig>,
+    pub connection_pool: Option<ConnectionPoolConfig>,
+    pub dns: Option<DnsConfig>,
+    pub spki_pins: Option<SpkiPins>,
+}
+
+impl HttpClientSettings {
+    /// Build a [reqwest::Client] applying all the settings.
+    pub fn build(&self) -> MithrilResult<reqwest::Client> {
+        let mut builder = reqwest::ClientBuilder::new();
+        if let Some(proxy) = &self.proxy {
+            builder = builder.proxy(proxy.to_reqwest_proxy()?);
+        }
+        match (&self.spki_pins, &self.tls) {
+            // The pins are checked by a rustls verifier that also applies the TLS settings
+            (Some(spki_pins), tls) => {
+                builder =
+                    builder.use_preconfigured_tls(spki_pins.rustls_client_config(tls.as_ref())?);
+            }
+            (None, Some(tls)) => builder = tls.apply_to(builder)?,
+            (None, None) => {}
+        }
+        if let Some(connection_pool) = &self.connection_pool {
+            builder = connection_pool.apply_to(builder);
+        }
+        if let Some(dns) = &self.dns {
+            builder = dns.apply_to(builder);
+        }
+
+        builder
+            .build()
+            .with_context(|| "Building http client failed")
+    }
+}
@@ -0,0 +1,166 @@
+use futures::{stream, Future, Stream};
+use std::collections::{HashSet, VecDeque};
+use std::time::Duration;
+
+use crate::utils::time;
+use crate::MithrilResult;
+
+struct WatchState<T, F> {
+    list: F,
+    key: fn(&T) -> String,
+    interval: Duration,
+    has_polled: bool,
+    is_first_poll: bool,
+    seen_keys: HashSet<String>,
+    pending: VecDeque<T>,
+}
+
+/// Poll the given `list` function every `interval` and stream the items that were not listed
+/// by the previous poll, oldest first.
+///
+/// The items listed by the first successful poll are considered already published and are not
+/// streamed, an item is streamed only once even if it is listed again after leaving the list.
+/// The `list` function is expected to return the items newest first, as the aggregator does.
+/// A listing error is streamed, then the polling goes on.
+pub(crate) fn watch_list<'a, T, F, Fut>(
+    interval: Duration,
+    list: F,
+    key: fn(&T) -> String,
+) -> impl Stream<Item = MithrilResult<T>> + 'a
+where
+    T: 'a,
+    F: Fn() -> Fut + 'a,
+    Fut: Future<Output = MithrilResult<Vec<T>>> + 'a,
+{
+    let state = WatchState {
+        list,
+        key,
+        interval,
+        has_polled: false,
+        is_first_poll: true,
+        seen_keys: HashSet::new(),
+        pending: VecDeque::new(),
+    };
+
+    stream::unfold(state, |mut state| async move {
+        loop {
+            if let Some(item) = state.pending.pop_front() {
+                return Some((Ok(item), state));
+            }
+
+            if state.has_polled {
+                time::sleep(state.interval).await;
+            }
+            state.has_polled = true;
+
+            match (state.list)().await {
+                Ok(items) => {
+                    let new_items: VecDeque<T> = items
+                        .into_iter()
+                        .rev()
+                        .filter(|item| state.seen_keys.insert((state.key)(item)))
+                        .collect();
+                    if !state.is_first_poll {
+                        state.pending = new_items;
+                    }
+                    state.is_first_poll = false;
+                }
+                Err(error) => return Some((Err(error), state)),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::anyhow;
+    use futures::StreamExt;
+    use std::sync::Mutex;
+
+    use super::*;
+
+    fn scripted_list(
+        responses: Vec<MithrilResult<Vec<&'static str>>>,
+    ) -> impl Fn() -> futures::future::Ready<MithrilResult<Vec<&'static str>>> {
+        let responses = Mutex::new(VecDeque::from(responses));
+        move || {
+            let response = responses
+                .lock()
+                .unwrap()
+                .pop_front()
+                .unwrap_or_else(|| Ok(vec![]));
+            futures::future::ready(response)
+        }
+    }
+
+    #[tokio::test]
+    async fn stream_newly_listed_items_oldest_first() {
+        let list = scripted_list(vec![
+            Ok(vec!["b", "a"]),
+            Ok(vec!["b", "a"]),
+            Ok(vec!["d", "c", "b"]),
+            Ok(vec!["e", "d", "c"]),
+        ]);
+
+        let items: Vec<&str> = watch_list(Duration::from_millis(1), list, |item| item.to_string())
+            .take(3)
+            .map(|item| item.unwrap())
+            .collect()
+            .await;
+
+        assert_eq!(vec!["c", "d", "e"], items);
+    }
+
+    #[tokio::test]
+    async fn stream_listing_errors_and_keep_polling() {
+        let list = scripted_list(vec![
+            Ok(vec!["a"]),
+            Err(anyhow!("listing error")),
+            Ok(vec!["b", "a"]),
+        ]);
+
+        let items: Vec<MithrilResult<&str>> =
+            watch_list(Duration::from_millis(1), list, |item| item.to_string())
+                .take(2)
+                .collect()
+                .await;
+
+        assert!(items[0].is_err());
+        assert_eq!("b", items[1].as_ref().unwrap().to_owned());
+    }
+
+    #[tokio::test]
+    async fn items_listed_after_a_failed_first_poll_are_considered_already_published() {
+        let list = scripted_list(vec![
+            Err(anyhow!("listing error")),
+            Ok(vec!["a"]),
+            Ok(vec!["b", "a"]),
+        ]);
+
+        let items: Vec<MithrilResult<&str>> =
+            watch_list(Duration::from_millis(1), list, |item| item.to_string())
+                .take(2)
+                .collect()
+                .await;
+
+        assert!(items[0].is_err());
+        assert_eq!("b", items[1].as_ref().unwrap().to_owned());
+    }
+
+    #[tokio::test]
+    async fn items_listed_again_after_leaving_the_list_are_not_streamed_twice() {
+        let list = scripted_list(vec![
+            Ok(vec!["b", "a"]),
+            Ok(vec!["c", "b"]),
+            Ok(vec!["d", "a"]),
+        ]);
+
+        let items: Vec<&str> = watch_list(Duration::from_millis(1), list, |item| item.to_string())
+            .take(2)
+            .map(|item| item.unwrap())
+            .collect()
+            .await;
+
+        assert_eq!(vec!["c", "d"], items);
+    }
+}
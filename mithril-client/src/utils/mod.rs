@@ -1,10 +1,23 @@
 //! Utilities module
 //! This module contains tools needed mostly for the snapshot download and unpack.
 
+#[cfg(not(target_family = "wasm"))]
+mod http_client;
+mod list_watcher;
+pub(crate) mod time;
+
+#[cfg(not(target_family = "wasm"))]
+pub(crate) use http_client::*;
+pub(crate) use list_watcher::*;
+
 cfg_fs! {
+    mod download_checkpoint;
+    mod download_pipeline;
     mod stream_reader;
     mod unpacker;
 
+    pub use download_checkpoint::*;
+    pub use download_pipeline::*;
     pub use stream_reader::*;
     pub use unpacker::*;
 }
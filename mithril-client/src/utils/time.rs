@@ -0,0 +1,17 @@
+//! Clock and timer that also work on wasm targets, where `std::time::Instant::now` panics and
+//! there is no tokio timer driver.
+
+#[cfg(not(target_family = "wasm"))]
+pub use std::time::Instant;
+#[cfg(target_family = "wasm")]
+pub use web_time::Instant;
+
+use std::time::Duration;
+
+/// Wait for the given duration.
+pub async fn sleep(duration: Duration) {
+    #[cfg(not(target_family = "wasm"))]
+    tokio::time::sleep(duration).await;
+    #[cfg(target_family = "wasm")]
+    gloo_timers::future::sleep(duration).await;
+}
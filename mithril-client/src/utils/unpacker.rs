@@ -1,51 +1,749 @@
-use anyhow::Context;
+use anyhow::{anyhow, Context};
 use flate2::read::GzDecoder;
 use flume::Receiver;
-use std::path::Path;
-use tar::Archive;
+use sha2::{Digest, Sha256};
+use std::fs::OpenOptions;
+use std::io::{self, Read, Write};
+use std::ops::RangeInclusive;
+use std::path::{Component, Path, PathBuf};
+use std::time::Instant;
+use tar::{Archive, Entry, EntryType};
 
-use crate::common::entities::CompressionAlgorithm;
-use crate::utils::StreamReader;
+use crate::common::digesters::ImmutableFile;
+use crate::common::entities::{
+    CompressionAlgorithm, HexEncodedDigest, ImmutableFileName, ImmutableFileNumber,
+};
+use crate::utils::{PipelineStageTimings, StageClock, StreamReader, TimedReader};
 use crate::MithrilResult;
 
+/// Policy applied when an unpacked entry already exists on the disk.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OverwritePolicy {
+    /// Replace the existing file with the one from the archive.
+    #[default]
+    Overwrite,
+
+    /// Keep the existing file and ignore the one from the archive.
+    Skip,
+
+    /// Stop the unpacking with an error.
+    Fail,
+}
+
+/// Options honored when unpacking a snapshot archive.
+///
+/// By default the permissions stored in the archive are kept, the owner is the user running the
+/// unpacking and existing files are overwritten.
+///
+/// Note: modes and ownership are only applied on unix platforms.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UnpackOptions {
+    /// Mode set on the unpacked directories, ie: `0o755`
+    pub dir_mode: Option<u32>,
+
+    /// Mode set on the unpacked files, ie: `0o644`
+    pub file_mode: Option<u32>,
+
+    /// Id of the user that will own the unpacked entries
+    pub uid: Option<u32>,
+
+    /// Id of the group that will own the unpacked entries
+    pub gid: Option<u32>,
+
+    /// Policy applied when an unpacked file already exists
+    pub overwrite_policy: OverwritePolicy,
+
+    /// Only unpack the immutable files whose number is in this range, ie: `0..=1000`, every other
+    /// entry of the archive is skipped.
+    ///
+    /// The unpacked files can be verified with
+    /// [verify_immutable_range][crate::common::digesters::verify_immutable_range].
+    pub immutable_files: Option<RangeInclusive<ImmutableFileNumber>>,
+}
+
+/// Digests of the immutable files computed while unpacking them, as stored in an
+/// [ImmutableFileDigestCacheProvider][crate::common::digesters::cache::ImmutableFileDigestCacheProvider].
+pub type UnpackedImmutableDigests = Vec<(ImmutableFileName, HexEncodedDigest)>;
+
+/// Result of the unpacking of an archive, see [SnapshotUnpacker::unpack_snapshot_with_report].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UnpackReport {
+    /// Digests of the unpacked immutable files, see
+    /// [with_immutable_files_digests][SnapshotUnpacker::with_immutable_files_digests]
+    pub immutable_digests: UnpackedImmutableDigests,
+
+    /// Timings of the unpacking side of the pipeline, only the
+    /// [decompression][PipelineStageTimings::decompression] and
+    /// [unpacking][PipelineStageTimings::unpacking] stages are set
+    pub timings: PipelineStageTimings,
+}
+
 /// Unpack a downloaded archive in a given directory.
 #[derive(Default)]
-pub struct SnapshotUnpacker;
+pub struct SnapshotUnpacker {
+    options: UnpackOptions,
+    digest_immutable_files: bool,
+}
 
 impl SnapshotUnpacker {
-    /// Unpack the snapshot from the given stream into the given directory.
-    pub fn unpack_snapshot(
+    /// Constructs a new `SnapshotUnpacker` that honors the given options.
+    pub fn new(options: UnpackOptions) -> Self {
+        Self {
+            options,
+            digest_immutable_files: false,
+        }
+    }
+
+    /// Compute the digests of the immutable files while writing them.
+    ///
+    /// The last immutable files trio is not digested, as the digester considers it incomplete.
+    pub fn with_immutable_files_digests(mut self, digest_immutable_files: bool) -> Self {
+        self.digest_immutable_files = digest_immutable_files;
+        self
+    }
+
+    /// Unpack the snapshot from the given stream into the given directory, measuring the time
+    /// spent decompressing the stream and unpacking its entries.
+    ///
+    /// The digests of the unpacked immutable files are part of the report if
+    /// [enabled][Self::with_immutable_files_digests].
+    pub fn unpack_snapshot_with_report(
         &self,
         stream: Receiver<Vec<u8>>,
         compression_algorithm: CompressionAlgorithm,
         unpack_dir: &Path,
-    ) -> MithrilResult<()> {
-        let input = StreamReader::new(stream);
+    ) -> MithrilResult<UnpackReport> {
+        self.unpack_reader_with_report(StreamReader::new(stream), compression_algorithm, unpack_dir)
+    }
 
-        match compression_algorithm {
+    /// Unpack the snapshot read from the given reader, ie: an archive file already on the disk,
+    /// like [unpack_snapshot_with_report][Self::unpack_snapshot_with_report].
+    pub fn unpack_reader_with_report<R: Read>(
+        &self,
+        reader: R,
+        compression_algorithm: CompressionAlgorithm,
+        unpack_dir: &Path,
+    ) -> MithrilResult<UnpackReport> {
+        let start = Instant::now();
+        // The time spent reading the decoder includes the time waiting for the input, which is
+        // measured apart to only account the decompression.
+        let input_clock = StageClock::default();
+        let decoder_clock = StageClock::default();
+        let input = TimedReader::new(reader, input_clock.clone());
+
+        let immutable_digests = match compression_algorithm {
             CompressionAlgorithm::Gzip => {
-                let gzip_decoder = GzDecoder::new(input);
-                let mut snapshot_archive = Archive::new(gzip_decoder);
-                snapshot_archive.unpack(unpack_dir).with_context(|| {
-                    format!(
-                        "Could not unpack from streamed data snapshot to directory '{}'",
-                        unpack_dir.display()
-                    )
-                })?;
+                let gzip_decoder = TimedReader::new(GzDecoder::new(input), decoder_clock.clone());
+                self.unpack_archive(Archive::new(gzip_decoder), unpack_dir)
             }
             CompressionAlgorithm::Zstandard => {
                 let zstandard_decoder = zstd::Decoder::new(input)
                     .with_context(|| "Unpack failed: Create Zstandard decoder error")?;
-                let mut snapshot_archive = Archive::new(zstandard_decoder);
-                snapshot_archive.unpack(unpack_dir).with_context(|| {
-                    format!(
-                        "Could not unpack from streamed data snapshot to directory '{}'",
-                        unpack_dir.display()
-                    )
-                })?;
+                let zstandard_decoder = TimedReader::new(zstandard_decoder, decoder_clock.clone());
+                self.unpack_archive(Archive::new(zstandard_decoder), unpack_dir)
             }
+        }?;
+
+        Ok(UnpackReport {
+            immutable_digests,
+            timings: PipelineStageTimings {
+                decompression: decoder_clock
+                    .elapsed()
+                    .saturating_sub(input_clock.elapsed()),
+                unpacking: start.elapsed().saturating_sub(decoder_clock.elapsed()),
+                ..PipelineStageTimings::default()
+            },
+        })
+    }
+
+    fn unpack_archive<R: Read>(
+        &self,
+        mut archive: Archive<R>,
+        unpack_dir: &Path,
+    ) -> MithrilResult<UnpackedImmutableDigests> {
+        let unpack_error = || {
+            format!(
+                "Could not unpack from streamed data snapshot to directory '{}'",
+                unpack_dir.display()
+            )
         };
+        // Directories modes are applied once every entry is unpacked, else a read only directory
+        // mode would prevent the unpacking of its content.
+        let mut unpacked_dirs = vec![];
+        let mut immutable_digests: Vec<(ImmutableFile, HexEncodedDigest)> = vec![];
+
+        for entry in archive.entries().with_context(unpack_error)? {
+            let mut entry = entry.with_context(unpack_error)?;
+            let is_dir = entry.header().entry_type() == EntryType::Directory;
+            if !self.is_selected(&entry.path().with_context(unpack_error)?, is_dir) {
+                continue;
+            }
+            let Some(entry_path) =
+                Self::sanitized_entry_path(unpack_dir, &entry.path().with_context(unpack_error)?)
+            else {
+                continue;
+            };
+
+            if !is_dir && entry_path.symlink_metadata().is_ok() {
+                match self.options.overwrite_policy {
+                    OverwritePolicy::Overwrite => {}
+                    OverwritePolicy::Skip => continue,
+                    OverwritePolicy::Fail => {
+                        return Err(anyhow!(
+                            "Unpacked file already exists: '{}'",
+                            entry_path.display()
+                        ))
+                        .with_context(unpack_error);
+                    }
+                }
+            }
+
+            match self.immutable_file_to_digest(&entry) {
+                Some(immutable_file) => {
+                    let digest = Self::unpack_and_digest(&mut entry, unpack_dir, &entry_path)
+                        .with_context(unpack_error)?;
+                    immutable_digests.push((immutable_file, digest));
+                }
+                None => {
+                    if !entry.unpack_in(unpack_dir).with_context(unpack_error)? {
+                        continue;
+                    }
+                }
+            }
+
+            if is_dir {
+                unpacked_dirs.push(entry_path);
+            } else if entry.header().entry_type() == EntryType::Regular {
+                self.apply_options(&entry_path, self.options.file_mode)?;
+            }
+        }
+
+        for dir in unpacked_dirs {
+            self.apply_options(&dir, self.options.dir_mode)?;
+        }
+
+        let last_number = immutable_digests.iter().map(|(file, _)| file.number).max();
+        Ok(immutable_digests
+            .into_iter()
+            .filter(|(file, _)| Some(file.number) != last_number)
+            .map(|(file, digest)| (file.filename, digest))
+            .collect())
+    }
+
+    /// Path where the given entry is unpacked in `unpack_dir`, sanitized the way
+    /// [Entry::unpack_in] does: root and current directory components are dropped and `None` is
+    /// returned if the entry would escape `unpack_dir` through a parent directory component.
+    fn sanitized_entry_path(unpack_dir: &Path, entry_path: &Path) -> Option<PathBuf> {
+        let mut path = unpack_dir.to_path_buf();
+        for component in entry_path.components() {
+            match component {
+                Component::Normal(part) => path.push(part),
+                Component::Prefix(_) | Component::RootDir | Component::CurDir => {}
+                Component::ParentDir => return None,
+            }
+        }
+
+        Some(path)
+    }
+
+    /// Get the immutable file of the given entry if it should be digested while unpacked, only
+    /// regular files with a plain relative path are.
+    fn immutable_file_to_digest<R: Read>(&self, entry: &Entry<R>) -> Option<ImmutableFile> {
+        if !self.digest_immutable_files || entry.header().entry_type() != EntryType::Regular {
+            return None;
+        }
+        let path = entry.path().ok()?;
+        if !path.components().all(|c| matches!(c, Component::Normal(_)))
+            || !path.iter().any(|component| component == "immutable")
+        {
+            return None;
+        }
+
+        ImmutableFile::new(path.to_path_buf()).ok()
+    }
+
+    /// Write the content of the entry to the given path, hashing it on the way.
+    ///
+    /// Since the file is not written with [Entry::unpack_in], its parent directories are created
+    /// without following symlinks and an existing entry at its path is removed, not followed,
+    /// before the file is created: the entry can't be written outside of `unpack_dir`.
+    fn unpack_and_digest<R: Read>(
+        entry: &mut Entry<R>,
+        unpack_dir: &Path,
+        entry_path: &Path,
+    ) -> MithrilResult<HexEncodedDigest> {
+        Self::create_parent_dirs(unpack_dir, entry_path)?;
+        match entry_path.symlink_metadata() {
+            Ok(metadata) if metadata.is_dir() => {
+                return Err(anyhow!(
+                    "Could not create file '{}': a directory exists at this path",
+                    entry_path.display()
+                ));
+            }
+            Ok(_) => std::fs::remove_file(entry_path)
+                .with_context(|| format!("Could not remove file '{}'", entry_path.display()))?,
+            Err(_) => {}
+        }
+        let file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(entry_path)
+            .with_context(|| format!("Could not create file '{}'", entry_path.display()))?;
+        let mut writer = DigestingWriter {
+            inner: file,
+            hasher: Sha256::new(),
+        };
+        io::copy(entry, &mut writer)
+            .with_context(|| format!("Could not write file '{}'", entry_path.display()))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            if let Ok(mode) = entry.header().mode() {
+                writer
+                    .inner
+                    .set_permissions(std::fs::Permissions::from_mode(mode & 0o777))
+                    .with_context(|| format!("Could not set mode of '{}'", entry_path.display()))?;
+            }
+        }
+
+        Ok(hex::encode(writer.hasher.finalize()))
+    }
+
+    /// Create the missing parent directories of the given entry path, failing if one of them is a
+    /// symlink or is not a directory, or if the parent directory is not inside `unpack_dir`.
+    fn create_parent_dirs(unpack_dir: &Path, entry_path: &Path) -> MithrilResult<()> {
+        let relative_parent = entry_path
+            .parent()
+            .and_then(|parent| parent.strip_prefix(unpack_dir).ok())
+            .with_context(|| {
+                format!(
+                    "Could not unpack '{}' outside of directory '{}'",
+                    entry_path.display(),
+                    unpack_dir.display()
+                )
+            })?;
+        let mut dir = unpack_dir.to_path_buf();
+        for component in relative_parent.components() {
+            dir.push(component);
+            match dir.symlink_metadata() {
+                Ok(metadata) if metadata.is_dir() => {}
+                Ok(_) => {
+                    return Err(anyhow!(
+                        "Could not unpack '{}': '{}' is not a directory",
+                        entry_path.display(),
+                        dir.display()
+                    ));
+                }
+                Err(error) if error.kind() == io::ErrorKind::NotFound => std::fs::create_dir(&dir)
+                    .with_context(|| format!("Could not create directory '{}'", dir.display()))?,
+                Err(error) => {
+                    return Err(error)
+                        .with_context(|| format!("Could not read metadata of '{}'", dir.display()))
+                }
+            }
+        }
+
+        let canonical_unpack_dir = unpack_dir
+            .canonicalize()
+            .with_context(|| format!("Could not canonicalize '{}'", unpack_dir.display()))?;
+        let canonical_parent = dir
+            .canonicalize()
+            .with_context(|| format!("Could not canonicalize '{}'", dir.display()))?;
+        if !canonical_parent.starts_with(&canonical_unpack_dir) {
+            return Err(anyhow!(
+                "Could not unpack '{}' outside of directory '{}'",
+                entry_path.display(),
+                unpack_dir.display()
+            ));
+        }
 
         Ok(())
     }
+
+    /// Check if the given archive entry is selected by the
+    /// [immutable files range][UnpackOptions::immutable_files] option.
+    fn is_selected(&self, entry_path: &Path, is_dir: bool) -> bool {
+        let Some(immutable_files) = &self.options.immutable_files else {
+            return true;
+        };
+        if !entry_path.iter().any(|component| component == "immutable") {
+            return false;
+        }
+
+        is_dir
+            || ImmutableFile::new(entry_path.to_path_buf())
+                .is_ok_and(|file| immutable_files.contains(&file.number))
+    }
+
+    #[cfg(unix)]
+    fn apply_options(&self, path: &Path, mode: Option<u32>) -> MithrilResult<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        if let Some(mode) = mode {
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))
+                .with_context(|| format!("Could not set mode of '{}'", path.display()))?;
+        }
+        if self.options.uid.is_some() || self.options.gid.is_some() {
+            std::os::unix::fs::chown(path, self.options.uid, self.options.gid)
+                .with_context(|| format!("Could not set owner of '{}'", path.display()))?;
+        }
+
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    fn apply_options(&self, _path: &Path, _mode: Option<u32>) -> MithrilResult<()> {
+        Ok(())
+    }
+}
+
+/// Writer hashing the data written to its inner writer.
+struct DigestingWriter<W: Write> {
+    inner: W,
+    hasher: Sha256,
+}
+
+impl<W: Write> Write for DigestingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.hasher.update(&buf[..written]);
+
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use flate2::{write::GzEncoder, Compression};
+    use std::fs;
+    use std::path::PathBuf;
+
+    use crate::test_utils;
+
+    use super::*;
+
+    fn gzip_archive_stream(files: &[(&str, &str)]) -> Receiver<Vec<u8>> {
+        let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+        for (path, content) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, path, content.as_bytes())
+                .unwrap();
+        }
+        let archive = builder.into_inner().unwrap().finish().unwrap();
+
+        let (sender, receiver) = flume::unbounded();
+        sender.send(archive).unwrap();
+
+        receiver
+    }
+
+    #[test]
+    fn sanitize_the_entry_paths_before_checking_for_existing_files() {
+        let unpack_dir = Path::new("/unpack");
+
+        assert_eq!(
+            Some(PathBuf::from("/unpack/immutable/00001.chunk")),
+            SnapshotUnpacker::sanitized_entry_path(
+                unpack_dir,
+                Path::new("/immutable/./00001.chunk")
+            )
+        );
+        assert_eq!(
+            None,
+            SnapshotUnpacker::sanitized_entry_path(unpack_dir, Path::new("../outside"))
+        );
+    }
+
+    #[test]
+    fn unpack_with_default_options_overwrite_existing_files() {
+        let dir = test_utils::get_test_directory(
+            "snapshot_unpacker",
+            "unpack_with_default_options_overwrite_existing_files",
+        );
+        fs::create_dir(dir.join("db")).unwrap();
+        fs::write(dir.join("db").join("file"), "old").unwrap();
+
+        SnapshotUnpacker::default()
+            .unpack_snapshot_with_report(
+                gzip_archive_stream(&[("db/file", "new")]),
+                CompressionAlgorithm::Gzip,
+                &dir,
+            )
+            .unwrap();
+
+        assert_eq!(
+            "new",
+            fs::read_to_string(dir.join("db").join("file")).unwrap()
+        );
+    }
+
+    #[test]
+    fn unpack_with_skip_policy_keep_existing_files() {
+        let dir = test_utils::get_test_directory(
+            "snapshot_unpacker",
+            "unpack_with_skip_policy_keep_existing_files",
+        );
+        fs::write(dir.join("existing"), "old").unwrap();
+        let unpacker = SnapshotUnpacker::new(UnpackOptions {
+            overwrite_policy: OverwritePolicy::Skip,
+            ..UnpackOptions::default()
+        });
+
+        unpacker
+            .unpack_snapshot_with_report(
+                gzip_archive_stream(&[("existing", "new"), ("other", "new")]),
+                CompressionAlgorithm::Gzip,
+                &dir,
+            )
+            .unwrap();
+
+        assert_eq!("old", fs::read_to_string(dir.join("existing")).unwrap());
+        assert_eq!("new", fs::read_to_string(dir.join("other")).unwrap());
+    }
+
+    #[test]
+    fn unpack_with_fail_policy_fails_if_a_file_exists() {
+        let dir = test_utils::get_test_directory(
+            "snapshot_unpacker",
+            "unpack_with_fail_policy_fails_if_a_file_exists",
+        );
+        fs::write(dir.join("existing"), "old").unwrap();
+        let unpacker = SnapshotUnpacker::new(UnpackOptions {
+            overwrite_policy: OverwritePolicy::Fail,
+            ..UnpackOptions::default()
+        });
+
+        unpacker
+            .unpack_snapshot_with_report(
+                gzip_archive_stream(&[("existing", "new")]),
+                CompressionAlgorithm::Gzip,
+                &dir,
+            )
+            .expect_err("unpack should fail when a file already exists");
+    }
+
+    #[test]
+    fn unpack_only_the_immutable_files_in_the_given_range() {
+        let dir = test_utils::get_test_directory(
+            "snapshot_unpacker",
+            "unpack_only_the_immutable_files_in_the_given_range",
+        );
+        let unpacker = SnapshotUnpacker::new(UnpackOptions {
+            immutable_files: Some(1..=2),
+            ..UnpackOptions::default()
+        });
+
+        unpacker
+            .unpack_snapshot_with_report(
+                gzip_archive_stream(&[
+                    ("db/immutable/00000.chunk", "0"),
+                    ("db/immutable/00001.chunk", "1"),
+                    ("db/immutable/00001.primary", "1"),
+                    ("db/immutable/00002.secondary", "2"),
+                    ("db/immutable/00003.chunk", "3"),
+                    ("db/immutable/clean", ""),
+                    ("db/ledger/437", "ledger"),
+                    ("db/protocolMagicId", "2"),
+                ]),
+                CompressionAlgorithm::Gzip,
+                &dir,
+            )
+            .unwrap();
+
+        let mut unpacked_files: Vec<String> = walkdir::WalkDir::new(&dir)
+            .into_iter()
+            .map(|entry| entry.unwrap())
+            .filter(|entry| entry.file_type().is_file())
+            .map(|entry| {
+                entry
+                    .path()
+                    .strip_prefix(&dir)
+                    .unwrap()
+                    .to_string_lossy()
+                    .to_string()
+            })
+            .collect();
+        unpacked_files.sort();
+        assert_eq!(
+            vec![
+                "db/immutable/00001.chunk",
+                "db/immutable/00001.primary",
+                "db/immutable/00002.secondary",
+            ],
+            unpacked_files
+        );
+    }
+
+    #[test]
+    fn unpack_and_digest_the_immutable_files_except_the_last_trio() {
+        let dir = test_utils::get_test_directory(
+            "snapshot_unpacker",
+            "unpack_and_digest_the_immutable_files_except_the_last_trio",
+        );
+        let unpacker = SnapshotUnpacker::default().with_immutable_files_digests(true);
+
+        let digests = unpacker
+            .unpack_snapshot_with_report(
+                gzip_archive_stream(&[
+                    ("db/immutable/00000.chunk", "0"),
+                    ("db/immutable/00000.primary", "0p"),
+                    ("db/immutable/00001.chunk", "1"),
+                    ("db/ledger/437", "ledger"),
+                ]),
+                CompressionAlgorithm::Gzip,
+                &dir,
+            )
+            .unwrap()
+            .immutable_digests;
+
+        let expected_digest = |filename: &str| {
+            let file = ImmutableFile::new(dir.join("db").join("immutable").join(filename)).unwrap();
+            (
+                filename.to_string(),
+                hex::encode(file.compute_raw_hash::<Sha256>().unwrap()),
+            )
+        };
+        assert_eq!(
+            vec![
+                expected_digest("00000.chunk"),
+                expected_digest("00000.primary")
+            ],
+            digests
+        );
+        assert_eq!(
+            "1",
+            fs::read_to_string(dir.join("db/immutable/00001.chunk")).unwrap()
+        );
+        assert_eq!(
+            "ledger",
+            fs::read_to_string(dir.join("db/ledger/437")).unwrap()
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unpack_and_digest_does_not_write_through_symlinks_of_the_archive() {
+        let dir = test_utils::get_test_directory(
+            "snapshot_unpacker",
+            "unpack_and_digest_does_not_write_through_symlinks_of_the_archive",
+        );
+        let unpack_dir = dir.join("unpack");
+        let outside_dir = dir.join("outside");
+        fs::create_dir(&unpack_dir).unwrap();
+        fs::create_dir(&outside_dir).unwrap();
+        fs::write(outside_dir.join("target"), "untouched").unwrap();
+        let malicious_archive = |symlink_path: &str, symlink_target: &Path| {
+            let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+            let mut header = tar::Header::new_gnu();
+            header.set_entry_type(EntryType::Symlink);
+            header.set_size(0);
+            builder
+                .append_link(&mut header, symlink_path, symlink_target)
+                .unwrap();
+            let mut header = tar::Header::new_gnu();
+            header.set_size(9);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(
+                    &mut header,
+                    "db/immutable/00001.chunk",
+                    "malicious".as_bytes(),
+                )
+                .unwrap();
+            let (sender, receiver) = flume::unbounded();
+            sender
+                .send(builder.into_inner().unwrap().finish().unwrap())
+                .unwrap();
+
+            receiver
+        };
+        let unpacker = SnapshotUnpacker::default().with_immutable_files_digests(true);
+
+        unpacker
+            .unpack_snapshot_with_report(
+                malicious_archive("db/immutable", &outside_dir),
+                CompressionAlgorithm::Gzip,
+                &unpack_dir,
+            )
+            .expect_err("unpack should fail if a parent directory is a symlink");
+        assert!(!outside_dir.join("00001.chunk").exists());
+
+        fs::remove_dir_all(&unpack_dir).unwrap();
+        fs::create_dir(&unpack_dir).unwrap();
+        unpacker
+            .unpack_snapshot_with_report(
+                malicious_archive("db/immutable/00001.chunk", &outside_dir.join("target")),
+                CompressionAlgorithm::Gzip,
+                &unpack_dir,
+            )
+            .unwrap();
+        assert_eq!(
+            "untouched",
+            fs::read_to_string(outside_dir.join("target")).unwrap()
+        );
+        assert_eq!(
+            "malicious",
+            fs::read_to_string(unpack_dir.join("db/immutable/00001.chunk")).unwrap()
+        );
+        assert!(!unpack_dir
+            .join("db/immutable/00001.chunk")
+            .symlink_metadata()
+            .unwrap()
+            .is_symlink());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unpack_apply_files_and_directories_modes() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = test_utils::get_test_directory(
+            "snapshot_unpacker",
+            "unpack_apply_files_and_directories_modes",
+        );
+        let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(EntryType::Directory);
+        header.set_mode(0o777);
+        header.set_size(0);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, "db", std::io::empty())
+            .unwrap();
+        let mut header = tar::Header::new_gnu();
+        header.set_mode(0o666);
+        header.set_size(4);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, "db/file", "data".as_bytes())
+            .unwrap();
+        let (sender, receiver) = flume::unbounded();
+        sender
+            .send(builder.into_inner().unwrap().finish().unwrap())
+            .unwrap();
+        drop(sender);
+        let unpacker = SnapshotUnpacker::new(UnpackOptions {
+            dir_mode: Some(0o750),
+            file_mode: Some(0o640),
+            ..UnpackOptions::default()
+        });
+
+        unpacker
+            .unpack_snapshot_with_report(receiver, CompressionAlgorithm::Gzip, &dir)
+            .unwrap();
+
+        let mode = |path: PathBuf| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+        assert_eq!(0o750, mode(dir.join("db")));
+        assert_eq!(0o640, mode(dir.join("db").join("file")));
+    }
 }
@@ -710,6 +710,13 @@ impl Ord for StmSig {
     }
 }
 
+impl<D: Clone + Digest + FixedOutput> StmAggrVerificationKey<D> {
+    /// Total stake of the registered parties.
+    pub fn total_stake(&self) -> Stake {
+        self.total_stake
+    }
+}
+
 impl<D: Clone + Digest + FixedOutput> From<&ClosedKeyReg<D>> for StmAggrVerificationKey<D> {
     fn from(reg: &ClosedKeyReg<D>) -> Self {
         Self {